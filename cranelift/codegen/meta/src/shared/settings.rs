@@ -51,17 +51,20 @@ pub(crate) fn define() -> SettingGroup {
             Supported levels:
 
             - `none`: Minimise compile time by disabling most optimizations.
-            - `speed`: Generate the fastest possible code
-            - `speed_and_size`: like "speed", but also perform transformations aimed at reducing code size.
+            - `speed`: Generate the fastest possible code, including fully unrolling small loops
+              with a constant trip count.
+            - `speed_and_size`: like "speed", but also perform transformations aimed at reducing code size,
+              and don't unroll loops.
         "#,
         vec!["none", "speed", "speed_and_size"],
     );
 
     settings.add_bool(
         "enable_alias_analysis",
-        "Do redundant-load optimizations with alias analysis.",
+        "Do redundant-load optimizations and dead-store elimination with alias analysis.",
         r#"
-            This enables the use of a simple alias analysis to optimize away redundant loads.
+            This enables the use of a simple alias analysis to optimize away redundant loads,
+            and stores whose values are overwritten before they can be observed.
             Only effective when `opt_level` is `speed` or `speed_and_size`.
         "#,
         true,
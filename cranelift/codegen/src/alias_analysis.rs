@@ -50,28 +50,49 @@
 //! location, or "store-to-load forwarding" if the value came from an
 //! earlier store to the same location.
 //!
-//! In theory we could also do *dead-store elimination*, where if a
-//! store overwrites a key in the table, *and* if no other load/store
-//! to the abstract state category occurred, *and* no other trapping
-//! instruction occurred (at which point we need an up-to-date memory
-//! state because post-trap-termination memory state can be observed),
-//! *and* we can prove the original store could not have trapped, then
-//! we can eliminate the original store. Because this is so complex,
-//! and the conditions for doing it correctly when post-trap state
-//! must be correct likely reduce the potential benefit, we don't yet
-//! do this.
+//! Two refinements let known memory values survive beyond a single
+//! "last store":
+//!
+//! - A store to the same address expression as a known memory value
+//!   but to a disjoint range of bytes (a different offset from the same
+//!   base `Value`) can't change that memory value. When processing such
+//!   a store we copy the known values it can't overwrite forward to the
+//!   new "last store".
+//!
+//! - At a merge point, where predecessors disagree on the last store
+//!   for a category of abstract state, the meet gives a fresh "last
+//!   store" (the first instruction of the block). We remember which
+//!   last stores flow into each such merge point, so a load which
+//!   misses in the table can still be satisfied if the same SSA value
+//!   is known for the location under every incoming last store. This
+//!   provides store-to-load forwarding across control-flow joins.
+//!
+//! Dead-store elimination, which relies on the same disjoint abstract
+//! state categories but scans backward, is a separate pass; see
+//! `dead_store_elimination.rs`.
 
 use crate::{
     cursor::{Cursor, FuncCursor},
     dominator_tree::DominatorTree,
     inst_predicates::{
-        has_memory_fence_semantics, inst_addr_offset_type, inst_store_data, visit_block_succs,
+        access_size, has_memory_fence_semantics, inst_addr_offset_type, inst_store_data,
+        visit_block_succs,
     },
-    ir::{immediates::Offset32, AliasRegion, Block, Function, Inst, Opcode, Type, Value},
+    ir::{immediates::Offset32, AliasRegion, Block, Function, Inst, Opcode, Type, Value, ValueDef},
     trace,
 };
 use cranelift_entity::{packed_option::PackedOption, EntityRef};
 use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+/// The maximum number of known memory values which are carried past a single
+/// store to a disjoint location, bounding the cost of long sequences of stores
+/// to the same base address.
+const MAX_CARRIED_VALUES: usize = 64;
+
+/// The maximum number of nested merge points looked through when searching
+/// for a known memory value at a merge point.
+const MAX_MERGE_DEPTH: usize = 4;
 
 /// For a given program point, the vector of last-store instruction
 /// indices for each disjoint category of abstract state.
@@ -108,14 +129,18 @@ impl LastStores {
         }
     }
 
+    fn get(&self, region: Option<AliasRegion>) -> PackedOption<Inst> {
+        match region {
+            None => self.other,
+            Some(AliasRegion::Heap) => self.heap,
+            Some(AliasRegion::Table) => self.table,
+            Some(AliasRegion::Vmctx) => self.vmctx,
+        }
+    }
+
     fn get_last_store(&self, func: &Function, inst: Inst) -> PackedOption<Inst> {
         if let Some(memflags) = func.dfg.insts[inst].memflags() {
-            match memflags.alias_region() {
-                None => self.other,
-                Some(AliasRegion::Heap) => self.heap,
-                Some(AliasRegion::Table) => self.table,
-                Some(AliasRegion::Vmctx) => self.vmctx,
-            }
+            self.get(memflags.alias_region())
         } else if func.dfg.insts[inst].opcode().can_load()
             || func.dfg.insts[inst].opcode().can_store()
         {
@@ -156,6 +181,7 @@ impl LastStores {
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct MemoryLoc {
     last_store: PackedOption<Inst>,
+    region: Option<AliasRegion>,
     address: Value,
     offset: Offset32,
     ty: Type,
@@ -187,6 +213,16 @@ pub struct AliasAnalysis<'a> {
     ///
     /// We keep the defining inst around for quick dominance checks.
     mem_values: FxHashMap<MemoryLoc, (Inst, Value)>,
+
+    /// The keys of `mem_values`, indexed by last store and address, so
+    /// that known values can be carried past stores to disjoint
+    /// locations.
+    locs_by_store: FxHashMap<(PackedOption<Inst>, Value), SmallVec<[MemoryLoc; 4]>>,
+
+    /// For each merge point created when predecessors disagree on the
+    /// last store to a category of abstract state, the last stores of
+    /// the predecessors.
+    merge_inputs: FxHashMap<(Inst, Option<AliasRegion>), SmallVec<[PackedOption<Inst>; 4]>>,
 }
 
 impl<'a> AliasAnalysis<'a> {
@@ -197,9 +233,12 @@ impl<'a> AliasAnalysis<'a> {
             domtree,
             block_input: FxHashMap::default(),
             mem_values: FxHashMap::default(),
+            locs_by_store: FxHashMap::default(),
+            merge_inputs: FxHashMap::default(),
         };
 
         analysis.compute_block_input_states(func);
+        analysis.compute_merge_inputs(func);
         analysis
     }
 
@@ -249,6 +288,53 @@ impl<'a> AliasAnalysis<'a> {
         }
     }
 
+    /// Record the last stores flowing into each merge point, now that
+    /// the block input states have reached a fixpoint.
+    fn compute_merge_inputs(&mut self, func: &Function) {
+        const REGIONS: [Option<AliasRegion>; 4] = [
+            None,
+            Some(AliasRegion::Heap),
+            Some(AliasRegion::Table),
+            Some(AliasRegion::Vmctx),
+        ];
+
+        for block in func.layout.blocks() {
+            let Some(mut state) = self.block_input.get(&block).copied() else {
+                continue;
+            };
+            for inst in func.layout.block_insts(block) {
+                state.update(func, inst);
+            }
+
+            visit_block_succs(func, block, |_inst, succ, _from_table| {
+                let succ_first_inst = func.layout.block_insts(succ).into_iter().next().unwrap();
+                let succ_state = self.block_input[&succ];
+
+                // The first instruction of the successor may itself be
+                // a store, in which case the merge point's last store is
+                // indistinguishable from the store and can't be used.
+                let mut first_inst_stores = LastStores::default();
+                first_inst_stores.update(func, succ_first_inst);
+
+                for region in REGIONS {
+                    if succ_state.get(region) != succ_first_inst.into()
+                        || first_inst_stores.get(region) == succ_first_inst.into()
+                    {
+                        continue;
+                    }
+                    let inputs = self
+                        .merge_inputs
+                        .entry((succ_first_inst, region))
+                        .or_default();
+                    let input = state.get(region);
+                    if !inputs.contains(&input) {
+                        inputs.push(input);
+                    }
+                }
+            });
+        }
+    }
+
     /// Get the starting state for a block.
     pub fn block_starting_state(&self, block: Block) -> LastStores {
         self.block_input
@@ -279,12 +365,16 @@ impl<'a> AliasAnalysis<'a> {
         {
             let address = func.dfg.resolve_aliases(address);
             let opcode = func.dfg.insts[inst].opcode();
+            let region = func.dfg.insts[inst]
+                .memflags()
+                .and_then(|flags| flags.alias_region());
 
             if opcode.can_store() {
                 let store_data = inst_store_data(func, inst).unwrap();
                 let store_data = func.dfg.resolve_aliases(store_data);
                 let mem_loc = MemoryLoc {
                     last_store: inst.into(),
+                    region,
                     address,
                     offset,
                     ty,
@@ -297,6 +387,12 @@ impl<'a> AliasAnalysis<'a> {
                     mem_loc
                 );
                 self.mem_values.insert(mem_loc, (inst, store_data));
+                self.record_loc(mem_loc);
+
+                // Values known to be in memory at other offsets from
+                // the same address are unaffected by this store.
+                let prev_store = state.get_last_store(func, inst);
+                self.carry_over_disjoint(prev_store, mem_loc);
 
                 None
             } else if opcode.can_load() {
@@ -304,6 +400,7 @@ impl<'a> AliasAnalysis<'a> {
                 let load_result = func.dfg.inst_results(inst)[0];
                 let mem_loc = MemoryLoc {
                     last_store,
+                    region,
                     address,
                     offset,
                     ty,
@@ -326,26 +423,23 @@ impl<'a> AliasAnalysis<'a> {
                 // load (stores will always dominate though if
                 // their `last_store` survives through
                 // meet-points to this use-site).
-                let aliased =
-                    if let Some((def_inst, value)) = self.mem_values.get(&mem_loc).cloned() {
+                let aliased = match self.known_value(func, mem_loc, inst, 0) {
+                    Some((def_inst, value)) => {
                         trace!(
-                            " -> sees known value v{} from inst{}",
+                            " -> sees known value v{} from inst{}; value equiv from v{} to v{} inserted",
                             value.index(),
-                            def_inst.index()
+                            def_inst.index(),
+                            load_result.index(),
+                            value.index()
                         );
-                        if self.domtree.dominates(def_inst, inst, &func.layout) {
-                            trace!(
-                                " -> dominates; value equiv from v{} to v{} inserted",
-                                load_result.index(),
-                                value.index()
-                            );
-                            Some(value)
-                        } else {
-                            None
+                        if !self.mem_values.contains_key(&mem_loc) {
+                            self.mem_values.insert(mem_loc, (def_inst, value));
+                            self.record_loc(mem_loc);
                         }
-                    } else {
-                        None
-                    };
+                        Some(value)
+                    }
+                    None => None,
+                };
 
                 // Otherwise, we can keep *this* load around
                 // as a new equivalent value.
@@ -355,7 +449,13 @@ impl<'a> AliasAnalysis<'a> {
                         load_result.index(),
                         mem_loc
                     );
-                    self.mem_values.insert(mem_loc, (inst, load_result));
+                    if self
+                        .mem_values
+                        .insert(mem_loc, (inst, load_result))
+                        .is_none()
+                    {
+                        self.record_loc(mem_loc);
+                    }
                 }
 
                 aliased
@@ -371,6 +471,119 @@ impl<'a> AliasAnalysis<'a> {
         replacing_value
     }
 
+    /// Find the SSA value known to be in memory at `loc`, with a
+    /// definition dominating `inst`.
+    ///
+    /// If `loc`'s last store is a merge point, this looks for the same
+    /// value under the last stores of all of the merge point's
+    /// predecessors. Predecessors whose last store is the merge point
+    /// itself, i.e. loop back-edges without any intervening store, don't
+    /// change the value in memory and are skipped.
+    fn known_value(
+        &self,
+        func: &Function,
+        loc: MemoryLoc,
+        inst: Inst,
+        depth: usize,
+    ) -> Option<(Inst, Value)> {
+        if let Some(&(def_inst, value)) = self.mem_values.get(&loc) {
+            if self.domtree.dominates(def_inst, inst, &func.layout)
+                || self.value_available(func, value, inst)
+            {
+                return Some((def_inst, value));
+            }
+        }
+
+        let merge = loc.last_store.expand()?;
+        let inputs = self.merge_inputs.get(&(merge, loc.region))?;
+        if depth >= MAX_MERGE_DEPTH {
+            return None;
+        }
+        let mut known = None;
+        for &input in inputs {
+            if input == loc.last_store {
+                continue;
+            }
+            let input_loc = MemoryLoc {
+                last_store: input,
+                ..loc
+            };
+            let (def_inst, value) = self.known_value(func, input_loc, inst, depth + 1)?;
+            match known {
+                Some((_, known_value)) if known_value != value => return None,
+                _ => known = Some((def_inst, value)),
+            }
+        }
+        trace!(
+            " -> merge point inst{} has known value {:?} for loc {:?}",
+            merge.index(),
+            known,
+            loc
+        );
+        known
+    }
+
+    /// Is `value` available for use at `inst`?
+    ///
+    /// A value whose defining instruction isn't in the layout (because the
+    /// egraph pass removed it as a pure node) is only considered available
+    /// if it doesn't depend on any other values, e.g. a constant.
+    fn value_available(&self, func: &Function, value: Value, inst: Inst) -> bool {
+        match func.dfg.value_def(value) {
+            ValueDef::Param(block, _) => self.domtree.dominates(block, inst, &func.layout),
+            ValueDef::Result(def_inst, _) => {
+                if func.layout.inst_block(def_inst).is_some() {
+                    self.domtree.dominates(def_inst, inst, &func.layout)
+                } else {
+                    func.dfg.inst_values(def_inst).next().is_none()
+                }
+            }
+            ValueDef::Union(..) => false,
+        }
+    }
+
+    fn record_loc(&mut self, loc: MemoryLoc) {
+        self.locs_by_store
+            .entry((loc.last_store, loc.address))
+            .or_default()
+            .push(loc);
+    }
+
+    /// Copy the values known to be in memory under `prev_store` at the
+    /// same address as `store_loc`, but which don't overlap it, to the
+    /// store at `store_loc`.
+    fn carry_over_disjoint(&mut self, prev_store: PackedOption<Inst>, store_loc: MemoryLoc) {
+        let Some(store_range) = store_loc.byte_range() else {
+            return;
+        };
+        let Some(locs) = self.locs_by_store.get(&(prev_store, store_loc.address)) else {
+            return;
+        };
+        let carried = locs
+            .iter()
+            .filter(|loc| loc.region == store_loc.region)
+            .filter(|loc| match loc.byte_range() {
+                Some(range) => range.end <= store_range.start || store_range.end <= range.start,
+                None => false,
+            })
+            .take(MAX_CARRIED_VALUES)
+            .map(|loc| {
+                let carried_loc = MemoryLoc {
+                    last_store: store_loc.last_store,
+                    ..*loc
+                };
+                (carried_loc, self.mem_values[loc])
+            })
+            .collect::<SmallVec<[_; 8]>>();
+        for (loc, def) in carried {
+            if !self.mem_values.contains_key(&loc) {
+                trace!(" -> carrying known value {:?} over to loc {:?}", def, loc);
+                self.mem_values.insert(loc, def);
+                self.record_loc(loc);
+            }
+        }
+    }
+
     /// Make a pass and update known-redundant loads to aliased
     /// values. We interleave the updates with the memory-location
     /// tracking because resolving some aliases may expose others
@@ -393,6 +606,16 @@ impl<'a> AliasAnalysis<'a> {
     }
 }
 
+impl MemoryLoc {
+    /// The range of bytes accessed, relative to `address`.
+    fn byte_range(&self) -> Option<core::ops::Range<i64>> {
+        let opcode = self.extending_opcode.unwrap_or(Opcode::Load);
+        let start = i64::from(self.offset);
+        let size = access_size(opcode, self.ty)?;
+        Some(start..start + i64::from(size))
+    }
+}
+
 fn get_ext_opcode(op: Opcode) -> Option<Opcode> {
     debug_assert!(op.can_load() || op.can_store());
     match op {
@@ -0,0 +1,622 @@
+//! Elimination of redundant bounds checks.
+//!
+//! A bounds check is a conditional trap, `trapnz` or `trapz`, on an unsigned
+//! comparison which traps if `base + offset > bound`, where `base` and
+//! `bound` are SSA values (or constants) and `offset` is a constant. The
+//! `base + offset` sum is either an explicit `+ 1` from comparing with `uge`
+//! instead of `ugt`, or an addition by `uadd_overflow_trap`, which guarantees
+//! that the sum doesn't wrap. This is the shape of the explicit bounds checks
+//! emitted for Wasm memory accesses when guard pages can't make them
+//! implicit, e.g. for 64-bit memories.
+//!
+//! A bounds check is removed if it can never trap, which is determined in
+//! two ways:
+//!
+//! - From *range facts*: the unsigned range of values which each SSA value
+//!   can take is computed from constants, extensions, masks, shifts, and so
+//!   on. For block parameters, the ranges of the values passed in on each
+//!   incoming edge are joined, refined by the branch condition on the edge.
+//!   This bounds loop induction variables by the loop's exit condition.
+//!
+//! - From *dominating checks*: once a bounds check has been passed, its
+//!   condition is known to be false at all points it dominates, as is the
+//!   condition of a branch in the successor taken when it is true or false.
+//!   A later check of the same `base` against the same `bound` with a smaller
+//!   `offset` is then redundant.
+//!
+//! Additionally, a `uadd_overflow_trap` is replaced by a plain `iadd` when
+//! either of these shows that its addition can't overflow, and a `select` of
+//! a null pointer on an out-of-bounds condition (used as an alternative to
+//! trapping) is replaced by its in-bounds operand when the condition is
+//! known to be false. `select_spectre_guard` is only replaced based on range
+//! facts, since a dominating check doesn't stop speculative execution past
+//! it.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::dominator_tree::{DominatorTree, DominatorTreePreorder};
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::condcodes::{CondCode, IntCC};
+use crate::ir::{Block, Function, Inst, InstBuilder, InstructionData, Opcode, Value, ValueDef};
+use crate::{timing, trace};
+use alloc::vec::Vec;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// How deeply nested value definitions are inspected when computing ranges.
+const MAX_RANGE_DEPTH: usize = 16;
+
+/// The right-hand side of a bounds check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Value(Value),
+    Const(u64),
+}
+
+/// The fact that `base + offset <= bound`, without wrapping.
+#[derive(Clone, Copy, Debug)]
+struct InBounds {
+    base: Value,
+    offset: u64,
+    bound: Bound,
+}
+
+impl InBounds {
+    /// Does this fact imply `other`?
+    fn implies(&self, other: &InBounds) -> bool {
+        if self.base != other.base {
+            return false;
+        }
+        match (self.bound, other.bound) {
+            (Bound::Value(a), Bound::Value(b)) => a == b && self.offset >= other.offset,
+            (Bound::Const(a), Bound::Const(b)) => {
+                u128::from(other.offset) + u128::from(a) <= u128::from(self.offset) + u128::from(b)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// An inclusive unsigned range of values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Range {
+    min: u64,
+    max: u64,
+}
+
+impl Range {
+    fn full(bits: u32) -> Range {
+        Range {
+            min: 0,
+            max: max_for_bits(bits),
+        }
+    }
+
+    fn constant(c: u64) -> Range {
+        Range { min: c, max: c }
+    }
+
+    fn join(self, other: Range) -> Range {
+        Range {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+fn max_for_bits(bits: u32) -> u64 {
+    match bits {
+        64 => u64::MAX,
+        bits => (1 << bits) - 1,
+    }
+}
+
+/// Remove bounds checks which can never trap.
+pub fn do_bounds_check_elimination(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+) {
+    let _tt = timing::bounds_check_elimination();
+
+    let mut domtree_preorder = DominatorTreePreorder::new();
+    domtree_preorder.compute(domtree);
+    let mut pass = BoundsCheckElimination {
+        cfg,
+        ranges: FxHashMap::default(),
+        in_progress: FxHashSet::default(),
+        facts: Vec::new(),
+        non_overflowing_adds: Vec::new(),
+        aliased: false,
+    };
+
+    let Some(entry) = func.layout.entry_block() else {
+        return;
+    };
+    // Visit blocks in a preorder of the dominator tree, keeping the facts
+    // known in each block on a stack.
+    let mut stack = alloc::vec![(entry, 0)];
+    while let Some((block, num_facts)) = stack.pop() {
+        pass.facts.truncate(num_facts);
+        pass.add_edge_facts(func, block);
+        pass.visit_block(func, block);
+        let num_facts = pass.facts.len();
+        for child in domtree_preorder.children(block) {
+            stack.push((child, num_facts));
+        }
+    }
+
+    // Only now replace the additions which can't overflow, so that the
+    // checks using them could still be recognized above.
+    for (inst, x, y) in pass.non_overflowing_adds {
+        func.dfg.replace(inst).iadd(x, y);
+    }
+
+    // Lowering expects no aliases, so resolve those of removed `select`s.
+    if pass.aliased {
+        func.dfg.resolve_all_aliases();
+    }
+}
+
+struct BoundsCheckElimination<'a> {
+    cfg: &'a ControlFlowGraph,
+    /// Memoized ranges of values.
+    ranges: FxHashMap<Value, Range>,
+    /// The values whose ranges are currently being computed, to break
+    /// cycles through block parameters.
+    in_progress: FxHashSet<Value>,
+    /// The facts known at the current program point.
+    facts: Vec<InBounds>,
+    /// The `uadd_overflow_trap` instructions which are known not to
+    /// overflow.
+    non_overflowing_adds: Vec<(Inst, Value, Value)>,
+    /// Whether any value was turned into an alias.
+    aliased: bool,
+}
+
+impl BoundsCheckElimination<'_> {
+    /// Add the facts known from the condition of the branch to `block` from
+    /// its only predecessor, if any.
+    fn add_edge_facts(&mut self, func: &Function, block: Block) {
+        let mut preds = self.cfg.pred_iter(block);
+        let (Some(pred), None) = (preds.next(), preds.next()) else {
+            return;
+        };
+        let InstructionData::Brif {
+            arg,
+            blocks: [then_call, else_call],
+            ..
+        } = func.dfg.insts[pred.inst]
+        else {
+            return;
+        };
+        let then_block = then_call.block(&func.dfg.value_lists);
+        let else_block = else_call.block(&func.dfg.value_lists);
+        if then_block == else_block {
+            return;
+        }
+        // The branch is taken to `then_block` if the condition is true,
+        // which is when a `trapnz` on it would trap.
+        if let Some(fact) = in_bounds_if(func, arg, block == then_block) {
+            trace!("bounds check elimination: {} starts with {:?}", block, fact);
+            self.facts.push(fact);
+        }
+    }
+
+    fn visit_block(&mut self, func: &mut Function, block: Block) {
+        let mut pos = FuncCursor::new(func).at_top(block);
+        while let Some(inst) = pos.next_inst() {
+            match pos.func.dfg.insts[inst] {
+                InstructionData::CondTrap { opcode, arg, .. } => {
+                    let traps_if_true = opcode == Opcode::Trapnz;
+                    let Some(check) = in_bounds_if(pos.func, arg, !traps_if_true) else {
+                        continue;
+                    };
+                    if self.is_known(pos.func, &check, true) {
+                        trace!("bounds check elimination: removing {}", inst);
+                        pos.remove_inst_and_step_back();
+                    } else {
+                        self.facts.push(check);
+                    }
+                }
+
+                InstructionData::IntAddTrap {
+                    opcode: Opcode::UaddOverflowTrap,
+                    args: [x, y],
+                    ..
+                } => {
+                    let no_overflow = |this: &mut Self, base: Value, offset: Value| {
+                        let Some(offset) = iconst_value(pos.func, offset) else {
+                            return false;
+                        };
+                        let bits = pos.func.dfg.value_type(base).bits();
+                        let check = InBounds {
+                            base,
+                            offset,
+                            bound: Bound::Const(max_for_bits(bits)),
+                        };
+                        this.is_known(pos.func, &check, true)
+                            || this
+                                .facts
+                                .iter()
+                                .any(|fact| fact.base == base && fact.offset >= offset)
+                    };
+                    if no_overflow(self, x, y) || no_overflow(self, y, x) {
+                        trace!("bounds check elimination: {} can't overflow", inst);
+                        self.non_overflowing_adds.push((inst, x, y));
+                    }
+                }
+
+                InstructionData::Ternary {
+                    opcode: opcode @ (Opcode::Select | Opcode::SelectSpectreGuard),
+                    args: [cond, _, if_false],
+                } => {
+                    let use_facts = opcode == Opcode::Select;
+                    let Some(check) = in_bounds_if(pos.func, cond, false) else {
+                        continue;
+                    };
+                    if self.is_known(pos.func, &check, use_facts) {
+                        trace!(
+                            "bounds check elimination: {} always selects {}",
+                            inst,
+                            if_false
+                        );
+                        let result = pos.func.dfg.first_result(inst);
+                        pos.func.dfg.clear_results(inst);
+                        pos.func.dfg.change_to_alias(result, if_false);
+                        self.aliased = true;
+                        pos.remove_inst_and_step_back();
+                    }
+                }
+
+                _ => {}
+            }
+        }
+    }
+
+    /// Is `check` known to hold at the current program point?
+    fn is_known(&mut self, func: &Function, check: &InBounds, use_facts: bool) -> bool {
+        if use_facts && self.facts.iter().any(|fact| fact.implies(check)) {
+            return true;
+        }
+        let base = self.range(func, check.base, 0);
+        let bound = match check.bound {
+            Bound::Const(c) => c,
+            Bound::Value(v) => self.range(func, v, 0).min,
+        };
+        u128::from(base.max) + u128::from(check.offset) <= u128::from(bound)
+    }
+
+    /// Compute the range of values that `value` may take.
+    fn range(&mut self, func: &Function, value: Value, depth: usize) -> Range {
+        let value = func.dfg.resolve_aliases(value);
+        let ty = func.dfg.value_type(value);
+        if !ty.is_int() || ty.bits() > 64 {
+            return Range::full(64);
+        }
+        let full = Range::full(ty.bits());
+        if let Some(range) = self.ranges.get(&value) {
+            return *range;
+        }
+        if depth > MAX_RANGE_DEPTH || !self.in_progress.insert(value) {
+            return full;
+        }
+
+        let range = match func.dfg.value_def(value) {
+            ValueDef::Result(inst, 0) => self.inst_range(func, inst, depth + 1),
+            ValueDef::Param(block, index) => self.param_range(func, block, index, depth + 1),
+            _ => None,
+        };
+        let range = match range {
+            Some(range) if range.max <= full.max && range.min <= range.max => range,
+            _ => full,
+        };
+
+        self.in_progress.remove(&value);
+        self.ranges.insert(value, range);
+        range
+    }
+
+    fn inst_range(&mut self, func: &Function, inst: Inst, depth: usize) -> Option<Range> {
+        let max = max_for_bits(func.dfg.ctrl_typevar(inst).bits());
+        match func.dfg.insts[inst] {
+            InstructionData::UnaryImm {
+                opcode: Opcode::Iconst,
+                imm,
+            } => {
+                let bits = func.dfg.ctrl_typevar(inst).bits();
+                Some(Range::constant(
+                    imm.zero_extend_from_width(bits).bits() as u64
+                ))
+            }
+            InstructionData::Unary {
+                opcode: Opcode::Uextend,
+                arg,
+            } => Some(self.range(func, arg, depth)),
+            InstructionData::Binary {
+                opcode,
+                args: [x, y],
+            } => {
+                let x = self.range(func, x, depth);
+                let y = self.range(func, y, depth);
+                match opcode {
+                    Opcode::Band => Some(Range {
+                        min: 0,
+                        max: x.max.min(y.max),
+                    }),
+                    Opcode::Iadd => {
+                        let sum = x.max.checked_add(y.max).filter(|&sum| sum <= max)?;
+                        Some(Range {
+                            min: x.min + y.min,
+                            max: sum,
+                        })
+                    }
+                    Opcode::Imul => {
+                        let product = x.max.checked_mul(y.max).filter(|&p| p <= max)?;
+                        Some(Range {
+                            min: x.min * y.min,
+                            max: product,
+                        })
+                    }
+                    Opcode::Ishl if y.min == y.max && u64::from(y.min as u32) == y.min => {
+                        let shift = y.min as u32 % max.count_ones();
+                        let shifted = x.max.checked_shl(shift).filter(|&s| s >> shift == x.max)?;
+                        (shifted <= max).then_some(Range {
+                            min: x.min << shift,
+                            max: shifted,
+                        })
+                    }
+                    Opcode::Ushr if y.min == y.max => {
+                        let shift = (y.min % u64::from(max.count_ones())) as u32;
+                        Some(Range {
+                            min: x.min >> shift,
+                            max: x.max >> shift,
+                        })
+                    }
+                    Opcode::Udiv if y.min > 0 => Some(Range {
+                        min: x.min / y.max,
+                        max: x.max / y.min,
+                    }),
+                    Opcode::Urem if y.max > 0 => Some(Range {
+                        min: 0,
+                        max: x.max.min(y.max - 1),
+                    }),
+                    Opcode::Umin => Some(Range {
+                        min: x.min.min(y.min),
+                        max: x.max.min(y.max),
+                    }),
+                    Opcode::Umax => Some(Range {
+                        min: x.min.max(y.min),
+                        max: x.max.max(y.max),
+                    }),
+                    _ => None,
+                }
+            }
+            InstructionData::IntAddTrap {
+                opcode: Opcode::UaddOverflowTrap,
+                args: [x, y],
+                ..
+            } => {
+                // The result is only produced if the addition doesn't wrap.
+                let x = self.range(func, x, depth);
+                let y = self.range(func, y, depth);
+                let min = x.min.checked_add(y.min).filter(|&min| min <= max)?;
+                Some(Range {
+                    min,
+                    max: x.max.saturating_add(y.max).min(max),
+                })
+            }
+            InstructionData::Ternary {
+                opcode: Opcode::Select,
+                args: [_, x, y],
+            } => Some(self.range(func, x, depth).join(self.range(func, y, depth))),
+            _ => None,
+        }
+    }
+
+    /// Compute the range of a block parameter from the values passed to it
+    /// on each incoming edge.
+    fn param_range(
+        &mut self,
+        func: &Function,
+        block: Block,
+        index: usize,
+        depth: usize,
+    ) -> Option<Range> {
+        let param = func.dfg.block_params(block)[index];
+        let mut range: Option<Range> = None;
+        for pred in self.cfg.pred_iter(block) {
+            let data = &func.dfg.insts[pred.inst];
+            let (cond, then_block) = match *data {
+                InstructionData::Brif {
+                    arg,
+                    blocks: [then_call, _],
+                    ..
+                } => (Some(arg), then_call.block(&func.dfg.value_lists)),
+                _ => (None, block),
+            };
+            let args: Vec<(bool, Value)> = data
+                .branch_destination(&func.dfg.jump_tables)
+                .iter()
+                .enumerate()
+                .filter(|(_, call)| call.block(&func.dfg.value_lists) == block)
+                .map(|(i, call)| {
+                    let is_then = cond.is_some() && i == 0 && then_block == block;
+                    (is_then, call.args_slice(&func.dfg.value_lists)[index])
+                })
+                .collect();
+            for (is_then, arg) in args {
+                let edge_range = match cond {
+                    Some(cond) => self.edge_range(func, cond, is_then, param, arg, depth),
+                    None => self.range(func, arg, depth),
+                };
+                range = Some(match range {
+                    Some(range) => range.join(edge_range),
+                    None => edge_range,
+                });
+            }
+        }
+        range
+    }
+
+    /// Compute the range of `arg`, passed to `param` on an edge taken when
+    /// `cond` is `taken_if`, refined by that condition.
+    fn edge_range(
+        &mut self,
+        func: &Function,
+        cond: Value,
+        taken_if: bool,
+        param: Value,
+        arg: Value,
+        depth: usize,
+    ) -> Range {
+        let arg = func.dfg.resolve_aliases(arg);
+        let max = max_for_bits(func.dfg.value_type(arg).bits());
+
+        // If the condition bounds the argument, or the parameter when the
+        // argument is the parameter plus a constant (as for a loop's
+        // induction variable), then use that bound.
+        if let Some(InBounds {
+            base,
+            offset,
+            bound: Bound::Const(bound),
+        }) = in_bounds_if(func, cond, taken_if)
+        {
+            let upper = if base == arg {
+                bound.checked_sub(offset)
+            } else if base == param {
+                add_const(func, arg, param).and_then(|step| {
+                    bound
+                        .checked_sub(offset)?
+                        .checked_add(step)
+                        .filter(|&upper| upper <= max)
+                })
+            } else {
+                None
+            };
+            if let Some(upper) = upper {
+                return Range { min: 0, max: upper };
+            }
+        }
+        self.range(func, arg, depth)
+    }
+}
+
+/// If `value` is `base + c` for a constant `c`, get `c`.
+fn add_const(func: &Function, value: Value, base: Value) -> Option<u64> {
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(value) else {
+        return None;
+    };
+    let InstructionData::Binary {
+        opcode: Opcode::Iadd,
+        args,
+    } = func.dfg.insts[inst]
+    else {
+        return None;
+    };
+    let [x, y] = args.map(|arg| func.dfg.resolve_aliases(arg));
+    if x == base {
+        iconst_value(func, y)
+    } else if y == base {
+        iconst_value(func, x)
+    } else {
+        None
+    }
+}
+
+/// Get the fact that holds when the condition `cond` is `if_value`, if it
+/// has the form of a bounds check.
+fn in_bounds_if(func: &Function, cond: Value, if_value: bool) -> Option<InBounds> {
+    let cond = func.dfg.resolve_aliases(cond);
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(cond) else {
+        return None;
+    };
+    let (cc, x, y) = match func.dfg.insts[inst] {
+        InstructionData::IntCompare { cond, args, .. } => (
+            cond,
+            func.dfg.resolve_aliases(args[0]),
+            Operand::Value(args[1]),
+        ),
+        InstructionData::IntCompareImm { cond, arg, imm, .. } => {
+            let bits = func.dfg.value_type(arg).bits();
+            let imm = imm.zero_extend_from_width(bits).bits() as u64;
+            (cond, func.dfg.resolve_aliases(arg), Operand::Const(imm))
+        }
+        InstructionData::Unary {
+            opcode: Opcode::Uextend,
+            arg,
+        } => return in_bounds_if(func, arg, if_value),
+        _ => return None,
+    };
+    if !func.dfg.value_type(x).is_int() {
+        return None;
+    }
+    let cc = if if_value { cc } else { cc.complement() };
+
+    // Normalize to `lhs + offset <= rhs`.
+    let (lhs, offset, rhs) = match (cc, Operand::Value(x), y) {
+        (IntCC::UnsignedLessThanOrEqual, lhs, rhs) => (lhs, 0, rhs),
+        (IntCC::UnsignedLessThan, lhs, rhs) => (lhs, 1, rhs),
+        (IntCC::UnsignedGreaterThanOrEqual, rhs, lhs) => (lhs, 0, rhs),
+        (IntCC::UnsignedGreaterThan, rhs, lhs) => (lhs, 1, rhs),
+        _ => return None,
+    };
+    let Operand::Value(lhs) = lhs else {
+        return None;
+    };
+    let lhs = func.dfg.resolve_aliases(lhs);
+    let bound = match rhs {
+        Operand::Value(v) => {
+            let v = func.dfg.resolve_aliases(v);
+            match iconst_value(func, v) {
+                Some(c) => Bound::Const(c),
+                None => Bound::Value(v),
+            }
+        }
+        Operand::Const(c) => Bound::Const(c),
+    };
+
+    // Look through an addition of a constant which can't overflow.
+    if let ValueDef::Result(add, 0) = func.dfg.value_def(lhs) {
+        if let InstructionData::IntAddTrap {
+            opcode: Opcode::UaddOverflowTrap,
+            args: [a, b],
+            ..
+        } = func.dfg.insts[add]
+        {
+            if let Some(c) = iconst_value(func, b) {
+                return Some(InBounds {
+                    base: func.dfg.resolve_aliases(a),
+                    offset: c.checked_add(offset)?,
+                    bound,
+                });
+            }
+        }
+    }
+    Some(InBounds {
+        base: lhs,
+        offset,
+        bound,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Operand {
+    Value(Value),
+    Const(u64),
+}
+
+fn iconst_value(func: &Function, value: Value) -> Option<u64> {
+    let value = func.dfg.resolve_aliases(value);
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(value) else {
+        return None;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => {
+            let bits = func.dfg.value_type(value).bits();
+            Some(imm.zero_extend_from_width(bits).bits() as u64)
+        }
+        _ => None,
+    }
+}
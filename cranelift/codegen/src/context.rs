@@ -10,13 +10,17 @@
 //! single ISA instance.
 
 use crate::alias_analysis::AliasAnalysis;
+use crate::bounds_check_elimination::do_bounds_check_elimination;
+use crate::dead_store_elimination::do_dead_store_elimination;
 use crate::dominator_tree::DominatorTree;
 use crate::egraph::EgraphPass;
 use crate::flowgraph::ControlFlowGraph;
+use crate::inline::{do_inlining, Inline};
 use crate::ir::Function;
 use crate::isa::TargetIsa;
 use crate::legalizer::simple_legalize;
 use crate::loop_analysis::LoopAnalysis;
+use crate::loop_opts::do_loop_opts;
 use crate::machinst::{CompiledCode, CompiledCodeStencil};
 use crate::nan_canonicalization::do_nan_canonicalization;
 use crate::remove_constant_phis::do_remove_constant_phis;
@@ -173,12 +177,21 @@ impl Context {
 
         self.compute_domtree();
         self.eliminate_unreachable_code(isa)?;
+        if opt_level != OptLevel::None {
+            self.optimize_loops(isa)?;
+        }
         self.remove_constant_phis(isa)?;
 
         self.func.dfg.resolve_all_aliases();
 
         if opt_level != OptLevel::None {
             self.egraph_pass(isa, ctrl_plane)?;
+            if !isa.flags().enable_pcc() {
+                self.eliminate_redundant_bounds_checks(isa)?;
+            }
+            if isa.flags().enable_alias_analysis() {
+                self.eliminate_dead_stores(isa)?;
+            }
         }
 
         Ok(())
@@ -324,6 +337,25 @@ impl Context {
         self.verify_if(fisa)
     }
 
+    /// Perform induction-variable based loop optimizations.
+    ///
+    /// Small loops with a constant trip count are only fully unrolled when
+    /// optimizing for speed alone.
+    pub fn optimize_loops<'a, FOI>(&mut self, fisa: FOI) -> CodegenResult<()>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        let fisa = fisa.into();
+        let unroll = fisa.flags.opt_level() == OptLevel::Speed;
+        self.compute_loop_analysis();
+        if do_loop_opts(&mut self.func, &self.cfg, &self.loop_analysis, unroll) {
+            self.compute_cfg();
+            self.compute_domtree();
+            self.loop_analysis.clear();
+        }
+        self.verify_if(fisa)
+    }
+
     /// Replace all redundant loads with the known values in
     /// memory. These are loads whose values were already loaded by
     /// other loads earlier, as well as loads whose values were stored
@@ -335,6 +367,36 @@ impl Context {
         Ok(())
     }
 
+    /// Remove bounds checks, and other conditional traps of the same form,
+    /// which are known never to trap from the ranges of their operands or
+    /// from dominating checks.
+    pub fn eliminate_redundant_bounds_checks<'a, FOI>(&mut self, fisa: FOI) -> CodegenResult<()>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        do_bounds_check_elimination(&mut self.func, &self.cfg, &self.domtree);
+        self.verify_if(fisa)
+    }
+
+    /// Remove stores whose values are overwritten by later stores before
+    /// they can be observed.
+    pub fn eliminate_dead_stores<'a, FOI>(&mut self, fisa: FOI) -> CodegenResult<()>
+    where
+        FOI: Into<FlagsOrIsa<'a>>,
+    {
+        do_dead_store_elimination(&mut self.func, &self.cfg, &self.domtree);
+        self.verify_if(fisa)
+    }
+
+    /// Inline the direct calls within this function selected by `inliner`.
+    ///
+    /// This is expected to be run before compiling the function, after which
+    /// the inlined callees are optimized along with the rest of the function.
+    /// Returns whether any call was inlined.
+    pub fn inline(&mut self, inliner: impl Inline) -> CodegenResult<bool> {
+        do_inlining(&mut self.func, inliner)
+    }
+
     /// Harvest candidate left-hand sides for superoptimization with Souper.
     #[cfg(feature = "souper-harvest")]
     pub fn souper_harvest(
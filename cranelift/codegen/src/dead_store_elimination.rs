@@ -0,0 +1,201 @@
+//! Dead-store elimination.
+//!
+//! A store is dead if the bytes it writes are overwritten by a later store
+//! before anything could observe them. This pass finds such stores by
+//! scanning each block backward and keeping a set of *pending* stores: the
+//! stores seen so far (i.e., later in program order) whose bytes haven't been
+//! observed between their position and the current scan position.
+//!
+//! Observing memory is understood in the same terms as for alias analysis
+//! (see `alias_analysis.rs`): a load only observes stores in its own
+//! category of abstract state, as given by the alias region in its
+//! `MemFlags`, while calls, fences, and any other instruction with unknown
+//! memory effects observe everything. Any instruction which may trap also
+//! observes everything, because the memory state after a trap may be
+//! visible to the embedder.
+//!
+//! Trapping stores need extra care. A store `S1` can only be removed in favor
+//! of a later trapping store `S2` if both access exactly the same bytes, and
+//! either `S1` can't trap at all (and so neither can `S2`), or both have the
+//! same trap code and nothing at all happened in between them, so that `S2`
+//! trapping in place of `S1` is indistinguishable.
+//!
+//! Pending stores are carried across a `jump` to a block whose only
+//! predecessor is the jumping block, so that chains of blocks which are
+//! straight-line code in all but name are handled too. Blocks are visited in
+//! postorder so that such a successor has been scanned before its
+//! predecessor.
+
+use crate::dominator_tree::DominatorTree;
+use crate::flowgraph::ControlFlowGraph;
+use crate::inst_predicates::{access_size, has_memory_fence_semantics, inst_addr_offset_type};
+use crate::ir::{AliasRegion, Block, Function, Inst, InstructionData, Opcode, TrapCode, Value};
+use crate::{timing, trace};
+use rustc_hash::FxHashMap;
+use smallvec::SmallVec;
+
+/// The maximum number of pending stores tracked at once, bounding the cost of
+/// checking each store against them.
+const MAX_PENDING_STORES: usize = 16;
+
+/// A store which will be executed later, and whose bytes haven't been
+/// observed since the current scan position.
+#[derive(Clone, Copy, Debug)]
+struct PendingStore {
+    address: Value,
+    start: i64,
+    end: i64,
+    trap_code: Option<TrapCode>,
+    region: Option<AliasRegion>,
+    /// Whether no other store has been passed since this one.
+    clean: bool,
+}
+
+impl PendingStore {
+    /// Get the store performed by `inst`, if it is a plain store which is a
+    /// candidate for removal.
+    fn for_inst(func: &Function, inst: Inst) -> Option<PendingStore> {
+        let InstructionData::Store { opcode, flags, .. } = func.dfg.insts[inst] else {
+            return None;
+        };
+        let (address, offset, ty) = inst_addr_offset_type(func, inst)?;
+        let start = i64::from(offset);
+        let size = access_size(opcode, ty)?;
+        Some(PendingStore {
+            address: func.dfg.resolve_aliases(address),
+            start,
+            end: start + i64::from(size),
+            trap_code: flags.trap_code(),
+            region: flags.alias_region(),
+            clean: true,
+        })
+    }
+
+    /// Does this store, executed later, make `earlier` dead?
+    fn kills(&self, earlier: &PendingStore) -> bool {
+        if self.address != earlier.address || self.region != earlier.region {
+            return false;
+        }
+        match self.trap_code {
+            None => self.start <= earlier.start && earlier.end <= self.end,
+            Some(code) => {
+                self.start == earlier.start
+                    && self.end == earlier.end
+                    && (earlier.trap_code.is_none()
+                        || (earlier.trap_code == Some(code) && self.clean))
+            }
+        }
+    }
+
+    /// May this store's bytes be read by a load of `start..end` from
+    /// `address` in `region`?
+    fn may_be_read_by(
+        &self,
+        region: Option<AliasRegion>,
+        address: Value,
+        start: i64,
+        end: i64,
+    ) -> bool {
+        self.region == region && (self.address != address || (start < self.end && self.start < end))
+    }
+}
+
+/// Remove stores which are overwritten before they can be observed.
+pub fn do_dead_store_elimination(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    domtree: &DominatorTree,
+) {
+    let _tt = timing::dead_store_elimination();
+
+    let mut block_entry_pending: FxHashMap<Block, SmallVec<[PendingStore; 4]>> =
+        FxHashMap::default();
+    let mut dead_stores = vec![];
+
+    for &block in domtree.cfg_postorder() {
+        let mut pending = initial_pending(func, cfg, block, &block_entry_pending);
+
+        let mut next_inst = func.layout.last_inst(block);
+        while let Some(inst) = next_inst {
+            next_inst = func.layout.prev_inst(inst);
+
+            if let Some(store) = PendingStore::for_inst(func, inst) {
+                if pending.iter().any(|p| p.kills(&store)) {
+                    trace!("dead store elimination: removing dead store {}", inst);
+                    dead_stores.push(inst);
+                    continue;
+                }
+                for p in pending.iter_mut() {
+                    p.clean = false;
+                }
+                if store.trap_code.is_some() {
+                    pending.clear();
+                }
+                if pending.len() < MAX_PENDING_STORES {
+                    pending.push(store);
+                }
+                continue;
+            }
+
+            let data = &func.dfg.insts[inst];
+            let opcode = data.opcode();
+            if opcode.can_load() {
+                match (data.memflags(), inst_addr_offset_type(func, inst)) {
+                    (Some(flags), Some((address, offset, ty))) if flags.trap_code().is_none() => {
+                        let address = func.dfg.resolve_aliases(address);
+                        let start = i64::from(offset);
+                        let Some(size) = access_size(opcode, ty) else {
+                            pending.clear();
+                            continue;
+                        };
+                        let end = start + i64::from(size);
+                        let region = flags.alias_region();
+                        pending.retain(|p| !p.may_be_read_by(region, address, start, end));
+                    }
+                    _ => pending.clear(),
+                }
+            } else if opcode.can_store()
+                || opcode.is_call()
+                || opcode.other_side_effects()
+                || has_memory_fence_semantics(opcode)
+                || opcode.can_trap()
+                || data.trap_code().is_some()
+            {
+                pending.clear();
+            }
+        }
+
+        block_entry_pending.insert(block, pending);
+    }
+
+    for inst in dead_stores {
+        func.layout.remove_inst(inst);
+    }
+}
+
+/// Get the pending stores at the end of `block`: those at the start of its
+/// successor if it unconditionally jumps to a block with no other
+/// predecessors, and none otherwise.
+fn initial_pending(
+    func: &Function,
+    cfg: &ControlFlowGraph,
+    block: Block,
+    block_entry_pending: &FxHashMap<Block, SmallVec<[PendingStore; 4]>>,
+) -> SmallVec<[PendingStore; 4]> {
+    let Some(terminator) = func.layout.last_inst(block) else {
+        return SmallVec::new();
+    };
+    let InstructionData::Jump {
+        opcode: Opcode::Jump,
+        destination,
+        ..
+    } = func.dfg.insts[terminator]
+    else {
+        return SmallVec::new();
+    };
+    let succ = destination.block(&func.dfg.value_lists);
+    if succ == block || cfg.pred_iter(succ).count() != 1 {
+        return SmallVec::new();
+    }
+    block_entry_pending.get(&succ).cloned().unwrap_or_default()
+}
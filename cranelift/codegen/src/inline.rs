@@ -0,0 +1,549 @@
+//! Function inlining.
+//!
+//! Cranelift compiles one function at a time and has no knowledge of the
+//! bodies of other functions, so inlining is driven by the embedder through
+//! the [`Inline`] trait: for each direct `call` in the function being
+//! compiled it decides whether the call is inlined and, if so, provides the
+//! callee's body.
+//!
+//! Inlining a call splits the caller's block at the call, copies the callee's
+//! blocks in between the two halves, turns the call into a jump to the copy of
+//! the callee's entry block, and turns the callee's `return`s into jumps to the
+//! second half of the split block, whose parameters replace the call's
+//! results. Calls within inlined bodies are not themselves considered for
+//! inlining.
+
+use crate::entity::{EntityList, SecondaryMap};
+use crate::ir::{
+    self, ArgumentPurpose, Block, BlockCall, ExternalName, FuncRef, GlobalValue, GlobalValueData,
+    Inst, InstBuilder, InstructionData, JumpTableData, Opcode, SigRef, StackSlot, Value,
+};
+use crate::packed_option::PackedOption;
+use crate::result::{CodegenError, CodegenResult};
+use crate::{timing, trace};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use smallvec::SmallVec;
+
+/// The decision of an [`Inline`] implementation for a single call.
+pub enum InlineCommand<'a> {
+    /// Leave the call as-is.
+    KeepCall,
+
+    /// Inline the call, using the given body for the callee.
+    ///
+    /// The callee's signature must be the same as the signature of the call.
+    Inline(Cow<'a, ir::Function>),
+}
+
+/// A policy for [`Context::inline`](crate::Context::inline) which decides
+/// which calls are inlined and provides the bodies of their callees.
+pub trait Inline {
+    /// Decides whether `call_inst`, a direct `call` of `callee` with
+    /// `call_args` within `caller`, is inlined.
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        call_inst: Inst,
+        callee: FuncRef,
+        call_args: &[Value],
+    ) -> InlineCommand<'_>;
+}
+
+impl<T: Inline + ?Sized> Inline for &mut T {
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        call_inst: Inst,
+        callee: FuncRef,
+        call_args: &[Value],
+    ) -> InlineCommand<'_> {
+        (**self).inline(caller, call_inst, callee, call_args)
+    }
+}
+
+/// Inlines the direct calls within `func` selected by `inliner`.
+///
+/// Returns whether any call was inlined.
+pub fn do_inlining(func: &mut ir::Function, mut inliner: impl Inline) -> CodegenResult<bool> {
+    let _tt = timing::inline();
+
+    // Collect the calls up front so that calls within inlined bodies aren't
+    // visited. Instructions keep their identity while blocks are split, so
+    // this list remains valid as calls are inlined.
+    let calls = func
+        .layout
+        .blocks()
+        .flat_map(|block| func.layout.block_insts(block))
+        .filter(|inst| func.dfg.insts[*inst].opcode() == Opcode::Call)
+        .collect::<Vec<_>>();
+
+    let mut inlined_any = false;
+    for call in calls {
+        let (callee_ref, args) = match func.dfg.insts[call] {
+            InstructionData::Call { func_ref, args, .. } => (
+                func_ref,
+                args.as_slice(&func.dfg.value_lists)
+                    .iter()
+                    .copied()
+                    .collect::<SmallVec<[Value; 8]>>(),
+            ),
+            _ => unreachable!(),
+        };
+        let callee = match inliner.inline(func, call, callee_ref, &args) {
+            InlineCommand::KeepCall => continue,
+            InlineCommand::Inline(callee) => callee,
+        };
+
+        let sig = func.dfg.ext_funcs[callee_ref].signature;
+        if callee.signature != func.dfg.signatures[sig] {
+            return Err(CodegenError::Unsupported(format!(
+                "cannot inline `{}` at {call}: its signature does not match the call's",
+                callee.name
+            )));
+        }
+        if let Err(reason) = can_inline(func, &callee, &args) {
+            trace!("not inlining `{}` at {call}: {reason}", callee.name);
+            continue;
+        }
+
+        trace!("inlining `{}` at {call}", callee.name);
+        Inliner::new(func, &callee).inline(call, &args);
+        inlined_any = true;
+    }
+
+    if inlined_any {
+        trace!("After inlining:\n{}", func.display());
+    }
+    Ok(inlined_any)
+}
+
+/// Checks for constructs within `callee` which can't be inlined into `func`
+/// with the call arguments `args`.
+fn can_inline(
+    func: &ir::Function,
+    callee: &ir::Function,
+    args: &[Value],
+) -> Result<(), &'static str> {
+    if callee.layout.entry_block().is_none() {
+        return Err("callee has no body");
+    }
+    if !callee.dynamic_stack_slots.is_empty() {
+        return Err("callee uses dynamic stack slots");
+    }
+    if !callee.memory_types.is_empty() {
+        return Err("callee uses memory types");
+    }
+
+    // The callee's `vmctx` global value refers to its own `vmctx` parameter,
+    // so it can only be mapped to the caller's when the call passes the
+    // caller's `vmctx` along.
+    let uses_vmctx = callee
+        .global_values
+        .values()
+        .any(|gv| matches!(gv, GlobalValueData::VMContext));
+    if uses_vmctx {
+        let passes_vmctx = callee
+            .signature
+            .special_param_index(ArgumentPurpose::VMContext)
+            .zip(func.special_param(ArgumentPurpose::VMContext))
+            .is_some_and(|(index, vmctx)| {
+                func.dfg.resolve_aliases(args[index]) == func.dfg.resolve_aliases(vmctx)
+            });
+        if !passes_vmctx {
+            return Err("callee's `vmctx` differs from the caller's");
+        }
+    }
+
+    // Instructions observing the current frame would observe the caller's
+    // frame instead once inlined.
+    for block in callee.layout.blocks() {
+        for inst in callee.layout.block_insts(block) {
+            match callee.dfg.insts[inst].opcode() {
+                Opcode::GetFramePointer
+                | Opcode::GetStackPointer
+                | Opcode::GetReturnAddress
+                | Opcode::StackSwitch => return Err("callee inspects its frame"),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// State for copying a single callee into a caller.
+struct Inliner<'a> {
+    func: &'a mut ir::Function,
+    callee: &'a ir::Function,
+    blocks: SecondaryMap<Block, PackedOption<Block>>,
+    values: SecondaryMap<Value, PackedOption<Value>>,
+    global_values: SecondaryMap<GlobalValue, PackedOption<GlobalValue>>,
+    sig_refs: SecondaryMap<SigRef, PackedOption<SigRef>>,
+    func_refs: SecondaryMap<FuncRef, PackedOption<FuncRef>>,
+    stack_slots: SecondaryMap<StackSlot, PackedOption<StackSlot>>,
+}
+
+impl<'a> Inliner<'a> {
+    fn new(func: &'a mut ir::Function, callee: &'a ir::Function) -> Self {
+        let mut inliner = Inliner {
+            func,
+            callee,
+            blocks: SecondaryMap::new(),
+            values: SecondaryMap::new(),
+            global_values: SecondaryMap::new(),
+            sig_refs: SecondaryMap::new(),
+            func_refs: SecondaryMap::new(),
+            stack_slots: SecondaryMap::new(),
+        };
+
+        // Signatures and external functions which the caller already declares
+        // are reused rather than declared again.
+        for (sig_ref, sig) in callee.dfg.signatures.iter() {
+            let existing = inliner
+                .func
+                .dfg
+                .signatures
+                .iter()
+                .find(|(_, s)| *s == sig)
+                .map(|(s, _)| s);
+            let sig_ref_in_caller =
+                existing.unwrap_or_else(|| inliner.func.import_signature(sig.clone()));
+            inliner.sig_refs[sig_ref] = sig_ref_in_caller.into();
+        }
+        for (func_ref, data) in callee.dfg.ext_funcs.iter() {
+            let data = ir::ExtFuncData {
+                name: inliner.external_name(&data.name),
+                signature: inliner.sig_refs[data.signature].unwrap(),
+                colocated: data.colocated,
+            };
+            let existing = inliner
+                .func
+                .dfg
+                .ext_funcs
+                .iter()
+                .find(|(_, f)| {
+                    f.name == data.name
+                        && f.colocated == data.colocated
+                        && inliner.func.dfg.signatures[f.signature]
+                            == inliner.func.dfg.signatures[data.signature]
+                })
+                .map(|(f, _)| f);
+            let func_ref_in_caller = existing.unwrap_or_else(|| inliner.func.import_function(data));
+            inliner.func_refs[func_ref] = func_ref_in_caller.into();
+        }
+        for (slot, data) in callee.sized_stack_slots.iter() {
+            inliner.stack_slots[slot] = inliner.func.create_sized_stack_slot(data.clone()).into();
+        }
+        inliner
+    }
+
+    /// Inlines the callee at `call`, which passes `args`.
+    fn inline(mut self, call: Inst, args: &[Value]) {
+        let callee = self.callee;
+
+        // Split the caller's block after the call, and replace the call's
+        // results with the parameters of the new block.
+        let return_block = self.func.dfg.make_block();
+        let next = self.func.layout.next_inst(call).unwrap();
+        self.func.layout.split_block(return_block, next);
+        let results = self.func.dfg.inst_results(call).to_vec();
+        self.func.dfg.clear_results(call);
+        for result in results {
+            let ty = self.func.dfg.value_type(result);
+            let param = self.func.dfg.append_block_param(return_block, ty);
+            self.func.dfg.change_to_alias(result, param);
+        }
+
+        // Create the callee's blocks. The entry block's parameters are the
+        // call's arguments, which is fine as the entry block can't be the
+        // target of any branch.
+        let entry = callee.layout.entry_block().unwrap();
+        for block in callee.layout.blocks() {
+            let new_block = self.func.dfg.make_block();
+            self.func.layout.insert_block(new_block, return_block);
+            if callee.layout.is_cold(block) {
+                self.func.layout.set_cold(new_block);
+            }
+            for (i, param) in callee.dfg.block_params(block).iter().enumerate() {
+                self.values[*param] = if block == entry {
+                    args[i].into()
+                } else {
+                    let ty = callee.dfg.value_type(*param);
+                    self.func.dfg.append_block_param(new_block, ty).into()
+                };
+            }
+            self.blocks[block] = new_block.into();
+        }
+
+        // Values live across the call in the caller remain live across every
+        // safepoint within the callee.
+        let call_stack_map = self.func.dfg.take_user_stack_map_entries(call);
+        let call_srcloc = self.func.srcloc(call);
+        self.func
+            .dfg
+            .replace(call)
+            .jump(self.blocks[entry].unwrap(), &[]);
+
+        // Copy all instructions, creating their results. Their operands still
+        // refer to the callee's values at this point since values may be used
+        // in the layout before they're defined.
+        let mut copied = Vec::new();
+        for block in callee.layout.blocks() {
+            let new_block = self.blocks[block].unwrap();
+            for inst in callee.layout.block_insts(block) {
+                let new_inst = self.copy_inst(inst, return_block, new_block);
+                copied.push(new_inst);
+
+                let srcloc = callee.srcloc(inst);
+                let srcloc = if srcloc.is_default() {
+                    call_srcloc
+                } else {
+                    srcloc
+                };
+                if !srcloc.is_default() {
+                    self.func.set_srcloc(new_inst, srcloc);
+                }
+
+                if self.func.dfg.insts[new_inst].opcode().is_safepoint() {
+                    let entries = callee.dfg.user_stack_map_entries(inst).unwrap_or(&[]);
+                    for entry in entries {
+                        let mut entry = entry.clone();
+                        entry.slot = self.stack_slots[entry.slot].unwrap();
+                        self.func.dfg.append_user_stack_map_entry(new_inst, entry);
+                    }
+                    for entry in call_stack_map.iter().flatten() {
+                        self.func
+                            .dfg
+                            .append_user_stack_map_entry(new_inst, entry.clone());
+                    }
+                }
+
+                // A converted `return_call` is followed by a jump returning
+                // the call's results.
+                if matches!(
+                    callee.dfg.insts[inst].opcode(),
+                    Opcode::ReturnCall | Opcode::ReturnCallIndirect
+                ) {
+                    let results = self.func.dfg.inst_results(new_inst).to_vec();
+                    let destination =
+                        BlockCall::new(return_block, &results, &mut self.func.dfg.value_lists);
+                    let jump = self.func.dfg.make_inst(InstructionData::Jump {
+                        opcode: Opcode::Jump,
+                        destination,
+                    });
+                    self.func.layout.append_inst(jump, new_block);
+                    if !call_srcloc.is_default() {
+                        self.func.set_srcloc(jump, call_srcloc);
+                    }
+                }
+            }
+        }
+
+        // Now that all values are defined, rewrite the operands.
+        let dfg = &mut self.func.dfg;
+        let values = &self.values;
+        for inst in copied {
+            dfg.insts[inst].map_values(&mut dfg.value_lists, &mut dfg.jump_tables, |v| {
+                values[callee.dfg.resolve_aliases(v)]
+                    .expand()
+                    .expect("callee value used but not defined")
+            });
+        }
+    }
+
+    /// Copies the callee's instruction `inst` to the end of `block`, turning
+    /// returns into jumps to `return_block`.
+    ///
+    /// The copy's operands still refer to the callee's values.
+    fn copy_inst(&mut self, inst: Inst, return_block: Block, block: Block) -> Inst {
+        let callee = self.callee;
+        let callee_pool = &callee.dfg.value_lists;
+        let mut data = callee.dfg.insts[inst];
+        match &mut data {
+            InstructionData::MultiAry {
+                opcode: Opcode::Return,
+                args,
+            } => {
+                let destination = BlockCall::new(
+                    return_block,
+                    args.as_slice(callee_pool),
+                    &mut self.func.dfg.value_lists,
+                );
+                data = InstructionData::Jump {
+                    opcode: Opcode::Jump,
+                    destination,
+                };
+            }
+            InstructionData::MultiAry { args, .. } => {
+                *args = self.copy_list(args);
+            }
+            InstructionData::Call {
+                opcode,
+                args,
+                func_ref,
+            } => {
+                // Tail calls within the callee become regular calls whose
+                // results are then returned.
+                if *opcode == Opcode::ReturnCall {
+                    *opcode = Opcode::Call;
+                }
+                *args = self.copy_list(args);
+                *func_ref = self.func_refs[*func_ref].unwrap();
+            }
+            InstructionData::CallIndirect {
+                opcode,
+                args,
+                sig_ref,
+            } => {
+                if *opcode == Opcode::ReturnCallIndirect {
+                    *opcode = Opcode::CallIndirect;
+                }
+                *args = self.copy_list(args);
+                *sig_ref = self.sig_refs[*sig_ref].unwrap();
+            }
+            InstructionData::FuncAddr { func_ref, .. } => {
+                *func_ref = self.func_refs[*func_ref].unwrap();
+            }
+            InstructionData::UnaryGlobalValue { global_value, .. } => {
+                *global_value = self.global_value(*global_value);
+            }
+            InstructionData::StackLoad { stack_slot, .. }
+            | InstructionData::StackStore { stack_slot, .. } => {
+                *stack_slot = self.stack_slots[*stack_slot].unwrap();
+            }
+            InstructionData::UnaryConst {
+                constant_handle, ..
+            } => {
+                let constant = callee.dfg.constants.get(*constant_handle).clone();
+                *constant_handle = self.func.dfg.constants.insert(constant);
+            }
+            InstructionData::Shuffle { imm, .. } => {
+                let immediate = callee.dfg.immediates[*imm].clone();
+                *imm = self.func.dfg.immediates.push(immediate);
+            }
+            InstructionData::Jump { destination, .. } => {
+                *destination = self.copy_block_call(*destination);
+            }
+            InstructionData::Brif { blocks, .. } => {
+                for block in blocks.iter_mut() {
+                    *block = self.copy_block_call(*block);
+                }
+            }
+            InstructionData::BranchTable { table, .. } => {
+                let branches = callee.dfg.jump_tables[*table]
+                    .all_branches()
+                    .iter()
+                    .map(|branch| self.copy_block_call(*branch))
+                    .collect::<SmallVec<[_; 16]>>();
+                *table = self
+                    .func
+                    .create_jump_table(JumpTableData::new(branches[0], &branches[1..]));
+            }
+            InstructionData::DynamicStackLoad { .. }
+            | InstructionData::DynamicStackStore { .. } => {
+                unreachable!("checked by `can_inline`")
+            }
+            _ => {}
+        }
+
+        let new_inst = self.func.dfg.make_inst(data);
+        self.func.layout.append_inst(new_inst, block);
+        let ctrl_typevar = callee.dfg.ctrl_typevar(inst);
+        self.func.dfg.make_inst_results(new_inst, ctrl_typevar);
+        let new_results = self.func.dfg.inst_results(new_inst);
+        for (result, new_result) in callee.dfg.inst_results(inst).iter().zip(new_results) {
+            self.values[*result] = (*new_result).into();
+        }
+        new_inst
+    }
+
+    /// Copies the callee's value list `list` into the caller's value list
+    /// pool.
+    fn copy_list(&mut self, list: &ir::ValueList) -> ir::ValueList {
+        EntityList::from_slice(
+            list.as_slice(&self.callee.dfg.value_lists),
+            &mut self.func.dfg.value_lists,
+        )
+    }
+
+    /// Copies the callee's block call `call`, mapping its block.
+    fn copy_block_call(&mut self, call: BlockCall) -> BlockCall {
+        let pool = &self.callee.dfg.value_lists;
+        BlockCall::new(
+            self.blocks[call.block(pool)].unwrap(),
+            call.args_slice(pool),
+            &mut self.func.dfg.value_lists,
+        )
+    }
+
+    /// Returns the caller's global value corresponding to the callee's `gv`,
+    /// creating it if necessary.
+    fn global_value(&mut self, gv: GlobalValue) -> GlobalValue {
+        if let Some(new_gv) = self.global_values[gv].expand() {
+            return new_gv;
+        }
+        let data = match &self.callee.global_values[gv] {
+            GlobalValueData::VMContext => self
+                .func
+                .global_values
+                .iter()
+                .find(|(_, data)| matches!(data, GlobalValueData::VMContext))
+                .map(|(new_gv, _)| new_gv),
+            _ => None,
+        };
+        let new_gv = match data {
+            Some(new_gv) => new_gv,
+            None => {
+                let data = match self.callee.global_values[gv].clone() {
+                    GlobalValueData::Load {
+                        base,
+                        offset,
+                        global_type,
+                        flags,
+                    } => GlobalValueData::Load {
+                        base: self.global_value(base),
+                        offset,
+                        global_type,
+                        flags,
+                    },
+                    GlobalValueData::IAddImm {
+                        base,
+                        offset,
+                        global_type,
+                    } => GlobalValueData::IAddImm {
+                        base: self.global_value(base),
+                        offset,
+                        global_type,
+                    },
+                    GlobalValueData::Symbol {
+                        name,
+                        offset,
+                        colocated,
+                        tls,
+                    } => GlobalValueData::Symbol {
+                        name: self.external_name(&name),
+                        offset,
+                        colocated,
+                        tls,
+                    },
+                    data @ (GlobalValueData::VMContext
+                    | GlobalValueData::DynScaleTargetConst { .. }) => data,
+                };
+                self.func.create_global_value(data)
+            }
+        };
+        self.global_values[gv] = new_gv.into();
+        new_gv
+    }
+
+    /// Returns the caller's equivalent of the callee's external `name`.
+    fn external_name(&mut self, name: &ExternalName) -> ExternalName {
+        match name {
+            ExternalName::User(name) => {
+                let name = self.callee.params.user_named_funcs()[*name].clone();
+                ExternalName::User(self.func.declare_imported_user_function(name))
+            }
+            other => other.clone(),
+        }
+    }
+}
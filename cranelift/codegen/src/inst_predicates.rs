@@ -128,6 +128,28 @@ pub fn inst_addr_offset_type(func: &Function, inst: Inst) -> Option<(Value, Offs
     }
 }
 
+/// Get the number of bytes of memory accessed by a load or store with the
+/// given opcode and accessed type, if known.
+///
+/// This differs from the size of the accessed type for extending loads and
+/// truncating stores.
+pub fn access_size(opcode: Opcode, ty: Type) -> Option<u32> {
+    let size = match opcode {
+        Opcode::Uload8 | Opcode::Sload8 | Opcode::Istore8 => 1,
+        Opcode::Uload16 | Opcode::Sload16 | Opcode::Istore16 => 2,
+        Opcode::Uload32 | Opcode::Sload32 | Opcode::Istore32 => 4,
+        Opcode::Uload8x8
+        | Opcode::Sload8x8
+        | Opcode::Uload16x4
+        | Opcode::Sload16x4
+        | Opcode::Uload32x2
+        | Opcode::Sload32x2 => 8,
+        Opcode::Load | Opcode::Store => ty.bytes(),
+        _ => return None,
+    };
+    Some(size)
+}
+
 /// Get the store data, if any, from an instruction.
 pub fn inst_store_data(func: &Function, inst: Inst) -> Option<Value> {
     match &func.dfg.insts[inst] {
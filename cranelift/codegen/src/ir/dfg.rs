@@ -586,6 +586,15 @@ impl DataFlowGraph {
         assert!(opcode.is_safepoint());
         self.user_stack_maps.entry(inst).or_default().push(entry);
     }
+
+    /// Remove and return the stack map entries associated with the given
+    /// instruction.
+    pub(crate) fn take_user_stack_map_entries(
+        &mut self,
+        inst: Inst,
+    ) -> Option<UserStackMapEntryVec> {
+        self.user_stack_maps.remove(&inst)
+    }
 }
 
 /// Where did a value come from?
@@ -7,8 +7,8 @@ use crate::entity::{PrimaryMap, SecondaryMap};
 use crate::ir::{
     self, pcc::Fact, Block, DataFlowGraph, DynamicStackSlot, DynamicStackSlotData,
     DynamicStackSlots, DynamicType, ExtFuncData, FuncRef, GlobalValue, GlobalValueData, Inst,
-    JumpTable, JumpTableData, Layout, MemoryType, MemoryTypeData, SigRef, Signature, SourceLocs,
-    StackSlot, StackSlotData, StackSlots, Type,
+    JumpTable, JumpTableData, Layout, MemoryType, MemoryTypeData, ProfileData, SigRef, Signature,
+    SourceLocs, StackSlot, StackSlotData, StackSlots, Type,
 };
 use crate::isa::CallConv;
 use crate::write::write_function;
@@ -196,6 +196,12 @@ pub struct FunctionStencil {
     /// ensure that a trap happens if the stack pointer goes below the
     /// threshold specified here.
     pub stack_limit: Option<ir::GlobalValue>,
+
+    /// Execution counts gathered from a previous run of this function, if any.
+    ///
+    /// These are only used as a hint to lay out hot blocks together and to move cold blocks out
+    /// of the way; they never affect the semantics of the function.
+    pub profile: ProfileData,
 }
 
 impl FunctionStencil {
@@ -210,6 +216,7 @@ impl FunctionStencil {
         self.layout.clear();
         self.srclocs.clear();
         self.stack_limit = None;
+        self.profile.clear();
     }
 
     /// Creates a jump table in the function, to be used by `br_table` instructions.
@@ -423,6 +430,7 @@ impl Function {
                 layout: Layout::new(),
                 srclocs: SecondaryMap::new(),
                 stack_limit: None,
+                profile: ProfileData::new(),
             },
             params: FunctionParameters::new(),
         }
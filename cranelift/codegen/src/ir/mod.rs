@@ -20,6 +20,7 @@ pub(crate) mod libcall;
 mod memflags;
 mod memtype;
 pub mod pcc;
+mod profile;
 mod progpoint;
 mod sourceloc;
 pub mod stackslot;
@@ -57,6 +58,7 @@ pub use crate::ir::libcall::{get_probestack_funcref, LibCall};
 pub use crate::ir::memflags::{AliasRegion, Endianness, MemFlags};
 pub use crate::ir::memtype::{MemoryTypeData, MemoryTypeField};
 pub use crate::ir::pcc::{BaseExpr, Expr, Fact, FactContext, PccError, PccResult};
+pub use crate::ir::profile::ProfileData;
 pub use crate::ir::progpoint::ProgramPoint;
 pub use crate::ir::sourceloc::RelSourceLoc;
 pub use crate::ir::sourceloc::SourceLoc;
@@ -0,0 +1,116 @@
+//! Execution profiles.
+//!
+//! A [ProfileData] records how often the blocks and control-flow edges of a function were executed
+//! in some representative run of the program, e.g. gathered by an instrumented build or a
+//! sampling profiler. Cranelift uses it to lay out frequently executed blocks next to each other
+//! and to move rarely executed blocks to the end of the function; see
+//! `machinst::blockorder`.
+
+use crate::entity::SecondaryMap;
+use crate::ir::Block;
+use alloc::collections::BTreeMap;
+
+#[cfg(feature = "enable-serde")]
+use serde_derive::{Deserialize, Serialize};
+
+/// Execution counts for the blocks and edges of a function.
+///
+/// Only the relative magnitude of the counts matters, so they may be scaled arbitrarily. Either
+/// kind of count may be omitted: a block without a count of its own is assumed to execute as
+/// often as all of its profiled incoming edges combined, and blocks and edges without any data
+/// are laid out as if there was no profile.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct ProfileData {
+    /// Number of times each block was entered.
+    block_counts: SecondaryMap<Block, Option<u64>>,
+    /// Number of times control passed from the first block to the second.
+    edge_counts: BTreeMap<(Block, Block), u64>,
+}
+
+impl ProfileData {
+    /// Create an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove all counts from this profile.
+    pub fn clear(&mut self) {
+        self.block_counts.clear();
+        self.edge_counts.clear();
+    }
+
+    /// Does this profile contain any counts at all?
+    pub fn is_empty(&self) -> bool {
+        self.edge_counts.is_empty() && self.block_counts.values().all(|count| count.is_none())
+    }
+
+    /// Record that `block` was entered `count` times.
+    pub fn set_block_count(&mut self, block: Block, count: u64) {
+        self.block_counts[block] = Some(count);
+    }
+
+    /// Record that control passed from `from` to `to` `count` times. Multiple edges between the
+    /// same two blocks, e.g. from a `br_table`, share a single count.
+    pub fn set_edge_count(&mut self, from: Block, to: Block, count: u64) {
+        self.edge_counts.insert((from, to), count);
+    }
+
+    /// The number of times `block` was entered, if it was recorded.
+    pub fn block_count(&self, block: Block) -> Option<u64> {
+        self.block_counts[block]
+    }
+
+    /// The number of times control passed from `from` to `to`, if it was recorded.
+    pub fn edge_count(&self, from: Block, to: Block) -> Option<u64> {
+        self.edge_counts.get(&(from, to)).copied()
+    }
+
+    /// Compute the count of every block: its own count when recorded, and otherwise the sum of
+    /// the counts of its recorded incoming edges.
+    pub fn resolved_block_counts(&self) -> SecondaryMap<Block, Option<u64>> {
+        let mut counts = self.block_counts.clone();
+        let mut incoming: SecondaryMap<Block, Option<u64>> = SecondaryMap::new();
+        for (&(_, to), &count) in &self.edge_counts {
+            let sum = incoming[to].get_or_insert(0);
+            *sum = sum.saturating_add(count);
+        }
+        for (block, sum) in incoming.iter() {
+            if counts[block].is_none() {
+                counts[block] = *sum;
+            }
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityRef;
+
+    #[test]
+    fn resolved_block_counts() {
+        let block0 = Block::new(0);
+        let block1 = Block::new(1);
+        let block2 = Block::new(2);
+        let block3 = Block::new(3);
+
+        let mut profile = ProfileData::new();
+        assert!(profile.is_empty());
+
+        profile.set_block_count(block0, 10);
+        profile.set_edge_count(block0, block1, 7);
+        profile.set_edge_count(block0, block2, 3);
+        profile.set_edge_count(block1, block2, 7);
+        profile.set_block_count(block1, 8);
+        assert!(!profile.is_empty());
+
+        let counts = profile.resolved_block_counts();
+        assert_eq!(counts[block0], Some(10));
+        // Explicit block counts take precedence over incoming edges.
+        assert_eq!(counts[block1], Some(8));
+        assert_eq!(counts[block2], Some(10));
+        assert_eq!(counts[block3], None);
+    }
+}
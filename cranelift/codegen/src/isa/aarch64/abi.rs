@@ -688,7 +688,7 @@ impl ABIMachineSpec for AArch64MachineDeps {
         }
     }
 
-    fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _: u32) {
+    fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _: u32, _: u32) {
         // TODO: implement if we ever require stack probes on an AArch64 host
         // (unlikely unless Lucet is ported)
         unimplemented!("Stack probing is unimplemented on AArch64");
@@ -422,7 +422,7 @@ where
         insts
     }
 
-    fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _frame_size: u32) {
+    fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _frame_size: u32, _guard_size: u32) {
         // Pulley doesn't implement stack probes since all stack pointer
         // decrements are checked already.
     }
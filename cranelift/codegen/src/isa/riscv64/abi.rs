@@ -126,11 +126,22 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         };
 
         for param in params {
-            if let ir::ArgumentPurpose::StructArgument(_) = param.purpose {
-                panic!(
-                    "StructArgument parameters are not supported on riscv64. \
-                    Use regular pointer arguments instead."
+            if let ir::ArgumentPurpose::StructArgument(size) = param.purpose {
+                // Struct arguments are copied into the stack argument area,
+                // as for aggregates which the C ABI passes in memory.
+                assert!(
+                    args_or_rets == ArgsOrRets::Args,
+                    "StructArgument in return position is unsupported"
                 );
+                next_stack = align_to(next_stack, 8);
+                let offset = next_stack as i64;
+                next_stack += align_to(size, 8);
+                args.push(ABIArg::StructArg {
+                    offset,
+                    size: size as u64,
+                    purpose: param.purpose,
+                });
+                continue;
             }
 
             // Find regclass(es) of the register(s) used to store a value of this type.
@@ -413,7 +424,7 @@ impl ABIMachineSpec for Riscv64MachineDeps {
         smallvec![Inst::Ret {}]
     }
 
-    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32) {
+    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32, _guard_size: u32) {
         insts.extend(Inst::load_constant_u32(writable_a0(), frame_size as u64));
         let mut info = CallInfo::empty(
             ExternalName::LibCall(LibCall::Probestack),
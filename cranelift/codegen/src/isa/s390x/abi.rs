@@ -135,6 +135,8 @@
 use crate::ir;
 use crate::ir::condcodes::IntCC;
 use crate::ir::types;
+use crate::ir::ExternalName;
+use crate::ir::LibCall;
 use crate::ir::MemFlags;
 use crate::ir::Signature;
 use crate::ir::Type;
@@ -144,6 +146,7 @@ use crate::isa::unwind::UnwindInst;
 use crate::machinst::*;
 use crate::settings;
 use crate::CodegenResult;
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 use regalloc2::{MachineEnv, PRegSet};
 use smallvec::{smallvec, SmallVec};
@@ -333,11 +336,28 @@ impl ABIMachineSpec for S390xMachineDeps {
         };
 
         for mut param in params.into_iter().copied() {
-            if let ir::ArgumentPurpose::StructArgument(_) = param.purpose {
-                panic!(
-                    "StructArgument parameters are not supported on s390x. \
-                    Use regular pointer arguments instead."
-                );
+            if let ir::ArgumentPurpose::StructArgument(size) = param.purpose {
+                // Struct arguments are copied into the stack argument area.
+                // The copy is made with a call to memcpy, which would clobber
+                // the part of the callee frame holding arguments passed with
+                // the tail-call convention.
+                if call_conv == isa::CallConv::Tail {
+                    return Err(crate::CodegenError::Unsupported(
+                        "StructArgument parameters are not supported \
+                        with the tail calling convention on s390x"
+                            .to_owned(),
+                    ));
+                }
+                debug_assert_eq!(args_or_rets, ArgsOrRets::Args);
+                next_stack = align_to(next_stack, 8);
+                let offset = next_stack as i64;
+                next_stack += align_to(size, 8);
+                args.push(ABIArg::StructArg {
+                    offset,
+                    size: size as u64,
+                    purpose: param.purpose,
+                });
+                continue;
             }
 
             let intreg = in_int_reg(param.value_type);
@@ -451,7 +471,6 @@ impl ABIMachineSpec for S390xMachineDeps {
         // allocate buffers for all ImplicitPtrArg arguments.
         for arg in args.args_mut() {
             match arg {
-                ABIArg::StructArg { .. } => unreachable!(),
                 ABIArg::ImplicitPtrArg { offset, ty, .. } => {
                     *offset = next_stack as i64;
                     next_stack += (ty_bits(*ty) / 8) as u32;
@@ -650,10 +669,10 @@ impl ABIMachineSpec for S390xMachineDeps {
         smallvec![Inst::Ret { link: gpr(14) }]
     }
 
-    fn gen_probestack(_insts: &mut SmallInstVec<Self::I>, _: u32) {
-        // TODO: implement if we ever require stack probes on an s390x host
-        // (unlikely unless Lucet is ported)
-        unimplemented!("Stack probing is unimplemented on S390x");
+    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32, guard_size: u32) {
+        // Calling a probestack function from the prologue would clobber the
+        // link register before it is saved, so always probe inline.
+        gen_probestack_inline(insts, frame_size, guard_size);
     }
 
     fn gen_inline_probestack(
@@ -662,53 +681,7 @@ impl ABIMachineSpec for S390xMachineDeps {
         frame_size: u32,
         guard_size: u32,
     ) {
-        // The stack probe loop currently takes 4 instructions and each unrolled
-        // probe takes 2.  Set this to 2 to keep the max size to 4 instructions.
-        const PROBE_MAX_UNROLL: u32 = 2;
-
-        // Calculate how many probes we need to perform. Round down, as we only
-        // need to probe whole guard_size regions we'd otherwise skip over.
-        let probe_count = frame_size / guard_size;
-        if probe_count == 0 {
-            // No probe necessary
-        } else if probe_count <= PROBE_MAX_UNROLL {
-            // Unrolled probe loop.
-            for _ in 0..probe_count {
-                insts.extend(Self::gen_sp_reg_adjust(-(guard_size as i32)));
-
-                insts.push(Inst::StoreImm8 {
-                    imm: 0,
-                    mem: MemArg::reg(stack_reg(), MemFlags::trusted()),
-                });
-            }
-        } else {
-            // Explicit probe loop.
-
-            // Load the number of probes into a register used as loop counter.
-            // `gen_inline_probestack` is called after regalloc2, so we can
-            // use the nonallocatable spilltmp register for this purpose.
-            let probe_count_reg = writable_spilltmp_reg();
-            if let Ok(probe_count) = i16::try_from(probe_count) {
-                insts.push(Inst::Mov32SImm16 {
-                    rd: probe_count_reg,
-                    imm: probe_count,
-                });
-            } else {
-                insts.push(Inst::Mov32Imm {
-                    rd: probe_count_reg,
-                    imm: probe_count,
-                });
-            }
-
-            // Emit probe loop.  The guard size is assumed to fit in 16 bits.
-            insts.push(Inst::StackProbeLoop {
-                probe_count: probe_count_reg,
-                guard_size: i16::try_from(guard_size).unwrap(),
-            });
-        }
-
-        // Restore the stack pointer to its original position.
-        insts.extend(Self::gen_sp_reg_adjust((probe_count * guard_size) as i32));
+        gen_probestack_inline(insts, frame_size, guard_size);
     }
 
     fn gen_clobber_save(
@@ -857,13 +830,44 @@ impl ABIMachineSpec for S390xMachineDeps {
     }
 
     fn gen_memcpy<F: FnMut(Type) -> Writable<Reg>>(
-        _call_conv: isa::CallConv,
-        _dst: Reg,
-        _src: Reg,
-        _size: usize,
-        _alloc: F,
+        call_conv: isa::CallConv,
+        dst: Reg,
+        src: Reg,
+        size: usize,
+        mut alloc_tmp: F,
     ) -> SmallVec<[Self::I; 8]> {
-        unimplemented!("StructArgs not implemented for S390X yet");
+        let mut insts = SmallVec::new();
+        let tmp = alloc_tmp(Self::word_type());
+        insts.push(Inst::Mov64SImm32 {
+            rd: tmp,
+            imm: i32::try_from(size).unwrap(),
+        });
+        insts.push(Inst::Call {
+            link: writable_gpr(14),
+            info: Box::new(CallInfo {
+                dest: ExternalName::LibCall(LibCall::Memcpy),
+                uses: smallvec![
+                    CallArgPair {
+                        vreg: dst,
+                        preg: gpr(2),
+                    },
+                    CallArgPair {
+                        vreg: src,
+                        preg: gpr(3),
+                    },
+                    CallArgPair {
+                        vreg: tmp.to_reg(),
+                        preg: gpr(4),
+                    },
+                ],
+                defs: smallvec![],
+                clobbers: Self::get_regs_clobbered_by_call(call_conv),
+                callee_pop_size: 0,
+                caller_conv: call_conv,
+                callee_conv: call_conv,
+            }),
+        });
+        insts
     }
 
     fn get_number_of_spillslots_for_value(
@@ -1023,6 +1027,60 @@ impl S390xMachineDeps {
     }
 }
 
+/// Emit an inline stack probe for a frame of `frame_size` bytes, touching
+/// every `guard_size` bytes of the new frame.
+fn gen_probestack_inline(insts: &mut SmallInstVec<Inst>, frame_size: u32, guard_size: u32) {
+    // The stack probe loop currently takes 4 instructions and each unrolled
+    // probe takes 2.  Set this to 2 to keep the max size to 4 instructions.
+    const PROBE_MAX_UNROLL: u32 = 2;
+
+    // Calculate how many probes we need to perform. Round down, as we only
+    // need to probe whole guard_size regions we'd otherwise skip over.
+    let probe_count = frame_size / guard_size;
+    if probe_count == 0 {
+        // No probe necessary
+    } else if probe_count <= PROBE_MAX_UNROLL {
+        // Unrolled probe loop.
+        for _ in 0..probe_count {
+            insts.extend(S390xMachineDeps::gen_sp_reg_adjust(-(guard_size as i32)));
+
+            insts.push(Inst::StoreImm8 {
+                imm: 0,
+                mem: MemArg::reg(stack_reg(), MemFlags::trusted()),
+            });
+        }
+    } else {
+        // Explicit probe loop.
+
+        // Load the number of probes into a register used as loop counter.
+        // Stack probes are generated after regalloc2, so we can
+        // use the nonallocatable spilltmp register for this purpose.
+        let probe_count_reg = writable_spilltmp_reg();
+        if let Ok(probe_count) = i16::try_from(probe_count) {
+            insts.push(Inst::Mov32SImm16 {
+                rd: probe_count_reg,
+                imm: probe_count,
+            });
+        } else {
+            insts.push(Inst::Mov32Imm {
+                rd: probe_count_reg,
+                imm: probe_count,
+            });
+        }
+
+        // Emit probe loop.
+        insts.push(Inst::StackProbeLoop {
+            probe_count: probe_count_reg,
+            guard_size: i32::try_from(guard_size).unwrap(),
+        });
+    }
+
+    // Restore the stack pointer to its original position.
+    insts.extend(S390xMachineDeps::gen_sp_reg_adjust(
+        (probe_count * guard_size) as i32,
+    ));
+}
+
 fn is_reg_saved_in_prologue(call_conv: isa::CallConv, r: RealReg) -> bool {
     match (call_conv, r.class()) {
         (isa::CallConv::Tail, RegClass::Int) => {
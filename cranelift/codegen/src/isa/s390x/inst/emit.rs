@@ -3403,12 +3403,20 @@ impl Inst {
                 let loop_start = sink.get_label();
                 sink.bind_label(loop_start, state.ctrl_plane_mut());
 
-                // aghi %r15, -GUARD_SIZE
-                let inst = Inst::AluRSImm16 {
-                    alu_op: ALUOp::Add64,
-                    rd: writable_stack_reg(),
-                    ri: stack_reg(),
-                    imm: -guard_size,
+                // aghi %r15, -GUARD_SIZE (or agfi for large guard sizes)
+                let inst = match i16::try_from(-guard_size) {
+                    Ok(imm) => Inst::AluRSImm16 {
+                        alu_op: ALUOp::Add64,
+                        rd: writable_stack_reg(),
+                        ri: stack_reg(),
+                        imm,
+                    },
+                    Err(_) => Inst::AluRSImm32 {
+                        alu_op: ALUOp::Add64,
+                        rd: writable_stack_reg(),
+                        ri: stack_reg(),
+                        imm: -guard_size,
+                    },
                 };
                 inst.emit(sink, emit_info, state);
 
@@ -6927,6 +6927,14 @@ fn test_s390x_binemit() {
         "A7FBF0009200F000A716FFFC",
         "0: aghi %r15, -4096 ; mvi 0(%r15), 0 ; brct %r1, 0b",
     ));
+    insns.push((
+        Inst::StackProbeLoop {
+            probe_count: writable_gpr(1),
+            guard_size: 65536,
+        },
+        "C2F8FFFF00009200F000A716FFFB",
+        "0: agfi %r15, -65536 ; mvi 0(%r15), 0 ; brct %r1, 0b",
+    ));
 
     insns.push((
         Inst::FpuMove32 {
@@ -3289,7 +3289,12 @@ impl Inst {
             } => {
                 let probe_count = pretty_print_reg(probe_count.to_reg());
                 let stack_reg = pretty_print_reg(stack_reg());
-                format!("0: aghi {stack_reg}, -{guard_size} ; mvi 0({stack_reg}), 0 ; brct {probe_count}, 0b")
+                let op = if i16::try_from(-guard_size).is_ok() {
+                    "aghi"
+                } else {
+                    "agfi"
+                };
+                format!("0: {op} {stack_reg}, -{guard_size} ; mvi 0({stack_reg}), 0 ; brct {probe_count}, 0b")
             }
             &Inst::Loop { ref body, cond } => {
                 let body = body
@@ -932,6 +932,19 @@ impl generated_code::Context for IsleContext<'_, '_, MInst, S390xBackend> {
         self.lower_ctx.emit(inst.clone());
     }
 
+    fn emit_memcpy(&mut self, dst: Reg, src: Reg, size: u64) {
+        let call_conv = CallConv::for_libcall(
+            self.backend.flags(),
+            self.lower_ctx.abi().call_conv(self.lower_ctx.sigs()),
+        );
+        let insts = S390xMachineDeps::gen_memcpy(call_conv, dst, src, size as usize, |ty| {
+            self.lower_ctx.alloc_tmp(ty).only_reg().unwrap()
+        });
+        for inst in insts {
+            self.lower_ctx.emit(inst);
+        }
+    }
+
     #[inline]
     fn preg_stack(&mut self) -> PReg {
         stack_reg().to_real_reg().unwrap().into()
@@ -599,7 +599,7 @@ impl ABIMachineSpec for X64ABIMachineSpec {
         smallvec![Inst::ret(stack_bytes_to_pop)]
     }
 
-    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32) {
+    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32, _guard_size: u32) {
         insts.push(Inst::imm(
             OperandSize::Size32,
             frame_size as u64,
@@ -47,6 +47,7 @@ pub mod data_value;
 pub mod dbg;
 pub mod dominator_tree;
 pub mod flowgraph;
+pub mod inline;
 pub mod ir;
 pub mod isa;
 pub mod loop_analysis;
@@ -69,13 +70,16 @@ pub use crate::machinst::{
 };
 
 mod alias_analysis;
+mod bounds_check_elimination;
 mod constant_hash;
 mod context;
 mod ctxhash;
+mod dead_store_elimination;
 mod egraph;
 mod inst_predicates;
 mod isle_prelude;
 mod legalizer;
+mod loop_opts;
 mod nan_canonicalization;
 mod opts;
 mod ranges;
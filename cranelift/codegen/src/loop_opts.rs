@@ -0,0 +1,607 @@
+//! Loop optimizations based on induction variables.
+//!
+//! A *basic induction variable* of a loop is a parameter of the loop header
+//! which, on every back edge, is passed its own value plus a constant step.
+//! Given the loop analysis, this pass finds the basic induction variables of
+//! each loop and uses them for the following rewrites:
+//!
+//! - Redundant induction variables, with the same step as another and the
+//!   same initial value on every edge entering the loop, are replaced by
+//!   that other induction variable.
+//!
+//! - Multiplications of an induction variable by a constant are strength
+//!   reduced to a new induction variable: for `v = imul iv, k`, the loop
+//!   header gets a new parameter which is initialized with `init * k` on
+//!   entry to the loop and incremented by `step * k` on every back edge.
+//!
+//! - Optionally, loops consisting of a single block with a constant trip
+//!   count small enough to be determined by simulating the induction
+//!   variable are fully unrolled into straight-line code.
+//!
+//! This runs before the egraph pass, which then cleans up after it: the
+//! constants and increments inserted on loop edges are subject to GVN and
+//! LICM, and the exit conditions and induction variable updates of an
+//! unrolled loop are constant-folded by the ISLE `opts` rules once
+//! `remove_constant_phis` has propagated the initial values.
+
+use crate::cursor::{Cursor, FuncCursor};
+use crate::flowgraph::ControlFlowGraph;
+use crate::ir::condcodes::IntCC;
+use crate::ir::{
+    Block, Function, Inst, InstBuilder, InstructionData, Opcode, Type, Value, ValueDef,
+};
+use crate::loop_analysis::{Loop, LoopAnalysis};
+use crate::{timing, trace};
+use alloc::vec::Vec;
+use rustc_hash::{FxHashMap, FxHashSet};
+use smallvec::SmallVec;
+
+/// The largest trip count of a loop which is fully unrolled.
+const MAX_UNROLL_TRIP_COUNT: u64 = 8;
+
+/// The largest number of instructions a fully unrolled loop may have.
+const MAX_UNROLLED_INSTS: usize = 64;
+
+/// A basic induction variable of a loop.
+#[derive(Clone, Copy, Debug)]
+struct InductionVar {
+    /// The loop header's parameter.
+    param: Value,
+    /// The index of `param` in the loop header's parameters.
+    index: usize,
+    ty: Type,
+    /// The constant added on every back edge, masked to `ty`'s width.
+    step: u64,
+}
+
+/// An edge into a loop header: a branch instruction and the index of the
+/// branch destination which targets the header.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    inst: Inst,
+    dest: usize,
+}
+
+/// The edges into a loop header, and its basic induction variables.
+struct LoopInfo {
+    header: Block,
+    entries: SmallVec<[Edge; 2]>,
+    back_edges: SmallVec<[Edge; 2]>,
+    ivs: SmallVec<[InductionVar; 4]>,
+}
+
+/// Perform loop optimizations on `func`.
+///
+/// Returns whether the control-flow graph was changed, in which case the
+/// control-flow graph and dominator tree must be recomputed.
+pub fn do_loop_opts(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    unroll: bool,
+) -> bool {
+    let _tt = timing::loop_opts();
+
+    let mut cfg_changed = false;
+    for lp in loop_analysis.loops() {
+        let Some(info) = LoopInfo::compute(func, cfg, loop_analysis, lp) else {
+            continue;
+        };
+        let info = remove_redundant_ivs(func, cfg, loop_analysis, lp, info);
+        reduce_multiplications(func, loop_analysis, lp, &info);
+        if unroll && fully_unroll(func, &info) {
+            cfg_changed = true;
+        }
+    }
+    cfg_changed
+}
+
+impl LoopInfo {
+    fn compute(
+        func: &Function,
+        cfg: &ControlFlowGraph,
+        loop_analysis: &LoopAnalysis,
+        lp: Loop,
+    ) -> Option<LoopInfo> {
+        let header = loop_analysis.loop_header(lp);
+        let mut entries = SmallVec::new();
+        let mut back_edges = SmallVec::new();
+        let mut seen = FxHashSet::default();
+        for pred in cfg.pred_iter(header) {
+            if !seen.insert(pred.inst) {
+                continue;
+            }
+            let data = &func.dfg.insts[pred.inst];
+            // Jump tables may be shared, so their edges can't be updated
+            // independently of other branches.
+            if let InstructionData::BranchTable { .. } = data {
+                return None;
+            }
+            let edges = if loop_analysis.is_in_loop(pred.block, lp) {
+                &mut back_edges
+            } else {
+                &mut entries
+            };
+            for (dest, call) in data
+                .branch_destination(&func.dfg.jump_tables)
+                .iter()
+                .enumerate()
+            {
+                if call.block(&func.dfg.value_lists) == header {
+                    edges.push(Edge {
+                        inst: pred.inst,
+                        dest,
+                    });
+                }
+            }
+        }
+        if entries.is_empty() || back_edges.is_empty() {
+            return None;
+        }
+
+        let mut ivs = SmallVec::new();
+        for (index, &param) in func.dfg.block_params(header).iter().enumerate() {
+            let ty = func.dfg.value_type(param);
+            if !ty.is_int() || ty.bits() > 64 {
+                continue;
+            }
+            let mut step = None;
+            for edge in &back_edges {
+                let next = edge_args(func, *edge)[index];
+                match (iv_step(func, param, next), step) {
+                    (Some(s), None) => step = Some(s),
+                    (Some(s), Some(prev)) if mask(ty, s as u64) == mask(ty, prev as u64) => {}
+                    _ => {
+                        step = None;
+                        break;
+                    }
+                }
+            }
+            if let Some(step) = step {
+                ivs.push(InductionVar {
+                    param,
+                    index,
+                    ty,
+                    step: mask(ty, step as u64),
+                });
+            }
+        }
+
+        Some(LoopInfo {
+            header,
+            entries,
+            back_edges,
+            ivs,
+        })
+    }
+}
+
+/// Replace induction variables which always have the same value as another
+/// induction variable of the same loop with that other variable.
+fn remove_redundant_ivs(
+    func: &mut Function,
+    cfg: &ControlFlowGraph,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    info: LoopInfo,
+) -> LoopInfo {
+    let mut redundant: SmallVec<[(InductionVar, Value); 4]> = SmallVec::new();
+    for (i, iv) in info.ivs.iter().enumerate() {
+        let same_as = info.ivs[..i].iter().find(|other| {
+            other.ty == iv.ty
+                && other.step == iv.step
+                && !redundant.iter().any(|(r, _)| r.param == other.param)
+                && info.entries.iter().all(|edge| {
+                    let args = edge_args(func, *edge);
+                    func.dfg.resolve_aliases(args[other.index])
+                        == func.dfg.resolve_aliases(args[iv.index])
+                })
+        });
+        if let Some(other) = same_as {
+            redundant.push((*iv, other.param));
+        }
+    }
+    if redundant.is_empty() {
+        return info;
+    }
+
+    // Remove the rightmost parameters first so that the indices of the
+    // remaining ones stay valid.
+    redundant.sort_by_key(|(iv, _)| core::cmp::Reverse(iv.index));
+    for (iv, replacement) in redundant {
+        trace!(
+            "loop opts: replacing induction variable {} with {}",
+            iv.param,
+            replacement
+        );
+        for edge in info.entries.iter().chain(info.back_edges.iter()) {
+            let dfg = &mut func.dfg;
+            dfg.insts[edge.inst].branch_destination_mut(&mut dfg.jump_tables)[edge.dest]
+                .remove(iv.index, &mut dfg.value_lists);
+        }
+        func.dfg.remove_block_param(iv.param);
+        func.dfg.change_to_alias(iv.param, replacement);
+    }
+
+    // Parameter indices have changed, so recompute everything.
+    LoopInfo::compute(func, cfg, loop_analysis, lp).unwrap()
+}
+
+/// Strength-reduce multiplications of induction variables by constants into
+/// new induction variables.
+fn reduce_multiplications(
+    func: &mut Function,
+    loop_analysis: &LoopAnalysis,
+    lp: Loop,
+    info: &LoopInfo,
+) {
+    if info.ivs.is_empty() {
+        return;
+    }
+
+    let mut candidates = Vec::new();
+    for block in func.layout.blocks() {
+        if !loop_analysis.is_in_loop(block, lp) {
+            continue;
+        }
+        for inst in func.layout.block_insts(block) {
+            let InstructionData::Binary {
+                opcode: Opcode::Imul,
+                args,
+            } = func.dfg.insts[inst]
+            else {
+                continue;
+            };
+            let [a, b] = args.map(|arg| func.dfg.resolve_aliases(arg));
+            let (iv, factor) = match (iv_for(info, a), iv_for(info, b)) {
+                (Some(iv), _) => (iv, iconst_value(func, b)),
+                (_, Some(iv)) => (iv, iconst_value(func, a)),
+                _ => continue,
+            };
+            if let Some(factor) = factor {
+                candidates.push((inst, iv, mask(iv.ty, factor as u64)));
+            }
+        }
+    }
+
+    let mut reduced: FxHashMap<(Value, u64), Value> = FxHashMap::default();
+    for (inst, iv, factor) in candidates {
+        let reduced_iv = *reduced
+            .entry((iv.param, factor))
+            .or_insert_with(|| add_scaled_iv(func, info, iv, factor));
+        trace!(
+            "loop opts: strength-reducing {} to induction variable {}",
+            inst,
+            reduced_iv
+        );
+        let result = func.dfg.first_result(inst);
+        func.dfg.clear_results(inst);
+        func.dfg.change_to_alias(result, reduced_iv);
+        func.layout.remove_inst(inst);
+    }
+}
+
+/// Add a new induction variable to the loop which is always `factor` times
+/// the value of `iv`.
+fn add_scaled_iv(func: &mut Function, info: &LoopInfo, iv: InductionVar, factor: u64) -> Value {
+    let ty = iv.ty;
+    let scaled = func.dfg.append_block_param(info.header, ty);
+    let scaled_step = mask(ty, iv.step.wrapping_mul(factor));
+
+    let mut pos = FuncCursor::new(func);
+    for edge in &info.entries {
+        pos.goto_inst(edge.inst);
+        let init = edge_args(pos.func, *edge)[iv.index];
+        let factor = pos.ins().iconst(ty, factor as i64);
+        let init = pos.ins().imul(init, factor);
+        append_edge_arg(pos.func, *edge, init);
+    }
+    for edge in &info.back_edges {
+        pos.goto_inst(edge.inst);
+        let step = pos.ins().iconst(ty, scaled_step as i64);
+        let next = pos.ins().iadd(scaled, step);
+        append_edge_arg(pos.func, *edge, next);
+    }
+    scaled
+}
+
+/// Fully unroll a loop consisting of only its header, if it has a small
+/// constant trip count.
+///
+/// Returns whether the loop was unrolled.
+fn fully_unroll(func: &mut Function, info: &LoopInfo) -> bool {
+    let header = info.header;
+    let [entry] = info.entries[..] else {
+        return false;
+    };
+    let [back_edge] = info.back_edges[..] else {
+        return false;
+    };
+    let term = back_edge.inst;
+    if func.layout.inst_block(term) != Some(header) {
+        return false;
+    }
+    let InstructionData::Brif {
+        arg: cond,
+        blocks: [then_call, else_call],
+        ..
+    } = func.dfg.insts[term]
+    else {
+        return false;
+    };
+    let back_if_true = back_edge.dest == 0;
+    let exit_call = if back_if_true { else_call } else { then_call };
+    if exit_call.block(&func.dfg.value_lists) == header {
+        return false;
+    }
+
+    let body: SmallVec<[Inst; 16]> = func
+        .layout
+        .block_insts(header)
+        .filter(|&inst| inst != term)
+        .collect();
+    if body
+        .iter()
+        .any(|&inst| func.dfg.user_stack_map_entries(inst).is_some())
+    {
+        return false;
+    }
+
+    let Some(trip_count) = info
+        .ivs
+        .iter()
+        .find_map(|iv| trip_count(func, entry, back_edge, *iv, cond, back_if_true))
+    else {
+        return false;
+    };
+    if (trip_count as usize).saturating_mul(body.len() + 1) > MAX_UNROLLED_INSTS {
+        return false;
+    }
+    trace!(
+        "loop opts: fully unrolling loop at {} with trip count {}",
+        header,
+        trip_count
+    );
+
+    // Emit a copy of the body for every iteration but the last, in front of
+    // the original body, which becomes the last iteration.
+    let params: SmallVec<[Value; 4]> = func.dfg.block_params(header).into();
+    let back_args: SmallVec<[Value; 4]> = edge_args(func, back_edge).into();
+    let mut param_values = params.clone();
+    let mut copies = FxHashSet::default();
+    let before = body.first().copied().unwrap_or(term);
+    for _ in 1..trip_count {
+        let mut values: FxHashMap<Value, Value> =
+            params.iter().copied().zip(param_values).collect();
+        for &inst in &body {
+            let copy = func.dfg.clone_inst(inst);
+            let args: SmallVec<[Value; 4]> = func
+                .dfg
+                .inst_values(copy)
+                .map(|arg| {
+                    let arg = func.dfg.resolve_aliases(arg);
+                    values.get(&arg).copied().unwrap_or(arg)
+                })
+                .collect();
+            func.dfg.overwrite_inst_values(copy, args.into_iter());
+            func.layout.insert_inst(copy, before);
+            let srcloc = func.srcloc(inst);
+            if !srcloc.is_default() {
+                func.set_srcloc(copy, srcloc);
+            }
+            copies.insert(copy);
+            for (&old, &new) in func
+                .dfg
+                .inst_results(inst)
+                .iter()
+                .zip(func.dfg.inst_results(copy))
+            {
+                values.insert(old, new);
+            }
+        }
+        param_values = back_args
+            .iter()
+            .map(|&arg| {
+                let arg = func.dfg.resolve_aliases(arg);
+                values.get(&arg).copied().unwrap_or(arg)
+            })
+            .collect();
+    }
+
+    // All other uses of the header's parameters see the values of the last
+    // iteration.
+    if trip_count > 1 {
+        let last: FxHashMap<Value, Value> = params.iter().copied().zip(param_values).collect();
+        let mut pos = FuncCursor::new(func);
+        while let Some(_block) = pos.next_block() {
+            while let Some(inst) = pos.next_inst() {
+                if copies.contains(&inst) {
+                    continue;
+                }
+                let dfg = &mut pos.func.dfg;
+                if dfg
+                    .inst_values(inst)
+                    .any(|v| last.contains_key(&dfg.resolve_aliases(v)))
+                {
+                    let args: SmallVec<[Value; 4]> = dfg
+                        .inst_values(inst)
+                        .map(|v| {
+                            let v = dfg.resolve_aliases(v);
+                            last.get(&v).copied().unwrap_or(v)
+                        })
+                        .collect();
+                    dfg.overwrite_inst_values(inst, args.into_iter());
+                }
+            }
+        }
+    }
+
+    // The last iteration always leaves the loop.
+    let InstructionData::Brif {
+        blocks: [then_call, else_call],
+        ..
+    } = func.dfg.insts[term]
+    else {
+        unreachable!()
+    };
+    let exit_call = if back_if_true { else_call } else { then_call };
+    let exit = exit_call.block(&func.dfg.value_lists);
+    let exit_args: SmallVec<[Value; 4]> = exit_call.args_slice(&func.dfg.value_lists).into();
+    func.dfg.replace(term).jump(exit, &exit_args);
+    true
+}
+
+/// Determine the number of times the body of a single-block loop is
+/// executed, by simulating the induction variable `iv` from its constant
+/// initial value until the loop's exit condition holds.
+fn trip_count(
+    func: &Function,
+    entry: Edge,
+    back_edge: Edge,
+    iv: InductionVar,
+    cond: Value,
+    back_if_true: bool,
+) -> Option<u64> {
+    let init = iconst_value(func, edge_args(func, entry)[iv.index])?;
+    let next = func
+        .dfg
+        .resolve_aliases(edge_args(func, back_edge)[iv.index]);
+    let mut current = mask(iv.ty, init as u64);
+    for trips in 1..=MAX_UNROLL_TRIP_COUNT {
+        let eval = |value: Value| -> Option<u64> {
+            let value = func.dfg.resolve_aliases(value);
+            if value == iv.param {
+                Some(current)
+            } else if value == next {
+                Some(mask(iv.ty, current.wrapping_add(iv.step)))
+            } else {
+                let ty = func.dfg.value_type(value);
+                iconst_value(func, value).map(|c| mask(ty, c as u64))
+            }
+        };
+        if eval_condition(func, cond, &eval)? != back_if_true {
+            return Some(trips);
+        }
+        current = mask(iv.ty, current.wrapping_add(iv.step));
+    }
+    None
+}
+
+/// Evaluate whether the branch condition `cond` is true, using `eval` for
+/// the values it depends on.
+fn eval_condition(
+    func: &Function,
+    cond: Value,
+    eval: &dyn Fn(Value) -> Option<u64>,
+) -> Option<bool> {
+    let cond = func.dfg.resolve_aliases(cond);
+    if let ValueDef::Result(inst, 0) = func.dfg.value_def(cond) {
+        match func.dfg.insts[inst] {
+            InstructionData::IntCompare { cond: cc, args, .. } => {
+                let ty = func.dfg.value_type(args[0]);
+                return Some(eval_icmp(cc, ty, eval(args[0])?, eval(args[1])?));
+            }
+            InstructionData::IntCompareImm {
+                cond: cc, arg, imm, ..
+            } => {
+                let ty = func.dfg.value_type(arg);
+                return Some(eval_icmp(cc, ty, eval(arg)?, mask(ty, imm.bits() as u64)));
+            }
+            InstructionData::Unary {
+                opcode: Opcode::Uextend | Opcode::Sextend,
+                arg,
+            } => return eval_condition(func, arg, eval),
+            _ => {}
+        }
+    }
+    eval(cond).map(|value| value != 0)
+}
+
+fn eval_icmp(cc: IntCC, ty: Type, a: u64, b: u64) -> bool {
+    let shift = 64 - ty.bits();
+    let signed = |x: u64| ((x << shift) as i64) >> shift;
+    match cc {
+        IntCC::Equal => a == b,
+        IntCC::NotEqual => a != b,
+        IntCC::SignedLessThan => signed(a) < signed(b),
+        IntCC::SignedGreaterThanOrEqual => signed(a) >= signed(b),
+        IntCC::SignedGreaterThan => signed(a) > signed(b),
+        IntCC::SignedLessThanOrEqual => signed(a) <= signed(b),
+        IntCC::UnsignedLessThan => a < b,
+        IntCC::UnsignedGreaterThanOrEqual => a >= b,
+        IntCC::UnsignedGreaterThan => a > b,
+        IntCC::UnsignedLessThanOrEqual => a <= b,
+    }
+}
+
+/// If `next` is `param` plus a constant, get that constant.
+fn iv_step(func: &Function, param: Value, next: Value) -> Option<i64> {
+    let next = func.dfg.resolve_aliases(next);
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(next) else {
+        return None;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::Binary {
+            opcode: Opcode::Iadd,
+            args,
+        } => {
+            let [a, b] = args.map(|arg| func.dfg.resolve_aliases(arg));
+            if a == param {
+                iconst_value(func, b)
+            } else if b == param {
+                iconst_value(func, a)
+            } else {
+                None
+            }
+        }
+        InstructionData::Binary {
+            opcode: Opcode::Isub,
+            args,
+        } if func.dfg.resolve_aliases(args[0]) == param => {
+            iconst_value(func, args[1]).map(i64::wrapping_neg)
+        }
+        InstructionData::BinaryImm64 {
+            opcode: Opcode::IaddImm,
+            arg,
+            imm,
+        } if func.dfg.resolve_aliases(arg) == param => Some(imm.bits()),
+        _ => None,
+    }
+}
+
+fn iv_for(info: &LoopInfo, value: Value) -> Option<InductionVar> {
+    info.ivs.iter().find(|iv| iv.param == value).copied()
+}
+
+fn iconst_value(func: &Function, value: Value) -> Option<i64> {
+    let value = func.dfg.resolve_aliases(value);
+    let ValueDef::Result(inst, 0) = func.dfg.value_def(value) else {
+        return None;
+    };
+    match func.dfg.insts[inst] {
+        InstructionData::UnaryImm {
+            opcode: Opcode::Iconst,
+            imm,
+        } => Some(imm.bits()),
+        _ => None,
+    }
+}
+
+/// Mask `value` to the width of the integer type `ty`.
+fn mask(ty: Type, value: u64) -> u64 {
+    match ty.bits() {
+        64 => value,
+        bits => value & ((1 << bits) - 1),
+    }
+}
+
+fn edge_args(func: &Function, edge: Edge) -> &[Value] {
+    func.dfg.insts[edge.inst].branch_destination(&func.dfg.jump_tables)[edge.dest]
+        .args_slice(&func.dfg.value_lists)
+}
+
+fn append_edge_arg(func: &mut Function, edge: Edge, arg: Value) {
+    let dfg = &mut func.dfg;
+    dfg.insts[edge.inst].branch_destination_mut(&mut dfg.jump_tables)[edge.dest]
+        .append_argument(arg, &mut dfg.value_lists);
+}
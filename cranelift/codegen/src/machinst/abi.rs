@@ -508,8 +508,9 @@ pub trait ABIMachineSpec {
         frame_layout: &FrameLayout,
     ) -> SmallInstVec<Self::I>;
 
-    /// Generate a probestack call.
-    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32);
+    /// Generate a probestack call. `guard_size` is the size of the stack
+    /// guard region, which the probes must not skip over.
+    fn gen_probestack(insts: &mut SmallInstVec<Self::I>, frame_size: u32, guard_size: u32);
 
     /// Generate a inline stack probe.
     fn gen_inline_probestack(
@@ -1818,7 +1819,7 @@ impl<M: ABIMachineSpec> Callee<M> {
                     ),
                     ProbestackStrategy::Outline => {
                         if total_stacksize >= guard_size {
-                            M::gen_probestack(&mut insts, total_stacksize);
+                            M::gen_probestack(&mut insts, total_stacksize, guard_size);
                         }
                     }
                 }
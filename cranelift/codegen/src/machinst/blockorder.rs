@@ -59,14 +59,26 @@
 //! Furthermore, the [MachBuffer] machine-code sink performs final peephole-like
 //! branch editing that in practice elides empty blocks and simplifies some of
 //! the other redundancies that this scheme produces.
+//!
+//! When the function carries an execution profile (see [ProfileData]), the DFS
+//! visits the hottest successor of each block last, so that it is placed
+//! directly after its predecessor in the reverse postorder. The [MachBuffer]
+//! then turns that edge into a fallthrough, inverting the branch condition if
+//! needed. Blocks that the profile shows to be rarely executed are marked cold
+//! and so are moved to the end of the function at emission time, just like
+//! blocks that are explicitly marked cold in the layout.
 
 use crate::dominator_tree::DominatorTree;
-use crate::entity::SecondaryMap;
+use crate::entity::{EntitySet, SecondaryMap};
 use crate::inst_predicates::visit_block_succs;
-use crate::ir::{Block, Function, Inst, Opcode};
+use crate::ir::{Block, Function, Inst, Opcode, ProfileData};
 use crate::{machinst::*, trace};
 use rustc_hash::{FxHashMap, FxHashSet};
 
+/// A block (or edge) whose profiled execution count is less than the entry block's count divided
+/// by this ratio is considered cold.
+const PROFILE_COLD_RATIO: u64 = 1000;
+
 /// Mapping from CLIF BBs to VCode BBs.
 #[derive(Debug)]
 pub struct BlockLoweringOrder {
@@ -187,12 +199,22 @@ impl BlockLoweringOrder {
             block_succ_range[block] = start..end;
         }
 
-        // Step 2: walk the postorder from the domtree in reverse to produce our desired node
-        // lowering order, identifying critical edges to split along the way.
+        // Step 2: walk the postorder in reverse to produce our desired node lowering order,
+        // identifying critical edges to split along the way. Without a profile this is the
+        // postorder computed by the domtree; with one, we compute our own that chases hot
+        // successors.
+
+        let profile = BlockProfile::new(f);
+        let profile_postorder = profile
+            .as_ref()
+            .map(|profile| profile.postorder(f, &block_succs, &block_succ_range));
+        let postorder = profile_postorder
+            .as_deref()
+            .unwrap_or_else(|| domtree.cfg_postorder());
 
         let mut lowered_order = Vec::new();
 
-        for &block in domtree.cfg_rpo() {
+        for &block in postorder.iter().rev() {
             lowered_order.push(LoweredBlock::Orig { block });
 
             if block_out_count[block] > 1 {
@@ -246,7 +268,9 @@ impl BlockLoweringOrder {
                         lowered_succ_indices
                             .extend(block_succs[range].iter().map(|lb| lb_to_bindex[lb]));
 
-                        if f.layout.is_cold(block) {
+                        if f.layout.is_cold(block)
+                            || profile.as_ref().is_some_and(|p| p.is_cold_block(block))
+                        {
                             cold_blocks.insert(bindex);
                         }
 
@@ -264,14 +288,16 @@ impl BlockLoweringOrder {
 
                     // Critical edges won't have successor information in block_succ_range, but
                     // they only have a single known successor to record anyway.
-                    &LoweredBlock::CriticalEdge { succ, .. } => {
+                    &LoweredBlock::CriticalEdge { pred, succ, .. } => {
                         let succ_index = lb_to_bindex[&LoweredBlock::Orig { block: succ }];
                         lowered_succ_indices.push(succ_index);
 
                         // Edges inherit indirect branch and cold block metadata from their
                         // successor.
 
-                        if f.layout.is_cold(succ) {
+                        if f.layout.is_cold(succ)
+                            || profile.as_ref().is_some_and(|p| p.is_cold_edge(pred, succ))
+                        {
                             cold_blocks.insert(bindex);
                         }
 
@@ -321,6 +347,117 @@ impl BlockLoweringOrder {
     }
 }
 
+/// Execution counts for a function's blocks and edges, derived from its [ProfileData].
+struct BlockProfile<'a> {
+    /// The profile attached to the function.
+    data: &'a ProfileData,
+    /// The function's entry block, which is never considered cold.
+    entry: Option<Block>,
+    /// The count of every block, where known.
+    counts: SecondaryMap<Block, Option<u64>>,
+    /// The count that blocks and edges are compared against to decide whether they are cold.
+    reference: u64,
+}
+
+impl<'a> BlockProfile<'a> {
+    /// Resolve the profile attached to `f`, if there is one.
+    fn new(f: &'a Function) -> Option<Self> {
+        if f.profile.is_empty() {
+            return None;
+        }
+        let entry = f.layout.entry_block();
+        let counts = f.profile.resolved_block_counts();
+        // Prefer the entry block's count, so that "cold" means "rarely executed per call", but
+        // fall back to the hottest block if the entry wasn't profiled.
+        let reference = entry
+            .and_then(|entry| counts[entry])
+            .unwrap_or_else(|| counts.values().filter_map(|c| *c).max().unwrap_or(0));
+        Some(BlockProfile {
+            data: &f.profile,
+            entry,
+            counts,
+            reference,
+        })
+    }
+
+    /// Is `count` so low compared to the reference count that it is cold?
+    fn is_cold_count(&self, count: u64) -> bool {
+        count.saturating_mul(PROFILE_COLD_RATIO) < self.reference
+    }
+
+    /// Is `block` known to be rarely executed?
+    fn is_cold_block(&self, block: Block) -> bool {
+        Some(block) != self.entry && self.counts[block].is_some_and(|c| self.is_cold_count(c))
+    }
+
+    /// Is the edge from `pred` to `succ` known to be rarely taken?
+    fn is_cold_edge(&self, pred: Block, succ: Block) -> bool {
+        self.is_cold_block(succ)
+            || self
+                .data
+                .edge_count(pred, succ)
+                .is_some_and(|c| self.is_cold_count(c))
+    }
+
+    /// The weight used to decide which successor of `pred` to place right after it: the edge
+    /// count if known, and otherwise the successor's block count.
+    fn edge_weight(&self, pred: Block, succ: Block) -> u64 {
+        self.data
+            .edge_count(pred, succ)
+            .or(self.counts[succ])
+            .unwrap_or(0)
+    }
+
+    /// Compute a postorder of the blocks reachable from the entry block, like the one the
+    /// dominator tree computes, but visiting the hottest successor of every block last so that it
+    /// directly follows the block in reverse postorder.
+    fn postorder(
+        &self,
+        f: &Function,
+        block_succs: &[LoweredBlock],
+        block_succ_range: &SecondaryMap<Block, std::ops::Range<usize>>,
+    ) -> Vec<Block> {
+        enum Event {
+            Enter(Block),
+            Exit(Block),
+        }
+
+        let mut visited = EntitySet::with_capacity(f.dfg.num_blocks());
+        let mut postorder = Vec::with_capacity(f.dfg.num_blocks());
+        let mut worklist: Vec<Event> = self.entry.into_iter().map(Event::Enter).collect();
+        let mut succs = SmallVec::<[(u64, Block); 8]>::new();
+
+        while let Some(event) = worklist.pop() {
+            match event {
+                Event::Enter(block) => {
+                    if !visited.insert(block) {
+                        continue;
+                    }
+                    worklist.push(Event::Exit(block));
+
+                    // Push the successors in reverse, like the dominator tree does, but stably
+                    // move the hottest ones to the bottom of the stack so that they are visited
+                    // last.
+                    succs.clear();
+                    succs.extend(
+                        block_succs[block_succ_range[block].clone()]
+                            .iter()
+                            .rev()
+                            .map(|lb| lb.orig_block().unwrap())
+                            .filter(|&succ| !visited.contains(succ))
+                            .map(|succ| (self.edge_weight(block, succ), succ)),
+                    );
+                    succs.sort_by(|a, b| b.0.cmp(&a.0));
+                    worklist.extend(succs.iter().map(|&(_, succ)| Event::Enter(succ)));
+                }
+                Event::Exit(block) => postorder.push(block),
+            }
+        }
+
+        postorder
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -332,6 +469,15 @@ mod test {
     use crate::isa::CallConv;
 
     fn build_test_func(n_blocks: usize, edges: &[(usize, usize)]) -> BlockLoweringOrder {
+        build_test_func_with_profile(n_blocks, edges, &[], &[])
+    }
+
+    fn build_test_func_with_profile(
+        n_blocks: usize,
+        edges: &[(usize, usize)],
+        block_counts: &[(usize, u64)],
+        edge_counts: &[(usize, usize, u64)],
+    ) -> BlockLoweringOrder {
         assert!(n_blocks > 0);
 
         let name = UserFuncName::testcase("test0");
@@ -370,6 +516,13 @@ mod test {
             }
         }
 
+        for &(block, count) in block_counts {
+            func.profile.set_block_count(blocks[block], count);
+        }
+        for &(from, to, count) in edge_counts {
+            func.profile.set_edge_count(blocks[from], blocks[to], count);
+        }
+
         let mut cfg = ControlFlowGraph::new();
         cfg.compute(&func);
         let dom_tree = DominatorTree::with_function(&func, &cfg);
@@ -462,4 +615,59 @@ mod test {
         assert!(order.lowered_order[8].in_edge().is_none());
         assert!(order.lowered_order[8].out_edge().is_none());
     }
+
+    fn orig_blocks(order: &BlockLoweringOrder) -> Vec<Option<u32>> {
+        order
+            .lowered_order
+            .iter()
+            .map(|lb| lb.orig_block().map(|b| b.as_u32()))
+            .collect()
+    }
+
+    #[test]
+    fn test_blockorder_profile_diamond() {
+        let edges = [(0, 1), (0, 2), (1, 3), (2, 3)];
+
+        // Without a profile, the second successor of block 0 directly follows it.
+        let order = build_test_func(4, &edges);
+        assert_eq!(orig_blocks(&order), [Some(0), Some(2), Some(1), Some(3)]);
+        assert!((0..4).all(|i| !order.is_cold(BlockIndex::new(i))));
+
+        // With a profile showing that block 1 is hot and block 2 is never executed, block 1
+        // follows block 0 instead and block 2 is cold.
+        let order =
+            build_test_func_with_profile(4, &edges, &[(0, 100), (1, 100), (2, 0), (3, 100)], &[]);
+        assert_eq!(orig_blocks(&order), [Some(0), Some(1), Some(2), Some(3)]);
+        assert!(!order.is_cold(BlockIndex::new(0)));
+        assert!(!order.is_cold(BlockIndex::new(1)));
+        assert!(order.is_cold(BlockIndex::new(2)));
+        assert!(!order.is_cold(BlockIndex::new(3)));
+    }
+
+    #[test]
+    fn test_blockorder_profile_cold_edge() {
+        //   0
+        //   | \
+        //   1  |
+        //   | /
+        //   2
+        //
+        // (0 -> 2 is a critical edge, and the profile says that it is never taken)
+        let order = build_test_func_with_profile(
+            3,
+            &[(0, 1), (0, 2), (1, 2)],
+            &[(0, 100)],
+            &[(0, 1, 100), (0, 2, 0), (1, 2, 100)],
+        );
+
+        assert_eq!(orig_blocks(&order), [Some(0), None, Some(1), Some(2)]);
+        assert_eq!(order.lowered_order[1].in_edge().unwrap().as_u32(), 0);
+        assert_eq!(order.lowered_order[1].out_edge().unwrap().as_u32(), 2);
+
+        // Only the edge block is cold; block 2 is still reached through block 1.
+        assert!(!order.is_cold(BlockIndex::new(0)));
+        assert!(order.is_cold(BlockIndex::new(1)));
+        assert!(!order.is_cold(BlockIndex::new(2)));
+        assert!(!order.is_cold(BlockIndex::new(3)));
+    }
 }
@@ -495,6 +495,13 @@ macro_rules! isle_lower_prelude_methods {
             }
         }
 
+        fn abi_arg_struct(&mut self, arg: &ABIArg) -> Option<(i64, u64)> {
+            match arg {
+                &ABIArg::StructArg { offset, size, .. } => Some((offset, size)),
+                _ => None,
+            }
+        }
+
         fn abi_unwrap_ret_area_ptr(&mut self) -> Reg {
             self.lower_ctx.abi().ret_area_ptr().unwrap()
         }
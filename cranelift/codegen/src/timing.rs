@@ -60,10 +60,14 @@ define_passes! {
     loop_analysis: "Loop analysis",
     preopt: "Pre-legalization rewriting",
     egraph: "Egraph based optimizations",
+    dead_store_elimination: "Dead store elimination",
+    bounds_check_elimination: "Bounds check elimination",
     gvn: "Global value numbering",
     licm: "Loop invariant code motion",
+    loop_opts: "Loop optimizations",
     unreachable_code: "Remove unreachable blocks",
     remove_constant_phis: "Remove constant phi-nodes",
+    inline: "Inline function calls",
 
     vcode_lower: "VCode lowering",
     vcode_emit: "VCode emission",
@@ -0,0 +1,108 @@
+//! Test command for testing the inliner.
+//!
+//! The `inline` test command inlines, within each function, every direct call
+//! to another function defined in the same test file. The callee is resolved
+//! by name, so `fn0 = %callee(i32) -> i32` refers to `function %callee`.
+//!
+//! The resulting function is verified and then sent to `filecheck`.
+
+use crate::runone::FileUpdate;
+use crate::subtest::{run_filecheck, Context, SubTest};
+use anyhow::Context as _;
+use cranelift_codegen::inline::{Inline, InlineCommand};
+use cranelift_codegen::ir::{ExternalName, FuncRef, Function, Inst, UserFuncName, Value};
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::print_errors::pretty_verifier_error;
+use cranelift_codegen::settings::Flags;
+use cranelift_codegen::verify_function;
+use cranelift_reader::{TestCommand, TestFile};
+use std::borrow::Cow;
+
+struct TestInline;
+
+pub fn subtest(parsed: &TestCommand) -> anyhow::Result<Box<dyn SubTest>> {
+    assert_eq!(parsed.command, "inline");
+    if !parsed.options.is_empty() {
+        anyhow::bail!("No options allowed on {}", parsed);
+    }
+    Ok(Box::new(TestInline))
+}
+
+/// Inlines calls to any of the functions of a test file.
+struct Inliner<'a> {
+    functions: &'a [(Function, cranelift_reader::Details<'a>)],
+}
+
+impl Inline for Inliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &Function,
+        _call_inst: Inst,
+        callee: FuncRef,
+        _call_args: &[Value],
+    ) -> InlineCommand<'_> {
+        let callee = match &caller.dfg.ext_funcs[callee].name {
+            ExternalName::TestCase(name) => UserFuncName::Testcase(name.clone()),
+            ExternalName::User(name) => {
+                UserFuncName::User(caller.params.user_named_funcs()[*name].clone())
+            }
+            _ => return InlineCommand::KeepCall,
+        };
+        match self.functions.iter().find(|(f, _)| f.name == callee) {
+            Some((f, _)) => InlineCommand::Inline(Cow::Borrowed(f)),
+            None => InlineCommand::KeepCall,
+        }
+    }
+}
+
+impl SubTest for TestInline {
+    fn name(&self) -> &'static str {
+        "inline"
+    }
+
+    fn is_mutating(&self) -> bool {
+        true
+    }
+
+    fn run_target<'a>(
+        &self,
+        testfile: &TestFile,
+        file_update: &mut FileUpdate,
+        file_path: &'a str,
+        flags: &'a Flags,
+        isa: Option<&'a dyn TargetIsa>,
+    ) -> anyhow::Result<()> {
+        for (func, details) in &testfile.functions {
+            let context = Context {
+                preamble_comments: &testfile.preamble_comments,
+                details,
+                flags,
+                isa,
+                file_path: file_path.as_ref(),
+                file_update,
+            };
+
+            let mut comp_ctx = cranelift_codegen::Context::for_function(func.clone());
+            comp_ctx
+                .inline(Inliner {
+                    functions: &testfile.functions,
+                })
+                .map_err(|e| crate::pretty_anyhow_error(&comp_ctx.func, e))
+                .context(self.name())?;
+            verify_function(&comp_ctx.func, context.flags_or_isa())
+                .map_err(|errors| {
+                    anyhow::anyhow!("{}", pretty_verifier_error(&comp_ctx.func, None, errors))
+                })
+                .context(self.name())?;
+
+            let text = comp_ctx.func.display().to_string();
+            run_filecheck(&text, &context).context(self.name())?;
+        }
+
+        Ok(())
+    }
+
+    fn run(&self, _func: Cow<Function>, _context: &Context) -> anyhow::Result<()> {
+        unreachable!("inlining needs the whole test file; see `run_target`")
+    }
+}
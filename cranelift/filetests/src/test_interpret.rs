@@ -82,7 +82,7 @@ fn run_test(func_store: &FunctionStore, func: &Function, details: &Details) -> a
             trace!("Parsed run command: {}", command);
 
             command
-                .run(|func_name, run_args| {
+                .run(|_, run_args| {
                     // Rebuild the interpreter state on every run to ensure that we don't accidentally depend on
                     // some leftover state
                     let state = InterpreterState::default()
@@ -103,9 +103,9 @@ fn run_test(func_store: &FunctionStore, func: &Function, details: &Details) -> a
                     let mut args = Vec::with_capacity(run_args.len());
                     args.extend_from_slice(run_args);
 
-                    // Because we have stored function names with a leading %, we need to re-add it.
-                    let func_name = &format!("%{func_name}");
-                    match Interpreter::new(state).call_by_name(func_name, &args) {
+                    // Like `test run`, always invoke the function the run command is attached to,
+                    // regardless of the name written in the command.
+                    match Interpreter::new(state).call_by_name(&func.name.to_string(), &args) {
                         Ok(ControlFlow::Return(results)) => Ok(results.to_vec()),
                         Ok(e) => {
                             panic!("Unexpected returned control flow: {e:?}")
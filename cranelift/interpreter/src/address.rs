@@ -15,20 +15,20 @@
 //! are the "entry" field, the amount of "entry" bits depends on the size of the address and
 //! the "region" of the address. The remaining bits belong to the "offset" field
 //!
-//! An example address could be a 32 bit address, in the `function` region, which has 1 "entry" bit
-//! this address would have 32 - 1 - 2 = 29 offset bits.
+//! An example address could be a 32 bit address, in the `function` region, which has 2 "entry" bits
+//! this address would have 32 - 2 - 2 = 28 offset bits.
 //!
-//! The only exception to this is the "stack" region, where, because we only have a single "stack"
-//! we have 0 "entry" bits, and thus is all offset.
+//! In the "stack" region the "entry" is the identifier of the stack the address points into; the
+//! main stack is entry 0, additional stacks can be created for use with `stack_switch`.
 //!
 //! | address size | address kind | region value (2 bits) | entry bits (#) | offset bits (#) |
 //! |--------------|--------------|-----------------------|----------------|-----------------|
-//! | 32           | Stack        | 0b00                  | 0              | 30              |
-//! | 32           | Function     | 0b01                  | 1              | 29              |
+//! | 32           | Stack        | 0b00                  | 4              | 26              |
+//! | 32           | Function     | 0b01                  | 2              | 28              |
 //! | 32           | Table        | 0b10                  | 5              | 25              |
 //! | 32           | GlobalValue  | 0b11                  | 6              | 24              |
-//! | 64           | Stack        | 0b00                  | 0              | 62              |
-//! | 64           | Function     | 0b01                  | 1              | 61              |
+//! | 64           | Stack        | 0b00                  | 10             | 52              |
+//! | 64           | Function     | 0b01                  | 2              | 60              |
 //! | 64           | Table        | 0b10                  | 10             | 52              |
 //! | 64           | GlobalValue  | 0b11                  | 12             | 50              |
 
@@ -139,12 +139,13 @@ impl Address {
 
     fn entry_bits(size: AddressSize, region: AddressRegion) -> u64 {
         match (size, region) {
-            // We only have one stack, so the whole address is offset
-            (_, AddressRegion::Stack) => 0,
+            // The entry selects one of the stacks created for stack switching.
+            (AddressSize::_32, AddressRegion::Stack) => 4,
+            (AddressSize::_64, AddressRegion::Stack) => 10,
 
-            // We have two function "entries", one for libcalls, and
-            // another for user functions.
-            (_, AddressRegion::Function) => 1,
+            // We have three function "entries", one for libcalls, one for user
+            // functions and another for external functions.
+            (_, AddressRegion::Function) => 2,
 
             (AddressSize::_32, AddressRegion::Table) => 5,
             (AddressSize::_32, AddressRegion::GlobalValue) => 6,
@@ -227,6 +228,7 @@ impl TryFrom<u64> for Address {
 pub enum AddressFunctionEntry {
     UserFunction = 0,
     LibCall,
+    ExternalFunction,
 }
 
 impl From<u64> for AddressFunctionEntry {
@@ -234,6 +236,7 @@ impl From<u64> for AddressFunctionEntry {
         match bits {
             0 => AddressFunctionEntry::UserFunction,
             1 => AddressFunctionEntry::LibCall,
+            2 => AddressFunctionEntry::ExternalFunction,
             _ => unreachable!(),
         }
     }
@@ -263,11 +266,13 @@ mod tests {
             (AddressSize::_32, AddressRegion::Stack, 0, 0),
             (AddressSize::_32, AddressRegion::Stack, 0, 1),
             (AddressSize::_32, AddressRegion::Stack, 0, 1024),
-            (AddressSize::_32, AddressRegion::Stack, 0, 0x3FFF_FFFF),
+            (AddressSize::_32, AddressRegion::Stack, 0, 0x3FF_FFFF),
+            (AddressSize::_32, AddressRegion::Stack, 15, 0x3FF_FFFF),
             (AddressSize::_32, AddressRegion::Function, 0, 0),
             (AddressSize::_32, AddressRegion::Function, 1, 1),
             (AddressSize::_32, AddressRegion::Function, 0, 1024),
             (AddressSize::_32, AddressRegion::Function, 1, 0x0FFF_FFFF),
+            (AddressSize::_32, AddressRegion::Function, 2, 0x0FFF_FFFF),
             (AddressSize::_32, AddressRegion::Table, 0, 0),
             (AddressSize::_32, AddressRegion::Table, 1, 1),
             (AddressSize::_32, AddressRegion::Table, 31, 0x1FF_FFFF),
@@ -280,7 +285,13 @@ mod tests {
                 AddressSize::_64,
                 AddressRegion::Stack,
                 0,
-                0x3FFFFFFF_FFFFFFFF,
+                0xF_FFFF_FFFF_FFFF,
+            ),
+            (
+                AddressSize::_64,
+                AddressRegion::Stack,
+                1023,
+                0xF_FFFF_FFFF_FFFF,
             ),
             (AddressSize::_64, AddressRegion::Function, 0, 0),
             (AddressSize::_64, AddressRegion::Function, 1, 1),
@@ -1,7 +1,11 @@
 //! Implements the function environment (e.g. a name-to-function mapping) for interpretation.
-use cranelift_codegen::ir::{FuncRef, Function};
+use crate::step::CraneliftTrap;
+use cranelift_codegen::data_value::DataValue;
+use cranelift_codegen::ir::{FuncRef, Function, Signature};
 use cranelift_entity::{entity_impl, PrimaryMap};
+use smallvec::SmallVec;
 use std::collections::HashMap;
+use std::rc::Rc;
 
 /// A function store contains all of the functions that are accessible to an interpreter.
 #[derive(Default, Clone)]
@@ -59,6 +63,87 @@ impl<'a> FunctionStore<'a> {
     }
 }
 
+/// The values passed to and returned from an [ExternalFunction].
+pub type ExternalFunctionValues = SmallVec<[DataValue; 1]>;
+
+/// A function implemented natively by the embedder of the interpreter rather than in CLIF; calls to
+/// it are not interpreted but forwarded to its handler.
+#[derive(Clone)]
+pub struct ExternalFunction<'a> {
+    signature: Signature,
+    handler:
+        Rc<dyn Fn(ExternalFunctionValues) -> Result<ExternalFunctionValues, CraneliftTrap> + 'a>,
+}
+
+impl<'a> ExternalFunction<'a> {
+    /// The signature the function is called with.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Run the native implementation of the function.
+    pub fn call(
+        &self,
+        args: ExternalFunctionValues,
+    ) -> Result<ExternalFunctionValues, CraneliftTrap> {
+        (self.handler)(args)
+    }
+}
+
+/// A registry of the [ExternalFunction]s available to an interpreter. Calls to functions that are
+/// not in the [FunctionStore] (including calls to well-known symbols) are resolved here by name.
+#[derive(Default, Clone)]
+pub struct ExternalFunctionRegistry<'a> {
+    functions: PrimaryMap<ExternalFuncIndex, ExternalFunction<'a>>,
+    function_names: HashMap<String, ExternalFuncIndex>,
+}
+
+/// An opaque reference to an [ExternalFunction] stored in the [ExternalFunctionRegistry].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExternalFuncIndex(u32);
+entity_impl!(ExternalFuncIndex, "extfn");
+
+impl<'a> ExternalFunctionRegistry<'a> {
+    /// Register a native `handler` for the function called `name`, e.g. `%puts` or
+    /// `%ElfGlobalOffsetTable`.
+    pub fn add<F>(&mut self, name: String, signature: Signature, handler: F)
+    where
+        F: Fn(ExternalFunctionValues) -> Result<ExternalFunctionValues, CraneliftTrap> + 'a,
+    {
+        assert!(!self.function_names.contains_key(&name));
+        let index = self.functions.push(ExternalFunction {
+            signature,
+            handler: Rc::new(handler),
+        });
+        self.function_names.insert(name, index);
+    }
+
+    /// Retrieve the index of an external function by its `name`.
+    pub fn index_of(&self, name: &str) -> Option<ExternalFuncIndex> {
+        self.function_names.get(name).cloned()
+    }
+
+    /// Retrieve an external function by its index in the registry.
+    pub fn get_by_index(&self, index: ExternalFuncIndex) -> Option<&ExternalFunction<'a>> {
+        self.functions.get(index)
+    }
+
+    /// Retrieve an external function by its name.
+    pub fn get_by_name(&self, name: &str) -> Option<&ExternalFunction<'a>> {
+        let index = self.index_of(name)?;
+        self.get_by_index(index)
+    }
+
+    /// Retrieve an external function from a [FuncRef] within a [Function].
+    pub fn get_from_func_ref(
+        &self,
+        func_ref: FuncRef,
+        function: &Function,
+    ) -> Option<&ExternalFunction<'a>> {
+        self.get_by_name(&get_function_name(func_ref, function))
+    }
+}
+
 /// Retrieve a function name from a [FuncRef] within a [Function]. TODO this should be optimized, if possible, as
 /// currently it retrieves the function name as a string and performs string matching.
 fn get_function_name(func_ref: FuncRef, function: &Function) -> String {
@@ -103,4 +188,19 @@ mod tests {
         let env: FunctionStore = func.into();
         assert_eq!(env.index_of("%test"), Some(FuncIndex::from_u32(0)));
     }
+
+    #[test]
+    fn external_functions() {
+        let mut registry = ExternalFunctionRegistry::default();
+        registry.add(
+            "%double".to_string(),
+            Signature::new(CallConv::SystemV),
+            |args| Ok(args.iter().chain(args.iter()).cloned().collect()),
+        );
+
+        let double = registry.get_by_name("%double").unwrap();
+        let results = double.call(smallvec::smallvec![DataValue::I8(1)]).unwrap();
+        assert_eq!(results.as_slice(), &[DataValue::I8(1), DataValue::I8(1)]);
+        assert!(registry.get_by_name("%half").is_none());
+    }
 }
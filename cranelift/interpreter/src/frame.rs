@@ -1,7 +1,7 @@
 //! Implements a call frame (activation record) for the Cranelift interpreter.
 
 use cranelift_codegen::data_value::DataValue;
-use cranelift_codegen::ir::{types, Function, Value as ValueRef};
+use cranelift_codegen::ir::{types, Function, Inst, Value as ValueRef};
 use cranelift_entity::EntityRef;
 use log::trace;
 
@@ -16,6 +16,9 @@ pub struct Frame<'a> {
     /// The current mapping of SSA value-references to their actual values. For efficiency, each SSA value is used as an
     /// index into the Vec, meaning some slots may be unused.
     registers: Entries,
+    /// The instruction this frame is executing; while a call is in progress this is the call
+    /// instruction that receives the returned values.
+    current_inst: Option<Inst>,
 }
 
 impl<'a> Frame<'a> {
@@ -27,6 +30,7 @@ impl<'a> Frame<'a> {
         Self {
             function,
             registers: vec![None; num_slots],
+            current_inst: None,
         }
     }
 
@@ -105,6 +109,16 @@ impl<'a> Frame<'a> {
     pub fn function(&self) -> &'a Function {
         self.function
     }
+
+    /// Accessor for the instruction this frame is executing.
+    pub fn current_inst(&self) -> Option<Inst> {
+        self.current_inst
+    }
+
+    /// Record the instruction this frame is executing.
+    pub fn set_current_inst(&mut self, inst: Inst) {
+        self.current_inst = Some(inst);
+    }
 }
 
 #[cfg(test)]
@@ -33,10 +33,20 @@ impl InstructionContext for DfgInstructionContext<'_> {
     }
 
     fn type_of(&self, v: Value) -> Option<Type> {
-        Some(self.1.value_type(v))
+        Some(fixed_type(self.1.value_type(v)))
     }
 
     fn controlling_type(&self) -> Option<Type> {
-        Some(self.1.ctrl_typevar(self.0))
+        Some(fixed_type(self.1.ctrl_typevar(self.0)))
+    }
+}
+
+/// The interpreter executes dynamic vector types with a scale of one, so a dynamic vector type is
+/// handled as its fixed-size base vector type.
+fn fixed_type(ty: Type) -> Type {
+    if ty.is_dynamic_vector() {
+        ty.dynamic_to_vector().unwrap()
+    } else {
+        ty
     }
 }
@@ -3,7 +3,9 @@
 //! This module partially contains the logic for interpreting Cranelift IR.
 
 use crate::address::{Address, AddressFunctionEntry, AddressRegion, AddressSize};
-use crate::environment::{FuncIndex, FunctionStore};
+use crate::environment::{
+    ExternalFuncIndex, ExternalFunction, ExternalFunctionRegistry, FuncIndex, FunctionStore,
+};
 use crate::frame::Frame;
 use crate::instruction::DfgInstructionContext;
 use crate::state::{InterpreterFunctionRef, MemoryError, State};
@@ -11,13 +13,13 @@ use crate::step::{step, ControlFlow, CraneliftTrap, StepError};
 use crate::value::{DataValueExt, ValueError};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::{
-    ArgumentPurpose, Block, Endianness, ExternalName, FuncRef, Function, GlobalValue,
-    GlobalValueData, LibCall, MemFlags, StackSlot, Type,
+    ArgumentPurpose, DynamicStackSlot, Endianness, ExternalName, FuncRef, Function, GlobalValue,
+    GlobalValueData, Inst, LibCall, MemFlags, StackSlot, Type,
 };
 use log::trace;
 use smallvec::SmallVec;
 use std::fmt::Debug;
-use std::iter;
+use std::{iter, mem};
 use thiserror::Error;
 
 /// The Cranelift interpreter; this contains some high-level functions to control the interpreter's
@@ -73,6 +75,15 @@ impl<'a> Interpreter<'a> {
         function: &'a Function,
         arguments: &[DataValue],
     ) -> Result<ControlFlow<'a>, InterpreterError> {
+        let entry_stack = self.state.active_stack;
+        let entry_depth = self.state.frame_stack.len();
+        let first_inst = self.enter(function, arguments);
+        self.run(first_inst, entry_stack, entry_depth)
+    }
+
+    /// Push a new frame for `function`, bind its parameters to `arguments` and return the first
+    /// instruction to interpret.
+    fn enter(&mut self, function: &'a Function, arguments: &[DataValue]) -> Option<Inst> {
         trace!("Call: {}({:?})", function.name, arguments);
         let first_block = function
             .layout
@@ -85,65 +96,82 @@ impl<'a> Interpreter<'a> {
             .current_frame_mut()
             .set_all(parameters, arguments.to_vec());
 
-        self.block(first_block)
+        trace!("Block: {}", first_block);
+        function.layout.first_inst(first_block)
+    }
+
+    /// Continue interpreting the current frame after the instruction it is suspended at (a call or
+    /// a `stack_switch`), assigning `values` to that instruction's results.
+    fn resume(&mut self, values: &[DataValue]) -> Result<Option<Inst>, InterpreterError> {
+        let frame = self
+            .state
+            .frame_stack
+            .last_mut()
+            .ok_or(InterpreterError::StackReturned(self.state.active_stack))?;
+        let function = frame.function();
+        let inst = frame
+            .current_inst()
+            .expect("a suspended frame to be executing an instruction");
+        frame.set_all(function.dfg.inst_results(inst), values.to_vec());
+        Ok(function.layout.next_inst(inst))
     }
 
-    /// Interpret a [Block] in a [Function]. This drives the interpretation over sequences of
-    /// instructions, which may continue in other blocks, until the function returns.
-    fn block(&mut self, block: Block) -> Result<ControlFlow<'a>, InterpreterError> {
-        trace!("Block: {}", block);
-        let function = self.state.current_frame_mut().function();
-        let layout = &function.layout;
-        let mut maybe_inst = layout.first_inst(block);
+    /// Interpret instructions, starting at `maybe_inst`, until the call that was entered with
+    /// `entry_depth` frames on the stack `entry_stack` returns. Calls, tail calls and stack
+    /// switches only change the frames held by the state, so arbitrarily deep call chains (e.g. a
+    /// long tail-recursive loop) do not grow the host stack.
+    fn run(
+        &mut self,
+        mut maybe_inst: Option<Inst>,
+        entry_stack: u64,
+        entry_depth: usize,
+    ) -> Result<ControlFlow<'a>, InterpreterError> {
         while let Some(inst) = maybe_inst {
             if self.consume_fuel() == FuelResult::Stop {
                 return Err(InterpreterError::FuelExhausted);
             }
 
+            let function = self.state.current_frame().function();
+            let layout = &function.layout;
+            self.state.current_frame_mut().set_current_inst(inst);
+
             let inst_context = DfgInstructionContext::new(inst, &function.dfg);
-            match step(&mut self.state, inst_context)? {
+            maybe_inst = match step(&mut self.state, inst_context)? {
                 ControlFlow::Assign(values) => {
                     self.state
                         .current_frame_mut()
                         .set_all(function.dfg.inst_results(inst), values.to_vec());
-                    maybe_inst = layout.next_inst(inst)
+                    layout.next_inst(inst)
                 }
-                ControlFlow::Continue => maybe_inst = layout.next_inst(inst),
+                ControlFlow::Continue => layout.next_inst(inst),
                 ControlFlow::ContinueAt(block, block_arguments) => {
                     trace!("Block: {}", block);
                     self.state
                         .current_frame_mut()
                         .set_all(function.dfg.block_params(block), block_arguments.to_vec());
-                    maybe_inst = layout.first_inst(block)
+                    layout.first_inst(block)
                 }
                 ControlFlow::Call(called_function, arguments) => {
-                    match self.call(called_function, &arguments)? {
-                        ControlFlow::Return(rets) => {
-                            self.state
-                                .current_frame_mut()
-                                .set_all(function.dfg.inst_results(inst), rets.to_vec());
-                            maybe_inst = layout.next_inst(inst)
-                        }
-                        ControlFlow::Trap(trap) => return Ok(ControlFlow::Trap(trap)),
-                        cf => {
-                            panic!("invalid control flow after call: {cf:?}")
-                        }
-                    }
+                    self.enter(called_function, &arguments)
                 }
                 ControlFlow::ReturnCall(callee, args) => {
                     self.state.pop_frame();
-
-                    return match self.call(callee, &args)? {
-                        ControlFlow::Return(rets) => Ok(ControlFlow::Return(rets)),
-                        ControlFlow::Trap(trap) => Ok(ControlFlow::Trap(trap)),
-                        cf => {
-                            panic!("invalid control flow after return_call: {cf:?}")
-                        }
-                    };
+                    self.enter(callee, &args)
                 }
                 ControlFlow::Return(returned_values) => {
                     self.state.pop_frame();
-                    return Ok(ControlFlow::Return(returned_values));
+                    if self.state.active_stack == entry_stack
+                        && self.state.frame_stack.len() == entry_depth
+                    {
+                        return Ok(ControlFlow::Return(returned_values));
+                    }
+                    self.resume(&returned_values)?
+                }
+                ControlFlow::StackSwitch(target, payload) => {
+                    match self.state.switch_stack(target)? {
+                        Some(function) => self.enter(function, &[payload]),
+                        None => self.resume(&[payload])?,
+                    }
                 }
                 ControlFlow::Trap(trap) => return Ok(ControlFlow::Trap(trap)),
             }
@@ -189,19 +217,43 @@ pub enum InterpreterError {
     ValueError(#[from] ValueError),
     #[error("fuel exhausted")]
     FuelExhausted,
+    #[error("cannot switch to stack {0}: it is not suspended")]
+    InvalidStackSwitch(u64),
+    #[error("the initial function of stack {0} returned")]
+    StackReturned(u64),
 }
 
 pub type LibCallValues = SmallVec<[DataValue; 1]>;
 pub type LibCallHandler = fn(LibCall, LibCallValues) -> Result<LibCallValues, CraneliftTrap>;
 
+/// The state of one of the stacks the interpreter can execute on; see `stack_switch`.
+pub enum StackContext<'a> {
+    /// The stack is executing: its frames and memory are the ones held by the [InterpreterState].
+    Active,
+    /// A stack that has not run yet; the first switch to it calls `function` with the switch
+    /// payload as its only argument.
+    New(&'a Function),
+    /// A stack suspended by a `stack_switch`, which a later switch will resume.
+    Suspended {
+        frame_stack: Vec<Frame<'a>>,
+        frame_offset: usize,
+        stack: Vec<u8>,
+    },
+}
+
 /// Maintains the [Interpreter]'s state, implementing the [State] trait.
 pub struct InterpreterState<'a> {
     pub functions: FunctionStore<'a>,
+    pub external_functions: ExternalFunctionRegistry<'a>,
     pub libcall_handler: LibCallHandler,
     pub frame_stack: Vec<Frame<'a>>,
     /// Number of bytes from the bottom of the stack where the current frame's stack space is
     pub frame_offset: usize,
     pub stack: Vec<u8>,
+    /// Identifier of the stack that `frame_stack`, `frame_offset` and `stack` belong to.
+    pub active_stack: u64,
+    /// All stacks known to the interpreter, indexed by their identifier; the main stack is 0.
+    pub stacks: Vec<StackContext<'a>>,
     pub pinned_reg: DataValue,
     pub native_endianness: Endianness,
 }
@@ -215,10 +267,13 @@ impl Default for InterpreterState<'_> {
         };
         Self {
             functions: FunctionStore::default(),
+            external_functions: ExternalFunctionRegistry::default(),
             libcall_handler: |_, _| Err(CraneliftTrap::UnreachableCodeReached),
             frame_stack: vec![],
             frame_offset: 0,
             stack: Vec::with_capacity(1024),
+            active_stack: 0,
+            stacks: vec![StackContext::Active],
             pinned_reg: DataValue::I64(0),
             native_endianness,
         }
@@ -235,6 +290,91 @@ impl<'a> InterpreterState<'a> {
         self.libcall_handler = handler;
         self
     }
+
+    /// Registers the natively implemented functions that interpreted code can call.
+    pub fn with_external_functions(self, external_functions: ExternalFunctionRegistry<'a>) -> Self {
+        Self {
+            external_functions,
+            ..self
+        }
+    }
+
+    /// Create a new stack that starts by calling `function` the first time a `stack_switch`
+    /// switches to it. Returns the stack's identifier, which is what a `stack_switch` context
+    /// holds: a single 64-bit value.
+    pub fn create_stack(&mut self, function: &'a Function) -> u64 {
+        self.stacks.push(StackContext::New(function));
+        (self.stacks.len() - 1) as u64
+    }
+
+    /// Suspend the active stack and continue on the `target` stack. If the target has not run
+    /// before, returns the function it should start by calling.
+    fn switch_stack(&mut self, target: u64) -> Result<Option<&'a Function>, InterpreterError> {
+        let context = match self.stacks.get_mut(target as usize) {
+            Some(context) if !matches!(context, StackContext::Active) => {
+                mem::replace(context, StackContext::Active)
+            }
+            _ => return Err(InterpreterError::InvalidStackSwitch(target)),
+        };
+
+        self.stacks[self.active_stack as usize] = StackContext::Suspended {
+            frame_stack: mem::take(&mut self.frame_stack),
+            frame_offset: self.frame_offset,
+            stack: mem::take(&mut self.stack),
+        };
+        self.active_stack = target;
+
+        match context {
+            StackContext::New(function) => {
+                self.frame_offset = 0;
+                Ok(Some(function))
+            }
+            StackContext::Suspended {
+                frame_stack,
+                frame_offset,
+                stack,
+            } => {
+                self.frame_stack = frame_stack;
+                self.frame_offset = frame_offset;
+                self.stack = stack;
+                Ok(None)
+            }
+            StackContext::Active => unreachable!(),
+        }
+    }
+
+    /// Retrieve the memory of the stack with the given identifier.
+    fn stack_memory(&self, id: u64) -> Option<&[u8]> {
+        if id == self.active_stack {
+            return Some(&self.stack);
+        }
+        match self.stacks.get(id as usize)? {
+            StackContext::Suspended { stack, .. } => Some(stack),
+            _ => None,
+        }
+    }
+
+    /// Mutable version of [Self::stack_memory].
+    fn stack_memory_mut(&mut self, id: u64) -> Option<&mut [u8]> {
+        if id == self.active_stack {
+            return Some(&mut self.stack);
+        }
+        match self.stacks.get_mut(id as usize)? {
+            StackContext::Suspended { stack, .. } => Some(stack),
+            _ => None,
+        }
+    }
+}
+
+/// The number of bytes a frame of `function` occupies on the stack: its sized stack slots followed
+/// by its dynamic stack slots, which use the size of their base vector type.
+fn frame_size(function: &Function) -> usize {
+    let dynamic_size: u32 = function
+        .dynamic_stack_slots
+        .values()
+        .map(|ss| function.dfg.dynamic_types[ss.dyn_ty].base_vector_ty.bytes())
+        .sum();
+    (function.fixed_stack_size() + dynamic_size) as usize
 }
 
 impl<'a> State<'a> for InterpreterState<'a> {
@@ -250,14 +390,20 @@ impl<'a> State<'a> for InterpreterState<'a> {
         self.libcall_handler
     }
 
+    fn get_external_function(&self, func_ref: FuncRef) -> Option<ExternalFunction<'a>> {
+        self.external_functions
+            .get_from_func_ref(func_ref, self.get_current_function())
+            .cloned()
+    }
+
     fn push_frame(&mut self, function: &'a Function) {
         if let Some(frame) = self.frame_stack.iter().last() {
-            self.frame_offset += frame.function().fixed_stack_size() as usize;
+            self.frame_offset += frame_size(frame.function());
         }
 
         // Grow the stack by the space necessary for this frame
         self.stack
-            .extend(iter::repeat(0).take(function.fixed_stack_size() as usize));
+            .extend(iter::repeat(0).take(frame_size(function)));
 
         self.frame_stack.push(Frame::new(function));
     }
@@ -265,11 +411,11 @@ impl<'a> State<'a> for InterpreterState<'a> {
         if let Some(frame) = self.frame_stack.pop() {
             // Shorten the stack after exiting the frame
             self.stack
-                .truncate(self.stack.len() - frame.function().fixed_stack_size() as usize);
+                .truncate(self.stack.len() - frame_size(frame.function()));
 
             // Reset frame_offset to the start of this function
             if let Some(frame) = self.frame_stack.iter().last() {
-                self.frame_offset -= frame.function().fixed_stack_size() as usize;
+                self.frame_offset -= frame_size(frame.function());
             }
         }
     }
@@ -296,16 +442,8 @@ impl<'a> State<'a> for InterpreterState<'a> {
         slot: StackSlot,
         offset: u64,
     ) -> Result<Address, MemoryError> {
-        let stack_slots = &self.get_current_function().sized_stack_slots;
-        let stack_slot = &stack_slots[slot];
-
-        // offset must be `0 <= Offset < sizeof(SS)`
-        if offset >= stack_slot.size as u64 {
-            return Err(MemoryError::InvalidOffset {
-                offset,
-                max: stack_slot.size as u64,
-            });
-        }
+        let function = self.get_current_function();
+        let stack_slots = &function.sized_stack_slots;
 
         // Calculate the offset from the current frame to the requested stack slot
         let slot_offset: u64 = stack_slots
@@ -314,8 +452,75 @@ impl<'a> State<'a> for InterpreterState<'a> {
             .map(|k| stack_slots[k].size as u64)
             .sum();
 
+        // The address must stay within the current frame, e.g. `ss0+16` for a 16 byte slot is
+        // fine as long as another slot follows it.
+        let max = frame_size(function) as u64;
+        if slot_offset + offset >= max {
+            return Err(MemoryError::InvalidOffset { offset, max });
+        }
+
         let final_offset = self.frame_offset as u64 + slot_offset + offset;
-        Address::from_parts(size, AddressRegion::Stack, 0, final_offset)
+        Address::from_parts(size, AddressRegion::Stack, self.active_stack, final_offset)
+    }
+
+    fn dynamic_stack_address(
+        &self,
+        size: AddressSize,
+        slot: DynamicStackSlot,
+    ) -> Result<Address, MemoryError> {
+        let function = self.get_current_function();
+
+        // Dynamic stack slots are placed after all of the sized stack slots
+        let slot_offset: u64 = function
+            .dynamic_stack_slots
+            .iter()
+            .filter(|(k, _)| k < &slot)
+            .map(|(_, ss)| function.dfg.dynamic_types[ss.dyn_ty].base_vector_ty.bytes() as u64)
+            .sum();
+
+        let final_offset =
+            self.frame_offset as u64 + function.fixed_stack_size() as u64 + slot_offset;
+        Address::from_parts(size, AddressRegion::Stack, self.active_stack, final_offset)
+    }
+
+    fn frame_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        Address::from_parts(
+            size,
+            AddressRegion::Stack,
+            self.active_stack,
+            self.frame_offset as u64,
+        )
+    }
+
+    fn stack_pointer(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        Address::from_parts(
+            size,
+            AddressRegion::Stack,
+            self.active_stack,
+            self.stack.len() as u64,
+        )
+    }
+
+    fn return_address(&self, size: AddressSize) -> Result<Address, MemoryError> {
+        let caller = match self.frame_stack.iter().rev().nth(1) {
+            Some(frame) => frame.function(),
+            None => return Address::from_parts(size, AddressRegion::Stack, 0, 0),
+        };
+        let name = caller.name.to_string();
+        let index = self
+            .functions
+            .index_of(&name)
+            .ok_or(MemoryError::UnknownSymbol(name))?;
+        Address::from_parts(
+            size,
+            AddressRegion::Function,
+            AddressFunctionEntry::UserFunction as u64,
+            index.as_u32() as u64,
+        )
+    }
+
+    fn current_stack(&self) -> u64 {
+        self.active_stack
     }
 
     fn checked_load(
@@ -328,19 +533,25 @@ impl<'a> State<'a> for InterpreterState<'a> {
         let addr_start = addr.offset as usize;
         let addr_end = addr_start + load_size;
 
+        // Only the stacks are backed by memory.
         let src = match addr.region {
-            AddressRegion::Stack => {
-                if addr_end > self.stack.len() {
+            AddressRegion::Stack => match self.stack_memory(addr.entry) {
+                Some(stack) if addr_end <= stack.len() => &stack[addr_start..addr_end],
+                _ => {
                     return Err(MemoryError::OutOfBoundsLoad {
                         addr,
                         load_size,
                         mem_flags,
-                    });
+                    })
                 }
-
-                &self.stack[addr_start..addr_end]
+            },
+            _ => {
+                return Err(MemoryError::OutOfBoundsLoad {
+                    addr,
+                    load_size,
+                    mem_flags,
+                })
             }
-            _ => unimplemented!(),
         };
 
         // Aligned flag is set and address is not aligned for the given type
@@ -363,20 +574,27 @@ impl<'a> State<'a> for InterpreterState<'a> {
         let store_size = v.ty().bytes() as usize;
         let addr_start = addr.offset as usize;
         let addr_end = addr_start + store_size;
+        let endianness = mem_flags.endianness(self.native_endianness);
 
+        // Only the stacks are backed by memory.
         let dst = match addr.region {
-            AddressRegion::Stack => {
-                if addr_end > self.stack.len() {
+            AddressRegion::Stack => match self.stack_memory_mut(addr.entry) {
+                Some(stack) if addr_end <= stack.len() => &mut stack[addr_start..addr_end],
+                _ => {
                     return Err(MemoryError::OutOfBoundsStore {
                         addr,
                         store_size,
                         mem_flags,
-                    });
+                    })
                 }
-
-                &mut self.stack[addr_start..addr_end]
+            },
+            _ => {
+                return Err(MemoryError::OutOfBoundsStore {
+                    addr,
+                    store_size,
+                    mem_flags,
+                })
             }
-            _ => unimplemented!(),
         };
 
         // Aligned flag is set and address is not aligned for the given type
@@ -384,7 +602,7 @@ impl<'a> State<'a> for InterpreterState<'a> {
             return Err(MemoryError::MisalignedStore { addr, store_size });
         }
 
-        Ok(match mem_flags.endianness(self.native_endianness) {
+        Ok(match endianness {
             Endianness::Big => v.write_to_slice_be(dst),
             Endianness::Little => v.write_to_slice_le(dst),
         })
@@ -396,22 +614,25 @@ impl<'a> State<'a> for InterpreterState<'a> {
         name: &ExternalName,
     ) -> Result<Address, MemoryError> {
         let curr_func = self.get_current_function();
-        let (entry, index) = match name {
-            ExternalName::User(username) => {
-                let ext_name = &curr_func.params.user_named_funcs()[*username];
-
-                // TODO: This is not optimal since we are looking up by string name
-                let index = self.functions.index_of(&ext_name.to_string()).unwrap();
 
-                (AddressFunctionEntry::UserFunction, index.as_u32())
+        // Interpreted functions take precedence over external functions of the same name.
+        // TODO: This is not optimal since we are looking up by string name
+        let lookup = |name: String| -> Result<(AddressFunctionEntry, u32), MemoryError> {
+            if let Some(index) = self.functions.index_of(&name) {
+                Ok((AddressFunctionEntry::UserFunction, index.as_u32()))
+            } else if let Some(index) = self.external_functions.index_of(&name) {
+                Ok((AddressFunctionEntry::ExternalFunction, index.as_u32()))
+            } else {
+                Err(MemoryError::UnknownSymbol(name))
             }
+        };
 
-            ExternalName::TestCase(testname) => {
-                // TODO: This is not optimal since we are looking up by string name
-                let index = self.functions.index_of(&testname.to_string()).unwrap();
-
-                (AddressFunctionEntry::UserFunction, index.as_u32())
+        let (entry, index) = match name {
+            ExternalName::User(username) => {
+                lookup(curr_func.params.user_named_funcs()[*username].to_string())?
             }
+            ExternalName::TestCase(testname) => lookup(testname.to_string())?,
+            ExternalName::KnownSymbol(_) => lookup(name.display(None).to_string())?,
             ExternalName::LibCall(libcall) => {
                 // We don't properly have a "libcall" store, but we can use `LibCall::all()`
                 // and index into that.
@@ -422,7 +643,6 @@ impl<'a> State<'a> for InterpreterState<'a> {
 
                 (AddressFunctionEntry::LibCall, index as u32)
             }
-            _ => unimplemented!("function_address: {:?}", name),
         };
 
         Address::from_parts(size, AddressRegion::Function, entry as u64, index as u64)
@@ -444,6 +664,12 @@ impl<'a> State<'a> for InterpreterState<'a> {
                 .get(index as usize)
                 .copied()
                 .map(InterpreterFunctionRef::from),
+
+            AddressFunctionEntry::ExternalFunction => self
+                .external_functions
+                .get_by_index(ExternalFuncIndex::from_u32(index))
+                .cloned()
+                .map(InterpreterFunctionRef::External),
         }
     }
 
@@ -524,8 +750,22 @@ impl<'a> State<'a> for InterpreterState<'a> {
                         action_stack.push(ResolveAction::Add(dv));
                         action_stack.push(ResolveAction::Resolve(base));
                     }
-                    GlobalValueData::Symbol { .. } => unimplemented!(),
-                    GlobalValueData::DynScaleTargetConst { .. } => unimplemented!(),
+                    GlobalValueData::Symbol {
+                        ref name, offset, ..
+                    } => {
+                        // Only functions have addresses in the interpreter, so symbols always
+                        // resolve to one of those.
+                        let addr = self.function_address(AddressSize::_64, name)?;
+                        current_val = DataValue::try_from(addr)?;
+                        let offset: i64 = offset.into();
+                        if offset != 0 {
+                            action_stack.push(ResolveAction::Add(DataValue::I64(offset)));
+                        }
+                    }
+                    GlobalValueData::DynScaleTargetConst { .. } => {
+                        // Dynamic vector types are interpreted with a scale of one.
+                        current_val = DataValue::I64(1);
+                    }
                 },
                 Some(ResolveAction::Add(dv)) => {
                     current_val = current_val
@@ -923,7 +1163,7 @@ mod tests {
     }
 
     #[test]
-    fn srem_trap() {
+    fn srem_min_by_neg_one_is_zero() {
         let code = "function %test() -> i64 {
         block0:
             v0 = iconst.i64 0x8000_0000_0000_0000
@@ -936,12 +1176,9 @@ mod tests {
         let mut env = FunctionStore::default();
         env.add(func.name.to_string(), &func);
         let state = InterpreterState::default().with_function_store(env);
-        let trap = Interpreter::new(state).call_by_name("%test", &[]).unwrap();
+        let result = Interpreter::new(state).call_by_name("%test", &[]).unwrap();
 
-        assert_eq!(
-            trap,
-            ControlFlow::Trap(CraneliftTrap::User(TrapCode::INTEGER_OVERFLOW))
-        );
+        assert_eq!(result, ControlFlow::Return(smallvec![DataValue::I64(0)]));
     }
 
     #[test]
@@ -974,6 +1211,104 @@ mod tests {
         )
     }
 
+    #[test]
+    fn external_function() {
+        let code = "function %test(i32) -> i32 {
+            fn0 = %double(i32) -> i32
+        block0(v0: i32):
+            v1 = call fn0(v0)
+            return v1
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        let mut external_functions = ExternalFunctionRegistry::default();
+        external_functions.add(
+            "%double".to_string(),
+            func.dfg.signatures.values().next().unwrap().clone(),
+            |args| match &args[..] {
+                [DataValue::I32(n)] => Ok(smallvec![DataValue::I32(n * 2)]),
+                _ => panic!("Unexpected args"),
+            },
+        );
+        let state = InterpreterState::default()
+            .with_function_store(env)
+            .with_external_functions(external_functions);
+
+        let result = Interpreter::new(state)
+            .call_by_name("%test", &[DataValue::I32(21)])
+            .unwrap();
+
+        assert_eq!(result, ControlFlow::Return(smallvec![DataValue::I32(42)]));
+    }
+
+    #[test]
+    fn stack_switch() {
+        let code = "
+        function %parent() -> i64 {
+            ss0 = explicit_slot 8
+            ss1 = explicit_slot 8
+
+        block0:
+            v0 = iconst.i64 1
+            stack_store v0, ss0
+            v1 = stack_addr.i64 ss1
+            v2 = stack_addr.i64 ss0
+            v3 = stack_switch v1, v2, v1
+            v4 = iadd_imm v3, 43
+            return v4
+        }
+
+        function %child(i64) -> i64 {
+            ss0 = explicit_slot 8
+
+        block0(v0: i64):
+            v1 = stack_addr.i64 ss0
+            v2 = iconst.i64 100
+            v3 = stack_switch v1, v0, v2
+            return v3
+        }";
+
+        let funcs = parse_functions(code).unwrap();
+        let mut env = FunctionStore::default();
+        for func in &funcs {
+            env.add(func.name.to_string(), func);
+        }
+        let mut state = InterpreterState::default().with_function_store(env);
+        // The parent switches to the child through a context holding the child's stack, which it
+        // passes the address of its own context so that the child can switch back.
+        assert_eq!(state.create_stack(&funcs[1]), 1);
+
+        let result = Interpreter::new(state)
+            .call_by_name("%parent", &[])
+            .unwrap();
+
+        assert_eq!(result, ControlFlow::Return(smallvec![DataValue::I64(143)]));
+    }
+
+    #[test]
+    fn stack_pointer() {
+        let code = "
+        function %test() -> i64 {
+            ss0 = explicit_slot 16
+
+        block0:
+            v0 = get_stack_pointer.i64
+            v1 = stack_addr.i64 ss0
+            v2 = isub v0, v1
+            return v2
+        }";
+
+        let func = parse_functions(code).unwrap().into_iter().next().unwrap();
+        let mut env = FunctionStore::default();
+        env.add(func.name.to_string(), &func);
+        let state = InterpreterState::default().with_function_store(env);
+        let result = Interpreter::new(state).call_by_name("%test", &[]).unwrap();
+
+        assert_eq!(result, ControlFlow::Return(smallvec![DataValue::I64(16)]));
+    }
+
     #[test]
     fn misaligned_store_traps() {
         let code = "
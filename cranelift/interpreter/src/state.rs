@@ -1,12 +1,13 @@
 //! Cranelift instructions modify the state of the machine; the [State] trait describes these
 //! ways this can happen.
 use crate::address::{Address, AddressSize};
+use crate::environment::ExternalFunction;
 use crate::frame::Frame;
 use crate::interpreter::LibCallHandler;
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::{
-    types, ExternalName, FuncRef, Function, GlobalValue, LibCall, MemFlags, Signature, StackSlot,
-    Type, Value,
+    types, DynamicStackSlot, ExternalName, FuncRef, Function, GlobalValue, LibCall, MemFlags,
+    Signature, StackSlot, Type, Value,
 };
 use cranelift_codegen::isa::CallConv;
 use smallvec::SmallVec;
@@ -27,6 +28,9 @@ pub trait State<'a> {
     fn get_current_function(&self) -> &'a Function;
     /// Retrieve the handler callback for a [LibCall]
     fn get_libcall_handler(&self) -> LibCallHandler;
+    /// Retrieve a natively implemented [ExternalFunction], for a [FuncRef] that does not refer to
+    /// an interpreted function.
+    fn get_external_function(&self, func_ref: FuncRef) -> Option<ExternalFunction<'a>>;
 
     /// Record that an interpreter has called into a new [Function].
     fn push_frame(&mut self, function: &'a Function);
@@ -49,6 +53,24 @@ pub trait State<'a> {
         slot: StackSlot,
         offset: u64,
     ) -> Result<Address, MemoryError>;
+    /// Computes the stack address for this dynamic stack slot. Dynamic vector types are
+    /// interpreted with a scale of one, i.e. as their fixed-size base vector type.
+    fn dynamic_stack_address(
+        &self,
+        size: AddressSize,
+        slot: DynamicStackSlot,
+    ) -> Result<Address, MemoryError>;
+    /// Computes the address of the start of the current frame.
+    fn frame_pointer(&self, size: AddressSize) -> Result<Address, MemoryError>;
+    /// Computes the address of the end of the current frame, where a called function's frame
+    /// would start.
+    fn stack_pointer(&self, size: AddressSize) -> Result<Address, MemoryError>;
+    /// Computes the address that returning from the current function transfers control to: the
+    /// address of the calling function, or a null address if there is none.
+    fn return_address(&self, size: AddressSize) -> Result<Address, MemoryError>;
+    /// Retrieve the identifier of the stack that is currently executing; this is the context that
+    /// `stack_switch` stores to resume this stack later.
+    fn current_stack(&self) -> u64;
     /// Retrieve a value `V` from memory at the given `address`, checking if it belongs either to the
     /// stack or to one of the heaps; the number of bytes loaded corresponds to the specified [Type].
     fn checked_load(
@@ -89,6 +111,7 @@ pub trait State<'a> {
 pub enum InterpreterFunctionRef<'a> {
     Function(&'a Function),
     LibCall(LibCall),
+    External(ExternalFunction<'a>),
 }
 
 impl<'a> InterpreterFunctionRef<'a> {
@@ -98,6 +121,7 @@ impl<'a> InterpreterFunctionRef<'a> {
             // CallConv here is sort of irrelevant, since we don't use it for anything
             // FIXME handle non-64bit systems
             InterpreterFunctionRef::LibCall(lc) => lc.signature(CallConv::SystemV, types::I64),
+            InterpreterFunctionRef::External(f) => f.signature().clone(),
         }
     }
 }
@@ -118,6 +142,8 @@ impl From<LibCall> for InterpreterFunctionRef<'_> {
 pub enum MemoryError {
     #[error("Invalid DataValue passed as an address: {0}")]
     InvalidAddress(DataValue),
+    #[error("Unknown symbol: {0}")]
+    UnknownSymbol(String),
     #[error("Invalid type for address: {0}")]
     InvalidAddressType(Type),
     #[error("Requested an the entry {entry} but only {max} entries are allowed")]
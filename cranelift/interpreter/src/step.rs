@@ -6,6 +6,7 @@ use crate::state::{InterpreterFunctionRef, MemoryError, State};
 use crate::value::{DataValueExt, ValueConversionKind, ValueError, ValueResult};
 use cranelift_codegen::data_value::DataValue;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::immediates::Ieee128;
 use cranelift_codegen::ir::{
     types, AbiParam, AtomicRmwOp, Block, BlockCall, Endianness, ExternalName, FuncRef, Function,
     InstructionData, MemFlags, Opcode, TrapCode, Type, Value as ValueRef,
@@ -162,7 +163,8 @@ where
         MemoryError::InvalidAddress(_)
         | MemoryError::InvalidAddressType(_)
         | MemoryError::InvalidOffset { .. }
-        | MemoryError::InvalidEntry { .. } => CraneliftTrap::User(TrapCode::HEAP_OUT_OF_BOUNDS),
+        | MemoryError::InvalidEntry { .. }
+        | MemoryError::UnknownSymbol(_) => CraneliftTrap::User(TrapCode::HEAP_OUT_OF_BOUNDS),
         MemoryError::OutOfBoundsStore { mem_flags, .. }
         | MemoryError::OutOfBoundsLoad { mem_flags, .. } => CraneliftTrap::User(
             mem_flags
@@ -251,6 +253,17 @@ where
         }
     };
 
+    // Retrieve an instruction's dynamic stack slot; expects the instruction to access one.
+    let dynamic_stack_slot = || match inst {
+        InstructionData::DynamicStackLoad {
+            dynamic_stack_slot, ..
+        }
+        | InstructionData::DynamicStackStore {
+            dynamic_stack_slot, ..
+        } => dynamic_stack_slot,
+        _ => unreachable!(),
+    };
+
     // Retrieve an instruction's trap code; expects the instruction to be a trap.
     let trap_code = || -> TrapCode { inst.trap_code().unwrap() };
 
@@ -304,6 +317,27 @@ where
                         ControlFlow::Trap(CraneliftTrap::BadSignature)
                     }
                 }
+                InterpreterFunctionRef::External(function) => {
+                    debug_assert!(
+                        !matches!(
+                            inst.opcode(),
+                            Opcode::ReturnCall | Opcode::ReturnCallIndirect,
+                        ),
+                        "Cannot tail call to external functions"
+                    );
+
+                    // Like libcalls, external functions are executed in place.
+                    let res = match function.call(args) {
+                        Err(trap) => return Ok(ControlFlow::Trap(trap)),
+                        Ok(rets) => rets,
+                    };
+
+                    if validate_signature_params(&signature.returns[..], &res[..]) {
+                        ControlFlow::Assign(res)
+                    } else {
+                        ControlFlow::Trap(CraneliftTrap::BadSignature)
+                    }
+                }
             })
         };
 
@@ -373,15 +407,22 @@ where
 
             let args = args();
             let func = match ext_data.name {
-                // These functions should be registered in the regular function store
-                ExternalName::User(_) | ExternalName::TestCase(_) => {
-                    let function = state
-                        .get_function(func_ref)
-                        .ok_or(StepError::UnknownFunction(func_ref))?;
-                    InterpreterFunctionRef::Function(function)
-                }
+                // These functions should be registered in the regular function store, or be
+                // provided natively as external functions.
+                ExternalName::User(_) | ExternalName::TestCase(_) => state
+                    .get_function(func_ref)
+                    .map(InterpreterFunctionRef::Function)
+                    .or_else(|| {
+                        state
+                            .get_external_function(func_ref)
+                            .map(InterpreterFunctionRef::External)
+                    })
+                    .ok_or(StepError::UnknownFunction(func_ref))?,
                 ExternalName::LibCall(libcall) => InterpreterFunctionRef::LibCall(libcall),
-                ExternalName::KnownSymbol(_) => unimplemented!(),
+                ExternalName::KnownSymbol(_) => state
+                    .get_external_function(func_ref)
+                    .map(InterpreterFunctionRef::External)
+                    .ok_or(StepError::UnknownFunction(func_ref))?,
             };
 
             let make_control_flow = match inst.opcode() {
@@ -456,12 +497,16 @@ where
                 Opcode::Sload16 => (types::I16, Some(ValueConversionKind::SignExtend(ctrl_ty))),
                 Opcode::Uload32 => (types::I32, Some(ValueConversionKind::ZeroExtend(ctrl_ty))),
                 Opcode::Sload32 => (types::I32, Some(ValueConversionKind::SignExtend(ctrl_ty))),
-                Opcode::Uload8x8
-                | Opcode::Sload8x8
-                | Opcode::Uload16x4
-                | Opcode::Sload16x4
-                | Opcode::Uload32x2
-                | Opcode::Sload32x2 => unimplemented!(),
+                // The widening loads read 8 bytes as a vector of half-width lanes, each lane is
+                // then extended individually.
+                Opcode::Uload8x8 | Opcode::Uload16x4 | Opcode::Uload32x2 => (
+                    ctrl_ty.half_width().unwrap(),
+                    Some(ValueConversionKind::ZeroExtend(ctrl_ty.lane_type())),
+                ),
+                Opcode::Sload8x8 | Opcode::Sload16x4 | Opcode::Sload32x2 => (
+                    ctrl_ty.half_width().unwrap(),
+                    Some(ValueConversionKind::SignExtend(ctrl_ty.lane_type())),
+                ),
                 _ => unreachable!(),
             };
 
@@ -473,6 +518,13 @@ where
             );
 
             match (loaded, kind) {
+                (ControlFlow::Assign(ret), Some(c)) if load_ty.is_vector() => {
+                    let lanes = extractlanes(&ret[0], load_ty)?
+                        .into_iter()
+                        .map(|lane| lane.convert(c.clone()))
+                        .collect::<ValueResult<SimdVec<DataValue>>>()?;
+                    assign(vectorizelanes(&lanes, ctrl_ty)?)
+                }
                 (ControlFlow::Assign(ret), Some(c)) => ControlFlow::Assign(
                     ret.into_iter()
                         .map(|loaded| loaded.convert(c.clone()))
@@ -536,9 +588,37 @@ where
                 })
             })
         }
-        Opcode::DynamicStackAddr => unimplemented!("DynamicStackSlot"),
-        Opcode::DynamicStackLoad => unimplemented!("DynamicStackLoad"),
-        Opcode::DynamicStackStore => unimplemented!("DynamicStackStore"),
+        Opcode::DynamicStackAddr => {
+            let addr_ty = inst_context.controlling_type().unwrap();
+            let slot = dynamic_stack_slot();
+            assign_or_memtrap({
+                AddressSize::try_from(addr_ty).and_then(|addr_size| {
+                    let addr = state.dynamic_stack_address(addr_size, slot)?;
+                    let dv = DataValue::try_from(addr)?;
+                    Ok(dv.into())
+                })
+            })
+        }
+        Opcode::DynamicStackLoad => {
+            let load_ty = inst_context.controlling_type().unwrap();
+            let slot = dynamic_stack_slot();
+            let mem_flags = MemFlags::trusted();
+            assign_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_load(addr, load_ty, mem_flags))
+            })
+        }
+        Opcode::DynamicStackStore => {
+            let arg = arg(0);
+            let slot = dynamic_stack_slot();
+            let mem_flags = MemFlags::trusted();
+            continue_or_memtrap({
+                state
+                    .dynamic_stack_address(AddressSize::_64, slot)
+                    .and_then(|addr| state.checked_store(addr, arg, mem_flags))
+            })
+        }
         Opcode::GlobalValue | Opcode::SymbolValue | Opcode::TlsValue => {
             if let InstructionData::UnaryGlobalValue { global_value, .. } = inst {
                 assign_or_memtrap(state.resolve_global_value(global_value))
@@ -601,11 +681,13 @@ where
             }
         }
         Opcode::AvgRound => {
-            let sum = DataValueExt::add(arg(0), arg(1))?;
-            let one = DataValueExt::int(1, arg(0).ty())?;
-            let inc = DataValueExt::add(sum, one)?;
-            let two = DataValueExt::int(2, arg(0).ty())?;
-            binary(DataValueExt::udiv, inc, two)?
+            // Compute the average in a wider type so that the sum cannot overflow.
+            let avg_round = |x: DataValue, y: DataValue| -> ValueResult<DataValue> {
+                let ty = x.ty();
+                let sum = x.into_int_unsigned()? + y.into_int_unsigned()? + 1;
+                DataValueExt::int((sum >> 1) as i128, ty)
+            };
+            assign(binary_arith(arg(0), arg(1), ctrl_ty, avg_round)?)
         }
         Opcode::Iadd => binary(DataValueExt::add, arg(0), arg(1))?,
         Opcode::UaddSat => assign(binary_arith(
@@ -856,47 +938,39 @@ where
         Opcode::Fneg => unary(DataValueExt::neg, arg(0))?,
         Opcode::Fabs => unary(DataValueExt::abs, arg(0))?,
         Opcode::Fcopysign => binary(DataValueExt::copysign, arg(0), arg(1))?,
-        Opcode::Fmin => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
-            (a, b) => a.smin(b)?,
-        }),
-        Opcode::Fmax => assign(match (arg(0), arg(1)) {
-            (a, _) if a.is_nan()? => a,
-            (_, b) if b.is_nan()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
-            (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
-            (a, b) => a.smax(b)?,
-        }),
+        Opcode::Fmin => binary(fmin, arg(0), arg(1))?,
+        Opcode::Fmax => binary(fmax, arg(0), arg(1))?,
         Opcode::Ceil => unary(DataValueExt::ceil, arg(0))?,
         Opcode::Floor => unary(DataValueExt::floor, arg(0))?,
         Opcode::Trunc => unary(DataValueExt::trunc, arg(0))?,
         Opcode::Nearest => unary(DataValueExt::nearest, arg(0))?,
-        Opcode::Bitcast | Opcode::ScalarToVector => {
-            let input_ty = inst_context.type_of(inst_context.args()[0]).unwrap();
-            let lanes = &if input_ty.is_vector() {
-                assert_eq!(
-                    inst.memflags()
-                        .expect("byte order flag to be set")
-                        .endianness(Endianness::Little),
-                    Endianness::Little,
-                    "Only little endian bitcasts on vectors are supported"
-                );
-                extractlanes(&arg(0), ctrl_ty)?
-            } else {
-                extractlanes(&arg(0), input_ty)?
-                    .into_iter()
-                    .map(|x| DataValue::convert(x, ValueConversionKind::Exact(ctrl_ty.lane_type())))
-                    .collect::<ValueResult<SimdVec<DataValue>>>()?
-            };
-            assign(match inst.opcode() {
-                Opcode::Bitcast => vectorizelanes(lanes, ctrl_ty)?,
-                Opcode::ScalarToVector => vectorizelanes_all(lanes, ctrl_ty)?,
-                _ => unreachable!(),
+        Opcode::Bitcast => {
+            // Reinterpret the bytes of the value, in the byte order given by the instruction's
+            // flags.
+            let endianness = inst.memflags().map_or(Endianness::Little, |flags| {
+                flags.endianness(Endianness::Little)
+            });
+            let mut bytes = [0; 16];
+            let size = ctrl_ty.bytes() as usize;
+            assign(match endianness {
+                Endianness::Little => {
+                    arg(0).write_to_slice_le(&mut bytes[..size]);
+                    DataValue::read_from_slice_le(&bytes[..size], ctrl_ty)
+                }
+                Endianness::Big => {
+                    arg(0).write_to_slice_be(&mut bytes[..size]);
+                    DataValue::read_from_slice_be(&bytes[..size], ctrl_ty)
+                }
             })
         }
+        Opcode::ScalarToVector => {
+            let input_ty = inst_context.type_of(inst_context.args()[0]).unwrap();
+            let lanes = extractlanes(&arg(0), input_ty)?
+                .into_iter()
+                .map(|x| DataValue::convert(x, ValueConversionKind::Exact(ctrl_ty.lane_type())))
+                .collect::<ValueResult<SimdVec<DataValue>>>()?;
+            assign(vectorizelanes_all(&lanes, ctrl_ty)?)
+        }
         Opcode::Ireduce => assign(DataValueExt::convert(
             arg(0),
             ValueConversionKind::Truncate(ctrl_ty),
@@ -1120,6 +1194,16 @@ where
                         x.into_int_signed()? as f64
                     })
                     .to_bits(),
+                    types::F16 => {
+                        let x = if inst.opcode() == Opcode::FcvtFromUint {
+                            x.into_int_unsigned()? as f64
+                        } else {
+                            x.into_int_signed()? as f64
+                        };
+                        DataValue::F64(x.into())
+                            .convert(ValueConversionKind::RoundNearestEven(types::F16))?
+                            .into_float()? as u64
+                    }
                     _ => unimplemented!("unexpected conversion to {:?}", ctrl_ty.lane_type()),
                 })
             };
@@ -1275,17 +1359,134 @@ where
             assign(binary_pairwise(arg(0), arg(1), ctrl_ty, DataValueExt::add)?)
         }
         Opcode::ExtractVector => {
-            unimplemented!("ExtractVector not supported");
-        }
-        Opcode::GetFramePointer => unimplemented!("GetFramePointer"),
-        Opcode::GetStackPointer => unimplemented!("GetStackPointer"),
-        Opcode::GetReturnAddress => unimplemented!("GetReturnAddress"),
-        Opcode::X86Pshufb => unimplemented!("X86Pshufb"),
-        Opcode::X86Blendv => unimplemented!("X86Blendv"),
-        Opcode::X86Pmulhrsw => unimplemented!("X86Pmulhrsw"),
-        Opcode::X86Pmaddubsw => unimplemented!("X86Pmaddubsw"),
-        Opcode::X86Cvtt2dq => unimplemented!("X86Cvtt2dq"),
-        Opcode::StackSwitch => unimplemented!("StackSwitch"),
+            // Dynamic vectors are interpreted with a scale of one, so they only contain the
+            // sub-vector at index 0.
+            let index = match inst {
+                InstructionData::BinaryImm8 { imm, .. } => imm,
+                _ => unreachable!(),
+            };
+            if index != 0 {
+                return Err(StepError::ValueError(ValueError::InvalidValue(ctrl_ty)));
+            }
+            assign(arg(0))
+        }
+        Opcode::GetFramePointer | Opcode::GetStackPointer | Opcode::GetReturnAddress => {
+            assign_or_memtrap({
+                AddressSize::try_from(ctrl_ty).and_then(|addr_size| {
+                    let addr = match inst.opcode() {
+                        Opcode::GetFramePointer => state.frame_pointer(addr_size),
+                        Opcode::GetStackPointer => state.stack_pointer(addr_size),
+                        Opcode::GetReturnAddress => state.return_address(addr_size),
+                        _ => unreachable!(),
+                    }?;
+                    let dv = DataValue::try_from(addr)?;
+                    Ok(dv.into())
+                })
+            })
+        }
+        Opcode::X86Pshufb => {
+            let x = extractlanes(&arg(0), types::I8X16)?;
+            let y = extractlanes(&arg(1), types::I8X16)?;
+            let lanes = y
+                .into_iter()
+                .map(|i| {
+                    let i = i.into_int_unsigned()? as usize;
+                    Ok(if i & 0x80 != 0 {
+                        DataValue::I8(0)
+                    } else {
+                        x[i & 0xf].clone()
+                    })
+                })
+                .collect::<ValueResult<SimdVec<DataValue>>>()?;
+            assign(vectorizelanes(&lanes, ctrl_ty)?)
+        }
+        Opcode::X86Blendv => {
+            let c = extractlanes(&arg(0), ctrl_ty)?;
+            let x = extractlanes(&arg(1), ctrl_ty)?;
+            let y = extractlanes(&arg(2), ctrl_ty)?;
+            let lanes = c
+                .into_iter()
+                .zip(x.into_iter().zip(y))
+                .map(|(c, (x, y))| {
+                    // Only the top bit of each lane of `c` is significant.
+                    let c = c.convert(ValueConversionKind::Exact(ctrl_ty.lane_type().as_int()))?;
+                    Ok(if c.into_int_signed()? < 0 { x } else { y })
+                })
+                .collect::<ValueResult<SimdVec<DataValue>>>()?;
+            assign(vectorizelanes(&lanes, ctrl_ty)?)
+        }
+        Opcode::X86Pmulhrsw => {
+            // Like `sqmul_round_sat`, but the result wraps instead of saturating.
+            let lane_type = ctrl_ty.lane_type();
+            let arg0 = extractlanes(&arg(0), ctrl_ty)?;
+            let arg1 = extractlanes(&arg(1), ctrl_ty)?;
+            let new_vec = arg0
+                .into_iter()
+                .zip(arg1)
+                .map(|(x, y)| {
+                    let x = x.into_int_signed()?;
+                    let y = y.into_int_signed()?;
+                    let z = (x * y + (1 << (lane_type.bits() - 2))) >> (lane_type.bits() - 1);
+                    DataValue::I128(z).convert(ValueConversionKind::Truncate(lane_type))
+                })
+                .collect::<ValueResult<SimdVec<_>>>()?;
+            assign(vectorizelanes(&new_vec, ctrl_ty)?)
+        }
+        Opcode::X86Pmaddubsw => {
+            let x = extractlanes(&arg(0), types::I8X16)?;
+            let y = extractlanes(&arg(1), types::I8X16)?;
+            let products = x
+                .into_iter()
+                .zip(y)
+                .map(|(x, y)| Ok(x.into_int_signed()? * y.into_int_unsigned()? as i128))
+                .collect::<ValueResult<SimdVec<i128>>>()?;
+            let lanes = products
+                .chunks(2)
+                .map(|pair| {
+                    let sum = (pair[0] + pair[1]).clamp(i16::MIN.into(), i16::MAX.into());
+                    DataValue::I16(sum as i16)
+                })
+                .collect::<SimdVec<DataValue>>();
+            assign(vectorizelanes(&lanes, ctrl_ty)?)
+        }
+        Opcode::X86Cvtt2dq => {
+            let in_ty = inst_context.type_of(inst_context.args()[0]).unwrap();
+            let x = extractlanes(&arg(0), in_ty)?;
+            let mut lanes = x
+                .into_iter()
+                .map(|x| {
+                    // NaN and out of range lanes produce `INT_MIN`.
+                    let x = match x {
+                        DataValue::F32(f) => f.as_f32() as f64,
+                        DataValue::F64(f) => f.as_f64(),
+                        _ => unreachable!(),
+                    };
+                    let in_range = x > f64::from(i32::MIN) - 1.0 && x < f64::from(i32::MAX) + 1.0;
+                    DataValue::I32(if in_range { x as i32 } else { i32::MIN })
+                })
+                .collect::<SimdVec<DataValue>>();
+            // Converting `f64x2` fills only the lower half of the result.
+            lanes.resize(ctrl_ty.lane_count() as usize, DataValue::I32(0));
+            assign(vectorizelanes(&lanes, ctrl_ty)?)
+        }
+        Opcode::StackSwitch => {
+            // A stack context holds the identifier of the stack it resumes.
+            let store_addr = Address::try_from(arg(0)).map_err(StepError::MemoryError)?;
+            let load_addr = Address::try_from(arg(1)).map_err(StepError::MemoryError)?;
+            let payload = arg(2);
+            let mem_flags = MemFlags::trusted();
+
+            let target = match state.checked_load(load_addr, types::I64, mem_flags) {
+                Ok(target) => target.into_int_unsigned()? as u64,
+                Err(e) => return Ok(ControlFlow::Trap(memerror_to_trap(e))),
+            };
+            let current = DataValue::I64(state.current_stack() as i64);
+            if let Err(e) = state.checked_store(store_addr, current, mem_flags) {
+                return Ok(ControlFlow::Trap(memerror_to_trap(e)));
+            }
+
+            ControlFlow::StackSwitch(target, payload)
+        }
     })
 }
 
@@ -1322,6 +1523,9 @@ pub enum ControlFlow<'a> {
     ReturnCall(&'a Function, SmallVec<[DataValue; 1]>),
     /// Return from the current function with the given parameters, e.g.: `return [v1, v2]`.
     Return(SmallVec<[DataValue; 1]>),
+    /// Suspend the current stack and switch to the stack with the given identifier, passing it
+    /// the given payload, e.g.: `stack_switch v0, v1, v2`.
+    StackSwitch(u64, DataValue),
     /// Stop with a program-generated trap; note that these are distinct from errors that may occur
     /// during interpretation.
     Trap(CraneliftTrap),
@@ -1433,20 +1637,21 @@ pub(crate) fn extractlanes(
         types::I16 | types::F16 => 2,
         types::I32 | types::F32 => 4,
         types::I64 | types::F64 => 8,
-        _ => unimplemented!("vectors with lanes wider than 64-bits are currently unsupported."),
+        types::I128 | types::F128 => 16,
+        _ => unimplemented!("vectors with lanes wider than 128-bits are currently unsupported."),
     };
 
     let x = x.into_array()?;
     for i in 0..vector_type.lane_count() {
-        let mut lane: i128 = 0;
+        let mut lane: u128 = 0;
         for j in 0..iterations {
-            lane += (x[((i * iterations) + j) as usize] as i128) << (8 * j);
+            lane |= (x[((i * iterations) + j) as usize] as u128) << (8 * j);
         }
 
-        let lane_val: DataValue = if lane_type.is_float() {
-            DataValueExt::float(lane as u64, lane_type)?
-        } else {
-            DataValueExt::int(lane, lane_type)?
+        let lane_val: DataValue = match lane_type {
+            types::F128 => DataValue::F128(Ieee128::with_bits(lane)),
+            _ if lane_type.is_float() => DataValueExt::float(lane as u64, lane_type)?,
+            _ => DataValueExt::int(lane as i128, lane_type)?,
         };
         lanes.push(lane_val);
     }
@@ -1456,8 +1661,8 @@ pub(crate) fn extractlanes(
 /// Convert a Rust array of [Value] back into a `Value::vector`.
 /// Supplying a single-element array will simply return its contained value.
 fn vectorizelanes(x: &[DataValue], vector_type: types::Type) -> ValueResult<DataValue> {
-    // If the array is only one element, return it as a scalar.
-    if x.len() == 1 {
+    // If the type is a scalar, return the only element as a scalar.
+    if !vector_type.is_vector() {
         Ok(x[0].clone())
     } else {
         vectorizelanes_all(x, vector_type)
@@ -1472,7 +1677,8 @@ fn vectorizelanes_all(x: &[DataValue], vector_type: types::Type) -> ValueResult<
         types::I16 | types::F16 => 2,
         types::I32 | types::F32 => 4,
         types::I64 | types::F64 => 8,
-        _ => unimplemented!("vectors with lanes wider than 64-bits are currently unsupported."),
+        types::I128 | types::F128 => 16,
+        _ => unimplemented!("vectors with lanes wider than 128-bits are currently unsupported."),
     };
     let mut result: [u8; 16] = [0; 16];
     for (i, val) in x.iter().enumerate() {
@@ -1488,6 +1694,28 @@ fn vectorizelanes_all(x: &[DataValue], vector_type: types::Type) -> ValueResult<
     DataValueExt::vector(result, vector_type)
 }
 
+/// The `fmin` of two scalar floats: NaN if either is NaN, and -0.0 is less than +0.0.
+fn fmin(a: DataValue, b: DataValue) -> ValueResult<DataValue> {
+    Ok(match (a, b) {
+        (a, _) if a.is_nan()? => a,
+        (_, b) if b.is_nan()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => a,
+        (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => b,
+        (a, b) => a.smin(b)?,
+    })
+}
+
+/// The `fmax` of two scalar floats: NaN if either is NaN, and +0.0 is greater than -0.0.
+fn fmax(a: DataValue, b: DataValue) -> ValueResult<DataValue> {
+    Ok(match (a, b) {
+        (a, _) if a.is_nan()? => a,
+        (_, b) if b.is_nan()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && a.is_negative()? => b,
+        (a, b) if a.is_zero()? && b.is_zero()? && b.is_negative()? => a,
+        (a, b) => a.smax(b)?,
+    })
+}
+
 /// Performs a lanewise fold on a vector type
 fn fold_vector<F>(v: DataValue, ty: types::Type, init: DataValue, op: F) -> ValueResult<DataValue>
 where
@@ -247,6 +247,65 @@ macro_rules! bitop {
     };
 }
 
+/// Convert the bits of a half-precision float to an `f64`; this is always exact.
+fn f16_to_f64(x: Ieee16) -> f64 {
+    let bits = x.bits();
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = bits & 0x3ff;
+    match exponent {
+        0 => sign * mantissa as f64 * 2f64.powi(-24),
+        0x1f if mantissa == 0 => sign * f64::INFINITY,
+        // Keep the sign and payload of NaNs.
+        0x1f => f64::from_bits(
+            ((bits as u64 & 0x8000) << 48) | (0x7ff << 52) | ((mantissa as u64) << 42),
+        ),
+        _ => sign * (0x400 | mantissa) as f64 * 2f64.powi(exponent as i32 - 25),
+    }
+}
+
+/// Convert an `f64` to a half-precision float, rounding to the nearest value with ties to even.
+fn f64_to_f16(x: f64) -> Ieee16 {
+    let bits = x.to_bits();
+    let sign = ((bits >> 48) & 0x8000) as u16;
+    let magnitude = x.abs();
+
+    let half = if x.is_nan() {
+        // Keep the top of the payload, making sure the result is still a NaN.
+        0x7e00 | ((bits >> 42) & 0x3ff) as u16
+    } else if magnitude >= 65520.0 {
+        // Anything at least halfway between the largest finite value and the next power of two
+        // rounds to infinity.
+        0x7c00
+    } else if magnitude < 2f64.powi(-14) {
+        // Subnormal: scaling by a power of two is exact, leaving a single rounding step. This may
+        // round up into the smallest normal value, which has the same encoding.
+        (magnitude * 2f64.powi(24)).round_ties_even() as u16
+    } else {
+        let exponent = ((bits >> 52) & 0x7ff) as u16 + 15 - 1023;
+        let mantissa = bits & ((1 << 52) - 1);
+        let truncated = (mantissa >> 42) as u16;
+        let remainder = mantissa & ((1 << 42) - 1);
+        let halfway = 1 << 41;
+        let round_up = remainder > halfway || (remainder == halfway && truncated & 1 == 1);
+        // A carry out of the mantissa correctly increments the exponent.
+        ((exponent << 10) | truncated) + round_up as u16
+    };
+    Ieee16::with_bits(sign | half)
+}
+
+/// Apply `op` to a half-precision float by way of `f64`.
+fn unary_f16(x: Ieee16, op: fn(f64) -> f64) -> DataValue {
+    DataValue::F16(f64_to_f16(op(f16_to_f64(x))))
+}
+
+/// Apply `op` to two half-precision floats by way of `f64`. The result of `+`, `-`, `*` and `/`
+/// on `f64`s is rounded to a half-precision float as if it was computed at that precision, since
+/// `f64` has more than twice the precision of `f16`.
+fn binary_f16(x: Ieee16, y: Ieee16, op: fn(f64, f64) -> f64) -> DataValue {
+    DataValue::F16(f64_to_f16(op(f16_to_f64(x), f16_to_f64(y))))
+}
+
 impl DataValueExt for DataValue {
     fn int(n: i128, ty: Type) -> ValueResult<Self> {
         if ty.is_vector() {
@@ -288,6 +347,7 @@ impl DataValueExt for DataValue {
     fn float(bits: u64, ty: Type) -> ValueResult<Self> {
         match ty {
             types::F32 => Ok(DataValue::F32(Ieee32::with_bits(u32::try_from(bits)?))),
+            types::F16 => Ok(DataValue::F16(Ieee16::with_bits(u16::try_from(bits)?))),
             types::F64 => Ok(DataValue::F64(Ieee64::with_bits(bits))),
             _ => Err(ValueError::InvalidType(ValueTypeClass::Float, ty)),
         }
@@ -295,6 +355,7 @@ impl DataValueExt for DataValue {
 
     fn into_float(self) -> ValueResult<f64> {
         match self {
+            DataValue::F16(n) => Ok(f16_to_f64(n)),
             DataValue::F32(n) => Ok(n.as_f32() as f64),
             DataValue::F64(n) => Ok(n.as_f64()),
             _ => Err(ValueError::InvalidType(ValueTypeClass::Float, self.ty())),
@@ -310,8 +371,10 @@ impl DataValueExt for DataValue {
 
     fn is_nan(&self) -> ValueResult<bool> {
         match self {
+            DataValue::F16(f) => Ok(f.is_nan()),
             DataValue::F32(f) => Ok(f.is_nan()),
             DataValue::F64(f) => Ok(f.is_nan()),
+            DataValue::F128(f) => Ok(f.is_nan()),
             _ => Err(ValueError::InvalidType(ValueTypeClass::Float, self.ty())),
         }
     }
@@ -393,6 +456,8 @@ impl DataValueExt for DataValue {
                 (DataValue::F64(n), types::I64) => DataValue::I64(n.bits() as i64),
                 (DataValue::F128(n), types::I128) => DataValue::I128(n.bits() as i128),
                 (DataValue::F32(n), types::F64) => DataValue::F64((n.as_f32() as f64).into()),
+                (DataValue::F16(n), types::F32) => DataValue::F32((f16_to_f64(n) as f32).into()),
+                (DataValue::F16(n), types::F64) => DataValue::F64(f16_to_f64(n).into()),
                 (dv, t) if (t.is_int() || t.is_float()) && dv.ty() == t => dv,
                 (dv, _) => unimplemented!("conversion: {} -> {:?}", dv.ty(), kind),
             },
@@ -452,6 +517,9 @@ impl DataValueExt for DataValue {
             },
             ValueConversionKind::RoundNearestEven(ty) => match (self, ty) {
                 (DataValue::F64(n), types::F32) => DataValue::F32(Ieee32::from(n.as_f64() as f32)),
+                // Every `f32` is exactly representable as an `f64`, so this rounds only once.
+                (DataValue::F32(n), types::F16) => DataValue::F16(f64_to_f16(n.as_f32() as f64)),
+                (DataValue::F64(n), types::F16) => DataValue::F16(f64_to_f16(n.as_f64())),
                 (s, _) => unimplemented!("conversion: {} -> {:?}", s.ty(), kind),
             },
             ValueConversionKind::ToBoolean => match self.ty() {
@@ -478,8 +546,10 @@ impl DataValueExt for DataValue {
 
     fn is_negative(&self) -> ValueResult<bool> {
         match self {
+            DataValue::F16(f) => Ok(f.is_negative()),
             DataValue::F32(f) => Ok(f.is_negative()),
             DataValue::F64(f) => Ok(f.is_negative()),
+            DataValue::F128(f) => Ok(f.is_negative()),
             _ => Err(ValueError::InvalidType(ValueTypeClass::Float, self.ty())),
         }
     }
@@ -542,6 +612,9 @@ impl DataValueExt for DataValue {
     }
 
     fn add(self, other: Self) -> ValueResult<Self> {
+        if let (DataValue::F16(a), DataValue::F16(b)) = (&self, &other) {
+            return Ok(binary_f16(*a, *b, |a, b| a + b));
+        }
         if self.is_float() {
             binary_match!(+(self, other); [F32, F64])
         } else {
@@ -550,6 +623,9 @@ impl DataValueExt for DataValue {
     }
 
     fn sub(self, other: Self) -> ValueResult<Self> {
+        if let (DataValue::F16(a), DataValue::F16(b)) = (&self, &other) {
+            return Ok(binary_f16(*a, *b, |a, b| a - b));
+        }
         if self.is_float() {
             binary_match!(-(self, other); [F32, F64])
         } else {
@@ -558,6 +634,9 @@ impl DataValueExt for DataValue {
     }
 
     fn mul(self, other: Self) -> ValueResult<Self> {
+        if let (DataValue::F16(a), DataValue::F16(b)) = (&self, &other) {
+            return Ok(binary_f16(*a, *b, |a, b| a * b));
+        }
         if self.is_float() {
             binary_match!(*(self, other); [F32, F64])
         } else {
@@ -566,6 +645,9 @@ impl DataValueExt for DataValue {
     }
 
     fn sdiv(self, other: Self) -> ValueResult<Self> {
+        if let (DataValue::F16(a), DataValue::F16(b)) = (&self, &other) {
+            return Ok(binary_f16(*a, *b, |a, b| a / b));
+        }
         if self.is_float() {
             return binary_match!(/(self, other); [F32, F64]);
         }
@@ -602,16 +684,16 @@ impl DataValueExt for DataValue {
     fn srem(self, other: Self) -> ValueResult<Self> {
         let denominator = other.clone().into_int_signed()?;
 
-        // Check if we are dividing INT_MIN / -1. This causes an integer overflow trap.
-        let min = DataValueExt::int(1i128 << (self.ty().bits() - 1), self.ty())?;
-        if self == min && denominator == -1 {
-            return Err(ValueError::IntegerOverflow);
-        }
-
         if denominator == 0 {
             return Err(ValueError::IntegerDivisionByZero);
         }
 
+        // The remainder of any division by -1 is zero; computing it directly would overflow for
+        // INT_MIN.
+        if denominator == -1 {
+            return DataValueExt::int(0, self.ty());
+        }
+
         binary_match!(%(&self, &other); [I8, I16, I32, I64, I128])
     }
 
@@ -626,11 +708,17 @@ impl DataValueExt for DataValue {
     }
 
     fn sqrt(self) -> ValueResult<Self> {
+        if let DataValue::F16(a) = self {
+            return Ok(unary_f16(a, f64::sqrt));
+        }
         unary_match!(sqrt(&self); [F32, F64]; [Ieee32, Ieee64])
     }
 
     fn fma(self, b: Self, c: Self) -> ValueResult<Self> {
         match (self, b, c) {
+            (DataValue::F16(a), DataValue::F16(b), DataValue::F16(c)) => Ok(DataValue::F16(
+                f64_to_f16(f16_to_f64(a).mul_add(f16_to_f64(b), f16_to_f64(c))),
+            )),
             (DataValue::F32(a), DataValue::F32(b), DataValue::F32(c)) => {
                 // The `fma` function for `x86_64-pc-windows-gnu` is incorrect. Use `libm`'s instead.
                 // See: https://github.com/bytecodealliance/wasmtime/issues/4512
@@ -664,7 +752,7 @@ impl DataValueExt for DataValue {
     }
 
     fn abs(self) -> ValueResult<Self> {
-        unary_match!(abs(&self); [F32, F64])
+        unary_match!(abs(&self); [F16, F32, F64, F128])
     }
 
     fn sadd_checked(self, other: Self) -> ValueResult<Option<Self>> {
@@ -700,26 +788,38 @@ impl DataValueExt for DataValue {
     }
 
     fn neg(self) -> ValueResult<Self> {
-        unary_match!(neg(&self); [F32, F64])
+        unary_match!(neg(&self); [F16, F32, F64, F128])
     }
 
     fn copysign(self, sign: Self) -> ValueResult<Self> {
-        binary_match!(copysign(&self, &sign); [F32, F64])
+        binary_match!(copysign(&self, &sign); [F16, F32, F64, F128])
     }
 
     fn ceil(self) -> ValueResult<Self> {
+        if let DataValue::F16(a) = self {
+            return Ok(unary_f16(a, f64::ceil));
+        }
         unary_match!(ceil(&self); [F32, F64])
     }
 
     fn floor(self) -> ValueResult<Self> {
+        if let DataValue::F16(a) = self {
+            return Ok(unary_f16(a, f64::floor));
+        }
         unary_match!(floor(&self); [F32, F64])
     }
 
     fn trunc(self) -> ValueResult<Self> {
+        if let DataValue::F16(a) = self {
+            return Ok(unary_f16(a, f64::trunc));
+        }
         unary_match!(trunc(&self); [F32, F64])
     }
 
     fn nearest(self) -> ValueResult<Self> {
+        if let DataValue::F16(a) = self {
+            return Ok(unary_f16(a, f64::round_ties_even));
+        }
         unary_match!(round_ties_even(&self); [F32, F64])
     }
 
@@ -2,9 +2,10 @@
 
 use clap::{ArgAction, Parser};
 use cranelift_codegen_meta::{generate_isle, isle::get_isle_compilations};
+use std::panic;
 use std::path::PathBuf;
 use std::{env, fs};
-use veri_engine_lib::verify::verify_rules;
+use veri_engine_lib::verify::{verify_rules, verify_rules_report, RuleStatus};
 use veri_engine_lib::Config;
 
 #[derive(Parser)]
@@ -45,6 +46,11 @@ struct Args {
     /// Don't check for distinct possible models
     #[clap(long, action=ArgAction::SetTrue)]
     nodistinct: bool,
+
+    /// Verify every rule containing `term` (by default, every lowering rule)
+    /// and report which rules pass, fail, or are unsupported
+    #[clap(long, action=ArgAction::SetTrue)]
+    report: bool,
 }
 
 impl Args {
@@ -118,6 +124,38 @@ fn main() -> anyhow::Result<()> {
         custom_assumptions: None,
     };
 
-    verify_rules(inputs, &config, &args.widths)
-        .map_err(|e| anyhow::anyhow!("failed to compile ISLE: {:?}", e))
+    if !args.report {
+        return verify_rules(inputs, &config, &args.widths)
+            .map_err(|e| anyhow::anyhow!("failed to compile ISLE: {:?}", e));
+    }
+
+    // Rules the verifier can't handle panic and are reported as unsupported,
+    // so log those panics instead of printing each one.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|info| log::debug!("{}", info)));
+    let reports = verify_rules_report(inputs, &config, &args.widths);
+    panic::set_hook(hook);
+    let reports = reports.map_err(|e| anyhow::anyhow!("failed to compile ISLE: {:?}", e))?;
+    let (mut passed, mut failed, mut unsupported, mut unknown) = (0, 0, 0, 0);
+    for report in &reports {
+        println!("{}", report);
+        match report.status {
+            RuleStatus::Pass => passed += 1,
+            RuleStatus::Fail(_) => failed += 1,
+            RuleStatus::Unsupported(_) => unsupported += 1,
+            RuleStatus::Unknown => unknown += 1,
+        }
+    }
+    println!(
+        "\n{} rules: {} passed, {} failed, {} unsupported, {} unknown",
+        reports.len(),
+        passed,
+        failed,
+        unsupported,
+        unknown
+    );
+    if failed > 0 {
+        anyhow::bail!("{} rules failed verification", failed);
+    }
+    Ok(())
 }
@@ -1632,7 +1632,9 @@ pub fn run_solver(
     }
 
     let mut solver = easy_smt::ContextBuilder::new()
-        .replay_file(Some(std::fs::File::create("dynamic_widths.smt2").unwrap()))
+        .replay_file(Some(
+            std::fs::File::create(std::env::temp_dir().join("dynamic_widths.smt2")).unwrap(),
+        ))
         .solver("z3", ["-smt2", "-in"])
         .build()
         .unwrap();
@@ -1822,7 +1824,9 @@ pub fn run_solver_with_static_widths(
 ) -> VerificationResult {
     // Declare variables again, this time with all static widths
     let mut solver = easy_smt::ContextBuilder::new()
-        .replay_file(Some(std::fs::File::create("static_widths.smt2").unwrap()))
+        .replay_file(Some(
+            std::fs::File::create(std::env::temp_dir().join("static_widths.smt2")).unwrap(),
+        ))
         .solver("z3", ["-smt2", "-in"])
         .build()
         .unwrap();
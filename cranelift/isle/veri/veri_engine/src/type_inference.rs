@@ -158,7 +158,7 @@ fn convert_type(aty: &annotation_ir::Type) -> veri_ir::Type {
     }
 }
 
-fn type_annotations_using_rule<'a>(
+pub(crate) fn type_annotations_using_rule<'a>(
     rule: &'a isle::sema::Rule,
     annotation_env: &'a AnnotationEnv,
     typeenv: &'a TypeEnv,
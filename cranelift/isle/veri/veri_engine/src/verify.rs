@@ -1,18 +1,51 @@
-use crate::type_inference::type_rules_with_term_and_types;
+use crate::type_inference::{type_annotations_using_rule, type_rules_with_term_and_types};
 use crate::Config;
 use cranelift_isle::error::Errors;
+use cranelift_isle::files::Files;
 use cranelift_isle::{self as isle};
 use isle::compile::create_envs;
-use isle::sema::{Pattern, RuleId, TermEnv, TypeEnv};
-use std::collections::HashMap;
+use isle::sema::{Expr, Pattern, Rule, RuleId, TermEnv, TermId, TypeEnv};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
 
-use crate::annotations::parse_annotations;
+use crate::annotations::{parse_annotations, AnnotationEnv};
 use crate::solver::run_solver;
 use crate::type_inference::RuleSemantics;
 use crate::{interp::Context, termname::pattern_contains_termname};
 use veri_ir::{ConcreteTest, TermSignature, VerificationResult};
 
+/// Only keep the type instantiations whose canonical type is one of `widths`, if given.
+fn filter_widths(types: Vec<TermSignature>, widths: &Option<Vec<String>>) -> Vec<TermSignature> {
+    let Some(widths) = widths else {
+        return types;
+    };
+    let mut width_types = Vec::new();
+
+    for w in widths {
+        let width_type = match w.as_str() {
+            "I8" => veri_ir::Type::BitVector(Some(8)),
+            "I16" => veri_ir::Type::BitVector(Some(16)),
+            "I32" => veri_ir::Type::BitVector(Some(32)),
+            "I64" => veri_ir::Type::BitVector(Some(64)),
+            _ => panic!("Invalid width type: {}", w),
+        };
+        width_types.push(width_type);
+    }
+
+    types
+        .into_iter()
+        .filter(|t| {
+            if let Some(canonical_type) = &t.canonical_type {
+                width_types.contains(canonical_type)
+            } else {
+                false
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
 pub fn verify_rules(
     inputs: Vec<PathBuf>,
     config: &Config,
@@ -31,33 +64,7 @@ pub fn verify_rules(
         .unwrap_or_else(|| panic!("Missing term type instantiation for {}", config.term))
         .clone();
 
-    let types_filtered = if let Some(widths) = widths {
-        let mut width_types = Vec::new();
-
-        for w in widths {
-            let width_type = match w.as_str() {
-                "I8" => veri_ir::Type::BitVector(Some(8)),
-                "I16" => veri_ir::Type::BitVector(Some(16)),
-                "I32" => veri_ir::Type::BitVector(Some(32)),
-                "I64" => veri_ir::Type::BitVector(Some(64)),
-                _ => panic!("Invalid width type: {}", w),
-            };
-            width_types.push(width_type);
-        }
-
-        types
-            .into_iter()
-            .filter(|t| {
-                if let Some(canonical_type) = &t.canonical_type {
-                    width_types.contains(canonical_type)
-                } else {
-                    false
-                }
-            })
-            .collect::<Vec<_>>()
-    } else {
-        types
-    };
+    let types_filtered = filter_widths(types, widths);
 
     for type_instantiation in types_filtered {
         let type_sols = type_rules_with_term_and_types(
@@ -134,3 +141,333 @@ pub fn verify_rules_for_term(
         panic!("No rules checked!")
     }
 }
+
+/// The outcome of verifying a single rule for every type instantiation of its term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleStatus {
+    /// The rule verified for every type instantiation it applies to.
+    Pass,
+    /// The solver found a problem with the rule, described by the message.
+    Fail(String),
+    /// The rule can't be verified yet, for the reason described by the message.
+    Unsupported(String),
+    /// The solver couldn't decide, or wasn't run.
+    Unknown,
+}
+
+impl fmt::Display for RuleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleStatus::Pass => write!(f, "pass"),
+            RuleStatus::Fail(_) => write!(f, "fail"),
+            RuleStatus::Unsupported(_) => write!(f, "unsupported"),
+            RuleStatus::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// The verification outcome for a single rule.
+#[derive(Clone, Debug)]
+pub struct RuleReport {
+    /// The rule's name, or its source location if it is unnamed.
+    pub rule: String,
+    /// The term whose type instantiations the rule was verified with, if any.
+    pub term: Option<String>,
+    pub status: RuleStatus,
+}
+
+impl fmt::Display for RuleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<12} {:<12} {}",
+            self.status.to_string(),
+            self.term.as_deref().unwrap_or("-"),
+            self.rule
+        )?;
+        match &self.status {
+            RuleStatus::Fail(msg) | RuleStatus::Unsupported(msg) => write!(f, ": {msg}"),
+            RuleStatus::Pass | RuleStatus::Unknown => Ok(()),
+        }
+    }
+}
+
+/// Verify every rule whose left-hand side contains `config.term` (by default,
+/// every `lower` rule), reporting whether each rule passed, failed, or could
+/// not be verified.
+///
+/// Unlike `verify_rules`, this does not stop at the first failure. Each rule is
+/// verified with the type instantiations of the outermost term on its
+/// left-hand side that has any, e.g. `iadd` in
+/// `(lower (has_type ty (iadd x y)))`.
+///
+/// Type inference and the solver interface panic on constructs they don't
+/// support; such panics are caught and the rule is reported as unsupported.
+/// The panic hook is left alone, so callers that don't want these panics
+/// printed should install their own.
+pub fn verify_rules_report(
+    inputs: Vec<PathBuf>,
+    config: &Config,
+    widths: &Option<Vec<String>>,
+) -> Result<Vec<RuleReport>, Errors> {
+    let files = Files::from_paths(&inputs).map_err(|(path, err)| {
+        Errors::from_io(err, format!("cannot read file {}", path.display()))
+    })?;
+    let (typeenv, termenv, defs) = create_envs(inputs)?;
+    let annotation_env = parse_annotations(&defs, &termenv, &typeenv);
+
+    let mut reports = vec![];
+    for rule in &termenv.rules {
+        let root = Pattern::Term(
+            cranelift_isle::sema::TypeId(0),
+            rule.root_term,
+            rule.args.clone(),
+        );
+        if !pattern_contains_termname(&root, &config.term, &termenv, &typeenv) {
+            continue;
+        }
+        let name = rule
+            .name
+            .map(|name| typeenv.syms[name.index()].clone())
+            .unwrap_or_else(|| rule.pos.pretty_print_line(&files));
+        if let Some(names) = &config.names {
+            if !names.contains(&name) {
+                continue;
+            }
+        }
+        log::debug!("Verifying rule: {}", name);
+
+        let term = instantiated_term(&root, &annotation_env);
+        let status = match term {
+            Some(term) => verify_rule_report(
+                rule,
+                term,
+                &termenv,
+                &typeenv,
+                &annotation_env,
+                config,
+                widths,
+            ),
+            None => RuleStatus::Unsupported(
+                "no term on the left-hand side has a type instantiation".to_string(),
+            ),
+        };
+        reports.push(RuleReport {
+            rule: name,
+            term: term.map(|t| typeenv.syms[termenv.terms[t.index()].name.index()].clone()),
+            status,
+        });
+    }
+
+    Ok(reports)
+}
+
+/// Verify `rule` for every type instantiation of `term`.
+fn verify_rule_report(
+    rule: &Rule,
+    term: TermId,
+    termenv: &TermEnv,
+    typeenv: &TypeEnv,
+    annotation_env: &AnnotationEnv,
+    config: &Config,
+    widths: &Option<Vec<String>>,
+) -> RuleStatus {
+    let term_name = typeenv.syms[termenv.terms[term.index()].name.index()].clone();
+    let rule_config = Config {
+        term: term_name.clone(),
+        names: None,
+        distinct_check: config.distinct_check,
+        custom_verification_condition: None,
+        custom_assumptions: None,
+    };
+
+    let types = filter_widths(annotation_env.instantiations_map[&term].clone(), widths);
+    let mut results = vec![];
+    for types in types {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let rule_sem = type_annotations_using_rule(
+                rule,
+                annotation_env,
+                typeenv,
+                termenv,
+                &term_name,
+                &types,
+                &None,
+            )?;
+            Some(run_solver(
+                &rule_sem,
+                rule,
+                termenv,
+                typeenv,
+                &None,
+                &rule_config,
+                &types,
+            ))
+        }));
+        match result {
+            Ok(Some(result)) => results.push((types, result)),
+            Ok(None) => {
+                let missing = unannotated_terms(rule, termenv, typeenv, annotation_env);
+                return RuleStatus::Unsupported(if missing.is_empty() {
+                    "type inference failed".to_string()
+                } else {
+                    format!(
+                        "missing specs for {}",
+                        missing.into_iter().collect::<Vec<_>>().join(", ")
+                    )
+                });
+            }
+            Err(payload) => {
+                let msg = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                let msg = msg.lines().next().unwrap_or_default();
+                return RuleStatus::Unsupported(format!("verifier error: {msg}"));
+            }
+        }
+    }
+
+    let width = |types: &TermSignature| match types.canonical_type {
+        Some(veri_ir::Type::BitVector(Some(w))) => format!("width {w}"),
+        _ => format!("{types}"),
+    };
+    let mut applicable = false;
+    let mut unknown = false;
+    for (types, result) in &results {
+        match result {
+            VerificationResult::Success => applicable = true,
+            VerificationResult::InapplicableRule => {}
+            VerificationResult::Unknown => unknown = true,
+            VerificationResult::Failure(_) => {
+                return RuleStatus::Fail(format!("counterexample at {}", width(types)))
+            }
+            VerificationResult::NoDistinctModels => {
+                return RuleStatus::Fail(format!("no distinct models at {}", width(types)))
+            }
+        }
+    }
+    if unknown {
+        RuleStatus::Unknown
+    } else if applicable {
+        RuleStatus::Pass
+    } else {
+        RuleStatus::Unsupported("not applicable to any type instantiation".to_string())
+    }
+}
+
+/// Find the outermost term in `pat` that has type instantiations.
+fn instantiated_term(pat: &Pattern, annotation_env: &AnnotationEnv) -> Option<TermId> {
+    match pat {
+        Pattern::Var(..)
+        | Pattern::ConstInt(..)
+        | Pattern::ConstBool(..)
+        | Pattern::ConstPrim(..)
+        | Pattern::Wildcard(..) => None,
+        Pattern::BindPattern(_, _, pat) => instantiated_term(pat, annotation_env),
+        Pattern::Term(_, term, args) => {
+            if annotation_env.instantiations_map.contains_key(term) {
+                return Some(*term);
+            }
+            args.iter()
+                .find_map(|p| instantiated_term(p, annotation_env))
+        }
+        Pattern::And(_, children) => children
+            .iter()
+            .find_map(|p| instantiated_term(p, annotation_env)),
+    }
+}
+
+/// Collect the names of all terms used by `rule` that have no spec.
+fn unannotated_terms(
+    rule: &Rule,
+    termenv: &TermEnv,
+    typeenv: &TypeEnv,
+    annotation_env: &AnnotationEnv,
+) -> BTreeSet<String> {
+    fn term(
+        term: &TermId,
+        termenv: &TermEnv,
+        typeenv: &TypeEnv,
+        annotation_env: &AnnotationEnv,
+        missing: &mut BTreeSet<String>,
+    ) {
+        if !annotation_env.annotation_map.contains_key(term) {
+            let name = termenv.terms[term.index()].name;
+            missing.insert(typeenv.syms[name.index()].clone());
+        }
+    }
+
+    fn pattern(
+        pat: &Pattern,
+        termenv: &TermEnv,
+        typeenv: &TypeEnv,
+        annotation_env: &AnnotationEnv,
+        missing: &mut BTreeSet<String>,
+    ) {
+        match pat {
+            Pattern::Var(..)
+            | Pattern::ConstInt(..)
+            | Pattern::ConstBool(..)
+            | Pattern::ConstPrim(..)
+            | Pattern::Wildcard(..) => {}
+            Pattern::BindPattern(_, _, pat) => {
+                pattern(pat, termenv, typeenv, annotation_env, missing)
+            }
+            Pattern::Term(_, t, args) => {
+                term(t, termenv, typeenv, annotation_env, missing);
+                for arg in args {
+                    pattern(arg, termenv, typeenv, annotation_env, missing);
+                }
+            }
+            Pattern::And(_, children) => {
+                for child in children {
+                    pattern(child, termenv, typeenv, annotation_env, missing);
+                }
+            }
+        }
+    }
+
+    fn expr(
+        e: &Expr,
+        termenv: &TermEnv,
+        typeenv: &TypeEnv,
+        annotation_env: &AnnotationEnv,
+        missing: &mut BTreeSet<String>,
+    ) {
+        match e {
+            Expr::Var(..) | Expr::ConstBool(..) | Expr::ConstInt(..) | Expr::ConstPrim(..) => {}
+            Expr::Term(_, t, args) => {
+                term(t, termenv, typeenv, annotation_env, missing);
+                for arg in args {
+                    expr(arg, termenv, typeenv, annotation_env, missing);
+                }
+            }
+            Expr::Let { bindings, body, .. } => {
+                for (_, _, binding) in bindings {
+                    expr(binding, termenv, typeenv, annotation_env, missing);
+                }
+                expr(body, termenv, typeenv, annotation_env, missing);
+            }
+        }
+    }
+
+    let mut missing = BTreeSet::new();
+    term(
+        &rule.root_term,
+        termenv,
+        typeenv,
+        annotation_env,
+        &mut missing,
+    );
+    for arg in &rule.args {
+        pattern(arg, termenv, typeenv, annotation_env, &mut missing);
+    }
+    for iflet in &rule.iflets {
+        pattern(&iflet.lhs, termenv, typeenv, annotation_env, &mut missing);
+        expr(&iflet.rhs, termenv, typeenv, annotation_env, &mut missing);
+    }
+    expr(&rule.rhs, termenv, typeenv, annotation_env, &mut missing);
+    missing
+}
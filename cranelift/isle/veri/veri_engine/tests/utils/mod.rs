@@ -35,7 +35,8 @@ static INIT: Once = Once::new();
 
 pub fn get_isle_files(name: &str) -> Vec<std::path::PathBuf> {
     let cur_dir = env::current_dir().expect("Can't access current working directory");
-    let gen_dir = cur_dir.join("test_output");
+    // Keep generated ISLE out of the source tree.
+    let gen_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("test_output");
     INIT.call_once(|| {
         // Logger
         env_logger::init();
@@ -755,6 +755,52 @@ fn test_named_slow_udiv() {
     )
 }
 
+#[test]
+fn test_named_udiv_concrete() {
+    test_concrete_aarch64_rule_with_lhs_termname(
+        "udiv",
+        "udiv",
+        ConcreteTest {
+            termname: "udiv".to_string(),
+            args: vec![
+                ConcreteInput {
+                    literal: "#b11111110".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(8)),
+                },
+                ConcreteInput {
+                    literal: "#b00110001".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(8)),
+                },
+            ],
+            output: ConcreteInput {
+                literal: "#b00000101".to_string(),
+                ty: veri_ir::Type::BitVector(Some(8)),
+            },
+        },
+    );
+    test_concrete_aarch64_rule_with_lhs_termname(
+        "udiv",
+        "udiv",
+        ConcreteTest {
+            termname: "udiv".to_string(),
+            args: vec![
+                ConcreteInput {
+                    literal: "#b11111111111111111111111111111110".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(32)),
+                },
+                ConcreteInput {
+                    literal: "#b00000000000000000000000000000111".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(32)),
+                },
+            ],
+            output: ConcreteInput {
+                literal: "#b00100100100100100100100100100100".to_string(),
+                ty: veri_ir::Type::BitVector(Some(32)),
+            },
+        },
+    )
+}
+
 #[test]
 fn test_broken_udiv() {
     test_from_file_with_lhs_termname_simple(
@@ -805,6 +851,52 @@ fn test_named_slow_sdiv_base_case() {
     )
 }
 
+#[test]
+fn test_named_sdiv_base_case_concrete() {
+    test_concrete_aarch64_rule_with_lhs_termname(
+        "sdiv_base_case",
+        "sdiv",
+        ConcreteTest {
+            termname: "sdiv".to_string(),
+            args: vec![
+                ConcreteInput {
+                    literal: "#b1111111110011100".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(16)),
+                },
+                ConcreteInput {
+                    literal: "#b0000000000000111".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(16)),
+                },
+            ],
+            output: ConcreteInput {
+                literal: "#b1111111111110010".to_string(),
+                ty: veri_ir::Type::BitVector(Some(16)),
+            },
+        },
+    );
+    test_concrete_aarch64_rule_with_lhs_termname(
+        "sdiv_base_case",
+        "sdiv",
+        ConcreteTest {
+            termname: "sdiv".to_string(),
+            args: vec![
+                ConcreteInput {
+                    literal: "#b11111111111111111111111111111001".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(32)),
+                },
+                ConcreteInput {
+                    literal: "#b00000000000000000000000000000010".to_string(),
+                    ty: veri_ir::Type::BitVector(Some(32)),
+                },
+            ],
+            output: ConcreteInput {
+                literal: "#b11111111111111111111111111111101".to_string(),
+                ty: veri_ir::Type::BitVector(Some(32)),
+            },
+        },
+    )
+}
+
 #[test]
 fn test_named_sdiv_safe_divisor() {
     test_aarch64_rule_with_lhs_termname_simple(
@@ -2880,6 +2972,132 @@ fn test_named_x64_iadd_base_case_32_or_64_lea() {
     )
 }
 
+#[test]
+fn test_named_x64_iadd_base_case_8_or_16() {
+    test_x64_rule_with_lhs_termname_simple(
+        "iadd_base_case_8_or_16",
+        "iadd",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::InapplicableRule),
+            (Bitwidth::I64, VerificationResult::InapplicableRule),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_isub_base_case() {
+    test_x64_rule_with_lhs_termname_simple(
+        "isub_base_case",
+        "isub",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_band_fits_in_64() {
+    test_x64_rule_with_lhs_termname_simple(
+        "band_fits_in_64",
+        "band",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_bor_fits_in_64() {
+    test_x64_rule_with_lhs_termname_simple(
+        "bor_fits_in_64",
+        "bor",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_bxor_fits_in_64() {
+    test_x64_rule_with_lhs_termname_simple(
+        "bxor_fits_in_64",
+        "bxor",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_ineg_base_case() {
+    test_x64_rule_with_lhs_termname_simple(
+        "ineg_base_case",
+        "ineg",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_bnot_fits_in_64() {
+    test_x64_rule_with_lhs_termname_simple(
+        "bnot_fits_in_64",
+        "bnot",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_imul_base_case_8() {
+    test_x64_rule_with_lhs_termname_simple(
+        "imul_base_case_8",
+        "imul",
+        vec![
+            (Bitwidth::I8, VerificationResult::Success),
+            (Bitwidth::I16, VerificationResult::InapplicableRule),
+            (Bitwidth::I32, VerificationResult::InapplicableRule),
+            (Bitwidth::I64, VerificationResult::InapplicableRule),
+        ],
+    )
+}
+
+#[test]
+fn test_named_x64_imul_base_case_16_to_64() {
+    test_x64_rule_with_lhs_termname_simple(
+        "imul_base_case_16_to_64",
+        "imul",
+        vec![
+            (Bitwidth::I8, VerificationResult::InapplicableRule),
+            (Bitwidth::I16, VerificationResult::Success),
+            (Bitwidth::I32, VerificationResult::Success),
+            (Bitwidth::I64, VerificationResult::Success),
+        ],
+    )
+}
+
 #[test]
 fn test_named_x64_to_amode_add_base_case() {
     test_x64_rule_with_lhs_termname_simple(
@@ -11,6 +11,8 @@ use cranelift_module::{
     DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
     ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
+#[cfg(feature = "debug-info")]
+use cranelift_module::{DebugRelocTarget, DebugSection};
 use log::info;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -20,6 +22,8 @@ use std::ptr;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicPtr, Ordering};
 use target_lexicon::PointerWidth;
+#[cfg(feature = "debug-info")]
+use wasmtime_jit_debug::gdb_jit_int::GdbJitImageRegistration;
 
 const WRITABLE_DATA_ALIGNMENT: u64 = 0x8;
 const READONLY_DATA_ALIGNMENT: u64 = 0x1;
@@ -30,6 +34,7 @@ pub struct JITBuilder {
     symbols: HashMap<String, SendWrapper<*const u8>>,
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    hotswap_enabled: bool,
 }
 
 impl JITBuilder {
@@ -93,6 +98,7 @@ impl JITBuilder {
             symbols,
             lookup_symbols,
             libcall_names,
+            hotswap_enabled: false,
         }
     }
 
@@ -143,6 +149,20 @@ impl JITBuilder {
         self.lookup_symbols.push(symbol_lookup_fn);
         self
     }
+
+    /// Enable or disable hotswap support.
+    ///
+    /// With hotswap support, all calls between functions in the module go
+    /// through their PLT entries, so that functions can be redefined after
+    /// they have been finalized. See
+    /// [`JITModule::prepare_for_function_redefine`] for more information.
+    ///
+    /// Enabling hotswap support requires PIC code, and the PLT is currently
+    /// only supported on x86_64.
+    pub fn hotswap(&mut self, enabled: bool) -> &mut Self {
+        self.hotswap_enabled = enabled;
+        self
+    }
 }
 
 /// A pending update to the GOT.
@@ -172,6 +192,7 @@ pub struct JITModule {
     symbols: RefCell<HashMap<String, SendWrapper<*const u8>>>,
     lookup_symbols: Vec<Box<dyn Fn(&str) -> Option<*const u8> + Send>>,
     libcall_names: Box<dyn Fn(ir::LibCall) -> String + Send + Sync>,
+    hotswap_enabled: bool,
     memory: MemoryHandle,
     declarations: ModuleDeclarations,
     function_got_entries: SecondaryMap<FuncId, Option<SendWrapper<NonNull<AtomicPtr<u8>>>>>,
@@ -181,11 +202,20 @@ pub struct JITModule {
     libcall_plt_entries: HashMap<ir::LibCall, SendWrapper<NonNull<[u8; 16]>>>,
     compiled_functions: SecondaryMap<FuncId, Option<CompiledBlob>>,
     compiled_data_objects: SecondaryMap<DataId, Option<CompiledBlob>>,
+    /// Previous definitions of redefined functions, which may still be
+    /// executing.
+    replaced_functions: Vec<(FuncId, CompiledBlob)>,
     functions_to_finalize: Vec<FuncId>,
     data_objects_to_finalize: Vec<DataId>,
 
     /// Updates to the GOT awaiting relocations to be made and region protections to be set
     pending_got_updates: Vec<GotUpdate>,
+
+    /// Debug info registered with debuggers along with the functions it
+    /// describes. It's unregistered again when any of those functions is
+    /// freed or redefined, or when this module is dropped.
+    #[cfg(feature = "debug-info")]
+    debug_registrations: Vec<(Vec<FuncId>, GdbJitImageRegistration)>,
 }
 
 /// A handle to allow freeing memory allocated by the `Module`.
@@ -210,6 +240,195 @@ impl JITModule {
         self.memory.writable.free_memory();
     }
 
+    /// Allow a single future `define_function` on a previously defined and
+    /// finalized function. This allows for hot code swapping, e.g. for
+    /// languages which let their users redefine functions interactively.
+    ///
+    /// Once the new definition is finalized, the function's GOT entry (see
+    /// [`JITModule::read_got_entry`]) points to it, and so do all calls from
+    /// other functions in this module. The previous definition is kept
+    /// alive, as it may still be executing, until
+    /// [`JITModule::free_replaced_functions`] is called.
+    ///
+    /// This requires hotswap support to be enabled first using
+    /// [`JITBuilder::hotswap`].
+    pub fn prepare_for_function_redefine(&mut self, func_id: FuncId) -> ModuleResult<()> {
+        assert!(self.hotswap_enabled, "Hotswap support is not enabled");
+        let decl = self.declarations.get_function_decl(func_id);
+        if !decl.linkage.is_definable() {
+            return Err(ModuleError::InvalidImportDefinition(
+                decl.linkage_name(func_id).into_owned(),
+            ));
+        }
+
+        if self.compiled_functions[func_id].is_none()
+            || self.functions_to_finalize.contains(&func_id)
+        {
+            return Err(ModuleError::Backend(anyhow::anyhow!(
+                "tried to redefine function {} which isn't defined and finalized",
+                decl.linkage_name(func_id),
+            )));
+        }
+
+        let previous = self.compiled_functions[func_id].take().unwrap();
+        self.replaced_functions.push((func_id, previous));
+        self.unregister_debug_info(func_id);
+        Ok(())
+    }
+
+    /// Free the code of all previous definitions of functions which have
+    /// been redefined using [`JITModule::prepare_for_function_redefine`].
+    ///
+    /// # Safety
+    ///
+    /// None of the previous definitions may be executing, and no pointers
+    /// to them retrieved with [`JITModule::get_finalized_function`] or
+    /// [`JITModule::read_got_entry`] may be called afterwards.
+    pub unsafe fn free_replaced_functions(&mut self) {
+        for (_, previous) in self.replaced_functions.drain(..) {
+            self.memory.code.free(previous.ptr);
+        }
+    }
+
+    /// Free the code of a function's finalized definition, if any, and of
+    /// all its previous definitions. The function then becomes undefined,
+    /// and may be defined again.
+    ///
+    /// # Safety
+    ///
+    /// The function must not be executing, and must not be called
+    /// afterwards until it is defined again and finalized, whether through
+    /// a pointer retrieved from this module or from another function. Unless
+    /// hotswap support is enabled (see [`JITBuilder::hotswap`]), other
+    /// functions in this module may call it directly, so they must not be
+    /// called either.
+    pub unsafe fn free_function(&mut self, func_id: FuncId) {
+        assert!(
+            !self.functions_to_finalize.contains(&func_id),
+            "function not yet finalized"
+        );
+        let memory = &mut self.memory.code;
+        self.replaced_functions.retain(|(id, previous)| {
+            if *id == func_id {
+                memory.free(previous.ptr);
+            }
+            *id != func_id
+        });
+        if let Some(compiled) = self.compiled_functions[func_id].take() {
+            memory.free(compiled.ptr);
+        }
+        self.unregister_debug_info(func_id);
+        if let Some(got_entry) = self.function_got_entries[func_id] {
+            got_entry
+                .0
+                .as_ref()
+                .store(ptr::null_mut(), Ordering::SeqCst);
+        }
+    }
+
+    /// Free the memory of a finalized data object. The data object then
+    /// becomes undefined, and may be defined again.
+    ///
+    /// # Safety
+    ///
+    /// The data object must not be accessed afterwards until it is defined
+    /// again and finalized, whether through a pointer retrieved from this
+    /// module or from a function or data object which refers to it.
+    pub unsafe fn free_data_object(&mut self, data_id: DataId) {
+        assert!(
+            !self.data_objects_to_finalize.contains(&data_id),
+            "data object not yet finalized"
+        );
+        let compiled = self.compiled_data_objects[data_id]
+            .take()
+            .expect("data object must be defined before it can be freed");
+        if self.declarations.get_data_decl(data_id).writable {
+            self.memory.writable.free(compiled.ptr);
+        } else {
+            self.memory.readonly.free(compiled.ptr);
+        }
+        if let Some(got_entry) = self.data_object_got_entries[data_id] {
+            got_entry
+                .0
+                .as_ref()
+                .store(ptr::null_mut(), Ordering::SeqCst);
+        }
+    }
+
+    /// Register DWARF debug info for finalized functions of this module, as
+    /// produced by a [`cranelift_module::DebugInfoBuilder`], with debuggers
+    /// through the GDB JIT interface, which both GDB and LLDB support.
+    ///
+    /// The debug info is wrapped in an in-memory ELF image, with the addresses
+    /// of the functions it describes filled in. It stays registered until this
+    /// module is dropped or its memory is freed, or until any function it
+    /// describes is freed with [`JITModule::free_function`] or redefined with
+    /// [`JITModule::prepare_for_function_redefine`], which unregisters the
+    /// debug info of all functions registered in the same call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any function described by the debug info isn't finalized.
+    #[cfg(feature = "debug-info")]
+    pub fn register_debug_info(&mut self, sections: &[DebugSection]) -> ModuleResult<()> {
+        use object::write::Object;
+        use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+
+        let architecture = match self.isa.triple().architecture {
+            target_lexicon::Architecture::X86_64 => Architecture::X86_64,
+            target_lexicon::Architecture::Aarch64(_) => Architecture::Aarch64,
+            target_lexicon::Architecture::Riscv64(_) => Architecture::Riscv64,
+            target_lexicon::Architecture::S390x => Architecture::S390x,
+            ref arch => {
+                return Err(ModuleError::Backend(anyhow::anyhow!(
+                    "registering debug info is not supported on {arch}"
+                )))
+            }
+        };
+        let endianness = self.isa.endianness();
+        let endian = match endianness {
+            ir::Endianness::Little => Endianness::Little,
+            ir::Endianness::Big => Endianness::Big,
+        };
+
+        let mut image = Object::new(BinaryFormat::Elf, architecture, endian);
+        let mut funcs = Vec::new();
+        for section in sections {
+            for reloc in &section.relocs {
+                if let DebugRelocTarget::Func(func_id) = reloc.target {
+                    if !funcs.contains(&func_id) {
+                        funcs.push(func_id);
+                    }
+                }
+            }
+            let body = section.relocated_body(endianness, |func_id| {
+                self.get_finalized_function(func_id) as u64
+            });
+            let id = image.add_section(
+                Vec::new(),
+                section.name.as_bytes().to_vec(),
+                SectionKind::Debug,
+            );
+            image.append_section_data(id, &body, 1);
+        }
+        let image = image
+            .write()
+            .map_err(|err| ModuleError::Backend(anyhow::anyhow!(err)))?;
+
+        self.debug_registrations
+            .push((funcs, GdbJitImageRegistration::register(image)));
+        Ok(())
+    }
+
+    /// Unregisters all debug info describing `func_id` from debuggers.
+    fn unregister_debug_info(&mut self, func_id: FuncId) {
+        #[cfg(feature = "debug-info")]
+        self.debug_registrations
+            .retain(|(funcs, _)| !funcs.contains(&func_id));
+        #[cfg(not(feature = "debug-info"))]
+        let _ = func_id;
+    }
+
     fn lookup_symbol(&self, name: &str) -> Option<*const u8> {
         match self.symbols.borrow_mut().entry(name.to_owned()) {
             std::collections::hash_map::Entry::Occupied(occ) => Some(occ.get().0),
@@ -300,6 +519,9 @@ impl JITModule {
         match *name {
             ModuleRelocTarget::User { .. } => {
                 let (name, linkage) = if ModuleDeclarations::is_function(name) {
+                    if self.hotswap_enabled {
+                        return self.get_plt_address(name);
+                    }
                     let func_id = FuncId::from_name(name);
                     match &self.compiled_functions[func_id] {
                         Some(compiled) => return compiled.ptr,
@@ -394,8 +616,9 @@ impl JITModule {
 
     /// Returns the address of a finalized function.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual function is used.
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_function`] is called, or the function is redefined and
+    /// [`JITModule::free_replaced_functions`] is called.
     pub fn get_finalized_function(&self, func_id: FuncId) -> *const u8 {
         let info = &self.compiled_functions[func_id];
         assert!(
@@ -409,8 +632,8 @@ impl JITModule {
 
     /// Returns the address and size of a finalized data object.
     ///
-    /// The pointer remains valid until either [`JITModule::free_memory`] is called or in the future
-    /// some way of deallocating this individual data object is used.
+    /// The pointer remains valid until either [`JITModule::free_memory`] or
+    /// [`JITModule::free_data_object`] is called.
     pub fn get_finalized_data(&self, data_id: DataId) -> (*const u8, usize) {
         let info = &self.compiled_data_objects[data_id];
         assert!(
@@ -488,6 +711,12 @@ impl JITModule {
 
     /// Create a new `JITModule`.
     pub fn new(builder: JITBuilder) -> Self {
+        if builder.hotswap_enabled {
+            assert!(
+                builder.isa.flags().is_pic(),
+                "Hotswapping requires PIC code"
+            );
+        }
         let branch_protection =
             if cfg!(target_arch = "aarch64") && use_bti(&builder.isa.isa_flags()) {
                 BranchProtection::BTI
@@ -499,6 +728,7 @@ impl JITModule {
             symbols: RefCell::new(builder.symbols),
             lookup_symbols: builder.lookup_symbols,
             libcall_names: builder.libcall_names,
+            hotswap_enabled: builder.hotswap_enabled,
             memory: MemoryHandle {
                 code: Memory::new(branch_protection),
                 // Branch protection is not applicable to non-executable memory.
@@ -513,9 +743,12 @@ impl JITModule {
             libcall_plt_entries: HashMap::new(),
             compiled_functions: SecondaryMap::new(),
             compiled_data_objects: SecondaryMap::new(),
+            replaced_functions: Vec::new(),
             functions_to_finalize: Vec::new(),
             data_objects_to_finalize: Vec::new(),
             pending_got_updates: Vec::new(),
+            #[cfg(feature = "debug-info")]
+            debug_registrations: Vec::new(),
         };
 
         // Pre-create a GOT and PLT entry for each libcall.
@@ -18,6 +18,9 @@ struct PtrLen {
 
     ptr: *mut u8,
     len: usize,
+
+    /// The number of allocations within this region which haven't been freed.
+    live: usize,
 }
 
 impl PtrLen {
@@ -29,6 +32,7 @@ impl PtrLen {
 
             ptr: ptr::null_mut(),
             len: 0,
+            live: 0,
         }
     }
 
@@ -44,6 +48,7 @@ impl PtrLen {
                 ptr: mmap.as_mut_ptr(),
                 map: Some(mmap),
                 len: alloc_size,
+                live: 0,
             }
         })
     }
@@ -61,6 +66,7 @@ impl PtrLen {
             Ok(Self {
                 ptr,
                 len: alloc_size,
+                live: 0,
             })
         } else {
             Err(io::Error::from(io::ErrorKind::OutOfMemory))
@@ -86,6 +92,7 @@ impl PtrLen {
             Ok(Self {
                 ptr: ptr as *mut u8,
                 len: region::page::ceil(size as *const ()) as usize,
+                live: 0,
             })
         } else {
             Err(io::Error::last_os_error())
@@ -162,6 +169,7 @@ impl Memory {
             // TODO: Ensure overflow is not possible.
             let ptr = unsafe { self.current.ptr.add(self.position) };
             self.position += size;
+            self.current.live += 1;
             return Ok(ptr);
         }
 
@@ -170,6 +178,7 @@ impl Memory {
         // TODO: Allocate more at a time.
         self.current = PtrLen::with_size(size)?;
         self.position = size;
+        self.current.live = 1;
 
         Ok(self.current.ptr)
     }
@@ -258,6 +267,45 @@ impl Memory {
         return iter.filter(|&PtrLen { len, .. }| *len != 0);
     }
 
+    /// Frees a single allocation returned by `allocate`.
+    ///
+    /// Memory is allocated in regions which may contain several allocations,
+    /// so this only returns the memory once all allocations in its region
+    /// have been freed.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `allocate` on this `Memory` and not
+    /// freed since, and must not be used afterwards.
+    pub(crate) unsafe fn free(&mut self, ptr: *const u8) {
+        let contains = |region: &PtrLen| {
+            let start = region.ptr as usize;
+            region.live != 0 && (start..start + region.len).contains(&(ptr as usize))
+        };
+        if contains(&self.current) {
+            self.current.live -= 1;
+            if self.current.live == 0 {
+                // Nothing is allocated from the region anymore, so release it
+                // rather than leaving it to `finish_current`, which may never
+                // be called, e.g. for writable data.
+                self.current = PtrLen::new();
+                self.position = 0;
+            }
+            return;
+        }
+        let region = self
+            .allocations
+            .iter_mut()
+            .find(|region| contains(region))
+            .expect("pointer wasn't allocated by this memory");
+        region.live -= 1;
+        if region.live == 0 {
+            // Leave an empty region in place so that the indices of the
+            // others, and so `already_protected`, remain valid.
+            drop(mem::replace(region, PtrLen::new()));
+        }
+    }
+
     /// Frees all allocated memory regions that would be leaked otherwise.
     /// Likely to invalidate existing function pointers, causing unsafety.
     pub(crate) unsafe fn free_memory(&mut self) {
@@ -274,3 +322,36 @@ impl Drop for Memory {
             .for_each(mem::forget);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the number of regions of `memory` which still hold memory.
+    fn held_regions(memory: &Memory) -> usize {
+        memory
+            .allocations
+            .iter()
+            .chain([&memory.current])
+            .filter(|region| !region.ptr.is_null())
+            .count()
+    }
+
+    #[test]
+    fn free_releases_current_region() {
+        // Writable data objects are allocated like this: their memory is
+        // never protected, so the region they're allocated from stays current.
+        let mut memory = Memory::new(BranchProtection::None);
+        let size = 2 * region::page::size();
+        for _ in 0..10 {
+            let a = memory.allocate(size, 8).unwrap();
+            let b = memory.allocate(8, 8).unwrap();
+            assert_eq!(held_regions(&memory), 2);
+            unsafe {
+                memory.free(b);
+                memory.free(a);
+            }
+            assert_eq!(held_regions(&memory), 0);
+        }
+    }
+}
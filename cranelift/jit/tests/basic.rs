@@ -215,3 +215,223 @@ fn empty_data_object() {
     data.define(Box::new([]));
     module.define_data(data_id, &data).unwrap();
 }
+
+fn define_constant_function(module: &mut JITModule, func_id: FuncId, value: i64) {
+    let mut ctx = module.make_context();
+    ctx.func.signature = module
+        .declarations()
+        .get_function_decl(func_id)
+        .signature
+        .clone();
+    ctx.func.name = UserFuncName::user(0, func_id.as_u32());
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let value = bcx.ins().iconst(types::I64, value);
+        bcx.ins().return_(&[value]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn redefine_function() {
+    let mut builder = JITBuilder::new(default_libcall_names()).unwrap();
+    builder.hotswap(true);
+    let mut module = JITModule::new(builder);
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let callee = module
+        .declare_function("callee", Linkage::Local, &sig)
+        .unwrap();
+    let caller = module
+        .declare_function("caller", Linkage::Local, &sig)
+        .unwrap();
+
+    define_constant_function(&mut module, callee, 1);
+
+    let mut ctx = module.make_context();
+    ctx.func.signature = sig.clone();
+    ctx.func.name = UserFuncName::user(0, caller.as_u32());
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.switch_to_block(block);
+        let local_callee = module.declare_func_in_func(callee, &mut bcx.func);
+        let call = bcx.ins().call(local_callee, &[]);
+        let result = bcx.inst_results(call)[0];
+        bcx.ins().return_(&[result]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(caller, &mut ctx).unwrap();
+    module.finalize_definitions().unwrap();
+
+    let ptr = module.get_finalized_function(caller);
+    let call_caller = unsafe { std::mem::transmute::<*const u8, extern "C" fn() -> i64>(ptr) };
+    assert_eq!(call_caller(), 1);
+
+    module.prepare_for_function_redefine(callee).unwrap();
+    define_constant_function(&mut module, callee, 2);
+    module.finalize_definitions().unwrap();
+    assert_eq!(
+        module.read_got_entry(callee),
+        module.get_finalized_function(callee)
+    );
+    assert_eq!(call_caller(), 2);
+
+    // The caller now only uses the new definition, so the old one can go.
+    unsafe { module.free_replaced_functions() };
+    assert_eq!(call_caller(), 2);
+
+    unsafe { module.free_function(callee) };
+    assert!(module.read_got_entry(callee).is_null());
+    define_constant_function(&mut module, callee, 3);
+    module.finalize_definitions().unwrap();
+    assert_eq!(call_caller(), 3);
+}
+
+#[test]
+fn free_function_and_data() {
+    let Some(isa) = isa() else {
+        return;
+    };
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+
+    let mut sig = module.make_signature();
+    sig.returns.push(AbiParam::new(types::I64));
+    let func_id = module
+        .declare_function("function", Linkage::Local, &sig)
+        .unwrap();
+    let data_id = module
+        .declare_data("data", Linkage::Local, true, false)
+        .unwrap();
+
+    // The same function and data object can be defined and freed again and
+    // again, e.g. by a REPL.
+    for i in 0..100 {
+        define_constant_function(&mut module, func_id, i);
+        let mut data = DataDescription::new();
+        data.define(Box::new(i.to_le_bytes()));
+        module.define_data(data_id, &data).unwrap();
+        module.finalize_definitions().unwrap();
+
+        let ptr = module.get_finalized_function(func_id);
+        let f = unsafe { std::mem::transmute::<*const u8, extern "C" fn() -> i64>(ptr) };
+        assert_eq!(f(), i);
+        let (ptr, size) = module.get_finalized_data(data_id);
+        assert_eq!(size, 8);
+        assert_eq!(unsafe { ptr.cast::<i64>().read_unaligned() }, i);
+
+        unsafe {
+            module.free_function(func_id);
+            module.free_data_object(data_id);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "debug-info")]
+fn register_debug_info() {
+    let isa = match isa() {
+        Some(isa) => isa,
+        None => return,
+    };
+    let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+    let mut debug_info = DebugInfoBuilder::new(module.isa(), "test", "/src", "foo.x");
+    let file = debug_info.add_file("foo.x");
+
+    let mut sig = module.make_signature();
+    sig.params.push(AbiParam::new(types::I64));
+    sig.returns.push(AbiParam::new(types::I64));
+    let func_id = module
+        .declare_function("double", Linkage::Local, &sig)
+        .unwrap();
+
+    let mut ctx = module.make_context();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        bcx.set_srcloc(debug_info.source_loc(file, 2, 5));
+        let x = bcx.block_params(block)[0];
+        bcx.set_val_label(x, ValueLabel::new(0));
+        let y = bcx.ins().iadd(x, x);
+        bcx.ins().return_(&[y]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+
+    let mut info = FunctionDebugInfo::new("double");
+    info.decl = Some((file, 1));
+    info.variables.push(VariableDebugInfo {
+        name: "x".into(),
+        label: ValueLabel::new(0),
+        ty: DebugType::Signed(8),
+        is_parameter: true,
+    });
+    debug_info
+        .add_function(module.isa(), func_id, ctx.compiled_code().unwrap(), &info)
+        .unwrap();
+    let sections = debug_info.finish().unwrap();
+
+    module.finalize_definitions().unwrap();
+    module.register_debug_info(&sections).unwrap();
+    assert_eq!(registered_debug_images(), 1);
+
+    let code = module.get_finalized_function(func_id);
+    let double = unsafe { std::mem::transmute::<*const u8, extern "C" fn(i64) -> i64>(code) };
+    assert_eq!(double(21), 42);
+
+    // Freeing the function unregisters the debug info describing it.
+    unsafe {
+        module.free_function(func_id);
+    }
+    assert_eq!(registered_debug_images(), 0);
+}
+
+/// Returns the number of images registered with debuggers through the GDB JIT
+/// interface.
+#[cfg(feature = "debug-info")]
+fn registered_debug_images() -> usize {
+    #[repr(C)]
+    struct JITCodeEntry {
+        next_entry: *const JITCodeEntry,
+        prev_entry: *const JITCodeEntry,
+        symfile_addr: *const u8,
+        symfile_size: u64,
+    }
+
+    #[repr(C)]
+    struct JITDescriptor {
+        version: u32,
+        action_flag: u32,
+        relevant_entry: *const JITCodeEntry,
+        first_entry: *const JITCodeEntry,
+    }
+
+    unsafe extern "C" {
+        static __jit_debug_descriptor: JITDescriptor;
+    }
+
+    let mut count = 0;
+    unsafe {
+        let mut entry = (*std::ptr::addr_of!(__jit_debug_descriptor)).first_entry;
+        while !entry.is_null() {
+            count += 1;
+            entry = (*entry).next_entry;
+        }
+    }
+    count
+}
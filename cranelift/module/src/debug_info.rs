@@ -0,0 +1,584 @@
+//! Generation of DWARF debug information for functions compiled with Cranelift.
+//!
+//! A [`DebugInfoBuilder`] collects the line tables and variable locations of
+//! the functions in a module, and emits them as DWARF sections which can be
+//! added to an object file or registered with a debugger for JIT-compiled
+//! code.
+//!
+//! Source locations are handed out by the builder: a frontend gets the
+//! [`ir::SourceLoc`] for a file, line and column with
+//! [`DebugInfoBuilder::source_loc`], and passes it to
+//! `FunctionBuilder::set_srcloc` before building the corresponding
+//! instructions. Variables are tracked with value labels, which are set with
+//! `FunctionBuilder::set_val_label`; note that this requires calling
+//! [`ir::Function::collect_debug_info`] before building the function.
+
+use crate::module::FuncId;
+use crate::{HashMap, ModuleError, ModuleResult};
+use cranelift_codegen::ir::{self, Endianness};
+use cranelift_codegen::isa::unwind::UnwindInfo;
+use cranelift_codegen::isa::TargetIsa;
+use cranelift_codegen::{CompiledCode, LabelValueLoc, ValueLocRange};
+use gimli::write::{
+    Address, AttributeValue, CieId, DwarfUnit, EndianVec, Expression, FileId, FrameTable,
+    LineProgram, LineString, Location, LocationList, Range, RangeList, Sections, UnitEntryId,
+    Writer,
+};
+use gimli::{constants, Encoding, Format, LineEncoding, Register, RunTimeEndian, SectionId};
+use std::string::String;
+use std::vec::Vec;
+
+/// A source file, added with [`DebugInfoBuilder::add_file`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DebugFileId(u32);
+
+/// The type of a variable, as shown by a debugger.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DebugType {
+    /// A signed integer of the given size in bytes.
+    Signed(u8),
+    /// An unsigned integer of the given size in bytes.
+    Unsigned(u8),
+    /// A floating-point number of the given size in bytes.
+    Float(u8),
+    /// A boolean.
+    Bool,
+    /// An untyped pointer.
+    Pointer,
+}
+
+/// Debug information about a variable of a function.
+#[derive(Clone, Debug)]
+pub struct VariableDebugInfo {
+    /// The name of the variable.
+    pub name: String,
+    /// The label of the values of the variable, as set with
+    /// `FunctionBuilder::set_val_label`.
+    pub label: ir::ValueLabel,
+    /// The type of the variable.
+    pub ty: DebugType,
+    /// Whether the variable is a parameter of the function.
+    pub is_parameter: bool,
+}
+
+/// Debug information about a function, beyond what's recorded in its IR.
+#[derive(Clone, Debug)]
+pub struct FunctionDebugInfo {
+    /// The name of the function.
+    pub name: String,
+    /// The file and line where the function is declared, if known.
+    pub decl: Option<(DebugFileId, u64)>,
+    /// The variables of the function.
+    pub variables: Vec<VariableDebugInfo>,
+}
+
+impl FunctionDebugInfo {
+    /// Create debug information for a function with the given name and no
+    /// variables.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            decl: None,
+            variables: Vec::new(),
+        }
+    }
+}
+
+/// A section of DWARF debug information, produced by
+/// [`DebugInfoBuilder::finish`].
+#[derive(Clone, Debug)]
+pub struct DebugSection {
+    /// The name of the section, e.g. `.debug_info`.
+    pub name: &'static str,
+    /// The contents of the section, with all relocations yet to be applied.
+    pub body: Vec<u8>,
+    /// The relocations which need to be applied to the contents.
+    pub relocs: Vec<DebugReloc>,
+}
+
+/// A relocation in a [`DebugSection`].
+#[derive(Clone, Debug)]
+pub struct DebugReloc {
+    /// The offset of the relocated value in the section.
+    pub offset: u32,
+    /// The size of the relocated value in bytes.
+    pub size: u8,
+    /// The address which the relocated value is relative to.
+    pub target: DebugRelocTarget,
+    /// The value relative to the target.
+    pub addend: i64,
+}
+
+/// The target of a [`DebugReloc`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugRelocTarget {
+    /// The start of a function.
+    Func(FuncId),
+    /// The start of another debug section, with the given name.
+    Section(&'static str),
+}
+
+impl DebugSection {
+    /// Get the contents of this section with its relocations applied, given
+    /// the address of each function, and with offsets into other debug
+    /// sections relative to their starts.
+    ///
+    /// This is suitable for images of JIT-compiled code for debuggers, where
+    /// the code has already been placed in memory.
+    pub fn relocated_body(
+        &self,
+        endianness: Endianness,
+        func_address: impl Fn(FuncId) -> u64,
+    ) -> Vec<u8> {
+        let mut body = self.body.clone();
+        for reloc in &self.relocs {
+            let value = match reloc.target {
+                DebugRelocTarget::Func(func_id) => {
+                    func_address(func_id).wrapping_add(reloc.addend as u64)
+                }
+                DebugRelocTarget::Section(_) => reloc.addend as u64,
+            };
+            let start = reloc.offset as usize;
+            let size = usize::from(reloc.size);
+            let bytes = match endianness {
+                Endianness::Little => value.to_le_bytes(),
+                Endianness::Big => value.to_be_bytes(),
+            };
+            let bytes = match endianness {
+                Endianness::Little => &bytes[..size],
+                Endianness::Big => &bytes[8 - size..],
+            };
+            body[start..start + size].copy_from_slice(bytes);
+        }
+        body
+    }
+}
+
+/// A builder of DWARF debug information for the functions of a module.
+///
+/// All functions are described by a single compilation unit.
+pub struct DebugInfoBuilder {
+    dwarf: DwarfUnit,
+    endianness: Endianness,
+    files: Vec<FileId>,
+    source_locs: Vec<(DebugFileId, u64, u64)>,
+    source_loc_ids: HashMap<(DebugFileId, u64, u64), ir::SourceLoc>,
+    base_types: HashMap<DebugType, UnitEntryId>,
+    ranges: Vec<Range>,
+    frames: Option<(FrameTable, CieId)>,
+}
+
+impl DebugInfoBuilder {
+    /// Create a new `DebugInfoBuilder` for code compiled for `isa`.
+    ///
+    /// `producer` describes the compiler, e.g. its name and version.
+    /// `comp_dir` is the directory that relative paths of source files are
+    /// relative to, and `name` is the path of the primary source file.
+    pub fn new(isa: &dyn TargetIsa, producer: &str, comp_dir: &str, name: &str) -> Self {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: isa.pointer_bytes(),
+        };
+        let mut dwarf = DwarfUnit::new(encoding);
+        dwarf.unit.line_program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(comp_dir.as_bytes().to_vec()),
+            LineString::String(name.as_bytes().to_vec()),
+            None,
+        );
+
+        let root = dwarf.unit.root();
+        let producer = dwarf.strings.add(producer);
+        let name = dwarf.strings.add(name);
+        let comp_dir = dwarf.strings.add(comp_dir);
+        let root = dwarf.unit.get_mut(root);
+        root.set(
+            constants::DW_AT_producer,
+            AttributeValue::StringRef(producer),
+        );
+        root.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        root.set(
+            constants::DW_AT_comp_dir,
+            AttributeValue::StringRef(comp_dir),
+        );
+
+        let frames = isa.create_systemv_cie().map(|cie| {
+            let mut frames = FrameTable::default();
+            let cie_id = frames.add_cie(cie);
+            (frames, cie_id)
+        });
+
+        Self {
+            dwarf,
+            endianness: isa.endianness(),
+            files: Vec::new(),
+            source_locs: Vec::new(),
+            source_loc_ids: HashMap::new(),
+            base_types: HashMap::new(),
+            ranges: Vec::new(),
+            frames,
+        }
+    }
+
+    /// Add a source file with the given path.
+    pub fn add_file(&mut self, path: &str) -> DebugFileId {
+        let (directory, file) = match path.rfind(['/', '\\']) {
+            Some(i) if i > 0 => (Some(&path[..i]), &path[i + 1..]),
+            _ => (None, path),
+        };
+        let line_program = &mut self.dwarf.unit.line_program;
+        let directory = match directory {
+            Some(directory) => {
+                line_program.add_directory(LineString::String(directory.as_bytes().to_vec()))
+            }
+            None => line_program.default_directory(),
+        };
+        let file = line_program.add_file(
+            LineString::String(file.as_bytes().to_vec()),
+            directory,
+            None,
+        );
+        self.files.push(file);
+        DebugFileId(u32::try_from(self.files.len() - 1).unwrap())
+    }
+
+    /// Get the source location to use for code at the given line and column
+    /// of a file. Lines and columns are numbered from 1, and a column of 0
+    /// means that the column is unknown.
+    pub fn source_loc(&mut self, file: DebugFileId, line: u64, column: u64) -> ir::SourceLoc {
+        *self
+            .source_loc_ids
+            .entry((file, line, column))
+            .or_insert_with(|| {
+                self.source_locs.push((file, line, column));
+                ir::SourceLoc::new(u32::try_from(self.source_locs.len() - 1).unwrap())
+            })
+    }
+
+    /// Add the debug information of a function which has been compiled into
+    /// `code`, and defined in a module as `func_id`.
+    pub fn add_function(
+        &mut self,
+        isa: &dyn TargetIsa,
+        func_id: FuncId,
+        code: &CompiledCode,
+        info: &FunctionDebugInfo,
+    ) -> ModuleResult<()> {
+        let symbol = func_id.as_u32() as usize;
+        let address = |addend: u32| Address::Symbol {
+            symbol,
+            addend: i64::from(addend),
+        };
+        let len = code.code_info().total_size;
+
+        self.ranges.push(Range::StartLength {
+            begin: address(0),
+            length: u64::from(len),
+        });
+        self.add_line_sequence(code, address(0), len);
+
+        let root = self.dwarf.unit.root();
+        let subprogram = self.dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let name = self.dwarf.strings.add(info.name.as_str());
+        let mut frame_base = Expression::new();
+        frame_base.op(constants::DW_OP_call_frame_cfa);
+        let entry = self.dwarf.unit.get_mut(subprogram);
+        entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+        entry.set(constants::DW_AT_low_pc, AttributeValue::Address(address(0)));
+        entry.set(
+            constants::DW_AT_high_pc,
+            AttributeValue::Udata(u64::from(len)),
+        );
+        entry.set(
+            constants::DW_AT_frame_base,
+            AttributeValue::Exprloc(frame_base),
+        );
+        if let Some((file, line)) = info.decl {
+            let file = self.files[file.0 as usize];
+            entry.set(
+                constants::DW_AT_decl_file,
+                AttributeValue::FileIndex(Some(file)),
+            );
+            entry.set(constants::DW_AT_decl_line, AttributeValue::Udata(line));
+        }
+
+        for variable in &info.variables {
+            let tag = if variable.is_parameter {
+                constants::DW_TAG_formal_parameter
+            } else {
+                constants::DW_TAG_variable
+            };
+            let ty = self.base_type(variable.ty);
+            let locations = code
+                .value_labels_ranges
+                .get(&variable.label)
+                .map(|ranges| self.location_list(isa, symbol, ranges))
+                .unwrap_or_default();
+            let location = (!locations.is_empty())
+                .then(|| self.dwarf.unit.locations.add(LocationList(locations)));
+
+            let var = self.dwarf.unit.add(subprogram, tag);
+            let name = self.dwarf.strings.add(variable.name.as_str());
+            let entry = self.dwarf.unit.get_mut(var);
+            entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+            entry.set(constants::DW_AT_type, AttributeValue::UnitRef(ty));
+            if let Some(location) = location {
+                entry.set(
+                    constants::DW_AT_location,
+                    AttributeValue::LocationListRef(location),
+                );
+            }
+        }
+
+        if let Some((frames, cie_id)) = &mut self.frames {
+            match code.create_unwind_info(isa)? {
+                Some(UnwindInfo::SystemV(unwind_info)) => {
+                    frames.add_fde(*cie_id, unwind_info.to_fde(address(0)));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a sequence of rows for the code of a function at `address` to the
+    /// line program.
+    fn add_line_sequence(&mut self, code: &CompiledCode, address: Address, len: u32) {
+        let line_program = &mut self.dwarf.unit.line_program;
+        line_program.begin_sequence(Some(address));
+        let mut end = 0;
+        for srcloc in code.buffer.get_srclocs_sorted() {
+            let location = (!srcloc.loc.is_default())
+                .then(|| self.source_locs.get(srcloc.loc.bits() as usize))
+                .flatten();
+            // Code without a known location, including the gaps between
+            // those with one, is attributed to line 0.
+            if srcloc.start > end || location.is_none() {
+                line_program.row().address_offset = u64::from(end.min(srcloc.start));
+                line_program.row().line = 0;
+                line_program.generate_row();
+            }
+            if let Some(&(file, line, column)) = location {
+                let row = line_program.row();
+                row.address_offset = u64::from(srcloc.start);
+                row.file = self.files[file.0 as usize];
+                row.line = line;
+                row.column = column;
+                line_program.generate_row();
+            }
+            end = srcloc.end;
+        }
+        line_program.end_sequence(u64::from(len));
+    }
+
+    /// Get the location list of a variable with values in the given ranges of
+    /// a function's code.
+    fn location_list(
+        &self,
+        isa: &dyn TargetIsa,
+        symbol: usize,
+        ranges: &[ValueLocRange],
+    ) -> Vec<Location> {
+        ranges
+            .iter()
+            .filter_map(|range| {
+                let mut data = Expression::new();
+                match range.loc {
+                    LabelValueLoc::Reg(reg) => {
+                        let reg = isa.map_regalloc_reg_to_dwarf(reg).ok()?;
+                        data.op_reg(Register(reg));
+                    }
+                    LabelValueLoc::CFAOffset(offset) => data.op_fbreg(offset),
+                }
+                Some(Location::StartEnd {
+                    begin: Address::Symbol {
+                        symbol,
+                        addend: i64::from(range.start),
+                    },
+                    end: Address::Symbol {
+                        symbol,
+                        addend: i64::from(range.end),
+                    },
+                    data,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the entry for a type, adding it if it doesn't exist yet.
+    fn base_type(&mut self, ty: DebugType) -> UnitEntryId {
+        if let Some(&id) = self.base_types.get(&ty) {
+            return id;
+        }
+        let root = self.dwarf.unit.root();
+        let id = match ty {
+            DebugType::Pointer => {
+                let id = self.dwarf.unit.add(root, constants::DW_TAG_pointer_type);
+                let size = self.dwarf.unit.encoding().address_size;
+                self.dwarf
+                    .unit
+                    .get_mut(id)
+                    .set(constants::DW_AT_byte_size, AttributeValue::Data1(size));
+                id
+            }
+            _ => {
+                let (name, encoding, size) = match ty {
+                    DebugType::Signed(size) => {
+                        (format!("i{}", size * 8), constants::DW_ATE_signed, size)
+                    }
+                    DebugType::Unsigned(size) => {
+                        (format!("u{}", size * 8), constants::DW_ATE_unsigned, size)
+                    }
+                    DebugType::Float(size) => {
+                        (format!("f{}", size * 8), constants::DW_ATE_float, size)
+                    }
+                    DebugType::Bool => ("bool".into(), constants::DW_ATE_boolean, 1),
+                    DebugType::Pointer => unreachable!(),
+                };
+                let id = self.dwarf.unit.add(root, constants::DW_TAG_base_type);
+                let name = self.dwarf.strings.add(name);
+                let entry = self.dwarf.unit.get_mut(id);
+                entry.set(constants::DW_AT_name, AttributeValue::StringRef(name));
+                entry.set(
+                    constants::DW_AT_encoding,
+                    AttributeValue::Encoding(encoding),
+                );
+                entry.set(constants::DW_AT_byte_size, AttributeValue::Data1(size));
+                id
+            }
+        };
+        self.base_types.insert(ty, id);
+        id
+    }
+
+    /// Emit the DWARF sections describing all the functions added so far.
+    pub fn finish(mut self) -> ModuleResult<Vec<DebugSection>> {
+        let root = self.dwarf.unit.root();
+        let ranges = self
+            .dwarf
+            .unit
+            .ranges
+            .add(RangeList(core::mem::take(&mut self.ranges)));
+        let root = self.dwarf.unit.get_mut(root);
+        root.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0)),
+        );
+        root.set(
+            constants::DW_AT_ranges,
+            AttributeValue::RangeListRef(ranges),
+        );
+
+        let endian = match self.endianness {
+            Endianness::Little => RunTimeEndian::Little,
+            Endianness::Big => RunTimeEndian::Big,
+        };
+        let mut sections = Sections::new(RelocatingWriter {
+            writer: EndianVec::new(endian),
+            relocs: Vec::new(),
+        });
+        self.dwarf.write(&mut sections).map_err(write_error)?;
+        if let Some((frames, _)) = &self.frames {
+            frames
+                .write_debug_frame(&mut sections.debug_frame)
+                .map_err(write_error)?;
+        }
+
+        let mut result = Vec::new();
+        sections
+            .for_each_mut(|id, section| -> gimli::write::Result<()> {
+                let body = section.writer.take();
+                if !body.is_empty() {
+                    result.push(DebugSection {
+                        name: id.name(),
+                        body,
+                        relocs: core::mem::take(&mut section.relocs),
+                    });
+                }
+                Ok(())
+            })
+            .map_err(write_error)?;
+        Ok(result)
+    }
+}
+
+fn write_error(err: gimli::write::Error) -> ModuleError {
+    ModuleError::Backend(anyhow::anyhow!("failed to write DWARF: {err}"))
+}
+
+/// A `Writer` which records relocations for symbolic addresses and offsets
+/// into other sections.
+#[derive(Clone)]
+struct RelocatingWriter {
+    writer: EndianVec<RunTimeEndian>,
+    relocs: Vec<DebugReloc>,
+}
+
+impl Writer for RelocatingWriter {
+    type Endian = RunTimeEndian;
+
+    fn endian(&self) -> Self::Endian {
+        self.writer.endian()
+    }
+
+    fn len(&self) -> usize {
+        self.writer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write(bytes)
+    }
+
+    fn write_at(&mut self, offset: usize, bytes: &[u8]) -> gimli::write::Result<()> {
+        self.writer.write_at(offset, bytes)
+    }
+
+    fn write_address(&mut self, address: Address, size: u8) -> gimli::write::Result<()> {
+        match address {
+            Address::Constant(val) => self.write_udata(val, size),
+            Address::Symbol { symbol, addend } => {
+                self.relocs.push(DebugReloc {
+                    offset: self.len() as u32,
+                    size,
+                    target: DebugRelocTarget::Func(FuncId::from_u32(symbol as u32)),
+                    addend,
+                });
+                self.write_udata(addend as u64, size)
+            }
+        }
+    }
+
+    fn write_offset(
+        &mut self,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        let offset = self.len();
+        self.write_offset_at(offset, val, section, size)?;
+        Ok(())
+    }
+
+    fn write_offset_at(
+        &mut self,
+        offset: usize,
+        val: usize,
+        section: SectionId,
+        size: u8,
+    ) -> gimli::write::Result<()> {
+        self.relocs.push(DebugReloc {
+            offset: offset as u32,
+            size,
+            target: DebugRelocTarget::Section(section.name()),
+            addend: val as i64,
+        });
+        if offset == self.len() {
+            self.write_udata(val as u64, size)
+        } else {
+            self.write_udata_at(offset, val as u64, size)
+        }
+    }
+}
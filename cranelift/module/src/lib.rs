@@ -21,10 +21,17 @@ use std::string::String;
 use cranelift_codegen::ir;
 
 mod data_context;
+#[cfg(feature = "debug-info")]
+mod debug_info;
 mod module;
 mod traps;
 
 pub use crate::data_context::{DataDescription, Init};
+#[cfg(feature = "debug-info")]
+pub use crate::debug_info::{
+    DebugFileId, DebugInfoBuilder, DebugReloc, DebugRelocTarget, DebugSection, DebugType,
+    FunctionDebugInfo, VariableDebugInfo,
+};
 pub use crate::module::{
     DataDeclaration, DataId, FuncId, FuncOrDataId, FunctionDeclaration, Linkage, Module,
     ModuleDeclarations, ModuleError, ModuleReloc, ModuleRelocTarget, ModuleResult,
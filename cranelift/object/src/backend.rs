@@ -10,7 +10,11 @@ use cranelift_module::{
     DataDescription, DataId, FuncId, Init, Linkage, Module, ModuleDeclarations, ModuleError,
     ModuleReloc, ModuleRelocTarget, ModuleResult,
 };
+#[cfg(feature = "debug-info")]
+use cranelift_module::{DebugRelocTarget, DebugSection};
 use log::info;
+#[cfg(feature = "debug-info")]
+use object::write::StandardSegment;
 use object::write::{
     Object, Relocation, SectionId, StandardSection, Symbol, SymbolId, SymbolSection,
 };
@@ -930,6 +934,68 @@ impl ObjectProduct {
         self.data_objects[id].unwrap().0
     }
 
+    /// Add the DWARF sections for the functions of this object, as produced
+    /// by a [`DebugInfoBuilder`](cranelift_module::DebugInfoBuilder).
+    #[cfg(feature = "debug-info")]
+    pub fn add_debug_sections(
+        &mut self,
+        sections: &[DebugSection],
+    ) -> Result<(), object::write::Error> {
+        let format = self.object.format();
+        let segment = self.object.segment_name(StandardSegment::Debug).to_vec();
+        let mut section_ids = HashMap::new();
+        for section in sections {
+            let name = match format {
+                object::BinaryFormat::MachO => format!("__{}", &section.name[1..]),
+                _ => section.name.to_owned(),
+            };
+            let section_id =
+                self.object
+                    .add_section(segment.clone(), name.into_bytes(), SectionKind::Debug);
+            self.object
+                .append_section_data(section_id, &section.body, 1);
+            section_ids.insert(section.name, section_id);
+        }
+
+        for section in sections {
+            for reloc in &section.relocs {
+                let (symbol, kind) = match reloc.target {
+                    DebugRelocTarget::Func(func_id) => {
+                        (self.function_symbol(func_id), RelocationKind::Absolute)
+                    }
+                    // Mach-O refers to other debug sections by offset alone.
+                    DebugRelocTarget::Section(_) if format == object::BinaryFormat::MachO => {
+                        continue
+                    }
+                    DebugRelocTarget::Section(name) => {
+                        let Some(&target) = section_ids.get(name) else {
+                            continue;
+                        };
+                        let kind = match format {
+                            object::BinaryFormat::Coff => RelocationKind::SectionOffset,
+                            _ => RelocationKind::Absolute,
+                        };
+                        (self.object.section_symbol(target), kind)
+                    }
+                };
+                self.object.add_relocation(
+                    section_ids[section.name],
+                    Relocation {
+                        offset: u64::from(reloc.offset),
+                        symbol,
+                        addend: reloc.addend,
+                        flags: RelocationFlags::Generic {
+                            kind,
+                            encoding: RelocationEncoding::Generic,
+                            size: reloc.size * 8,
+                        },
+                    },
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     /// Write the object bytes in memory.
     #[inline]
     pub fn emit(self) -> Result<Vec<u8>, object::write::Error> {
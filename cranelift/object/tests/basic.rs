@@ -239,3 +239,120 @@ fn reject_nul_byte_symbol_for_data() {
         )
         .unwrap();
 }
+
+#[test]
+#[cfg(feature = "debug-info")]
+fn debug_info() {
+    use gimli::{EndianSlice, LittleEndian};
+    use object::{Object as _, ObjectSection as _};
+
+    let flag_builder = settings::builder();
+    let isa_builder = cranelift_codegen::isa::lookup_by_name("x86_64-unknown-linux-gnu").unwrap();
+    let isa = isa_builder
+        .finish(settings::Flags::new(flag_builder))
+        .unwrap();
+    let mut module =
+        ObjectModule::new(ObjectBuilder::new(isa, "foo", default_libcall_names()).unwrap());
+    let mut debug_info = DebugInfoBuilder::new(module.isa(), "test", "/src", "foo.x");
+    let file = debug_info.add_file("foo.x");
+
+    let mut sig = module.make_signature();
+    sig.params.push(AbiParam::new(types::I64));
+    sig.returns.push(AbiParam::new(types::I64));
+    let func_id = module
+        .declare_function("product", Linkage::Export, &sig)
+        .unwrap();
+
+    let mut ctx = module.make_context();
+    ctx.func = Function::with_name_signature(UserFuncName::user(0, func_id.as_u32()), sig);
+    ctx.func.collect_debug_info();
+    let mut func_ctx = FunctionBuilderContext::new();
+    {
+        let mut bcx: FunctionBuilder = FunctionBuilder::new(&mut ctx.func, &mut func_ctx);
+        let block = bcx.create_block();
+        bcx.append_block_params_for_function_params(block);
+        bcx.switch_to_block(block);
+        bcx.set_srcloc(debug_info.source_loc(file, 2, 5));
+        let x = bcx.block_params(block)[0];
+        bcx.set_val_label(x, ValueLabel::new(0));
+        let y = bcx.ins().iadd_imm(x, 1);
+        bcx.set_val_label(y, ValueLabel::new(1));
+        bcx.set_srcloc(debug_info.source_loc(file, 3, 5));
+        let z = bcx.ins().imul(x, y);
+        bcx.ins().return_(&[z]);
+        bcx.seal_all_blocks();
+        bcx.finalize();
+    }
+    module.define_function(func_id, &mut ctx).unwrap();
+
+    let mut info = FunctionDebugInfo::new("product");
+    info.decl = Some((file, 1));
+    info.variables.push(VariableDebugInfo {
+        name: "x".into(),
+        label: ValueLabel::new(0),
+        ty: DebugType::Signed(8),
+        is_parameter: true,
+    });
+    info.variables.push(VariableDebugInfo {
+        name: "y".into(),
+        label: ValueLabel::new(1),
+        ty: DebugType::Signed(8),
+        is_parameter: false,
+    });
+    debug_info
+        .add_function(module.isa(), func_id, ctx.compiled_code().unwrap(), &info)
+        .unwrap();
+    let sections = debug_info.finish().unwrap();
+
+    let mut product = module.finish();
+    product.add_debug_sections(&sections).unwrap();
+    let bytes = product.emit().unwrap();
+
+    // The function is at the start of the text section, so the addresses in
+    // the debug information are right without applying relocations.
+    let file = object::read::elf::ElfFile64::<object::Endianness>::parse(&*bytes).unwrap();
+    let dwarf = gimli::Dwarf::load(|id| -> gimli::Result<_> {
+        let data = file
+            .section_by_name(id.name())
+            .and_then(|section| section.data().ok())
+            .unwrap_or(&[]);
+        Ok(EndianSlice::new(data, LittleEndian))
+    })
+    .unwrap();
+    let unit = dwarf.units().next().unwrap().unwrap();
+    let unit = dwarf.unit(unit).unwrap();
+
+    let mut entries_with_names = vec![];
+    let mut entries = unit.entries();
+    while let Some((_, entry)) = entries.next_dfs().unwrap() {
+        if let Some(name) = entry.attr_value(gimli::DW_AT_name).unwrap() {
+            let name = dwarf.attr_string(&unit, name).unwrap();
+            let has_location = entry.attr(gimli::DW_AT_location).unwrap().is_some();
+            entries_with_names.push((
+                entry.tag(),
+                name.to_string().unwrap().to_owned(),
+                has_location,
+            ));
+        }
+    }
+    assert_eq!(
+        entries_with_names,
+        [
+            (gimli::DW_TAG_compile_unit, "foo.x".to_owned(), false),
+            (gimli::DW_TAG_base_type, "i64".to_owned(), false),
+            (gimli::DW_TAG_subprogram, "product".to_owned(), false),
+            (gimli::DW_TAG_formal_parameter, "x".to_owned(), true),
+            (gimli::DW_TAG_variable, "y".to_owned(), true),
+        ]
+    );
+
+    let mut lines = vec![];
+    let mut rows = unit.line_program.clone().unwrap().rows();
+    while let Some((_, row)) = rows.next_row().unwrap() {
+        if let Some(line) = row.line() {
+            lines.push(line.get());
+        }
+    }
+    lines.dedup();
+    assert_eq!(lines, [2, 3]);
+}
@@ -0,0 +1,206 @@
+//! A disk-backed store for Cranelift's incremental compilation cache.
+//!
+//! Unlike [`ModuleCacheEntry`](crate::ModuleCacheEntry), which caches entire
+//! compiled modules, this caches individual functions. Editing one function in
+//! a large module then only requires recompiling that function on the next
+//! run, as all other functions are found in the cache.
+
+use super::fs_write_atomic;
+use base64::Engine;
+use log::{debug, info, trace, warn};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use wasmtime_environ::CacheStore;
+
+/// Implementation of [`CacheStore`] which stores each cache entry as a file
+/// in a directory on disk.
+///
+/// Keys are hashed to produce file names so arbitrary keys can be used. Cache
+/// entries are compressed with `zstd` before being written to disk.
+///
+/// This store never removes entries on its own; the directory it's given is
+/// expected to be managed, and cleaned up, by the user.
+pub struct IncrementalCacheStore {
+    directory: PathBuf,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    insert_failures: AtomicU64,
+}
+
+/// Statistics about the usage of an [`IncrementalCacheStore`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct IncrementalCacheStats {
+    /// The number of lookups which found an entry in the cache.
+    pub hits: u64,
+    /// The number of lookups which did not find an entry in the cache.
+    pub misses: u64,
+    /// The number of entries successfully written to the cache.
+    pub inserts: u64,
+    /// The number of entries which failed to be written to the cache.
+    pub insert_failures: u64,
+}
+
+// Cache entries are small, one per function, so favor speed over ratio.
+const COMPRESSION_LEVEL: i32 = 1;
+
+impl IncrementalCacheStore {
+    /// Creates a new store which persists cache entries in `directory`.
+    ///
+    /// The directory is created if it doesn't already exist.
+    pub fn new(directory: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory).map_err(|err| {
+            anyhow::anyhow!(
+                "failed to create incremental cache directory `{}`: {err}",
+                directory.display()
+            )
+        })?;
+        Ok(Self {
+            directory: directory.to_path_buf(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            inserts: AtomicU64::new(0),
+            insert_failures: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the directory in which cache entries are stored.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Returns statistics about the lookups and insertions performed on this
+    /// store so far.
+    pub fn stats(&self) -> IncrementalCacheStats {
+        IncrementalCacheStats {
+            hits: self.hits.load(Relaxed),
+            misses: self.misses.load(Relaxed),
+            inserts: self.inserts.load(Relaxed),
+            insert_failures: self.insert_failures.load(Relaxed),
+        }
+    }
+
+    fn entry_path(&self, key: &[u8]) -> PathBuf {
+        let hash: [u8; 32] = Sha256::digest(key).into();
+        // standard encoding uses '/' which can't be used for filename
+        let name = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(&hash);
+        self.directory.join(name)
+    }
+
+    fn read_entry(&self, path: &Path) -> Option<Vec<u8>> {
+        let compressed = fs::read(path).ok()?;
+        zstd::decode_all(&compressed[..])
+            .map_err(|err| {
+                warn!(
+                    "Failed to decompress incremental cache entry {}: {}",
+                    path.display(),
+                    err
+                )
+            })
+            .ok()
+    }
+
+    fn write_entry(&self, path: &Path, value: &[u8]) -> Option<()> {
+        let compressed = zstd::encode_all(value, COMPRESSION_LEVEL)
+            .map_err(|err| warn!("Failed to compress incremental cache entry: {}", err))
+            .ok()?;
+        match fs_write_atomic(path, "incr", &compressed) {
+            Ok(()) => Some(()),
+            Err(err) => {
+                // This can happen if another process is concurrently writing
+                // the same entry, in which case the entry will show up anyway.
+                debug!(
+                    "Failed to write incremental cache entry {}: {}",
+                    path.display(),
+                    err
+                );
+                None
+            }
+        }
+    }
+}
+
+impl CacheStore for IncrementalCacheStore {
+    fn get(&self, key: &[u8]) -> Option<Cow<'_, [u8]>> {
+        let path = self.entry_path(key);
+        trace!("incremental cache get() for path: {}", path.display());
+        match self.read_entry(&path) {
+            Some(value) => {
+                self.hits.fetch_add(1, Relaxed);
+                Some(value.into())
+            }
+            None => {
+                self.misses.fetch_add(1, Relaxed);
+                None
+            }
+        }
+    }
+
+    fn insert(&self, key: &[u8], value: Vec<u8>) -> bool {
+        let path = self.entry_path(key);
+        trace!("incremental cache insert() for path: {}", path.display());
+        match self.write_entry(&path, &value) {
+            Some(()) => {
+                self.inserts.fetch_add(1, Relaxed);
+                true
+            }
+            None => {
+                self.insert_failures.fetch_add(1, Relaxed);
+                false
+            }
+        }
+    }
+}
+
+impl fmt::Debug for IncrementalCacheStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncrementalCacheStore")
+            .field("directory", &self.directory)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl Drop for IncrementalCacheStore {
+    fn drop(&mut self) {
+        let stats = self.stats();
+        if stats != IncrementalCacheStats::default() {
+            info!("incremental cache {}: {}", self.directory.display(), stats);
+        }
+    }
+}
+
+impl IncrementalCacheStats {
+    /// Returns the fraction of lookups which were cache hits, or `None` if no
+    /// lookups have been performed.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            None
+        } else {
+            Some(self.hits as f64 / lookups as f64)
+        }
+    }
+}
+
+impl fmt::Display for IncrementalCacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} hits, {} misses, {} inserts, {} failed inserts",
+            self.hits, self.misses, self.inserts, self.insert_failures
+        )?;
+        if let Some(rate) = self.hit_rate() {
+            write!(f, " ({:.1}% hit rate)", rate * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
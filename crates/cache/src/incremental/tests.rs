@@ -0,0 +1,46 @@
+use super::*;
+use crate::config::tests::test_prolog;
+
+#[test]
+fn test_get_and_insert() {
+    let (_tempdir, cache_dir, _config_path) = test_prolog();
+    let store = IncrementalCacheStore::new(&cache_dir).unwrap();
+    assert!(cache_dir.is_dir());
+
+    assert!(store.get(b"key").is_none());
+    assert!(store.insert(b"key", b"value".to_vec()));
+    assert_eq!(store.get(b"key").as_deref(), Some(&b"value"[..]));
+    assert!(store.get(b"other key").is_none());
+
+    assert_eq!(
+        store.stats(),
+        IncrementalCacheStats {
+            hits: 1,
+            misses: 2,
+            inserts: 1,
+            insert_failures: 0,
+        }
+    );
+}
+
+#[test]
+fn test_persists_across_stores() {
+    let (_tempdir, cache_dir, _config_path) = test_prolog();
+    {
+        let store = IncrementalCacheStore::new(&cache_dir).unwrap();
+        assert!(store.insert(&[0, 1, 2], vec![42; 1000]));
+    }
+
+    let store = IncrementalCacheStore::new(&cache_dir).unwrap();
+    assert_eq!(store.get(&[0, 1, 2]).as_deref(), Some(&[42; 1000][..]));
+    assert_eq!(store.stats().hit_rate(), Some(1.0));
+}
+
+#[test]
+fn test_corrupt_entry_is_a_miss() {
+    let (_tempdir, cache_dir, _config_path) = test_prolog();
+    let store = IncrementalCacheStore::new(&cache_dir).unwrap();
+    fs::write(store.entry_path(b"key"), b"not zstd").unwrap();
+    assert!(store.get(b"key").is_none());
+    assert_eq!(store.stats().misses, 1);
+}
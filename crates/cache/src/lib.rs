@@ -10,9 +10,11 @@ use std::{fs, io};
 
 #[macro_use] // for tests
 mod config;
+mod incremental;
 mod worker;
 
 pub use config::{create_new_config, CacheConfig};
+pub use incremental::{IncrementalCacheStats, IncrementalCacheStore};
 use worker::Worker;
 
 /// Module level cache entry.
@@ -229,10 +229,22 @@ wasmtime_option_group! {
         pub cache: Option<bool>,
         /// Configuration for compiled module caching.
         pub cache_config: Option<String>,
+        /// Directory in which to persist Cranelift's per-function incremental
+        /// compilation cache.
+        ///
+        /// Only functions which changed since a previous compilation using the
+        /// same directory are recompiled.
+        pub incremental_cache: Option<String>,
+        /// Print statistics about incremental compilation cache hits and
+        /// misses to stderr once compilation has finished.
+        pub incremental_cache_stats: Option<bool>,
         /// Whether or not to enable parallel compilation of modules.
         pub parallel_compilation: Option<bool>,
         /// Whether to enable proof-carrying code (PCC)-based validation.
         pub pcc: Option<bool>,
+        /// Whether to inline calls to small functions defined in the same
+        /// module (default: no).
+        pub inlining: Option<bool>,
         /// Controls whether native unwind information is present in compiled
         /// object files.
         pub native_unwind_info: Option<bool>,
@@ -354,6 +366,14 @@ wasmtime_option_group! {
         /// the specification. Note that enabling this option may come at a
         /// performance cost.
         pub relaxed_simd_deterministic: Option<bool>,
+        /// Execute WebAssembly deterministically, producing identical results
+        /// on all hosts.
+        ///
+        /// This canonicalizes NaNs, makes relaxed-simd deterministic, disables
+        /// threads, and replaces the clocks and randomness of WASI with
+        /// deterministic versions. Execution must be bounded with `-W fuel`;
+        /// epoch-based options such as `-W timeout` can't be used.
+        pub deterministic: Option<bool>,
         /// Configure support for the tail-call proposal.
         pub tail_call: Option<bool>,
         /// Configure support for the threads proposal.
@@ -549,6 +569,13 @@ pub struct CommonOptions {
     #[arg(long = "config", value_name = "FILE")]
     #[serde(skip)]
     pub config: Option<PathBuf>,
+
+    // The incremental compilation cache created by `config`, if any, kept
+    // around to report its statistics.
+    #[cfg(feature = "incremental-cache")]
+    #[arg(skip)]
+    #[serde(skip)]
+    incremental_cache_store: Option<std::sync::Arc<wasmtime_cache::IncrementalCacheStore>>,
 }
 
 macro_rules! match_feature {
@@ -589,6 +616,8 @@ impl CommonOptions {
             wasi: Default::default(),
             target: None,
             config: None,
+            #[cfg(feature = "incremental-cache")]
+            incremental_cache_store: None,
         }
     }
 
@@ -613,6 +642,19 @@ impl CommonOptions {
         Ok(())
     }
 
+    /// Prints the statistics of the incremental compilation cache to stderr if
+    /// `-C incremental-cache-stats` was passed.
+    ///
+    /// This should be called once all compilation has finished.
+    pub fn print_incremental_cache_stats(&self) {
+        #[cfg(feature = "incremental-cache")]
+        if self.codegen.incremental_cache_stats == Some(true) {
+            if let Some(store) = &self.incremental_cache_store {
+                eprintln!("incremental cache: {}", store.stats());
+            }
+        }
+    }
+
     pub fn init_logging(&mut self) -> Result<()> {
         self.configure()?;
         if self.debug.logging == Some(false) {
@@ -697,6 +739,11 @@ impl CommonOptions {
             enable => config.cranelift_pcc(enable),
             true => err,
         }
+        match_feature! {
+            ["cranelift" : self.codegen.inlining]
+            enable => config.cranelift_inlining(enable),
+            true => err,
+        }
 
         self.enable_wasm_features(&mut config)?;
 
@@ -735,6 +782,22 @@ impl CommonOptions {
             anyhow::bail!("support for caching disabled at compile time");
         }
 
+        #[cfg(feature = "incremental-cache")]
+        if let Some(dir) = &self.codegen.incremental_cache {
+            let store = std::sync::Arc::new(wasmtime_cache::IncrementalCacheStore::new(dir)?);
+            config.enable_incremental_compilation(store.clone())?;
+            self.incremental_cache_store = Some(store);
+        }
+        #[cfg(not(feature = "incremental-cache"))]
+        if self.codegen.incremental_cache.is_some() {
+            anyhow::bail!("support for incremental compilation caching disabled at compile time");
+        }
+        if self.codegen.incremental_cache_stats == Some(true)
+            && self.codegen.incremental_cache.is_none()
+        {
+            anyhow::bail!("`-C incremental-cache-stats` requires `-C incremental-cache`");
+        }
+
         match_feature! {
             ["parallel-compilation" : self.codegen.parallel_compilation]
             enable => config.parallel_compilation(enable),
@@ -931,6 +994,12 @@ impl CommonOptions {
         if let Some(enable) = self.wasm.relaxed_simd_deterministic {
             config.relaxed_simd_deterministic(enable);
         }
+        if let Some(enable) = self.wasm.deterministic {
+            if enable && self.wasm.fuel.is_none() {
+                anyhow::bail!("`-W deterministic` requires `-W fuel=N` to bound execution");
+            }
+            config.deterministic(enable);
+        }
         match_feature! {
             ["cranelift" : self.wasm.wmemcheck]
             enable => config.wmemcheck(enable),
@@ -1005,6 +1074,30 @@ impl CommonOptions {
         toml::from_str::<CommonOptions>(&file_contents)
             .with_context(|| format!("failed to parse TOML config file {path_ref:?}"))
     }
+
+    /// Returns a copy of these options with the command-line flags in `args`,
+    /// such as `-Oopt-level=0`, applied on top of them.
+    pub fn with_args<I, T>(&self, args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let overrides = CommonOptions::try_parse_from(
+            std::iter::once(std::ffi::OsString::from("wasmtime"))
+                .chain(args.into_iter().map(Into::into)),
+        )?;
+        let mut ret = self.clone();
+        ret.configure()?;
+        ret.opts.configure_with(&overrides.opts_raw);
+        ret.codegen.configure_with(&overrides.codegen_raw);
+        ret.debug.configure_with(&overrides.debug_raw);
+        ret.wasm.configure_with(&overrides.wasm_raw);
+        ret.wasi.configure_with(&overrides.wasi_raw);
+        if overrides.target.is_some() {
+            ret.target = overrides.target;
+        }
+        Ok(ret)
+    }
 }
 
 #[cfg(test)]
@@ -1013,6 +1106,19 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn with_args() -> Result<()> {
+        let base =
+            CommonOptions::try_parse_from(["wasmtime", "-Oopt-level=0", "-Wfuel=10", "-Ccache=n"])?;
+        let options = base.with_args(["-Oopt-level=2", "-Ccompiler=winch"])?;
+        assert_eq!(options.opts.opt_level, Some(OptLevel::Speed));
+        assert_eq!(options.codegen.compiler, Some(wasmtime::Strategy::Winch));
+        assert_eq!(options.codegen.cache, Some(false));
+        assert_eq!(options.wasm.fuel, Some(10));
+        assert!(base.with_args(["-Cnot-an-option"]).is_err());
+        Ok(())
+    }
+
     #[test]
     fn from_toml() {
         // empty toml
@@ -1152,6 +1258,8 @@ impl fmt::Display for CommonOptions {
             configured,
             target,
             config,
+            #[cfg(feature = "incremental-cache")]
+                incremental_cache_store: _,
         } = self;
         if let Some(target) = target {
             write!(f, "--target {target} ")?;
@@ -0,0 +1,637 @@
+/// Auto-generated bindings for a pre-instantiated version of a
+/// component which implements the world `the-world`.
+///
+/// This structure is created through [`TheWorldPre::new`] which
+/// takes a [`InstancePre`](wasmtime::component::InstancePre) that
+/// has been created through a [`Linker`](wasmtime::component::Linker).
+///
+/// For more information see [`TheWorld`] as well.
+pub struct TheWorldPre<T> {
+    instance_pre: wasmtime::component::InstancePre<T>,
+    indices: TheWorldIndices,
+}
+impl<T> Clone for TheWorldPre<T> {
+    fn clone(&self) -> Self {
+        Self {
+            instance_pre: self.instance_pre.clone(),
+            indices: self.indices.clone(),
+        }
+    }
+}
+impl<_T> TheWorldPre<_T> {
+    /// Creates a new copy of `TheWorldPre` bindings which can then
+    /// be used to instantiate into a particular store.
+    ///
+    /// This method may fail if the component behind `instance_pre`
+    /// does not have the required exports.
+    pub fn new(
+        instance_pre: wasmtime::component::InstancePre<_T>,
+    ) -> wasmtime::Result<Self> {
+        let indices = TheWorldIndices::new(instance_pre.component())?;
+        Ok(Self { instance_pre, indices })
+    }
+    pub fn engine(&self) -> &wasmtime::Engine {
+        self.instance_pre.engine()
+    }
+    pub fn instance_pre(&self) -> &wasmtime::component::InstancePre<_T> {
+        &self.instance_pre
+    }
+    /// Instantiates a new instance of [`TheWorld`] within the
+    /// `store` provided.
+    ///
+    /// This function will use `self` as the pre-instantiated
+    /// instance to perform instantiation. Afterwards the preloaded
+    /// indices in `self` are used to lookup all exports on the
+    /// resulting instance.
+    pub async fn instantiate_async(
+        &self,
+        mut store: impl wasmtime::AsContextMut<Data = _T>,
+    ) -> wasmtime::Result<TheWorld>
+    where
+        _T: Send + 'static,
+    {
+        let mut store = store.as_context_mut();
+        let instance = self.instance_pre.instantiate_async(&mut store).await?;
+        self.indices.load(&mut store, &instance)
+    }
+}
+/// Auto-generated bindings for index of the exports of
+/// `the-world`.
+///
+/// This is an implementation detail of [`TheWorldPre`] and can
+/// be constructed if needed as well.
+///
+/// For more information see [`TheWorld`] as well.
+#[derive(Clone)]
+pub struct TheWorldIndices {
+    interface0: exports::foo::foo::transfer::GuestIndices,
+}
+/// Auto-generated bindings for an instance a component which
+/// implements the world `the-world`.
+///
+/// This structure can be created through a number of means
+/// depending on your requirements and what you have on hand:
+///
+/// * The most convenient way is to use
+///   [`TheWorld::instantiate_async`] which only needs a
+///   [`Store`], [`Component`], and [`Linker`].
+///
+/// * Alternatively you can create a [`TheWorldPre`] ahead of
+///   time with a [`Component`] to front-load string lookups
+///   of exports once instead of per-instantiation. This
+///   method then uses [`TheWorldPre::instantiate_async`] to
+///   create a [`TheWorld`].
+///
+/// * If you've instantiated the instance yourself already
+///   then you can use [`TheWorld::new`].
+///
+/// * You can also access the guts of instantiation through
+///   [`TheWorldIndices::new_instance`] followed
+///   by [`TheWorldIndices::load`] to crate an instance of this
+///   type.
+///
+/// These methods are all equivalent to one another and move
+/// around the tradeoff of what work is performed when.
+///
+/// [`Store`]: wasmtime::Store
+/// [`Component`]: wasmtime::component::Component
+/// [`Linker`]: wasmtime::component::Linker
+pub struct TheWorld {
+    interface0: exports::foo::foo::transfer::Guest,
+}
+const _: () = {
+    #[allow(unused_imports)]
+    use wasmtime::component::__internal::anyhow;
+    impl TheWorldIndices {
+        /// Creates a new copy of `TheWorldIndices` bindings which can then
+        /// be used to instantiate into a particular store.
+        ///
+        /// This method may fail if the component does not have the
+        /// required exports.
+        pub fn new(
+            component: &wasmtime::component::Component,
+        ) -> wasmtime::Result<Self> {
+            let _component = component;
+            let interface0 = exports::foo::foo::transfer::GuestIndices::new(_component)?;
+            Ok(TheWorldIndices { interface0 })
+        }
+        /// Creates a new instance of [`TheWorldIndices`] from an
+        /// instantiated component.
+        ///
+        /// This method of creating a [`TheWorld`] will perform string
+        /// lookups for all exports when this method is called. This
+        /// will only succeed if the provided instance matches the
+        /// requirements of [`TheWorld`].
+        pub fn new_instance(
+            mut store: impl wasmtime::AsContextMut,
+            instance: &wasmtime::component::Instance,
+        ) -> wasmtime::Result<Self> {
+            let _instance = instance;
+            let interface0 = exports::foo::foo::transfer::GuestIndices::new_instance(
+                &mut store,
+                _instance,
+            )?;
+            Ok(TheWorldIndices { interface0 })
+        }
+        /// Uses the indices stored in `self` to load an instance
+        /// of [`TheWorld`] from the instance provided.
+        ///
+        /// Note that at this time this method will additionally
+        /// perform type-checks of all exports.
+        pub fn load(
+            &self,
+            mut store: impl wasmtime::AsContextMut,
+            instance: &wasmtime::component::Instance,
+        ) -> wasmtime::Result<TheWorld> {
+            let _instance = instance;
+            let interface0 = self.interface0.load(&mut store, &_instance)?;
+            Ok(TheWorld { interface0 })
+        }
+    }
+    impl TheWorld {
+        /// Convenience wrapper around [`TheWorldPre::new`] and
+        /// [`TheWorldPre::instantiate_async`].
+        pub async fn instantiate_async<_T>(
+            mut store: impl wasmtime::AsContextMut<Data = _T>,
+            component: &wasmtime::component::Component,
+            linker: &wasmtime::component::Linker<_T>,
+        ) -> wasmtime::Result<TheWorld>
+        where
+            _T: Send + 'static,
+        {
+            let pre = linker.instantiate_pre(component)?;
+            TheWorldPre::new(pre)?.instantiate_async(store).await
+        }
+        /// Convenience wrapper around [`TheWorldIndices::new_instance`] and
+        /// [`TheWorldIndices::load`].
+        pub fn new(
+            mut store: impl wasmtime::AsContextMut,
+            instance: &wasmtime::component::Instance,
+        ) -> wasmtime::Result<TheWorld> {
+            let indices = TheWorldIndices::new_instance(&mut store, instance)?;
+            indices.load(store, instance)
+        }
+        pub fn add_to_linker<T, U>(
+            linker: &mut wasmtime::component::Linker<T>,
+            get: impl Fn(&mut T) -> &mut U + Send + Sync + Copy + 'static,
+        ) -> wasmtime::Result<()>
+        where
+            T: Send + foo::foo::transfer::Host<Data = T> + 'static,
+            U: Send + foo::foo::transfer::Host<Data = T>,
+        {
+            foo::foo::transfer::add_to_linker(linker, get)?;
+            Ok(())
+        }
+        pub fn foo_foo_transfer(&self) -> &exports::foo::foo::transfer::Guest {
+            &self.interface0
+        }
+    }
+};
+pub mod foo {
+    pub mod foo {
+        #[allow(clippy::all)]
+        pub mod transfer {
+            #[allow(unused_imports)]
+            use wasmtime::component::__internal::{anyhow, Box};
+            #[derive(wasmtime::component::ComponentType)]
+            #[derive(wasmtime::component::Lift)]
+            #[derive(wasmtime::component::Lower)]
+            #[component(record)]
+            #[derive(Clone)]
+            pub struct Chunk {
+                #[component(name = "bytes")]
+                pub bytes: wasmtime::component::__internal::Vec<u8>,
+            }
+            impl core::fmt::Debug for Chunk {
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.debug_struct("Chunk").field("bytes", &self.bytes).finish()
+                }
+            }
+            const _: () = {
+                assert!(8 == < Chunk as wasmtime::component::ComponentType >::SIZE32);
+                assert!(4 == < Chunk as wasmtime::component::ComponentType >::ALIGN32);
+            };
+            pub trait Host {
+                type Data;
+                fn read_all(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                    s: wasmtime::component::HostStream<Chunk>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostFuture<u32> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized;
+                fn echo(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                    s: wasmtime::component::HostStream<u8>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostStream<u8> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized;
+                fn ready(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostFuture<()> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized;
+            }
+            pub trait GetHost<
+                T,
+                D,
+            >: Fn(T) -> <Self as GetHost<T, D>>::Host + Send + Sync + Copy + 'static {
+                type Host: Host<Data = D> + Send;
+            }
+            impl<F, T, D, O> GetHost<T, D> for F
+            where
+                F: Fn(T) -> O + Send + Sync + Copy + 'static,
+                O: Host<Data = D> + Send,
+            {
+                type Host = O;
+            }
+            pub fn add_to_linker_get_host<
+                T,
+                G: for<'a> GetHost<&'a mut T, T, Host: Host<Data = T> + Send>,
+            >(
+                linker: &mut wasmtime::component::Linker<T>,
+                host_getter: G,
+            ) -> wasmtime::Result<()>
+            where
+                T: Send + 'static,
+            {
+                let mut inst = linker.instance("foo:foo/transfer")?;
+                inst.func_wrap_concurrent(
+                    "read-all",
+                    move |
+                        mut caller: wasmtime::StoreContextMut<'_, T>,
+                        (arg0,): (wasmtime::component::HostStream<Chunk>,)|
+                    {
+                        let host = caller;
+                        let r = <G::Host as Host>::read_all(host, arg0);
+                        Box::pin(async move {
+                            let fun = r.await;
+                            Box::new(move |mut caller: wasmtime::StoreContextMut<'_, T>| {
+                                let r = fun(caller);
+                                Ok((r,))
+                            })
+                                as Box<
+                                    dyn FnOnce(
+                                        wasmtime::StoreContextMut<'_, T>,
+                                    ) -> wasmtime::Result<
+                                            (wasmtime::component::HostFuture<u32>,),
+                                        > + Send + Sync,
+                                >
+                        })
+                            as ::core::pin::Pin<
+                                Box<
+                                    dyn ::core::future::Future<
+                                        Output = Box<
+                                            dyn FnOnce(
+                                                wasmtime::StoreContextMut<'_, T>,
+                                            ) -> wasmtime::Result<
+                                                    (wasmtime::component::HostFuture<u32>,),
+                                                > + Send + Sync,
+                                        >,
+                                    > + Send + Sync + 'static,
+                                >,
+                            >
+                    },
+                )?;
+                inst.func_wrap_concurrent(
+                    "echo",
+                    move |
+                        mut caller: wasmtime::StoreContextMut<'_, T>,
+                        (arg0,): (wasmtime::component::HostStream<u8>,)|
+                    {
+                        let host = caller;
+                        let r = <G::Host as Host>::echo(host, arg0);
+                        Box::pin(async move {
+                            let fun = r.await;
+                            Box::new(move |mut caller: wasmtime::StoreContextMut<'_, T>| {
+                                let r = fun(caller);
+                                Ok((r,))
+                            })
+                                as Box<
+                                    dyn FnOnce(
+                                        wasmtime::StoreContextMut<'_, T>,
+                                    ) -> wasmtime::Result<
+                                            (wasmtime::component::HostStream<u8>,),
+                                        > + Send + Sync,
+                                >
+                        })
+                            as ::core::pin::Pin<
+                                Box<
+                                    dyn ::core::future::Future<
+                                        Output = Box<
+                                            dyn FnOnce(
+                                                wasmtime::StoreContextMut<'_, T>,
+                                            ) -> wasmtime::Result<
+                                                    (wasmtime::component::HostStream<u8>,),
+                                                > + Send + Sync,
+                                        >,
+                                    > + Send + Sync + 'static,
+                                >,
+                            >
+                    },
+                )?;
+                inst.func_wrap_concurrent(
+                    "ready",
+                    move |mut caller: wasmtime::StoreContextMut<'_, T>, (): ()| {
+                        let host = caller;
+                        let r = <G::Host as Host>::ready(host);
+                        Box::pin(async move {
+                            let fun = r.await;
+                            Box::new(move |mut caller: wasmtime::StoreContextMut<'_, T>| {
+                                let r = fun(caller);
+                                Ok((r,))
+                            })
+                                as Box<
+                                    dyn FnOnce(
+                                        wasmtime::StoreContextMut<'_, T>,
+                                    ) -> wasmtime::Result<
+                                            (wasmtime::component::HostFuture<()>,),
+                                        > + Send + Sync,
+                                >
+                        })
+                            as ::core::pin::Pin<
+                                Box<
+                                    dyn ::core::future::Future<
+                                        Output = Box<
+                                            dyn FnOnce(
+                                                wasmtime::StoreContextMut<'_, T>,
+                                            ) -> wasmtime::Result<
+                                                    (wasmtime::component::HostFuture<()>,),
+                                                > + Send + Sync,
+                                        >,
+                                    > + Send + Sync + 'static,
+                                >,
+                            >
+                    },
+                )?;
+                Ok(())
+            }
+            pub fn add_to_linker<T, U>(
+                linker: &mut wasmtime::component::Linker<T>,
+                get: impl Fn(&mut T) -> &mut U + Send + Sync + Copy + 'static,
+            ) -> wasmtime::Result<()>
+            where
+                U: Host<Data = T> + Send,
+                T: Send + 'static,
+            {
+                add_to_linker_get_host(linker, get)
+            }
+            impl<_T: Host> Host for &mut _T {
+                type Data = _T::Data;
+                fn read_all(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                    s: wasmtime::component::HostStream<Chunk>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostFuture<u32> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized,
+                {
+                    <_T as Host>::read_all(store, s)
+                }
+                fn echo(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                    s: wasmtime::component::HostStream<u8>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostStream<u8> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized,
+                {
+                    <_T as Host>::echo(store, s)
+                }
+                fn ready(
+                    store: wasmtime::StoreContextMut<'_, Self::Data>,
+                ) -> impl ::core::future::Future<
+                    Output = impl FnOnce(
+                        wasmtime::StoreContextMut<'_, Self::Data>,
+                    ) -> wasmtime::component::HostFuture<()> + Send + Sync + 'static,
+                > + Send + Sync + 'static
+                where
+                    Self: Sized,
+                {
+                    <_T as Host>::ready(store)
+                }
+            }
+        }
+    }
+}
+pub mod exports {
+    pub mod foo {
+        pub mod foo {
+            #[allow(clippy::all)]
+            pub mod transfer {
+                #[allow(unused_imports)]
+                use wasmtime::component::__internal::{anyhow, Box};
+                #[derive(wasmtime::component::ComponentType)]
+                #[derive(wasmtime::component::Lift)]
+                #[derive(wasmtime::component::Lower)]
+                #[component(record)]
+                #[derive(Clone)]
+                pub struct Chunk {
+                    #[component(name = "bytes")]
+                    pub bytes: wasmtime::component::__internal::Vec<u8>,
+                }
+                impl core::fmt::Debug for Chunk {
+                    fn fmt(
+                        &self,
+                        f: &mut core::fmt::Formatter<'_>,
+                    ) -> core::fmt::Result {
+                        f.debug_struct("Chunk").field("bytes", &self.bytes).finish()
+                    }
+                }
+                const _: () = {
+                    assert!(
+                        8 == < Chunk as wasmtime::component::ComponentType >::SIZE32
+                    );
+                    assert!(
+                        4 == < Chunk as wasmtime::component::ComponentType >::ALIGN32
+                    );
+                };
+                pub struct Guest {
+                    read_all: wasmtime::component::Func,
+                    echo: wasmtime::component::Func,
+                    ready: wasmtime::component::Func,
+                }
+                #[derive(Clone)]
+                pub struct GuestIndices {
+                    read_all: wasmtime::component::ComponentExportIndex,
+                    echo: wasmtime::component::ComponentExportIndex,
+                    ready: wasmtime::component::ComponentExportIndex,
+                }
+                impl GuestIndices {
+                    /// Constructor for [`GuestIndices`] which takes a
+                    /// [`Component`](wasmtime::component::Component) as input and can be executed
+                    /// before instantiation.
+                    ///
+                    /// This constructor can be used to front-load string lookups to find exports
+                    /// within a component.
+                    pub fn new(
+                        component: &wasmtime::component::Component,
+                    ) -> wasmtime::Result<GuestIndices> {
+                        let (_, instance) = component
+                            .export_index(None, "foo:foo/transfer")
+                            .ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "no exported instance named `foo:foo/transfer`"
+                                )
+                            })?;
+                        Self::_new(|name| {
+                            component.export_index(Some(&instance), name).map(|p| p.1)
+                        })
+                    }
+                    /// This constructor is similar to [`GuestIndices::new`] except that it
+                    /// performs string lookups after instantiation time.
+                    pub fn new_instance(
+                        mut store: impl wasmtime::AsContextMut,
+                        instance: &wasmtime::component::Instance,
+                    ) -> wasmtime::Result<GuestIndices> {
+                        let instance_export = instance
+                            .get_export(&mut store, None, "foo:foo/transfer")
+                            .ok_or_else(|| {
+                                anyhow::anyhow!(
+                                    "no exported instance named `foo:foo/transfer`"
+                                )
+                            })?;
+                        Self::_new(|name| {
+                            instance.get_export(&mut store, Some(&instance_export), name)
+                        })
+                    }
+                    fn _new(
+                        mut lookup: impl FnMut(
+                            &str,
+                        ) -> Option<wasmtime::component::ComponentExportIndex>,
+                    ) -> wasmtime::Result<GuestIndices> {
+                        let mut lookup = move |name| {
+                            lookup(name)
+                                .ok_or_else(|| {
+                                    anyhow::anyhow!(
+                                        "instance export `foo:foo/transfer` does \
+                not have export `{name}`"
+                                    )
+                                })
+                        };
+                        let _ = &mut lookup;
+                        let read_all = lookup("read-all")?;
+                        let echo = lookup("echo")?;
+                        let ready = lookup("ready")?;
+                        Ok(GuestIndices {
+                            read_all,
+                            echo,
+                            ready,
+                        })
+                    }
+                    pub fn load(
+                        &self,
+                        mut store: impl wasmtime::AsContextMut,
+                        instance: &wasmtime::component::Instance,
+                    ) -> wasmtime::Result<Guest> {
+                        let mut store = store.as_context_mut();
+                        let _ = &mut store;
+                        let _instance = instance;
+                        let read_all = *_instance
+                            .get_typed_func::<
+                                (wasmtime::component::HostStream<Chunk>,),
+                                (wasmtime::component::HostFuture<u32>,),
+                            >(&mut store, &self.read_all)?
+                            .func();
+                        let echo = *_instance
+                            .get_typed_func::<
+                                (wasmtime::component::HostStream<u8>,),
+                                (wasmtime::component::HostStream<u8>,),
+                            >(&mut store, &self.echo)?
+                            .func();
+                        let ready = *_instance
+                            .get_typed_func::<
+                                (),
+                                (wasmtime::component::HostFuture<()>,),
+                            >(&mut store, &self.ready)?
+                            .func();
+                        Ok(Guest { read_all, echo, ready })
+                    }
+                }
+                impl Guest {
+                    pub async fn call_read_all<S: wasmtime::AsContextMut>(
+                        &self,
+                        mut store: S,
+                        arg0: wasmtime::component::HostStream<Chunk>,
+                    ) -> wasmtime::Result<
+                        wasmtime::component::Promise<
+                            wasmtime::component::HostFuture<u32>,
+                        >,
+                    >
+                    where
+                        <S as wasmtime::AsContext>::Data: Send + 'static,
+                    {
+                        let callee = unsafe {
+                            wasmtime::component::TypedFunc::<
+                                (wasmtime::component::HostStream<Chunk>,),
+                                (wasmtime::component::HostFuture<u32>,),
+                            >::new_unchecked(self.read_all)
+                        };
+                        let promise = callee
+                            .call_concurrent(store.as_context_mut(), (arg0,))
+                            .await?;
+                        Ok(promise.map(|(v,)| v))
+                    }
+                    pub async fn call_echo<S: wasmtime::AsContextMut>(
+                        &self,
+                        mut store: S,
+                        arg0: wasmtime::component::HostStream<u8>,
+                    ) -> wasmtime::Result<
+                        wasmtime::component::Promise<wasmtime::component::HostStream<u8>>,
+                    >
+                    where
+                        <S as wasmtime::AsContext>::Data: Send + 'static,
+                    {
+                        let callee = unsafe {
+                            wasmtime::component::TypedFunc::<
+                                (wasmtime::component::HostStream<u8>,),
+                                (wasmtime::component::HostStream<u8>,),
+                            >::new_unchecked(self.echo)
+                        };
+                        let promise = callee
+                            .call_concurrent(store.as_context_mut(), (arg0,))
+                            .await?;
+                        Ok(promise.map(|(v,)| v))
+                    }
+                    pub async fn call_ready<S: wasmtime::AsContextMut>(
+                        &self,
+                        mut store: S,
+                    ) -> wasmtime::Result<
+                        wasmtime::component::Promise<wasmtime::component::HostFuture<()>>,
+                    >
+                    where
+                        <S as wasmtime::AsContext>::Data: Send + 'static,
+                    {
+                        let callee = unsafe {
+                            wasmtime::component::TypedFunc::<
+                                (),
+                                (wasmtime::component::HostFuture<()>,),
+                            >::new_unchecked(self.ready)
+                        };
+                        let promise = callee
+                            .call_concurrent(store.as_context_mut(), ())
+                            .await?;
+                        Ok(promise.map(|(v,)| v))
+                    }
+                }
+            }
+        }
+    }
+}
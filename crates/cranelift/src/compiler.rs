@@ -27,14 +27,16 @@ use std::path;
 use std::sync::{Arc, Mutex};
 use wasmparser::{FuncValidatorAllocations, FunctionBody};
 use wasmtime_environ::{
-    AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, DefinedFuncIndex, FlagValue,
-    FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation, ModuleTypesBuilder, PtrSize,
-    RelocationTarget, StackMapInformation, StaticModuleIndex, TrapEncodingBuilder, TrapSentinel,
-    TripleExt, Tunables, VMOffsets, WasmFuncType, WasmFunctionInfo, WasmValType,
+    AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, DefinedFuncIndex,
+    ExecutionProfile, FlagValue, FunctionBodyData, FunctionLoc, HostCall, ModuleTranslation,
+    ModuleTypesBuilder, PtrSize, RelocationTarget, StackMapInformation, StaticModuleIndex,
+    TrapEncodingBuilder, TrapSentinel, TripleExt, Tunables, VMOffsets, WasmFuncType,
+    WasmFunctionInfo, WasmValType,
 };
 
 #[cfg(feature = "component-model")]
 mod component;
+mod inline;
 
 struct IncrementalCacheContext {
     #[cfg(feature = "incremental-cache")]
@@ -266,6 +268,7 @@ impl wasmtime_environ::Compiler for Compiler {
             }
         }
         let FunctionBodyData { validator, body } = input;
+        let features = validator.features;
         let mut validator =
             validator.into_validator(mem::take(&mut compiler.cx.validator_allocations));
         compiler.cx.func_translator.translate_body(
@@ -275,6 +278,29 @@ impl wasmtime_environ::Compiler for Compiler {
             &mut func_env,
         )?;
 
+        // Inline small callees defined in this module, if enabled. This is
+        // skipped when generating native debug information since inlined code
+        // isn't described in the DWARF for the caller.
+        if self.tunables.inlining && !self.tunables.generate_native_debuginfo {
+            let inliner = inline::ModuleInliner::new(
+                self,
+                translation,
+                types,
+                validator.resources(),
+                features,
+                func_index,
+            );
+            if let Some(inliner) = inliner {
+                context
+                    .inline(inliner)
+                    .map_err(|error| CompileError::Codegen(pretty_error(&context.func, error)))?;
+            }
+        }
+
+        if let Some(profile) = &translation.execution_profile {
+            apply_execution_profile(&mut context.func, profile, body.range());
+        }
+
         let (info, func) = compiler.finish_with_info(
             Some((&body, &self.tunables)),
             &format!("wasm_func_{}", func_index.as_u32()),
@@ -1095,6 +1121,46 @@ fn clif_to_env_stack_maps(
     stack_maps
 }
 
+/// Attach the counts in `profile` to the blocks of `func`, whose Wasm body
+/// occupies `range` in the original module.
+///
+/// Each block gets the highest count of any of its instructions. Instructions
+/// that originate from this function's body but have no count of their own are
+/// considered to never have executed, unless the profile has no counts for
+/// this function at all, in which case no profile is attached.
+fn apply_execution_profile(
+    func: &mut ir::Function,
+    profile: &ExecutionProfile,
+    range: std::ops::Range<usize>,
+) {
+    let range = u32::try_from(range.start).unwrap()..u32::try_from(range.end).unwrap();
+    if profile.range(range.clone()).next().is_none() {
+        return;
+    }
+
+    let counts = func
+        .layout
+        .blocks()
+        .filter_map(|block| {
+            func.layout
+                .block_insts(block)
+                .filter_map(|inst| {
+                    // Code inlined from other functions carries offsets outside
+                    // of this body, and is left for the edges into it to decide.
+                    let loc = func.srcloc(inst);
+                    let offset = loc.bits();
+                    (!loc.is_default() && range.contains(&offset))
+                        .then(|| profile.count(offset).unwrap_or(0))
+                })
+                .max()
+                .map(|count| (block, count))
+        })
+        .collect::<Vec<_>>();
+    for (block, count) in counts {
+        func.profile.set_block_count(block, count);
+    }
+}
+
 fn declare_and_call(
     builder: &mut FunctionBuilder,
     signature: ir::Signature,
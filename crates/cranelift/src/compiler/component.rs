@@ -262,6 +262,11 @@ impl<'a> TrampolineCompiler<'a> {
                     me.raise_if_host_trapped(rets.pop().unwrap());
                 })
             }
+            Trampoline::AdapterCopy => {
+                self.translate_host_libcall(host::adapter_copy, |me, rets| {
+                    me.raise_if_host_trapped(rets.pop().unwrap());
+                })
+            }
             Trampoline::SyncEnterCall => self.translate_sync_enter(),
             Trampoline::SyncExitCall { callback } => self.translate_sync_exit(*callback),
             Trampoline::AsyncEnterCall => {
@@ -445,8 +450,8 @@ impl<'a> TrampolineCompiler<'a> {
     fn translate_waitable_join(&mut self, instance: RuntimeComponentInstanceIndex) {
         let args = self.builder.func.dfg.block_params(self.block0).to_vec();
         let vmctx = args[0];
-        let set = args[2];
-        let waitable = args[3];
+        let waitable = args[2];
+        let set = args[3];
 
         let instance = self
             .builder
@@ -631,20 +636,22 @@ impl<'a> TrampolineCompiler<'a> {
     ) {
         let args = self.builder.func.dfg.block_params(self.block0).to_vec();
         let vmctx = args[0];
+        let set = args[2];
+        let payload = args[3];
 
-        let mut callee_args = vec![
+        let callee_args = [
             vmctx,
             self.builder
                 .ins()
                 .iconst(ir::types::I32, i64::from(caller_instance.as_u32())),
+            set,
             self.builder
                 .ins()
                 .iconst(ir::types::I8, if async_ { 1 } else { 0 }),
             self.load_memory(vmctx, memory),
+            payload,
         ];
 
-        callee_args.extend(args[2..].iter().copied());
-
         self.translate_intrinsic_libcall(
             vmctx,
             get_libcall,
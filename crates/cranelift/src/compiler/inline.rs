@@ -0,0 +1,155 @@
+//! The policy for inlining calls between functions of the same module.
+//!
+//! Only direct calls to small functions defined in the same module are
+//! inlined. Callees are translated from their original Wasm bodies, once per
+//! caller no matter how many call sites they have, which keeps the policy
+//! independent of the order in which functions are compiled.
+
+use crate::compiler::Compiler;
+use crate::func_environ::FuncEnvironment;
+use crate::translate::FuncTranslator;
+use crate::wasm_call_signature;
+use cranelift_codegen::inline::{Inline, InlineCommand};
+use cranelift_codegen::ir::{self, UserExternalName, UserFuncName};
+use cranelift_codegen::settings::OptLevel;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use wasmparser::{FuncToValidate, ValidatorResources, WasmFeatures, WasmModuleResources};
+use wasmtime_environ::{FuncIndex, ModuleTranslation, ModuleTypesBuilder};
+
+/// Size limits, in bytes of Wasm function bodies, for inlining.
+struct Budget {
+    /// The largest callee which is inlined.
+    callee: usize,
+    /// The total size of all callees inlined into one caller.
+    total: usize,
+}
+
+impl Budget {
+    fn for_opt_level(opt_level: OptLevel) -> Option<Budget> {
+        match opt_level {
+            OptLevel::None => None,
+            OptLevel::Speed => Some(Budget {
+                callee: 64,
+                total: 2048,
+            }),
+            OptLevel::SpeedAndSize => Some(Budget {
+                callee: 16,
+                total: 256,
+            }),
+        }
+    }
+}
+
+/// An [`Inline`] implementation which inlines small callees defined within
+/// the same module as the caller.
+pub(super) struct ModuleInliner<'a> {
+    compiler: &'a Compiler,
+    translation: &'a ModuleTranslation<'a>,
+    types: &'a ModuleTypesBuilder,
+    resources: &'a ValidatorResources,
+    features: WasmFeatures,
+    caller: FuncIndex,
+    budget: Budget,
+    /// Callees translated so far, or `None` for those which aren't inlined.
+    callees: HashMap<FuncIndex, Option<ir::Function>>,
+}
+
+impl<'a> ModuleInliner<'a> {
+    /// Creates an inliner for the calls within `caller`, or `None` if
+    /// inlining is disabled at the configured optimization level.
+    pub(super) fn new(
+        compiler: &'a Compiler,
+        translation: &'a ModuleTranslation<'a>,
+        types: &'a ModuleTypesBuilder,
+        resources: &'a ValidatorResources,
+        features: WasmFeatures,
+        caller: FuncIndex,
+    ) -> Option<Self> {
+        let budget = Budget::for_opt_level(compiler.isa().flags().opt_level())?;
+        Some(ModuleInliner {
+            compiler,
+            translation,
+            types,
+            resources,
+            features,
+            caller,
+            budget,
+            callees: HashMap::new(),
+        })
+    }
+
+    /// Returns the CLIF of the Wasm function `index` if it's small enough to
+    /// be inlined at another call site.
+    fn callee(&mut self, index: FuncIndex) -> Option<&ir::Function> {
+        if index == self.caller {
+            return None;
+        }
+        let module = &self.translation.module;
+        let defined = module.defined_func_index(index)?;
+        let size = self.translation.function_bodies[defined].range().len();
+        if size > self.budget.callee || size > self.budget.total {
+            return None;
+        }
+        if !self.callees.contains_key(&index) {
+            let func = self.translate(index);
+            self.callees.insert(index, func);
+        }
+        let func = self.callees[&index].as_ref()?;
+        self.budget.total -= size;
+        Some(func)
+    }
+
+    /// Translates the Wasm function `index` to CLIF, or returns `None` if it
+    /// fails to translate.
+    fn translate(&self, index: FuncIndex) -> Option<ir::Function> {
+        let module = &self.translation.module;
+        let defined = module.defined_func_index(index)?;
+        let body = self.translation.function_bodies[defined].clone();
+
+        let sig = module.functions[index].signature.unwrap_module_type_index();
+        let wasm_func_ty = self.types[sig].unwrap_func();
+        let mut func = ir::Function::with_name_signature(
+            UserFuncName::User(UserExternalName {
+                namespace: crate::NS_WASM_FUNC,
+                index: index.as_u32(),
+            }),
+            wasm_call_signature(self.compiler.isa(), wasm_func_ty, self.compiler.tunables()),
+        );
+        let mut func_env =
+            FuncEnvironment::new(self.compiler, self.translation, self.types, wasm_func_ty);
+        let mut validator = FuncToValidate {
+            resources: self.resources,
+            index: index.as_u32(),
+            ty: self.resources.type_index_of_function(index.as_u32())?,
+            features: self.features,
+        }
+        .into_validator(Default::default());
+        FuncTranslator::new()
+            .translate_body(&mut validator, body, &mut func, &mut func_env)
+            .ok()?;
+        Some(func)
+    }
+}
+
+impl Inline for ModuleInliner<'_> {
+    fn inline(
+        &mut self,
+        caller: &ir::Function,
+        _call_inst: ir::Inst,
+        callee: ir::FuncRef,
+        _call_args: &[ir::Value],
+    ) -> InlineCommand<'_> {
+        let ir::ExternalName::User(name) = caller.dfg.ext_funcs[callee].name else {
+            return InlineCommand::KeepCall;
+        };
+        let name = &caller.params.user_named_funcs()[name];
+        if name.namespace != crate::NS_WASM_FUNC {
+            return InlineCommand::KeepCall;
+        }
+        match self.callee(FuncIndex::from_u32(name.index)) {
+            Some(func) => InlineCommand::Inline(Cow::Borrowed(func)),
+            None => InlineCommand::KeepCall,
+        }
+    }
+}
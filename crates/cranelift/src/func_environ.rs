@@ -20,11 +20,11 @@ use smallvec::SmallVec;
 use std::mem;
 use wasmparser::{Operator, WasmFeatures};
 use wasmtime_environ::{
-    BuiltinFunctionIndex, DataIndex, ElemIndex, EngineOrModuleTypeIndex, FuncIndex, GlobalIndex,
-    IndexType, Memory, MemoryIndex, Module, ModuleInternedTypeIndex, ModuleTranslation,
-    ModuleTypesBuilder, PtrSize, Table, TableIndex, TripleExt, Tunables, TypeConvert, TypeIndex,
-    VMOffsets, WasmCompositeInnerType, WasmFuncType, WasmHeapTopType, WasmHeapType, WasmRefType,
-    WasmResult, WasmValType,
+    BuiltinFunctionIndex, CoverageMap, DataIndex, ElemIndex, EngineOrModuleTypeIndex, FuncIndex,
+    GlobalIndex, IndexType, Memory, MemoryIndex, Module, ModuleInternedTypeIndex,
+    ModuleTranslation, ModuleTypesBuilder, PtrSize, Table, TableIndex, TripleExt, Tunables,
+    TypeConvert, TypeIndex, VMOffsets, WasmCompositeInnerType, WasmFuncType, WasmHeapTopType,
+    WasmHeapType, WasmRefType, WasmResult, WasmValType,
 };
 use wasmtime_environ::{FUNCREF_INIT_BIT, FUNCREF_MASK};
 
@@ -154,6 +154,10 @@ pub struct FuncEnvironment<'module_environment> {
 
     fuel_consumed: i64,
 
+    /// Whether the next instruction starts a new basic block, which gets a
+    /// code coverage counter when `Tunables::coverage` is enabled.
+    coverage_block_start: bool,
+
     /// A `GlobalValue` in CLIF which represents the stack limit.
     ///
     /// Typically this resides in the `stack_limit` value of `ir::Function` but
@@ -205,6 +209,8 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             // functions should consume at least some fuel.
             fuel_consumed: 1,
 
+            coverage_block_start: true,
+
             #[cfg(feature = "wmemcheck")]
             translation,
 
@@ -452,6 +458,33 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         }
     }
 
+    /// Increments the code coverage counter of the block starting at the
+    /// instruction at `pos`, if one starts there.
+    fn coverage_before_op(
+        &mut self,
+        pos: usize,
+        builder: &mut FunctionBuilder<'_>,
+        reachable: bool,
+    ) {
+        if !mem::replace(&mut self.coverage_block_start, false) || !reachable {
+            return;
+        }
+        let pos = u32::try_from(pos).unwrap();
+        let Some(counter) = self.module.coverage.counter(pos) else {
+            debug_assert!(false, "no coverage counter for block at {pos:#x}");
+            return;
+        };
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let offset = i32::try_from(self.offsets.vmctx_coverage_counter(counter)).unwrap();
+        let count = builder
+            .ins()
+            .load(ir::types::I64, ir::MemFlags::trusted(), vmctx, offset);
+        let count = builder.ins().iadd_imm(count, 1);
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), count, vmctx, offset);
+    }
+
     /// Adds `self.fuel_consumed` to the `fuel_var`, zero-ing out the amount of
     /// fuel consumed at that point.
     fn fuel_increment_var(&mut self, builder: &mut FunctionBuilder<'_>) {
@@ -3045,12 +3078,16 @@ impl FuncEnvironment<'_> {
     pub fn before_translate_operator(
         &mut self,
         op: &Operator,
+        pos: usize,
         builder: &mut FunctionBuilder,
         state: &FuncTranslationState,
     ) -> WasmResult<()> {
         if self.tunables.consume_fuel {
             self.fuel_before_op(op, builder, state.reachable());
         }
+        if self.tunables.coverage {
+            self.coverage_before_op(pos, builder, state.reachable());
+        }
         Ok(())
     }
 
@@ -3063,6 +3100,9 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_after_op(op, builder);
         }
+        if self.tunables.coverage {
+            self.coverage_block_start = CoverageMap::ends_block(op);
+        }
         Ok(())
     }
 
@@ -168,7 +168,7 @@ impl Factc {
             });
         }
 
-        let mut fact_module = Module::new(&types, self.debug);
+        let mut fact_module = Module::new(&types, self.debug, false);
         for (i, adapter) in adapters.iter().enumerate() {
             fact_module.adapt(&format!("adapter{i}"), adapter);
         }
@@ -195,7 +195,7 @@ fn target(data: &[u8]) -> arbitrary::Result<()> {
         validator.reset();
     }
 
-    let mut fact_module = Module::new(&types, ret.debug);
+    let mut fact_module = Module::new(&types, ret.debug, false);
     for (i, adapter) in adapters.iter().enumerate() {
         fact_module.adapt(&format!("adapter{i}"), adapter);
     }
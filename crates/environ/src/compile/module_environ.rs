@@ -1,14 +1,15 @@
 use crate::module::{
-    FuncRefIndex, Initializer, MemoryInitialization, MemoryInitializer, Module, TableSegment,
-    TableSegmentElements,
+    CoverageMap, FuncRefIndex, Initializer, MemoryInitialization, MemoryInitializer, Module,
+    TableSegment, TableSegmentElements,
 };
 use crate::prelude::*;
 use crate::{
     ConstExpr, ConstOp, DataIndex, DefinedFuncIndex, ElemIndex, EngineOrModuleTypeIndex,
-    EntityIndex, EntityType, FuncIndex, GlobalIndex, IndexType, InitMemory, MemoryIndex,
-    ModuleInternedTypeIndex, ModuleTypesBuilder, PrimaryMap, SizeOverflow, StaticMemoryInitializer,
-    TableIndex, TableInitialValue, Tag, TagIndex, Tunables, TypeConvert, TypeIndex, Unsigned,
-    WasmError, WasmHeapTopType, WasmHeapType, WasmResult, WasmValType, WasmparserTypeConverter,
+    EntityIndex, EntityType, ExecutionProfile, FuncIndex, GlobalIndex, IndexType, InitMemory,
+    MemoryIndex, ModuleInternedTypeIndex, ModuleTypesBuilder, PrimaryMap, SizeOverflow,
+    StaticMemoryInitializer, TableIndex, TableInitialValue, Tag, TagIndex, Tunables, TypeConvert,
+    TypeIndex, Unsigned, WasmError, WasmHeapTopType, WasmHeapType, WasmResult, WasmValType,
+    WasmparserTypeConverter,
 };
 use anyhow::{bail, Result};
 use cranelift_entity::packed_option::ReservedValue;
@@ -54,6 +55,13 @@ pub struct ModuleTranslation<'data> {
     /// References to the function bodies.
     pub function_body_inputs: PrimaryMap<DefinedFuncIndex, FunctionBodyData<'data>>,
 
+    /// The bodies of all functions defined in this module.
+    ///
+    /// Unlike `function_body_inputs` these are not consumed by compilation and
+    /// remain available while other functions are compiled, for example to
+    /// inline callees into their callers.
+    pub function_bodies: PrimaryMap<DefinedFuncIndex, FunctionBody<'data>>,
+
     /// A list of type signatures which are considered exported from this
     /// module, or those that can possibly be called. This list is sorted, and
     /// trampolines for each of these signatures are required.
@@ -88,6 +96,12 @@ pub struct ModuleTranslation<'data> {
     /// concatenated for the final artifact.
     pub passive_data: Vec<&'data [u8]>,
 
+    /// Execution profile of this module used to guide code layout, if any.
+    ///
+    /// This is filled in by the embedder after translation since profiles
+    /// are specific to a single module binary.
+    pub execution_profile: Option<Arc<ExecutionProfile>>,
+
     /// Total size of all passive data pushed into `passive_data` so far.
     total_passive_data: u32,
 
@@ -528,6 +542,7 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                 self.validator.code_section_start(count, &range)?;
                 let cnt = usize::try_from(count).unwrap();
                 self.result.function_body_inputs.reserve_exact(cnt);
+                self.result.function_bodies.reserve_exact(cnt);
                 self.result.debuginfo.wasm_file.code_section_offset = range.start as u64;
             }
 
@@ -557,6 +572,10 @@ impl<'a, 'data> ModuleEnvironment<'a, 'data> {
                             params: sig.params().into(),
                         });
                 }
+                if self.tunables.coverage {
+                    self.record_coverage_blocks(&body)?;
+                }
+                self.result.function_bodies.push(body.clone());
                 self.result
                     .function_body_inputs
                     .push(FunctionBodyData { validator, body });
@@ -790,6 +809,28 @@ and for re-adding support for interface types you can see this issue:
         self.result.module.num_escaped_funcs += 1;
     }
 
+    /// Records the start of each basic block of `body` in the module's
+    /// `CoverageMap`.
+    fn record_coverage_blocks(&mut self, body: &FunctionBody<'data>) -> WasmResult<()> {
+        let coverage = &mut self.result.module.coverage;
+        let first = coverage.num_counters();
+        let mut reader = body.get_operators_reader()?;
+        let mut block_start = true;
+        while !reader.eof() {
+            let offset = u32::try_from(reader.original_position()).unwrap();
+            let op = reader.read()?;
+            if block_start {
+                coverage.blocks.push(offset);
+            }
+            block_start = CoverageMap::ends_block(&op);
+        }
+        let end = u32::try_from(body.range().end).unwrap();
+        coverage
+            .functions
+            .push((first..coverage.num_counters(), end));
+        Ok(())
+    }
+
     /// Parses the Name section of the wasm module.
     fn name_section(&mut self, names: NameSectionReader<'data>) -> WasmResult<()> {
         for subsection in names {
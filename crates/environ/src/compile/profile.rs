@@ -0,0 +1,57 @@
+//! Execution profiles used to guide code layout during compilation.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Execution counts for a WebAssembly module, used to guide code layout.
+///
+/// Counts are keyed by the byte offset of a WebAssembly instruction within the
+/// original module binary, the same offsets that appear in Wasmtime's address
+/// maps and backtraces. A profile is therefore only meaningful for the exact
+/// module it was recorded from: compiling a different module with it is not
+/// an error, but will lay out code according to unrelated counts.
+///
+/// Profiles can be gathered in any way that produces such counts, for example
+/// by sampling the program counter of a running module and mapping the
+/// samples back to Wasm offsets, or from an instrumented run of the module.
+///
+/// When compiling a function, an instruction without a count is treated as
+/// never having executed as long as the profile contains a count for some
+/// other instruction in the same function. Functions without any counts at
+/// all are compiled as if there was no profile.
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq)]
+pub struct ExecutionProfile {
+    counts: BTreeMap<u32, u64>,
+}
+
+impl ExecutionProfile {
+    /// Creates a new, empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the instruction at `offset` executed `count` more times.
+    pub fn add_count(&mut self, offset: u32, count: u64) {
+        let total = self.counts.entry(offset).or_insert(0);
+        *total = total.saturating_add(count);
+    }
+
+    /// Returns the number of times the instruction at `offset` executed, if it
+    /// was recorded.
+    pub fn count(&self, offset: u32) -> Option<u64> {
+        self.counts.get(&offset).copied()
+    }
+
+    /// Returns whether this profile contains no counts at all.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Returns all recorded `(offset, count)` pairs whose offset lies within
+    /// `range`, in increasing order of offset.
+    pub fn range(&self, range: Range<u32>) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.counts
+            .range(range)
+            .map(|(&offset, &count)| (offset, count))
+    }
+}
@@ -83,6 +83,9 @@ macro_rules! foreach_builtin_component_function {
             resource_enter_call(vmctx: vmctx);
             resource_exit_call(vmctx: vmctx) -> bool;
 
+            // Charges `bytes` copied by an adapter against the store's limits.
+            adapter_copy(vmctx: vmctx, bytes: u64) -> bool;
+
             #[cfg(feature = "component-model-async")]
             backpressure_set(vmctx: vmctx, caller_instance: u32, enabled: u32) -> bool;
             #[cfg(feature = "component-model-async")]
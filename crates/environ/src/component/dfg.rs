@@ -384,6 +384,7 @@ pub enum Trampoline {
     ResourceTransferBorrow,
     ResourceEnterCall,
     ResourceExitCall,
+    AdapterCopy,
     SyncEnterCall,
     SyncExitCall {
         callback: Option<CallbackId>,
@@ -897,6 +898,7 @@ impl LinearizeDfg<'_> {
             Trampoline::ResourceTransferBorrow => info::Trampoline::ResourceTransferBorrow,
             Trampoline::ResourceEnterCall => info::Trampoline::ResourceEnterCall,
             Trampoline::ResourceExitCall => info::Trampoline::ResourceExitCall,
+            Trampoline::AdapterCopy => info::Trampoline::AdapterCopy,
             Trampoline::SyncEnterCall => info::Trampoline::SyncEnterCall,
             Trampoline::SyncExitCall { callback } => info::Trampoline::SyncExitCall {
                 callback: callback.map(|v| self.runtime_callback(v)),
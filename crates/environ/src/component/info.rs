@@ -929,6 +929,11 @@ pub enum Trampoline {
     /// Same as `ResourceEnterCall` except for when exiting a call.
     ResourceExitCall,
 
+    /// An intrinsic used by FACT-generated modules which charges the number
+    /// of bytes about to be copied between components against the limits
+    /// configured for the store, trapping if they're exceeded.
+    AdapterCopy,
+
     /// An intrinsic used by FACT-generated modules to begin a call involving a
     /// sync-lowered import and async-lifted export.
     SyncEnterCall,
@@ -1035,6 +1040,7 @@ impl Trampoline {
             ResourceTransferBorrow => format!("component-resource-transfer-borrow"),
             ResourceEnterCall => format!("component-resource-enter-call"),
             ResourceExitCall => format!("component-resource-exit-call"),
+            AdapterCopy => format!("component-adapter-copy"),
             SyncEnterCall => format!("component-sync-enter-call"),
             SyncExitCall { .. } => format!("component-sync-exit-call"),
             AsyncEnterCall => format!("component-async-enter-call"),
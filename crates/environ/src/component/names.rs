@@ -138,6 +138,33 @@ where
     pub fn raw_get_mut(&mut self, key: &K) -> Option<&mut V> {
         self.definitions.get_mut(key)
     }
+
+    /// Removes all entries from this map for which `f` returns `false`.
+    ///
+    /// Semver-compatible lookups which resolved to a removed entry are removed
+    /// as well. Note that they are not redirected to any other entry on the
+    /// same semver track which remains in the map, so callers are expected to
+    /// remove either all or none of the versions of a name.
+    pub fn raw_retain(&mut self, mut f: impl FnMut(&K, &V) -> bool) {
+        let removed = self
+            .definitions
+            .iter()
+            .filter(|(k, v)| !f(k, v))
+            .map(|(k, _)| k.clone())
+            .collect::<Vec<_>>();
+        for key in removed.iter() {
+            self.definitions.swap_remove(key);
+        }
+        let alternates = self
+            .alternate_lookups
+            .iter()
+            .filter(|(_, (k, _))| removed.contains(k))
+            .map(|(alternate, _)| alternate.clone())
+            .collect::<Vec<_>>();
+        for key in alternates.iter() {
+            self.alternate_lookups.swap_remove(key);
+        }
+    }
 }
 
 impl<K, V> Default for NameMap<K, V>
@@ -271,5 +298,10 @@ mod tests {
         assert_eq!(map.get("a:b/c@1.0.1", &intern), Some(&3));
         assert_eq!(map.get("a:b/c@1.0.2", &intern), Some(&3));
         assert_eq!(map.get("a:b/c@1.1.0", &intern), Some(&3));
+
+        map.raw_retain(|k, _| !k.starts_with("a:b/c@"));
+        assert_eq!(map.get("a:b/c@1.0.0", &intern), None);
+        assert_eq!(map.get("a:b/c@1.0.2", &intern), None);
+        assert_eq!(map.get("a", &intern), Some(&0));
     }
 }
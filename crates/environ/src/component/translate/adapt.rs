@@ -187,8 +187,11 @@ impl<'data> Translator<'_, 'data> {
         // the module using standard core wasm translation, and then fills out
         // the dfg metadata for each adapter.
         for (module_id, adapter_module) in state.adapter_modules.iter() {
-            let mut module =
-                fact::Module::new(self.types.types(), self.tunables.debug_adapter_modules);
+            let mut module = fact::Module::new(
+                self.types.types(),
+                self.tunables.debug_adapter_modules,
+                self.tunables.component_adapter_copy_limit,
+            );
             let mut names = Vec::with_capacity(adapter_module.adapters.len());
             for adapter in adapter_module.adapters.iter() {
                 let name = format!("adapter{}", adapter.as_u32());
@@ -304,6 +307,7 @@ fn fact_import_to_core_def(
         }
         fact::Import::ResourceEnterCall => simple_intrinsic(dfg::Trampoline::ResourceEnterCall),
         fact::Import::ResourceExitCall => simple_intrinsic(dfg::Trampoline::ResourceExitCall),
+        fact::Import::AdapterCopy => simple_intrinsic(dfg::Trampoline::AdapterCopy),
         fact::Import::SyncEnterCall => simple_intrinsic(dfg::Trampoline::SyncEnterCall),
         fact::Import::SyncExitCall { callback } => {
             simple_intrinsic(dfg::Trampoline::SyncExitCall {
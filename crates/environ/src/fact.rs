@@ -60,6 +60,9 @@ pub static SYNC_ENTER_FIXED_PARAMS: &[ValType] = &[
 pub struct Module<'a> {
     /// Whether or not debug code is inserted into the adapters themselves.
     debug: bool,
+    /// Whether or not adapters charge the bytes they copy against the store's
+    /// limits.
+    limit_adapter_copies: bool,
     /// Type information from the creator of this `Module`
     types: &'a ComponentTypesBuilder,
 
@@ -85,6 +88,10 @@ pub struct Module<'a> {
     imported_resource_enter_call: Option<FuncIndex>,
     imported_resource_exit_call: Option<FuncIndex>,
 
+    /// Cached version of the imported trampoline charging bytes copied by
+    /// adapters against the store's limits.
+    imported_adapter_copy: Option<FuncIndex>,
+
     // Cached versions of imported trampolines for working with the async ABI.
     imported_async_enter_call: Option<FuncIndex>,
     imported_async_exit_call: Option<FuncIndex>,
@@ -210,9 +217,14 @@ enum HelperLocation {
 
 impl<'a> Module<'a> {
     /// Creates an empty module.
-    pub fn new(types: &'a ComponentTypesBuilder, debug: bool) -> Module<'a> {
+    pub fn new(
+        types: &'a ComponentTypesBuilder,
+        debug: bool,
+        limit_adapter_copies: bool,
+    ) -> Module<'a> {
         Module {
             debug,
+            limit_adapter_copies,
             types,
             core_types: Default::default(),
             core_imports: Default::default(),
@@ -229,6 +241,7 @@ impl<'a> Module<'a> {
             imported_resource_transfer_borrow: None,
             imported_resource_enter_call: None,
             imported_resource_exit_call: None,
+            imported_adapter_copy: None,
             imported_async_enter_call: None,
             imported_async_exit_call: None,
             imported_future_transfer: None,
@@ -692,6 +705,17 @@ impl<'a> Module<'a> {
         )
     }
 
+    fn import_adapter_copy(&mut self) -> FuncIndex {
+        self.import_simple(
+            "limits",
+            "adapter-copy",
+            &[ValType::I64],
+            &[],
+            Import::AdapterCopy,
+            |me| &mut me.imported_adapter_copy,
+        )
+    }
+
     fn translate_helper(&mut self, helper: Helper) -> FunctionId {
         *self.helper_funcs.entry(helper).or_insert_with(|| {
             // Generate a fresh `Function` with a unique id for what we're about to
@@ -818,6 +842,9 @@ pub enum Import {
     /// Tears down a previous entry and handles checking borrow-related
     /// metadata.
     ResourceExitCall,
+    /// Charges the bytes an adapter is about to copy against the limits of
+    /// the store.
+    AdapterCopy,
     /// An intrinsic used by FACT-generated modules to begin a call involving a
     /// sync-lowered import and async-lifted export.
     SyncEnterCall,
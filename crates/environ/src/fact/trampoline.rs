@@ -3081,6 +3081,24 @@ impl<'a, 'b> Compiler<'a, 'b> {
 
     fn malloc<'c>(&mut self, opts: &'c Options, size: MallocSize, align: u32) -> Memory<'c> {
         let realloc = opts.realloc.unwrap();
+
+        // Everything an adapter copies into the destination is first
+        // allocated here, so charge the allocation against the store's limit
+        // on bytes copied between components before performing it.
+        if self.module.limit_adapter_copies {
+            let copy = self.module.import_adapter_copy();
+            match size {
+                MallocSize::Const(size) => self.instruction(I64Const(size.into())),
+                MallocSize::Local(idx) => {
+                    self.instruction(LocalGet(idx));
+                    if !opts.memory64 {
+                        self.instruction(I64ExtendI32U);
+                    }
+                }
+            }
+            self.instruction(Call(copy.as_u32()));
+        }
+
         self.ptr_uconst(opts, 0);
         self.ptr_uconst(opts, 0);
         self.ptr_uconst(opts, align);
@@ -287,6 +287,73 @@ impl TableSegmentElements {
     }
 }
 
+/// The basic blocks of a module which are instrumented with counters when
+/// compiling with [`Tunables::coverage`].
+///
+/// Each counter is stored in the module's `VMContext` and counts how many
+/// times execution entered its block.
+#[derive(Default, Debug, Serialize, Deserialize)]
+pub struct CoverageMap {
+    /// The offset, within the original wasm file, of the first instruction of
+    /// each instrumented block, in ascending order. The index of a block in
+    /// this list is the index of its counter.
+    pub blocks: Vec<u32>,
+
+    /// For each defined function, the range of `blocks` it contains and the
+    /// offset of the end of its body.
+    pub functions: PrimaryMap<DefinedFuncIndex, (Range<u32>, u32)>,
+}
+
+impl CoverageMap {
+    /// Returns the index of the counter for the block starting at `offset`,
+    /// if there is one.
+    pub fn counter(&self, offset: u32) -> Option<u32> {
+        let index = self.blocks.binary_search(&offset).ok()?;
+        Some(u32::try_from(index).unwrap())
+    }
+
+    /// Returns the number of counters.
+    pub fn num_counters(&self) -> u32 {
+        u32::try_from(self.blocks.len()).unwrap()
+    }
+
+    /// Returns whether a new block starts right after the instruction `op`.
+    ///
+    /// Blocks start at the entry of a function, at loop headers, at the
+    /// start of either arm of an `if`, at the join point after a control
+    /// construct and after conditional branches.
+    pub fn ends_block(op: &wasmparser::Operator<'_>) -> bool {
+        use wasmparser::Operator;
+        matches!(
+            op,
+            Operator::Loop { .. }
+                | Operator::If { .. }
+                | Operator::Else
+                | Operator::End
+                | Operator::BrIf { .. }
+                | Operator::BrOnNull { .. }
+                | Operator::BrOnNonNull { .. }
+                | Operator::BrOnCast { .. }
+                | Operator::BrOnCastFail { .. }
+        )
+    }
+
+    /// Returns the range of wasm offsets covered by each counter of the
+    /// defined function `index`, along with the counter's index.
+    pub fn function_blocks(
+        &self,
+        index: DefinedFuncIndex,
+    ) -> impl Iterator<Item = (u32, Range<u32>)> + '_ {
+        let (counters, end) = self.functions[index].clone();
+        counters.map(move |i| {
+            let start = self.blocks[i as usize];
+            let next = self.blocks.get(i as usize + 1).copied();
+            let block_end = next.filter(|n| *n < end).unwrap_or(end);
+            (i, start..block_end)
+        })
+    }
+}
+
 /// A translated WebAssembly module, excluding the function bodies and
 /// memory initializers.
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -360,6 +427,10 @@ pub struct Module {
 
     /// WebAssembly exception and control tags.
     pub tags: PrimaryMap<TagIndex, Tag>,
+
+    /// Basic blocks instrumented for code coverage, empty unless compiling
+    /// with [`Tunables::coverage`].
+    pub coverage: CoverageMap,
 }
 
 /// Initialization routines for creating an instance, encompassing imports,
@@ -622,6 +693,7 @@ impl TypeTrace for Module {
             globals,
             global_initializers: _,
             tags,
+            coverage: _,
         } = self;
 
         for t in types.values().copied() {
@@ -671,6 +743,7 @@ impl TypeTrace for Module {
             globals,
             global_initializers: _,
             tags,
+            coverage: _,
         } = self;
 
         for t in types.values_mut() {
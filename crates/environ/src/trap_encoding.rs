@@ -92,6 +92,12 @@ pub enum Trap {
     /// Async-lifted export failed to produce a result by calling `task.return`
     /// before returning `STATUS_DONE` and/or after all host tasks completed.
     NoAsyncResult,
+
+    /// When the `component-model` feature is enabled this trap represents a
+    /// component exceeding one of the limits configured for the store through
+    /// a `ResourceLimiter`, such as the number of live resource handles or the
+    /// number of bytes transferred during a call.
+    ComponentLimitExceeded,
     // if adding a variant here be sure to update the `check!` macro below
 }
 
@@ -129,6 +135,7 @@ impl Trap {
             CastFailure
             CannotEnterComponent
             NoAsyncResult
+            ComponentLimitExceeded
         }
 
         None
@@ -160,6 +167,7 @@ impl fmt::Display for Trap {
             CastFailure => "cast failure",
             CannotEnterComponent => "cannot enter component instance",
             NoAsyncResult => "async-lifted export failed to produce a result",
+            ComponentLimitExceeded => "component exceeded a configured limit",
         };
         write!(f, "wasm trap: {desc}")
     }
@@ -76,6 +76,10 @@ define_tunables! {
         /// Whether or not to generate native DWARF debug information.
         pub generate_native_debuginfo: bool,
 
+        /// Whether or not small functions are inlined into their callers
+        /// within the same module.
+        pub inlining: bool,
+
         /// Whether or not to retain DWARF sections in compiled modules.
         pub parse_wasm_debuginfo: bool,
 
@@ -86,6 +90,10 @@ define_tunables! {
         /// Whether or not we use epoch-based interruption.
         pub epoch_interruption: bool,
 
+        /// Whether or not generated code counts how many times each basic
+        /// block is entered, for code coverage.
+        pub coverage: bool,
+
         /// Whether or not linear memories are allowed to be reallocated after
         /// initial allocation at runtime.
         pub memory_may_move: bool,
@@ -108,6 +116,10 @@ define_tunables! {
         /// assertions baked into them.
         pub debug_adapter_modules: bool,
 
+        /// Whether adapter modules charge the bytes they copy between
+        /// components against the store's limits.
+        pub component_adapter_copy_limit: bool,
+
         /// Whether or not lowerings for relaxed simd instructions are forced to
         /// be deterministic.
         pub relaxed_simd_deterministic: bool,
@@ -179,14 +191,17 @@ impl Tunables {
             // General options which have the same defaults regardless of
             // architecture.
             generate_native_debuginfo: false,
+            inlining: false,
             parse_wasm_debuginfo: true,
             consume_fuel: false,
             epoch_interruption: false,
+            coverage: false,
             memory_may_move: true,
             guard_before_linear_memory: true,
             table_lazy_init: true,
             generate_address_map: true,
             debug_adapter_modules: false,
+            component_adapter_copy_limit: false,
             relaxed_simd_deterministic: false,
             winch_callable: false,
             signals_based_traps: false,
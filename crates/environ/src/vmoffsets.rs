@@ -34,6 +34,7 @@
 //      globals: [VMGlobalDefinition; module.num_defined_globals],
 //      tags: [VMTagDefinition; module.num_defined_tags],
 //      func_refs: [VMFuncRef; module.num_escaped_funcs],
+//      coverage_counters: [u64; module.coverage.num_counters()],
 // }
 
 use crate::{
@@ -86,6 +87,8 @@ pub struct VMOffsets<P> {
     /// The number of escaped functions in the module, the size of the func_refs
     /// array.
     pub num_escaped_funcs: u32,
+    /// The number of code coverage counters in the module.
+    pub num_coverage_counters: u32,
 
     // precalculated offsets of various member fields
     imported_functions: u32,
@@ -99,6 +102,7 @@ pub struct VMOffsets<P> {
     defined_globals: u32,
     defined_tags: u32,
     defined_func_refs: u32,
+    coverage_counters: u32,
     size: u32,
 }
 
@@ -352,6 +356,8 @@ pub struct VMOffsetsFields<P> {
     /// The number of escaped functions in the module, the size of the function
     /// references array.
     pub num_escaped_funcs: u32,
+    /// The number of code coverage counters in the module.
+    pub num_coverage_counters: u32,
 }
 
 impl<P: PtrSize> VMOffsets<P> {
@@ -378,6 +384,7 @@ impl<P: PtrSize> VMOffsets<P> {
             num_defined_globals: cast_to_u32(module.globals.len() - module.num_imported_globals),
             num_defined_tags: cast_to_u32(module.tags.len() - module.num_imported_tags),
             num_escaped_funcs: cast_to_u32(module.num_escaped_funcs),
+            num_coverage_counters: module.coverage.num_counters(),
         })
     }
 
@@ -409,6 +416,7 @@ impl<P: PtrSize> VMOffsets<P> {
                     num_defined_tags: _,
                     num_owned_memories: _,
                     num_escaped_funcs: _,
+                    num_coverage_counters: _,
 
                     // used as the initial size below
                     size,
@@ -437,6 +445,7 @@ impl<P: PtrSize> VMOffsets<P> {
         }
 
         calculate_sizes! {
+            coverage_counters: "coverage counters",
             defined_func_refs: "module functions",
             defined_tags: "defined tags",
             defined_globals: "defined globals",
@@ -467,6 +476,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             num_defined_globals: fields.num_defined_globals,
             num_defined_tags: fields.num_defined_tags,
             num_escaped_funcs: fields.num_escaped_funcs,
+            num_coverage_counters: fields.num_coverage_counters,
             imported_functions: 0,
             imported_tables: 0,
             imported_memories: 0,
@@ -478,6 +488,7 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
             defined_globals: 0,
             defined_tags: 0,
             defined_func_refs: 0,
+            coverage_counters: 0,
             size: 0,
         };
 
@@ -536,6 +547,8 @@ impl<P: PtrSize> From<VMOffsetsFields<P>> for VMOffsets<P> {
                 ret.num_escaped_funcs,
                 ret.ptr.size_of_vm_func_ref(),
             ),
+            align(8),
+            size(coverage_counters) = cmul(ret.num_coverage_counters, 8),
         }
 
         ret.size = next_field_offset;
@@ -753,6 +766,12 @@ impl<P: PtrSize> VMOffsets<P> {
         self.defined_func_refs
     }
 
+    /// The offset of the `coverage_counters` array.
+    #[inline]
+    pub fn vmctx_coverage_counters_begin(&self) -> u32 {
+        self.coverage_counters
+    }
+
     /// Return the size of the `VMContext` allocation.
     #[inline]
     pub fn size_of_vmctx(&self) -> u32 {
@@ -845,6 +864,13 @@ impl<P: PtrSize> VMOffsets<P> {
         self.vmctx_func_refs_begin() + index.as_u32() * u32::from(self.ptr.size_of_vm_func_ref())
     }
 
+    /// Return the offset to the code coverage counter at index `index`.
+    #[inline]
+    pub fn vmctx_coverage_counter(&self, index: u32) -> u32 {
+        assert!(index < self.num_coverage_counters);
+        self.vmctx_coverage_counters_begin() + index * 8
+    }
+
     /// Return the offset to the `wasm_call` field in `*const VMFunctionBody` index `index`.
     #[inline]
     pub fn vmctx_vmfunction_import_wasm_call(&self, index: FuncIndex) -> u32 {
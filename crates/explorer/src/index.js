@@ -3,14 +3,20 @@
 /*** State *********************************************************************/
 
 class State {
-  constructor(wat, clif, asm) {
+  constructor(wat, clif, asm, diffAsm) {
     this.wat = wat;
     this.clif = clif;
     this.asm = asm;
+    this.diffAsm = diffAsm;
   }
 }
 
-const state = (window.STATE = new State(window.WAT, window.CLIF, window.ASM));
+const state = (window.STATE = new State(
+  window.WAT,
+  window.CLIF,
+  window.ASM,
+  window.DIFF_ASM,
+));
 
 /*** Hues for Offsets **********************************************************/
 
@@ -58,6 +64,26 @@ const asmByOffset = new Map();
 // Get clif instruction elements by Wasm offset.
 const clifByOffset = new Map();
 
+// Get diffed asm instruction elements by Wasm offset.
+const diffAsmByOffset = new Map();
+
+// Get the DWARF source location, rendered as `file:line:column`, by Wasm
+// offset.
+const sourceByOffset = new Map();
+
+const renderSource = source => {
+  const file = state.wat.files[source.file];
+  return source.column == 0
+    ? `${file}:${source.line}`
+    : `${file}:${source.line}:${source.column}`;
+};
+
+for (const chunk of state.wat.chunks) {
+  if (chunk.wasm_offset != null && chunk.source != null) {
+    sourceByOffset.set(chunk.wasm_offset, renderSource(chunk.source));
+  }
+}
+
 // Get all (WAT chunk or asm instruction) elements by offset.
 const anyByOffset = new Map();
 
@@ -97,92 +123,75 @@ const addClifElem = (offset, elem) => {
   anyByOffset.get(offset).push(elem);
 };
 
-/*** Event Handlers ************************************************************/
+const addDiffAsmElem = (offset, elem) => {
+  if (!diffAsmByOffset.has(offset)) {
+    diffAsmByOffset.set(offset, []);
+  }
+  diffAsmByOffset.get(offset).push(elem);
 
-const watElem = document.getElementById("wat");
-watElem.addEventListener(
-  "click",
-  event => {
-    if (event.target.dataset.wasmOffset == null) {
-      return;
-    }
+  if (!anyByOffset.has(offset)) {
+    anyByOffset.set(offset, []);
+  }
+  anyByOffset.get(offset).push(elem);
+};
 
-    const offset = parseInt(event.target.dataset.wasmOffset);
-    if (!asmByOffset.get(offset)) {
-      return;
+// Scroll the first element for the given offset in each of the given maps
+// into view, skipping maps which have no element for that offset.
+const scrollToOffset = (offset, ...maps) => {
+  for (const map of maps) {
+    const elems = map.get(offset);
+    if (!elems) {
+      continue;
     }
-
-    const firstClifElem = clifByOffset.get(offset)[0];
-    firstClifElem.scrollIntoView({
+    elems[0].scrollIntoView({
       behavior: "smooth",
       block: "center",
       inline: "nearest",
     });
-    const firstAsmElem = asmByOffset.get(offset)[0];
-    firstAsmElem.scrollIntoView({
-      behavior: "smooth",
-      block: "center",
-      inline: "nearest",
-    });
-  },
-  { passive: true },
-);
+  }
+};
 
-const asmElem = document.getElementById("asm");
-asmElem.addEventListener(
-  "click",
-  event => {
+/*** Event Handlers ************************************************************/
+
+const onClick = (...maps) => {
+  return event => {
     if (event.target.dataset.wasmOffset == null) {
       return;
     }
 
     const offset = parseInt(event.target.dataset.wasmOffset);
-    if (!watByOffset.get(offset)) {
-      return;
-    }
+    scrollToOffset(offset, ...maps);
+  };
+};
 
-    const firstWatElem = watByOffset.get(offset)[0];
-    firstWatElem.scrollIntoView({
-      behavior: "smooth",
-      block: "center",
-      inline: "nearest",
-    });
-    const firstClifElem = clifByOffset.get(offset)[0];
-    firstClifElem.scrollIntoView({
-      behavior: "smooth",
-      block: "center",
-      inline: "nearest",
-    });
-  },
+const watElem = document.getElementById("wat");
+watElem.addEventListener(
+  "click",
+  onClick(clifByOffset, asmByOffset, diffAsmByOffset),
+  { passive: true },
+);
+
+const asmElem = document.getElementById("asm");
+asmElem.addEventListener(
+  "click",
+  onClick(watByOffset, clifByOffset, diffAsmByOffset),
   { passive: true },
 );
+
 const clifElem = document.getElementById("clif");
 if (clifElem) {
   clifElem.addEventListener(
     "click",
-    event => {
-      if (event.target.dataset.wasmOffset == null) {
-        return;
-      }
-
-      const offset = parseInt(event.target.dataset.wasmOffset);
-      if (!watByOffset.get(offset)) {
-        return;
-      }
+    onClick(watByOffset, asmByOffset, diffAsmByOffset),
+    { passive: true },
+  );
+}
 
-      const firstWatElem = watByOffset.get(offset)[0];
-      firstWatElem.scrollIntoView({
-        behavior: "smooth",
-        block: "center",
-        inline: "nearest",
-      });
-      const firstAsmElem = asmByOffset.get(offset)[0];
-      firstAsmElem.scrollIntoView({
-        behavior: "smooth",
-        block: "center",
-        inline: "nearest",
-      });
-    },
+const diffAsmElem = document.getElementById("diff-asm");
+if (diffAsmElem) {
+  diffAsmElem.addEventListener(
+    "click",
+    onClick(watByOffset, clifByOffset, asmByOffset),
     { passive: true },
   );
 }
@@ -268,6 +277,9 @@ if (clifElem) {
         instElem.addEventListener("mouseenter", onMouseEnter);
         instElem.addEventListener("mouseleave", onMouseLeave);
         addClifElem(inst.wasm_offset, instElem);
+        if (sourceByOffset.has(inst.wasm_offset)) {
+          instElem.title = sourceByOffset.get(inst.wasm_offset);
+        }
       }
       bodyElem.appendChild(instElem);
     }
@@ -279,40 +291,72 @@ if (clifElem) {
 
 // Render the ASM.
 
-for (const func of state.asm.functions) {
-  const funcElem = document.createElement("div");
-
-  const funcHeader = document.createElement("h3");
-  let func_name =
-    func.name === null ? `function[${func.func_index}]` : func.name;
-  let demangled_name =
-    func.demangled_name !== null ? func.demangled_name : func_name;
-  funcHeader.textContent = `Disassembly of function <${demangled_name}>:`;
-  funcHeader.title = `Function ${func.func_index}: ${func_name}`;
-  funcElem.appendChild(funcHeader);
-
-  const bodyElem = document.createElement("pre");
-  for (const inst of func.instructions) {
-    const instElem = document.createElement("span");
-    instElem.textContent = `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}\n`;
-    if (inst.wasm_offset != null) {
-      instElem.setAttribute("data-wasm-offset", inst.wasm_offset);
-      const hue = hueForOffset(inst.wasm_offset);
-      instElem.style.backgroundColor = `hsl(${hue} 50% 90%)`;
-      instElem.addEventListener("mouseenter", onMouseEnter);
-      instElem.addEventListener("mouseleave", onMouseLeave);
-      addAsmElem(inst.wasm_offset, instElem);
+const renderAsm = (asm, containerElem, title, addElem) => {
+  for (const func of asm.functions) {
+    const funcElem = document.createElement("div");
+
+    const funcHeader = document.createElement("h3");
+    let func_name =
+      func.name === null ? `function[${func.func_index}]` : func.name;
+    let demangled_name =
+      func.demangled_name !== null ? func.demangled_name : func_name;
+    funcHeader.textContent = `${title} of function <${demangled_name}>:`;
+    funcHeader.title = `Function ${func.func_index}: ${func_name}`;
+    funcElem.appendChild(funcHeader);
+
+    const bodyElem = document.createElement("pre");
+    for (const inst of func.instructions) {
+      const instElem = document.createElement("span");
+      instElem.textContent = `${renderAddress(inst.address)}    ${renderBytes(inst.bytes)}    ${renderInst(inst.mnemonic, inst.operands)}\n`;
+      if (inst.changed) {
+        instElem.classList.add("changed");
+      }
+      if (inst.wasm_offset != null) {
+        instElem.setAttribute("data-wasm-offset", inst.wasm_offset);
+        const hue = hueForOffset(inst.wasm_offset);
+        instElem.style.backgroundColor = `hsl(${hue} 50% 90%)`;
+        instElem.addEventListener("mouseenter", onMouseEnter);
+        instElem.addEventListener("mouseleave", onMouseLeave);
+        addElem(inst.wasm_offset, instElem);
+        if (sourceByOffset.has(inst.wasm_offset)) {
+          instElem.title = sourceByOffset.get(inst.wasm_offset);
+        }
+      }
+      bodyElem.appendChild(instElem);
     }
-    bodyElem.appendChild(instElem);
+    funcElem.appendChild(bodyElem);
+
+    containerElem.appendChild(funcElem);
   }
-  funcElem.appendChild(bodyElem);
+};
 
-  asmElem.appendChild(funcElem);
+renderAsm(state.asm, asmElem, "Disassembly", addAsmElem);
+if (diffAsmElem) {
+  renderAsm(
+    state.diffAsm,
+    diffAsmElem,
+    "Compared disassembly",
+    addDiffAsmElem,
+  );
 }
 
 // Render the WAT.
 
+let lastSource = null;
 for (const chunk of state.wat.chunks) {
+  // Note the source location whenever it changes, as DWARF-equipped
+  // compilers will emit many instructions for each source line.
+  if (chunk.source != null) {
+    const source = renderSource(chunk.source);
+    if (source !== lastSource) {
+      const sourceElem = document.createElement("span");
+      sourceElem.classList.add("source");
+      sourceElem.textContent = `;; ${source}\n`;
+      watElem.appendChild(sourceElem);
+      lastSource = source;
+    }
+  }
+
   const chunkElem = document.createElement("span");
   if (chunk.wasm_offset != null) {
     chunkElem.dataset.wasmOffset = chunk.wasm_offset;
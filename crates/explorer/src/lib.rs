@@ -2,17 +2,30 @@ use anyhow::Result;
 use capstone::arch::BuildsCapstone;
 use serde_derive::Serialize;
 use std::{
+    collections::HashMap,
     fs::File,
     io::{read_to_string, Write},
     path::Path,
     str::FromStr,
 };
-use wasmtime_environ::demangle_function_name;
+use wasmtime_environ::{demangle_function_name, wasmparser, TripleExt};
 
+/// Generates the HTML page of the explorer for `wasm` compiled with `config`.
+///
+/// If `clif_dir` is provided then it's expected to contain the CLIF emitted
+/// while compiling with `config`, which is then shown alongside the machine
+/// code.
+///
+/// If `diff` is provided then the module is additionally compiled with that
+/// configuration and its machine code is shown next to the machine code for
+/// `config`, with instructions which differ between the two highlighted. This
+/// can be used, for example, to compare Cranelift with Winch or to compare two
+/// different sets of Cranelift settings.
 pub fn generate(
     config: &wasmtime::Config,
     target: Option<&str>,
     clif_dir: Option<&Path>,
+    diff: Option<&wasmtime::Config>,
     wasm: &[u8],
     dest: &mut dyn Write,
 ) -> Result<()> {
@@ -23,7 +36,14 @@ pub fn generate(
 
     let wat = annotate_wat(wasm)?;
     let wat_json = serde_json::to_string(&wat)?;
-    let asm = annotate_asm(config, &target, wasm)?;
+    let mut asm = annotate_asm(config, &target, wasm)?;
+    let diff_json = diff
+        .map::<anyhow::Result<String>, _>(|diff| {
+            let mut diff_asm = annotate_asm(diff, &target, wasm)?;
+            mark_changed_instructions(&mut asm, &mut diff_asm);
+            Ok(serde_json::to_string(&diff_asm)?)
+        })
+        .transpose()?;
     let asm_json = serde_json::to_string(&asm)?;
     let clif_json = clif_dir
         .map::<anyhow::Result<String>, _>(|clif_dir| {
@@ -53,10 +73,13 @@ pub fn generate(
     if clif_json.is_some() {
         write!(dest, r#"<div id="clif"></div>"#)?;
     }
+    write!(dest, r#"<div id="asm"></div>"#)?;
+    if diff_json.is_some() {
+        write!(dest, r#"<div id="diff-asm"></div>"#)?;
+    }
     write!(
         dest,
         r#"
-    <div id="asm"></div>
     <script>
       window.WAT = {wat_json};
         "#
@@ -69,6 +92,14 @@ pub fn generate(
             "#
         )?;
     }
+    if let Some(diff_json) = diff_json {
+        write!(
+            dest,
+            r#"
+          window.DIFF_ASM = {diff_json};
+            "#
+        )?;
+    }
     write!(
         dest,
         r#"
@@ -89,26 +120,150 @@ struct WasmOffset(u32);
 
 #[derive(Serialize, Debug)]
 struct AnnotatedWat {
+    /// The source files referenced by the `source` of chunks.
+    files: Vec<String>,
     chunks: Vec<AnnotatedWatChunk>,
 }
 
 #[derive(Serialize, Debug)]
 struct AnnotatedWatChunk {
     wasm_offset: Option<WasmOffset>,
+    source: Option<SourceLocation>,
     wat: String,
 }
 
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+struct SourceLocation {
+    /// Index into `AnnotatedWat::files`.
+    file: u32,
+    line: u32,
+    column: u32,
+}
+
 fn annotate_wat(wasm: &[u8]) -> Result<AnnotatedWat> {
+    let source_lines = SourceLines::new(wasm)?;
     let printer = wasmprinter::Config::new();
     let mut storage = String::new();
     let chunks = printer
         .offsets_and_lines(wasm, &mut storage)?
         .map(|(offset, wat)| AnnotatedWatChunk {
             wasm_offset: offset.map(|o| WasmOffset(u32::try_from(o).unwrap())),
+            source: offset.and_then(|o| source_lines.lookup(o)),
             wat: wat.to_string(),
         })
         .collect();
-    Ok(AnnotatedWat { chunks })
+    Ok(AnnotatedWat {
+        files: source_lines.files,
+        chunks,
+    })
+}
+
+/// Mapping from Wasm offsets to source locations, as described by the
+/// module's DWARF line tables.
+#[derive(Default)]
+struct SourceLines {
+    files: Vec<String>,
+    /// Sorted by Wasm offset; `None` marks the end of a sequence.
+    rows: Vec<(usize, Option<SourceLocation>)>,
+}
+
+impl SourceLines {
+    fn new(wasm: &[u8]) -> Result<SourceLines> {
+        let mut sections = HashMap::new();
+        let mut code_section_offset = None;
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            match payload? {
+                wasmparser::Payload::CustomSection(section)
+                    if section.name().starts_with(".debug_") =>
+                {
+                    sections.insert(section.name(), section.data());
+                }
+                wasmparser::Payload::CodeSectionStart { range, .. } => {
+                    code_section_offset = Some(range.start);
+                }
+                // Nested modules and components have their own DWARF, if any,
+                // which isn't supported here.
+                wasmparser::Payload::ModuleSection { .. }
+                | wasmparser::Payload::ComponentSection { .. } => {
+                    return Ok(SourceLines::default());
+                }
+                _ => {}
+            }
+        }
+        let code_section_offset = match code_section_offset {
+            Some(offset) if sections.contains_key(".debug_line") => offset,
+            _ => return Ok(SourceLines::default()),
+        };
+
+        let dwarf = gimli::Dwarf::load(|id| -> Result<_> {
+            let data = sections.get(id.name()).copied().unwrap_or(&[]);
+            Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+        })?;
+
+        let mut lines = SourceLines::default();
+        let mut file_indices = HashMap::new();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next()? {
+            let unit = dwarf.unit(header)?;
+            let Some(program) = unit.line_program.clone() else {
+                continue;
+            };
+            let mut rows = program.rows();
+            while let Some((header, row)) = rows.next_row()? {
+                let offset = code_section_offset + usize::try_from(row.address())?;
+                if row.end_sequence() {
+                    lines.rows.push((offset, None));
+                    continue;
+                }
+                let Some(file) = row.file(header) else {
+                    continue;
+                };
+                let mut path = String::new();
+                if let Some(dir) = file.directory(header) {
+                    path.push_str(&dwarf.attr_string(&unit, dir)?.to_string_lossy());
+                }
+                let name = dwarf.attr_string(&unit, file.path_name())?;
+                let name = name.to_string_lossy();
+                if path.is_empty() || name.starts_with('/') {
+                    path = name.into_owned();
+                } else {
+                    path.push('/');
+                    path.push_str(&name);
+                }
+                let next_index = u32::try_from(lines.files.len()).unwrap();
+                let file = *file_indices.entry(path.clone()).or_insert_with(|| {
+                    lines.files.push(path);
+                    next_index
+                });
+                lines.rows.push((
+                    offset,
+                    Some(SourceLocation {
+                        file,
+                        line: row.line().map_or(0, |l| u32::try_from(l.get()).unwrap()),
+                        column: match row.column() {
+                            gimli::ColumnType::LeftEdge => 0,
+                            gimli::ColumnType::Column(c) => u32::try_from(c.get()).unwrap(),
+                        },
+                    }),
+                ));
+            }
+        }
+        // Keep the last row for any given offset, as that's the one which
+        // applies to the instruction at that offset.
+        lines.rows.reverse();
+        lines.rows.sort_by_key(|(offset, _)| *offset);
+        lines.rows.dedup_by_key(|(offset, _)| *offset);
+        Ok(lines)
+    }
+
+    fn lookup(&self, offset: usize) -> Option<SourceLocation> {
+        let i = match self.rows.binary_search_by_key(&offset, |(o, _)| *o) {
+            Ok(i) => i,
+            Err(0) => return None,
+            Err(i) => i - 1,
+        };
+        self.rows[i].1
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -131,6 +286,9 @@ struct AnnotatedInstruction {
     bytes: Vec<u8>,
     mnemonic: Option<String>,
     operands: Option<String>,
+    /// Whether this instruction has no counterpart in the function it's
+    /// being diffed against, if any.
+    changed: bool,
 }
 
 fn annotate_asm(
@@ -171,53 +329,24 @@ fn annotate_asm(
         .map(|function| {
             let body = &text[function.offset..][..function.len];
 
-            let mut cs = match target.architecture {
-                target_lexicon::Architecture::Aarch64(_) => capstone::Capstone::new()
-                    .arm64()
-                    .mode(capstone::arch::arm64::ArchMode::Arm)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::Riscv64(_) => capstone::Capstone::new()
-                    .riscv()
-                    .mode(capstone::arch::riscv::ArchMode::RiscV64)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::S390x => capstone::Capstone::new()
-                    .sysz()
-                    .mode(capstone::arch::sysz::ArchMode::Default)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                target_lexicon::Architecture::X86_64 => capstone::Capstone::new()
-                    .x86()
-                    .mode(capstone::arch::x86::ArchMode::Mode64)
-                    .build()
-                    .map_err(|e| anyhow::anyhow!("{e}"))?,
-                _ => anyhow::bail!("Unsupported target: {target}"),
+            let instructions = if target.is_pulley() {
+                disas_pulley(body, function.offset)?
+            } else {
+                disas_native(target, body, function.offset)?
             };
-
-            // This tells capstone to skip over anything that looks like data,
-            // such as inline constant pools and things like that. This also
-            // additionally is required to skip over trapping instructions on
-            // AArch64.
-            cs.set_skipdata(true).unwrap();
-
-            let instructions = cs
-                .disasm_all(body, function.offset as u64)
-                .map_err(|e| anyhow::anyhow!("{e}"))?;
             let instructions = instructions
-                .iter()
-                .map(|inst| {
-                    let address = u32::try_from(inst.address()).unwrap();
-                    let wasm_offset = wasm_offset_for_address(function.offset, address);
-                    Ok(AnnotatedInstruction {
-                        wasm_offset,
+                .into_iter()
+                .map(
+                    |(address, bytes, mnemonic, operands)| AnnotatedInstruction {
+                        wasm_offset: wasm_offset_for_address(function.offset, address),
                         address,
-                        bytes: inst.bytes().to_vec(),
-                        mnemonic: inst.mnemonic().map(ToString::to_string),
-                        operands: inst.op_str().map(ToString::to_string),
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
+                        bytes,
+                        mnemonic,
+                        operands,
+                        changed: false,
+                    },
+                )
+                .collect();
 
             let demangled_name = if let Some(name) = &function.name {
                 let mut demangled = String::new();
@@ -242,6 +371,176 @@ fn annotate_asm(
     Ok(AnnotatedAsm { functions })
 }
 
+/// An instruction's address, bytes, mnemonic and operands.
+type DisasInst = (u32, Vec<u8>, Option<String>, Option<String>);
+
+fn disas_native(
+    target: &target_lexicon::Triple,
+    body: &[u8],
+    offset: usize,
+) -> Result<Vec<DisasInst>> {
+    let mut cs = match target.architecture {
+        target_lexicon::Architecture::Aarch64(_) => capstone::Capstone::new()
+            .arm64()
+            .mode(capstone::arch::arm64::ArchMode::Arm)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::Riscv64(_) => capstone::Capstone::new()
+            .riscv()
+            .mode(capstone::arch::riscv::ArchMode::RiscV64)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::S390x => capstone::Capstone::new()
+            .sysz()
+            .mode(capstone::arch::sysz::ArchMode::Default)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        target_lexicon::Architecture::X86_64 => capstone::Capstone::new()
+            .x86()
+            .mode(capstone::arch::x86::ArchMode::Mode64)
+            .build()
+            .map_err(|e| anyhow::anyhow!("{e}"))?,
+        _ => anyhow::bail!("Unsupported target: {target}"),
+    };
+
+    // This tells capstone to skip over anything that looks like data,
+    // such as inline constant pools and things like that. This also
+    // additionally is required to skip over trapping instructions on
+    // AArch64.
+    cs.set_skipdata(true).unwrap();
+
+    let instructions = cs
+        .disasm_all(body, offset as u64)
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    Ok(instructions
+        .iter()
+        .map(|inst| {
+            (
+                u32::try_from(inst.address()).unwrap(),
+                inst.bytes().to_vec(),
+                inst.mnemonic().map(ToString::to_string),
+                inst.op_str().map(ToString::to_string),
+            )
+        })
+        .collect())
+}
+
+fn disas_pulley(body: &[u8], offset: usize) -> Result<Vec<DisasInst>> {
+    use pulley_interpreter::decode::{Decoder, DecodingError, OpVisitor};
+    use pulley_interpreter::disas::Disassembler;
+
+    let mut result = Vec::new();
+    let mut disas = Disassembler::new(body);
+    disas.offsets(false).hexdump(false).start_offset(offset);
+    let mut decoder = Decoder::new();
+    let mut last_disas_pos = 0;
+    loop {
+        let start = disas.bytecode().position();
+        match decoder.decode_one(&mut disas) {
+            // EOF at the start of an instruction means we're done.
+            Err(DecodingError::UnexpectedEof { position }) if position == start => break,
+            Err(e) => {
+                return Err(anyhow::Error::from(e).context("failed to disassemble pulley bytecode"))
+            }
+            Ok(()) => {}
+        }
+        let end = disas.bytecode().position();
+        let text = disas.disas()[last_disas_pos..].trim();
+        last_disas_pos = disas.disas().len();
+        let (mnemonic, operands) = text.split_once(' ').unwrap_or((text, ""));
+        result.push((
+            u32::try_from(offset + start).unwrap(),
+            body[start..end].to_vec(),
+            Some(mnemonic.to_string()),
+            Some(operands.trim().to_string()),
+        ));
+    }
+    Ok(result)
+}
+
+/// Marks the instructions of functions in `a` and `b` which don't appear in
+/// the other's version of the same function.
+///
+/// Instructions are compared by their textual disassembly, so anything
+/// embedding an absolute address is likely to show up as changed if the code
+/// before it changed size.
+fn mark_changed_instructions(a: &mut AnnotatedAsm, b: &mut AnnotatedAsm) {
+    let mut b_funcs: HashMap<u32, &mut AnnotatedFunction> =
+        b.functions.iter_mut().map(|f| (f.func_index, f)).collect();
+    for a_func in a.functions.iter_mut() {
+        match b_funcs.remove(&a_func.func_index) {
+            Some(b_func) => diff_instructions(&mut a_func.instructions, &mut b_func.instructions),
+            None => a_func
+                .instructions
+                .iter_mut()
+                .for_each(|i| i.changed = true),
+        }
+    }
+    for b_func in b_funcs.into_values() {
+        b_func
+            .instructions
+            .iter_mut()
+            .for_each(|i| i.changed = true);
+    }
+}
+
+fn diff_instructions(a: &mut [AnnotatedInstruction], b: &mut [AnnotatedInstruction]) {
+    // Bound the quadratic longest-common-subsequence below; larger changes are
+    // just reported as entirely changed.
+    const MAX_LCS_CELLS: usize = 1 << 22;
+
+    fn same(a: &AnnotatedInstruction, b: &AnnotatedInstruction) -> bool {
+        a.mnemonic == b.mnemonic && a.operands == b.operands
+    }
+
+    let prefix = a
+        .iter()
+        .zip(b.iter())
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    let (a, b) = (&mut a[prefix..], &mut b[prefix..]);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(a, b)| same(a, b))
+        .count();
+    let (a_len, b_len) = (a.len() - suffix, b.len() - suffix);
+    let (a, b) = (&mut a[..a_len], &mut b[..b_len]);
+    a.iter_mut().for_each(|i| i.changed = true);
+    b.iter_mut().for_each(|i| i.changed = true);
+    if a.len().saturating_mul(b.len()) > MAX_LCS_CELLS {
+        return;
+    }
+
+    // `lcs[i][j]` is the length of the longest common subsequence of `a[i..]`
+    // and `b[j..]`.
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if same(&a[i], &b[j]) {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if same(&a[i], &b[j]) {
+            a[i].changed = false;
+            b[j].changed = false;
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+}
+
 #[derive(Serialize, Debug)]
 struct AnnotatedClif {
     functions: Vec<AnnotatedClifFunction>,
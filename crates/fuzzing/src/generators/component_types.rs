@@ -110,6 +110,9 @@ pub fn arbitrary_val(ty: &component::Type, input: &mut Unstructured) -> arbitrar
 
         // Resources aren't fuzzed at this time.
         Type::Own(_) | Type::Borrow(_) => unreachable!(),
+
+        // Neither are async types.
+        Type::Future(_) | Type::Stream(_) | Type::ErrorContext => unreachable!(),
     })
 }
 
@@ -1,7 +1,7 @@
 use crate::{
     clocks::{
         host::{monotonic_clock, wall_clock},
-        HostMonotonicClock, HostWallClock,
+        FixedMonotonicClock, FixedWallClock, HostMonotonicClock, HostWallClock,
     },
     filesystem::{Dir, OpenMode},
     network::{SocketAddrCheck, SocketAddrUse},
@@ -52,6 +52,7 @@ pub struct WasiCtxBuilder {
     monotonic_clock: Box<dyn HostMonotonicClock + Send>,
     allowed_network_uses: AllowedNetworkUses,
     allow_blocking_current_thread: bool,
+    sort_directory_entries: bool,
     built: bool,
 }
 
@@ -101,6 +102,7 @@ impl WasiCtxBuilder {
             monotonic_clock: monotonic_clock(),
             allowed_network_uses: AllowedNetworkUses::default(),
             allow_blocking_current_thread: false,
+            sort_directory_entries: false,
             built: false,
         }
     }
@@ -400,6 +402,51 @@ impl WasiCtxBuilder {
         self
     }
 
+    /// Configures whether directory entries are returned sorted by name.
+    ///
+    /// By default directory entries are returned in whatever order the host
+    /// filesystem yields them in, which can differ between hosts and even
+    /// between runs.
+    pub fn sort_directory_entries(&mut self, enable: bool) -> &mut Self {
+        self.sort_directory_entries = enable;
+        self
+    }
+
+    /// Configures this context to behave deterministically, for use with
+    /// [`Config::deterministic`](wasmtime::Config::deterministic).
+    ///
+    /// This replaces the sources of nondeterminism in WASI with deterministic
+    /// implementations:
+    ///
+    /// * The wall clock is fixed at the Unix epoch and the monotonic clock is
+    ///   fixed at zero.
+    /// * The secure and insecure random number generators, and the insecure
+    ///   random seed, are all derived from `seed`.
+    /// * Directory entries are sorted by name, see
+    ///   [`WasiCtxBuilder::sort_directory_entries`].
+    ///
+    /// These can be individually overridden after this method is called, for
+    /// example to install a custom clock which advances in a deterministic
+    /// fashion.
+    ///
+    /// Note that this doesn't restrict what the guest has access to, and
+    /// inputs such as stdin, environment variables, preopened directories and
+    /// the network are still provided by the host.
+    ///
+    /// The random data produced with `seed` is predictable by definition and
+    /// must not be relied on for security.
+    pub fn deterministic(&mut self, seed: u64) -> &mut Self {
+        use cap_rand::rngs::StdRng;
+        let mut rng = StdRng::seed_from_u64(seed);
+        self.random = Box::new(StdRng::from_seed(rng.r#gen()));
+        self.insecure_random = Box::new(StdRng::from_seed(rng.r#gen()));
+        self.insecure_random_seed = rng.r#gen();
+        self.wall_clock = Box::new(FixedWallClock);
+        self.monotonic_clock = Box::new(FixedMonotonicClock);
+        self.sort_directory_entries = true;
+        self
+    }
+
     /// Allow all network addresses accessible to the host.
     ///
     /// This method will inherit all network addresses meaning that any address
@@ -480,6 +527,7 @@ impl WasiCtxBuilder {
             monotonic_clock,
             allowed_network_uses,
             allow_blocking_current_thread,
+            sort_directory_entries,
             built: _,
         } = mem::replace(self, Self::new());
         self.built = true;
@@ -499,6 +547,7 @@ impl WasiCtxBuilder {
             monotonic_clock,
             allowed_network_uses,
             allow_blocking_current_thread,
+            sort_directory_entries,
         }
     }
 
@@ -583,6 +632,7 @@ pub struct WasiCtx {
     pub(crate) socket_addr_check: SocketAddrCheck,
     pub(crate) allowed_network_uses: AllowedNetworkUses,
     pub(crate) allow_blocking_current_thread: bool,
+    pub(crate) sort_directory_entries: bool,
 }
 
 impl WasiCtx {
@@ -283,6 +283,7 @@ where
         &mut self,
         fd: Resource<types::Descriptor>,
     ) -> FsResult<Resource<types::DirectoryEntryStream>> {
+        let sort_directory_entries = self.ctx().sort_directory_entries;
         let table = self.table();
         let d = table.get(&fd)?.dir()?;
         if !d.perms.contains(DirPerms::READ) {
@@ -299,7 +300,7 @@ where
             }
         }
 
-        let entries = d
+        let mut entries = d
             .run_blocking(|d| {
                 // Both `entries` and `metadata` perform syscalls, which is why they are done
                 // within this `block` call, rather than delay calculating the metadata
@@ -319,8 +320,18 @@ where
                         .collect::<Vec<Result<types::DirectoryEntry, ReaddirError>>>(),
                 )
             })
-            .await?
-            .into_iter();
+            .await?;
+
+        // Entries which failed to be read have no name to order them by and
+        // are sorted first.
+        if sort_directory_entries {
+            entries.sort_by(|a, b| {
+                let a = a.as_ref().ok().map(|e| &e.name);
+                let b = b.as_ref().ok().map(|e| &e.name);
+                a.cmp(&b)
+            });
+        }
+        let entries = entries.into_iter();
 
         // On windows, filter out files like `C:\DumpStack.log.tmp` which we
         // can't get full metadata for.
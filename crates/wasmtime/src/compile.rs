@@ -31,13 +31,14 @@ use std::{
     borrow::Cow,
     collections::{btree_map, BTreeMap, BTreeSet},
     mem,
+    sync::Arc,
 };
 
 #[cfg(feature = "component-model")]
 use wasmtime_environ::component::Translator;
 use wasmtime_environ::{
     BuiltinFunctionIndex, CompiledFunctionInfo, CompiledModuleInfo, Compiler, DefinedFuncIndex,
-    FinishedObject, FunctionBodyData, ModuleEnvironment, ModuleInternedTypeIndex,
+    ExecutionProfile, FinishedObject, FunctionBodyData, ModuleEnvironment, ModuleInternedTypeIndex,
     ModuleTranslation, ModuleTypes, ModuleTypesBuilder, ObjectKind, PrimaryMap, RelocationTarget,
     StaticModuleIndex, WasmFunctionInfo,
 };
@@ -60,10 +61,14 @@ mod runtime;
 /// Additionally compilation returns an `Option` here which is always
 /// `Some`, notably compiled metadata about the module in addition to the
 /// type information found within.
+///
+/// The `execution_profile`, if any, takes precedence over the one configured
+/// on the `Engine`.
 pub(crate) fn build_artifacts<T: FinishedObject>(
     engine: &Engine,
     wasm: &[u8],
     dwarf_package: Option<&[u8]>,
+    execution_profile: Option<&Arc<ExecutionProfile>>,
     obj_state: &T::State,
 ) -> Result<(T, Option<(CompiledModuleInfo, ModuleTypes)>)> {
     let tunables = engine.tunables();
@@ -79,6 +84,9 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     let mut translation = ModuleEnvironment::new(tunables, &mut validator, &mut types)
         .translate(parser, wasm)
         .context("failed to parse WebAssembly module")?;
+    translation.execution_profile = execution_profile
+        .or(engine.config().execution_profile.as_ref())
+        .cloned();
     let functions = mem::take(&mut translation.function_body_inputs);
 
     let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
@@ -129,6 +137,9 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
     engine: &Engine,
     binary: &[u8],
     _dwarf_package: Option<&[u8]>,
+    // Profiles are keyed by offsets within a single core module, so neither
+    // this nor the `Engine`'s profile applies to the modules of a component.
+    _execution_profile: Option<&Arc<ExecutionProfile>>,
     obj_state: &T::State,
 ) -> Result<(T, Option<wasmtime_environ::component::ComponentArtifacts>)> {
     use wasmtime_environ::component::{
@@ -5,7 +5,7 @@ use crate::runtime::vm::MmapVec;
 use crate::{CodeBuilder, CodeMemory, Engine, Module};
 use object::write::WritableBuffer;
 use std::sync::Arc;
-use wasmtime_environ::{FinishedObject, ObjectBuilder};
+use wasmtime_environ::{ExecutionProfile, FinishedObject, ObjectBuilder};
 
 impl<'a> CodeBuilder<'a> {
     fn compile_cached<T, S>(
@@ -14,6 +14,7 @@ impl<'a> CodeBuilder<'a> {
             &Engine,
             &[u8],
             Option<&[u8]>,
+            Option<&Arc<ExecutionProfile>>,
             &S,
         ) -> Result<(MmapVecWrapper, Option<T>)>,
         state: &S,
@@ -31,6 +32,7 @@ impl<'a> CodeBuilder<'a> {
                 crate::compile::HashedEngineCompileEnv(self.engine),
                 &wasm,
                 &dwarf_package,
+                &self.execution_profile,
                 // Don't hash this as it's just its own "pure" function pointer.
                 NotHashed(build_artifacts),
                 // Don't hash the FinishedObject state: this contains
@@ -43,22 +45,23 @@ impl<'a> CodeBuilder<'a> {
                     .get_data_raw(
                         &state,
                         // Cache miss, compute the actual artifacts
-                        |(engine, wasm, dwarf_package, build_artifacts, state)| -> Result<_> {
+                        |(engine, wasm, dwarf_package, execution_profile, build_artifacts, state)| -> Result<_> {
                             let (mmap, info) = (build_artifacts.0)(
                                 engine.0,
                                 wasm,
                                 dwarf_package.as_deref(),
+                                execution_profile.as_ref(),
                                 state.0,
                             )?;
                             let code = publish_mmap(engine.0, mmap.0)?;
                             Ok((code, info))
                         },
                         // Implementation of how to serialize artifacts
-                        |(_engine, _wasm, _, _, _), (code, _info_and_types)| {
+                        |(_engine, _wasm, _, _, _, _), (code, _info_and_types)| {
                             Some(code.mmap().to_vec())
                         },
                         // Cache hit, deserialize the provided artifacts
-                        |(engine, wasm, _, _, _), serialized_bytes| {
+                        |(engine, wasm, _, _, _, _), serialized_bytes| {
                             let kind = if wasmparser::Parser::is_component(&wasm) {
                                 wasmtime_environ::ObjectKind::Component
                             } else {
@@ -79,8 +82,13 @@ impl<'a> CodeBuilder<'a> {
 
         #[cfg(not(feature = "cache"))]
         {
-            let (mmap, info_and_types) =
-                build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), state)?;
+            let (mmap, info_and_types) = build_artifacts(
+                self.engine,
+                &wasm,
+                dwarf_package.as_deref(),
+                self.execution_profile.as_ref(),
+                state,
+            )?;
             let code = publish_mmap(self.engine, mmap.0)?;
             return Ok((code, info_and_types));
         }
@@ -30,6 +30,8 @@ use wasmtime_fiber::RuntimeFiberStackCreator;
 pub use crate::runtime::code_memory::CustomCodeMemory;
 #[cfg(all(feature = "incremental-cache", feature = "cranelift"))]
 pub use wasmtime_environ::CacheStore;
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+pub use wasmtime_environ::ExecutionProfile;
 
 /// Represents the module instance allocation strategy to use.
 #[derive(Clone)]
@@ -159,6 +161,9 @@ pub struct Config {
     pub(crate) memory_guaranteed_dense_image_size: u64,
     pub(crate) force_memory_init_memfd: bool,
     pub(crate) wmemcheck: bool,
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) execution_profile: Option<Arc<ExecutionProfile>>,
+    pub(crate) deterministic: bool,
     #[cfg(feature = "coredump")]
     pub(crate) coredump_on_trap: bool,
     pub(crate) macos_use_mach_ports: bool,
@@ -264,6 +269,9 @@ impl Config {
             memory_guaranteed_dense_image_size: 16 << 20,
             force_memory_init_memfd: false,
             wmemcheck: false,
+            #[cfg(any(feature = "cranelift", feature = "winch"))]
+            execution_profile: None,
+            deterministic: false,
             #[cfg(feature = "coredump")]
             coredump_on_trap: false,
             macos_use_mach_ports: !cfg!(miri),
@@ -670,6 +678,65 @@ impl Config {
         self
     }
 
+    /// Configures whether compiled WebAssembly counts how often each of its
+    /// basic blocks is executed, for line-level code coverage.
+    ///
+    /// When enabled each block is instrumented with a counter which is
+    /// incremented every time the block is entered. The counters of all
+    /// instances in a [`Store`] can be collected and mapped back to source
+    /// lines with a [`Coverage`](crate::Coverage). Mapping counters to source
+    /// lines requires the DWARF debug information of modules so
+    /// [`Config::wasm_backtrace_details`] should typically be enabled as well.
+    ///
+    /// Note that the instrumentation slows down execution of WebAssembly.
+    ///
+    /// By default this option is `false`.
+    ///
+    /// **Note** Enabling this option is not compatible with the Winch compiler.
+    ///
+    /// [`Store`]: crate::Store
+    pub fn coverage(&mut self, enable: bool) -> &mut Self {
+        self.tunables.coverage = Some(enable);
+        self
+    }
+
+    /// Configures whether WebAssembly executes deterministically, producing
+    /// bit-identical results on all hosts.
+    ///
+    /// WebAssembly is deterministic except for a few well-defined sources of
+    /// nondeterminism. When this option is enabled Wasmtime eliminates them:
+    ///
+    /// * The bit patterns of NaNs produced by floating-point operations are
+    ///   canonicalized, as with [`Config::cranelift_nan_canonicalization`].
+    /// * Instructions from the relaxed SIMD proposal use their deterministic
+    ///   behavior, as with [`Config::relaxed_simd_deterministic`].
+    /// * The [threads] proposal, and with it shared memories, is disabled as
+    ///   the interleaving of threads can't be controlled.
+    ///
+    /// Execution must be bounded deterministically with fuel, so
+    /// [`Config::consume_fuel`] has to be enabled as well. Epoch-based
+    /// interruption, which depends on wall-clock time, can't be used.
+    ///
+    /// If fuel isn't enabled, or any other option conflicts with deterministic
+    /// execution, for example by explicitly enabling the threads proposal or
+    /// epoch interruption, then [`Engine::new`](crate::Engine::new) returns an
+    /// error. The Winch compiler is not supported in this mode.
+    ///
+    /// Note that this only covers the execution of WebAssembly itself and not
+    /// any host functions it calls. Embedders are responsible for making host
+    /// functions deterministic, for example with
+    /// `wasmtime_wasi::WasiCtxBuilder::deterministic` for WASI. Also note
+    /// that the maximum depth of recursion before a stack overflow may still
+    /// differ between compilers and hosts.
+    ///
+    /// By default this option is `false`.
+    ///
+    /// [threads]: https://github.com/webassembly/threads
+    pub fn deterministic(&mut self, enable: bool) -> &mut Self {
+        self.deterministic = enable;
+        self
+    }
+
     /// Configures the maximum amount of stack space available for
     /// executing WebAssembly code.
     ///
@@ -1192,6 +1259,9 @@ impl Config {
     /// optimization level used for generated code in a few various ways. For
     /// more information see the documentation of [`OptLevel`].
     ///
+    /// When [`Config::cranelift_inlining`] is enabled the optimization level
+    /// also determines which calls are inlined.
+    ///
     /// The default value for this is `OptLevel::None`.
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     pub fn cranelift_opt_level(&mut self, level: OptLevel) -> &mut Self {
@@ -1227,6 +1297,29 @@ impl Config {
         self
     }
 
+    /// Configures whether Cranelift inlines calls to small functions.
+    ///
+    /// Direct calls to functions defined in the same module are inlined when
+    /// the callee's body is small: up to 64 bytes of wasm with
+    /// [`OptLevel::Speed`] and up to 16 bytes with [`OptLevel::SpeedAndSize`].
+    /// The total size of callees inlined into one function is limited to 2048
+    /// and 256 bytes respectively. Recursive calls and calls within inlined
+    /// callees aren't inlined, and nothing is inlined with [`OptLevel::None`]
+    /// or when [`Config::debug_info`] is enabled.
+    ///
+    /// Inlined callees don't get frames of their own, so a trap within an
+    /// inlined callee is reported in a [`WasmBacktrace`](crate::WasmBacktrace)
+    /// as a frame of its caller, although the frame's
+    /// [`module_offset`](crate::FrameInfo::module_offset) still points at the
+    /// trapping instruction within the callee.
+    ///
+    /// The default value for this is `false`.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn cranelift_inlining(&mut self, enable: bool) -> &mut Self {
+        self.tunables.inlining = Some(enable);
+        self
+    }
+
     /// Configures whether Cranelift should perform a NaN-canonicalization pass.
     ///
     /// When Cranelift is used as a code generation backend this will configure
@@ -1957,6 +2050,34 @@ impl Config {
         self
     }
 
+    /// Provides an execution profile used to guide the layout of compiled
+    /// code.
+    ///
+    /// The profile records how often each WebAssembly instruction executed in
+    /// some representative run, keyed by the instruction's byte offset within
+    /// the module. When compiling, Cranelift places the most frequently taken
+    /// successor of each branch directly after it and moves rarely executed
+    /// code to the end of each function, which can noticeably improve the
+    /// performance of hot loops.
+    ///
+    /// Because the profile is keyed by offset it only describes one particular
+    /// module binary. Prefer
+    /// [`CodeBuilder::execution_profile`](crate::CodeBuilder::execution_profile)
+    /// which applies a profile to just the module being compiled; the profile
+    /// configured here is only a fallback for core modules compiled without
+    /// one, and is never applied to the core modules within components. Using
+    /// it with a module other than the one it was recorded for is not unsafe,
+    /// but code will be laid out according to meaningless counts. The profile
+    /// never affects the behavior of compiled code, only its performance.
+    ///
+    /// This is only used by Cranelift and is ignored by Winch. By default no
+    /// profile is used.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn execution_profile(&mut self, profile: Option<Arc<ExecutionProfile>>) -> &mut Self {
+        self.execution_profile = profile;
+        self
+    }
+
     /// Configures the "guaranteed dense image size" for copy-on-write
     /// initialized memories.
     ///
@@ -2103,6 +2224,12 @@ impl Config {
         // compiler backend may panic on if the module contains them.
         features = features & !self.compiler_panicking_wasm_features();
 
+        // Threads can't be deterministic so they're off by default in
+        // deterministic mode, and an error if explicitly enabled.
+        if self.deterministic {
+            features &= !(WasmFeatures::THREADS | WasmFeatures::SHARED_EVERYTHING_THREADS);
+        }
+
         // After wasmtime's defaults are configured then factor in user requests
         // and disable/enable features. Note that the enable/disable sets should
         // be disjoint.
@@ -2194,10 +2321,40 @@ impl Config {
 
         self.tunables.configure(&mut tunables);
 
+        if self.deterministic {
+            if features.contains(WasmFeatures::THREADS)
+                || features.contains(WasmFeatures::SHARED_EVERYTHING_THREADS)
+            {
+                bail!("the wasm threads proposal cannot be enabled in deterministic mode");
+            }
+            if tunables.epoch_interruption {
+                bail!(
+                    "epoch interruption cannot be enabled in deterministic mode, \
+                     use fuel instead"
+                );
+            }
+            if !tunables.consume_fuel {
+                bail!(
+                    "deterministic mode requires fuel to bound execution, \
+                     see `Config::consume_fuel`"
+                );
+            }
+            if self.tunables.relaxed_simd_deterministic == Some(false) {
+                bail!("relaxed simd must be deterministic in deterministic mode");
+            }
+            tunables.relaxed_simd_deterministic = true;
+        }
+
         // If we're going to compile with winch, we must use the winch calling convention.
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
             tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+            if tunables.winch_callable && tunables.coverage {
+                bail!("coverage instrumentation is not supported with the Winch compiler");
+            }
+            if tunables.winch_callable && self.deterministic {
+                bail!("deterministic mode is not supported with the Winch compiler");
+            }
         }
 
         tunables.collector = if features.gc_types() {
@@ -2422,6 +2579,14 @@ impl Config {
             }
         }
 
+        if self.deterministic
+            && !self
+                .compiler_config
+                .ensure_setting_unset_or_given("enable_nan_canonicalization", "true")
+        {
+            bail!("NaN canonicalization cannot be disabled in deterministic mode");
+        }
+
         if features.contains(WasmFeatures::RELAXED_SIMD) && !features.contains(WasmFeatures::SIMD) {
             bail!("cannot disable the simd proposal but enable the relaxed simd proposal");
         }
@@ -2454,6 +2619,22 @@ impl Config {
         self
     }
 
+    /// Configures whether adapters between components enforce
+    /// [`ResourceLimiter::component_adapter_copy_bytes`](crate::ResourceLimiter::component_adapter_copy_bytes).
+    ///
+    /// When enabled, adapter modules call into the host each time they
+    /// allocate memory in the destination component to account for the bytes
+    /// they are about to copy. This adds overhead to every cross-component
+    /// call which transfers lists, strings, or spilled arguments, so it's
+    /// only worth enabling when that limit is configured.
+    ///
+    /// This option is `false` by default.
+    #[cfg(feature = "component-model")]
+    pub fn component_adapter_copy_limit(&mut self, enable: bool) -> &mut Self {
+        self.tunables.component_adapter_copy_limit = Some(enable);
+        self
+    }
+
     /// Enables clif output when compiling a WebAssembly module.
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     pub fn emit_clif(&mut self, path: &Path) -> &mut Self {
@@ -2594,6 +2775,7 @@ impl fmt::Debug for Config {
         }
 
         f.field("parallel_compilation", &self.parallel_compilation);
+        f.field("deterministic", &self.deterministic);
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
             f.field("compiler_config", &self.compiler_config);
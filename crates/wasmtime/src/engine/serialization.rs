@@ -366,6 +366,7 @@ impl Metadata<'_> {
             parse_wasm_debuginfo,
             consume_fuel,
             epoch_interruption,
+            coverage,
             memory_may_move,
             guard_before_linear_memory,
             table_lazy_init,
@@ -373,6 +374,7 @@ impl Metadata<'_> {
             winch_callable,
             signals_based_traps,
             memory_init_cow,
+            component_adapter_copy_limit,
             // This doesn't affect compilation, it's just a runtime setting.
             memory_reservation_for_growth: _,
 
@@ -384,6 +386,10 @@ impl Metadata<'_> {
 
             // Just a debugging aid, doesn't affect functionality at all.
             debug_adapter_modules: _,
+
+            // Only changes how code is generated within functions, modules
+            // compiled either way are otherwise identical.
+            inlining: _,
         } = self.tunables;
 
         Self::check_collector(collector, other.collector)?;
@@ -413,6 +419,12 @@ impl Metadata<'_> {
             other.epoch_interruption,
             "epoch interruption",
         )?;
+        Self::check_bool(coverage, other.coverage, "coverage instrumentation")?;
+        Self::check_bool(
+            component_adapter_copy_limit,
+            other.component_adapter_copy_limit,
+            "component adapter copy limit",
+        )?;
         Self::check_bool(memory_may_move, other.memory_may_move, "memory may move")?;
         Self::check_bool(
             guard_before_linear_memory,
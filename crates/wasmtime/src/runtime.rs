@@ -97,12 +97,19 @@ pub use vm::PoolConcurrencyLimitError;
 mod profiling;
 #[cfg(feature = "profiling")]
 pub use profiling::GuestProfiler;
+#[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+pub use profiling::{ProfileFormat, SamplingProfiler};
 
 #[cfg(feature = "async")]
 pub(crate) mod stack;
 #[cfg(feature = "async")]
 pub use stack::*;
 
+#[cfg(feature = "coverage")]
+mod coverage;
+#[cfg(feature = "coverage")]
+pub use coverage::Coverage;
+
 #[cfg(feature = "coredump")]
 mod coredump;
 #[cfg(feature = "coredump")]
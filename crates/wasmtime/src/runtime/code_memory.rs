@@ -227,6 +227,14 @@ impl CodeMemory {
         &self.mmap
     }
 
+    /// Returns the offsets within [`CodeMemory::mmap`] at which the address of
+    /// each libcall is written when this image is published.
+    pub fn relocations(&self) -> impl Iterator<Item = (usize, obj::LibCall)> + '_ {
+        self.relocations
+            .iter()
+            .map(|(offset, libcall)| (self.text.start + offset, *libcall))
+    }
+
     /// Returns the contents of the text section of the ELF executable this
     /// represents.
     #[inline]
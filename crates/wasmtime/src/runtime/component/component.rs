@@ -8,6 +8,7 @@ use crate::runtime::vm::open_file_for_mmap;
 use crate::runtime::vm::{
     CompiledModuleId, VMArrayCallFunction, VMFuncRef, VMFunctionBody, VMWasmCallFunction,
 };
+use crate::sync::OnceLock;
 use crate::{
     code::CodeObject, code_memory::CodeMemory, type_registry::TypeCollection, Engine, Module,
     ResourcesRequired,
@@ -94,6 +95,10 @@ struct ComponentInner {
     /// `realloc`, to avoid the need to look up types in the registry and take
     /// locks when calling `realloc` via `TypedFunc::call_raw`.
     realloc_func_type: Arc<dyn Any + Send + Sync>,
+
+    /// Hash identifying this component in instance snapshots, computed on
+    /// first use.
+    snapshot_hash: OnceLock<u64>,
 }
 
 pub(crate) struct AllCallFuncPointers {
@@ -454,6 +459,7 @@ impl Component {
                 code,
                 info,
                 realloc_func_type,
+                snapshot_hash: OnceLock::new(),
             }),
         })
     }
@@ -504,6 +510,12 @@ impl Component {
         &self.inner.code
     }
 
+    /// Returns the hash identifying this component in instance snapshots,
+    /// computing it with `hash` the first time it's requested.
+    pub(crate) fn snapshot_hash(&self, hash: impl FnOnce(&Component) -> u64) -> u64 {
+        *self.inner.snapshot_hash.get_or_init(|| hash(self))
+    }
+
     /// Same as [`Module::serialize`], except for a component.
     ///
     /// Note that the artifact produced here must be passed to
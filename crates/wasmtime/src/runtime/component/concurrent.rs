@@ -1,22 +1,109 @@
+//! Runtime support for the component model async ABI.
+//!
+//! This module implements the store-wide scheduler which drives async-lifted
+//! exports, async-lowered imports, waitable sets, streams, futures, and
+//! `error-context`s.
+//!
+//! Guest code always runs on the stack of whichever call is currently driving
+//! the store, so a "task" here is bookkeeping rather than a fiber of its own:
+//!
+//! * A task for an export lifted with a `callback` is resumed by invoking the
+//!   callback with each event delivered to it.
+//! * Operations which must block, such as `waitable-set.wait` or a
+//!   sync-lowered call to an async-lifted export, run a nested event loop
+//!   (`poll_until`) until whatever they're waiting on is ready. While a task
+//!   is blocked synchronously no other task may start or be resumed within
+//!   the same (sub)component instance.
+//!
+//! A consequence of this design is that an async-lifted export without a
+//! `callback` which blocks can only be resumed once every task which started
+//! blocking after it has resumed. Should the event loop run out of work while
+//! waiting, a "deadlock detected" error is raised rather than hanging.
+//!
+//! Host functions defined with `LinkerInstance::func_wrap_concurrent` produce
+//! futures which are stored in the store's `ConcurrentState` and polled by the
+//! event loop. The event loop runs whenever a [`Promise`] is resolved via
+//! [`Promise::get`] or [`PromisesUnordered::next`], or whenever guest code
+//! blocks.
+
 use {
     crate::{
-        store::StoreInner,
-        vm::{component::ComponentInstance, VMFuncRef, VMMemoryDefinition},
-        AsContextMut, ValRaw,
+        component::func::Options,
+        prelude::*,
+        runtime::vm::{
+            component::{ComponentInstance, ErrorContextState, WaitableState},
+            SendSyncPtr, VMFuncRef, VMMemoryDefinition, VMStore,
+        },
+        store::{StoreInner, StoreOpaque},
+        AsContextMut, StoreContextMut, ValRaw,
+    },
+    alloc::collections::VecDeque,
+    core::{
+        future::Future,
+        mem::{self, MaybeUninit},
+        pin::Pin,
+        ptr::NonNull,
+        task::{Context, Poll},
+    },
+    futures::{
+        channel::oneshot,
+        future::{self as future_util, FutureExt},
+        stream::{FuturesUnordered, StreamExt},
+        task::noop_waker_ref,
     },
-    anyhow::Result,
-    futures::{stream::FuturesUnordered, FutureExt},
-    std::{boxed::Box, future::Future, mem::MaybeUninit, pin::Pin},
     wasmtime_environ::component::{
-        RuntimeComponentInstanceIndex, TypeComponentLocalErrorContextTableIndex,
-        TypeFutureTableIndex, TypeStreamTableIndex, TypeTupleIndex,
+        RuntimeComponentInstanceIndex, StringEncoding, TypeComponentLocalErrorContextTableIndex,
+        TypeFutureTableIndex, TypeStreamTableIndex, TypeTupleIndex, MAX_FLAT_PARAMS,
+        MAX_FLAT_RESULTS,
     },
+    wasmtime_slab::{Id, Slab},
 };
 
-pub use futures_and_streams::{ErrorContext, FutureReader, StreamReader};
+pub use futures_and_streams::{
+    future, stream, ErrorContext, FutureReader, FutureWriter, HostFuture, HostSink, HostStream,
+    StreamReader, StreamWriter,
+};
 
 mod futures_and_streams;
 
+/// Status codes returned by async-lowered calls, in the upper two bits of the
+/// result with a subtask handle (if any) in the lower 30 bits.
+const STATUS_STARTING: u32 = 0;
+const STATUS_STARTED: u32 = 1;
+const STATUS_RETURNED: u32 = 2;
+const STATUS_DONE: u32 = 3;
+
+/// Event codes delivered to callbacks and returned by `waitable-set.wait`.
+const EVENT_CALL_STARTED: u32 = 1;
+const EVENT_CALL_RETURNED: u32 = 2;
+const EVENT_CALL_DONE: u32 = 3;
+const EVENT_STREAM_READ: u32 = 5;
+const EVENT_STREAM_WRITE: u32 = 6;
+const EVENT_FUTURE_READ: u32 = 7;
+const EVENT_FUTURE_WRITE: u32 = 8;
+
+/// Mirrors `wasmtime_environ::fact::EXIT_FLAG_ASYNC_CALLEE`, which is only
+/// available with the `compile` feature.
+const EXIT_FLAG_ASYNC_CALLEE: u32 = 1 << 0;
+
+/// A unit of work which needs exclusive access to the store, such as applying
+/// the result of a host future.
+type Thunk = Box<dyn FnOnce(&mut dyn VMStore) -> Result<()> + Send + Sync>;
+
+/// A host future which, once ready, yields a `Thunk` to apply its result.
+type EventLoopFuture = Pin<Box<dyn Future<Output = Thunk> + Send + Sync>>;
+
+/// Polls `future` once with a no-op waker.
+///
+/// This is sufficient for anything which is only ever woken by the event loop
+/// itself, since the loop re-checks readiness after each unit of work.
+fn poll_now<F: Future + ?Sized>(future: Pin<&mut F>) -> Option<F::Output> {
+    match future.poll(&mut Context::from_waker(noop_waker_ref())) {
+        Poll::Ready(value) => Some(value),
+        Poll::Pending => None,
+    }
+}
+
 /// Represents the result of a concurrent operation.
 ///
 /// This is similar to a [`std::future::Future`] except that it represents an
@@ -37,9 +124,18 @@ impl<T: 'static> Promise<T> {
     /// The returned future will require exclusive use of the store until it
     /// completes.  If you need to await more than one `Promise` concurrently,
     /// use [`PromisesUnordered`].
-    pub async fn get<U: Send>(self, store: impl AsContextMut<Data = U>) -> Result<T> {
-        _ = store;
-        todo!()
+    pub async fn get<U: Send>(self, mut store: impl AsContextMut<Data = U>) -> Result<T> {
+        let mut store = store.as_context_mut();
+        let mut future = self.0;
+        if store.0.async_support() {
+            store
+                .on_fiber(move |store| {
+                    poll_until(store.as_context_mut(), |_| poll_now(future.as_mut()))
+                })
+                .await?
+        } else {
+            poll_until(store, |_| poll_now(future.as_mut()))
+        }
     }
 
     /// Convert this `Promise` to a future which may be `await`ed for its
@@ -75,18 +171,910 @@ impl<T: 'static> PromisesUnordered<T> {
     }
 
     /// Get the next result from this collection, if any.
-    pub async fn next<U: Send>(&mut self, store: impl AsContextMut<Data = U>) -> Result<Option<T>> {
-        _ = store;
-        todo!()
+    pub async fn next<U: Send>(
+        &mut self,
+        mut store: impl AsContextMut<Data = U>,
+    ) -> Result<Option<T>> {
+        if self.0.is_empty() {
+            return Ok(None);
+        }
+        let mut store = store.as_context_mut();
+        let promises = &mut self.0;
+        if store.0.async_support() {
+            store
+                .on_fiber(move |store| {
+                    poll_until(store.as_context_mut(), |_| {
+                        poll_now(Pin::new(&mut promises.next()))
+                    })
+                })
+                .await?
+        } else {
+            poll_until(store, |_| poll_now(Pin::new(&mut promises.next())))
+        }
+    }
+}
+
+impl<T: 'static> Default for PromisesUnordered<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Store-wide state of the component model async ABI.
+#[derive(Default)]
+pub struct ConcurrentState {
+    /// Guest and host tasks which are in progress, or which have completed
+    /// but have yet to be dropped.
+    tasks: Slab<Task>,
+    /// Everything which may be waited on by a guest: subtasks and the ends of
+    /// streams and futures.
+    waitables: Slab<Waitable>,
+    /// Waitable sets created by `waitable-set.new`.
+    sets: Slab<WaitableSet>,
+    /// Streams and futures, shared between their two ends.
+    transmits: Slab<futures_and_streams::TransmitState>,
+    /// `error-context` values, which may be referenced from many tables.
+    error_contexts: Slab<ErrorContextData>,
+    /// Outstanding host futures, polled by the event loop.
+    futures: FuturesUnordered<EventLoopFuture>,
+    /// Work which is ready to run.
+    work: VecDeque<Work>,
+    /// The guest task which is currently running, if any.
+    current_task: Option<u32>,
+    /// State stashed by `sync_enter` and `async_enter` for the `*_exit`
+    /// which always immediately follows.
+    pending_call: Option<PendingCall>,
+}
+
+enum Work {
+    /// Run a thunk, e.g. to apply the result of a host future.
+    Thunk(Thunk),
+    /// Start the guest task with this id if its instance allows.
+    Start(u32),
+    /// Deliver pending events to the callback of the guest task with this id.
+    Deliver(u32),
+}
+
+enum Task {
+    Guest(GuestTask),
+    Host(HostTask),
+}
+
+/// A call to an async-lowered host function which didn't complete
+/// immediately.
+struct HostTask {
+    waitable: u32,
+    done: bool,
+}
+
+/// A call into a guest export, made either by another guest or by the host.
+struct GuestTask {
+    /// The component instance containing the callee.
+    instance: SendSyncPtr<ComponentInstance>,
+    /// The (sub)component instance which the callee belongs to.
+    runtime_instance: RuntimeComponentInstanceIndex,
+    /// Enters the callee; present until the task has started.
+    start: Option<StartFn>,
+    /// The callback of a callback-lifted export and its most recent context.
+    callback: Option<(SendSyncPtr<VMFuncRef>, u32)>,
+    /// Where results go once available.
+    caller: Caller,
+    /// The type expected by `task.return`, for async-lifted exports.
+    task_return_type: Option<TypeTupleIndex>,
+    /// The waitable representing this task in its guest caller, if any.
+    waitable: Option<u32>,
+    /// Waitables owned by this task which have an event pending, in order.
+    events: VecDeque<u32>,
+    /// Whether a callback of this task is currently on the stack.
+    running: bool,
+    /// Whether this task has produced its result.
+    returned: bool,
+    /// Whether this task has finished entirely.
+    done: bool,
+}
+
+type StartFn = Box<dyn FnOnce(&mut dyn VMStore, u32) -> Result<()> + Send + Sync>;
+
+/// Lifts the results of a host-initiated call out of the flat values passed to
+/// `task.return`.
+type ResultFn = Box<dyn FnOnce(&mut dyn VMStore, &[ValRaw]) -> Result<()> + Send + Sync>;
+
+enum Caller {
+    /// Called from another guest through a fused adapter.
+    Guest {
+        /// Adapter which copies the results into the caller.
+        return_: SendSyncPtr<VMFuncRef>,
+        /// Where the caller wants results stored, if indirect.
+        retptr: Option<ValRaw>,
+        /// How many flat values `return_` returns to a sync-lowered caller.
+        result_count: usize,
+        /// Those values, once available.
+        results: Option<Vec<ValRaw>>,
+    },
+    /// Called from the host via `TypedFunc::call_concurrent`.
+    Host { lift: Option<ResultFn> },
+}
+
+struct Waitable {
+    /// Handle of this waitable within its owning instance, or 0 if held by the
+    /// host.
+    handle: u32,
+    /// Task to which events are delivered when this isn't in a set.
+    owner: Option<u32>,
+    /// Set which this waitable has joined, if any.
+    set: Option<u32>,
+    /// Most recent undelivered event and its payload.
+    event: Option<(u32, u32)>,
+}
+
+#[derive(Default)]
+struct WaitableSet {
+    /// Waitables in this set with an event pending, in order.
+    ready: VecDeque<u32>,
+}
+
+struct ErrorContextData {
+    debug_message: String,
+    /// Number of table entries and host handles referring to this value.
+    refs: usize,
+}
+
+struct PendingCall {
+    start: SendSyncPtr<VMFuncRef>,
+    return_: SendSyncPtr<VMFuncRef>,
+    task_return_type: TypeTupleIndex,
+    lower: PendingLower,
+}
+
+enum PendingLower {
+    Sync {
+        storage: Vec<ValRaw>,
+        result_count: u32,
+    },
+    Async {
+        params: u32,
+        results: u32,
+    },
+}
+
+fn rep(id: Id) -> u32 {
+    id.into_raw()
+}
+
+fn id(rep: u32) -> Id {
+    Id::from_raw(rep)
+}
+
+impl ConcurrentState {
+    fn guest_task(&mut self, rep: u32) -> Result<&mut GuestTask> {
+        match self.tasks.get_mut(id(rep)) {
+            Some(Task::Guest(task)) => Ok(task),
+            _ => bail!("unknown guest task"),
+        }
+    }
+
+    fn new_waitable(&mut self, handle: u32, owner: Option<u32>) -> u32 {
+        rep(self.waitables.alloc(Waitable {
+            handle,
+            owner,
+            set: None,
+            event: None,
+        }))
+    }
+
+    fn waitable(&mut self, rep: u32) -> &mut Waitable {
+        self.waitables.get_mut(id(rep)).unwrap()
+    }
+
+    /// Records `event` for `waitable`, superseding any previous undelivered
+    /// event, and routes it to the waitable's set or owning task.
+    fn push_event(&mut self, waitable: u32, event: u32, payload: u32) {
+        let w = self.waitable(waitable);
+        let queued = w.event.is_some();
+        w.event = Some((event, payload));
+        if queued {
+            return;
+        }
+        let (set, owner) = (w.set, w.owner);
+        if let Some(set) = set {
+            self.sets
+                .get_mut(id(set))
+                .unwrap()
+                .ready
+                .push_back(waitable);
+        } else if let Some(owner) = owner {
+            if let Ok(task) = self.guest_task(owner) {
+                task.events.push_back(waitable);
+                if task.callback.is_some() && !task.running {
+                    self.work.push_back(Work::Deliver(owner));
+                }
+            }
+        }
+    }
+
+    /// Takes the pending event of `waitable`, if any.
+    fn take_event(&mut self, waitable: u32) -> Option<(u32, u32)> {
+        self.waitable(waitable).event.take()
+    }
+
+    /// Pops the next event from `set`, returning the handle it's for along
+    /// with the event code and payload.
+    fn pop_set_event(&mut self, set: u32) -> Option<(u32, u32, u32)> {
+        while let Some(waitable) = self.sets.get_mut(id(set))?.ready.pop_front() {
+            let Some(w) = self.waitables.get_mut(id(waitable)) else {
+                continue;
+            };
+            if w.set != Some(set) {
+                continue;
+            }
+            if let Some((event, payload)) = w.event.take() {
+                return Some((w.handle, event, payload));
+            }
+        }
+        None
+    }
+
+    /// Pops the next event delivered directly to `task`.
+    fn pop_task_event(&mut self, task: u32) -> Option<(u32, u32, u32)> {
+        while let Some(waitable) = self.guest_task(task).ok()?.events.pop_front() {
+            let Some(w) = self.waitables.get_mut(id(waitable)) else {
+                continue;
+            };
+            if w.owner != Some(task) || w.set.is_some() {
+                continue;
+            }
+            if let Some((event, payload)) = w.event.take() {
+                return Some((w.handle, event, payload));
+            }
+        }
+        None
+    }
+
+    /// Moves `waitable` into `set` (or out of any set if `None`), carrying
+    /// along a pending event.
+    fn join(&mut self, waitable: u32, set: Option<u32>) {
+        let w = self.waitable(waitable);
+        w.set = set;
+        let (pending, owner) = (w.event.is_some(), w.owner);
+        if !pending {
+            return;
+        }
+        match set {
+            Some(set) => self
+                .sets
+                .get_mut(id(set))
+                .unwrap()
+                .ready
+                .push_back(waitable),
+            None => {
+                if let Some(owner) = owner {
+                    if let Ok(task) = self.guest_task(owner) {
+                        task.events.push_back(waitable);
+                        if task.callback.is_some() && !task.running {
+                            self.work.push_back(Work::Deliver(owner));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Transfers ownership of `waitable` to a new handle, discarding any set
+    /// membership and pending event.
+    fn rehome(&mut self, waitable: u32, handle: u32, owner: Option<u32>) {
+        let w = self.waitable(waitable);
+        w.handle = handle;
+        w.owner = owner;
+        w.set = None;
+        w.event = None;
+    }
+
+    fn push_future(&mut self, future: EventLoopFuture) {
+        self.futures.push(future);
+    }
+}
+
+/// Converts a type-erased store back to its concrete type.
+///
+/// # Unsafety
+///
+/// `T` must be the type of data stored within `store`.
+pub(crate) unsafe fn store_context<'a, T>(store: &'a mut dyn VMStore) -> StoreContextMut<'a, T> {
+    StoreContextMut(&mut *(store as *mut dyn VMStore).cast::<StoreInner<T>>())
+}
+
+/// Whether tasks in `runtime_instance` may be started (`starting`) or resumed
+/// right now.
+fn instance_available(
+    instance: *mut ComponentInstance,
+    runtime_instance: RuntimeComponentInstanceIndex,
+    starting: bool,
+) -> bool {
+    let state = unsafe { (*instance).instance_state(runtime_instance) };
+    state.blocked == 0 && !(starting && state.backpressure)
+}
+
+/// Re-queues tasks which were waiting on `runtime_instance` to become
+/// available.
+fn wake_pending(
+    state: &mut ConcurrentState,
+    instance: *mut ComponentInstance,
+    runtime_instance: RuntimeComponentInstanceIndex,
+) {
+    let pending = mem::take(unsafe { &mut (*instance).instance_state(runtime_instance).pending });
+    for task in pending {
+        match state.guest_task(task) {
+            Ok(t) if t.start.is_some() => state.work.push_back(Work::Start(task)),
+            Ok(_) => state.work.push_back(Work::Deliver(task)),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Runs the event loop for `store` until `ready` returns a value.
+///
+/// Each iteration either finds `ready` satisfied, runs a single unit of work,
+/// or waits for at least one host future to complete. If there's nothing left
+/// that could make progress then an error is returned.
+fn poll_until<T, R>(
+    mut store: StoreContextMut<'_, T>,
+    mut ready: impl FnMut(&mut ConcurrentState) -> Option<R>,
+) -> Result<R> {
+    loop {
+        if let Some(value) = ready(store.0.concurrent_state_mut()) {
+            return Ok(value);
+        }
+        if let Some(work) = store.0.concurrent_state_mut().work.pop_front() {
+            run_work(store.as_context_mut(), work)?;
+            continue;
+        }
+        if store.0.concurrent_state_mut().futures.is_empty() {
+            bail!("deadlock detected: event loop cannot make further progress");
+        }
+        let completed = poll_host_futures(store.0, true)?;
+        store.0.concurrent_state_mut().work.extend(completed);
+    }
+}
+
+/// Makes as much progress as possible without blocking, as used by `yield`
+/// and `waitable-set.poll`.
+fn poll_once<T>(mut store: StoreContextMut<'_, T>) -> Result<()> {
+    let completed = poll_host_futures(store.0, false)?;
+    store.0.concurrent_state_mut().work.extend(completed);
+    let count = store.0.concurrent_state_mut().work.len();
+    for _ in 0..count {
+        let Some(work) = store.0.concurrent_state_mut().work.pop_front() else {
+            break;
+        };
+        run_work(store.as_context_mut(), work)?;
+    }
+    Ok(())
+}
+
+/// Polls outstanding host futures, returning work to apply the results of
+/// those which completed.
+///
+/// If `block` is set and async support is enabled then this suspends the
+/// current fiber until at least one future completes.
+fn poll_host_futures(store: &mut StoreOpaque, block: bool) -> Result<Vec<Work>> {
+    let mut futures = mem::take(&mut store.concurrent_state_mut().futures);
+    let mut ready = Vec::new();
+    let mut poll = |cx: &mut Context<'_>| {
+        while let Poll::Ready(Some(thunk)) = futures.poll_next_unpin(cx) {
+            ready.push(Work::Thunk(thunk));
+        }
+        if ready.is_empty() && !futures.is_empty() {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    };
+    let result = if block && store.async_support() {
+        match store.async_cx() {
+            Some(cx) => unsafe { cx.block_on(future_util::poll_fn(&mut poll)) },
+            None => Err(anyhow!("cannot block outside of a fiber")),
+        }
+    } else {
+        _ = poll(&mut Context::from_waker(noop_waker_ref()));
+        Ok(())
+    };
+
+    let added = mem::replace(&mut store.concurrent_state_mut().futures, futures);
+    store.concurrent_state_mut().futures.extend(added);
+    result?;
+
+    if block && ready.is_empty() {
+        bail!("deadlock detected: host futures cannot complete without async support");
+    }
+    Ok(ready)
+}
+
+fn run_work<T>(store: StoreContextMut<'_, T>, work: Work) -> Result<()> {
+    match work {
+        Work::Thunk(thunk) => thunk(store.0),
+        Work::Start(task) => start_task(store, task),
+        Work::Deliver(task) => deliver(store, task),
+    }
+}
+
+/// How an export was lifted, which determines what its core function returns.
+#[derive(Copy, Clone)]
+enum LiftKind {
+    /// Async with a `callback`: returns a context, or 0 if done.
+    Callback,
+    /// Async without a `callback`: returns nothing and is done on return.
+    Stackful,
+    /// Sync: returns its results directly.
+    Sync {
+        result_count: usize,
+        post_return: Option<SendSyncPtr<VMFuncRef>>,
+    },
+}
+
+/// Calls `func` with `args`, which must be large enough to hold both the
+/// parameters and results of `func`.
+unsafe fn call_func<T>(
+    store: &mut StoreContextMut<'_, T>,
+    func: SendSyncPtr<VMFuncRef>,
+    args: &mut [ValRaw],
+) -> Result<()> {
+    crate::Func::call_unchecked_raw(store, func.as_non_null(), NonNull::from(args))
+}
+
+/// Runs `f` with `task` as the current task.
+fn with_task<T, R>(
+    mut store: StoreContextMut<'_, T>,
+    task: u32,
+    f: impl FnOnce(StoreContextMut<'_, T>) -> Result<R>,
+) -> Result<R> {
+    let prev = store.0.concurrent_state_mut().current_task.replace(task);
+    let result = f(store.as_context_mut());
+    store.0.concurrent_state_mut().current_task = prev;
+    result
+}
+
+/// Starts the guest task `rep` unless its instance is unavailable, in which
+/// case it's queued until the instance becomes available.
+fn start_task<T>(store: StoreContextMut<'_, T>, rep: u32) -> Result<()> {
+    let state = store.0.concurrent_state_mut();
+    let Ok(task) = state.guest_task(rep) else {
+        return Ok(());
+    };
+    if task.start.is_none() {
+        return Ok(());
+    }
+    let (instance, runtime_instance) = (task.instance.as_ptr(), task.runtime_instance);
+    if !instance_available(instance, runtime_instance, true) {
+        unsafe {
+            (*instance)
+                .instance_state(runtime_instance)
+                .pending
+                .push_back(rep)
+        };
+        return Ok(());
+    }
+    let start = task.start.take().unwrap();
+    if let Some(waitable) = task.waitable {
+        state.push_event(waitable, EVENT_CALL_STARTED, 0);
+    }
+    start(store.0, rep)
+}
+
+/// Calls the core function `callee` of the guest task `rep` with `args` and
+/// handles its result according to `lift`.
+///
+/// This must be called with `rep` as the current task.
+unsafe fn enter_guest<T>(
+    mut store: StoreContextMut<'_, T>,
+    rep: u32,
+    callee: SendSyncPtr<VMFuncRef>,
+    mut args: Vec<ValRaw>,
+    lift: LiftKind,
+) -> Result<()> {
+    let task = store.0.concurrent_state_mut().guest_task(rep)?;
+    let (instance, runtime_instance) = (task.instance.as_ptr(), task.runtime_instance);
+
+    // Sync-lifted exports lock their instance for the duration of the call.
+    let sync = matches!(lift, LiftKind::Sync { .. });
+    if sync {
+        (*instance).instance_state(runtime_instance).blocked += 1;
+    }
+    let result = call_func(&mut store, callee, &mut args);
+    if sync {
+        let state = (*instance).instance_state(runtime_instance);
+        state.blocked -= 1;
+        if state.blocked == 0 {
+            wake_pending(store.0.concurrent_state_mut(), instance, runtime_instance);
+        }
+    }
+    result?;
+
+    match lift {
+        LiftKind::Callback => {
+            let ctx = args[0].get_u32();
+            if ctx == 0 {
+                return task_exit(store, rep);
+            }
+            let state = store.0.concurrent_state_mut();
+            let task = state.guest_task(rep)?;
+            if let Some((_, prev)) = &mut task.callback {
+                *prev = ctx;
+            }
+            if !task.events.is_empty() {
+                state.work.push_back(Work::Deliver(rep));
+            }
+            Ok(())
+        }
+        LiftKind::Stackful => task_exit(store, rep),
+        LiftKind::Sync {
+            result_count,
+            post_return,
+        } => {
+            let mut results = args[..result_count].to_vec();
+            deliver_results(store.as_context_mut(), rep, &results)?;
+            if let Some(post_return) = post_return {
+                call_func(&mut store, post_return, &mut results)?;
+            }
+            task_exit(store, rep)
+        }
     }
 }
 
+/// Hands the results of the guest task `rep` to its caller.
+fn deliver_results<T>(
+    mut store: StoreContextMut<'_, T>,
+    rep: u32,
+    values: &[ValRaw],
+) -> Result<()> {
+    let task = store.0.concurrent_state_mut().guest_task(rep)?;
+    if task.returned {
+        bail!("`task.return` called more than once");
+    }
+    task.returned = true;
+    match &mut task.caller {
+        Caller::Guest {
+            return_,
+            retptr,
+            result_count,
+            ..
+        } => {
+            let (return_, result_count) = (*return_, *result_count);
+            let mut args = values.to_vec();
+            args.extend(*retptr);
+            if args.len() < result_count {
+                args.resize(result_count, ValRaw::i32(0));
+            }
+            unsafe { call_func(&mut store, return_, &mut args)? };
+            if result_count > 0 {
+                if let Caller::Guest { results, .. } =
+                    &mut store.0.concurrent_state_mut().guest_task(rep)?.caller
+                {
+                    *results = Some(args[..result_count].to_vec());
+                }
+            }
+        }
+        Caller::Host { lift } => {
+            let lift = lift.take().unwrap();
+            lift(store.0, values)?;
+        }
+    }
+
+    let state = store.0.concurrent_state_mut();
+    if let Some(waitable) = state.guest_task(rep)?.waitable {
+        state.push_event(waitable, EVENT_CALL_RETURNED, 0);
+    }
+    Ok(())
+}
+
+/// Marks the guest task `rep` as finished.
+fn task_exit<T>(store: StoreContextMut<'_, T>, rep: u32) -> Result<()> {
+    let state = store.0.concurrent_state_mut();
+    let task = state.guest_task(rep)?;
+    if !task.returned {
+        bail!("async-lifted export exited without calling `task.return`");
+    }
+    task.done = true;
+    let waitable = task.waitable;
+    let host = matches!(task.caller, Caller::Host { .. });
+    if let Some(waitable) = waitable {
+        state.push_event(waitable, EVENT_CALL_DONE, 0);
+    }
+    if host {
+        state.tasks.dealloc(id(rep));
+    }
+    Ok(())
+}
+
+/// Delivers the next pending event of the callback-lifted guest task `rep`.
+fn deliver<T>(mut store: StoreContextMut<'_, T>, rep: u32) -> Result<()> {
+    let state = store.0.concurrent_state_mut();
+    let Ok(task) = state.guest_task(rep) else {
+        return Ok(());
+    };
+    if task.done || task.running || task.start.is_some() {
+        return Ok(());
+    }
+    let Some((callback, ctx)) = task.callback else {
+        return Ok(());
+    };
+    let (instance, runtime_instance) = (task.instance.as_ptr(), task.runtime_instance);
+    if !instance_available(instance, runtime_instance, false) {
+        unsafe {
+            (*instance)
+                .instance_state(runtime_instance)
+                .pending
+                .push_back(rep)
+        };
+        return Ok(());
+    }
+    let Some((handle, event, payload)) = state.pop_task_event(rep) else {
+        return Ok(());
+    };
+
+    state.guest_task(rep)?.running = true;
+    let mut args = [
+        ValRaw::u32(ctx),
+        ValRaw::u32(event),
+        ValRaw::u32(handle),
+        ValRaw::u32(payload),
+    ];
+    let result = with_task(store.as_context_mut(), rep, |mut store| unsafe {
+        call_func(&mut store, callback, &mut args)
+    });
+    let state = store.0.concurrent_state_mut();
+    let task = state.guest_task(rep)?;
+    task.running = false;
+    result?;
+
+    if args[0].get_u32() != 0 {
+        return task_exit(store, rep);
+    }
+    if !task.events.is_empty() {
+        state.work.push_back(Work::Deliver(rep));
+    }
+    Ok(())
+}
+
+/// Runs the event loop until the guest task `rep` is done while `instance`
+/// is blocked, then returns its results (if any) and removes it.
+fn wait_for_task<T>(
+    mut store: StoreContextMut<'_, T>,
+    instance: *mut ComponentInstance,
+    caller_instance: RuntimeComponentInstanceIndex,
+    rep: u32,
+) -> Result<Option<Vec<ValRaw>>> {
+    unsafe { (*instance).instance_state(caller_instance).blocked += 1 };
+    let result = poll_until(store.as_context_mut(), |state| {
+        match state.guest_task(rep) {
+            Ok(task) if !task.done => None,
+            _ => Some(()),
+        }
+    });
+    let blocked = unsafe { &mut (*instance).instance_state(caller_instance).blocked };
+    *blocked -= 1;
+    if *blocked == 0 {
+        wake_pending(store.0.concurrent_state_mut(), instance, caller_instance);
+    }
+    result?;
+
+    let state = store.0.concurrent_state_mut();
+    let results = match &mut state.guest_task(rep)?.caller {
+        Caller::Guest { results, .. } => results.take(),
+        Caller::Host { .. } => None,
+    };
+    state.tasks.dealloc(id(rep));
+    Ok(results)
+}
+
+/// Describes an async-lifted export to call from the host.
+pub(crate) struct GuestCall {
+    pub instance: SendSyncPtr<ComponentInstance>,
+    pub runtime_instance: RuntimeComponentInstanceIndex,
+    pub callee: SendSyncPtr<VMFuncRef>,
+    pub callback: Option<SendSyncPtr<VMFuncRef>>,
+    pub task_return_type: TypeTupleIndex,
+}
+
+/// Queues a host-initiated call to an async-lifted export.
+///
+/// The call starts once the event loop runs. `lower` produces the flat
+/// parameters of the callee and `lift` converts the values passed to
+/// `task.return` into the result of the returned `Promise`.
+pub(crate) fn start_guest_call<T, R>(
+    store: StoreContextMut<'_, T>,
+    call: GuestCall,
+    lower: impl FnOnce(&mut dyn VMStore) -> Result<Vec<ValRaw>> + Send + Sync + 'static,
+    lift: impl FnOnce(&mut dyn VMStore, &[ValRaw]) -> Result<R> + Send + Sync + 'static,
+) -> Promise<R>
+where
+    R: Send + Sync + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let GuestCall {
+        instance,
+        runtime_instance,
+        callee,
+        callback,
+        task_return_type,
+    } = call;
+    let lift_kind = if callback.is_some() {
+        LiftKind::Callback
+    } else {
+        LiftKind::Stackful
+    };
+    let start: StartFn = Box::new(move |store, rep| {
+        let mut store = unsafe { store_context::<T>(store) };
+        with_task(store.as_context_mut(), rep, |mut store| unsafe {
+            store.0.reset_component_adapter_copied();
+            let mut args = lower(store.0)?;
+            if args.is_empty() {
+                args.push(ValRaw::i32(0));
+            }
+            enter_guest(store.as_context_mut(), rep, callee, args, lift_kind)
+        })
+    });
+    let state = store.0.concurrent_state_mut();
+    let rep = rep(state.tasks.alloc(Task::Guest(GuestTask {
+        instance,
+        runtime_instance,
+        start: Some(start),
+        callback: callback.map(|c| (c, 0)),
+        caller: Caller::Host {
+            lift: Some(Box::new(move |store, values| {
+                _ = tx.send(lift(store, values)?);
+                Ok(())
+            })),
+        },
+        task_return_type: Some(task_return_type),
+        waitable: None,
+        events: VecDeque::new(),
+        running: false,
+        returned: false,
+        done: false,
+    })));
+    state.work.push_back(Work::Start(rep));
+    Promise(Box::pin(rx.map(|result| {
+        result.expect("task exited without producing a result")
+    })))
+}
+
+/// Queues a host-initiated call to a sync-lifted export.
+///
+/// `call` runs the next time the event loop runs, and its result resolves the
+/// returned `Promise`.
+pub(crate) fn queue_call<T, R>(
+    store: &mut StoreOpaque,
+    call: impl FnOnce(StoreContextMut<'_, T>) -> Result<R> + Send + Sync + 'static,
+) -> Promise<R>
+where
+    T: 'static,
+    R: Send + Sync + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    store
+        .concurrent_state_mut()
+        .work
+        .push_back(Work::Thunk(Box::new(move |store| {
+            let store = unsafe { store_context::<T>(store) };
+            _ = tx.send(call(store)?);
+            Ok(())
+        })));
+    Promise(Box::pin(rx.map(|result| {
+        result.expect("call exited without producing a result")
+    })))
+}
+
+/// Waits for `future`, produced by a host function called from a sync-lowered
+/// import, while running the event loop.
+///
+/// The calling instance is blocked in the meantime.
+pub(crate) fn wait_for_host<T, R>(
+    mut store: StoreContextMut<'_, T>,
+    instance: *mut ComponentInstance,
+    caller_instance: RuntimeComponentInstanceIndex,
+    future: impl Future<Output = R> + Send + Sync + 'static,
+) -> Result<R>
+where
+    R: Send + Sync + 'static,
+{
+    let mut future = Box::pin(future);
+    if let Some(value) = poll_now(future.as_mut()) {
+        return Ok(value);
+    }
+    let (tx, mut rx) = oneshot::channel();
+    store
+        .0
+        .concurrent_state_mut()
+        .push_future(Box::pin(future.map(move |value| {
+            Box::new(move |_: &mut dyn VMStore| {
+                _ = tx.send(value);
+                Ok(())
+            }) as Thunk
+        })));
+
+    unsafe { (*instance).instance_state(caller_instance).blocked += 1 };
+    let result = poll_until(store.as_context_mut(), |_| rx.try_recv().ok().flatten());
+    let blocked = unsafe { &mut (*instance).instance_state(caller_instance).blocked };
+    *blocked -= 1;
+    if *blocked == 0 {
+        wake_pending(store.0.concurrent_state_mut(), instance, caller_instance);
+    }
+    result
+}
+
+/// Starts `future`, produced by a host function called from an async-lowered
+/// import.
+///
+/// If `future` completes immediately then `complete` is applied right away
+/// and `Ok(None)` is returned. Otherwise a subtask is created in
+/// `caller_instance` and its handle is returned; `complete` is applied and
+/// the subtask marked done once `future` completes.
+pub(crate) fn start_host_call<T, R>(
+    store: StoreContextMut<'_, T>,
+    instance: *mut ComponentInstance,
+    caller_instance: RuntimeComponentInstanceIndex,
+    future: impl Future<Output = R> + Send + Sync + 'static,
+    complete: impl FnOnce(StoreContextMut<'_, T>, R) -> Result<()> + Send + Sync + 'static,
+) -> Result<Option<u32>>
+where
+    R: Send + Sync + 'static,
+{
+    let mut future = Box::pin(future);
+    if let Some(value) = poll_now(future.as_mut()) {
+        complete(store, value)?;
+        return Ok(None);
+    }
+
+    let state = store.0.concurrent_state_mut();
+    let owner = state.current_task;
+    let task = rep(state.tasks.alloc(Task::Host(HostTask {
+        waitable: 0,
+        done: false,
+    })));
+    let handle = unsafe {
+        (*instance)
+            .instance_state(caller_instance)
+            .handles
+            .insert(WaitableState::Task { rep: task })?
+    };
+    let waitable = state.new_waitable(handle, owner);
+    if let Some(Task::Host(host)) = state.tasks.get_mut(id(task)) {
+        host.waitable = waitable;
+    }
+
+    state.push_future(Box::pin(future.map(move |value| {
+        Box::new(move |store: &mut dyn VMStore| {
+            let mut store = unsafe { store_context::<T>(store) };
+            complete(store.as_context_mut(), value)?;
+            let state = store.0.concurrent_state_mut();
+            if let Some(Task::Host(host)) = state.tasks.get_mut(id(task)) {
+                host.done = true;
+                state.push_event(waitable, EVENT_CALL_DONE, 0);
+            }
+            Ok(())
+        }) as Thunk
+    })));
+    Ok(Some(handle))
+}
+/// The status returned by an async-lowered import which completed
+/// immediately.
+pub(crate) fn status_done() -> u32 {
+    STATUS_DONE << 30
+}
+
+/// The status returned by an async-lowered import which continues as the
+/// subtask `handle`.
+pub(crate) fn status_started(handle: u32) -> u32 {
+    (STATUS_STARTED << 30) | handle
+}
+
 /// Trait representing component model ABI async intrinsics and fused adapter
 /// helper functions.
 pub unsafe trait VMComponentAsyncStore {
     /// The `backpressure.set` intrinsic.
     fn backpressure_set(
         &mut self,
+        instance: &mut ComponentInstance,
         caller_instance: RuntimeComponentInstanceIndex,
         enabled: u32,
     ) -> Result<()>;
@@ -413,16 +1401,160 @@ pub unsafe trait VMComponentAsyncStore {
         ty: TypeComponentLocalErrorContextTableIndex,
         err_ctx_handle: u32,
     ) -> Result<()>;
+
+    /// Transfers the readable end of the future at `src_idx` in the `src`
+    /// table to the `dst` table, returning its new handle.
+    fn future_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeFutureTableIndex,
+        dst: TypeFutureTableIndex,
+    ) -> Result<u32>;
+
+    /// Transfers the readable end of the stream at `src_idx` in the `src`
+    /// table to the `dst` table, returning its new handle.
+    fn stream_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeStreamTableIndex,
+        dst: TypeStreamTableIndex,
+    ) -> Result<u32>;
+
+    /// Copies the `error-context` at `src_idx` in the `src` table to the `dst`
+    /// table, returning its new handle.
+    fn error_context_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeComponentLocalErrorContextTableIndex,
+        dst: TypeComponentLocalErrorContextTableIndex,
+    ) -> Result<u32>;
+}
+
+/// Looks up `handle` in the waitable table of `runtime_instance`.
+fn lookup(
+    instance: *mut ComponentInstance,
+    runtime_instance: RuntimeComponentInstanceIndex,
+    handle: u32,
+) -> Result<WaitableState> {
+    unsafe { (*instance).instance_state(runtime_instance) }
+        .handles
+        .get(handle)
+        .copied()
+        .ok_or_else(|| anyhow!("unknown handle: {handle}"))
+}
+
+fn lookup_set(
+    instance: *mut ComponentInstance,
+    runtime_instance: RuntimeComponentInstanceIndex,
+    handle: u32,
+) -> Result<u32> {
+    match lookup(instance, runtime_instance, handle)? {
+        WaitableState::Set { rep } => Ok(rep),
+        _ => bail!("handle {handle} is not a waitable set"),
+    }
+}
+
+/// Writes the handle and payload of an event to `address` in `memory`.
+fn write_event(
+    store: &mut StoreOpaque,
+    memory: *mut VMMemoryDefinition,
+    address: u32,
+    handle: u32,
+    payload: u32,
+) -> Result<()> {
+    let options =
+        unsafe { Options::new(store.id(), NonNull::new(memory), None, StringEncoding::Utf8) };
+    let address = usize::try_from(address)?;
+    if address % 4 != 0 {
+        bail!("pointer not aligned");
+    }
+    let bytes = options
+        .memory_mut(store)
+        .get_mut(address..)
+        .and_then(|b| b.get_mut(..8))
+        .ok_or_else(|| anyhow!("pointer out of bounds of memory"))?;
+    bytes[..4].copy_from_slice(&handle.to_le_bytes());
+    bytes[4..].copy_from_slice(&payload.to_le_bytes());
+    Ok(())
+}
+
+impl ConcurrentState {
+    /// Returns the waitable for the task `rep`, if it has one.
+    fn task_waitable(&mut self, rep: u32) -> Result<Option<u32>> {
+        match self.tasks.get(id(rep)) {
+            Some(Task::Guest(task)) => Ok(task.waitable),
+            Some(Task::Host(task)) => Ok(Some(task.waitable)),
+            None => bail!("unknown subtask"),
+        }
+    }
+
+    fn new_guest_task(
+        &mut self,
+        instance: *mut ComponentInstance,
+        runtime_instance: RuntimeComponentInstanceIndex,
+        callback: *mut VMFuncRef,
+        caller: Caller,
+        task_return_type: Option<TypeTupleIndex>,
+        start: StartFn,
+    ) -> u32 {
+        rep(self.tasks.alloc(Task::Guest(GuestTask {
+            instance: SendSyncPtr::new(NonNull::new(instance).unwrap()),
+            runtime_instance,
+            start: Some(start),
+            callback: NonNull::new(callback).map(|c| (SendSyncPtr::new(c), 0)),
+            caller,
+            task_return_type,
+            waitable: None,
+            events: VecDeque::new(),
+            running: false,
+            returned: false,
+            done: false,
+        })))
+    }
+}
+
+/// Creates the function which starts a guest-to-guest call: `start` copies
+/// the parameters from caller to callee and then `callee` is entered.
+fn guest_start<T>(
+    start: SendSyncPtr<VMFuncRef>,
+    mut args: Vec<ValRaw>,
+    callee: SendSyncPtr<VMFuncRef>,
+    lift: LiftKind,
+) -> StartFn {
+    Box::new(move |store, rep| {
+        let mut store = unsafe { store_context::<T>(store) };
+        with_task(store.as_context_mut(), rep, |mut store| unsafe {
+            let len = args.len().max(MAX_FLAT_PARAMS);
+            args.resize(len, ValRaw::i32(0));
+            call_func(&mut store, start, &mut args)?;
+            enter_guest(store, rep, callee, args, lift)
+        })
+    })
+}
+
+fn func_ptr(func: *mut VMFuncRef) -> Result<SendSyncPtr<VMFuncRef>> {
+    NonNull::new(func)
+        .map(SendSyncPtr::new)
+        .ok_or_else(|| anyhow!("unexpected null function reference"))
 }
 
 unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
     fn backpressure_set(
         &mut self,
+        instance: &mut ComponentInstance,
         caller_instance: RuntimeComponentInstanceIndex,
         enabled: u32,
     ) -> Result<()> {
-        _ = (caller_instance, enabled);
-        todo!()
+        let instance: *mut ComponentInstance = instance;
+        let state = unsafe { (*instance).instance_state(caller_instance) };
+        state.backpressure = enabled != 0;
+        if !state.backpressure {
+            wake_pending(self.concurrent_state_mut(), instance, caller_instance);
+        }
+        Ok(())
     }
 
     fn task_return(
@@ -432,8 +1564,17 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         storage: *mut ValRaw,
         storage_len: usize,
     ) -> Result<()> {
-        _ = (instance, ty, storage, storage_len);
-        todo!()
+        _ = instance;
+        let store = StoreContextMut(self);
+        let state = store.0.concurrent_state_mut();
+        let rep = state
+            .current_task
+            .ok_or_else(|| anyhow!("`task.return` called outside of a task"))?;
+        if state.guest_task(rep)?.task_return_type != Some(ty) {
+            bail!("invalid `task.return` signature and/or options for current task");
+        }
+        let values = unsafe { core::slice::from_raw_parts(storage, storage_len) }.to_vec();
+        deliver_results(store, rep, &values)
     }
 
     fn waitable_set_new(
@@ -441,8 +1582,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         instance: &mut ComponentInstance,
         caller_instance: RuntimeComponentInstanceIndex,
     ) -> Result<u32> {
-        _ = (instance, caller_instance);
-        todo!();
+        let set = rep(self
+            .concurrent_state_mut()
+            .sets
+            .alloc(WaitableSet::default()));
+        instance
+            .instance_state(caller_instance)
+            .handles
+            .insert(WaitableState::Set { rep: set })
     }
 
     fn waitable_set_wait(
@@ -454,8 +1601,23 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         memory: *mut VMMemoryDefinition,
         payload: u32,
     ) -> Result<u32> {
-        _ = (instance, caller_instance, set, async_, memory, payload);
-        todo!();
+        let instance: *mut ComponentInstance = instance;
+        let set = lookup_set(instance, caller_instance, set)?;
+        let mut store = StoreContextMut(self);
+        if !async_ {
+            unsafe { (*instance).instance_state(caller_instance).blocked += 1 };
+        }
+        let result = poll_until(store.as_context_mut(), |state| state.pop_set_event(set));
+        if !async_ {
+            let blocked = unsafe { &mut (*instance).instance_state(caller_instance).blocked };
+            *blocked -= 1;
+            if *blocked == 0 {
+                wake_pending(store.0.concurrent_state_mut(), instance, caller_instance);
+            }
+        }
+        let (handle, event, value) = result?;
+        write_event(store.0, memory, payload, handle, value)?;
+        Ok(event)
     }
 
     fn waitable_set_poll(
@@ -467,8 +1629,17 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         memory: *mut VMMemoryDefinition,
         payload: u32,
     ) -> Result<u32> {
-        _ = (instance, caller_instance, set, async_, memory, payload);
-        todo!();
+        _ = async_;
+        let set = lookup_set(instance, caller_instance, set)?;
+        let mut store = StoreContextMut(self);
+        poll_once(store.as_context_mut())?;
+        match store.0.concurrent_state_mut().pop_set_event(set) {
+            Some((handle, event, value)) => {
+                write_event(store.0, memory, payload, handle, value)?;
+                Ok(event)
+            }
+            None => Ok(0),
+        }
     }
 
     fn waitable_set_drop(
@@ -477,8 +1648,16 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         caller_instance: RuntimeComponentInstanceIndex,
         set: u32,
     ) -> Result<()> {
-        _ = (instance, caller_instance, set);
-        todo!();
+        let rep = lookup_set(instance, caller_instance, set)?;
+        instance.instance_state(caller_instance).handles.remove(set);
+        let state = self.concurrent_state_mut();
+        for (_, waitable) in state.waitables.iter_mut() {
+            if waitable.set == Some(rep) {
+                waitable.set = None;
+            }
+        }
+        state.sets.dealloc(id(rep));
+        Ok(())
     }
 
     fn waitable_join(
@@ -488,13 +1667,31 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         set: u32,
         waitable: u32,
     ) -> Result<()> {
-        _ = (instance, caller_instance, set, waitable);
-        todo!();
+        let set = match set {
+            0 => None,
+            set => Some(lookup_set(instance, caller_instance, set)?),
+        };
+        let waitables = match lookup(instance, caller_instance, waitable)? {
+            WaitableState::Task { rep } => self
+                .concurrent_state_mut()
+                .task_waitable(rep)?
+                .into_iter()
+                .collect(),
+            WaitableState::Stream { rep, end, .. } | WaitableState::Future { rep, end, .. } => {
+                futures_and_streams::waitables(self.concurrent_state_mut(), rep, end)
+            }
+            WaitableState::Set { .. } => bail!("handle {waitable} is not a waitable"),
+        };
+        let state = self.concurrent_state_mut();
+        for waitable in waitables {
+            state.join(waitable, set);
+        }
+        Ok(())
     }
 
     fn yield_(&mut self, instance: &mut ComponentInstance, async_: bool) -> Result<()> {
         _ = (instance, async_);
-        todo!()
+        poll_once(StoreContextMut(self))
     }
 
     fn subtask_drop(
@@ -503,8 +1700,28 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         caller_instance: RuntimeComponentInstanceIndex,
         task_id: u32,
     ) -> Result<()> {
-        _ = (instance, caller_instance, task_id);
-        todo!()
+        let rep = match lookup(instance, caller_instance, task_id)? {
+            WaitableState::Task { rep } => rep,
+            _ => bail!("handle {task_id} is not a subtask"),
+        };
+        let state = self.concurrent_state_mut();
+        let (done, waitable) = match state.tasks.get(id(rep)) {
+            Some(Task::Guest(task)) => (task.done, task.waitable),
+            Some(Task::Host(task)) => (task.done, Some(task.waitable)),
+            None => bail!("unknown subtask"),
+        };
+        if !done {
+            bail!("cannot drop a subtask which has not yet resolved");
+        }
+        state.tasks.dealloc(id(rep));
+        if let Some(waitable) = waitable {
+            state.waitables.dealloc(id(waitable));
+        }
+        instance
+            .instance_state(caller_instance)
+            .handles
+            .remove(task_id);
+        Ok(())
     }
 
     fn sync_enter(
@@ -517,16 +1734,18 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         storage: *mut ValRaw,
         storage_len: usize,
     ) -> Result<()> {
-        _ = (
-            start,
-            return_,
-            caller_instance,
+        _ = caller_instance;
+        let storage = unsafe { core::slice::from_raw_parts(storage, storage_len) }.to_vec();
+        self.concurrent_state_mut().pending_call = Some(PendingCall {
+            start: func_ptr(start)?,
+            return_: func_ptr(return_)?,
             task_return_type,
-            result_count,
-            storage,
-            storage_len,
-        );
-        todo!()
+            lower: PendingLower::Sync {
+                storage,
+                result_count,
+            },
+        });
+        Ok(())
     }
 
     fn sync_exit(
@@ -540,17 +1759,58 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         storage: *mut MaybeUninit<ValRaw>,
         storage_len: usize,
     ) -> Result<()> {
-        _ = (
+        _ = param_count;
+        let instance: *mut ComponentInstance = instance;
+        let mut store = StoreContextMut(self);
+        let state = store.0.concurrent_state_mut();
+        let Some(PendingCall {
+            start,
+            return_,
+            task_return_type,
+            lower:
+                PendingLower::Sync {
+                    storage: mut params,
+                    result_count,
+                },
+        }) = state.pending_call.take()
+        else {
+            bail!("`sync_exit` called without a matching `sync_enter`");
+        };
+
+        // Results which don't fit in `MAX_FLAT_RESULTS` are stored via the
+        // return pointer passed as the caller's last parameter.
+        let result_count = usize::try_from(result_count)?;
+        let (retptr, result_count) = if result_count > MAX_FLAT_RESULTS {
+            (params.pop(), 0)
+        } else {
+            (None, result_count)
+        };
+        let lift = if callback.is_null() {
+            LiftKind::Stackful
+        } else {
+            LiftKind::Callback
+        };
+        let rep = state.new_guest_task(
             instance,
-            callback,
-            caller_instance,
-            callee,
             callee_instance,
-            param_count,
-            storage,
-            storage_len,
+            callback,
+            Caller::Guest {
+                return_,
+                retptr,
+                result_count,
+                results: None,
+            },
+            Some(task_return_type),
+            guest_start::<T>(start, params, func_ptr(callee)?, lift),
         );
-        todo!()
+        start_task(store.as_context_mut(), rep)?;
+
+        let results = wait_for_task(store, instance, caller_instance, rep)?;
+        let storage = unsafe { core::slice::from_raw_parts_mut(storage, storage_len) };
+        for (dst, src) in storage.iter_mut().zip(results.iter().flatten()) {
+            dst.write(*src);
+        }
+        Ok(())
     }
 
     fn async_enter(
@@ -562,15 +1822,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         params: u32,
         results: u32,
     ) -> Result<()> {
-        _ = (
-            start,
-            return_,
-            caller_instance,
+        _ = caller_instance;
+        self.concurrent_state_mut().pending_call = Some(PendingCall {
+            start: func_ptr(start)?,
+            return_: func_ptr(return_)?,
             task_return_type,
-            params,
-            results,
-        );
-        todo!()
+            lower: PendingLower::Async { params, results },
+        });
+        Ok(())
     }
 
     fn async_exit(
@@ -585,18 +1844,69 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         result_count: u32,
         flags: u32,
     ) -> Result<u32> {
-        _ = (
+        _ = param_count;
+        let instance: *mut ComponentInstance = instance;
+        let mut store = StoreContextMut(self);
+        let state = store.0.concurrent_state_mut();
+        let Some(PendingCall {
+            start,
+            return_,
+            task_return_type,
+            lower: PendingLower::Async { params, results },
+        }) = state.pending_call.take()
+        else {
+            bail!("`async_exit` called without a matching `async_enter`");
+        };
+
+        let async_callee = flags & EXIT_FLAG_ASYNC_CALLEE != 0;
+        let lift = if !async_callee {
+            LiftKind::Sync {
+                result_count: usize::try_from(result_count)?,
+                post_return: NonNull::new(post_return).map(SendSyncPtr::new),
+            }
+        } else if callback.is_null() {
+            LiftKind::Stackful
+        } else {
+            LiftKind::Callback
+        };
+        let rep = state.new_guest_task(
             instance,
-            callback,
-            post_return,
-            caller_instance,
-            callee,
             callee_instance,
-            param_count,
-            result_count,
-            flags,
+            callback,
+            Caller::Guest {
+                return_,
+                retptr: Some(ValRaw::u32(results)),
+                result_count: 0,
+                results: None,
+            },
+            async_callee.then_some(task_return_type),
+            guest_start::<T>(start, vec![ValRaw::u32(params)], func_ptr(callee)?, lift),
         );
-        todo!()
+        start_task(store.as_context_mut(), rep)?;
+
+        let state = store.0.concurrent_state_mut();
+        let task = state.guest_task(rep)?;
+        if task.done {
+            state.tasks.dealloc(id(rep));
+            return Ok(STATUS_DONE << 30);
+        }
+        let status = if task.start.is_some() {
+            STATUS_STARTING
+        } else if task.returned {
+            STATUS_RETURNED
+        } else {
+            STATUS_STARTED
+        };
+        let handle = unsafe {
+            (*instance)
+                .instance_state(caller_instance)
+                .handles
+                .insert(WaitableState::Task { rep })?
+        };
+        let owner = state.current_task;
+        let waitable = state.new_waitable(handle, owner);
+        state.guest_task(rep)?.waitable = Some(waitable);
+        Ok((status << 30) | handle)
     }
 
     fn future_new(
@@ -604,8 +1914,7 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         instance: &mut ComponentInstance,
         ty: TypeFutureTableIndex,
     ) -> Result<u32> {
-        _ = (instance, ty);
-        todo!()
+        futures_and_streams::guest_new(self.concurrent_state_mut(), instance, ty.into())
     }
 
     fn future_write(
@@ -618,16 +1927,18 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         future: u32,
         address: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_write(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
             string_encoding,
-            ty,
+            ty.into(),
+            None,
             future,
             address,
-        );
-        todo!()
+            1,
+        )
     }
 
     fn future_read(
@@ -641,17 +1952,19 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         future: u32,
         address: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_read(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
             string_encoding,
-            ty,
+            ty.into(),
             err_ctx_ty,
+            None,
             future,
             address,
-        );
-        todo!()
+            1,
+        )
     }
 
     fn future_cancel_write(
@@ -661,8 +1974,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         async_: bool,
         writer: u32,
     ) -> Result<u32> {
-        _ = (instance, ty, async_, writer);
-        todo!()
+        _ = async_;
+        futures_and_streams::guest_cancel(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            writer,
+            true,
+        )
     }
 
     fn future_cancel_read(
@@ -672,8 +1991,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         async_: bool,
         reader: u32,
     ) -> Result<u32> {
-        _ = (instance, ty, async_, reader);
-        todo!()
+        _ = async_;
+        futures_and_streams::guest_cancel(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            reader,
+            false,
+        )
     }
 
     fn future_close_writable(
@@ -684,8 +2009,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         writer: u32,
         error: u32,
     ) -> Result<()> {
-        _ = (instance, ty, err_ctx_ty, writer, error);
-        todo!()
+        futures_and_streams::guest_close_writable(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            err_ctx_ty,
+            writer,
+            error,
+        )
     }
 
     fn future_close_readable(
@@ -695,8 +2026,13 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         reader: u32,
         error: u32,
     ) -> Result<()> {
-        _ = (instance, ty, reader, error);
-        todo!()
+        _ = error;
+        futures_and_streams::guest_close_readable(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            reader,
+        )
     }
 
     fn stream_new(
@@ -704,8 +2040,7 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         instance: &mut ComponentInstance,
         ty: TypeStreamTableIndex,
     ) -> Result<u32> {
-        _ = (instance, ty);
-        todo!()
+        futures_and_streams::guest_new(self.concurrent_state_mut(), instance, ty.into())
     }
 
     fn stream_write(
@@ -719,17 +2054,18 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         address: u32,
         count: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_write(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
             string_encoding,
-            ty,
+            ty.into(),
+            None,
             stream,
             address,
             count,
-        );
-        todo!()
+        )
     }
 
     fn stream_read(
@@ -744,18 +2080,19 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         address: u32,
         count: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_read(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
             string_encoding,
-            ty,
+            ty.into(),
             err_ctx_ty,
+            None,
             stream,
             address,
             count,
-        );
-        todo!()
+        )
     }
 
     fn stream_cancel_write(
@@ -765,8 +2102,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         async_: bool,
         writer: u32,
     ) -> Result<u32> {
-        _ = (instance, ty, async_, writer);
-        todo!()
+        _ = async_;
+        futures_and_streams::guest_cancel(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            writer,
+            true,
+        )
     }
 
     fn stream_cancel_read(
@@ -776,8 +2119,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         async_: bool,
         reader: u32,
     ) -> Result<u32> {
-        _ = (instance, ty, async_, reader);
-        todo!()
+        _ = async_;
+        futures_and_streams::guest_cancel(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            reader,
+            false,
+        )
     }
 
     fn stream_close_writable(
@@ -788,8 +2137,14 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         writer: u32,
         error: u32,
     ) -> Result<()> {
-        _ = (instance, ty, err_ctx_ty, writer, error);
-        todo!()
+        futures_and_streams::guest_close_writable(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            err_ctx_ty,
+            writer,
+            error,
+        )
     }
 
     fn stream_close_readable(
@@ -799,8 +2154,13 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         reader: u32,
         error: u32,
     ) -> Result<()> {
-        _ = (instance, ty, reader, error);
-        todo!()
+        _ = error;
+        futures_and_streams::guest_close_readable(
+            self.concurrent_state_mut(),
+            instance,
+            ty.into(),
+            reader,
+        )
     }
 
     fn flat_stream_write(
@@ -815,18 +2175,18 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         address: u32,
         count: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_write(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
-            ty,
-            payload_size,
-            payload_align,
+            StringEncoding::Utf8 as u8,
+            ty.into(),
+            Some((payload_size, payload_align)),
             stream,
             address,
             count,
-        );
-        todo!()
+        )
     }
 
     fn flat_stream_read(
@@ -842,19 +2202,19 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         address: u32,
         count: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::guest_read(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
-            ty,
+            StringEncoding::Utf8 as u8,
+            ty.into(),
             err_ctx_ty,
-            payload_size,
-            payload_align,
+            Some((payload_size, payload_align)),
             stream,
             address,
             count,
-        );
-        todo!()
+        )
     }
 
     fn error_context_new(
@@ -867,7 +2227,8 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         debug_msg_address: u32,
         debug_msg_len: u32,
     ) -> Result<u32> {
-        _ = (
+        futures_and_streams::error_context_new(
+            self,
             instance,
             memory,
             realloc,
@@ -875,8 +2236,7 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
             ty,
             debug_msg_address,
             debug_msg_len,
-        );
-        todo!()
+        )
     }
 
     fn error_context_debug_message(
@@ -889,7 +2249,8 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         err_ctx_handle: u32,
         debug_msg_address: u32,
     ) -> Result<()> {
-        _ = (
+        futures_and_streams::error_context_debug_message(
+            StoreContextMut(self),
             instance,
             memory,
             realloc,
@@ -897,8 +2258,7 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
             ty,
             err_ctx_handle,
             debug_msg_address,
-        );
-        todo!()
+        )
     }
 
     fn error_context_drop(
@@ -907,7 +2267,62 @@ unsafe impl<T> VMComponentAsyncStore for StoreInner<T> {
         ty: TypeComponentLocalErrorContextTableIndex,
         err_ctx_handle: u32,
     ) -> Result<()> {
-        _ = (instance, ty, err_ctx_handle);
-        todo!()
+        let rep = instance
+            .error_context_table(ty)
+            .remove(err_ctx_handle)
+            .ok_or_else(|| anyhow!("unknown error-context handle: {err_ctx_handle}"))?
+            .rep;
+        futures_and_streams::error_context_release(self.concurrent_state_mut(), rep);
+        Ok(())
+    }
+
+    fn future_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeFutureTableIndex,
+        dst: TypeFutureTableIndex,
+    ) -> Result<u32> {
+        futures_and_streams::transfer(
+            self.concurrent_state_mut(),
+            instance,
+            src_idx,
+            src.into(),
+            dst.into(),
+        )
+    }
+
+    fn stream_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeStreamTableIndex,
+        dst: TypeStreamTableIndex,
+    ) -> Result<u32> {
+        futures_and_streams::transfer(
+            self.concurrent_state_mut(),
+            instance,
+            src_idx,
+            src.into(),
+            dst.into(),
+        )
+    }
+
+    fn error_context_transfer(
+        &mut self,
+        instance: &mut ComponentInstance,
+        src_idx: u32,
+        src: TypeComponentLocalErrorContextTableIndex,
+        dst: TypeComponentLocalErrorContextTableIndex,
+    ) -> Result<u32> {
+        let rep = instance
+            .error_context_table(src)
+            .get(src_idx)
+            .ok_or_else(|| anyhow!("unknown error-context handle: {src_idx}"))?
+            .rep;
+        futures_and_streams::error_context_retain(self.concurrent_state_mut(), rep);
+        instance
+            .error_context_table(dst)
+            .insert(ErrorContextState { rep })
     }
 }
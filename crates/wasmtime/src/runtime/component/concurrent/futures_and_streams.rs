@@ -1,14 +1,2011 @@
-use std::marker::PhantomData;
+//! Streams, futures, and `error-context`s.
+//!
+//! Both ends of a stream or future share a single `TransmitState`. Each end
+//! is either owned by a guest, in which case it has a handle in the waitable
+//! table of some instance, or by the host via a `StreamReader`,
+//! `StreamWriter`, `FutureReader`, or `FutureWriter`.
+//!
+//! Reads and writes complete as soon as the other end has an operation
+//! pending. Otherwise guest operations return `BLOCKED`, and the guest later
+//! receives an event once the other end arrives. Host operations return a
+//! [`Promise`] instead.
+//!
+//! [`HostStream`] and [`HostFuture`] build on the host operations to expose
+//! streams and futures as `futures`-style types, which is how `bindgen!`
+//! represents them. Values are moved between those and the underlying
+//! `TransmitState` by pumps running on the store's event loop.
+
+use {
+    super::{
+        lookup, store_context, ConcurrentState, Promise, Thunk, EVENT_FUTURE_READ,
+        EVENT_FUTURE_WRITE, EVENT_STREAM_READ, EVENT_STREAM_WRITE,
+    },
+    crate::{
+        component::{
+            __internal::bad_type_info,
+            func::{desc, LiftContext, LowerContext, Options},
+            matching::InstanceType,
+            values::Val,
+            ComponentType, Lift, Lower,
+        },
+        prelude::*,
+        runtime::vm::{
+            component::{ComponentInstance, ErrorContextState, TransmitEnd, WaitableState},
+            SendSyncPtr, VMFuncRef, VMMemoryDefinition, VMStore,
+        },
+        store::StoreOpaque,
+        AsContextMut, StoreContextMut, ValRaw,
+    },
+    alloc::sync::Arc,
+    core::{
+        any::Any,
+        fmt,
+        future::Future,
+        marker::PhantomData,
+        mem,
+        mem::MaybeUninit,
+        pin::Pin,
+        ptr::NonNull,
+        task::{Context, Poll},
+    },
+    futures::{
+        channel::{mpsc, oneshot},
+        future as future_util, FutureExt, Sink, SinkExt, Stream, StreamExt,
+    },
+    std::sync::Mutex,
+    wasmtime_environ::component::{
+        CanonicalAbiInfo, ComponentTypes, InterfaceType, RuntimeComponentInstanceIndex,
+        StringEncoding, TypeComponentLocalErrorContextTableIndex, TypeFutureTableIndex,
+        TypeStreamTableIndex,
+    },
+    wasmtime_slab::Id,
+};
+
+/// Result of a read or write which couldn't complete immediately.
+const BLOCKED: u32 = 0xffff_ffff;
+
+/// Result of a read or write whose other end has been closed, optionally ORed
+/// with an `error-context` handle.
+const CLOSED: u32 = 0x8000_0000;
+
+/// Result of successfully canceling a read or write.
+const CANCELED: u32 = 0;
+
+/// The type of a stream or future within a component.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(super) enum TableIndex {
+    Future(TypeFutureTableIndex),
+    Stream(TypeStreamTableIndex),
+}
+
+impl From<TypeFutureTableIndex> for TableIndex {
+    fn from(ty: TypeFutureTableIndex) -> Self {
+        TableIndex::Future(ty)
+    }
+}
+
+impl From<TypeStreamTableIndex> for TableIndex {
+    fn from(ty: TypeStreamTableIndex) -> Self {
+        TableIndex::Stream(ty)
+    }
+}
+
+impl TableIndex {
+    fn kind(&self) -> &'static str {
+        match self {
+            TableIndex::Future(_) => "future",
+            TableIndex::Stream(_) => "stream",
+        }
+    }
+
+    fn is_future(&self) -> bool {
+        matches!(self, TableIndex::Future(_))
+    }
+
+    /// The (sub)component instance whose table holds handles of this type.
+    fn instance(&self, types: &ComponentTypes) -> RuntimeComponentInstanceIndex {
+        match self {
+            TableIndex::Future(ty) => types[*ty].instance,
+            TableIndex::Stream(ty) => types[*ty].instance,
+        }
+    }
+
+    fn payload(&self, types: &ComponentTypes) -> Option<InterfaceType> {
+        match self {
+            TableIndex::Future(ty) => types[types[*ty].ty].payload,
+            TableIndex::Stream(ty) => types[types[*ty].ty].payload,
+        }
+    }
+
+    fn handle_state(&self, rep: u32, end: TransmitEnd) -> WaitableState {
+        match *self {
+            TableIndex::Future(ty) => WaitableState::Future { ty, rep, end },
+            TableIndex::Stream(ty) => WaitableState::Stream { ty, rep, end },
+        }
+    }
+
+    fn read_event(&self) -> u32 {
+        if self.is_future() {
+            EVENT_FUTURE_READ
+        } else {
+            EVENT_STREAM_READ
+        }
+    }
+
+    fn write_event(&self) -> u32 {
+        if self.is_future() {
+            EVENT_FUTURE_WRITE
+        } else {
+            EVENT_STREAM_WRITE
+        }
+    }
+}
+
+/// State shared by both ends of a stream or future.
+pub(super) struct TransmitState {
+    future: bool,
+    read: ReadState,
+    write: WriteState,
+    /// Waitable of the readable end.
+    read_waitable: u32,
+    /// Waitable of the writable end.
+    write_waitable: u32,
+    /// For futures, whether the value has been delivered.
+    done: bool,
+}
+
+enum ReadState {
+    Open,
+    GuestReady(GuestBuffer),
+    HostReady(HostRead),
+    Closed,
+}
+
+enum WriteState {
+    Open,
+    GuestReady(GuestBuffer),
+    HostReady(HostWrite),
+    /// Closed, optionally with the `rep` of an `error-context`.
+    Closed(Option<u32>),
+}
+
+/// Linear memory provided by a guest to read into or write from.
+struct GuestBuffer {
+    instance: SendSyncPtr<ComponentInstance>,
+    types: Arc<ComponentTypes>,
+    options: Options,
+    payload: Option<InterfaceType>,
+    /// Whether the payload may be copied with `memcpy`.
+    flat: bool,
+    size: usize,
+    address: usize,
+    count: usize,
+    waitable: u32,
+    /// Table into which `error-context`s are lowered, for reads.
+    err_ctx_ty: Option<TypeComponentLocalErrorContextTableIndex>,
+}
+
+/// Lowers the first `n` values of a `Vec<U>` into a guest buffer.
+type LowerFn =
+    unsafe fn(&mut dyn VMStore, &mut (dyn Any + Send + Sync), &GuestBuffer, usize) -> Result<()>;
+
+/// Lifts `n` values out of a guest buffer into a `Vec<U>`.
+type LiftFn =
+    unsafe fn(&mut StoreOpaque, &GuestBuffer, usize) -> Result<Box<dyn Any + Send + Sync>>;
+
+struct HostWrite {
+    /// A `Vec<U>` of values yet to be written.
+    values: Box<dyn Any + Send + Sync>,
+    remaining: usize,
+    lower: LowerFn,
+    /// Receives whether all values were written before the reader closed.
+    tx: oneshot::Sender<bool>,
+    /// Whether the writable end closes once this write completes.
+    once: bool,
+}
+
+struct HostRead {
+    lift: LiftFn,
+    /// Receives a `Vec<U>`, or `None` if the writer closed.
+    tx: oneshot::Sender<Option<Box<dyn Any + Send + Sync>>>,
+    /// Whether the readable end closes once this read completes.
+    once: bool,
+}
+
+fn transmit(state: &mut ConcurrentState, rep: u32) -> Result<&mut TransmitState> {
+    state
+        .transmits
+        .get_mut(Id::from_raw(rep))
+        .ok_or_else(|| anyhow!("unknown stream or future"))
+}
+
+fn new_transmit(state: &mut ConcurrentState, future: bool, handle: u32) -> u32 {
+    let rep = state.transmits.alloc(TransmitState {
+        future,
+        read: ReadState::Open,
+        write: WriteState::Open,
+        read_waitable: 0,
+        write_waitable: 0,
+        done: false,
+    });
+    let read_waitable = state.new_waitable(handle, None);
+    let write_waitable = state.new_waitable(handle, None);
+    let t = state.transmits.get_mut(rep).unwrap();
+    t.read_waitable = read_waitable;
+    t.write_waitable = write_waitable;
+    rep.into_raw()
+}
+
+/// Deallocates `rep` once both of its ends are closed.
+fn maybe_delete(state: &mut ConcurrentState, rep: u32) {
+    let Some(t) = state.transmits.get(Id::from_raw(rep)) else {
+        return;
+    };
+    let WriteState::Closed(err) = t.write else {
+        return;
+    };
+    if !matches!(t.read, ReadState::Closed) {
+        return;
+    }
+    let (read_waitable, write_waitable) = (t.read_waitable, t.write_waitable);
+    state.transmits.dealloc(Id::from_raw(rep));
+    state.waitables.dealloc(Id::from_raw(read_waitable));
+    state.waitables.dealloc(Id::from_raw(write_waitable));
+    if let Some(err) = err {
+        error_context_release(state, err);
+    }
+}
+
+/// Returns the waitables which a handle to `end` of `rep` refers to.
+pub(super) fn waitables(state: &mut ConcurrentState, rep: u32, end: TransmitEnd) -> Vec<u32> {
+    let Ok(t) = transmit(state, rep) else {
+        return Vec::new();
+    };
+    match end {
+        TransmitEnd::Local => vec![t.read_waitable, t.write_waitable],
+        TransmitEnd::Read => vec![t.read_waitable],
+        TransmitEnd::Write => vec![t.write_waitable],
+    }
+}
+
+/// Looks up `handle` as a stream or future of type `ty`.
+fn lookup_transmit(
+    instance: *mut ComponentInstance,
+    ty: TableIndex,
+    handle: u32,
+) -> Result<(u32, TransmitEnd)> {
+    let runtime_instance = ty.instance(unsafe { (*instance).component_types() });
+    match lookup(instance, runtime_instance, handle)? {
+        WaitableState::Future { ty: t, rep, end } if ty == TableIndex::Future(t) => Ok((rep, end)),
+        WaitableState::Stream { ty: t, rep, end } if ty == TableIndex::Stream(t) => Ok((rep, end)),
+        _ => bail!(
+            "handle {handle} is not a {} of the expected type",
+            ty.kind()
+        ),
+    }
+}
+
+/// Removes the readable end from `handle`, leaving the writable end (if any)
+/// behind, and returns the `rep` of its stream or future.
+fn take_readable(instance: *mut ComponentInstance, ty: TableIndex, handle: u32) -> Result<u32> {
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    let runtime_instance = ty.instance(unsafe { (*instance).component_types() });
+    let handles = unsafe { &mut (*instance).instance_state(runtime_instance).handles };
+    match end {
+        TransmitEnd::Local => {
+            *handles.get_mut(handle).unwrap() = ty.handle_state(rep, TransmitEnd::Write)
+        }
+        TransmitEnd::Read => {
+            handles.remove(handle);
+        }
+        TransmitEnd::Write => bail!("cannot transfer the writable end of a {}", ty.kind()),
+    }
+    Ok(rep)
+}
+
+/// Same as `take_readable`, but for the writable end.
+fn take_writable(instance: *mut ComponentInstance, ty: TableIndex, handle: u32) -> Result<u32> {
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    let runtime_instance = ty.instance(unsafe { (*instance).component_types() });
+    let handles = unsafe { &mut (*instance).instance_state(runtime_instance).handles };
+    match end {
+        TransmitEnd::Local => {
+            *handles.get_mut(handle).unwrap() = ty.handle_state(rep, TransmitEnd::Read)
+        }
+        TransmitEnd::Write => {
+            handles.remove(handle);
+        }
+        TransmitEnd::Read => bail!("handle {handle} is not the writable end of a {}", ty.kind()),
+    }
+    Ok(rep)
+}
+
+/// Gives the readable end of `rep` a new handle of type `ty` in `instance`.
+fn give_readable(
+    state: &mut ConcurrentState,
+    instance: *mut ComponentInstance,
+    ty: TableIndex,
+    rep: u32,
+) -> Result<u32> {
+    let runtime_instance = ty.instance(unsafe { (*instance).component_types() });
+    let handle = unsafe {
+        (*instance)
+            .instance_state(runtime_instance)
+            .handles
+            .insert(ty.handle_state(rep, TransmitEnd::Read))?
+    };
+    let waitable = transmit(state, rep)?.read_waitable;
+    state.rehome(waitable, handle, None);
+    Ok(handle)
+}
+
+/// Moves the readable end at `src_idx` of type `src` to a new handle of type
+/// `dst`.
+pub(super) fn transfer(
+    state: &mut ConcurrentState,
+    instance: &mut ComponentInstance,
+    src_idx: u32,
+    src: TableIndex,
+    dst: TableIndex,
+) -> Result<u32> {
+    let instance: *mut ComponentInstance = instance;
+    let rep = take_readable(instance, src, src_idx)?;
+    give_readable(state, instance, dst, rep)
+}
+
+/// The `stream.new` and `future.new` intrinsics.
+pub(super) fn guest_new(
+    state: &mut ConcurrentState,
+    instance: &mut ComponentInstance,
+    ty: TableIndex,
+) -> Result<u32> {
+    let runtime_instance = ty.instance(instance.component_types());
+    let handles = &mut instance.instance_state(runtime_instance).handles;
+    // Reserve the handle first so the waitables can refer to it.
+    let handle = handles.insert(ty.handle_state(0, TransmitEnd::Local))?;
+    let rep = new_transmit(state, ty.is_future(), handle);
+    *handles.get_mut(handle).unwrap() = ty.handle_state(rep, TransmitEnd::Local);
+    Ok(handle)
+}
+
+impl GuestBuffer {
+    fn new(
+        store: &mut StoreOpaque,
+        instance: *mut ComponentInstance,
+        memory: *mut VMMemoryDefinition,
+        realloc: *mut VMFuncRef,
+        string_encoding: u8,
+        ty: TableIndex,
+        flat: Option<(u32, u32)>,
+        address: u32,
+        count: u32,
+        waitable: u32,
+        err_ctx_ty: Option<TypeComponentLocalErrorContextTableIndex>,
+    ) -> Result<GuestBuffer> {
+        let types = unsafe { (*instance).component_types().clone() };
+        let payload = ty.payload(&types);
+        let (size, align) = match (flat, payload) {
+            (Some((size, align)), _) => (size, align),
+            (None, Some(payload)) => {
+                let abi = types.canonical_abi(&payload);
+                (abi.size32, abi.align32)
+            }
+            (None, None) => (0, 1),
+        };
+        let options = unsafe {
+            Options::new(
+                store.id(),
+                NonNull::new(memory),
+                NonNull::new(realloc),
+                StringEncoding::from_u8(string_encoding).unwrap(),
+            )
+        };
+        let size = usize::try_from(size)?;
+        let address = usize::try_from(address)?;
+        let count = usize::try_from(count)?;
+        if size > 0 {
+            if address % usize::try_from(align)? != 0 {
+                bail!("pointer not aligned");
+            }
+            let end = size
+                .checked_mul(count)
+                .and_then(|len| len.checked_add(address))
+                .ok_or_else(|| anyhow!("buffer size overflow"))?;
+            if end > options.memory(store).len() {
+                bail!("buffer out of bounds of memory");
+            }
+        }
+        Ok(GuestBuffer {
+            instance: SendSyncPtr::new(NonNull::new(instance).unwrap()),
+            types,
+            options,
+            payload,
+            flat: flat.is_some(),
+            size,
+            address,
+            count,
+            waitable,
+            err_ctx_ty,
+        })
+    }
+}
+
+/// Copies `n` values from the guest buffer `src` to the guest buffer `dst`.
+fn copy<T>(
+    mut store: StoreContextMut<'_, T>,
+    src: &GuestBuffer,
+    dst: &GuestBuffer,
+    n: usize,
+) -> Result<()> {
+    let (Some(src_ty), Some(dst_ty)) = (src.payload, dst.payload) else {
+        return Ok(());
+    };
+    if n == 0 {
+        return Ok(());
+    }
+    if src.flat && dst.flat {
+        let len = n * src.size;
+        let bytes = src.options.memory(store.0)[src.address..][..len].to_vec();
+        dst.options.memory_mut(store.0)[dst.address..][..len].copy_from_slice(&bytes);
+        return Ok(());
+    }
+
+    let values = unsafe {
+        let mut cx = LiftContext::new(store.0, &src.options, &src.types, src.instance.as_ptr());
+        let memory = cx.memory();
+        (0..n)
+            .map(|i| {
+                Val::load(
+                    &mut cx,
+                    src_ty,
+                    &memory[src.address + i * src.size..][..src.size],
+                )
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    let mut cx = unsafe {
+        LowerContext::new(
+            store.as_context_mut(),
+            &dst.options,
+            &dst.types,
+            dst.instance.as_ptr(),
+        )
+    };
+    for (i, value) in values.iter().enumerate() {
+        value.store(&mut cx, dst_ty, dst.address + i * dst.size)?;
+    }
+    Ok(())
+}
+
+unsafe fn lower_values<T, U>(
+    store: &mut dyn VMStore,
+    values: &mut (dyn Any + Send + Sync),
+    buffer: &GuestBuffer,
+    n: usize,
+) -> Result<()>
+where
+    U: Lower + Send + Sync + 'static,
+{
+    let values = values.downcast_mut::<Vec<U>>().unwrap();
+    let values = values.drain(..n);
+    let Some(ty) = buffer.payload else {
+        return Ok(());
+    };
+    let mut cx = LowerContext::new(
+        store_context::<T>(store),
+        &buffer.options,
+        &buffer.types,
+        buffer.instance.as_ptr(),
+    );
+    for (i, value) in values.enumerate() {
+        value.store(&mut cx, ty, buffer.address + i * buffer.size)?;
+    }
+    Ok(())
+}
+
+unsafe fn lift_values<U>(
+    store: &mut StoreOpaque,
+    buffer: &GuestBuffer,
+    n: usize,
+) -> Result<Box<dyn Any + Send + Sync>>
+where
+    U: Lift + Send + Sync + 'static,
+{
+    let values = match buffer.payload {
+        Some(ty) => {
+            let mut cx = LiftContext::new(
+                store,
+                &buffer.options,
+                &buffer.types,
+                buffer.instance.as_ptr(),
+            );
+            let memory = cx.memory();
+            (0..n)
+                .map(|i| {
+                    U::load(
+                        &mut cx,
+                        ty,
+                        &memory[buffer.address + i * buffer.size..][..buffer.size],
+                    )
+                })
+                .collect::<Result<Vec<U>>>()?
+        }
+        None => (0..n).map(|_| unit::<U>()).collect(),
+    };
+    Ok(Box::new(values))
+}
+
+/// Creates the value of a payload-less stream or future.
+fn unit<U: ComponentType>() -> U {
+    // Type-checking guarantees that `U` is `()` for payload-less types.
+    assert!(U::IS_RUST_UNIT_TYPE && mem::size_of::<U>() == 0);
+    unsafe { mem::zeroed() }
+}
+
+/// Completes a host write by closing the writable end if needed.
+fn finish_host_write(state: &mut ConcurrentState, rep: u32, write: HostWrite, ok: bool) {
+    if write.once {
+        if let Ok(t) = transmit(state, rep) {
+            t.write = WriteState::Closed(None);
+        }
+    }
+    _ = write.tx.send(ok);
+}
+
+/// Completes a host read by closing the readable end if needed.
+fn finish_host_read(
+    state: &mut ConcurrentState,
+    rep: u32,
+    read: HostRead,
+    values: Option<Box<dyn Any + Send + Sync>>,
+) {
+    if read.once {
+        if let Ok(t) = transmit(state, rep) {
+            t.read = ReadState::Closed;
+        }
+    }
+    _ = read.tx.send(values);
+}
+
+/// The result of reading from a closed writable end, lowering the
+/// `error-context` it was closed with (if any) into the reader's table.
+fn closed(state: &mut ConcurrentState, reader: &GuestBuffer, err: Option<u32>) -> Result<u32> {
+    let (Some(err), Some(ty)) = (err, reader.err_ctx_ty) else {
+        return Ok(CLOSED);
+    };
+    error_context_retain(state, err);
+    let handle = unsafe {
+        (*reader.instance.as_ptr())
+            .error_context_table(ty)
+            .insert(ErrorContextState { rep: err })?
+    };
+    Ok(CLOSED | handle)
+}
+
+/// The `stream.write` and `future.write` intrinsics.
+pub(super) fn guest_write<T>(
+    mut store: StoreContextMut<'_, T>,
+    instance: &mut ComponentInstance,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: u8,
+    ty: TableIndex,
+    flat: Option<(u32, u32)>,
+    handle: u32,
+    address: u32,
+    count: u32,
+) -> Result<u32> {
+    let instance: *mut ComponentInstance = instance;
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    if !end.writable() {
+        bail!("handle {handle} is not the writable end of a {}", ty.kind());
+    }
+    let waitable = transmit(store.0.concurrent_state_mut(), rep)?.write_waitable;
+    let buffer = GuestBuffer::new(
+        store.0,
+        instance,
+        memory,
+        realloc,
+        string_encoding,
+        ty,
+        flat,
+        address,
+        count,
+        waitable,
+        None,
+    )?;
+
+    let state = store.0.concurrent_state_mut();
+    let t = transmit(state, rep)?;
+    if !matches!(t.write, WriteState::Open) {
+        bail!(
+            "cannot write to {} while another write is pending",
+            ty.kind()
+        );
+    }
+    if t.future && t.done {
+        bail!("cannot write to future after previous write succeeded");
+    }
+    match mem::replace(&mut t.read, ReadState::Open) {
+        ReadState::Open => {
+            t.write = WriteState::GuestReady(buffer);
+            let owner = state.current_task;
+            state.waitable(waitable).owner = owner;
+            Ok(BLOCKED)
+        }
+        ReadState::Closed => {
+            t.read = ReadState::Closed;
+            Ok(CLOSED)
+        }
+        ReadState::GuestReady(read) => {
+            let n = buffer.count.min(read.count);
+            copy(store.as_context_mut(), &buffer, &read, n)?;
+            let state = store.0.concurrent_state_mut();
+            transmit(state, rep)?.done = true;
+            state.push_event(read.waitable, ty.read_event(), u32::try_from(n)?);
+            Ok(u32::try_from(n)?)
+        }
+        ReadState::HostReady(read) => {
+            let n = buffer.count;
+            let values = unsafe { (read.lift)(store.0, &buffer, n)? };
+            let state = store.0.concurrent_state_mut();
+            transmit(state, rep)?.done = true;
+            finish_host_read(state, rep, read, Some(values));
+            Ok(u32::try_from(n)?)
+        }
+    }
+}
+
+/// The `stream.read` and `future.read` intrinsics.
+pub(super) fn guest_read<T>(
+    mut store: StoreContextMut<'_, T>,
+    instance: &mut ComponentInstance,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: u8,
+    ty: TableIndex,
+    err_ctx_ty: TypeComponentLocalErrorContextTableIndex,
+    flat: Option<(u32, u32)>,
+    handle: u32,
+    address: u32,
+    count: u32,
+) -> Result<u32> {
+    let instance: *mut ComponentInstance = instance;
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    if !end.readable() {
+        bail!("handle {handle} is not the readable end of a {}", ty.kind());
+    }
+    let waitable = transmit(store.0.concurrent_state_mut(), rep)?.read_waitable;
+    let buffer = GuestBuffer::new(
+        store.0,
+        instance,
+        memory,
+        realloc,
+        string_encoding,
+        ty,
+        flat,
+        address,
+        count,
+        waitable,
+        Some(err_ctx_ty),
+    )?;
+
+    let state = store.0.concurrent_state_mut();
+    let t = transmit(state, rep)?;
+    if !matches!(t.read, ReadState::Open) {
+        bail!(
+            "cannot read from {} while another read is pending",
+            ty.kind()
+        );
+    }
+    if t.future && t.done {
+        bail!("cannot read from future after previous read succeeded");
+    }
+    match mem::replace(&mut t.write, WriteState::Open) {
+        WriteState::Open => {
+            t.read = ReadState::GuestReady(buffer);
+            let owner = state.current_task;
+            state.waitable(waitable).owner = owner;
+            Ok(BLOCKED)
+        }
+        WriteState::Closed(err) => {
+            t.write = WriteState::Closed(err);
+            closed(state, &buffer, err)
+        }
+        WriteState::GuestReady(write) => {
+            let n = buffer.count.min(write.count);
+            copy(store.as_context_mut(), &write, &buffer, n)?;
+            let state = store.0.concurrent_state_mut();
+            transmit(state, rep)?.done = true;
+            state.push_event(write.waitable, ty.write_event(), u32::try_from(n)?);
+            Ok(u32::try_from(n)?)
+        }
+        WriteState::HostReady(mut write) => {
+            let n = buffer.count.min(write.remaining);
+            unsafe { (write.lower)(store.0, &mut *write.values, &buffer, n)? };
+            write.remaining -= n;
+            let state = store.0.concurrent_state_mut();
+            let t = transmit(state, rep)?;
+            t.done = true;
+            if write.remaining == 0 {
+                finish_host_write(state, rep, write, true);
+            } else {
+                t.write = WriteState::HostReady(write);
+            }
+            Ok(u32::try_from(n)?)
+        }
+    }
+}
+
+/// The `{stream,future}.cancel-{read,write}` intrinsics.
+pub(super) fn guest_cancel(
+    state: &mut ConcurrentState,
+    instance: &mut ComponentInstance,
+    ty: TableIndex,
+    handle: u32,
+    writer: bool,
+) -> Result<u32> {
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    if (writer && !end.writable()) || (!writer && !end.readable()) {
+        bail!("handle {handle} has no pending operation to cancel");
+    }
+    let t = transmit(state, rep)?;
+    let waitable = if writer {
+        if let WriteState::GuestReady(_) = t.write {
+            t.write = WriteState::Open;
+            return Ok(CANCELED);
+        }
+        t.write_waitable
+    } else {
+        if let ReadState::GuestReady(_) = t.read {
+            t.read = ReadState::Open;
+            return Ok(CANCELED);
+        }
+        t.read_waitable
+    };
+    // The operation completed in the meantime, so report that instead.
+    match state.take_event(waitable) {
+        Some((_, payload)) => Ok(payload),
+        None => bail!("handle {handle} has no pending operation to cancel"),
+    }
+}
+
+/// Closes the writable end of `rep`, notifying the reader.
+fn close_writable(
+    state: &mut ConcurrentState,
+    rep: u32,
+    ty: TableIndex,
+    err: Option<u32>,
+) -> Result<()> {
+    let t = transmit(state, rep)?;
+    if let WriteState::HostReady(write) = mem::replace(&mut t.write, WriteState::Closed(err)) {
+        _ = write.tx.send(false);
+    }
+    let write_waitable = t.write_waitable;
+    match mem::replace(&mut t.read, ReadState::Open) {
+        ReadState::Open => {}
+        ReadState::Closed => t.read = ReadState::Closed,
+        ReadState::GuestReady(read) => {
+            let payload = closed(state, &read, err)?;
+            state.push_event(read.waitable, ty.read_event(), payload);
+        }
+        ReadState::HostReady(read) => finish_host_read(state, rep, read, None),
+    }
+    state.rehome(write_waitable, 0, None);
+    maybe_delete(state, rep);
+    Ok(())
+}
+
+/// Closes the readable end of `rep`, notifying the writer.
+fn close_readable(state: &mut ConcurrentState, rep: u32, ty: TableIndex) -> Result<()> {
+    let t = transmit(state, rep)?;
+    if let ReadState::HostReady(read) = mem::replace(&mut t.read, ReadState::Closed) {
+        _ = read.tx.send(None);
+    }
+    let read_waitable = t.read_waitable;
+    match mem::replace(&mut t.write, WriteState::Open) {
+        WriteState::Open => {}
+        WriteState::Closed(err) => t.write = WriteState::Closed(err),
+        WriteState::GuestReady(write) => {
+            state.push_event(write.waitable, ty.write_event(), CLOSED);
+        }
+        WriteState::HostReady(write) => finish_host_write(state, rep, write, false),
+    }
+    state.rehome(read_waitable, 0, None);
+    maybe_delete(state, rep);
+    Ok(())
+}
+
+/// The `stream.close-writable` and `future.close-writable` intrinsics.
+pub(super) fn guest_close_writable(
+    state: &mut ConcurrentState,
+    instance: &mut ComponentInstance,
+    ty: TableIndex,
+    err_ctx_ty: TypeComponentLocalErrorContextTableIndex,
+    handle: u32,
+    error: u32,
+) -> Result<()> {
+    let instance: *mut ComponentInstance = instance;
+    let err = match error {
+        0 => None,
+        error => {
+            let rep = unsafe { (*instance).error_context_table(err_ctx_ty) }
+                .get(error)
+                .ok_or_else(|| anyhow!("unknown error-context handle: {error}"))?
+                .rep;
+            error_context_retain(state, rep);
+            Some(rep)
+        }
+    };
+    let rep = take_writable(instance, ty, handle)?;
+    close_writable(state, rep, ty, err)
+}
+
+/// The `stream.close-readable` and `future.close-readable` intrinsics.
+pub(super) fn guest_close_readable(
+    state: &mut ConcurrentState,
+    instance: &mut ComponentInstance,
+    ty: TableIndex,
+    handle: u32,
+) -> Result<()> {
+    let (rep, end) = lookup_transmit(instance, ty, handle)?;
+    if !end.readable() {
+        bail!("handle {handle} is not the readable end of a {}", ty.kind());
+    }
+    take_readable(instance, ty, handle)?;
+    close_readable(state, rep, ty)
+}
+
+/// Writes `values` from the host to the writable end of `rep`.
+fn host_write<T, U>(
+    store: StoreContextMut<'_, T>,
+    rep: u32,
+    ty: TableIndex,
+    values: Vec<U>,
+) -> Result<Promise<bool>>
+where
+    U: Lower + Send + Sync + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let state = store.0.concurrent_state_mut();
+    let t = transmit(state, rep)?;
+    if !matches!(t.write, WriteState::Open) {
+        bail!(
+            "cannot write to {} while another write is pending",
+            ty.kind()
+        );
+    }
+    let mut write = HostWrite {
+        remaining: values.len(),
+        values: Box::new(values),
+        lower: lower_values::<T, U>,
+        tx,
+        once: t.future,
+    };
+    match mem::replace(&mut t.read, ReadState::Open) {
+        ReadState::Open => t.write = WriteState::HostReady(write),
+        ReadState::Closed => {
+            t.read = ReadState::Closed;
+            finish_host_write(state, rep, write, false);
+        }
+        ReadState::GuestReady(read) => {
+            let n = read.count.min(write.remaining);
+            unsafe { (write.lower)(store.0, &mut *write.values, &read, n)? };
+            write.remaining -= n;
+            let state = store.0.concurrent_state_mut();
+            let t = transmit(state, rep)?;
+            t.done = true;
+            if write.remaining == 0 {
+                finish_host_write(state, rep, write, true);
+            } else {
+                t.write = WriteState::HostReady(write);
+            }
+            state.push_event(read.waitable, ty.read_event(), u32::try_from(n)?);
+        }
+        ReadState::HostReady(read) => {
+            t.done = true;
+            let HostWrite {
+                values, tx, once, ..
+            } = write;
+            finish_host_read(state, rep, read, Some(values));
+            finish_host_write(
+                state,
+                rep,
+                HostWrite {
+                    values: Box::new(()),
+                    remaining: 0,
+                    lower: lower_values::<T, U>,
+                    tx,
+                    once,
+                },
+                true,
+            );
+        }
+    }
+    maybe_delete(store.0.concurrent_state_mut(), rep);
+    Ok(Promise(Box::pin(rx.map(|ok| ok.unwrap_or(false)))))
+}
+
+/// Reads values into the host from the readable end of `rep`.
+fn host_read<U>(
+    store: &mut StoreOpaque,
+    rep: u32,
+    ty: TableIndex,
+) -> Result<Promise<Option<Vec<U>>>>
+where
+    U: Lift + Send + Sync + 'static,
+{
+    let (tx, rx) = oneshot::channel();
+    let state = store.concurrent_state_mut();
+    let t = transmit(state, rep)?;
+    if !matches!(t.read, ReadState::Open) {
+        bail!(
+            "cannot read from {} while another read is pending",
+            ty.kind()
+        );
+    }
+    let read = HostRead {
+        lift: lift_values::<U>,
+        tx,
+        once: t.future,
+    };
+    match mem::replace(&mut t.write, WriteState::Open) {
+        WriteState::Open => t.read = ReadState::HostReady(read),
+        WriteState::Closed(err) => {
+            t.write = WriteState::Closed(err);
+            finish_host_read(state, rep, read, None);
+        }
+        WriteState::GuestReady(write) => {
+            let values = unsafe { (read.lift)(store, &write, write.count)? };
+            let state = store.concurrent_state_mut();
+            transmit(state, rep)?.done = true;
+            state.push_event(
+                write.waitable,
+                ty.write_event(),
+                u32::try_from(write.count)?,
+            );
+            finish_host_read(state, rep, read, Some(values));
+        }
+        WriteState::HostReady(write) => {
+            t.done = true;
+            let HostWrite {
+                values,
+                tx,
+                once,
+                lower,
+                ..
+            } = write;
+            finish_host_write(
+                state,
+                rep,
+                HostWrite {
+                    values: Box::new(()),
+                    remaining: 0,
+                    lower,
+                    tx,
+                    once,
+                },
+                true,
+            );
+            finish_host_read(state, rep, read, Some(values));
+        }
+    }
+    maybe_delete(store.concurrent_state_mut(), rep);
+    Ok(Promise(Box::pin(rx.map(|values| {
+        values
+            .ok()
+            .flatten()
+            .map(|values| *values.downcast::<Vec<U>>().unwrap())
+    }))))
+}
+
+/// Creates a new future, returning its writable and readable ends.
+///
+/// Both ends are initially owned by the host. The readable end may be passed
+/// to a guest in order to receive a value written with
+/// [`FutureWriter::write`].
+pub fn future<T>(mut store: impl AsContextMut) -> Result<(FutureWriter<T>, FutureReader<T>)> {
+    let store = store.as_context_mut();
+    let rep = new_transmit(store.0.concurrent_state_mut(), true, 0);
+    Ok((
+        FutureWriter {
+            rep,
+            _phantom: PhantomData,
+        },
+        FutureReader {
+            rep,
+            _phantom: PhantomData,
+        },
+    ))
+}
+
+/// Creates a new stream, returning its writable and readable ends.
+///
+/// Both ends are initially owned by the host. The readable end may be passed
+/// to a guest in order to receive values written with
+/// [`StreamWriter::write`].
+pub fn stream<T>(mut store: impl AsContextMut) -> Result<(StreamWriter<T>, StreamReader<T>)> {
+    let store = store.as_context_mut();
+    let rep = new_transmit(store.0.concurrent_state_mut(), false, 0);
+    Ok((
+        StreamWriter {
+            rep,
+            _phantom: PhantomData,
+        },
+        StreamReader {
+            rep,
+            _phantom: PhantomData,
+        },
+    ))
+}
+
+/// Placeholder type index used for host-only operations, where only whether
+/// the transmit is a future or a stream matters.
+fn host_index(future: bool) -> TableIndex {
+    if future {
+        TableIndex::Future(TypeFutureTableIndex::from_u32(0))
+    } else {
+        TableIndex::Stream(TypeStreamTableIndex::from_u32(0))
+    }
+}
+
+/// Represents the writable end of a Component Model `future`.
+pub struct FutureWriter<T> {
+    rep: u32,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T> FutureWriter<T> {
+    /// Writes `value` to this future.
+    ///
+    /// The returned [`Promise`] resolves to `true` once the value has been
+    /// received by the reader, or `false` if the reader closed its end first.
+    pub fn write<U>(self, mut store: impl AsContextMut<Data = U>, value: T) -> Result<Promise<bool>>
+    where
+        T: Lower + Send + Sync + 'static,
+    {
+        host_write(
+            store.as_context_mut(),
+            self.rep,
+            host_index(true),
+            vec![value],
+        )
+    }
+
+    /// Closes this end of the future without writing a value.
+    pub fn close(self, mut store: impl AsContextMut) -> Result<()> {
+        close_writable(
+            store.as_context_mut().0.concurrent_state_mut(),
+            self.rep,
+            host_index(true),
+            None,
+        )
+    }
+}
 
 /// Represents the readable end of a Component Model `future`.
 pub struct FutureReader<T> {
-    _phantom: PhantomData<T>,
+    rep: u32,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> FutureReader<T> {
+    /// Reads the value of this future.
+    ///
+    /// The returned [`Promise`] resolves to `None` if the writer closed its
+    /// end without writing a value.
+    pub fn read(self, mut store: impl AsContextMut) -> Result<Promise<Option<T>>>
+    where
+        T: Lift + Send + Sync + 'static,
+    {
+        Ok(
+            host_read::<T>(store.as_context_mut().0, self.rep, host_index(true))?
+                .map(|values| values.and_then(|v| v.into_iter().next())),
+        )
+    }
+
+    /// Closes this end of the future without reading a value.
+    pub fn close(self, mut store: impl AsContextMut) -> Result<()> {
+        close_readable(
+            store.as_context_mut().0.concurrent_state_mut(),
+            self.rep,
+            host_index(true),
+        )
+    }
+}
+
+/// Represents the writable end of a Component Model `stream`.
+pub struct StreamWriter<T> {
+    rep: u32,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T> StreamWriter<T> {
+    /// Writes `values` to this stream.
+    ///
+    /// The returned [`Promise`] resolves once all values have been received
+    /// by the reader, yielding this writer for further writes, or `None` if
+    /// the reader closed its end first.
+    pub fn write<U>(
+        self,
+        mut store: impl AsContextMut<Data = U>,
+        values: Vec<T>,
+    ) -> Result<Promise<Option<StreamWriter<T>>>>
+    where
+        T: Lower + Send + Sync + 'static,
+    {
+        Ok(
+            host_write(store.as_context_mut(), self.rep, host_index(false), values)?
+                .map(move |ok| ok.then_some(self)),
+        )
+    }
+
+    /// Closes this end of the stream.
+    pub fn close(self, mut store: impl AsContextMut) -> Result<()> {
+        close_writable(
+            store.as_context_mut().0.concurrent_state_mut(),
+            self.rep,
+            host_index(false),
+            None,
+        )
+    }
 }
 
 /// Represents the readable end of a Component Model `stream`.
 pub struct StreamReader<T> {
-    _phantom: PhantomData<T>,
+    rep: u32,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> StreamReader<T> {
+    /// Reads the next batch of values from this stream.
+    ///
+    /// The returned [`Promise`] resolves to this reader, for further reads,
+    /// along with the values read, or `None` if the writer closed its end.
+    pub fn read(
+        self,
+        mut store: impl AsContextMut,
+    ) -> Result<Promise<Option<(StreamReader<T>, Vec<T>)>>>
+    where
+        T: Lift + Send + Sync + 'static,
+    {
+        Ok(
+            host_read::<T>(store.as_context_mut().0, self.rep, host_index(false))?
+                .map(move |values| values.map(|values| (self, values))),
+        )
+    }
+
+    /// Closes this end of the stream.
+    pub fn close(self, mut store: impl AsContextMut) -> Result<()> {
+        close_readable(
+            store.as_context_mut().0.concurrent_state_mut(),
+            self.rep,
+            host_index(false),
+        )
+    }
+}
+
+/// Type-checks the payload of a stream or future against `T`.
+fn typecheck_payload<T: ComponentType>(
+    payload: Option<&InterfaceType>,
+    types: &InstanceType<'_>,
+) -> Result<()> {
+    match payload {
+        Some(payload) => T::typecheck(payload, types),
+        None if T::IS_RUST_UNIT_TYPE => Ok(()),
+        None => bail!("expected a payload-less type"),
+    }
+}
+
+/// Removes the readable end at `index` from the instance being lifted from.
+fn lift_readable(cx: &mut LiftContext<'_>, ty: TableIndex, index: u32) -> Result<u32> {
+    let instance = cx.instance_ptr();
+    let rep = take_readable(instance, ty, index)?;
+    let state = cx.concurrent_state_mut();
+    let waitable = transmit(state, rep)?.read_waitable;
+    state.rehome(waitable, 0, None);
+    Ok(rep)
+}
+
+/// Gives the readable end `rep` to the instance being lowered into.
+fn lower_readable<U>(cx: &mut LowerContext<'_, U>, ty: TableIndex, rep: u32) -> Result<u32> {
+    let instance = cx.instance_ptr();
+    give_readable(cx.store.0.concurrent_state_mut(), instance, ty, rep)
+}
+
+macro_rules! readable_impls {
+    ($reader:ident, $variant:ident, $kind:tt) => {
+        unsafe impl<T: ComponentType> ComponentType for $reader<T> {
+            const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+            type Lower = <u32 as ComponentType>::Lower;
+
+            fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+                match ty {
+                    InterfaceType::$variant(ty) => typecheck_payload::<T>(
+                        types.types[types.types[*ty].ty].payload.as_ref(),
+                        types,
+                    ),
+                    other => bail!(concat!("expected `", $kind, "`, found `{}`"), desc(other)),
+                }
+            }
+        }
+
+        unsafe impl<T: ComponentType> Lower for $reader<T> {
+            fn lower<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                dst: &mut MaybeUninit<Self::Lower>,
+            ) -> Result<()> {
+                let InterfaceType::$variant(t) = ty else {
+                    bad_type_info()
+                };
+                lower_readable(cx, t.into(), self.rep)?.lower(cx, InterfaceType::U32, dst)
+            }
+
+            fn store<U>(
+                &self,
+                cx: &mut LowerContext<'_, U>,
+                ty: InterfaceType,
+                offset: usize,
+            ) -> Result<()> {
+                let InterfaceType::$variant(t) = ty else {
+                    bad_type_info()
+                };
+                lower_readable(cx, t.into(), self.rep)?.store(cx, InterfaceType::U32, offset)
+            }
+        }
+
+        unsafe impl<T: ComponentType> Lift for $reader<T> {
+            fn lift(
+                cx: &mut LiftContext<'_>,
+                ty: InterfaceType,
+                src: &Self::Lower,
+            ) -> Result<Self> {
+                let index = u32::lift(cx, InterfaceType::U32, src)?;
+                Self::lift_from_index(cx, ty, index)
+            }
+
+            fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+                let index = u32::load(cx, InterfaceType::U32, bytes)?;
+                Self::lift_from_index(cx, ty, index)
+            }
+        }
+
+        impl<T> $reader<T> {
+            fn lift_from_index(
+                cx: &mut LiftContext<'_>,
+                ty: InterfaceType,
+                index: u32,
+            ) -> Result<Self> {
+                let InterfaceType::$variant(t) = ty else {
+                    bad_type_info()
+                };
+                Ok($reader {
+                    rep: lift_readable(cx, t.into(), index)?,
+                    _phantom: PhantomData,
+                })
+            }
+        }
+    };
+}
+
+readable_impls!(FutureReader, Future, "future");
+readable_impls!(StreamReader, Stream, "stream");
+
+/// Number of values a [`HostStream`] received from a guest buffers before the
+/// guest's writes block.
+const HOST_STREAM_CAPACITY: usize = 16;
+
+/// Maximum number of values forwarded from a [`HostStream`] to a guest in a
+/// single write.
+const HOST_STREAM_BATCH: usize = 64;
+
+type BoxStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Option<T>> + Send>>;
+
+/// Writes values from the host to a stream or future, see `host_write`.
+///
+/// This is a function pointer so the pumps below need not be generic over the
+/// type of data within the store.
+type HostWriteFn<T> = fn(&mut dyn VMStore, u32, TableIndex, Vec<T>) -> Result<Promise<bool>>;
+
+fn host_write_erased<D, T>(
+    store: &mut dyn VMStore,
+    rep: u32,
+    ty: TableIndex,
+    values: Vec<T>,
+) -> Result<Promise<bool>>
+where
+    T: Lower + Send + Sync + 'static,
+{
+    // SAFETY: this is only instantiated with the data type of the store that
+    // `rep` belongs to.
+    host_write(unsafe { store_context::<D>(store) }, rep, ty, values)
+}
+
+/// Wraps a `Send` value so it's `Sync` as well, which is sound since the
+/// value is only ever accessed by value or through `Pin<&mut Self>`.
+struct Exclusive<T>(T);
+
+unsafe impl<T: Send> Sync for Exclusive<T> {}
+
+impl<T> Exclusive<T> {
+    fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<F: Future> Future for Exclusive<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        // SAFETY: this is a plain structural projection of a pinned field.
+        unsafe { self.map_unchecked_mut(|f| &mut f.0) }.poll(cx)
+    }
+}
+
+/// Schedules `thunk` to run on the event loop with access to the store.
+fn defer(state: &mut ConcurrentState, thunk: Thunk) {
+    state.push_future(Box::pin(future_util::ready(thunk)));
+}
+
+/// Forwards values read from the host-owned readable end `rep` to `tx`.
+///
+/// The next read is only started once `tx` has accepted everything read so
+/// far, so a full channel makes the guest's writes block.
+fn read_pump<T>(store: &mut StoreOpaque, rep: u32, mut tx: mpsc::Sender<T>) -> Result<()>
+where
+    T: Lift + Send + Sync + 'static,
+{
+    let ty = host_index(false);
+    let read = host_read::<T>(store, rep, ty)?;
+    store
+        .concurrent_state_mut()
+        .push_future(Box::pin(async move {
+            let open = match read.0.await {
+                Some(values) => send_all(&mut tx, values).await,
+                None => false,
+            };
+            let thunk: Thunk = if open {
+                Box::new(move |store| read_pump(store, rep, tx))
+            } else {
+                Box::new(move |store| close_readable(store.concurrent_state_mut(), rep, ty))
+            };
+            thunk
+        }));
+    Ok(())
+}
+
+async fn send_all<T>(tx: &mut mpsc::Sender<T>, values: Vec<T>) -> bool {
+    for value in values {
+        if tx.send(value).await.is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+/// Forwards values from `stream` to the host-owned writable end `rep`.
+///
+/// Only one write is outstanding at a time so a slow reader applies
+/// backpressure to `stream`.
+fn write_pump<T>(
+    state: &mut ConcurrentState,
+    write: HostWriteFn<T>,
+    rep: u32,
+    mut stream: BoxStream<T>,
+) where
+    T: Send + 'static,
+{
+    let ty = host_index(false);
+    state.push_future(Box::pin(Exclusive(async move {
+        let Some(first) = stream.next().await else {
+            let thunk: Thunk =
+                Box::new(move |store| close_writable(store.concurrent_state_mut(), rep, ty, None));
+            return thunk;
+        };
+        let mut values = vec![first];
+        while values.len() < HOST_STREAM_BATCH {
+            match stream.next().now_or_never() {
+                Some(Some(value)) => values.push(value),
+                _ => break,
+            }
+        }
+        let pending = Exclusive((stream, values));
+        let thunk: Thunk = Box::new(move |store| {
+            let (stream, values) = pending.into_inner();
+            let written = write(store, rep, ty, values)?;
+            let stream = Exclusive(stream);
+            store
+                .concurrent_state_mut()
+                .push_future(Box::pin(async move {
+                    let thunk: Thunk = if written.0.await {
+                        Box::new(move |store| {
+                            write_pump(
+                                store.concurrent_state_mut(),
+                                write,
+                                rep,
+                                stream.into_inner(),
+                            );
+                            Ok(())
+                        })
+                    } else {
+                        Box::new(move |store| {
+                            close_writable(store.concurrent_state_mut(), rep, ty, None)
+                        })
+                    };
+                    thunk
+                }));
+            Ok(())
+        });
+        thunk
+    })));
+}
+
+/// A Component Model `stream<T>` represented as a [`futures::Stream`].
+///
+/// A `HostStream` lifted from a guest yields the values the guest writes to
+/// it. At most a small number of values are buffered, after which the guest's
+/// writes block until the host catches up. Conversely a `HostStream` lowered
+/// into a guest forwards the values of the underlying stream to the guest,
+/// only polling for more once the guest has read the previous values.
+///
+/// Values are transferred by the store's event loop, so the host must be
+/// running concurrently with the store, for example within a host function
+/// defined with [`LinkerInstance::func_wrap_concurrent`], for transfers to make
+/// progress.
+///
+/// [`LinkerInstance::func_wrap_concurrent`]: crate::component::LinkerInstance::func_wrap_concurrent
+pub struct HostStream<T> {
+    stream: Mutex<Option<BoxStream<T>>>,
+}
+
+impl<T> fmt::Debug for HostStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostStream").finish_non_exhaustive()
+    }
+}
+
+impl<T> HostStream<T> {
+    /// Creates a new `HostStream` which yields the values of `stream`.
+    pub fn new(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        HostStream {
+            stream: Mutex::new(Some(Box::pin(stream))),
+        }
+    }
+
+    /// Creates a new `HostStream` along with a [`HostSink`] used to send
+    /// values to it.
+    ///
+    /// At most `capacity` values are buffered before sending to the
+    /// [`HostSink`] waits for the reader.
+    pub fn channel(capacity: usize) -> (HostSink<T>, HostStream<T>)
+    where
+        T: Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(capacity);
+        (HostSink { tx }, HostStream::new(rx))
+    }
+
+    fn take(&self) -> Result<BoxStream<T>> {
+        self.stream
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow!("stream has already been transferred"))
+    }
+}
+
+impl<T> Stream for HostStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.get_mut().stream.get_mut().unwrap() {
+            Some(stream) => stream.as_mut().poll_next(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// The sending half of a [`HostStream`] created with
+/// [`HostStream::channel`], usable as a [`futures::Sink`].
+///
+/// Sending fails once the [`HostStream`] has been dropped or its reader has
+/// closed the stream.
+pub struct HostSink<T> {
+    tx: mpsc::Sender<T>,
+}
+
+impl<T> fmt::Debug for HostSink<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostSink").finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for HostSink<T> {
+    fn clone(&self) -> Self {
+        HostSink {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+fn sink_closed(_: mpsc::SendError) -> Error {
+    anyhow!("stream was closed by its reader")
+}
+
+impl<T> Sink<T> for HostSink<T> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.get_mut().tx.poll_ready(cx).map_err(sink_closed)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<()> {
+        self.get_mut().tx.start_send(item).map_err(sink_closed)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().tx)
+            .poll_flush(cx)
+            .map_err(sink_closed)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Pin::new(&mut self.get_mut().tx)
+            .poll_close(cx)
+            .map_err(sink_closed)
+    }
+}
+
+unsafe impl<T: ComponentType> ComponentType for HostStream<T> {
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+    type Lower = <u32 as ComponentType>::Lower;
+
+    fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+        StreamReader::<T>::typecheck(ty, types)
+    }
+}
+
+unsafe impl<T: Lower + Send + Sync + 'static> Lower for HostStream<T> {
+    fn lower<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        dst: &mut MaybeUninit<Self::Lower>,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .lower(cx, InterfaceType::U32, dst)
+    }
+
+    fn store<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        offset: usize,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .store(cx, InterfaceType::U32, offset)
+    }
+}
+
+unsafe impl<T: Lift + Send + Sync + 'static> Lift for HostStream<T> {
+    fn lift(cx: &mut LiftContext<'_>, ty: InterfaceType, src: &Self::Lower) -> Result<Self> {
+        let index = u32::lift(cx, InterfaceType::U32, src)?;
+        Self::lift_from_index(cx, ty, index)
+    }
+
+    fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+        let index = u32::load(cx, InterfaceType::U32, bytes)?;
+        Self::lift_from_index(cx, ty, index)
+    }
+}
+
+impl<T> HostStream<T> {
+    fn lower_to_index<U>(&self, cx: &mut LowerContext<'_, U>, ty: InterfaceType) -> Result<u32>
+    where
+        T: Lower + Send + Sync + 'static,
+    {
+        let InterfaceType::Stream(t) = ty else {
+            bad_type_info()
+        };
+        let stream = self.take()?;
+        let state = cx.store.0.concurrent_state_mut();
+        let rep = new_transmit(state, false, 0);
+        write_pump(state, host_write_erased::<U, T>, rep, stream);
+        lower_readable(cx, t.into(), rep)
+    }
+
+    fn lift_from_index(cx: &mut LiftContext<'_>, ty: InterfaceType, index: u32) -> Result<Self>
+    where
+        T: Lift + Send + Sync + 'static,
+    {
+        let InterfaceType::Stream(t) = ty else {
+            bad_type_info()
+        };
+        let rep = lift_readable(cx, t.into(), index)?;
+        let (tx, rx) = mpsc::channel(HOST_STREAM_CAPACITY);
+        defer(
+            cx.concurrent_state_mut(),
+            Box::new(move |store| read_pump::<T>(store, rep, tx)),
+        );
+        Ok(HostStream::new(rx))
+    }
+}
+
+/// A Component Model `future<T>` represented as a [`core::future::Future`].
+///
+/// A `HostFuture` lifted from a guest resolves to the value the guest writes
+/// to it, or `None` if the guest closed its end without writing a value.
+/// Conversely a `HostFuture` lowered into a guest writes the value of the
+/// underlying future to the guest once it resolves.
+///
+/// As with [`HostStream`], values are transferred by the store's event loop.
+pub struct HostFuture<T> {
+    future: Mutex<Option<BoxFuture<T>>>,
+}
+
+impl<T> fmt::Debug for HostFuture<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostFuture").finish_non_exhaustive()
+    }
+}
+
+impl<T> HostFuture<T> {
+    /// Creates a new `HostFuture` which resolves to the output of `future`.
+    pub fn new(future: impl Future<Output = T> + Send + 'static) -> Self
+    where
+        T: 'static,
+    {
+        HostFuture::from_boxed(Box::pin(future.map(Some)))
+    }
+
+    fn from_boxed(future: BoxFuture<T>) -> Self {
+        HostFuture {
+            future: Mutex::new(Some(future)),
+        }
+    }
+
+    fn take(&self) -> Result<BoxFuture<T>> {
+        self.future
+            .lock()
+            .unwrap()
+            .take()
+            .ok_or_else(|| anyhow!("future has already been transferred"))
+    }
+}
+
+impl<T> Future for HostFuture<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.get_mut().future.get_mut().unwrap() {
+            Some(future) => future.as_mut().poll(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+unsafe impl<T: ComponentType> ComponentType for HostFuture<T> {
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+    type Lower = <u32 as ComponentType>::Lower;
+
+    fn typecheck(ty: &InterfaceType, types: &InstanceType<'_>) -> Result<()> {
+        FutureReader::<T>::typecheck(ty, types)
+    }
+}
+
+unsafe impl<T: Lower + Send + Sync + 'static> Lower for HostFuture<T> {
+    fn lower<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        dst: &mut MaybeUninit<Self::Lower>,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .lower(cx, InterfaceType::U32, dst)
+    }
+
+    fn store<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        offset: usize,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .store(cx, InterfaceType::U32, offset)
+    }
+}
+
+unsafe impl<T: Lift + Send + Sync + 'static> Lift for HostFuture<T> {
+    fn lift(cx: &mut LiftContext<'_>, ty: InterfaceType, src: &Self::Lower) -> Result<Self> {
+        let index = u32::lift(cx, InterfaceType::U32, src)?;
+        Self::lift_from_index(cx, ty, index)
+    }
+
+    fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+        let index = u32::load(cx, InterfaceType::U32, bytes)?;
+        Self::lift_from_index(cx, ty, index)
+    }
+}
+
+impl<T> HostFuture<T> {
+    fn lower_to_index<U>(&self, cx: &mut LowerContext<'_, U>, ty: InterfaceType) -> Result<u32>
+    where
+        T: Lower + Send + Sync + 'static,
+    {
+        let InterfaceType::Future(t) = ty else {
+            bad_type_info()
+        };
+        let future = self.take()?;
+        let state = cx.store.0.concurrent_state_mut();
+        let rep = new_transmit(state, true, 0);
+        let write: HostWriteFn<T> = host_write_erased::<U, T>;
+        state.push_future(Box::pin(Exclusive(async move {
+            let value = future.await;
+            let thunk: Thunk = Box::new(move |store| match value {
+                // The writable end of a future closes once its value has been
+                // written, so there's nothing to do once the write completes.
+                Some(value) => write(store, rep, host_index(true), vec![value]).map(drop),
+                None => close_writable(store.concurrent_state_mut(), rep, host_index(true), None),
+            });
+            thunk
+        })));
+        lower_readable(cx, t.into(), rep)
+    }
+
+    fn lift_from_index(cx: &mut LiftContext<'_>, ty: InterfaceType, index: u32) -> Result<Self>
+    where
+        T: Lift + Send + Sync + 'static,
+    {
+        let InterfaceType::Future(t) = ty else {
+            bad_type_info()
+        };
+        let rep = lift_readable(cx, t.into(), index)?;
+        let (tx, rx) = oneshot::channel();
+        defer(
+            cx.concurrent_state_mut(),
+            Box::new(move |store| {
+                let read = host_read::<T>(store, rep, host_index(true))?;
+                store
+                    .concurrent_state_mut()
+                    .push_future(Box::pin(async move {
+                        let value = read.0.await.and_then(|v| v.into_iter().next());
+                        _ = tx.send(value);
+                        let thunk: Thunk = Box::new(|_| Ok(()));
+                        thunk
+                    }));
+                Ok(())
+            }),
+        );
+        Ok(HostFuture::from_boxed(Box::pin(
+            rx.map(|v| v.ok().flatten()),
+        )))
+    }
 }
 
 /// Represents a Component Model `error-context`.
-pub struct ErrorContext {}
+///
+/// Values of this type are reference-counted within the store. A host-owned
+/// `ErrorContext` keeps its value alive until [`ErrorContext::drop`] is
+/// called.
+pub struct ErrorContext {
+    rep: u32,
+}
+
+impl ErrorContext {
+    /// Creates a new `error-context` with the specified debug message.
+    pub fn new(mut store: impl AsContextMut, debug_message: impl Into<String>) -> Result<Self> {
+        let store = store.as_context_mut();
+        let rep = store
+            .0
+            .concurrent_state_mut()
+            .error_contexts
+            .alloc(super::ErrorContextData {
+                debug_message: debug_message.into(),
+                refs: 1,
+            });
+        Ok(ErrorContext {
+            rep: rep.into_raw(),
+        })
+    }
+
+    /// Returns the debug message associated with this `error-context`.
+    pub fn debug_message(&self, mut store: impl AsContextMut) -> Result<String> {
+        let store = store.as_context_mut();
+        Ok(store
+            .0
+            .concurrent_state_mut()
+            .error_contexts
+            .get(Id::from_raw(self.rep))
+            .ok_or_else(|| anyhow!("unknown error-context"))?
+            .debug_message
+            .clone())
+    }
+
+    /// Releases the host's reference to this `error-context`.
+    pub fn drop(self, mut store: impl AsContextMut) -> Result<()> {
+        error_context_release(store.as_context_mut().0.concurrent_state_mut(), self.rep);
+        Ok(())
+    }
+}
+
+unsafe impl ComponentType for ErrorContext {
+    const ABI: CanonicalAbiInfo = CanonicalAbiInfo::SCALAR4;
+
+    type Lower = <u32 as ComponentType>::Lower;
+
+    fn typecheck(ty: &InterfaceType, _types: &InstanceType<'_>) -> Result<()> {
+        match ty {
+            InterfaceType::ErrorContext(_) => Ok(()),
+            other => bail!("expected `error-context`, found `{}`", desc(other)),
+        }
+    }
+}
+
+impl ErrorContext {
+    fn lower_to_index<U>(&self, cx: &mut LowerContext<'_, U>, ty: InterfaceType) -> Result<u32> {
+        let InterfaceType::ErrorContext(ty) = ty else {
+            bad_type_info()
+        };
+        error_context_retain(cx.store.0.concurrent_state_mut(), self.rep);
+        let instance = cx.instance_ptr();
+        unsafe { (*instance).error_context_table(ty) }.insert(ErrorContextState { rep: self.rep })
+    }
+
+    fn lift_from_index(cx: &mut LiftContext<'_>, ty: InterfaceType, index: u32) -> Result<Self> {
+        let InterfaceType::ErrorContext(ty) = ty else {
+            bad_type_info()
+        };
+        let instance = cx.instance_ptr();
+        let rep = unsafe { (*instance).error_context_table(ty) }
+            .get(index)
+            .ok_or_else(|| anyhow!("unknown error-context handle: {index}"))?
+            .rep;
+        error_context_retain(cx.concurrent_state_mut(), rep);
+        Ok(ErrorContext { rep })
+    }
+}
+
+unsafe impl Lower for ErrorContext {
+    fn lower<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        dst: &mut MaybeUninit<Self::Lower>,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .lower(cx, InterfaceType::U32, dst)
+    }
+
+    fn store<U>(
+        &self,
+        cx: &mut LowerContext<'_, U>,
+        ty: InterfaceType,
+        offset: usize,
+    ) -> Result<()> {
+        self.lower_to_index(cx, ty)?
+            .store(cx, InterfaceType::U32, offset)
+    }
+}
+
+unsafe impl Lift for ErrorContext {
+    fn lift(cx: &mut LiftContext<'_>, ty: InterfaceType, src: &Self::Lower) -> Result<Self> {
+        let index = u32::lift(cx, InterfaceType::U32, src)?;
+        ErrorContext::lift_from_index(cx, ty, index)
+    }
+
+    fn load(cx: &mut LiftContext<'_>, ty: InterfaceType, bytes: &[u8]) -> Result<Self> {
+        let index = u32::load(cx, InterfaceType::U32, bytes)?;
+        ErrorContext::lift_from_index(cx, ty, index)
+    }
+}
+
+pub(super) fn error_context_retain(state: &mut ConcurrentState, rep: u32) {
+    if let Some(err) = state.error_contexts.get_mut(Id::from_raw(rep)) {
+        err.refs += 1;
+    }
+}
+
+pub(super) fn error_context_release(state: &mut ConcurrentState, rep: u32) {
+    let id = Id::from_raw(rep);
+    if let Some(err) = state.error_contexts.get_mut(id) {
+        err.refs -= 1;
+        if err.refs == 0 {
+            state.error_contexts.dealloc(id);
+        }
+    }
+}
+
+/// The `error-context.new` intrinsic.
+pub(super) fn error_context_new(
+    store: &mut StoreOpaque,
+    instance: &mut ComponentInstance,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: u8,
+    ty: TypeComponentLocalErrorContextTableIndex,
+    debug_msg_address: u32,
+    debug_msg_len: u32,
+) -> Result<u32> {
+    let options = unsafe {
+        Options::new(
+            store.id(),
+            NonNull::new(memory),
+            NonNull::new(realloc),
+            StringEncoding::from_u8(string_encoding).unwrap(),
+        )
+    };
+    let types = instance.component_types().clone();
+    let debug_message = unsafe {
+        let mut cx = LiftContext::new(store, &options, &types, instance);
+        String::lift(
+            &mut cx,
+            InterfaceType::String,
+            &[ValRaw::u32(debug_msg_address), ValRaw::u32(debug_msg_len)],
+        )?
+    };
+    let rep = store
+        .concurrent_state_mut()
+        .error_contexts
+        .alloc(super::ErrorContextData {
+            debug_message,
+            refs: 1,
+        });
+    instance.error_context_table(ty).insert(ErrorContextState {
+        rep: rep.into_raw(),
+    })
+}
+
+/// The `error-context.debug-message` intrinsic.
+pub(super) fn error_context_debug_message<T>(
+    mut store: StoreContextMut<'_, T>,
+    instance: &mut ComponentInstance,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: u8,
+    ty: TypeComponentLocalErrorContextTableIndex,
+    err_ctx_handle: u32,
+    debug_msg_address: u32,
+) -> Result<()> {
+    let rep = instance
+        .error_context_table(ty)
+        .get(err_ctx_handle)
+        .ok_or_else(|| anyhow!("unknown error-context handle: {err_ctx_handle}"))?
+        .rep;
+    let debug_message = store
+        .0
+        .concurrent_state_mut()
+        .error_contexts
+        .get(Id::from_raw(rep))
+        .ok_or_else(|| anyhow!("unknown error-context"))?
+        .debug_message
+        .clone();
+    let options = unsafe {
+        Options::new(
+            store.0.id(),
+            NonNull::new(memory),
+            NonNull::new(realloc),
+            StringEncoding::from_u8(string_encoding).unwrap(),
+        )
+    };
+    let address = usize::try_from(debug_msg_address)?;
+    if address % 4 != 0 {
+        bail!("pointer not aligned");
+    }
+    if address
+        .checked_add(8)
+        .map_or(true, |end| end > options.memory(store.0).len())
+    {
+        bail!("pointer out of bounds of memory");
+    }
+    let types = instance.component_types().clone();
+    let mut cx = unsafe { LowerContext::new(store.as_context_mut(), &options, &types, instance) };
+    debug_message
+        .as_str()
+        .store(&mut cx, InterfaceType::String, address)
+}
@@ -1,4 +1,6 @@
 use crate::component::instance::{Instance, InstanceData};
+#[cfg(all(feature = "call-hook", feature = "std"))]
+use crate::component::intercept::{self, CallKind};
 use crate::component::storage::storage_as_slice;
 use crate::component::types::Type;
 use crate::component::values::Val;
@@ -6,13 +8,18 @@ use crate::prelude::*;
 use crate::runtime::vm::component::ResourceTables;
 use crate::runtime::vm::{Export, ExportFunction};
 use crate::store::{StoreOpaque, Stored};
+#[cfg(feature = "component-model-async")]
+use crate::{
+    component::concurrent,
+    runtime::vm::{SendSyncPtr, VMFuncRef},
+};
 use crate::{AsContext, AsContextMut, StoreContextMut, ValRaw};
 use alloc::sync::Arc;
 use core::mem::{self, MaybeUninit};
 use core::ptr::NonNull;
 use wasmtime_environ::component::{
-    CanonicalOptions, ComponentTypes, CoreDef, InterfaceType, RuntimeComponentInstanceIndex,
-    TypeFuncIndex, TypeTuple, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
+    CanonicalOptions, ComponentTypes, CoreDef, ExportIndex, InterfaceType,
+    RuntimeComponentInstanceIndex, TypeFuncIndex, TypeTuple, MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
 };
 
 mod host;
@@ -41,6 +48,8 @@ pub struct Func(Stored<FuncData>);
 #[doc(hidden)]
 pub struct FuncData {
     export: ExportFunction,
+    #[cfg_attr(not(all(feature = "call-hook", feature = "std")), allow(dead_code))]
+    index: ExportIndex,
     ty: TypeFuncIndex,
     types: Arc<ComponentTypes>,
     options: Options,
@@ -48,6 +57,10 @@ pub struct FuncData {
     component_instance: RuntimeComponentInstanceIndex,
     post_return: Option<ExportFunction>,
     post_return_arg: Option<ValRaw>,
+    #[cfg(feature = "component-model-async")]
+    async_: bool,
+    #[cfg(feature = "component-model-async")]
+    callback: Option<SendSyncPtr<VMFuncRef>>,
 }
 
 impl Func {
@@ -55,6 +68,7 @@ impl Func {
         store: &mut StoreOpaque,
         instance: &Instance,
         data: &InstanceData,
+        index: ExportIndex,
         ty: TypeFuncIndex,
         func: &CoreDef,
         options: &CanonicalOptions,
@@ -72,9 +86,17 @@ impl Func {
             ExportFunction { func_ref }
         });
         let component_instance = options.instance;
+        #[cfg(feature = "component-model-async")]
+        let (async_, callback) = (
+            options.async_,
+            options
+                .callback
+                .map(|i| SendSyncPtr::new(data.instance().runtime_callback(i))),
+        );
         let options = unsafe { Options::new(store.id(), memory, realloc, options.string_encoding) };
         Func(store.store_data_mut().insert(FuncData {
             export,
+            index,
             options,
             ty,
             types: data.component_types().clone(),
@@ -82,9 +104,53 @@ impl Func {
             component_instance,
             post_return,
             post_return_arg: None,
+            #[cfg(feature = "component-model-async")]
+            async_,
+            #[cfg(feature = "component-model-async")]
+            callback,
         }))
     }
 
+    /// Returns the options, types, instance and type of this function for
+    /// lowering into or lifting out of it.
+    #[cfg(feature = "component-model-async")]
+    fn lowering_info(
+        &self,
+        store: &StoreOpaque,
+    ) -> (
+        Options,
+        Arc<ComponentTypes>,
+        *mut crate::runtime::vm::component::ComponentInstance,
+        TypeFuncIndex,
+    ) {
+        let data = &store[self.0];
+        let instance = store[data.instance.0].as_ref().unwrap();
+        (
+            data.options,
+            instance.component_types().clone(),
+            instance.instance_ptr(),
+            data.ty,
+        )
+    }
+
+    /// Describes how to call this function as a task, or returns `None` if
+    /// it was lifted synchronously.
+    #[cfg(feature = "component-model-async")]
+    pub(crate) fn guest_call(&self, store: &StoreOpaque) -> Option<concurrent::GuestCall> {
+        let data = &store[self.0];
+        if !data.async_ {
+            return None;
+        }
+        let instance = store[data.instance.0].as_ref().unwrap().instance_ptr();
+        Some(concurrent::GuestCall {
+            instance: SendSyncPtr::new(NonNull::new(instance).unwrap()),
+            runtime_instance: data.component_instance,
+            callee: SendSyncPtr::new(data.export.func_ref),
+            callback: data.callback,
+            task_return_type: data.types[data.ty].results,
+        })
+    }
+
     /// Attempt to cast this [`Func`] to a statically typed [`TypedFunc`] with
     /// the provided `Params` and `Return`.
     ///
@@ -313,7 +379,7 @@ impl Func {
             .await?
     }
 
-    fn call_impl(
+    pub(crate) fn call_impl(
         &self,
         mut store: impl AsContextMut,
         params: &[Val],
@@ -412,6 +478,11 @@ impl Func {
             ..
         } = store.0[self.0];
 
+        #[cfg(feature = "component-model-async")]
+        if store.0[self.0].async_ {
+            bail!("async-lifted exports must be called with `call_concurrent`");
+        }
+
         let space = &mut MaybeUninit::<ParamsAndResults<LowerParams, LowerReturn>>::uninit();
 
         // Double-check the size/alignment of `space`, just in case.
@@ -449,6 +520,7 @@ impl Func {
             debug_assert!(flags.may_leave());
             flags.set_may_leave(false);
             let instance_ptr = instance.instance_ptr();
+            store.0.reset_component_adapter_copied();
             let mut cx = LowerContext::new(store.as_context_mut(), &options, &types, instance_ptr);
             cx.enter_call();
             let result = lower(
@@ -460,6 +532,26 @@ impl Func {
             flags.set_may_leave(true);
             result?;
 
+            #[cfg(all(feature = "call-hook", feature = "std"))]
+            let intercepted = if store.0.has_component_call_interceptor() {
+                let FuncData {
+                    instance, index, ..
+                } = store.0[self.0];
+                let name = instance.export_func_name(store.0, index);
+                let params = core::slice::from_raw_parts(
+                    space.as_ptr().cast::<MaybeUninit<ValRaw>>(),
+                    mem::size_of::<LowerParams>() / mem::size_of::<ValRaw>(),
+                );
+                let start = intercept::before_call(store, name.call(CallKind::Export), |store| {
+                    let mut cx = LiftContext::new(store, &options, &types, instance_ptr)
+                        .inspect(Inspect::Lowered);
+                    intercept::lift_tuple(&mut cx, types[ty].params, MAX_FLAT_PARAMS, params)
+                })?;
+                Some((name, start))
+            } else {
+                None
+            };
+
             // This is unsafe as we are providing the guarantee that all the
             // inputs are valid. The various pointers passed in for the function
             // are all valid since they're coming from our store, and the
@@ -487,6 +579,17 @@ impl Func {
             // values).
             let ret = map_maybe_uninit!(space.ret).assume_init_ref();
 
+            #[cfg(all(feature = "call-hook", feature = "std"))]
+            if let Some((name, start)) = &intercepted {
+                let results =
+                    mem::transmute::<&[ValRaw], &[MaybeUninit<ValRaw>]>(storage_as_slice(ret));
+                intercept::after_call(store, name.call(CallKind::Export), *start, |store| {
+                    let mut cx = LiftContext::new(store, &options, &types, instance_ptr)
+                        .inspect(Inspect::Lifted);
+                    intercept::lift_tuple(&mut cx, types[ty].results, MAX_FLAT_RESULTS, results)
+                })?;
+            }
+
             // Lift the result into the host while managing post-return state
             // here as well.
             //
@@ -571,7 +674,7 @@ impl Func {
         store.on_fiber(|store| self.post_return_impl(store)).await?
     }
 
-    fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
+    pub(crate) fn post_return_impl(&self, mut store: impl AsContextMut) -> Result<()> {
         let mut store = store.as_context_mut();
         let data = &mut store.0[self.0];
         let instance = data.instance;
@@ -1,22 +1,49 @@
 use crate::component::func::{LiftContext, LowerContext, Options};
 use crate::component::matching::InstanceType;
 use crate::component::storage::slice_to_storage_mut;
+use crate::component::types::{self, Type};
+#[cfg(all(feature = "call-hook", feature = "std"))]
+use crate::component::{
+    func::Inspect,
+    intercept::{self, CallKind},
+};
 use crate::component::{ComponentNamedList, ComponentType, Lift, Lower, Val};
 use crate::prelude::*;
 use crate::runtime::vm::component::{
     ComponentInstance, InstanceFlags, VMComponentContext, VMLowering, VMLoweringCallee,
 };
 use crate::runtime::vm::{VMFuncRef, VMGlobalDefinition, VMMemoryDefinition, VMOpaqueContext};
+#[cfg(feature = "component-model-async")]
+use crate::{component::concurrent, runtime::vm::SendSyncPtr, store::StoreOpaque};
 use crate::{AsContextMut, CallHook, StoreContextMut, ValRaw};
 use alloc::sync::Arc;
 use core::any::Any;
+#[cfg(feature = "component-model-async")]
+use core::future::Future;
 use core::mem::{self, MaybeUninit};
 use core::ptr::NonNull;
+#[cfg(feature = "component-model-async")]
+use wasmtime_environ::component::RuntimeComponentInstanceIndex;
 use wasmtime_environ::component::{
     CanonicalAbiInfo, ComponentTypes, InterfaceType, StringEncoding, TypeFuncIndex,
     MAX_FLAT_PARAMS, MAX_FLAT_RESULTS,
 };
 
+/// The name a host function is defined with in a `Linker`.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(all(feature = "call-hook", feature = "std")), allow(dead_code))]
+pub(crate) struct FuncName {
+    /// The path of instances the function is defined in, joined with `/`.
+    pub interface: Option<Arc<str>>,
+    pub name: Arc<str>,
+}
+
+/// The closure of a `HostFunc` along with its name.
+struct Named<F> {
+    name: FuncName,
+    func: F,
+}
+
 pub struct HostFunc {
     entrypoint: VMLoweringCallee,
     typecheck: Box<dyn (Fn(TypeFuncIndex, &InstanceType<'_>) -> Result<()>) + Send + Sync>,
@@ -30,7 +57,7 @@ impl core::fmt::Debug for HostFunc {
 }
 
 impl HostFunc {
-    pub(crate) fn from_closure<T, F, P, R>(func: F) -> Arc<HostFunc>
+    pub(crate) fn from_closure<T, F, P, R>(name: FuncName, func: F) -> Arc<HostFunc>
     where
         F: Fn(StoreContextMut<T>, P) -> Result<R> + Send + Sync + 'static,
         P: ComponentNamedList + Lift + 'static,
@@ -40,7 +67,7 @@ impl HostFunc {
         Arc::new(HostFunc {
             entrypoint,
             typecheck: Box::new(typecheck::<P, R>),
-            func: Box::new(func),
+            func: Box::new(Named { name, func }),
         })
     }
 
@@ -62,7 +89,7 @@ impl HostFunc {
         P: ComponentNamedList + Lift + 'static,
         R: ComponentNamedList + Lower + 'static,
     {
-        let data = data.as_ptr() as *const F;
+        let data = data.as_ptr() as *const Named<F>;
         unsafe {
             call_host_and_handle_result::<T>(cx, |instance, types, store| {
                 call_host::<_, _, _, _>(
@@ -76,13 +103,107 @@ impl HostFunc {
                     StringEncoding::from_u8(string_encoding).unwrap(),
                     async_ != 0,
                     NonNull::slice_from_raw_parts(storage, storage_len).as_mut(),
-                    |store, args| (*data)(store, args),
+                    &(*data).name,
+                    |store, args| ((*data).func)(store, args),
                 )
             })
         }
     }
 
-    pub(crate) fn new_dynamic<T, F>(func: F) -> Arc<HostFunc>
+    #[cfg(feature = "component-model-async")]
+    pub(crate) fn from_concurrent<T, F, P, R, N, FN>(name: FuncName, func: F) -> Arc<HostFunc>
+    where
+        T: 'static,
+        F: Fn(StoreContextMut<T>, P) -> FN + Send + Sync + 'static,
+        P: ComponentNamedList + Lift + 'static,
+        R: ComponentNamedList + Lower + 'static,
+        N: FnOnce(StoreContextMut<T>) -> Result<R> + Send + Sync + 'static,
+        FN: Future<Output = N> + Send + Sync + 'static,
+    {
+        let entrypoint = Self::concurrent_entrypoint::<T, F, P, R, N, FN>;
+        Arc::new(HostFunc {
+            entrypoint,
+            typecheck: Box::new(typecheck::<P, R>),
+            func: Box::new(Named { name, func }),
+        })
+    }
+
+    #[cfg(feature = "component-model-async")]
+    extern "C" fn concurrent_entrypoint<T, F, P, R, N, FN>(
+        cx: NonNull<VMOpaqueContext>,
+        data: NonNull<u8>,
+        ty: u32,
+        caller_instance: u32,
+        flags: NonNull<VMGlobalDefinition>,
+        memory: *mut VMMemoryDefinition,
+        realloc: *mut VMFuncRef,
+        string_encoding: u8,
+        async_: u8,
+        storage: NonNull<MaybeUninit<ValRaw>>,
+        storage_len: usize,
+    ) -> bool
+    where
+        T: 'static,
+        F: Fn(StoreContextMut<T>, P) -> FN,
+        P: ComponentNamedList + Lift + 'static,
+        R: ComponentNamedList + Lower + 'static,
+        N: FnOnce(StoreContextMut<T>) -> Result<R> + Send + Sync + 'static,
+        FN: Future<Output = N> + Send + Sync + 'static,
+    {
+        let data = data.as_ptr() as *const Named<F>;
+        let caller_instance = RuntimeComponentInstanceIndex::from_u32(caller_instance);
+        unsafe {
+            call_host_and_handle_result::<T>(cx, |instance, types, store| {
+                let storage = NonNull::slice_from_raw_parts(storage, storage_len).as_mut();
+                let flags = InstanceFlags::from_raw(flags);
+                let string_encoding = StringEncoding::from_u8(string_encoding).unwrap();
+                let ty = TypeFuncIndex::from_u32(ty);
+                if async_ != 0 {
+                    call_host_concurrent::<_, _, _, _, _, _>(
+                        instance,
+                        types,
+                        store,
+                        ty,
+                        flags,
+                        memory,
+                        realloc,
+                        string_encoding,
+                        caller_instance,
+                        storage,
+                        |store, args| ((*data).func)(store, args),
+                    )
+                } else {
+                    // A sync-lowered caller blocks until the host function's
+                    // future completes, running other tasks meanwhile.
+                    call_host::<_, _, _, _>(
+                        instance,
+                        types,
+                        store,
+                        ty,
+                        flags,
+                        memory,
+                        realloc,
+                        string_encoding,
+                        false,
+                        storage,
+                        &(*data).name,
+                        |mut store, args| {
+                            let future = ((*data).func)(store.as_context_mut(), args);
+                            let finish = concurrent::wait_for_host(
+                                store.as_context_mut(),
+                                instance,
+                                caller_instance,
+                                future,
+                            )?;
+                            finish(store)
+                        },
+                    )
+                }
+            })
+        }
+    }
+
+    pub(crate) fn new_dynamic<T, F>(name: FuncName, func: F) -> Arc<HostFunc>
     where
         F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
     {
@@ -92,7 +213,40 @@ impl HostFunc {
             // not need to perform up-front type checks. Instead everything is
             // dynamically managed at runtime.
             typecheck: Box::new(move |_expected_index, _expected_types| Ok(())),
-            func: Box::new(func),
+            func: Box::new(Named { name, func }),
+        })
+    }
+
+    /// Same as [`HostFunc::new_dynamic`] except that `func` is known to have
+    /// the `params` and `results` types, which are checked up-front against
+    /// the type that it's imported with.
+    pub(crate) fn new_dynamic_typed<T, F>(
+        name: FuncName,
+        func: F,
+        params: Box<[Type]>,
+        results: Box<[Type]>,
+    ) -> Arc<HostFunc>
+    where
+        F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
+    {
+        Arc::new(HostFunc {
+            entrypoint: dynamic_entrypoint::<T, F>,
+            typecheck: Box::new(move |expected_index, expected_types| {
+                let expected = types::ComponentFunc::from(expected_index, expected_types);
+                ensure!(
+                    expected
+                        .params()
+                        .map(|(_, ty)| ty)
+                        .eq(params.iter().cloned()),
+                    "type mismatch with parameters"
+                );
+                ensure!(
+                    expected.results().eq(results.iter().cloned()),
+                    "type mismatch with results"
+                );
+                Ok(())
+            }),
+            func: Box::new(Named { name, func }),
         })
     }
 
@@ -153,6 +307,7 @@ unsafe fn call_host<T, Params, Return, F>(
     string_encoding: StringEncoding,
     async_: bool,
     storage: &mut [MaybeUninit<ValRaw>],
+    name: &FuncName,
     closure: F,
 ) -> Result<()>
 where
@@ -160,10 +315,6 @@ where
     Return: Lower,
     F: FnOnce(StoreContextMut<'_, T>, Params) -> Result<Return>,
 {
-    if async_ {
-        todo!()
-    }
-
     /// Representation of arguments to this function when a return pointer is in
     /// use, namely the argument list is followed by a single value which is the
     /// return pointer.
@@ -200,6 +351,32 @@ where
     let param_tys = InterfaceType::Tuple(ty.params);
     let result_tys = InterfaceType::Tuple(ty.results);
 
+    // Async-lowered imports always pass their parameters and results
+    // indirectly, and return a status code in place of results. A host
+    // function which isn't itself concurrent always completes immediately.
+    if async_ {
+        #[cfg(feature = "component-model-async")]
+        {
+            let params = lift_indirect::<Params>(
+                cx.0,
+                &options,
+                types,
+                instance,
+                param_tys,
+                storage[0].assume_init_ref(),
+            )?;
+            let retptr = storage[1].assume_init();
+            let ret = closure(cx.as_context_mut(), params)?;
+            flags.set_may_leave(false);
+            lower_indirect(cx, &options, types, instance, result_tys, &retptr, ret)?;
+            flags.set_may_leave(true);
+            storage[0] = MaybeUninit::new(ValRaw::u32(concurrent::status_done()));
+            return Ok(());
+        }
+        #[cfg(not(feature = "component-model-async"))]
+        unreachable!("async lowering requires the `component-model-async` feature");
+    }
+
     // There's a 2x2 matrix of whether parameters and results are stored on the
     // stack or on the heap. Each of the 4 branches here have a different
     // representation of the storage of arguments/returns.
@@ -209,6 +386,16 @@ where
     // trivially DCE'd by LLVM. Perhaps one day with enough const programming in
     // Rust we can make monomorphizations of this function codegen only one
     // branch, but today is not that day.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    let raw: *const [MaybeUninit<ValRaw>] = storage;
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    let start = intercept::before_call(&mut cx, name.call(CallKind::Import), |store| {
+        let mut cx = LiftContext::new(store, &options, types, instance).inspect(Inspect::Lifted);
+        intercept::lift_tuple(&mut cx, ty.params, MAX_FLAT_PARAMS, &*raw)
+    })?;
+    #[cfg(not(all(feature = "call-hook", feature = "std")))]
+    let _ = name;
+
     let mut storage: Storage<'_, Params, Return> = if Params::flatten_count() <= MAX_FLAT_PARAMS {
         if Return::flatten_count() <= MAX_FLAT_RESULTS {
             Storage::Direct(slice_to_storage_mut(storage))
@@ -228,12 +415,25 @@ where
 
     let ret = closure(cx.as_context_mut(), params)?;
     flags.set_may_leave(false);
-    let mut lower = LowerContext::new(cx, &options, types, instance);
+    let mut lower = LowerContext::new(cx.as_context_mut(), &options, types, instance);
     storage.lower_results(&mut lower, result_tys, ret)?;
     flags.set_may_leave(true);
 
     lower.exit_call()?;
 
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    intercept::after_call(&mut cx, name.call(CallKind::Import), start, |store| {
+        // Results are either stored flat or through a pointer which follows
+        // the parameters.
+        let results = match Params::flatten_count() {
+            _ if Return::flatten_count() <= MAX_FLAT_RESULTS => &*raw,
+            n if n <= MAX_FLAT_PARAMS => &(&*raw)[n..],
+            _ => &(&*raw)[1..],
+        };
+        let mut cx = LiftContext::new(store, &options, types, instance).inspect(Inspect::Lifted);
+        intercept::lift_tuple(&mut cx, ty.results, MAX_FLAT_RESULTS, results)
+    })?;
+
     return Ok(());
 
     enum Storage<'a, P: ComponentType, R: ComponentType> {
@@ -303,6 +503,123 @@ fn validate_inbounds<T: ComponentType>(memory: &[u8], ptr: &ValRaw) -> Result<us
     Ok(ptr)
 }
 
+/// Lifts parameters of type `ty` from the linear memory address `ptr`.
+#[cfg(feature = "component-model-async")]
+unsafe fn lift_indirect<P: Lift>(
+    store: &mut StoreOpaque,
+    options: &Options,
+    types: &Arc<ComponentTypes>,
+    instance: *mut ComponentInstance,
+    ty: InterfaceType,
+    ptr: &ValRaw,
+) -> Result<P> {
+    let mut cx = LiftContext::new(store, options, types, instance);
+    cx.enter_call();
+    let ptr = validate_inbounds::<P>(cx.memory(), ptr)?;
+    let bytes = &cx.memory()[ptr..][..P::SIZE32];
+    let params = P::load(&mut cx, ty, bytes)?;
+    cx.exit_call()?;
+    Ok(params)
+}
+
+/// Stores results of type `ty` to the linear memory address `ptr`.
+#[cfg(feature = "component-model-async")]
+unsafe fn lower_indirect<T, R: Lower>(
+    store: StoreContextMut<'_, T>,
+    options: &Options,
+    types: &Arc<ComponentTypes>,
+    instance: *mut ComponentInstance,
+    ty: InterfaceType,
+    ptr: &ValRaw,
+    ret: R,
+) -> Result<()> {
+    let mut cx = LowerContext::new(store, options, types, instance);
+    let ptr = validate_inbounds::<R>(cx.as_slice_mut(), ptr)?;
+    ret.store(&mut cx, ty, ptr)
+}
+
+/// Same as `call_host`, but for a host function added with
+/// `func_wrap_concurrent` and lowered with the async ABI.
+///
+/// The future returned by `closure` runs as a subtask of the caller, which is
+/// notified once the results have been stored.
+#[cfg(feature = "component-model-async")]
+unsafe fn call_host_concurrent<T, Params, Return, F, N, FN>(
+    instance: *mut ComponentInstance,
+    types: &Arc<ComponentTypes>,
+    mut cx: StoreContextMut<'_, T>,
+    ty: TypeFuncIndex,
+    mut flags: InstanceFlags,
+    memory: *mut VMMemoryDefinition,
+    realloc: *mut VMFuncRef,
+    string_encoding: StringEncoding,
+    caller_instance: RuntimeComponentInstanceIndex,
+    storage: &mut [MaybeUninit<ValRaw>],
+    closure: F,
+) -> Result<()>
+where
+    Params: Lift,
+    Return: Lower + 'static,
+    F: FnOnce(StoreContextMut<'_, T>, Params) -> FN,
+    FN: Future<Output = N> + Send + Sync + 'static,
+    N: FnOnce(StoreContextMut<'_, T>) -> Result<Return> + Send + Sync + 'static,
+{
+    let options = Options::new(
+        cx.0.id(),
+        NonNull::new(memory),
+        NonNull::new(realloc),
+        string_encoding,
+    );
+
+    if !flags.may_leave() {
+        bail!("cannot leave component instance");
+    }
+
+    let ty = &types[ty];
+    let param_tys = InterfaceType::Tuple(ty.params);
+    let result_tys = InterfaceType::Tuple(ty.results);
+    let params = lift_indirect::<Params>(
+        cx.0,
+        &options,
+        types,
+        instance,
+        param_tys,
+        storage[0].assume_init_ref(),
+    )?;
+    let retptr = storage[1].assume_init();
+    let future = closure(cx.as_context_mut(), params);
+
+    let types = types.clone();
+    let instance_ptr = SendSyncPtr::new(NonNull::new(instance).unwrap());
+    let handle = concurrent::start_host_call(
+        cx,
+        instance,
+        caller_instance,
+        future,
+        move |mut store, finish: N| {
+            let ret = finish(store.as_context_mut())?;
+            flags.set_may_leave(false);
+            lower_indirect(
+                store,
+                &options,
+                &types,
+                instance_ptr.as_ptr(),
+                result_tys,
+                &retptr,
+                ret,
+            )?;
+            flags.set_may_leave(true);
+            Ok(())
+        },
+    )?;
+    let status = match handle {
+        Some(handle) => concurrent::status_started(handle),
+        None => concurrent::status_done(),
+    };
+    storage[0] = MaybeUninit::new(ValRaw::u32(status));
+    Ok(())
+}
+
 unsafe fn call_host_and_handle_result<T>(
     cx: NonNull<VMOpaqueContext>,
     func: impl FnOnce(
@@ -336,14 +653,17 @@ unsafe fn call_host_dynamic<T, F>(
     string_encoding: StringEncoding,
     async_: bool,
     storage: &mut [MaybeUninit<ValRaw>],
+    name: &FuncName,
     closure: F,
 ) -> Result<()>
 where
     F: FnOnce(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()>,
 {
-    if async_ {
-        todo!()
-    }
+    #[cfg(not(feature = "component-model-async"))]
+    assert!(
+        !async_,
+        "async lowering requires the `component-model-async` feature"
+    );
 
     let options = Options::new(
         store.0.id(),
@@ -365,9 +685,30 @@ where
     let func_ty = &types[ty];
     let param_tys = &types[func_ty.params];
     let result_tys = &types[func_ty.results];
+
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    let start = if async_ {
+        None
+    } else {
+        let raw: *const [MaybeUninit<ValRaw>] = storage;
+        intercept::before_call(&mut store, name.call(CallKind::Import), |store| {
+            let mut cx =
+                LiftContext::new(store, &options, types, instance).inspect(Inspect::Lifted);
+            intercept::lift_tuple(&mut cx, func_ty.params, MAX_FLAT_PARAMS, &*raw)
+        })?
+    };
+    #[cfg(not(all(feature = "call-hook", feature = "std")))]
+    let _ = name;
+
     let mut cx = LiftContext::new(store.0, &options, types, instance);
     cx.enter_call();
-    if let Some(param_count) = param_tys.abi.flat_count(MAX_FLAT_PARAMS) {
+    // Async-lowered imports always pass their parameters and results
+    // indirectly.
+    let flat_params = param_tys
+        .abi
+        .flat_count(MAX_FLAT_PARAMS)
+        .filter(|_| !async_);
+    if let Some(param_count) = flat_params {
         // NB: can use `MaybeUninit::slice_assume_init_ref` when that's stable
         let mut iter =
             mem::transmute::<&[MaybeUninit<ValRaw>], &[ValRaw]>(&storage[..param_count]).iter();
@@ -401,8 +742,12 @@ where
     closure(store.as_context_mut(), &args, &mut result_vals)?;
     flags.set_may_leave(false);
 
-    let mut cx = LowerContext::new(store, &options, types, instance);
-    if let Some(cnt) = result_tys.abi.flat_count(MAX_FLAT_RESULTS) {
+    let mut cx = LowerContext::new(store.as_context_mut(), &options, types, instance);
+    let flat_results = result_tys
+        .abi
+        .flat_count(MAX_FLAT_RESULTS)
+        .filter(|_| !async_);
+    if let Some(cnt) = flat_results {
         let mut dst = storage[..cnt].iter_mut();
         for (val, ty) in result_vals.iter().zip(result_tys.types.iter()) {
             val.lower(&mut cx, *ty, &mut dst)?;
@@ -421,6 +766,25 @@ where
 
     cx.exit_call()?;
 
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    {
+        let raw: *const [MaybeUninit<ValRaw>] = storage;
+        let results = match flat_results {
+            Some(_) => &*raw,
+            None => &(&*raw)[ret_index..],
+        };
+        intercept::after_call(&mut store, name.call(CallKind::Import), start, |store| {
+            let mut cx =
+                LiftContext::new(store, &options, types, instance).inspect(Inspect::Lifted);
+            intercept::lift_tuple(&mut cx, func_ty.results, MAX_FLAT_RESULTS, results)
+        })?;
+    }
+
+    #[cfg(feature = "component-model-async")]
+    if async_ {
+        storage[0] = MaybeUninit::new(ValRaw::u32(concurrent::status_done()));
+    }
+
     return Ok(());
 }
 
@@ -456,7 +820,7 @@ extern "C" fn dynamic_entrypoint<T, F>(
 where
     F: Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
 {
-    let data = data.as_ptr() as *const F;
+    let data = data.as_ptr() as *const Named<F>;
     unsafe {
         call_host_and_handle_result(cx, |instance, types, store| {
             call_host_dynamic::<T, _>(
@@ -470,7 +834,8 @@ where
                 StringEncoding::from_u8(string_encoding).unwrap(),
                 async_ != 0,
                 NonNull::slice_from_raw_parts(storage, storage_len).as_mut(),
-                |store, params, results| (*data)(store, params, results),
+                &(*data).name,
+                |store, params, results| ((*data).func)(store, params, results),
             )
         })
     }
@@ -1,3 +1,5 @@
+#[cfg(all(feature = "call-hook", feature = "std"))]
+use crate::component::func::bad_type_info;
 use crate::component::matching::InstanceType;
 use crate::component::resources::{HostResourceData, HostResourceIndex, HostResourceTables};
 use crate::component::ResourceType;
@@ -10,6 +12,8 @@ use crate::store::{StoreId, StoreOpaque};
 use crate::{FuncType, StoreContextMut};
 use alloc::sync::Arc;
 use core::ptr::NonNull;
+#[cfg(all(feature = "call-hook", feature = "std"))]
+use wasmtime_environ::component::InterfaceType;
 use wasmtime_environ::component::{ComponentTypes, StringEncoding, TypeResourceTableIndex};
 
 /// Runtime representation of canonical ABI options in the component model.
@@ -222,6 +226,11 @@ impl<'a, T> LowerContext<'a, T> {
         }
     }
 
+    /// Returns the component instance raw pointer that is being lowered into.
+    pub fn instance_ptr(&self) -> *mut ComponentInstance {
+        self.instance
+    }
+
     /// Returns a view into memory as a mutable slice of bytes.
     ///
     /// # Panics
@@ -408,6 +417,29 @@ pub struct LiftContext<'a> {
     host_resource_data: &'a mut HostResourceData,
 
     calls: &'a mut CallContexts,
+
+    /// Remaining bytes of lists and strings which may be lifted, see
+    /// `ResourceLimiter::component_lift_bytes`.
+    lift_bytes: usize,
+
+    /// Whether values are only being inspected, see `LiftContext::inspect`.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    inspect: Option<Inspect>,
+
+    #[cfg(feature = "component-model-async")]
+    concurrent_state: &'a mut crate::component::concurrent::ConcurrentState,
+}
+
+/// Where values inspected by a `LiftContext` originate from.
+#[cfg(all(feature = "call-hook", feature = "std"))]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Inspect {
+    /// The values were lowered by a guest, for example as the arguments to an
+    /// imported function.
+    Lifted,
+    /// The values were lowered into a guest by the host, namely the arguments
+    /// to an exported function.
+    Lowered,
 }
 
 #[doc(hidden)]
@@ -431,8 +463,11 @@ impl<'a> LiftContext<'a> {
         // so it's hacked around a bit. This unsafe pointer cast could be fixed
         // with more methods in more places, but it doesn't seem worth doing it
         // at this time.
+        let lift_bytes = store.component_limits().lift_bytes;
         let (calls, host_table, host_resource_data) =
             (&mut *(store as *mut StoreOpaque)).component_resource_state();
+        #[cfg(feature = "component-model-async")]
+        let concurrent_state = (&mut *(store as *mut StoreOpaque)).concurrent_state_mut();
         let memory = options.memory.map(|_| options.memory(store));
 
         LiftContext {
@@ -443,6 +478,71 @@ impl<'a> LiftContext<'a> {
             calls,
             host_table,
             host_resource_data,
+            lift_bytes,
+            #[cfg(all(feature = "call-hook", feature = "std"))]
+            inspect: None,
+            #[cfg(feature = "component-model-async")]
+            concurrent_state,
+        }
+    }
+
+    /// Configures this context to only inspect values rather than lifting them
+    /// for use by the host.
+    ///
+    /// Resources are lifted without modifying the guest's tables and are
+    /// instead presented as host `borrow`s which must be dropped before the
+    /// current call scope exits. Lifted bytes are additionally not charged
+    /// against the store's limits.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    pub(crate) fn inspect(mut self, inspect: Inspect) -> Self {
+        self.inspect = Some(inspect);
+        self.lift_bytes = usize::MAX;
+        self
+    }
+
+    /// When inspecting values, returns the representation of the guest
+    /// resource handle `idx` of type `ty` without modifying any tables.
+    ///
+    /// Returns `None` if values aren't being inspected.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    pub(crate) fn guest_resource_inspect(
+        &mut self,
+        ty: InterfaceType,
+        idx: u32,
+    ) -> Result<Option<u32>> {
+        let Some(inspect) = self.inspect else {
+            return Ok(None);
+        };
+        let (t, borrow) = match ty {
+            InterfaceType::Own(t) => (t, false),
+            InterfaceType::Borrow(t) => (t, true),
+            _ => bad_type_info(),
+        };
+        // Mirror the special case of `guest_resource_lower_borrow` where a
+        // borrow lowered into the instance which defines the resource is
+        // its `rep`.
+        //
+        // Note that the unsafety here should be valid given the contract of
+        // `LiftContext::new`.
+        if borrow
+            && inspect == Inspect::Lowered
+            && unsafe { (*self.instance).resource_owned_by_own_instance(t) }
+        {
+            return Ok(Some(idx));
+        }
+        Ok(Some(self.resource_tables().guest_resource_rep(idx, t)?))
+    }
+
+    /// Charges `bytes` of a list or string being lifted against the limit on
+    /// bytes lifted in a single call, returning an error if it's exceeded.
+    pub(crate) fn charge_lift_bytes(&mut self, bytes: usize) -> Result<()> {
+        match self.lift_bytes.checked_sub(bytes) {
+            Some(remaining) => {
+                self.lift_bytes = remaining;
+                Ok(())
+            }
+            None => Err(crate::Trap::ComponentLimitExceeded)
+                .context("too many bytes of lists and strings lifted in a single call"),
         }
     }
 
@@ -468,6 +568,14 @@ impl<'a> LiftContext<'a> {
         self.instance
     }
 
+    /// Returns the store's state for async tasks, streams, and futures.
+    #[cfg(feature = "component-model-async")]
+    pub(crate) fn concurrent_state_mut(
+        &mut self,
+    ) -> &mut crate::component::concurrent::ConcurrentState {
+        self.concurrent_state
+    }
+
     /// Lifts an `own` resource from the guest at the `idx` specified into its
     /// representation.
     ///
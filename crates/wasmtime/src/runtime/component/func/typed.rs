@@ -18,7 +18,13 @@ use wasmtime_environ::component::{
 };
 
 #[cfg(feature = "component-model-async")]
-use crate::component::concurrent::Promise;
+use crate::{
+    component::{
+        concurrent::{self, Promise},
+        storage::slice_to_storage_mut,
+    },
+    store::StoreOpaque,
+};
 
 /// A statically-typed version of [`Func`] which takes `Params` as input and
 /// returns `Return`.
@@ -200,7 +206,7 @@ where
     /// made using this method may run concurrently with other calls to the same
     /// instance.
     #[cfg(feature = "component-model-async")]
-    pub async fn call_concurrent<T: Send>(
+    pub async fn call_concurrent<T: Send + 'static>(
         self,
         mut store: impl AsContextMut<Data = T>,
         params: Params,
@@ -214,8 +220,74 @@ where
             store.0.async_support(),
             "cannot use `call_concurrent` when async support is not enabled on the config"
         );
-        _ = params;
-        todo!()
+        let Some(call) = self.func.guest_call(store.0) else {
+            // Sync-lifted exports run to completion once the event loop gets
+            // to them.
+            return Ok(concurrent::queue_call::<T, _>(store.0, move |mut store| {
+                let result = self.call_impl(&mut store, params)?;
+                self.func.post_return_impl(&mut store)?;
+                Ok(result)
+            }));
+        };
+        Ok(concurrent::start_guest_call(
+            store,
+            call,
+            move |store| {
+                let store = unsafe { concurrent::store_context::<T>(store) };
+                self.lower_concurrent_args(store, &params)
+            },
+            move |store, values| self.lift_concurrent_result(store.store_opaque_mut(), values),
+        ))
+    }
+
+    /// Lowers the parameters of an async-lifted export, which are passed
+    /// directly if they fit in `MAX_FLAT_PARAMS` and through the heap
+    /// otherwise.
+    #[cfg(feature = "component-model-async")]
+    fn lower_concurrent_args<T>(
+        &self,
+        store: StoreContextMut<'_, T>,
+        params: &Params,
+    ) -> Result<Vec<ValRaw>> {
+        let (options, types, instance_ptr, ty) = self.func.lowering_info(store.0);
+        let mut cx = unsafe { LowerContext::new(store, &options, &types, instance_ptr) };
+        let ty = InterfaceType::Tuple(types[ty].params);
+        if Params::flatten_count() <= MAX_FLAT_PARAMS {
+            let mut dst = MaybeUninit::<Params::Lower>::uninit();
+            Self::lower_stack_args(&mut cx, params, ty, &mut dst)?;
+            Ok(unsafe { storage_as_slice(dst.assume_init_ref()) }.to_vec())
+        } else {
+            let mut dst = MaybeUninit::uninit();
+            Self::lower_heap_args(&mut cx, params, ty, &mut dst)?;
+            Ok(vec![unsafe { dst.assume_init() }])
+        }
+    }
+
+    /// Lifts the values passed to `task.return` by an async-lifted export,
+    /// which are passed directly if they fit in `MAX_FLAT_PARAMS` and through
+    /// the heap otherwise.
+    #[cfg(feature = "component-model-async")]
+    fn lift_concurrent_result(&self, store: &mut StoreOpaque, values: &[ValRaw]) -> Result<Return> {
+        let (options, types, instance_ptr, ty) = self.func.lowering_info(store);
+        let mut cx = unsafe { LiftContext::new(store, &options, &types, instance_ptr) };
+        let ty = InterfaceType::Tuple(types[ty].results);
+        if Return::flatten_count() <= MAX_FLAT_PARAMS {
+            if values.len() != Return::flatten_count() {
+                bail!("invalid number of values passed to `task.return`");
+            }
+            let mut src = values
+                .iter()
+                .copied()
+                .map(MaybeUninit::new)
+                .collect::<Vec<_>>();
+            let src = unsafe { slice_to_storage_mut::<Return::Lower>(&mut src).assume_init_ref() };
+            Return::lift(&mut cx, ty, src)
+        } else {
+            let [ptr] = values else {
+                bail!("invalid number of values passed to `task.return`");
+            };
+            Self::lift_heap_result(&mut cx, ty, ptr)
+        }
     }
 
     fn call_impl(&self, mut store: impl AsContextMut, params: Params) -> Result<Return> {
@@ -1297,7 +1369,7 @@ impl WasmStr {
             }
         };
         match byte_len.and_then(|len| ptr.checked_add(len)) {
-            Some(n) if n <= cx.memory().len() => {}
+            Some(n) if n <= cx.memory().len() => cx.charge_lift_bytes(n - ptr)?,
             _ => bail!("string pointer/length out of bounds of memory"),
         }
         Ok(WasmStr {
@@ -1542,7 +1614,7 @@ impl<T: Lift> WasmList<T> {
             .checked_mul(T::SIZE32)
             .and_then(|len| ptr.checked_add(len))
         {
-            Some(n) if n <= cx.memory().len() => {}
+            Some(n) if n <= cx.memory().len() => cx.charge_lift_bytes(n - ptr)?,
             _ => bail!("list pointer/length out of bounds of memory"),
         }
         if ptr % usize::try_from(T::ALIGN32)? != 0 {
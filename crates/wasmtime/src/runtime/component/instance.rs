@@ -1,3 +1,5 @@
+#[cfg(all(feature = "call-hook", feature = "std"))]
+use crate::component::func::FuncName;
 use crate::component::func::HostFunc;
 use crate::component::matching::InstanceType;
 use crate::component::{
@@ -6,7 +8,7 @@ use crate::component::{
 use crate::instance::OwnedImports;
 use crate::linker::DefinitionType;
 use crate::prelude::*;
-use crate::runtime::vm::component::{ComponentInstance, OwnedComponentInstance};
+use crate::runtime::vm::component::{ComponentInstance, InstanceFlags, OwnedComponentInstance};
 use crate::runtime::vm::{CompiledModuleId, VMFuncRef};
 use crate::store::{StoreOpaque, Stored};
 use crate::{AsContextMut, Engine, Module, StoreContextMut};
@@ -171,7 +173,7 @@ impl Instance {
         let ret = name.lookup(&data.component).and_then(|index| {
             match &data.component.env_component().export_items[index] {
                 Export::LiftedFunction { ty, func, options } => Some(Func::from_lifted_func(
-                    store, self, &data, *ty, func, options,
+                    store, self, &data, index, *ty, func, options,
                 )),
                 _ => None,
             }
@@ -308,6 +310,117 @@ impl Instance {
         })
     }
 
+    /// Returns the name of the function exported at `index`, along with the
+    /// path of exported instances it's found in.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    pub(crate) fn export_func_name(&self, store: &StoreOpaque, index: ExportIndex) -> FuncName {
+        fn find<'a>(
+            info: &'a wasmtime_environ::component::Component,
+            exports: &'a NameMap<String, ExportIndex>,
+            index: ExportIndex,
+            path: &mut Vec<&'a str>,
+        ) -> Option<&'a str> {
+            for (name, i) in exports.raw_iter() {
+                if *i == index {
+                    return Some(name);
+                }
+                if let Export::Instance { exports, .. } = &info.export_items[*i] {
+                    path.push(name);
+                    if let Some(name) = find(info, exports, index, path) {
+                        return Some(name);
+                    }
+                    path.pop();
+                }
+            }
+            None
+        }
+
+        let data = store[self.0].as_ref().unwrap();
+        let info = data.component.env_component();
+        let mut path = Vec::new();
+        let name = find(info, &info.exports, index, &mut path).unwrap_or("");
+        FuncName {
+            interface: (!path.is_empty()).then(|| path.join("/").into()),
+            name: name.into(),
+        }
+    }
+
+    /// Returns the names and indices of the exports of this instance, or of
+    /// the nested exported `instance` if specified.
+    ///
+    /// Returns `None` if `instance` isn't an exported instance.
+    pub(crate) fn exports(
+        &self,
+        store: &StoreOpaque,
+        instance: Option<&ComponentExportIndex>,
+    ) -> Option<Vec<(String, ComponentExportIndex)>> {
+        let data = store[self.0].as_ref().unwrap();
+        let info = data.component.env_component();
+        let exports = match instance {
+            Some(idx) => {
+                if idx.id != data.component_id() {
+                    return None;
+                }
+                match &info.export_items[idx.index] {
+                    Export::Instance { exports, .. } => exports,
+                    _ => return None,
+                }
+            }
+            None => &info.exports,
+        };
+        Some(
+            exports
+                .raw_iter()
+                .map(|(name, index)| {
+                    let index = ComponentExportIndex {
+                        id: data.component_id(),
+                        index: *index,
+                    };
+                    (name.clone(), index)
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the destructor of the exported resource `export` along with the
+    /// flags of the instance which defines it, if it's defined by a component.
+    pub(crate) fn resource_dtor_and_flags(
+        &self,
+        store: &StoreOpaque,
+        export: &ComponentExportIndex,
+    ) -> Option<(Option<NonNull<VMFuncRef>>, Option<InstanceFlags>)> {
+        let (data, export, _) = self.lookup_export(store, export)?;
+        match export {
+            Export::Type(TypeDef::Resource(id)) => Some(data.instance().dtor_and_flags(*id)),
+            _ => None,
+        }
+    }
+
+    /// Finds the export of the resource `ty`, returning the instance it's
+    /// exported from and its name.
+    pub(crate) fn find_resource(
+        &self,
+        store: &StoreOpaque,
+        instance: Option<ComponentExportIndex>,
+        ty: ResourceType,
+    ) -> Option<(Option<ComponentExportIndex>, String)> {
+        let data = store[self.0].as_ref().unwrap();
+        for (name, index) in self.exports(store, instance.as_ref())? {
+            match &data.component.env_component().export_items[index.index] {
+                Export::Type(TypeDef::Resource(id)) if data.ty().resource_type(*id) == ty => {
+                    return Some((instance, name));
+                }
+                Export::Instance { .. } => {
+                    if let Some(found) = self.find_resource(store, Some(index), ty) {
+                        return Some(found);
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
     fn lookup_export<'a>(
         &self,
         store: &'a StoreOpaque,
@@ -414,6 +527,14 @@ impl InstanceData {
         instance.get_export_by_index(idx)
     }
 
+    pub fn core_instances(&self) -> &PrimaryMap<RuntimeInstanceIndex, crate::Instance> {
+        &self.instances
+    }
+
+    pub fn component(&self) -> &Component {
+        &self.component
+    }
+
     #[inline]
     pub fn instance(&self) -> &ComponentInstance {
         &self.state
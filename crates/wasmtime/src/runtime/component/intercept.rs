@@ -0,0 +1,246 @@
+use crate::component::func::{FuncName, LiftContext};
+use crate::component::linker::release_borrows;
+use crate::component::resources::HostResourceTables;
+use crate::component::Val;
+use crate::prelude::*;
+use crate::store::StoreOpaque;
+use crate::{AsContextMut, StoreContextMut, ValRaw};
+use core::mem::{self, MaybeUninit};
+use core::time::Duration;
+use std::time::Instant;
+use wasmtime_environ::component::TypeTupleIndex;
+
+/// The direction of a call observed by a [`CallInterceptor`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CallKind {
+    /// The host is calling a function exported by a component instance.
+    Export,
+    /// A component instance is calling a function imported from the host.
+    Import,
+}
+
+/// Describes a call observed by a [`CallInterceptor`].
+#[derive(Copy, Clone, Debug)]
+pub struct InterceptedCall<'a> {
+    kind: CallKind,
+    interface: Option<&'a str>,
+    name: &'a str,
+}
+
+impl<'a> InterceptedCall<'a> {
+    /// Returns whether this is a call of an export or of an import.
+    pub fn kind(&self) -> CallKind {
+        self.kind
+    }
+
+    /// Returns the name of the instance, typically a WIT interface such as
+    /// `wasi:cli/stdout@0.2.0`, that the function belongs to.
+    ///
+    /// This is `None` for functions exported or imported at the root of a
+    /// component. For imports this is the path of
+    /// [`LinkerInstance`](crate::component::LinkerInstance)s the function was
+    /// defined in, joined with `/`.
+    pub fn interface(&self) -> Option<&'a str> {
+        self.interface
+    }
+
+    /// Returns the name of the function being called.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+}
+
+/// A host-defined observer of calls crossing the boundary between the host and
+/// component instances.
+///
+/// An interceptor is configured with
+/// [`Store::component_call_interceptor`](crate::Store::component_call_interceptor)
+/// and is invoked for both calls of exported functions, through [`Func`] or
+/// [`TypedFunc`], and calls of functions imported from a
+/// [`Linker`](crate::component::Linker), including those generated by
+/// [`bindgen!`](crate::component::bindgen). This makes it suitable for tracing,
+/// auditing, or enforcing policies on calls without wrapping each host
+/// function by hand.
+///
+/// Arguments and results are presented as [`Val`]s lifted from the canonical
+/// ABI representation of the call, independently of whether the function is
+/// typed or dynamically typed. Resources within these values are presented as
+/// `borrow` handles which are only valid for the duration of the callback they
+/// are passed to and are released afterwards.
+///
+/// Note that calls made by the interceptor itself are not intercepted, and
+/// that concurrent calls of async-lifted exports and async-lowered imports are
+/// not intercepted at this time.
+///
+/// [`Func`]: crate::component::Func
+/// [`TypedFunc`]: crate::component::TypedFunc
+pub trait CallInterceptor<T>: Send + Sync {
+    /// Invoked before the function described by `call` is called with
+    /// `params`.
+    ///
+    /// Returning an error aborts the call with that error. For exports this is
+    /// treated like a trap within the component instance being called, which
+    /// means that the instance can't be entered again afterwards.
+    fn before_call(
+        &mut self,
+        store: StoreContextMut<'_, T>,
+        call: InterceptedCall<'_>,
+        params: &[Val],
+    ) -> Result<()> {
+        let _ = (store, call, params);
+        Ok(())
+    }
+
+    /// Invoked after the function described by `call` has returned `results`,
+    /// which took `elapsed` to execute.
+    ///
+    /// Returning an error causes the call to fail with that error instead of
+    /// returning its results.
+    fn after_call(
+        &mut self,
+        store: StoreContextMut<'_, T>,
+        call: InterceptedCall<'_>,
+        results: &[Val],
+        elapsed: Duration,
+    ) -> Result<()> {
+        let _ = (store, call, results, elapsed);
+        Ok(())
+    }
+}
+
+impl FuncName {
+    pub(crate) fn call(&self, kind: CallKind) -> InterceptedCall<'_> {
+        InterceptedCall {
+            kind,
+            interface: self.interface.as_deref(),
+            name: &self.name,
+        }
+    }
+}
+
+/// Invokes [`CallInterceptor::before_call`] of the store's interceptor, if
+/// any, with the parameters lifted by `inspect`.
+///
+/// Returns when the call started for a subsequent `after_call`, or `None` if
+/// the store has no interceptor.
+pub(crate) fn before_call<T>(
+    store: &mut StoreContextMut<'_, T>,
+    call: InterceptedCall<'_>,
+    inspect: impl FnOnce(&mut StoreOpaque) -> Result<Vec<Val>>,
+) -> Result<Option<Instant>> {
+    let Some(mut interceptor) = store.0.take_component_call_interceptor() else {
+        return Ok(None);
+    };
+    let result = with_inspected(store, inspect, |store, params| {
+        interceptor.before_call(store, call, params)
+    });
+    store.0.restore_component_call_interceptor(interceptor);
+    result?;
+    Ok(Some(Instant::now()))
+}
+
+/// Invokes [`CallInterceptor::after_call`] of the store's interceptor with the
+/// results lifted by `inspect` if `start` indicates that `before_call` was
+/// previously invoked.
+pub(crate) fn after_call<T>(
+    store: &mut StoreContextMut<'_, T>,
+    call: InterceptedCall<'_>,
+    start: Option<Instant>,
+    inspect: impl FnOnce(&mut StoreOpaque) -> Result<Vec<Val>>,
+) -> Result<()> {
+    let Some(start) = start else {
+        return Ok(());
+    };
+    let elapsed = start.elapsed();
+    let Some(mut interceptor) = store.0.take_component_call_interceptor() else {
+        return Ok(());
+    };
+    let result = with_inspected(store, inspect, |store, results| {
+        interceptor.after_call(store, call, results, elapsed)
+    });
+    store.0.restore_component_call_interceptor(interceptor);
+    result
+}
+
+/// Runs `f` with values lifted by `inspect` within a call scope of their own,
+/// releasing the `borrow`s created for resources within them afterwards.
+fn with_inspected<T>(
+    store: &mut StoreContextMut<'_, T>,
+    inspect: impl FnOnce(&mut StoreOpaque) -> Result<Vec<Val>>,
+    f: impl FnOnce(StoreContextMut<'_, T>, &[Val]) -> Result<()>,
+) -> Result<()> {
+    HostResourceTables::new_host(store.0).enter_call();
+    let vals = match inspect(store.0) {
+        Ok(vals) => vals,
+        Err(e) => {
+            // Pop the scope entered above even though values lifted before
+            // the error may have left borrows behind in it.
+            let _ = HostResourceTables::new_host(store.0).exit_call();
+            return Err(e);
+        }
+    };
+    let result = f(store.as_context_mut(), &vals);
+    for val in vals.iter() {
+        // Errors are ignored here as the interceptor may have already dropped
+        // the borrow itself.
+        let _ = release_borrows(store, val);
+    }
+    HostResourceTables::new_host(store.0).exit_call()?;
+    result
+}
+
+/// Lifts the values of the tuple `ty` from `storage` with `cx`, which is
+/// expected to be configured with `LiftContext::inspect`.
+///
+/// The values are either stored flat within `storage` if there are no more
+/// than `max_flat` of them, or otherwise `storage` starts with a pointer to
+/// them in linear memory.
+///
+/// # Unsafety
+///
+/// The used prefix of `storage` must be initialized.
+pub(crate) unsafe fn lift_tuple(
+    cx: &mut LiftContext<'_>,
+    ty: TypeTupleIndex,
+    max_flat: usize,
+    storage: &[MaybeUninit<ValRaw>],
+) -> Result<Vec<Val>> {
+    let types = cx.types;
+    let ty = &types[ty];
+    if let Some(count) = ty.abi.flat_count(max_flat) {
+        let flat = mem::transmute::<&[MaybeUninit<ValRaw>], &[ValRaw]>(&storage[..count]);
+        let mut flat = flat.iter();
+        return ty
+            .types
+            .iter()
+            .map(|ty| Val::lift(cx, *ty, &mut flat))
+            .collect();
+    }
+
+    // Components can't use 64-bit linear memories yet, they're rejected when
+    // a component is validated, so the pointer here is always 32-bit. Reading
+    // an `i64` for 64-bit memories will be required here, as elsewhere in
+    // lifting, once that restriction is lifted (#4311).
+    let ptr = usize::try_from(storage[0].assume_init_ref().get_u32())?;
+    if ptr % usize::try_from(ty.abi.align32)? != 0 {
+        bail!("pointer not aligned");
+    }
+    let memory = cx.memory();
+    let bytes = memory
+        .get(ptr..)
+        .and_then(|b| b.get(..usize::try_from(ty.abi.size32).unwrap()))
+        .ok_or_else(|| anyhow!("pointer out of bounds of memory"))?;
+    let mut offset = 0;
+    ty.types
+        .iter()
+        .map(|ty| {
+            let abi = types.canonical_abi(ty);
+            let offset = abi.next_field32_size(&mut offset);
+            Val::load(
+                cx,
+                *ty,
+                &bytes[offset..][..usize::try_from(abi.size32).unwrap()],
+            )
+        })
+        .collect()
+}
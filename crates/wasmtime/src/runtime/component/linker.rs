@@ -1,15 +1,18 @@
-use crate::component::func::HostFunc;
+use crate::component::func::{FuncName, HostFunc};
 use crate::component::instance::RuntimeImport;
 use crate::component::matching::{InstanceType, TypeChecker};
 use crate::component::types;
 use crate::component::{
-    Component, ComponentNamedList, Instance, InstancePre, Lift, Lower, ResourceType, Val,
+    Component, ComponentExportIndex, ComponentNamedList, Func, Instance, InstancePre, Lift, Lower,
+    ResourceType, Val,
 };
 use crate::hash_map::HashMap;
 use crate::prelude::*;
-use crate::{AsContextMut, Engine, Module, StoreContextMut};
+use crate::runtime::vm::SendSyncPtr;
+use crate::{AsContextMut, Engine, Module, StoreContextMut, Trap, ValRaw};
 use alloc::sync::Arc;
 use core::marker;
+use core::ptr::NonNull;
 #[cfg(feature = "async")]
 use core::{future::Future, pin::Pin};
 use wasmtime_environ::component::{NameMap, NameMapIntern};
@@ -158,6 +161,138 @@ impl<T> Linker<T> {
         self.root().into_instance(name)
     }
 
+    /// Defines all exports of the component `instance` within the root
+    /// namespace of this linker.
+    ///
+    /// This is a convenience for [`LinkerInstance::define_component_instance`]
+    /// on [`Linker::root`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the exports of `instance` are already
+    /// defined within this linker.
+    pub fn define_component_instance(
+        &mut self,
+        store: impl AsContextMut<Data = T>,
+        instance: &Instance,
+    ) -> Result<()> {
+        self.root().define_component_instance(store, instance)
+    }
+
+    /// Defines the instance `name` within this linker using the exports of the
+    /// instance exported from `instance` at `export`.
+    ///
+    /// This is a convenience for [`LinkerInstance::instance_from`] on
+    /// [`Linker::root`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is already defined within this linker or if
+    /// `export` doesn't refer to an instance exported from `instance`.
+    pub fn instance_from(
+        &mut self,
+        store: impl AsContextMut<Data = T>,
+        name: &str,
+        instance: &Instance,
+        export: &ComponentExportIndex,
+    ) -> Result<()> {
+        self.root().instance_from(store, name, instance, export)
+    }
+
+    /// Replaces definitions of `interface` within the root namespace of this
+    /// linker with the matching exports of the component `instance`.
+    ///
+    /// This is used to "virtualize" an interface, typically one otherwise
+    /// implemented by the host such as a WASI interface, with guest code.
+    /// Components subsequently instantiated with this linker have their
+    /// imports of `interface` satisfied by `instance` while all other imports
+    /// continue to be satisfied by the existing definitions of this linker.
+    /// The virtualizing component is typically itself instantiated with this
+    /// linker beforehand so that it may be implemented in terms of the
+    /// original definitions of `interface`.
+    ///
+    /// The `interface` is either the name of an interface, such as
+    /// `wasi:filesystem/types`, or of a package, such as `wasi:filesystem`, in
+    /// which case the interfaces within that package exported by `instance`
+    /// are replaced. Only what `instance` exports is replaced: interfaces of
+    /// the package which it doesn't export, and functions and other items of
+    /// an interface which it doesn't export, keep their existing definitions.
+    /// Versions are ignored when matching names: all versions of a replaced
+    /// interface previously defined in this linker are removed, with their
+    /// items not exported by `instance` carried over, and all versions
+    /// exported by `instance` are defined. The exports of `instance` are
+    /// defined in the same manner as
+    /// [`LinkerInstance::define_component_instance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `instance` doesn't export anything matching
+    /// `interface`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own `instance`.
+    pub fn virtualize(
+        &mut self,
+        mut store: impl AsContextMut<Data = T>,
+        interface: &str,
+        instance: &Instance,
+    ) -> Result<()> {
+        let mut store = store.as_context_mut();
+        let exports = instance
+            .exports(store.0, None)
+            .expect("root exports are always available")
+            .into_iter()
+            .filter(|(name, _)| virtualizes(interface, name))
+            .collect::<Vec<_>>();
+        if exports.is_empty() {
+            bail!("component instance does not export anything matching `{interface}`");
+        }
+
+        // Remove the existing definitions of the interfaces exported by
+        // `instance`, leaving any others within a package in place.
+        let strings = &self.strings.strings;
+        let mut replaced = Vec::new();
+        self.map.raw_retain(|name, def| {
+            let name = &strings[*name];
+            if !exports
+                .iter()
+                .any(|(export, _)| unversioned(export) == unversioned(name))
+            {
+                return true;
+            }
+            replaced.push((name.clone(), def.clone()));
+            false
+        });
+
+        for (name, export) in exports.iter() {
+            self.root()
+                .define_export(store.as_context_mut(), instance, name, export)?;
+
+            // Items of a replaced interface which `instance` doesn't export
+            // continue to be provided by their previous definitions.
+            let key = self.strings.lookup(name);
+            let Some(Definition::Instance(map)) = key.and_then(|k| self.map.raw_get_mut(&k)) else {
+                continue;
+            };
+            for (prev, def) in replaced.iter() {
+                if unversioned(prev) != unversioned(name) {
+                    continue;
+                }
+                let Definition::Instance(prev) = def else {
+                    continue;
+                };
+                for (item, def) in prev.raw_iter() {
+                    if map.raw_get_mut(item).is_none() {
+                        let item = self.strings.strings[*item].clone();
+                        map.insert(&item, &mut self.strings, false, def.clone())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn typecheck<'a>(&'a self, component: &'a Component) -> Result<TypeChecker<'a>> {
         let mut cx = TypeChecker {
             engine: &self.engine,
@@ -407,7 +542,10 @@ impl<T> LinkerInstance<'_, T> {
         Params: ComponentNamedList + Lift + 'static,
         Return: ComponentNamedList + Lower + 'static,
     {
-        self.insert(name, Definition::Func(HostFunc::from_closure(func)))?;
+        self.insert(
+            name,
+            Definition::Func(HostFunc::from_closure(self.func_name(name), func)),
+        )?;
         Ok(())
     }
 
@@ -461,6 +599,7 @@ impl<T> LinkerInstance<'_, T> {
     #[cfg(feature = "component-model-async")]
     pub fn func_wrap_concurrent<Params, Return, F, N, FN>(&mut self, name: &str, f: F) -> Result<()>
     where
+        T: 'static,
         N: FnOnce(StoreContextMut<T>) -> Result<Return> + Send + Sync + 'static,
         FN: Future<Output = N> + Send + Sync + 'static,
         F: Fn(StoreContextMut<T>, Params) -> FN + Send + Sync + 'static,
@@ -471,8 +610,11 @@ impl<T> LinkerInstance<'_, T> {
             self.engine.config().async_support,
             "cannot use `func_wrap_concurrent` without enabling async support in the config"
         );
-        _ = (name, f);
-        todo!()
+        self.insert(
+            name,
+            Definition::Func(HostFunc::from_concurrent(self.func_name(name), f)),
+        )?;
+        Ok(())
     }
 
     /// Define a new host-provided function using dynamically typed values.
@@ -579,7 +721,10 @@ impl<T> LinkerInstance<'_, T> {
         name: &str,
         func: impl Fn(StoreContextMut<'_, T>, &[Val], &mut [Val]) -> Result<()> + Send + Sync + 'static,
     ) -> Result<()> {
-        self.insert(name, Definition::Func(HostFunc::new_dynamic(func)))?;
+        self.insert(
+            name,
+            Definition::Func(HostFunc::new_dynamic(self.func_name(name), func)),
+        )?;
         Ok(())
     }
 
@@ -688,6 +833,239 @@ impl<T> LinkerInstance<'_, T> {
         Ok(())
     }
 
+    /// Defines all exports of the component `instance` within this instance.
+    ///
+    /// This is used to satisfy the imports of one component with the exports
+    /// of another, already-instantiated, component, composing the two at
+    /// runtime. Exported functions are defined as host functions which lift
+    /// their arguments from the caller and lower them into `instance`, and
+    /// vice-versa for results. Exported resources are defined with the same
+    /// [`ResourceType`] so handles can flow between the two components, and
+    /// dropping an owned handle runs the destructor of `instance`. Exported
+    /// modules are defined as-is and exported instances are defined
+    /// recursively.
+    ///
+    /// Definitions created here are tied to `store`: components importing
+    /// them must be instantiated within the same store as `instance`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the exports of `instance` are already
+    /// defined within this instance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own `instance`.
+    pub fn define_component_instance(
+        &mut self,
+        mut store: impl AsContextMut<Data = T>,
+        instance: &Instance,
+    ) -> Result<()> {
+        self.define_exports(store.as_context_mut(), instance, None)
+    }
+
+    /// Defines the nested instance `name` within this instance using the
+    /// exports of the instance exported from `instance` at `export`.
+    ///
+    /// This is commonly used to satisfy an interface import of one component
+    /// with the same interface exported from another, for example:
+    ///
+    /// ```
+    /// # use wasmtime::component::{Component, Linker};
+    /// # use wasmtime::{Engine, Store};
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let provider = Component::new(&engine, r#"
+    ///     (component
+    ///         (core module $m
+    ///             (func (export "double") (param i32) (result i32)
+    ///                 (i32.add (local.get 0) (local.get 0)))
+    ///         )
+    ///         (core instance $i (instantiate $m))
+    ///         (func $double (param "x" u32) (result u32)
+    ///             (canon lift (core func $i "double")))
+    ///         (instance (export "math") (export "double" (func $double)))
+    ///     )
+    /// "#)?;
+    /// let consumer = Component::new(&engine, r#"
+    ///     (component
+    ///         (import "math" (instance $math
+    ///             (export "double" (func (param "x" u32) (result u32)))
+    ///         ))
+    ///         (alias export $math "double" (func $double))
+    ///         (core func $double (canon lower (func $double)))
+    ///         (core module $m
+    ///             (import "" "double" (func $double (param i32) (result i32)))
+    ///             (func (export "quadruple") (param i32) (result i32)
+    ///                 (call $double (call $double (local.get 0))))
+    ///         )
+    ///         (core instance $i (instantiate $m
+    ///             (with "" (instance (export "double" (func $double))))
+    ///         ))
+    ///         (func (export "quadruple") (param "x" u32) (result u32)
+    ///             (canon lift (core func $i "quadruple")))
+    ///     )
+    /// "#)?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let mut linker = Linker::new(&engine);
+    /// let provider = linker.instantiate(&mut store, &provider)?;
+    /// let math = provider.get_export(&mut store, None, "math").unwrap();
+    /// linker.instance_from(&mut store, "math", &provider, &math)?;
+    ///
+    /// let consumer = linker.instantiate(&mut store, &consumer)?;
+    /// let quadruple = consumer.get_typed_func::<(u32,), (u32,)>(&mut store, "quadruple")?;
+    /// assert_eq!(quadruple.call(&mut store, (10,))?, (40,));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// See [`LinkerInstance::define_component_instance`] for more information.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` is already defined within this instance or
+    /// if `export` doesn't refer to an instance exported from `instance`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own `instance`.
+    pub fn instance_from(
+        &mut self,
+        mut store: impl AsContextMut<Data = T>,
+        name: &str,
+        instance: &Instance,
+        export: &ComponentExportIndex,
+    ) -> Result<()> {
+        let store = store.as_context_mut();
+        if instance.exports(store.0, Some(export)).is_none() {
+            bail!("export is not an instance exported from the component instance");
+        }
+        self.instance(name)?
+            .define_exports(store, instance, Some(export))
+    }
+
+    fn define_exports(
+        &mut self,
+        mut store: StoreContextMut<'_, T>,
+        instance: &Instance,
+        parent: Option<&ComponentExportIndex>,
+    ) -> Result<()> {
+        let exports = instance
+            .exports(store.0, parent)
+            .expect("parent should be an exported instance");
+        for (name, export) in exports {
+            self.define_export(store.as_context_mut(), instance, &name, &export)?;
+        }
+        Ok(())
+    }
+
+    fn define_export(
+        &mut self,
+        mut store: StoreContextMut<'_, T>,
+        instance: &Instance,
+        name: &str,
+        export: &ComponentExportIndex,
+    ) -> Result<()> {
+        if let Some(func) = instance.get_func(&mut store, export) {
+            self.define_func_from(store, name, func)?;
+        } else if let Some(ty) = instance.get_resource(&mut store, export) {
+            self.define_resource_from(store, name, instance, export, ty)?;
+        } else if let Some(module) = instance.get_module(&mut store, export) {
+            self.module(name, &module)?;
+        } else if instance.exports(store.0, Some(export)).is_some() {
+            self.instance(name)?
+                .define_exports(store, instance, Some(export))?;
+        }
+        // Other exported types aren't represented in a linker.
+        Ok(())
+    }
+
+    fn define_func_from(
+        &mut self,
+        store: StoreContextMut<'_, T>,
+        name: &str,
+        func: Func,
+    ) -> Result<()> {
+        let id = store.0.id();
+        let params = func
+            .params(&store)
+            .iter()
+            .map(|(_, ty)| ty.clone())
+            .collect();
+        let results = func.results(&store);
+        let forward =
+            move |mut store: StoreContextMut<'_, T>, params: &[Val], results: &mut [Val]| {
+                ensure!(
+                    store.0.id() == id,
+                    "cannot call a function of a component instance from a different store"
+                );
+                func.call_impl(&mut store, params, results)?;
+                func.post_return_impl(&mut store)?;
+
+                // Borrows in `params` were lent to the host for the duration
+                // of this call, so they must be released before returning.
+                params
+                    .iter()
+                    .try_for_each(|param| release_borrows(&mut store, param))
+            };
+        self.insert(
+            name,
+            Definition::Func(HostFunc::new_dynamic_typed(
+                self.func_name(name),
+                forward,
+                params,
+                results,
+            )),
+        )?;
+        Ok(())
+    }
+
+    fn define_resource_from(
+        &mut self,
+        store: StoreContextMut<'_, T>,
+        name: &str,
+        instance: &Instance,
+        export: &ComponentExportIndex,
+        ty: ResourceType,
+    ) -> Result<()> {
+        let id = store.0.id();
+        let (dtor, flags) = instance.resource_dtor_and_flags(store.0, export).unwrap();
+        let dtor = dtor.map(SendSyncPtr::new);
+        let dtor = Arc::new(crate::func::HostFunc::wrap_inner(
+            &self.engine,
+            move |mut cx: crate::Caller<'_, T>, (rep,): (u32,)| {
+                let mut store = cx.as_context_mut();
+                ensure!(
+                    store.0.id() == id,
+                    "cannot drop a resource of a component instance from a different store"
+                );
+
+                // Perform the same reentrance check as dropping the resource
+                // directly within the instance which defined it would.
+                if let Some(flags) = flags {
+                    if unsafe { !flags.may_enter() } {
+                        bail!(Trap::CannotEnterComponent);
+                    }
+                }
+                let dtor = match dtor {
+                    Some(dtor) => dtor.as_non_null(),
+                    None => return Ok(()),
+                };
+                let mut args = [ValRaw::u32(rep)];
+
+                // This should be safe because `dtor` belongs to the store, as
+                // checked above, and destructors are guaranteed to take one
+                // i32 argument and return no results.
+                unsafe {
+                    crate::Func::call_unchecked_raw(&mut store, dtor, NonNull::from(&mut args))
+                }
+            },
+        ));
+        self.insert(name, Definition::Resource(ty, dtor))?;
+        Ok(())
+    }
+
     /// Defines a nested instance within this instance.
     ///
     /// This can be used to describe arbitrarily nested levels of instances
@@ -710,6 +1088,22 @@ impl<T> LinkerInstance<'_, T> {
         Ok(self)
     }
 
+    /// Returns the name of the function `name` defined in this instance.
+    fn func_name(&self, name: &str) -> FuncName {
+        let path = &self.path[..self.path_len];
+        let interface = (!path.is_empty()).then(|| {
+            path.iter()
+                .map(|idx| &*self.strings.strings[*idx])
+                .collect::<Vec<_>>()
+                .join("/")
+                .into()
+        });
+        FuncName {
+            interface,
+            name: name.into(),
+        }
+    }
+
     fn insert(&mut self, name: &str, item: Definition) -> Result<usize> {
         self.map
             .insert(name, self.strings, self.allow_shadowing, item)
@@ -738,3 +1132,35 @@ impl NameMapIntern for Strings {
         self.string2idx.get(string).cloned()
     }
 }
+
+/// Releases the `borrow` handles within `val`, which were lent to the host
+/// while forwarding a call between component instances.
+pub(crate) fn release_borrows<T>(store: &mut StoreContextMut<'_, T>, val: &Val) -> Result<()> {
+    match val {
+        Val::Resource(resource) if !resource.owned() => resource.resource_drop_impl(store),
+        Val::List(vals) | Val::Tuple(vals) => {
+            vals.iter().try_for_each(|val| release_borrows(store, val))
+        }
+        Val::Record(fields) => fields
+            .iter()
+            .try_for_each(|(_, val)| release_borrows(store, val)),
+        Val::Variant(_, Some(val))
+        | Val::Option(Some(val))
+        | Val::Result(Ok(Some(val)) | Err(Some(val))) => release_borrows(store, val),
+        _ => Ok(()),
+    }
+}
+
+/// Returns whether `name` is a version of the interface `interface` or of an
+/// interface within the package `interface`, as used by [`Linker::virtualize`].
+fn virtualizes(interface: &str, name: &str) -> bool {
+    match unversioned(name).strip_prefix(interface) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Returns `name` without its `@`-separated version, if any.
+fn unversioned(name: &str) -> &str {
+    name.split('@').next().unwrap()
+}
@@ -105,10 +105,14 @@ mod component;
 pub(crate) mod concurrent;
 mod func;
 mod instance;
+#[cfg(all(feature = "call-hook", feature = "std"))]
+mod intercept;
 mod linker;
 mod matching;
+mod resource_funcs;
 mod resource_table;
 mod resources;
+mod snapshot;
 mod storage;
 mod store;
 pub mod types;
@@ -116,15 +120,20 @@ mod values;
 pub use self::component::{Component, ComponentExportIndex};
 #[cfg(feature = "component-model-async")]
 pub use self::concurrent::{
-    ErrorContext, FutureReader, Promise, PromisesUnordered, StreamReader, VMComponentAsyncStore,
+    future, stream, ErrorContext, FutureReader, FutureWriter, HostFuture, HostSink, HostStream,
+    Promise, PromisesUnordered, StreamReader, StreamWriter, VMComponentAsyncStore,
 };
 pub use self::func::{
     ComponentNamedList, ComponentType, Func, Lift, Lower, TypedFunc, WasmList, WasmStr,
 };
 pub use self::instance::{Instance, InstanceExportLookup, InstancePre};
+#[cfg(all(feature = "call-hook", feature = "std"))]
+pub use self::intercept::{CallInterceptor, CallKind, InterceptedCall};
 pub use self::linker::{Linker, LinkerInstance};
+pub use self::resource_funcs::ResourceFuncs;
 pub use self::resource_table::{ResourceTable, ResourceTableError};
 pub use self::resources::{Resource, ResourceAny};
+pub use self::snapshot::{HostResourceSerializer, InstanceSnapshot};
 pub use self::types::{ResourceType, Type};
 pub use self::values::Val;
 
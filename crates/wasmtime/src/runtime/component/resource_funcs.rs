@@ -0,0 +1,320 @@
+use crate::component::{Func, Instance, ResourceAny, ResourceType, Val};
+use crate::prelude::*;
+use crate::AsContextMut;
+
+/// The functions a component instance exports for one of its resources.
+///
+/// The component model names the functions which operate on a resource `r`
+/// `[constructor]r`, `[method]r.name`, and `[static]r.name`, and exports them
+/// alongside `r` itself. This type collects those functions so that guest
+/// resources can be created and operated on dynamically, similarly to how
+/// [`Func::call`] works with [`Val`]s, without the use of `bindgen!`.
+///
+/// This is created with [`Instance::get_resource_funcs`]. Methods are invoked
+/// with a [`ResourceAny`] of this resource's type which is passed as the
+/// method's `self` parameter.
+///
+/// Note that the `call_*` methods here also invoke [`Func::post_return`] so
+/// there's no need to do so afterwards.
+#[derive(Clone, Debug)]
+pub struct ResourceFuncs {
+    ty: ResourceType,
+    name: String,
+    constructor: Option<Func>,
+    methods: Vec<(String, Func)>,
+    statics: Vec<(String, Func)>,
+}
+
+impl ResourceFuncs {
+    /// Returns the type of the resource these functions operate on.
+    pub fn ty(&self) -> ResourceType {
+        self.ty
+    }
+
+    /// Returns the name the resource is exported under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the `[constructor]` of this resource, if it has one.
+    pub fn constructor(&self) -> Option<Func> {
+        self.constructor
+    }
+
+    /// Returns the `[method]` of this resource named `name`, if any.
+    pub fn method(&self, name: &str) -> Option<Func> {
+        find(&self.methods, name)
+    }
+
+    /// Returns the names and functions of all `[method]`s of this resource.
+    pub fn methods(&self) -> impl ExactSizeIterator<Item = (&str, Func)> + '_ {
+        self.methods
+            .iter()
+            .map(|(name, func)| (name.as_str(), *func))
+    }
+
+    /// Returns the `[static]` function of this resource named `name`, if any.
+    pub fn static_func(&self, name: &str) -> Option<Func> {
+        find(&self.statics, name)
+    }
+
+    /// Returns the names and functions of all `[static]` functions of this
+    /// resource.
+    pub fn static_funcs(&self) -> impl ExactSizeIterator<Item = (&str, Func)> + '_ {
+        self.statics
+            .iter()
+            .map(|(name, func)| (name.as_str(), *func))
+    }
+
+    /// Creates a new resource by invoking its constructor with `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this resource has no constructor or in any of the
+    /// situations that [`Func::call`] returns an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on an asynchronous store or if `store` does
+    /// not own these functions.
+    pub fn construct(&self, mut store: impl AsContextMut, params: &[Val]) -> Result<ResourceAny> {
+        let func = self.get_constructor()?;
+        let results = call(func, store.as_context_mut(), params)?;
+        constructed(results)
+    }
+
+    /// Invokes the `[method]` named `name` with `resource` as its `self`
+    /// parameter followed by `params`, returning its results.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no method named `name`, if `resource` is
+    /// not of this resource's type, or in any of the situations that
+    /// [`Func::call`] returns an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on an asynchronous store or if `store` does
+    /// not own these functions.
+    pub fn call_method(
+        &self,
+        store: impl AsContextMut,
+        resource: &ResourceAny,
+        name: &str,
+        params: &[Val],
+    ) -> Result<Vec<Val>> {
+        let (func, params) = self.method_and_params(resource, name, params)?;
+        call(func, store, &params)
+    }
+
+    /// Invokes the `[static]` function named `name` with `params`, returning
+    /// its results.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no static function named `name` or in any
+    /// of the situations that [`Func::call`] returns an error.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is called on an asynchronous store or if `store` does
+    /// not own these functions.
+    pub fn call_static(
+        &self,
+        store: impl AsContextMut,
+        name: &str,
+        params: &[Val],
+    ) -> Result<Vec<Val>> {
+        call(self.get_static(name)?, store, params)
+    }
+
+    /// Exactly like [`Self::construct`] except for use on async stores.
+    #[cfg(feature = "async")]
+    pub async fn construct_async<T: Send>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        params: &[Val],
+    ) -> Result<ResourceAny> {
+        let func = self.get_constructor()?;
+        let results = call_async(func, store.as_context_mut(), params).await?;
+        constructed(results)
+    }
+
+    /// Exactly like [`Self::call_method`] except for use on async stores.
+    #[cfg(feature = "async")]
+    pub async fn call_method_async<T: Send>(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        resource: &ResourceAny,
+        name: &str,
+        params: &[Val],
+    ) -> Result<Vec<Val>> {
+        let (func, params) = self.method_and_params(resource, name, params)?;
+        call_async(func, store, &params).await
+    }
+
+    /// Exactly like [`Self::call_static`] except for use on async stores.
+    #[cfg(feature = "async")]
+    pub async fn call_static_async<T: Send>(
+        &self,
+        store: impl AsContextMut<Data = T>,
+        name: &str,
+        params: &[Val],
+    ) -> Result<Vec<Val>> {
+        call_async(self.get_static(name)?, store, params).await
+    }
+
+    fn get_constructor(&self) -> Result<Func> {
+        self.constructor
+            .ok_or_else(|| anyhow!("resource `{}` has no constructor", self.name))
+    }
+
+    fn get_static(&self, name: &str) -> Result<Func> {
+        self.static_func(name).ok_or_else(|| {
+            anyhow!(
+                "resource `{}` has no static function named `{name}`",
+                self.name
+            )
+        })
+    }
+
+    fn method_and_params(
+        &self,
+        resource: &ResourceAny,
+        name: &str,
+        params: &[Val],
+    ) -> Result<(Func, Vec<Val>)> {
+        let func = self
+            .method(name)
+            .ok_or_else(|| anyhow!("resource `{}` has no method named `{name}`", self.name))?;
+        if resource.ty() != self.ty {
+            bail!("resource is not of type `{}`", self.name);
+        }
+        let mut all = Vec::with_capacity(params.len() + 1);
+        all.push(Val::Resource(*resource));
+        all.extend_from_slice(params);
+        Ok((func, all))
+    }
+}
+
+fn find(funcs: &[(String, Func)], name: &str) -> Option<Func> {
+    funcs.iter().find(|(n, _)| n == name).map(|(_, func)| *func)
+}
+
+fn constructed(results: Vec<Val>) -> Result<ResourceAny> {
+    match results.as_slice() {
+        [Val::Resource(resource)] => Ok(*resource),
+        _ => bail!("constructor did not return a resource"),
+    }
+}
+
+fn call(func: Func, mut store: impl AsContextMut, params: &[Val]) -> Result<Vec<Val>> {
+    let mut results = vec![Val::Bool(false); func.results(&store).len()];
+    func.call(&mut store, params, &mut results)?;
+    func.post_return(&mut store)?;
+    Ok(results)
+}
+
+#[cfg(feature = "async")]
+async fn call_async<T: Send>(
+    func: Func,
+    mut store: impl AsContextMut<Data = T>,
+    params: &[Val],
+) -> Result<Vec<Val>> {
+    let mut results = vec![Val::Bool(false); func.results(&store).len()];
+    func.call_async(&mut store, params, &mut results).await?;
+    func.post_return_async(&mut store).await?;
+    Ok(results)
+}
+
+impl Instance {
+    /// Looks up the functions this instance exports for the resource `ty`.
+    ///
+    /// The resource is searched for among this instance's exports, including
+    /// those of exported instances, and its constructor, methods, and static
+    /// functions are those exported next to it. This means that the type of a
+    /// [`ResourceAny`] returned from one of this instance's functions can be
+    /// used to find the functions to operate on it.
+    ///
+    /// Returns `None` if this instance doesn't export the resource `ty`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wasmtime::{Engine, Store};
+    /// use wasmtime::component::{Component, Linker, Val};
+    ///
+    /// # fn main() -> wasmtime::Result<()> {
+    /// let engine = Engine::default();
+    /// let component = Component::new(&engine, r#"
+    ///     (component
+    ///         (type $counter' (resource (rep i32)))
+    ///         (export $counter "counter" (type $counter'))
+    ///         (core func $new (canon resource.new $counter))
+    ///         (core module $m
+    ///             (import "" "new" (func $new (param i32) (result i32)))
+    ///             (func (export "ctor") (param i32) (result i32)
+    ///                 (call $new (local.get 0)))
+    ///             (func (export "get") (param i32) (result i32)
+    ///                 local.get 0)
+    ///         )
+    ///         (core instance $i (instantiate $m
+    ///             (with "" (instance
+    ///                 (export "new" (func $new))
+    ///             ))
+    ///         ))
+    ///         (func (export "[constructor]counter") (param "v" u32) (result (own $counter))
+    ///             (canon lift (core func $i "ctor")))
+    ///         (func (export "[method]counter.get") (param "self" (borrow $counter)) (result u32)
+    ///             (canon lift (core func $i "get")))
+    ///     )
+    /// "#)?;
+    ///
+    /// let mut store = Store::new(&engine, ());
+    /// let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    /// let ty = instance.get_resource(&mut store, "counter").unwrap();
+    /// let funcs = instance.get_resource_funcs(&mut store, ty).unwrap();
+    ///
+    /// let counter = funcs.construct(&mut store, &[Val::U32(42)])?;
+    /// let results = funcs.call_method(&mut store, &counter, "get", &[])?;
+    /// assert_eq!(results, [Val::U32(42)]);
+    /// counter.resource_drop(&mut store)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_resource_funcs(
+        &self,
+        mut store: impl AsContextMut,
+        ty: ResourceType,
+    ) -> Option<ResourceFuncs> {
+        let store = store.as_context_mut().0;
+        let (namespace, name) = self.find_resource(store, None, ty)?;
+        let mut funcs = ResourceFuncs {
+            ty,
+            constructor: None,
+            methods: Vec::new(),
+            statics: Vec::new(),
+            name,
+        };
+        let constructor = format!("[constructor]{}", funcs.name);
+        let method = format!("[method]{}.", funcs.name);
+        let static_ = format!("[static]{}.", funcs.name);
+        for (export, index) in self.exports(store, namespace.as_ref())? {
+            let Some(func) = self.get_func(&mut *store, &index) else {
+                continue;
+            };
+            if export == constructor {
+                funcs.constructor = Some(func);
+            } else if let Some(name) = export.strip_prefix(&method) {
+                funcs.methods.push((name.to_string(), func));
+            } else if let Some(name) = export.strip_prefix(&static_) {
+                funcs.statics.push((name.to_string(), func));
+            }
+        }
+        Some(funcs)
+    }
+}
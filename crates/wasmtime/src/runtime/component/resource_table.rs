@@ -36,6 +36,7 @@ impl core::error::Error for ResourceTableError {}
 pub struct ResourceTable {
     entries: Vec<Entry>,
     free_head: Option<usize>,
+    max_capacity: usize,
 }
 
 #[derive(Debug)]
@@ -103,6 +104,7 @@ impl ResourceTable {
         ResourceTable {
             entries: Vec::new(),
             free_head: None,
+            max_capacity: usize::MAX,
         }
     }
 
@@ -111,9 +113,21 @@ impl ResourceTable {
         ResourceTable {
             entries: Vec::with_capacity(capacity),
             free_head: None,
+            max_capacity: usize::MAX,
         }
     }
 
+    /// Limits the number of entries this table may hold at once.
+    ///
+    /// Once the table is full, further insertions fail with
+    /// [`ResourceTableError::Full`] until existing entries are deleted. This
+    /// bounds the host memory a guest can cause to be allocated by repeatedly
+    /// calling host functions which create resources. Tables are unbounded by
+    /// default.
+    pub fn set_max_capacity(&mut self, max: usize) {
+        self.max_capacity = max;
+    }
+
     /// Inserts a new value `T` into this table, returning a corresponding
     /// `Resource<T>` which can be used to refer to it after it was inserted.
     pub fn push<T>(&mut self, entry: T) -> Result<Resource<T>, ResourceTableError>
@@ -162,6 +176,9 @@ impl ResourceTable {
             self.entries[free] = Entry::Occupied { entry: e };
             Ok(free.try_into().unwrap())
         } else {
+            if self.entries.len() >= self.max_capacity {
+                return Err(ResourceTableError::Full);
+            }
             let ix = self
                 .entries
                 .len()
@@ -314,6 +331,84 @@ impl ResourceTable {
             child.entry.as_ref()
         }))
     }
+
+    /// Captures all entries of this table, serializing each with `serialize`.
+    pub(crate) fn snapshot(
+        &self,
+        serialize: &mut dyn FnMut(&(dyn Any + Send)) -> Result<Vec<u8>>,
+    ) -> Result<HostTableSnapshot> {
+        let entries = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| match entry {
+                Entry::Free { next } => Ok(HostEntrySnapshot::Free { next: *next }),
+                Entry::Occupied { entry } => Ok(HostEntrySnapshot::Occupied {
+                    data: serialize(&*entry.entry)
+                        .with_context(|| format!("failed to serialize host resource {i}"))?,
+                    parent: entry.parent,
+                }),
+            })
+            .collect::<Result<_>>()?;
+        Ok(HostTableSnapshot {
+            entries,
+            free_head: self.free_head,
+        })
+    }
+
+    /// Replaces the contents of this empty table with those of `snapshot`,
+    /// recreating each entry with `deserialize`.
+    ///
+    /// Entries retain their original indices so `Resource<T>` handles held by
+    /// the restored instance continue to refer to the same resources.
+    pub(crate) fn restore(
+        &mut self,
+        snapshot: &HostTableSnapshot,
+        deserialize: &mut dyn FnMut(&[u8]) -> Result<Box<dyn Any + Send>>,
+    ) -> Result<()> {
+        if self.entries.iter().any(|e| e.occupied().is_some()) {
+            bail!("cannot restore into a resource table which is in use");
+        }
+        let mut entries = Vec::with_capacity(snapshot.entries.len());
+        for (i, entry) in snapshot.entries.iter().enumerate() {
+            entries.push(match entry {
+                HostEntrySnapshot::Free { next } => Entry::Free { next: *next },
+                HostEntrySnapshot::Occupied { data, parent } => Entry::Occupied {
+                    entry: TableEntry::new(
+                        deserialize(data)
+                            .with_context(|| format!("failed to deserialize host resource {i}"))?,
+                        *parent,
+                    ),
+                },
+            });
+        }
+        for child in 0..entries.len() {
+            let Some(parent) = entries[child].occupied().and_then(|e| e.parent) else {
+                continue;
+            };
+            entries
+                .get_mut(parent as usize)
+                .and_then(Entry::occupied_mut)
+                .ok_or_else(|| anyhow!("host resource {child} has a missing parent"))?
+                .add_child(u32::try_from(child).unwrap());
+        }
+        self.entries = entries;
+        self.free_head = snapshot.free_head;
+        Ok(())
+    }
+}
+
+/// Serializable state of a `ResourceTable`, see `ResourceTable::snapshot`.
+#[derive(Clone, Debug, serde_derive::Serialize, serde_derive::Deserialize)]
+pub(crate) struct HostTableSnapshot {
+    entries: Vec<HostEntrySnapshot>,
+    free_head: Option<usize>,
+}
+
+#[derive(Clone, Debug, serde_derive::Serialize, serde_derive::Deserialize)]
+enum HostEntrySnapshot {
+    Free { next: Option<usize> },
+    Occupied { data: Vec<u8>, parent: Option<u32> },
 }
 
 impl Default for ResourceTable {
@@ -351,3 +446,19 @@ pub fn test_free_list() {
     let x = table.push(()).unwrap();
     assert_eq!(x.rep(), 2);
 }
+
+#[test]
+pub fn test_max_capacity() {
+    let mut table = ResourceTable::new();
+    table.set_max_capacity(2);
+
+    let x = table.push(()).unwrap();
+    let _y = table.push(()).unwrap();
+    assert!(matches!(table.push(()), Err(ResourceTableError::Full)));
+
+    // Deleting an entry frees up a slot which can be reused.
+    table.delete(x).unwrap();
+    let x = table.push(()).unwrap();
+    assert_eq!(x.rep(), 0);
+    assert!(matches!(table.push(()), Err(ResourceTableError::Full)));
+}
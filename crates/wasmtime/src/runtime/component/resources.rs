@@ -594,6 +594,13 @@ impl<'a> HostResourceTables<'a> {
         self.tables.resource_lift_borrow(Some(ty), idx)
     }
 
+    /// Returns the `rep` of the handle `idx` in the table `ty` without
+    /// modifying the table.
+    #[cfg(all(feature = "call-hook", feature = "std"))]
+    pub fn guest_resource_rep(&mut self, idx: u32, ty: TypeResourceTableIndex) -> Result<u32> {
+        self.tables.resource_rep(Some(ty), idx)
+    }
+
     /// Begins a call into the component instance, starting recording of
     /// metadata related to resource borrowing.
     #[inline]
@@ -1011,7 +1018,7 @@ impl ResourceAny {
             .await?
     }
 
-    fn resource_drop_impl<T>(self, store: &mut StoreContextMut<'_, T>) -> Result<()> {
+    pub(crate) fn resource_drop_impl<T>(self, store: &mut StoreContextMut<'_, T>) -> Result<()> {
         // Attempt to remove `self.idx` from the host table in `store`.
         //
         // This could fail if the index is invalid or if this is removing an
@@ -1077,6 +1084,17 @@ impl ResourceAny {
     }
 
     fn lift_from_index(cx: &mut LiftContext<'_>, ty: InterfaceType, index: u32) -> Result<Self> {
+        #[cfg(all(feature = "call-hook", feature = "std"))]
+        if let Some(rep) = cx.guest_resource_inspect(ty, index)? {
+            let (InterfaceType::Own(t) | InterfaceType::Borrow(t)) = ty else {
+                bad_type_info()
+            };
+            return Ok(ResourceAny {
+                idx: cx.host_resource_lower_borrow(rep)?,
+                ty: cx.resource_type(t),
+                owned: false,
+            });
+        }
         match ty {
             InterfaceType::Own(t) => {
                 let ty = cx.resource_type(t);
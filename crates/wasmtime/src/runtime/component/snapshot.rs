@@ -0,0 +1,553 @@
+//! Capturing and restoring the complete state of a component instance.
+//!
+//! A snapshot contains the contents of all memories, globals and tables
+//! defined by the core instances within a component instance, the component's
+//! own resource handle tables, and optionally the host's [`ResourceTable`].
+//! Snapshots are restored into a fresh instantiation of the same component,
+//! possibly within a different process or on a different machine.
+
+use crate::component::{Component, Instance, ResourceTable};
+use crate::hash_map::HashMap;
+use crate::prelude::*;
+use crate::runtime::vm::component::{ComponentInstance, ResourceTableSnapshot};
+use crate::store::StoreOpaque;
+use crate::{
+    AsContextMut, Engine, Func, Global, Memory, Mutability, StoreContextMut, Table, Val, ValType,
+};
+use core::any::Any;
+use core::hash::{Hash, Hasher};
+use core::mem;
+use serde_derive::{Deserialize, Serialize};
+use wasmtime_environ::component::{RuntimeComponentInstanceIndex, RuntimeInstanceIndex};
+use wasmtime_environ::{EntityRef, FuncIndex, PrimaryMap};
+
+use super::resource_table::HostTableSnapshot;
+
+/// Prefix of all serialized snapshots, followed by a version byte.
+const MAGIC: &[u8] = b"\0wasmtime-snapshot";
+const VERSION: u8 = 1;
+
+/// Granularity at which zero bytes of linear memory are elided.
+const MEMORY_CHUNK: usize = 4096;
+
+/// The captured state of a component [`Instance`], created with
+/// [`Instance::snapshot`] and applied with [`Instance::restore`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InstanceSnapshot {
+    engine: u64,
+    component: u64,
+    instances: Vec<CoreInstanceSnapshot>,
+    resource_tables: Vec<ResourceTableSnapshot>,
+    host_table: Option<HostTableSnapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CoreInstanceSnapshot {
+    memories: Vec<MemorySnapshot>,
+    globals: Vec<ValSnapshot>,
+    tables: Vec<Vec<ValSnapshot>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct MemorySnapshot {
+    size: u64,
+    /// Runs of memory starting at the offset listed, everything else is zero.
+    data: Vec<(u64, Vec<u8>)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum ValSnapshot {
+    I32(i32),
+    I64(i64),
+    F32(u32),
+    F64(u64),
+    V128(u128),
+    Null,
+    /// A function defined by, or imported into, a core instance of the
+    /// component.
+    Func {
+        instance: u32,
+        func: u32,
+    },
+}
+
+/// Serialization hooks for the host's [`ResourceTable`] when snapshotting an
+/// instance.
+///
+/// Host resources are arbitrary Rust values so embedders are responsible for
+/// converting them to and from bytes. Resources retain their indices within
+/// the table so `Resource<T>` handles held by the guest remain valid.
+pub trait HostResourceSerializer<T> {
+    /// Returns the table of host resources within the store's data.
+    fn table<'a>(&mut self, data: &'a mut T) -> &'a mut ResourceTable;
+
+    /// Serializes a single resource within the table.
+    fn serialize(&mut self, resource: &(dyn Any + Send)) -> Result<Vec<u8>>;
+
+    /// Recreates a resource previously serialized with
+    /// [`HostResourceSerializer::serialize`].
+    fn deserialize(&mut self, bytes: &[u8]) -> Result<Box<dyn Any + Send>>;
+}
+
+impl InstanceSnapshot {
+    /// Serializes this snapshot to bytes, for example to send it to another
+    /// host.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend(postcard::to_allocvec(self).unwrap());
+        bytes
+    }
+
+    /// Deserializes a snapshot previously created with
+    /// [`InstanceSnapshot::to_bytes`].
+    ///
+    /// Compatibility with the engine it's restored into is checked by
+    /// [`Instance::restore`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<InstanceSnapshot> {
+        let bytes = bytes
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| anyhow!("not a serialized instance snapshot"))?;
+        match bytes.split_first() {
+            Some((&VERSION, bytes)) => Ok(postcard::from_bytes(bytes)?),
+            _ => bail!("unsupported instance snapshot version"),
+        }
+    }
+}
+
+impl Instance {
+    /// Captures the complete state of this instance.
+    ///
+    /// The returned snapshot contains the contents of all memories, globals
+    /// and tables defined within this component as well as its resource
+    /// handle tables. If `host` is provided then the host's [`ResourceTable`]
+    /// is captured as well.
+    ///
+    /// Resources owned by the host through [`ResourceAny`](super::ResourceAny)
+    /// are not captured.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the instance is not quiescent,
+    /// for example if it's currently executing, has trapped, needs a call to
+    /// `post_return`, or has outstanding async tasks or streams. Errors are
+    /// also returned for state which can't be represented in a snapshot such
+    /// as shared memories, non-null GC references, or tables containing
+    /// functions from outside of this instance.
+    pub fn snapshot<T>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        host: Option<&mut dyn HostResourceSerializer<T>>,
+    ) -> Result<InstanceSnapshot> {
+        let mut store = store.as_context_mut();
+        let data = store.0[self.0].as_ref().unwrap();
+        let component_hash = component_hash(data.component());
+        let core_instances = data.core_instances().clone();
+        // SAFETY: the instance is kept alive by the store and isn't otherwise
+        // borrowed while this reference is in use.
+        let component = unsafe { &mut *data.instance_ptr() };
+        check_quiescent(component)?;
+
+        let resource_tables = component
+            .component_resource_tables()
+            .values()
+            .map(|table| table.snapshot())
+            .collect::<Result<_>>()?;
+
+        let mut funcs = None;
+        let mut instances = Vec::new();
+        for instance in core_instances.values() {
+            let (memories, globals, tables) = defined_items(store.0, instance);
+
+            let mut snapshot = CoreInstanceSnapshot {
+                memories: Vec::new(),
+                globals: Vec::new(),
+                tables: Vec::new(),
+            };
+            for memory in memories {
+                if memory.ty(&store).is_shared() {
+                    bail!("cannot snapshot a shared memory");
+                }
+                snapshot.memories.push(snapshot_memory(memory.data(&store)));
+            }
+            for global in globals {
+                let val = global.get(&mut store);
+                snapshot
+                    .globals
+                    .push(snapshot_val(store.0, &core_instances, &mut funcs, val)?);
+            }
+            for table in tables {
+                let mut elems = Vec::new();
+                for i in 0..table.size(&store) {
+                    let elem = table.get(&mut store, i).unwrap();
+                    elems.push(snapshot_val(
+                        store.0,
+                        &core_instances,
+                        &mut funcs,
+                        elem.into(),
+                    )?);
+                }
+                snapshot.tables.push(elems);
+            }
+            instances.push(snapshot);
+        }
+
+        let host_table = match host {
+            Some(host) => Some(
+                host.table(store.data_mut())
+                    .snapshot(&mut |resource| host.serialize(resource))?,
+            ),
+            None => None,
+        };
+
+        Ok(InstanceSnapshot {
+            engine: engine_hash(store.engine()),
+            component: component_hash,
+            instances,
+            resource_tables,
+            host_table,
+        })
+    }
+
+    /// Restores the state captured in `snapshot` into this instance.
+    ///
+    /// This instance must be a fresh instantiation of the same component that
+    /// `snapshot` was taken from, and it must not have been used yet. The
+    /// component may have been compiled anew or deserialized, but it must have
+    /// been compiled from the same original wasm with the same settings. The
+    /// engine of `store` must target the same platform with the same wasm
+    /// features and tunables as the engine the snapshot was taken with.
+    ///
+    /// If the snapshot contains the host's [`ResourceTable`] then `host` must
+    /// be provided and its table must be empty. The restored resources retain
+    /// their original indices.
+    pub fn restore<T>(
+        &self,
+        mut store: impl AsContextMut<Data = T>,
+        snapshot: &InstanceSnapshot,
+        host: Option<&mut dyn HostResourceSerializer<T>>,
+    ) -> Result<()> {
+        let mut store = store.as_context_mut();
+        if snapshot.engine != engine_hash(store.engine()) {
+            bail!("snapshot was taken with an incompatible engine configuration");
+        }
+        if snapshot.host_table.is_some() && host.is_none() {
+            bail!("snapshot contains host resources but no host was provided");
+        }
+        let data = store.0[self.0].as_ref().unwrap();
+        if snapshot.component != component_hash(data.component()) {
+            bail!("snapshot was taken from a different component");
+        }
+        let core_instances = data.core_instances().clone();
+        // SAFETY: see `Instance::snapshot`.
+        let component = unsafe { &mut *data.instance_ptr() };
+        check_quiescent(component)?;
+
+        let tables = component.component_resource_tables();
+        if tables.len() != snapshot.resource_tables.len()
+            || core_instances.len() != snapshot.instances.len()
+        {
+            bail!("snapshot does not match the component of this instance");
+        }
+        for (table, snapshot) in tables.values_mut().zip(&snapshot.resource_tables) {
+            table.restore(snapshot)?;
+        }
+
+        for (instance, snapshot) in core_instances.values().zip(&snapshot.instances) {
+            let (memories, globals, tables) = defined_items(store.0, instance);
+            if memories.len() != snapshot.memories.len()
+                || globals.len() != snapshot.globals.len()
+                || tables.len() != snapshot.tables.len()
+            {
+                bail!("snapshot does not match the component of this instance");
+            }
+
+            for (memory, snapshot) in memories.iter().zip(&snapshot.memories) {
+                restore_memory(&mut store, memory, snapshot)?;
+            }
+            for (global, snapshot) in globals.iter().zip(&snapshot.globals) {
+                let ty = global.ty(&store);
+                if ty.mutability() == Mutability::Const {
+                    continue;
+                }
+                let val = restore_val(store.0, &core_instances, ty.content(), snapshot)?;
+                global.set(&mut store, val)?;
+            }
+            for (table, elems) in tables.iter().zip(&snapshot.tables) {
+                let ty = ValType::Ref(table.ty(&store).element().clone());
+                let size = table.size(&store);
+                let len = u64::try_from(elems.len()).unwrap();
+                if size < len {
+                    let init = Val::null_ref(ty.unwrap_ref().heap_type());
+                    table.grow(&mut store, len - size, init.ref_().unwrap())?;
+                } else if size > len {
+                    bail!("snapshot does not match the component of this instance");
+                }
+                for (i, elem) in (0..).zip(elems) {
+                    let val = restore_val(store.0, &core_instances, &ty, elem)?;
+                    table.set(&mut store, i, val.ref_().unwrap())?;
+                }
+            }
+        }
+
+        if let (Some(table), Some(host)) = (&snapshot.host_table, host) {
+            host.table(store.data_mut())
+                .restore(table, &mut |bytes| host.deserialize(bytes))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns an error if `instance` is in a state that can't be captured or
+/// overwritten.
+fn check_quiescent(instance: &mut ComponentInstance) -> Result<()> {
+    for i in 0..instance.component().num_runtime_component_instances {
+        let i = RuntimeComponentInstanceIndex::from_u32(i);
+        let flags = instance.instance_flags(i);
+        // SAFETY: the flags are owned by `instance` which is live.
+        unsafe {
+            if flags.needs_post_return() {
+                bail!("instance needs a call to `post_return`");
+            }
+            if !flags.may_enter() {
+                bail!("instance is currently executing or has trapped");
+            }
+        }
+        #[cfg(feature = "component-model-async")]
+        if instance.instance_state(i).handles.iter().next().is_some() {
+            bail!("instance has outstanding async tasks or streams");
+        }
+    }
+    #[cfg(feature = "component-model-async")]
+    for i in 0..instance.component().num_error_context_tables {
+        let i = wasmtime_environ::component::TypeComponentLocalErrorContextTableIndex::new(i);
+        if instance.error_context_table(i).iter().next().is_some() {
+            bail!("instance has outstanding error contexts");
+        }
+    }
+    Ok(())
+}
+
+/// Returns the memories, globals and tables defined, not imported, by
+/// `instance`.
+fn defined_items(
+    store: &mut StoreOpaque,
+    instance: &crate::Instance,
+) -> (Vec<Memory>, Vec<Global>, Vec<Table>) {
+    let id = instance.id(store);
+    let handle = store.instance_mut(id);
+    let memories = handle.defined_memories().collect::<Vec<_>>();
+    let globals = handle.defined_globals().map(|(_, g)| g).collect::<Vec<_>>();
+    let tables = handle.defined_tables().collect::<Vec<_>>();
+    // SAFETY: all of these items were just taken from an instance within
+    // `store`.
+    unsafe {
+        (
+            memories
+                .into_iter()
+                .map(|m| Memory::from_wasmtime_memory(m, store))
+                .collect(),
+            globals
+                .into_iter()
+                .map(|g| Global::from_wasmtime_global(g, store))
+                .collect(),
+            tables
+                .into_iter()
+                .map(|t| Table::from_wasmtime_table(t, store))
+                .collect(),
+        )
+    }
+}
+
+fn snapshot_memory(data: &[u8]) -> MemorySnapshot {
+    let mut runs: Vec<(u64, Vec<u8>)> = Vec::new();
+    let mut prev_end = None;
+    for (i, chunk) in data.chunks(MEMORY_CHUNK).enumerate() {
+        if chunk.iter().all(|b| *b == 0) {
+            continue;
+        }
+        let offset = u64::try_from(i * MEMORY_CHUNK).unwrap();
+        match runs.last_mut() {
+            Some((_, run)) if prev_end == Some(offset) => run.extend_from_slice(chunk),
+            _ => runs.push((offset, chunk.to_vec())),
+        }
+        prev_end = Some(offset + u64::try_from(chunk.len()).unwrap());
+    }
+    MemorySnapshot {
+        size: u64::try_from(data.len()).unwrap(),
+        data: runs,
+    }
+}
+
+fn restore_memory<T>(
+    store: &mut StoreContextMut<'_, T>,
+    memory: &Memory,
+    snapshot: &MemorySnapshot,
+) -> Result<()> {
+    let size = u64::try_from(memory.data_size(&*store)).unwrap();
+    if size < snapshot.size {
+        let page_size = memory.page_size(&*store);
+        memory.grow(&mut *store, (snapshot.size - size) / page_size)?;
+    } else if size > snapshot.size {
+        bail!("snapshot does not match the component of this instance");
+    }
+    let data = memory.data_mut(&mut *store);
+    data.fill(0);
+    for (offset, bytes) in snapshot.data.iter() {
+        let offset = usize::try_from(*offset)?;
+        data.get_mut(offset..)
+            .and_then(|d| d.get_mut(..bytes.len()))
+            .ok_or_else(|| anyhow!("memory snapshot out of bounds"))?
+            .copy_from_slice(bytes);
+    }
+    Ok(())
+}
+
+/// Map from `VMFuncRef` addresses to the core instance and function index
+/// they originate from.
+type FuncMap = HashMap<usize, (u32, u32)>;
+
+fn snapshot_val(
+    store: &mut StoreOpaque,
+    instances: &PrimaryMap<RuntimeInstanceIndex, crate::Instance>,
+    funcs: &mut Option<FuncMap>,
+    val: Val,
+) -> Result<ValSnapshot> {
+    Ok(match val {
+        Val::I32(i) => ValSnapshot::I32(i),
+        Val::I64(i) => ValSnapshot::I64(i),
+        Val::F32(f) => ValSnapshot::F32(f),
+        Val::F64(f) => ValSnapshot::F64(f),
+        Val::V128(v) => ValSnapshot::V128(v.as_u128()),
+        Val::FuncRef(None) | Val::ExternRef(None) | Val::AnyRef(None) => ValSnapshot::Null,
+        Val::FuncRef(Some(func)) => {
+            let funcs = funcs.get_or_insert_with(|| func_map(store, instances));
+            let func_ref = func.vm_func_ref(store).as_ptr() as usize;
+            let (instance, func) = funcs.get(&func_ref).ok_or_else(|| {
+                anyhow!("cannot snapshot a reference to a function outside of the instance")
+            })?;
+            ValSnapshot::Func {
+                instance: *instance,
+                func: *func,
+            }
+        }
+        Val::ExternRef(Some(_)) | Val::AnyRef(Some(_)) => {
+            bail!("cannot snapshot a non-null GC reference")
+        }
+    })
+}
+
+fn func_map(
+    store: &mut StoreOpaque,
+    instances: &PrimaryMap<RuntimeInstanceIndex, crate::Instance>,
+) -> FuncMap {
+    let mut funcs = HashMap::new();
+    for (i, instance) in instances.iter() {
+        let id = instance.id(store);
+        let handle = store.instance_mut(id);
+        for func in 0..handle.module().functions.len() {
+            let func_ref = handle.get_exported_func(FuncIndex::new(func)).func_ref;
+            funcs
+                .entry(func_ref.as_ptr() as usize)
+                .or_insert((i.as_u32(), u32::try_from(func).unwrap()));
+        }
+    }
+    funcs
+}
+
+fn restore_val(
+    store: &mut StoreOpaque,
+    instances: &PrimaryMap<RuntimeInstanceIndex, crate::Instance>,
+    ty: &ValType,
+    snapshot: &ValSnapshot,
+) -> Result<Val> {
+    Ok(match (snapshot, ty) {
+        (ValSnapshot::I32(i), ValType::I32) => Val::I32(*i),
+        (ValSnapshot::I64(i), ValType::I64) => Val::I64(*i),
+        (ValSnapshot::F32(f), ValType::F32) => Val::F32(*f),
+        (ValSnapshot::F64(f), ValType::F64) => Val::F64(*f),
+        (ValSnapshot::V128(v), ValType::V128) => Val::V128((*v).into()),
+        (ValSnapshot::Null, ValType::Ref(r)) => Val::null_ref(r.heap_type()),
+        (ValSnapshot::Func { instance, func }, ValType::Ref(_)) => {
+            let instance = instances
+                .get(RuntimeInstanceIndex::from_u32(*instance))
+                .ok_or_else(|| anyhow!("invalid instance in snapshot"))?;
+            let id = instance.id(store);
+            let handle = store.instance_mut(id);
+            let func = FuncIndex::from_u32(*func);
+            if func.index() >= handle.module().functions.len() {
+                bail!("invalid function in snapshot");
+            }
+            let func_ref = handle.get_exported_func(func).func_ref;
+            // SAFETY: the function reference was just taken from an instance
+            // within `store`.
+            Val::FuncRef(Some(unsafe { Func::from_vm_func_ref(store, func_ref) }))
+        }
+        _ => bail!("snapshot does not match the component of this instance"),
+    })
+}
+
+/// A hasher that's stable across processes.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Hashes the parts of `engine`'s configuration which determine the layout of
+/// an instance's state.
+///
+/// This is available without a compiler, unlike
+/// [`Engine::precompile_compatibility_hash`], so that snapshots may be
+/// restored into runtime-only builds.
+fn engine_hash(engine: &Engine) -> u64 {
+    let mut hasher = Fnv::new();
+    engine.target().to_string().hash(&mut hasher);
+    engine.features().bits().hash(&mut hasher);
+    engine.tunables().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns a hash of the compiled artifact of `component`, which is the same
+/// whether it was compiled in this process or deserialized in another.
+///
+/// The artifact records the compiler settings it was produced with, so this
+/// also covers what [`Engine::precompile_compatibility_hash`] does. Libcall
+/// addresses patched into the code when it's loaded depend on the process's
+/// address layout, so the libcall is hashed in their place instead. The hash
+/// is only computed once per component.
+fn component_hash(component: &Component) -> u64 {
+    component.snapshot_hash(|component| {
+        let code = component.code_object().code_memory();
+        let image = &code.mmap()[..];
+        let mut relocations = code.relocations().collect::<Vec<_>>();
+        relocations.sort_by_key(|(offset, _)| *offset);
+
+        let mut hasher = Fnv::new();
+        let mut pos = 0;
+        for (offset, libcall) in relocations {
+            hasher.write(&image[pos..offset]);
+            libcall.hash(&mut hasher);
+            pos = offset + mem::size_of::<usize>();
+        }
+        hasher.write(&image[pos..]);
+        hasher.finish()
+    })
+}
@@ -7,9 +7,9 @@ use core::fmt;
 use core::ops::Deref;
 use wasmtime_environ::component::{
     ComponentTypes, InterfaceType, ResourceIndex, TypeComponentIndex, TypeComponentInstanceIndex,
-    TypeDef, TypeEnumIndex, TypeFlagsIndex, TypeFuncIndex, TypeListIndex, TypeModuleIndex,
-    TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex, TypeTupleIndex,
-    TypeVariantIndex,
+    TypeDef, TypeEnumIndex, TypeFlagsIndex, TypeFuncIndex, TypeFutureIndex, TypeListIndex,
+    TypeModuleIndex, TypeOptionIndex, TypeRecordIndex, TypeResourceTableIndex, TypeResultIndex,
+    TypeStreamIndex, TypeTupleIndex, TypeVariantIndex,
 };
 use wasmtime_environ::PrimaryMap;
 
@@ -145,9 +145,16 @@ impl TypeChecker<'_> {
             (InterfaceType::String, _) => false,
             (InterfaceType::Char, InterfaceType::Char) => true,
             (InterfaceType::Char, _) => false,
-            (InterfaceType::Future(_), _)
-            | (InterfaceType::Stream(_), _)
-            | (InterfaceType::ErrorContext(_), _) => todo!(),
+            (InterfaceType::Future(f1), InterfaceType::Future(f2)) => {
+                self.futures_equal(self.a_types[f1].ty, self.b_types[f2].ty)
+            }
+            (InterfaceType::Future(_), _) => false,
+            (InterfaceType::Stream(s1), InterfaceType::Stream(s2)) => {
+                self.streams_equal(self.a_types[s1].ty, self.b_types[s2].ty)
+            }
+            (InterfaceType::Stream(_), _) => false,
+            (InterfaceType::ErrorContext(_), InterfaceType::ErrorContext(_)) => true,
+            (InterfaceType::ErrorContext(_), _) => false,
         }
     }
 
@@ -224,6 +231,26 @@ impl TypeChecker<'_> {
         self.interface_types_equal(a.ty, b.ty)
     }
 
+    fn futures_equal(&self, f1: TypeFutureIndex, f2: TypeFutureIndex) -> bool {
+        let a = &self.a_types[f1];
+        let b = &self.b_types[f2];
+        self.payloads_equal(a.payload, b.payload)
+    }
+
+    fn streams_equal(&self, s1: TypeStreamIndex, s2: TypeStreamIndex) -> bool {
+        let a = &self.a_types[s1];
+        let b = &self.b_types[s2];
+        self.payloads_equal(a.payload, b.payload)
+    }
+
+    fn payloads_equal(&self, a: Option<InterfaceType>, b: Option<InterfaceType>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => self.interface_types_equal(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
     fn enums_equal(&self, e1: TypeEnumIndex, e2: TypeEnumIndex) -> bool {
         let a = &self.a_types[e1];
         let b = &self.b_types[e2];
@@ -419,6 +446,58 @@ impl PartialEq for OptionType {
 
 impl Eq for OptionType {}
 
+/// A `future` interface type
+#[derive(Clone, Debug)]
+pub struct FutureType(Handle<TypeFutureIndex>);
+
+impl FutureType {
+    pub(crate) fn from(index: TypeFutureIndex, ty: &InstanceType<'_>) -> Self {
+        FutureType(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type parameter for this `future`, if any.
+    pub fn ty(&self) -> Option<Type> {
+        Some(Type::from(
+            self.0.types[self.0.index].payload.as_ref()?,
+            &self.0.instance(),
+        ))
+    }
+}
+
+impl PartialEq for FutureType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equivalent(&other.0, TypeChecker::futures_equal)
+    }
+}
+
+impl Eq for FutureType {}
+
+/// A `stream` interface type
+#[derive(Clone, Debug)]
+pub struct StreamType(Handle<TypeStreamIndex>);
+
+impl StreamType {
+    pub(crate) fn from(index: TypeStreamIndex, ty: &InstanceType<'_>) -> Self {
+        StreamType(Handle::new(index, ty))
+    }
+
+    /// Retrieve the type parameter for this `stream`, if any.
+    pub fn ty(&self) -> Option<Type> {
+        Some(Type::from(
+            self.0.types[self.0.index].payload.as_ref()?,
+            &self.0.instance(),
+        ))
+    }
+}
+
+impl PartialEq for StreamType {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.equivalent(&other.0, TypeChecker::streams_equal)
+    }
+}
+
+impl Eq for StreamType {}
+
 /// An `expected` interface type
 #[derive(Clone, Debug)]
 pub struct ResultType(Handle<TypeResultIndex>);
@@ -506,6 +585,9 @@ pub enum Type {
     Flags(Flags),
     Own(ResourceType),
     Borrow(ResourceType),
+    Future(FutureType),
+    Stream(StreamType),
+    ErrorContext,
 }
 
 impl Type {
@@ -663,9 +745,13 @@ impl Type {
             InterfaceType::Flags(index) => Type::Flags(Flags::from(*index, instance)),
             InterfaceType::Own(index) => Type::Own(instance.resource_type(*index)),
             InterfaceType::Borrow(index) => Type::Borrow(instance.resource_type(*index)),
-            InterfaceType::Future(_)
-            | InterfaceType::Stream(_)
-            | InterfaceType::ErrorContext(_) => todo!(),
+            InterfaceType::Future(index) => {
+                Type::Future(FutureType::from(instance.types[*index].ty, instance))
+            }
+            InterfaceType::Stream(index) => {
+                Type::Stream(StreamType::from(instance.types[*index].ty, instance))
+            }
+            InterfaceType::ErrorContext(_) => Type::ErrorContext,
         }
     }
 
@@ -694,6 +780,9 @@ impl Type {
             Type::Flags(_) => "flags",
             Type::Own(_) => "own",
             Type::Borrow(_) => "borrow",
+            Type::Future(_) => "future",
+            Type::Stream(_) => "stream",
+            Type::ErrorContext => "error-context",
         }
     }
 }
@@ -200,7 +200,7 @@ impl Val {
             }
             InterfaceType::Future(_)
             | InterfaceType::Stream(_)
-            | InterfaceType::ErrorContext(_) => todo!(),
+            | InterfaceType::ErrorContext(_) => unsupported(ty)?,
         })
     }
 
@@ -324,7 +324,7 @@ impl Val {
             }
             InterfaceType::Future(_)
             | InterfaceType::Stream(_)
-            | InterfaceType::ErrorContext(_) => todo!(),
+            | InterfaceType::ErrorContext(_) => unsupported(ty)?,
         })
     }
 
@@ -437,7 +437,7 @@ impl Val {
             (InterfaceType::Flags(_), _) => unexpected(ty, self),
             (InterfaceType::Future(_), _)
             | (InterfaceType::Stream(_), _)
-            | (InterfaceType::ErrorContext(_), _) => todo!(),
+            | (InterfaceType::ErrorContext(_), _) => unsupported(ty),
         }
     }
 
@@ -575,7 +575,7 @@ impl Val {
             (InterfaceType::Flags(_), _) => unexpected(ty, self),
             (InterfaceType::Future(_), _)
             | (InterfaceType::Stream(_), _)
-            | (InterfaceType::ErrorContext(_), _) => todo!(),
+            | (InterfaceType::ErrorContext(_), _) => unsupported(ty),
         }
     }
 
@@ -839,7 +839,7 @@ fn load_list(cx: &mut LiftContext<'_>, ty: TypeListIndex, ptr: usize, len: usize
         .checked_mul(element_size)
         .and_then(|len| ptr.checked_add(len))
     {
-        Some(n) if n <= cx.memory().len() => {}
+        Some(n) if n <= cx.memory().len() => cx.charge_lift_bytes(n - ptr)?,
         _ => bail!("list pointer/length out of bounds of memory"),
     }
     if ptr % usize::try_from(element_alignment)? != 0 {
@@ -1000,3 +1000,7 @@ fn unexpected<T>(ty: InterfaceType, val: &Val) -> Result<T> {
         val.desc()
     )
 }
+
+fn unsupported<T>(ty: InterfaceType) -> Result<T> {
+    bail!("`{}` values are not supported by `Val`", desc(&ty))
+}
@@ -0,0 +1,312 @@
+use crate::prelude::*;
+use crate::runtime::vm::CompiledModuleId;
+use crate::{AsContextMut, Module};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use wasmtime_environ::demangle_function_name_or_index;
+
+/// Line-level code coverage of WebAssembly guests.
+///
+/// When [`Config::coverage`](crate::Config::coverage) is enabled compiled
+/// code counts how many times each of its basic blocks is entered. A
+/// `Coverage` accumulates these counters from one or more stores with
+/// [`Coverage::collect`], after which they're mapped back to source lines
+/// with the DWARF debug information of each module and written out as an
+/// [lcov] tracefile with [`Coverage::write_lcov`] or as [`llvm-cov export`]
+/// JSON with [`Coverage::write_llvm_json`].
+///
+/// Modules without DWARF debug information, or whose debug information
+/// wasn't retained because
+/// [`Config::wasm_backtrace_details`](crate::Config::wasm_backtrace_details)
+/// is disabled, can't be mapped to source lines and are left out of the
+/// reports.
+///
+/// [lcov]: https://github.com/linux-test-project/lcov
+/// [`llvm-cov export`]: https://llvm.org/docs/CommandGuide/llvm-cov.html#llvm-cov-export
+#[derive(Default)]
+pub struct Coverage {
+    modules: HashMap<CompiledModuleId, ModuleCoverage>,
+}
+
+struct ModuleCoverage {
+    module: Module,
+    counters: Vec<u64>,
+}
+
+/// Coverage of a single function, ready to be reported.
+struct FunctionReport {
+    name: String,
+    /// The file and line of the function's entry.
+    file: String,
+    line: u32,
+    /// The number of times the function was entered.
+    count: u64,
+    /// Execution counts of each `(file, line)` in this function.
+    lines: BTreeMap<(String, u32), u64>,
+}
+
+/// Coverage of a single source file, ready to be reported.
+#[derive(Default)]
+struct FileReport<'a> {
+    functions: Vec<&'a FunctionReport>,
+    lines: BTreeMap<u32, u64>,
+}
+
+impl Coverage {
+    /// Creates a new, empty, set of coverage data.
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    /// Adds the coverage counters of all instances within `store` to this
+    /// coverage data.
+    ///
+    /// The counters of the instances are reset afterwards, so collecting from
+    /// the same store again only adds what was executed in the meantime.
+    /// Instances don't outlive their store so this must be called before
+    /// `store` is dropped.
+    pub fn collect(&mut self, mut store: impl AsContextMut) {
+        let store = store.as_context_mut().0;
+        let instances = store.all_instances().collect::<Vec<_>>();
+        for instance in instances {
+            let (module, counters) = instance.coverage_counters_mut(store);
+            if counters.is_empty() {
+                continue;
+            }
+            let id = module.compiled_module().unique_id();
+            let entry = self.modules.entry(id).or_insert_with(|| ModuleCoverage {
+                counters: vec![0; counters.len()],
+                module,
+            });
+            for (total, counter) in entry.counters.iter_mut().zip(counters) {
+                *total += core::mem::take(counter);
+            }
+        }
+    }
+
+    /// Writes the coverage collected so far as an [lcov] tracefile.
+    ///
+    /// [lcov]: https://github.com/linux-test-project/lcov
+    pub fn write_lcov(&self, mut output: impl Write) -> Result<()> {
+        let functions = self.functions();
+        for (name, file) in files(&functions) {
+            writeln!(output, "TN:")?;
+            writeln!(output, "SF:{name}")?;
+            for function in &file.functions {
+                writeln!(output, "FN:{},{}", function.line, function.name)?;
+            }
+            for function in &file.functions {
+                writeln!(output, "FNDA:{},{}", function.count, function.name)?;
+            }
+            writeln!(output, "FNF:{}", file.functions.len())?;
+            writeln!(output, "FNH:{}", file.functions_hit())?;
+            for (line, count) in &file.lines {
+                writeln!(output, "DA:{line},{count}")?;
+            }
+            writeln!(output, "LF:{}", file.lines.len())?;
+            writeln!(output, "LH:{}", file.lines_hit())?;
+            writeln!(output, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the coverage collected so far in the JSON format of
+    /// [`llvm-cov export`].
+    ///
+    /// Each source line is reported as a single region.
+    ///
+    /// [`llvm-cov export`]: https://llvm.org/docs/CommandGuide/llvm-cov.html#llvm-cov-export
+    pub fn write_llvm_json(&self, output: impl Write) -> Result<()> {
+        let functions = self.functions();
+        let files = files(&functions);
+
+        let mut json_files = Vec::new();
+        for (name, file) in &files {
+            let mut segments = Vec::new();
+            for (line, count) in &file.lines {
+                segments.push(json!([line, 1, count, true, true, false]));
+                if !file.lines.contains_key(&(line + 1)) {
+                    segments.push(json!([line + 1, 1, 0, false, false, false]));
+                }
+            }
+            let lines = summary(file.lines.len(), file.lines_hit());
+            let functions = summary(file.functions.len(), file.functions_hit());
+            json_files.push(json!({
+                "filename": name,
+                "segments": segments,
+                "branches": [],
+                "expansions": [],
+                "summary": {
+                    "lines": lines,
+                    "regions": lines,
+                    "functions": functions,
+                    "instantiations": functions,
+                    "branches": summary(0, 0),
+                },
+            }));
+        }
+
+        let mut json_functions = Vec::new();
+        for function in &functions {
+            let mut filenames = Vec::<&str>::new();
+            let mut regions = Vec::new();
+            for ((file, line), count) in &function.lines {
+                let file_id = match filenames.iter().position(|f| f == file) {
+                    Some(id) => id,
+                    None => {
+                        filenames.push(file);
+                        filenames.len() - 1
+                    }
+                };
+                regions.push(json!([line, 1, line + 1, 1, count, file_id, 0, 0]));
+            }
+            json_functions.push(json!({
+                "name": function.name,
+                "count": function.count,
+                "regions": regions,
+                "filenames": filenames,
+                "branches": [],
+            }));
+        }
+
+        let num_lines = files.values().map(|f| f.lines.len()).sum();
+        let lines_hit = files.values().map(|f| f.lines_hit()).sum();
+        let lines = summary(num_lines, lines_hit);
+        let functions_hit = functions.iter().filter(|f| f.count > 0).count();
+        let functions = summary(functions.len(), functions_hit);
+        let export = json!({
+            "type": "llvm.coverage.json.export",
+            "version": "2.0.1",
+            "data": [{
+                "files": json_files,
+                "functions": json_functions,
+                "totals": {
+                    "lines": lines,
+                    "regions": lines,
+                    "functions": functions,
+                    "instantiations": functions,
+                    "branches": summary(0, 0),
+                },
+            }],
+        });
+        serde_json::to_writer(output, &export)?;
+        Ok(())
+    }
+
+    /// Maps the counters of all modules back to their functions and source
+    /// lines.
+    fn functions(&self) -> Vec<FunctionReport> {
+        let mut ret = Vec::new();
+        for coverage in self.modules.values() {
+            let compiled = coverage.module.compiled_module();
+            let Ok(Some(cx)) = compiled.symbolize_context() else {
+                continue;
+            };
+            // DWARF addresses are relative to the start of the code section.
+            let to_dwarf = |offset: u32| u64::from(offset) - cx.code_section_offset();
+            let env = coverage.module.env_module();
+
+            for (index, (counters, _end)) in env.coverage.functions.iter() {
+                if counters.is_empty() {
+                    continue;
+                }
+                let entry = env.coverage.blocks[counters.start as usize];
+                let Some((file, line)) = cx
+                    .addr2line()
+                    .find_location(to_dwarf(entry))
+                    .ok()
+                    .flatten()
+                    .and_then(|l| Some((l.file?.to_string(), l.line?)))
+                else {
+                    continue;
+                };
+
+                // A line is executed as often as the most frequently executed
+                // block it's part of.
+                let mut lines = BTreeMap::new();
+                for (counter, range) in env.coverage.function_blocks(index) {
+                    let count = coverage.counters[counter as usize];
+                    let Ok(locations) = cx
+                        .addr2line()
+                        .find_location_range(to_dwarf(range.start), to_dwarf(range.end))
+                    else {
+                        continue;
+                    };
+                    for (_, _, location) in locations {
+                        if let (Some(file), Some(line)) = (location.file, location.line) {
+                            let entry = lines.entry((file.to_string(), line)).or_insert(0);
+                            *entry = count.max(*entry);
+                        }
+                    }
+                }
+
+                let func_index = env.func_index(index);
+                let mut name = String::new();
+                demangle_function_name_or_index(
+                    &mut name,
+                    compiled.func_name(func_index),
+                    func_index.as_u32() as usize,
+                )
+                .unwrap();
+                ret.push(FunctionReport {
+                    name,
+                    file,
+                    line,
+                    count: coverage.counters[counters.start as usize],
+                    lines,
+                });
+            }
+        }
+        ret.sort_by(|a, b| (&a.file, a.line, &a.name).cmp(&(&b.file, b.line, &b.name)));
+        ret
+    }
+}
+
+/// Groups the coverage of `functions` by source file.
+///
+/// Lines which appear in several functions, for example because they were
+/// inlined, are executed as often as all of them combined.
+fn files(functions: &[FunctionReport]) -> BTreeMap<&str, FileReport<'_>> {
+    let mut files = BTreeMap::<&str, FileReport<'_>>::new();
+    for function in functions {
+        files
+            .entry(&function.file)
+            .or_default()
+            .functions
+            .push(function);
+        for ((file, line), count) in &function.lines {
+            *files
+                .entry(file)
+                .or_default()
+                .lines
+                .entry(*line)
+                .or_insert(0) += count;
+        }
+    }
+    files
+}
+
+impl FileReport<'_> {
+    fn functions_hit(&self) -> usize {
+        self.functions.iter().filter(|f| f.count > 0).count()
+    }
+
+    fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|c| **c > 0).count()
+    }
+}
+
+fn summary(count: usize, covered: usize) -> Value {
+    let percent = if count == 0 {
+        0.0
+    } else {
+        covered as f64 * 100.0 / count as f64
+    };
+    json!({
+        "count": count,
+        "covered": covered,
+        "notcovered": count - covered,
+        "percent": percent,
+    })
+}
@@ -383,6 +383,21 @@ impl Instance {
         store.module_for_instance(id).unwrap()
     }
 
+    /// Returns this instance's module along with its code coverage counters.
+    #[cfg(feature = "coverage")]
+    pub(crate) fn coverage_counters_mut<'a>(
+        &self,
+        store: &'a mut StoreOpaque,
+    ) -> (Module, &'a mut [u64]) {
+        let module = self._module(store).clone();
+        let InstanceData { id, .. } = store[self.0];
+        let counters = store
+            .instance_mut(id)
+            .instance_mut()
+            .coverage_counters_mut();
+        (module, counters)
+    }
+
     /// Returns the list of exported items from this [`Instance`].
     ///
     /// # Panics
@@ -151,6 +151,66 @@ pub trait ResourceLimiter {
     fn memories(&self) -> usize {
         DEFAULT_MEMORY_LIMIT
     }
+
+    /// The maximum number of live resource handles within each resource
+    /// table of a component instance, and within the `Store`'s table of
+    /// resources owned by the host.
+    ///
+    /// Creating or transferring a handle which would exceed this limit traps
+    /// with [`Trap::ComponentLimitExceeded`](crate::Trap::ComponentLimitExceeded).
+    ///
+    /// By default, resource handles will not be limited.
+    #[cfg(feature = "component-model")]
+    fn component_resource_handles(&self) -> usize {
+        usize::MAX
+    }
+
+    /// The maximum number of live resource handles across all resource tables
+    /// of a component instance.
+    ///
+    /// Creating or transferring a handle which would exceed this limit traps
+    /// with [`Trap::ComponentLimitExceeded`](crate::Trap::ComponentLimitExceeded).
+    ///
+    /// By default, resource handles will not be limited.
+    #[cfg(feature = "component-model")]
+    fn component_instance_resource_handles(&self) -> usize {
+        usize::MAX
+    }
+
+    /// The maximum number of bytes of lists and strings the host lifts from a
+    /// component in a single call.
+    ///
+    /// This applies to the results of exported functions called by the host
+    /// and the arguments of host-defined imports called by components. It
+    /// bounds how much host memory a component can make the host allocate,
+    /// for example by returning a giant `list<u8>`. Exceeding this limit traps
+    /// with [`Trap::ComponentLimitExceeded`](crate::Trap::ComponentLimitExceeded).
+    ///
+    /// By default, lifted bytes will not be limited.
+    #[cfg(feature = "component-model")]
+    fn component_lift_bytes(&self) -> usize {
+        usize::MAX
+    }
+
+    /// The maximum number of bytes adapters copy between components during a
+    /// single call of an exported function by the host.
+    ///
+    /// This is measured as the bytes allocated in the destination component
+    /// to hold the lists, strings, and spilled arguments or results passed
+    /// between components. Exceeding this limit traps with
+    /// [`Trap::ComponentLimitExceeded`](crate::Trap::ComponentLimitExceeded).
+    /// The count restarts whenever the host starts a call into a component,
+    /// including calls started with `call_concurrent`.
+    ///
+    /// This limit is only enforced for components compiled with
+    /// [`Config::component_adapter_copy_limit`](crate::Config::component_adapter_copy_limit)
+    /// enabled.
+    ///
+    /// By default, copied bytes will not be limited.
+    #[cfg(feature = "component-model")]
+    fn component_adapter_copy_bytes(&self) -> usize {
+        usize::MAX
+    }
 }
 
 /// Used by hosts to limit resource consumption of instances, blocking
@@ -215,6 +275,75 @@ pub trait ResourceLimiterAsync {
     fn memories(&self) -> usize {
         DEFAULT_MEMORY_LIMIT
     }
+
+    /// Identical to [`ResourceLimiter::component_resource_handles`]
+    #[cfg(feature = "component-model")]
+    fn component_resource_handles(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Identical to [`ResourceLimiter::component_instance_resource_handles`]
+    #[cfg(feature = "component-model")]
+    fn component_instance_resource_handles(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Identical to [`ResourceLimiter::component_lift_bytes`]
+    #[cfg(feature = "component-model")]
+    fn component_lift_bytes(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Identical to [`ResourceLimiter::component_adapter_copy_bytes`]
+    #[cfg(feature = "component-model")]
+    fn component_adapter_copy_bytes(&self) -> usize {
+        usize::MAX
+    }
+}
+
+/// Limits on components configured through a [`ResourceLimiter`] or
+/// [`ResourceLimiterAsync`] which are cached within a store.
+#[cfg(feature = "component-model")]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct ComponentLimits {
+    pub resource_handles: usize,
+    pub instance_resource_handles: usize,
+    pub lift_bytes: usize,
+    pub adapter_copy_bytes: usize,
+}
+
+#[cfg(feature = "component-model")]
+impl ComponentLimits {
+    pub(crate) fn new(limiter: &dyn ResourceLimiter) -> ComponentLimits {
+        ComponentLimits {
+            resource_handles: limiter.component_resource_handles(),
+            instance_resource_handles: limiter.component_instance_resource_handles(),
+            lift_bytes: limiter.component_lift_bytes(),
+            adapter_copy_bytes: limiter.component_adapter_copy_bytes(),
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn new_async(limiter: &dyn ResourceLimiterAsync) -> ComponentLimits {
+        ComponentLimits {
+            resource_handles: limiter.component_resource_handles(),
+            instance_resource_handles: limiter.component_instance_resource_handles(),
+            lift_bytes: limiter.component_lift_bytes(),
+            adapter_copy_bytes: limiter.component_adapter_copy_bytes(),
+        }
+    }
+}
+
+#[cfg(feature = "component-model")]
+impl Default for ComponentLimits {
+    fn default() -> ComponentLimits {
+        ComponentLimits {
+            resource_handles: usize::MAX,
+            instance_resource_handles: usize::MAX,
+            lift_bytes: usize::MAX,
+            adapter_copy_bytes: usize::MAX,
+        }
+    }
 }
 
 /// Used to build [`StoreLimits`].
@@ -284,6 +413,60 @@ impl StoreLimitsBuilder {
         self
     }
 
+    /// The maximum number of live resource handles within each resource
+    /// table of a component instance.
+    ///
+    /// See [`ResourceLimiter::component_resource_handles`] for more
+    /// information.
+    ///
+    /// By default, resource handles will not be limited.
+    #[cfg(feature = "component-model")]
+    pub fn component_resource_handles(mut self, limit: usize) -> Self {
+        self.0.component.resource_handles = limit;
+        self
+    }
+
+    /// The maximum number of live resource handles across all resource tables
+    /// of a component instance.
+    ///
+    /// See [`ResourceLimiter::component_instance_resource_handles`] for more
+    /// information.
+    ///
+    /// By default, resource handles will not be limited.
+    #[cfg(feature = "component-model")]
+    pub fn component_instance_resource_handles(mut self, limit: usize) -> Self {
+        self.0.component.instance_resource_handles = limit;
+        self
+    }
+
+    /// The maximum number of bytes of lists and strings the host lifts from a
+    /// component in a single call.
+    ///
+    /// See [`ResourceLimiter::component_lift_bytes`] for more information.
+    ///
+    /// By default, lifted bytes will not be limited.
+    #[cfg(feature = "component-model")]
+    pub fn component_lift_bytes(mut self, limit: usize) -> Self {
+        self.0.component.lift_bytes = limit;
+        self
+    }
+
+    /// The maximum number of bytes adapters copy between components during a
+    /// single call of an exported function by the host.
+    ///
+    /// See [`ResourceLimiter::component_adapter_copy_bytes`] for more
+    /// information. Note that this limit is only enforced for components
+    /// compiled with
+    /// [`Config::component_adapter_copy_limit`](crate::Config::component_adapter_copy_limit)
+    /// enabled.
+    ///
+    /// By default, copied bytes will not be limited.
+    #[cfg(feature = "component-model")]
+    pub fn component_adapter_copy_bytes(mut self, limit: usize) -> Self {
+        self.0.component.adapter_copy_bytes = limit;
+        self
+    }
+
     /// Indicates that a trap should be raised whenever a growth operation
     /// would fail.
     ///
@@ -320,6 +503,8 @@ pub struct StoreLimits {
     tables: usize,
     memories: usize,
     trap_on_grow_failure: bool,
+    #[cfg(feature = "component-model")]
+    component: ComponentLimits,
 }
 
 impl Default for StoreLimits {
@@ -331,6 +516,8 @@ impl Default for StoreLimits {
             tables: DEFAULT_TABLE_LIMIT,
             memories: DEFAULT_MEMORY_LIMIT,
             trap_on_grow_failure: false,
+            #[cfg(feature = "component-model")]
+            component: ComponentLimits::default(),
         }
     }
 }
@@ -405,4 +592,24 @@ impl ResourceLimiter for StoreLimits {
     fn memories(&self) -> usize {
         self.memories
     }
+
+    #[cfg(feature = "component-model")]
+    fn component_resource_handles(&self) -> usize {
+        self.component.resource_handles
+    }
+
+    #[cfg(feature = "component-model")]
+    fn component_instance_resource_handles(&self) -> usize {
+        self.component.instance_resource_handles
+    }
+
+    #[cfg(feature = "component-model")]
+    fn component_lift_bytes(&self) -> usize {
+        self.component.lift_bytes
+    }
+
+    #[cfg(feature = "component-model")]
+    fn component_adapter_copy_bytes(&self) -> usize {
+        self.component.adapter_copy_bytes
+    }
 }
@@ -808,6 +808,9 @@ impl SharedMemory {
         if !ty.is_shared() {
             bail!("shared memory must have the `shared` flag enabled on its memory type")
         }
+        if engine.config().deterministic {
+            bail!("shared memories cannot be created in deterministic mode");
+        }
         debug_assert!(ty.maximum().is_some());
 
         let tunables = engine.tunables();
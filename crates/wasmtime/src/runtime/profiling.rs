@@ -12,6 +12,15 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use wasmtime_environ::demangle_function_name_or_index;
 
+#[cfg(target_has_atomic = "64")]
+mod pprof;
+#[cfg(target_has_atomic = "64")]
+mod sampling;
+#[cfg(target_has_atomic = "64")]
+pub(crate) use sampling::AttachedProfiler;
+#[cfg(target_has_atomic = "64")]
+pub use sampling::{ProfileFormat, SamplingProfiler};
+
 // TODO: collect more data
 // - On non-Windows, measure thread-local CPU usage between events with
 //   rustix::time::clock_gettime(ClockId::ThreadCPUTime)
@@ -0,0 +1,230 @@
+//! A minimal encoder for the [pprof] protobuf format.
+//!
+//! Only the subset of the format needed by the sampling profiler is
+//! supported. This is hand-written rather than generated to avoid pulling a
+//! protobuf implementation into Wasmtime for the sake of one message type.
+//!
+//! [pprof]: https://github.com/google/pprof/blob/main/proto/profile.proto
+
+use crate::prelude::*;
+use std::collections::HashMap;
+
+/// A profile which is encoded as a `perftools.profiles.Profile` message.
+#[derive(Default)]
+pub struct Profile {
+    strings: Vec<String>,
+    string_ids: HashMap<String, i64>,
+    /// `(type, unit)` of each value in a sample.
+    sample_types: Vec<(i64, i64)>,
+    /// Location ids, leaf first, and values of each sample.
+    samples: Vec<(Vec<u64>, Vec<i64>)>,
+    /// Lines of each location, innermost inlined function first, as
+    /// `(function id, line, column)`.
+    locations: Vec<Vec<(u64, i64, i64)>>,
+    /// `(name, filename)` of each function.
+    functions: Vec<(i64, i64)>,
+    time_nanos: i64,
+    duration_nanos: i64,
+    period_type: (i64, i64),
+    period: i64,
+}
+
+impl Profile {
+    pub fn new() -> Profile {
+        let mut profile = Profile::default();
+        // The string table must always start with the empty string.
+        profile.string("");
+        profile
+    }
+
+    pub fn string(&mut self, s: &str) -> i64 {
+        if let Some(id) = self.string_ids.get(s) {
+            return *id;
+        }
+        let id = i64::try_from(self.strings.len()).unwrap();
+        self.strings.push(s.to_string());
+        self.string_ids.insert(s.to_string(), id);
+        id
+    }
+
+    pub fn add_sample_type(&mut self, ty: &str, unit: &str) {
+        let ty = (self.string(ty), self.string(unit));
+        self.sample_types.push(ty);
+    }
+
+    pub fn set_period(&mut self, ty: &str, unit: &str, period: i64) {
+        self.period_type = (self.string(ty), self.string(unit));
+        self.period = period;
+    }
+
+    pub fn set_time(&mut self, time_nanos: i64, duration_nanos: i64) {
+        self.time_nanos = time_nanos;
+        self.duration_nanos = duration_nanos;
+    }
+
+    /// Adds a function, returning its id.
+    pub fn add_function(&mut self, name: &str, filename: &str) -> u64 {
+        let function = (self.string(name), self.string(filename));
+        self.functions.push(function);
+        u64::try_from(self.functions.len()).unwrap()
+    }
+
+    /// Adds a location, returning its id.
+    pub fn add_location(&mut self, lines: Vec<(u64, i64, i64)>) -> u64 {
+        self.locations.push(lines);
+        u64::try_from(self.locations.len()).unwrap()
+    }
+
+    pub fn add_sample(&mut self, locations: Vec<u64>, values: Vec<i64>) {
+        debug_assert_eq!(values.len(), self.sample_types.len());
+        self.samples.push((locations, values));
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Encoder::default();
+        for (ty, unit) in &self.sample_types {
+            out.message(1, |m| {
+                m.int(1, *ty);
+                m.int(2, *unit);
+            });
+        }
+        for (locations, values) in &self.samples {
+            out.message(2, |m| {
+                m.packed(1, locations.iter().copied());
+                m.packed(2, values.iter().map(|v| *v as u64));
+            });
+        }
+        for (id, lines) in self.locations.iter().enumerate() {
+            out.message(4, |m| {
+                m.int(1, id as i64 + 1);
+                for (function, line, column) in lines {
+                    m.message(4, |m| {
+                        m.int(1, *function as i64);
+                        m.int(2, *line);
+                        m.int(3, *column);
+                    });
+                }
+            });
+        }
+        for (id, (name, filename)) in self.functions.iter().enumerate() {
+            out.message(5, |m| {
+                m.int(1, id as i64 + 1);
+                m.int(2, *name);
+                m.int(3, *name);
+                m.int(4, *filename);
+            });
+        }
+        for s in &self.strings {
+            out.bytes(6, s.as_bytes());
+        }
+        out.int(9, self.time_nanos);
+        out.int(10, self.duration_nanos);
+        out.message(11, |m| {
+            m.int(1, self.period_type.0);
+            m.int(2, self.period_type.1);
+        });
+        out.int(12, self.period);
+        out.buf
+    }
+}
+
+#[derive(Default)]
+struct Encoder {
+    buf: Vec<u8>,
+}
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_LEN: u64 = 2;
+
+impl Encoder {
+    fn varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.buf.push(byte);
+                return;
+            }
+            self.buf.push(byte | 0x80);
+        }
+    }
+
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.varint((field << 3) | wire_type);
+    }
+
+    /// Encodes an integer field, omitting it if it's the default of zero.
+    fn int(&mut self, field: u64, value: i64) {
+        if value != 0 {
+            self.key(field, WIRE_VARINT);
+            self.varint(value as u64);
+        }
+    }
+
+    fn bytes(&mut self, field: u64, bytes: &[u8]) {
+        self.key(field, WIRE_LEN);
+        self.varint(bytes.len() as u64);
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn packed(&mut self, field: u64, values: impl Iterator<Item = u64>) {
+        let mut m = Encoder::default();
+        for value in values {
+            m.varint(value);
+        }
+        if !m.buf.is_empty() {
+            self.bytes(field, &m.buf);
+        }
+    }
+
+    fn message(&mut self, field: u64, f: impl FnOnce(&mut Encoder)) {
+        let mut m = Encoder::default();
+        f(&mut m);
+        self.bytes(field, &m.buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varints() {
+        let mut e = Encoder::default();
+        e.varint(0);
+        e.varint(1);
+        e.varint(300);
+        e.varint(u64::MAX);
+        assert_eq!(
+            e.buf,
+            [0, 1, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+        );
+    }
+
+    #[test]
+    fn encode_profile() {
+        let mut profile = Profile::new();
+        profile.add_sample_type("samples", "count");
+        let f = profile.add_function("f", "");
+        let loc = profile.add_location(vec![(f, 0, 0)]);
+        profile.add_sample(vec![loc], vec![1]);
+        assert_eq!(
+            profile.encode(),
+            [
+                // sample_type { type: 1, unit: 2 }
+                0x0a, 4, 0x08, 1, 0x10, 2, //
+                // sample { location_id: [1], value: [1] }
+                0x12, 6, 0x0a, 1, 1, 0x12, 1, 1, //
+                // location { id: 1, line { function_id: 1 } }
+                0x22, 6, 0x08, 1, 0x22, 2, 0x08, 1, //
+                // function { id: 1, name: 3, system_name: 3 }
+                0x2a, 6, 0x08, 1, 0x10, 3, 0x18, 3, //
+                // string_table: ["", "samples", "count", "f"]
+                0x32, 0, 0x32, 7, b's', b'a', b'm', b'p', b'l', b'e', b's', //
+                0x32, 5, b'c', b'o', b'u', b'n', b't', 0x32, 1, b'f', //
+                // period_type {}
+                0x5a, 0,
+            ]
+        );
+    }
+}
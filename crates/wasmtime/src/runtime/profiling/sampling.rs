@@ -0,0 +1,348 @@
+use super::pprof;
+use crate::prelude::*;
+use crate::runtime::vm::{Backtrace, CompiledModuleId};
+use crate::store::StoreOpaque;
+use crate::{Engine, FrameInfo, WasmBacktrace};
+use fxprof_processed_profile::{
+    CategoryHandle, Frame, FrameFlags, Profile, ReferenceTimestamp, Timestamp,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use wasmtime_environ::{demangle_function_name, demangle_function_name_or_index};
+
+/// The format in which a [`SamplingProfiler`] writes its profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// The JSON-formatted [Firefox "processed profile format"][fmt], which
+    /// can be visualized at <https://profiler.firefox.com/>.
+    ///
+    /// [fmt]: https://github.com/firefox-devtools/profiler/blob/main/docs-developer/processed-profile-format.md
+    Firefox,
+    /// The [pprof] protobuf format, which can be analyzed with `go tool pprof`
+    /// and many other tools.
+    ///
+    /// [pprof]: https://github.com/google/pprof/blob/main/proto/README.md
+    Pprof,
+}
+
+impl ProfileFormat {
+    /// Guesses the format to use for a profile written to `path` from its
+    /// extension: `.pb` and `.pprof` files use [`ProfileFormat::Pprof`] and
+    /// everything else uses [`ProfileFormat::Firefox`].
+    pub fn from_path(path: impl AsRef<Path>) -> ProfileFormat {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("pb" | "pprof") => ProfileFormat::Pprof,
+            _ => ProfileFormat::Firefox,
+        }
+    }
+}
+
+/// A sampling profiler for WebAssembly guests which is driven by the
+/// [`Store`](crate::Store) that it's attached to.
+///
+/// Unlike [`GuestProfiler`](crate::GuestProfiler), which requires the
+/// embedder to call [`GuestProfiler::sample`](crate::GuestProfiler::sample)
+/// at regular intervals, this profiler is attached to a store with
+/// [`Store::attach_sampling_profiler`](crate::Store::attach_sampling_profiler)
+/// after which samples are collected automatically until it's detached again
+/// with
+/// [`Store::detach_sampling_profiler`](crate::Store::detach_sampling_profiler).
+/// The resulting profile can then be written out in any of the supported
+/// [`ProfileFormat`]s with [`SamplingProfiler::finish`].
+///
+/// # Sampling
+///
+/// Sampling is driven by epoch interruption, so
+/// [`Config::epoch_interruption`](crate::Config::epoch_interruption) must be
+/// enabled. While the profiler is attached a background thread increments the
+/// [`Engine`]'s epoch once per sampling interval, and the store takes a sample
+/// each time guest code observes a new epoch. This means that samples are
+/// only taken at function entries and loop headers, and only while guest code
+/// is running.
+///
+/// As the epoch is shared by all stores within an engine, attaching a profiler
+/// makes the epoch advance for all of them. Within the profiled store itself
+/// epoch deadlines, and the behavior configured for when they're reached, keep
+/// working as usual, with each tick of the profiler counting as one tick.
+///
+/// # Frames
+///
+/// Stacks are captured in the same way as [`WasmBacktrace`], so they include
+/// the frames of all modules instantiated within the store, including the
+/// core modules of components. When modules have DWARF debug information and
+/// [`Config::wasm_backtrace_details`](crate::Config::wasm_backtrace_details)
+/// is enabled, frames are resolved to source functions and lines, including
+/// any inlined functions.
+#[derive(Debug)]
+pub struct SamplingProfiler {
+    name: String,
+    interval: Duration,
+    start: Instant,
+    start_time: SystemTime,
+    duration: Option<Duration>,
+    /// `(name, file)` of every function that appears in a location.
+    functions: Vec<(String, Option<String>)>,
+    function_ids: HashMap<(String, Option<String>), usize>,
+    /// Each location is a Wasm instruction which was on the stack, resolved to
+    /// `(function, line, column)` triples, innermost inlined function first.
+    locations: Vec<Vec<(usize, u32, u32)>>,
+    location_ids: HashMap<(CompiledModuleId, u32, Option<usize>), usize>,
+    /// The time and the stack of locations, newest frame first, of each
+    /// sample.
+    samples: Vec<(Duration, Vec<usize>)>,
+}
+
+impl SamplingProfiler {
+    /// Creates a new profiler which, once attached to a store, collects a
+    /// sample every `interval`.
+    ///
+    /// The `name` is recorded in the profile to help identify where it came
+    /// from.
+    pub fn new(name: &str, interval: Duration) -> SamplingProfiler {
+        SamplingProfiler {
+            name: name.to_string(),
+            interval,
+            start: Instant::now(),
+            start_time: SystemTime::now(),
+            duration: None,
+            functions: Vec::new(),
+            function_ids: HashMap::new(),
+            locations: Vec::new(),
+            location_ids: HashMap::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Returns the interval between samples this profiler was created with.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Returns the number of samples collected so far.
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Collects a sample of the Wasm frames currently on the stack of
+    /// `store`.
+    pub(crate) fn sample(&mut self, store: &StoreOpaque) {
+        let time = self.start.elapsed();
+        let trace = WasmBacktrace::from_captured(store, Backtrace::new(store), None);
+        let stack = trace
+            .frames()
+            .iter()
+            .map(|frame| self.location(frame))
+            .collect();
+        self.samples.push((time, stack));
+    }
+
+    fn location(&mut self, frame: &FrameInfo) -> usize {
+        let key = (
+            frame.module().compiled_module().unique_id(),
+            frame.func_index(),
+            frame.module_offset(),
+        );
+        if let Some(id) = self.location_ids.get(&key) {
+            return *id;
+        }
+
+        let mut func_name = String::new();
+        demangle_function_name_or_index(
+            &mut func_name,
+            frame.func_name(),
+            frame.func_index() as usize,
+        )
+        .unwrap();
+        let lines = if frame.symbols().is_empty() {
+            vec![(self.function(func_name, None), 0, 0)]
+        } else {
+            frame
+                .symbols()
+                .iter()
+                .map(|symbol| {
+                    let name = match symbol.name() {
+                        Some(name) => {
+                            let mut demangled = String::new();
+                            match demangle_function_name(&mut demangled, name) {
+                                Ok(()) => demangled,
+                                Err(_) => name.to_string(),
+                            }
+                        }
+                        None => func_name.clone(),
+                    };
+                    let file = symbol.file().map(|f| f.to_string());
+                    (
+                        self.function(name, file),
+                        symbol.line().unwrap_or(0),
+                        symbol.column().unwrap_or(0),
+                    )
+                })
+                .collect()
+        };
+
+        let id = self.locations.len();
+        self.locations.push(lines);
+        self.location_ids.insert(key, id);
+        id
+    }
+
+    fn function(&mut self, name: String, file: Option<String>) -> usize {
+        let key = (name, file);
+        if let Some(id) = self.function_ids.get(&key) {
+            return *id;
+        }
+        let id = self.functions.len();
+        self.functions.push(key.clone());
+        self.function_ids.insert(key, id);
+        id
+    }
+
+    /// Writes the profile collected so far to `output` in the given
+    /// `format`.
+    pub fn finish(mut self, format: ProfileFormat, output: impl std::io::Write) -> Result<()> {
+        self.duration.get_or_insert_with(|| self.start.elapsed());
+        match format {
+            ProfileFormat::Firefox => self.finish_firefox(output),
+            ProfileFormat::Pprof => self.finish_pprof(output),
+        }
+    }
+
+    fn finish_firefox(&self, output: impl std::io::Write) -> Result<()> {
+        let timestamp = |time: Duration| {
+            Timestamp::from_nanos_since_reference(time.as_nanos().try_into().unwrap())
+        };
+        let mut profile = Profile::new(
+            &self.name,
+            ReferenceTimestamp::from(self.start_time),
+            self.interval.into(),
+        );
+        let process = profile.add_process(&self.name, 0, timestamp(Duration::ZERO));
+        let thread = profile.add_thread(process, 0, timestamp(Duration::ZERO), true);
+        let labels = self
+            .functions
+            .iter()
+            .map(|(name, _)| profile.intern_string(name))
+            .collect::<Vec<_>>();
+        for (time, stack) in &self.samples {
+            // The Firefox profiler wants the oldest frame first, which for
+            // inlined functions is the outermost one.
+            let frames = stack
+                .iter()
+                .rev()
+                .flat_map(|location| self.locations[*location].iter().rev())
+                .map(|(function, _, _)| fxprof_processed_profile::FrameInfo {
+                    frame: Frame::Label(labels[*function]),
+                    category_pair: CategoryHandle::OTHER.into(),
+                    flags: FrameFlags::empty(),
+                });
+            profile.add_sample(thread, timestamp(*time), frames, Duration::ZERO.into(), 1);
+        }
+        let end = timestamp(self.duration.unwrap());
+        profile.set_thread_end_time(thread, end);
+        profile.set_process_end_time(process, end);
+
+        serde_json::to_writer(output, &profile)?;
+        Ok(())
+    }
+
+    fn finish_pprof(&self, mut output: impl std::io::Write) -> Result<()> {
+        let nanos = |d: Duration| i64::try_from(d.as_nanos()).unwrap_or(i64::MAX);
+        let interval = nanos(self.interval);
+
+        let mut profile = pprof::Profile::new();
+        profile.add_sample_type("samples", "count");
+        profile.add_sample_type("wall", "nanoseconds");
+        profile.set_period("wall", "nanoseconds", interval);
+        profile.set_time(
+            nanos(
+                self.start_time
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default(),
+            ),
+            nanos(self.duration.unwrap()),
+        );
+        let functions = self
+            .functions
+            .iter()
+            .map(|(name, file)| profile.add_function(name, file.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>();
+        let locations = self
+            .locations
+            .iter()
+            .map(|lines| {
+                profile.add_location(
+                    lines
+                        .iter()
+                        .map(|(function, line, column)| {
+                            (functions[*function], i64::from(*line), i64::from(*column))
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (_, stack) in &self.samples {
+            profile.add_sample(
+                stack.iter().map(|location| locations[*location]).collect(),
+                vec![1, interval],
+            );
+        }
+
+        output.write_all(&profile.encode())?;
+        Ok(())
+    }
+}
+
+/// A [`SamplingProfiler`] which is attached to a store.
+pub(crate) struct AttachedProfiler {
+    pub profiler: SamplingProfiler,
+    /// The epoch deadline which the store's owner configured, as opposed to
+    /// the one the store actually uses so the profiler sees every tick.
+    pub user_deadline: u64,
+    _ticker: EpochTicker,
+}
+
+impl AttachedProfiler {
+    pub fn new(profiler: SamplingProfiler, engine: &Engine, user_deadline: u64) -> Self {
+        let ticker = EpochTicker::new(engine.clone(), profiler.interval);
+        AttachedProfiler {
+            profiler,
+            user_deadline,
+            _ticker: ticker,
+        }
+    }
+
+    /// Returns the epoch deadline the store should actually use, given the
+    /// current epoch, so that it's interrupted on every tick.
+    pub fn deadline(&self, current_epoch: u64) -> u64 {
+        self.user_deadline.min(current_epoch.saturating_add(1))
+    }
+}
+
+/// Increments an engine's epoch at a regular interval on a background thread
+/// for as long as it's alive.
+struct EpochTicker {
+    stop: Arc<AtomicBool>,
+}
+
+impl EpochTicker {
+    fn new(engine: Engine, interval: Duration) -> EpochTicker {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop2 = stop.clone();
+        std::thread::spawn(move || {
+            while !stop2.load(Ordering::Relaxed) {
+                std::thread::sleep(interval);
+                engine.increment_epoch();
+            }
+        });
+        EpochTicker { stop }
+    }
+}
+
+impl Drop for EpochTicker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
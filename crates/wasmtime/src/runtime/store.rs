@@ -223,9 +223,13 @@ pub struct StoreInner<T> {
 
     limiter: Option<ResourceLimiterInner<T>>,
     call_hook: Option<CallHookInner<T>>,
+    #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+    component_call_interceptor: Option<Box<dyn crate::component::CallInterceptor<T>>>,
     #[cfg(target_has_atomic = "64")]
     epoch_deadline_behavior:
         Option<Box<dyn FnMut(StoreContextMut<T>) -> Result<UpdateDeadline> + Send + Sync>>,
+    #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+    sampling_profiler: Option<Box<crate::runtime::profiling::AttachedProfiler>>,
     // for comments about `ManuallyDrop`, see `Store::into_data`
     data: ManuallyDrop<T>,
 }
@@ -384,6 +388,18 @@ pub struct StoreOpaque {
     component_calls: crate::runtime::vm::component::CallContexts,
     #[cfg(feature = "component-model")]
     host_resource_data: crate::component::HostResourceData,
+    #[cfg(feature = "component-model")]
+    component_limits: crate::runtime::limits::ComponentLimits,
+    /// Bytes copied by adapters since the host last called an export, see
+    /// `ResourceLimiter::component_adapter_copy_bytes`.
+    #[cfg(feature = "component-model")]
+    component_adapter_copied: usize,
+
+    /// Store-wide state of the component model async ABI, such as tasks,
+    /// waitables, streams, and futures, along with the host futures driving
+    /// them.
+    #[cfg(feature = "component-model-async")]
+    concurrent_state: crate::component::concurrent::ConcurrentState,
 
     /// State related to the executor of wasm code.
     ///
@@ -559,6 +575,12 @@ impl<T> Store<T> {
                 component_calls: Default::default(),
                 #[cfg(feature = "component-model")]
                 host_resource_data: Default::default(),
+                #[cfg(feature = "component-model")]
+                component_limits: Default::default(),
+                #[cfg(feature = "component-model")]
+                component_adapter_copied: 0,
+                #[cfg(feature = "component-model-async")]
+                concurrent_state: Default::default(),
                 #[cfg(has_host_compiler_backend)]
                 executor: if cfg!(feature = "pulley") && engine.target().is_pulley() {
                     Executor::Interpreter(Interpreter::new(engine))
@@ -573,8 +595,12 @@ impl<T> Store<T> {
             },
             limiter: None,
             call_hook: None,
+            #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+            component_call_interceptor: None,
             #[cfg(target_has_atomic = "64")]
             epoch_deadline_behavior: None,
+            #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+            sampling_profiler: None,
             data: ManuallyDrop::new(data),
         });
 
@@ -733,6 +759,11 @@ impl<T> Store<T> {
             let l = limiter(&mut inner.data);
             (l.instances(), l.tables(), l.memories())
         };
+        #[cfg(feature = "component-model")]
+        {
+            let limits = crate::runtime::limits::ComponentLimits::new(limiter(&mut inner.data));
+            inner.inner.set_component_limits(limits);
+        }
         let innermost = &mut inner.inner;
         innermost.instance_limit = instance_limit;
         innermost.table_limit = table_limit;
@@ -764,6 +795,24 @@ impl<T> Store<T> {
         self.inner.call_hook = Some(CallHookInner::Sync(Box::new(hook)));
     }
 
+    /// Configures an interceptor which observes calls of component functions
+    /// exported to the host and of host functions imported by components.
+    ///
+    /// Unlike [`Store::call_hook`] the interceptor is told which function is
+    /// being called along with its arguments and results. See
+    /// [`CallInterceptor`](crate::component::CallInterceptor) for more
+    /// information.
+    ///
+    /// Only one interceptor can be configured at a time and this replaces any
+    /// previously configured interceptor.
+    #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+    pub fn component_call_interceptor(
+        &mut self,
+        interceptor: impl crate::component::CallInterceptor<T> + 'static,
+    ) {
+        self.inner.component_call_interceptor = Some(Box::new(interceptor));
+    }
+
     /// Returns the [`Engine`] that this store is associated with.
     pub fn engine(&self) -> &Engine {
         self.inner.engine()
@@ -943,6 +992,31 @@ impl<T> Store<T> {
     ) {
         self.inner.epoch_deadline_callback(Box::new(callback));
     }
+
+    /// Attaches a [`SamplingProfiler`](crate::SamplingProfiler) to this store,
+    /// which then collects samples of the WebAssembly executing within this
+    /// store until it's detached with [`Store::detach_sampling_profiler`].
+    ///
+    /// See the documentation of [`SamplingProfiler`](crate::SamplingProfiler)
+    /// for how samples are collected.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if
+    /// [`Config::epoch_interruption`](crate::Config::epoch_interruption) isn't
+    /// enabled, or if a profiler is already attached to this store.
+    #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+    pub fn attach_sampling_profiler(&mut self, profiler: crate::SamplingProfiler) -> Result<()> {
+        self.inner.attach_sampling_profiler(profiler)
+    }
+
+    /// Detaches the profiler previously attached with
+    /// [`Store::attach_sampling_profiler`], returning it, or `None` if no
+    /// profiler is attached.
+    #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+    pub fn detach_sampling_profiler(&mut self) -> Option<crate::SamplingProfiler> {
+        self.inner.detach_sampling_profiler()
+    }
 }
 
 impl<'a, T> StoreContext<'a, T> {
@@ -1049,6 +1123,31 @@ impl<T> StoreInner<T> {
         &mut self.data
     }
 
+    #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+    #[inline]
+    pub(crate) fn has_component_call_interceptor(&self) -> bool {
+        self.component_call_interceptor.is_some()
+    }
+
+    /// Takes the configured component call interceptor, if any, out of this
+    /// store so it can be invoked with access to the store.
+    ///
+    /// The interceptor must be returned with `restore_component_call_interceptor`.
+    #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+    pub(crate) fn take_component_call_interceptor(
+        &mut self,
+    ) -> Option<Box<dyn crate::component::CallInterceptor<T>>> {
+        self.component_call_interceptor.take()
+    }
+
+    #[cfg(all(feature = "component-model", feature = "call-hook", feature = "std"))]
+    pub(crate) fn restore_component_call_interceptor(
+        &mut self,
+        interceptor: Box<dyn crate::component::CallInterceptor<T>>,
+    ) {
+        self.component_call_interceptor = Some(interceptor);
+    }
+
     #[inline]
     pub fn call_hook(&mut self, s: CallHook) -> Result<()> {
         if self.inner.pkey.is_none() && self.call_hook.is_none() {
@@ -1855,6 +1954,44 @@ at https://bytecodealliance.org/security.
         )
     }
 
+    #[inline]
+    #[cfg(feature = "component-model-async")]
+    pub(crate) fn concurrent_state_mut(
+        &mut self,
+    ) -> &mut crate::component::concurrent::ConcurrentState {
+        &mut self.concurrent_state
+    }
+
+    #[cfg(feature = "component-model")]
+    fn set_component_limits(&mut self, limits: crate::runtime::limits::ComponentLimits) {
+        self.component_calls
+            .set_handle_limits(limits.resource_handles, limits.instance_resource_handles);
+        self.component_limits = limits;
+    }
+
+    #[cfg(feature = "component-model")]
+    pub(crate) fn component_limits(&self) -> &crate::runtime::limits::ComponentLimits {
+        &self.component_limits
+    }
+
+    /// Charges `bytes` copied between components by an adapter against
+    /// `ResourceLimiter::component_adapter_copy_bytes`.
+    #[cfg(feature = "component-model")]
+    pub(crate) fn component_adapter_copy(&mut self, bytes: u64) -> Result<()> {
+        let bytes = usize::try_from(bytes).unwrap_or(usize::MAX);
+        self.component_adapter_copied = self.component_adapter_copied.saturating_add(bytes);
+        if self.component_adapter_copied > self.component_limits.adapter_copy_bytes {
+            return Err(Trap::ComponentLimitExceeded)
+                .context("too many bytes copied between components in a single call");
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "component-model")]
+    pub(crate) fn reset_component_adapter_copied(&mut self) {
+        self.component_adapter_copied = 0;
+    }
+
     #[cfg(feature = "component-model")]
     pub(crate) fn push_component_instance(&mut self, instance: crate::component::Instance) {
         // We don't actually need the instance itself right now, but it seems
@@ -2005,6 +2142,20 @@ unsafe impl<T> crate::runtime::vm::VMStore for StoreInner<T> {
 
     #[cfg(target_has_atomic = "64")]
     fn new_epoch(&mut self) -> Result<u64, anyhow::Error> {
+        // With a sampling profiler attached every tick is observed here, but
+        // the configured behavior only applies once the deadline configured
+        // by the store's owner is reached.
+        #[cfg(feature = "profiling")]
+        if let Some(attached) = &mut self.sampling_profiler {
+            attached.profiler.sample(&self.inner);
+            let current_epoch = self.inner.engine().current_epoch();
+            if current_epoch < attached.user_deadline {
+                let deadline = attached.deadline(current_epoch);
+                self.set_raw_epoch_deadline(deadline);
+                return Ok(deadline);
+            }
+        }
+
         // Temporarily take the configured behavior to avoid mutably borrowing
         // multiple times.
         let mut behavior = self.epoch_deadline_behavior.take();
@@ -2096,13 +2247,55 @@ impl<T> StoreInner<T> {
         // Also, note that when this update is performed while Wasm is
         // on the stack, the Wasm will reload the new value once we
         // return into it.
+        let current_epoch = self.engine().current_epoch();
+        let deadline = current_epoch + delta;
+        #[cfg(feature = "profiling")]
+        let deadline = match &mut self.sampling_profiler {
+            Some(attached) => {
+                attached.user_deadline = deadline;
+                attached.deadline(current_epoch)
+            }
+            None => deadline,
+        };
+        self.set_raw_epoch_deadline(deadline);
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    fn set_raw_epoch_deadline(&mut self, deadline: u64) {
+        // Safety: see `set_epoch_deadline` above.
         let epoch_deadline = unsafe {
             self.vm_store_context_ptr()
                 .as_mut()
                 .epoch_deadline
                 .get_mut()
         };
-        *epoch_deadline = self.engine().current_epoch() + delta;
+        *epoch_deadline = deadline;
+    }
+
+    #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+    fn attach_sampling_profiler(&mut self, profiler: crate::SamplingProfiler) -> Result<()> {
+        if !self.engine().tunables().epoch_interruption {
+            bail!("sampling profiler requires epoch interruption to be enabled in the config");
+        }
+        if self.sampling_profiler.is_some() {
+            bail!("a sampling profiler is already attached to this store");
+        }
+        let attached = crate::runtime::profiling::AttachedProfiler::new(
+            profiler,
+            self.engine(),
+            self.get_epoch_deadline(),
+        );
+        let deadline = attached.deadline(self.engine().current_epoch());
+        self.sampling_profiler = Some(Box::new(attached));
+        self.set_raw_epoch_deadline(deadline);
+        Ok(())
+    }
+
+    #[cfg(all(feature = "profiling", target_has_atomic = "64"))]
+    fn detach_sampling_profiler(&mut self) -> Option<crate::SamplingProfiler> {
+        let attached = self.sampling_profiler.take()?;
+        self.set_raw_epoch_deadline(attached.user_deadline);
+        Some(attached.profiler)
     }
 
     #[cfg(target_has_atomic = "64")]
@@ -97,6 +97,12 @@ impl<T> Store<T> {
             let l = limiter(&mut inner.data);
             (l.instances(), l.tables(), l.memories())
         };
+        #[cfg(feature = "component-model")]
+        {
+            let limits =
+                crate::runtime::limits::ComponentLimits::new_async(limiter(&mut inner.data));
+            inner.inner.set_component_limits(limits);
+        }
         let innermost = &mut inner.inner;
         innermost.instance_limit = instance_limit;
         innermost.table_limit = table_limit;
@@ -260,7 +260,7 @@ impl WasmBacktrace {
         Self::from_captured(store.0, crate::runtime::vm::Backtrace::new(store.0), None)
     }
 
-    fn from_captured(
+    pub(crate) fn from_captured(
         store: &StoreOpaque,
         runtime_trace: crate::runtime::vm::Backtrace,
         trap_pc: Option<usize>,
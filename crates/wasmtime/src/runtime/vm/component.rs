@@ -29,8 +29,13 @@ const INVALID_PTR: usize = 0xdead_dead_beef_beef_u64 as usize;
 
 mod libcalls;
 mod resources;
+#[cfg(feature = "component-model-async")]
+mod states;
 
+pub use self::resources::ResourceTableSnapshot;
 pub use self::resources::{CallContexts, ResourceTable, ResourceTables};
+#[cfg(feature = "component-model-async")]
+pub use self::states::{ErrorContextState, InstanceState, StateTable, TransmitEnd, WaitableState};
 
 /// Runtime representation of a component instance and all state necessary for
 /// the instance itself.
@@ -58,6 +63,17 @@ pub struct ComponentInstance {
     /// is how this field is manipulated.
     component_resource_tables: PrimaryMap<TypeResourceTableIndex, ResourceTable>,
 
+    /// State of the async ABI for each (sub)component instance within this
+    /// component, most notably its table of waitable handles.
+    #[cfg(feature = "component-model-async")]
+    instance_states: PrimaryMap<RuntimeComponentInstanceIndex, InstanceState>,
+
+    /// Tables of `error-context` handles for all
+    /// `TypeComponentLocalErrorContextTableIndex` values for this component.
+    #[cfg(feature = "component-model-async")]
+    error_context_tables:
+        PrimaryMap<TypeComponentLocalErrorContextTableIndex, StateTable<ErrorContextState>>,
+
     /// Storage for the type information about resources within this component
     /// instance.
     ///
@@ -214,6 +230,20 @@ impl ComponentInstance {
             component_resource_tables.push(ResourceTable::default());
         }
 
+        #[cfg(feature = "component-model-async")]
+        let (instance_states, error_context_tables) = {
+            let component = runtime_info.component();
+            let mut instance_states = PrimaryMap::new();
+            for _ in 0..component.num_runtime_component_instances {
+                instance_states.push(InstanceState::default());
+            }
+            let mut error_context_tables = PrimaryMap::new();
+            for _ in 0..component.num_error_context_tables {
+                error_context_tables.push(StateTable::default());
+            }
+            (instance_states, error_context_tables)
+        };
+
         ptr::write(
             ptr.as_ptr(),
             ComponentInstance {
@@ -227,6 +257,10 @@ impl ComponentInstance {
                     .unwrap(),
                 ),
                 component_resource_tables,
+                #[cfg(feature = "component-model-async")]
+                instance_states,
+                #[cfg(feature = "component-model-async")]
+                error_context_tables,
                 runtime_info,
                 resource_types,
                 store: VMStoreRawPtr(store),
@@ -313,6 +347,18 @@ impl ComponentInstance {
         }
     }
 
+    /// Returns the async callback pointer corresponding to the index provided.
+    ///
+    /// This can only be called after `idx` has been initialized at runtime
+    /// during the instantiation process of a component.
+    pub fn runtime_callback(&self, idx: RuntimeCallbackIndex) -> NonNull<VMFuncRef> {
+        unsafe {
+            let ret = *self.vmctx_plus_offset::<VmPtr<_>>(self.offsets.runtime_callback(idx));
+            debug_assert!(ret.as_ptr() as usize != INVALID_PTR);
+            ret.as_non_null()
+        }
+    }
+
     /// Returns the host information for the lowered function at the index
     /// specified.
     ///
@@ -668,6 +714,24 @@ impl ComponentInstance {
         self.resource_tables().exit_call()
     }
 
+    /// Returns the async ABI state of the (sub)component `instance`.
+    #[cfg(feature = "component-model-async")]
+    pub fn instance_state(
+        &mut self,
+        instance: RuntimeComponentInstanceIndex,
+    ) -> &mut InstanceState {
+        &mut self.instance_states[instance]
+    }
+
+    /// Returns the table of `error-context` handles for `ty`.
+    #[cfg(feature = "component-model-async")]
+    pub fn error_context_table(
+        &mut self,
+        ty: TypeComponentLocalErrorContextTableIndex,
+    ) -> &mut StateTable<ErrorContextState> {
+        &mut self.error_context_tables[ty]
+    }
+
     #[cfg(feature = "component-model-async")]
     pub(crate) fn future_transfer(
         &mut self,
@@ -675,8 +739,12 @@ impl ComponentInstance {
         src: TypeFutureTableIndex,
         dst: TypeFutureTableIndex,
     ) -> Result<u32> {
-        _ = (src_idx, src, dst);
-        todo!()
+        let store = self.store();
+        unsafe {
+            (*store)
+                .component_async_store()
+                .future_transfer(self, src_idx, src, dst)
+        }
     }
 
     #[cfg(feature = "component-model-async")]
@@ -686,8 +754,12 @@ impl ComponentInstance {
         src: TypeStreamTableIndex,
         dst: TypeStreamTableIndex,
     ) -> Result<u32> {
-        _ = (src_idx, src, dst);
-        todo!()
+        let store = self.store();
+        unsafe {
+            (*store)
+                .component_async_store()
+                .stream_transfer(self, src_idx, src, dst)
+        }
     }
 
     #[cfg(feature = "component-model-async")]
@@ -697,8 +769,12 @@ impl ComponentInstance {
         src: TypeComponentLocalErrorContextTableIndex,
         dst: TypeComponentLocalErrorContextTableIndex,
     ) -> Result<u32> {
-        _ = (src_idx, src, dst);
-        todo!()
+        let store = self.store();
+        unsafe {
+            (*store)
+                .component_async_store()
+                .error_context_transfer(self, src_idx, src, dst)
+        }
     }
 }
 
@@ -575,6 +575,12 @@ unsafe fn resource_exit_call(vmctx: NonNull<VMComponentContext>) -> Result<()> {
     ComponentInstance::from_vmctx(vmctx, |instance| instance.resource_exit_call())
 }
 
+unsafe fn adapter_copy(vmctx: NonNull<VMComponentContext>, bytes: u64) -> Result<()> {
+    ComponentInstance::from_vmctx(vmctx, |instance| {
+        (*instance.store()).component_adapter_copy(bytes)
+    })
+}
+
 unsafe fn trap(_vmctx: NonNull<VMComponentContext>, code: u8) -> Result<Infallible> {
     Err(wasmtime_environ::Trap::from_u8(code).unwrap().into())
 }
@@ -589,6 +595,7 @@ unsafe fn backpressure_set(
         (*instance.store())
             .component_async_store()
             .backpressure_set(
+                instance,
                 wasmtime_environ::component::RuntimeComponentInstanceIndex::from_u32(
                     caller_instance,
                 ),
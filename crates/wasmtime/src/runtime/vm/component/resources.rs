@@ -24,6 +24,7 @@
 //! namely in the `Resource<T>` and `ResourceAny` types.
 
 use crate::prelude::*;
+use crate::Trap;
 use core::mem;
 use wasmtime_environ::component::TypeResourceTableIndex;
 use wasmtime_environ::PrimaryMap;
@@ -78,6 +79,8 @@ pub struct ResourceTable {
     next: u32,
     /// Runtime state of all slots.
     slots: Vec<Slot>,
+    /// Number of slots which aren't free.
+    live: usize,
 }
 
 enum Slot {
@@ -102,9 +105,39 @@ enum Slot {
 /// This is created once per `Store` and updated and modified throughout the
 /// lifetime of the store. This primarily tracks borrow counts and what slots
 /// should be updated when calls go out of scope.
+///
+/// This also holds the limits on the number of live handles configured for
+/// the store since it's available whenever handles are created.
 #[derive(Default)]
 pub struct CallContexts {
     scopes: Vec<CallContext>,
+    handle_limits: HandleLimits,
+}
+
+#[derive(Copy, Clone)]
+struct HandleLimits {
+    per_table: usize,
+    per_instance: usize,
+}
+
+impl Default for HandleLimits {
+    fn default() -> HandleLimits {
+        HandleLimits {
+            per_table: usize::MAX,
+            per_instance: usize::MAX,
+        }
+    }
+}
+
+impl CallContexts {
+    /// Configures the maximum number of live handles within each table and
+    /// across all tables of a component instance.
+    pub fn set_handle_limits(&mut self, per_table: usize, per_instance: usize) {
+        self.handle_limits = HandleLimits {
+            per_table,
+            per_instance,
+        };
+    }
 }
 
 #[derive(Default)]
@@ -127,11 +160,35 @@ impl ResourceTables<'_> {
         }
     }
 
+    /// Inserts `slot` into the table for `ty`, first checking the limits on
+    /// the number of live handles.
+    fn insert(&mut self, ty: Option<TypeResourceTableIndex>, slot: Slot) -> Result<u32> {
+        let limits = self.calls.handle_limits;
+        if self.table(ty).live >= limits.per_table {
+            return Err(Trap::ComponentLimitExceeded)
+                .context("too many live handles in a resource table");
+        }
+        if ty.is_some() && limits.per_instance != usize::MAX {
+            let live = self
+                .tables
+                .as_ref()
+                .unwrap()
+                .values()
+                .map(|table| table.live)
+                .sum::<usize>();
+            if live >= limits.per_instance {
+                return Err(Trap::ComponentLimitExceeded)
+                    .context("too many live resource handles in a component instance");
+            }
+        }
+        self.table(ty).insert(slot)
+    }
+
     /// Implementation of the `resource.new` canonical intrinsic.
     ///
     /// Note that this is the same as `resource_lower_own`.
     pub fn resource_new(&mut self, ty: Option<TypeResourceTableIndex>, rep: u32) -> Result<u32> {
-        self.table(ty).insert(Slot::Own { rep, lend_count: 0 })
+        self.insert(ty, Slot::Own { rep, lend_count: 0 })
     }
 
     /// Implementation of the `resource.rep` canonical intrinsic.
@@ -183,7 +240,7 @@ impl ResourceTables<'_> {
         ty: Option<TypeResourceTableIndex>,
         rep: u32,
     ) -> Result<u32> {
-        self.table(ty).insert(Slot::Own { rep, lend_count: 0 })
+        self.insert(ty, Slot::Own { rep, lend_count: 0 })
     }
 
     /// Attempts to remove an "own" handle from the specified table and its
@@ -252,9 +309,10 @@ impl ResourceTables<'_> {
         rep: u32,
     ) -> Result<u32> {
         let scope = self.calls.scopes.len() - 1;
+        let idx = self.insert(ty, Slot::Borrow { rep, scope })?;
         let borrow_count = &mut self.calls.scopes.last_mut().unwrap().borrow_count;
         *borrow_count = borrow_count.checked_add(1).unwrap();
-        self.table(ty).insert(Slot::Borrow { rep, scope })
+        Ok(idx)
     }
 
     /// Enters a new calling context, starting a fresh count of borrows and
@@ -303,6 +361,7 @@ impl ResourceTable {
             Slot::Free { next } => next,
             _ => unreachable!(),
         };
+        self.live += 1;
 
         // The component model reserves index 0 as never allocatable so add one
         // to the table index to start the numbering at 1 instead. Also note
@@ -346,6 +405,68 @@ impl ResourceTable {
         let to_fill = Slot::Free { next: self.next };
         let ret = mem::replace(self.get_mut(idx)?, to_fill);
         self.next = idx - 1;
+        self.live -= 1;
         Ok(ret)
     }
+
+    /// Captures the state of this table for a snapshot of its instance.
+    ///
+    /// This fails if any handle is currently lent out or is a `borrow` since
+    /// those only exist for the duration of a call.
+    pub fn snapshot(&self) -> Result<ResourceTableSnapshot> {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| match slot {
+                Slot::Free { next } => Ok(SlotSnapshot::Free { next: *next }),
+                Slot::Own { rep, lend_count: 0 } => Ok(SlotSnapshot::Own { rep: *rep }),
+                Slot::Own { .. } | Slot::Borrow { .. } => {
+                    bail!("cannot snapshot a resource table with active borrows")
+                }
+            })
+            .collect::<Result<_>>()?;
+        Ok(ResourceTableSnapshot {
+            next: self.next,
+            slots,
+        })
+    }
+
+    /// Replaces the contents of this table with those of `snapshot`.
+    pub fn restore(&mut self, snapshot: &ResourceTableSnapshot) -> Result<()> {
+        if self
+            .slots
+            .iter()
+            .any(|slot| !matches!(slot, Slot::Free { .. }))
+        {
+            bail!("cannot restore into a resource table which is in use");
+        }
+        self.next = snapshot.next;
+        self.slots = snapshot
+            .slots
+            .iter()
+            .map(|slot| match *slot {
+                SlotSnapshot::Free { next } => Slot::Free { next },
+                SlotSnapshot::Own { rep } => Slot::Own { rep, lend_count: 0 },
+            })
+            .collect();
+        self.live = snapshot
+            .slots
+            .iter()
+            .filter(|slot| matches!(slot, SlotSnapshot::Own { .. }))
+            .count();
+        Ok(())
+    }
+}
+
+/// Serializable state of a `ResourceTable`, see `ResourceTable::snapshot`.
+#[derive(Clone, Debug, serde_derive::Serialize, serde_derive::Deserialize)]
+pub struct ResourceTableSnapshot {
+    next: u32,
+    slots: Vec<SlotSnapshot>,
+}
+
+#[derive(Clone, Debug, serde_derive::Serialize, serde_derive::Deserialize)]
+enum SlotSnapshot {
+    Free { next: u32 },
+    Own { rep: u32 },
 }
@@ -0,0 +1,186 @@
+//! Per-instance state used by the component model async ABI.
+//!
+//! Each (sub)component instance has a single table of "waitable" handles which
+//! is shared between subtasks, waitable sets, and the readable/writable ends of
+//! streams and futures. Additionally each `error-context` table within a
+//! component has its own table of handles.
+//!
+//! Entries within these tables are lightweight and only refer to store-wide
+//! state, managed in `crate::runtime::component::concurrent`, via a `rep`.
+//! This module only deals with allocating handles and mapping them back to
+//! what they refer to.
+
+use crate::prelude::*;
+use alloc::collections::VecDeque;
+use core::mem;
+use wasmtime_environ::component::{TypeFutureTableIndex, TypeStreamTableIndex};
+
+/// The maximum handle value is specified in
+/// <https://github.com/WebAssembly/component-model/blob/main/design/mvp/CanonicalABI.md>
+/// currently and keeps the upper bit free for use in the component.
+const MAX_HANDLE: u32 = 1 << 30;
+
+/// A generic slab of handles, numbered starting at 1.
+pub struct StateTable<T> {
+    /// Next slot to allocate, or `self.slots.len()` if they're all full.
+    next: u32,
+    /// Runtime state of all slots.
+    slots: Vec<Slot<T>>,
+}
+
+enum Slot<T> {
+    Free { next: u32 },
+    Occupied(T),
+}
+
+impl<T> Default for StateTable<T> {
+    fn default() -> Self {
+        StateTable {
+            next: 0,
+            slots: Vec::new(),
+        }
+    }
+}
+
+impl<T> StateTable<T> {
+    /// Inserts `value` into this table, returning its new handle.
+    pub fn insert(&mut self, value: T) -> Result<u32> {
+        let next = self.next as usize;
+        if next == self.slots.len() {
+            self.slots.push(Slot::Free {
+                next: self.next.checked_add(1).unwrap(),
+            });
+        }
+        let ret = self.next;
+        self.next = match mem::replace(&mut self.slots[next], Slot::Occupied(value)) {
+            Slot::Free { next } => next,
+            Slot::Occupied(_) => unreachable!(),
+        };
+
+        // Like resources, handle 0 is never allocated so tables start at 1.
+        let ret = ret + 1;
+        if ret >= MAX_HANDLE {
+            bail!("cannot allocate another handle: index overflow");
+        }
+        Ok(ret)
+    }
+
+    fn slot_index(&self, handle: u32) -> Option<usize> {
+        usize::try_from(handle.checked_sub(1)?).ok()
+    }
+
+    /// Returns the value stored for `handle`, if any.
+    pub fn get(&self, handle: u32) -> Option<&T> {
+        match self.slots.get(self.slot_index(handle)?)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Free { .. } => None,
+        }
+    }
+
+    /// Same as `get`, but returns a mutable reference.
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut T> {
+        let index = self.slot_index(handle)?;
+        match self.slots.get_mut(index)? {
+            Slot::Occupied(value) => Some(value),
+            Slot::Free { .. } => None,
+        }
+    }
+
+    /// Removes `handle` from this table, returning what it referred to.
+    pub fn remove(&mut self, handle: u32) -> Option<T> {
+        let index = self.slot_index(handle)?;
+        match self.slots.get(index)? {
+            Slot::Occupied(_) => {}
+            Slot::Free { .. } => return None,
+        }
+        let prev = mem::replace(&mut self.slots[index], Slot::Free { next: self.next });
+        self.next = handle - 1;
+        match prev {
+            Slot::Occupied(value) => Some(value),
+            Slot::Free { .. } => unreachable!(),
+        }
+    }
+
+    /// Iterates over all occupied handles in this table.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &T)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| match slot {
+                Slot::Occupied(value) => Some((u32::try_from(i).unwrap() + 1, value)),
+                Slot::Free { .. } => None,
+            })
+    }
+}
+
+/// What a handle within an instance's waitable table refers to.
+///
+/// Each `rep` is an index into store-wide state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WaitableState {
+    /// A subtask started by this instance.
+    Task { rep: u32 },
+    /// A waitable set created by this instance.
+    Set { rep: u32 },
+    /// One or both ends of a stream.
+    Stream {
+        ty: TypeStreamTableIndex,
+        rep: u32,
+        end: TransmitEnd,
+    },
+    /// One or both ends of a future.
+    Future {
+        ty: TypeFutureTableIndex,
+        rep: u32,
+        end: TransmitEnd,
+    },
+}
+
+/// Which end(s) of a stream or future a handle refers to.
+///
+/// `stream.new` and `future.new` return a single handle which refers to both
+/// ends. Once the readable end is transferred elsewhere (e.g. by passing it as
+/// a parameter) the handle continues to refer to only the writable end.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransmitEnd {
+    /// Both ends are owned by this instance.
+    Local,
+    /// Only the readable end is owned by this instance.
+    Read,
+    /// Only the writable end is owned by this instance.
+    Write,
+}
+
+impl TransmitEnd {
+    /// Whether this handle may be used to read.
+    pub fn readable(&self) -> bool {
+        matches!(self, TransmitEnd::Local | TransmitEnd::Read)
+    }
+
+    /// Whether this handle may be used to write.
+    pub fn writable(&self) -> bool {
+        matches!(self, TransmitEnd::Local | TransmitEnd::Write)
+    }
+}
+
+/// State of a single (sub)component instance related to async tasks.
+#[derive(Default)]
+pub struct InstanceState {
+    /// Handles owned by this instance.
+    pub handles: StateTable<WaitableState>,
+    /// Whether `backpressure.set` has disabled entering this instance.
+    pub backpressure: bool,
+    /// Number of tasks within this instance which are synchronously blocked,
+    /// during which no other task may run in this instance.
+    pub blocked: u32,
+    /// Tasks, identified by their store-wide `rep`, which are waiting for
+    /// this instance to become available.
+    pub pending: VecDeque<u32>,
+}
+
+/// A handle within an `error-context` table.
+#[derive(Copy, Clone, Debug)]
+pub struct ErrorContextState {
+    /// The store-wide `rep` of the error context.
+    pub rep: u32,
+}
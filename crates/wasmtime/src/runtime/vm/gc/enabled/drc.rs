@@ -1032,6 +1032,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_coverage_counters: 0,
         });
 
         assert_eq!(
@@ -1062,6 +1063,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_coverage_counters: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_next() as usize,
@@ -1091,6 +1093,7 @@ mod tests {
             num_defined_globals: 0,
             num_defined_tags: 0,
             num_escaped_funcs: 0,
+            num_coverage_counters: 0,
         });
         assert_eq!(
             offsets.vm_gc_ref_activation_table_end() as usize,
@@ -399,6 +399,18 @@ impl Instance {
             .cast()
     }
 
+    /// Returns the code coverage counters of this instance, one per block in
+    /// the module's `CoverageMap`.
+    pub fn coverage_counters_mut(&mut self) -> &mut [u64] {
+        let offsets = self.runtime_info.offsets();
+        let begin = offsets.vmctx_coverage_counters_begin();
+        let len = usize::try_from(offsets.num_coverage_counters).unwrap();
+        unsafe {
+            let ptr = self.vmctx_plus_offset_mut::<u64>(begin);
+            core::slice::from_raw_parts_mut(ptr.as_ptr(), len)
+        }
+    }
+
     pub(crate) fn env_module(&self) -> &Arc<wasmtime_environ::Module> {
         self.runtime_info.env_module()
     }
@@ -1494,6 +1506,14 @@ impl Instance {
             ));
             ptr = ptr.add(1);
         }
+
+        // Zero-initialize the code coverage counters, if any.
+        let ptr = self.vmctx_plus_offset_mut::<u64>(offsets.vmctx_coverage_counters_begin());
+        ptr::write_bytes(
+            ptr.as_ptr(),
+            0,
+            usize::try_from(offsets.num_coverage_counters).unwrap(),
+        );
     }
 
     fn wasm_fault(&self, addr: usize) -> Option<WasmFault> {
@@ -168,16 +168,8 @@ pub trait RustGenerator<'a> {
             TypeDefKind::Enum(_) => {
                 panic!("unsupported anonymous type reference: enum")
             }
-            TypeDefKind::Future(ty) => {
-                self.push_str("wasmtime::component::FutureReader<");
-                self.print_optional_ty(ty.as_ref(), TypeMode::Owned);
-                self.push_str(">");
-            }
-            TypeDefKind::Stream(ty) => {
-                self.push_str("wasmtime::component::StreamReader<");
-                self.print_optional_ty(ty.as_ref(), TypeMode::Owned);
-                self.push_str(">");
-            }
+            TypeDefKind::Future(ty) => self.print_future(ty.as_ref()),
+            TypeDefKind::Stream(ty) => self.print_stream(ty.as_ref()),
             TypeDefKind::Handle(handle) => {
                 self.print_handle(handle);
             }
@@ -226,14 +218,14 @@ pub trait RustGenerator<'a> {
 
     fn print_stream(&mut self, ty: Option<&Type>) {
         let wt = self.wasmtime_path();
-        self.push_str(&format!("{wt}::component::StreamReader<"));
+        self.push_str(&format!("{wt}::component::HostStream<"));
         self.print_optional_ty(ty, TypeMode::Owned);
         self.push_str(">");
     }
 
     fn print_future(&mut self, ty: Option<&Type>) {
         let wt = self.wasmtime_path();
-        self.push_str(&format!("{wt}::component::FutureReader<"));
+        self.push_str(&format!("{wt}::component::HostFuture<"));
         self.print_optional_ty(ty, TypeMode::Owned);
         self.push_str(">");
     }
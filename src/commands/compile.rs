@@ -97,6 +97,7 @@ impl CompileCommand {
             }
             Some(CodeHint::Module) | None => code.compile_module_serialized()?,
         };
+        self.common.print_incremental_cache_stats();
         fs::write(&output, output_bytes)
             .with_context(|| format!("failed to write output: {}", output.display()))?;
 
@@ -21,6 +21,17 @@ pub struct ExploreCommand {
     /// provided)
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Also compile the module with these options applied on top of the
+    /// other options and show the resulting machine code side-by-side,
+    /// highlighting instructions which differ (e.g. `--diff='-Oopt-level=0'`)
+    #[arg(long, value_name = "OPTIONS", allow_hyphen_values = true)]
+    diff: Option<String>,
+
+    /// Also compile the module with Winch and show its machine code
+    /// side-by-side; shorthand for `--diff=-Ccompiler=winch`
+    #[arg(long, conflicts_with = "diff")]
+    winch: bool,
 }
 
 impl ExploreCommand {
@@ -29,6 +40,14 @@ impl ExploreCommand {
         self.common.init_logging()?;
 
         let mut config = self.common.config(None)?;
+        let diff = match (&self.diff, self.winch) {
+            (Some(diff), _) => Some(diff.split_whitespace().collect::<Vec<_>>()),
+            (None, true) => Some(vec!["-Ccompiler=winch"]),
+            (None, false) => None,
+        };
+        let diff_config = diff
+            .map(|args| self.common.with_args(args)?.config(None))
+            .transpose()?;
 
         let bytes =
             Cow::Owned(std::fs::read(&self.module).with_context(|| {
@@ -61,6 +80,7 @@ impl ExploreCommand {
             &config,
             self.common.target.as_deref(),
             clif_dir.as_ref().map(|tmp_dir| tmp_dir.path()),
+            diff_config.as_ref(),
             &bytes,
             &mut output_file,
         )?;
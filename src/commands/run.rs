@@ -51,10 +51,23 @@ pub struct RunCommand {
     pub run: RunCommon,
 
     /// The name of the function to run
+    ///
+    /// For core modules this is the name of an exported function whose
+    /// arguments are the remaining positional arguments. For components this
+    /// is a WAVE-encoded function call such as `add(1, 2)` naming an exported
+    /// function, possibly nested within exported instances, and its results
+    /// are printed as WAVE.
     #[arg(long, value_name = "FUNCTION")]
     pub invoke: Option<String>,
 
     /// Load the given WebAssembly module before the main module
+    ///
+    /// For core modules the exports of the preloaded module are available to
+    /// satisfy imports from the module `NAME`. For components a preloaded
+    /// component is instantiated and its exported instance `NAME`, such as
+    /// `wasi:http/types@0.2.0`, satisfies the imports of `NAME` by later
+    /// preloads and the main component, while a preloaded core module
+    /// satisfies an imported core module named `NAME`.
     #[arg(
         long = "preload",
         number_of_values = 1,
@@ -63,6 +76,23 @@ pub struct RunCommand {
     )]
     pub preloads: Vec<(String, PathBuf)>,
 
+    /// Implement an interface imported by the main component with a component
+    ///
+    /// The component at `COMPONENT_PATH` is instantiated, with access to the
+    /// host's implementation of all interfaces, and its exports of
+    /// `INTERFACE` then satisfy the imports of `INTERFACE` from preloads and
+    /// the main component instead of the host. `INTERFACE` is either an
+    /// interface such as `wasi:filesystem/types` or a package such as
+    /// `wasi:filesystem`, in which case those of its interfaces which the
+    /// component exports are replaced and the host still provides the rest.
+    #[arg(
+        long = "virtualize",
+        number_of_values = 1,
+        value_name = "INTERFACE=COMPONENT_PATH",
+        value_parser = parse_preloads,
+    )]
+    pub virtualize: Vec<(String, PathBuf)>,
+
     /// Override the value of `argv[0]`, typically the name of the executable of
     /// the application being run.
     ///
@@ -106,8 +136,22 @@ impl RunCommand {
                 // Further configured down below as well.
                 config.epoch_interruption(true);
             }
+            Some(Profile::Sample { .. }) => {
+                // Further configured down below as well.
+                config.epoch_interruption(true);
+                config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+            }
             None => {}
         }
+        if self.run.coverage.is_some() {
+            #[cfg(not(feature = "coverage"))]
+            bail!("support for coverage disabled at compile time");
+            #[cfg(feature = "coverage")]
+            {
+                config.coverage(true);
+                config.wasm_backtrace_details(wasmtime::WasmBacktraceDetails::Enable);
+            }
+        }
 
         let engine = Engine::new(&config)?;
 
@@ -186,41 +230,97 @@ impl RunCommand {
                 if let RunTarget::Core(m) = &main {
                     modules.push((String::new(), m.clone()));
                 }
-                for (name, path) in self.preloads.iter() {
-                    // Read the wasm module binary either as `*.wat` or a raw binary
-                    let module = match self.run.load_module(&engine, path)? {
-                        RunTarget::Core(m) => m,
+
+                // Instantiate the components virtualizing interfaces and then
+                // replace the host's definitions of those interfaces with
+                // their exports.
+                for (interface, path) in self.virtualize.iter() {
+                    let context = || {
+                        format!(
+                            "failed to virtualize `{}` with `{}`",
+                            interface,
+                            path.display()
+                        )
+                    };
+                    match (&mut linker, self.run.load_module(&engine, path)?) {
                         #[cfg(feature = "component-model")]
-                        RunTarget::Component(_) => {
-                            bail!("components cannot be loaded with `--preload`")
+                        (CliLinker::Component(linker), RunTarget::Component(component)) => {
+                            let instance = linker
+                                .instantiate_async(&mut store, &component)
+                                .await
+                                .with_context(context)?;
+                            linker
+                                .virtualize(&mut store, interface, &instance)
+                                .with_context(context)?;
                         }
+                        _ => bail!("`--virtualize` is only supported for components"),
+                    }
+                }
+
+                for (name, path) in self.preloads.iter() {
+                    let context = || {
+                        format!(
+                            "failed to process preload `{}` at `{}`",
+                            name,
+                            path.display()
+                        )
                     };
-                    modules.push((name.clone(), module.clone()));
+
+                    // Read the wasm module binary either as `*.wat` or a raw binary
+                    let preload = self.run.load_module(&engine, path)?;
 
                     // Add the module's functions to the linker.
-                    match &mut linker {
+                    match (&mut linker, preload) {
                         #[cfg(feature = "cranelift")]
-                        CliLinker::Core(linker) => {
+                        (CliLinker::Core(linker), RunTarget::Core(module)) => {
+                            modules.push((name.clone(), module.clone()));
                             linker
                                 .module_async(&mut store, name, &module)
                                 .await
-                                .context(format!(
-                                    "failed to process preload `{}` at `{}`",
-                                    name,
-                                    path.display()
-                                ))?;
+                                .with_context(context)?;
                         }
                         #[cfg(not(feature = "cranelift"))]
-                        CliLinker::Core(_) => {
+                        (CliLinker::Core(_), RunTarget::Core(_)) => {
                             bail!("support for --preload disabled at compile time");
                         }
                         #[cfg(feature = "component-model")]
-                        CliLinker::Component(_) => {
-                            bail!("--preload cannot be used with components");
+                        (CliLinker::Core(_), RunTarget::Component(_)) => {
+                            bail!("components cannot be preloaded for a core module")
+                        }
+
+                        // Core modules are provided to components as module
+                        // imports named `name`.
+                        #[cfg(feature = "component-model")]
+                        (CliLinker::Component(linker), RunTarget::Core(module)) => {
+                            linker.root().module(name, &module).with_context(context)?;
+                        }
+
+                        // Components are instantiated and then their exported
+                        // instance `name` is made available to satisfy the
+                        // imports of subsequent preloads and the main
+                        // component.
+                        #[cfg(feature = "component-model")]
+                        (CliLinker::Component(linker), RunTarget::Component(component)) => {
+                            let instance = linker
+                                .instantiate_async(&mut store, &component)
+                                .await
+                                .with_context(context)?;
+                            let export = instance
+                                .get_export(&mut store, None, name)
+                                .ok_or_else(|| {
+                                    anyhow!("component does not export an instance named `{name}`")
+                                })
+                                .with_context(context)?;
+                            linker
+                                .instance_from(&mut store, name, &instance, &export)
+                                .with_context(context)?;
                         }
                     }
                 }
 
+                // Everything has been compiled at this point.
+                self.run.common.print_incremental_cache_stats();
+
                 self.load_main_module(&mut store, &mut linker, &main, modules)
                     .await
                     .with_context(|| {
@@ -307,6 +407,16 @@ impl RunCommand {
             }
         }
 
+        if let Some(Profile::Sample { path, interval }) = &self.run.profile {
+            #[cfg(feature = "profiling")]
+            return self.setup_sampling_profiler(store, path, *interval);
+            #[cfg(not(feature = "profiling"))]
+            {
+                let _ = (path, interval);
+                bail!("support for profiling disabled at compile time");
+            }
+        }
+
         if let Some(timeout) = self.run.common.wasm.timeout {
             store.set_epoch_deadline(1);
             let engine = store.engine().clone();
@@ -396,6 +506,51 @@ impl RunCommand {
         });
     }
 
+    #[cfg(feature = "profiling")]
+    fn setup_sampling_profiler(
+        &self,
+        store: &mut Store<Host>,
+        path: &str,
+        interval: std::time::Duration,
+    ) -> Result<Box<dyn FnOnce(&mut Store<Host>)>> {
+        use wasmtime::{ProfileFormat, SamplingProfiler, UpdateDeadline};
+
+        // The profiler ticks the epoch once per `interval`, so express the
+        // timeout, if any, in those ticks.
+        if let Some(timeout) = self.run.common.wasm.timeout {
+            let timeout = (timeout.as_secs_f64() / interval.as_secs_f64()).ceil() as u64;
+            assert!(timeout > 0);
+            store.set_epoch_deadline(timeout);
+        } else {
+            store.set_epoch_deadline(1);
+            store.epoch_deadline_callback(|_store| Ok(UpdateDeadline::Continue(1)));
+        }
+
+        let module_name = self.module_and_args[0].to_str().unwrap_or("<main module>");
+        store.attach_sampling_profiler(SamplingProfiler::new(module_name, interval))?;
+
+        let path = path.to_string();
+        Ok(Box::new(move |store| {
+            let profiler = store.detach_sampling_profiler().unwrap();
+            let format = ProfileFormat::from_path(&path);
+            if let Err(e) = std::fs::File::create(&path)
+                .map_err(anyhow::Error::new)
+                .and_then(|output| profiler.finish(format, std::io::BufWriter::new(output)))
+            {
+                eprintln!("failed writing profile at {path}: {e:#}");
+            } else {
+                eprintln!();
+                eprintln!("Profile written to: {path}");
+                match format {
+                    ProfileFormat::Firefox => {
+                        eprintln!("View this profile at https://profiler.firefox.com/.")
+                    }
+                    ProfileFormat::Pprof => eprintln!("View this profile with `go tool pprof`."),
+                }
+            }
+        }))
+    }
+
     async fn load_main_module(
         &self,
         store: &mut Store<Host>,
@@ -469,35 +624,49 @@ impl RunCommand {
             }
             #[cfg(feature = "component-model")]
             CliLinker::Component(linker) => {
-                if self.invoke.is_some() {
-                    bail!("using `--invoke` with components is not supported");
-                }
-
                 let component = module.unwrap_component();
 
-                let command = wasmtime_wasi::bindings::Command::instantiate_async(
-                    &mut *store,
-                    component,
-                    linker,
-                )
-                .await?;
-                let result = command
-                    .wasi_cli_run()
-                    .call_run(&mut *store)
-                    .await
-                    .context("failed to invoke `run` function")
-                    .map_err(|e| self.handle_core_dump(&mut *store, e));
-
-                // Translate the `Result<(),()>` produced by wasm into a feigned
-                // explicit exit here with status 1 if `Err(())` is returned.
-                result.and_then(|wasm_result| match wasm_result {
-                    Ok(()) => Ok(()),
-                    Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
-                })
+                if let Some(invoke) = &self.invoke {
+                    let instance = linker
+                        .instantiate_async(&mut *store, component)
+                        .await
+                        .context(format!(
+                            "failed to instantiate {:?}",
+                            self.module_and_args[0]
+                        ))?;
+                    self.invoke_component_func(store, component, &instance, invoke)
+                        .await
+                } else {
+                    let command = wasmtime_wasi::bindings::Command::instantiate_async(
+                        &mut *store,
+                        component,
+                        linker,
+                    )
+                    .await?;
+                    let result = command
+                        .wasi_cli_run()
+                        .call_run(&mut *store)
+                        .await
+                        .context("failed to invoke `run` function")
+                        .map_err(|e| self.handle_core_dump(&mut *store, e));
+
+                    // Translate the `Result<(),()>` produced by wasm into a
+                    // feigned explicit exit here with status 1 if `Err(())` is
+                    // returned.
+                    result.and_then(|wasm_result| match wasm_result {
+                        Ok(()) => Ok(()),
+                        Err(()) => Err(wasmtime_wasi::I32Exit(1).into()),
+                    })
+                }
             }
         };
         finish_epoch_handler(store);
 
+        #[cfg(feature = "coverage")]
+        if let Some(path) = &self.run.coverage {
+            write_coverage(store, path);
+        }
+
         result
     }
 
@@ -587,6 +756,84 @@ impl RunCommand {
         Ok(())
     }
 
+    /// Invokes the component export described by the WAVE-encoded function
+    /// call `invoke`, for example `add(1, 2)`, and prints its results as WAVE.
+    #[cfg(feature = "component-model")]
+    async fn invoke_component_func(
+        &self,
+        store: &mut Store<Host>,
+        component: &wasmtime::component::Component,
+        instance: &wasmtime::component::Instance,
+        invoke: &str,
+    ) -> Result<()> {
+        use wasmtime::component::wasm_wave::{
+            untyped::UntypedFuncCall,
+            wasm::{DisplayFuncResults, WasmFunc},
+        };
+        use wasmtime::component::Val;
+
+        let call = UntypedFuncCall::parse(invoke).with_context(|| {
+            format!("failed to parse `--invoke` value `{invoke}` as a WAVE function call")
+        })?;
+        let name = call.name();
+
+        let mut matches = Vec::new();
+        search_component_funcs(
+            store.engine(),
+            &mut Vec::new(),
+            component.component_type().exports(store.engine()),
+            name,
+            &mut matches,
+        );
+        // Exports closer to the root of the component shadow those nested
+        // deeper within exported instances.
+        if let Some(depth) = matches.iter().map(|(path, _)| path.len()).min() {
+            matches.retain(|(path, _)| path.len() == depth);
+        }
+        let (path, ty) = match matches.len() {
+            0 => bail!("no func export named `{name}` found"),
+            1 => matches.pop().unwrap(),
+            _ => bail!(
+                "multiple func exports named `{name}` found: {}",
+                matches
+                    .iter()
+                    .map(|(path, _)| format!("`{}`", path.join("#")))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        let param_types = WasmFunc::params(&ty).collect::<Vec<_>>();
+        let params = call
+            .to_wasm_params::<Val>(&param_types)
+            .with_context(|| format!("failed to parse arguments of `{invoke}`"))?;
+
+        let mut export = None;
+        for name in &path {
+            export = Some(
+                instance
+                    .get_export(&mut *store, export.as_ref(), name)
+                    .ok_or_else(|| anyhow!("no export named `{name}` found"))?,
+            );
+        }
+        let func = instance
+            .get_func(&mut *store, export.unwrap())
+            .ok_or_else(|| anyhow!("export `{}` is not a function", path.join("#")))?;
+
+        let mut results = vec![Val::Bool(false); ty.results().len()];
+        let invoke_res = func
+            .call_async(&mut *store, &params, &mut results)
+            .await
+            .with_context(|| format!("failed to invoke `{name}`"));
+        if let Err(err) = invoke_res {
+            return Err(self.handle_core_dump(&mut *store, err));
+        }
+        func.post_return_async(&mut *store).await?;
+
+        println!("{}", DisplayFuncResults(&results));
+        Ok(())
+    }
+
     #[cfg(feature = "coredump")]
     fn handle_core_dump(&self, store: &mut Store<Host>, err: Error) -> Error {
         let coredump_path = match &self.run.common.debug.coredump {
@@ -864,6 +1111,9 @@ impl RunCommand {
     }
 
     fn set_preview1_ctx(&self, store: &mut Store<Host>) -> Result<()> {
+        if self.run.common.wasm.deterministic == Some(true) {
+            bail!("deterministic execution is not supported with `-S preview2=n`");
+        }
         let mut builder = WasiCtxBuilder::new();
         builder.inherit_stdio().args(&self.compute_argv()?)?;
 
@@ -1038,6 +1288,33 @@ fn ctx_set_listenfd(mut num_fd: usize, builder: &mut WasiCtxBuilder) -> Result<u
     Ok(num_fd)
 }
 
+/// Recursively collects the paths of all function exports named `name`,
+/// descending into exported instances.
+#[cfg(feature = "component-model")]
+fn search_component_funcs<'a>(
+    engine: &Engine,
+    path: &mut Vec<String>,
+    exports: impl Iterator<Item = (&'a str, wasmtime::component::types::ComponentItem)>,
+    name: &str,
+    matches: &mut Vec<(Vec<String>, wasmtime::component::types::ComponentFunc)>,
+) {
+    use wasmtime::component::types::ComponentItem;
+
+    for (export, item) in exports {
+        path.push(export.to_string());
+        match item {
+            ComponentItem::ComponentFunc(func) if export == name => {
+                matches.push((path.clone(), func));
+            }
+            ComponentItem::ComponentInstance(instance) => {
+                search_component_funcs(engine, path, instance.exports(engine), name, matches);
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
 #[cfg(feature = "coredump")]
 fn write_core_dump(
     store: &mut Store<Host>,
@@ -1061,3 +1338,26 @@ fn write_core_dump(
         .with_context(|| format!("failed to write core dump file at `{path}`"))?;
     Ok(())
 }
+
+#[cfg(feature = "coverage")]
+fn write_coverage(store: &mut Store<Host>, path: &str) {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    let mut coverage = wasmtime::Coverage::new();
+    coverage.collect(&mut *store);
+    let result = File::create(path)
+        .with_context(|| format!("failed to create file at `{path}`"))
+        .and_then(|file| {
+            let file = BufWriter::new(file);
+            if path.ends_with(".json") {
+                coverage.write_llvm_json(file)
+            } else {
+                coverage.write_lcov(file)
+            }
+        });
+    match result {
+        Ok(()) => eprintln!("Coverage written to: {path}"),
+        Err(e) => eprintln!("failed writing coverage at {path}: {e:#}"),
+    }
+}
@@ -105,8 +105,17 @@ impl ServeCommand {
 
         // We force cli errors before starting to listen for connections so then
         // we don't accidentally delay them to the first request.
-        if let Some(Profile::Guest { .. }) = &self.run.profile {
-            bail!("Cannot use the guest profiler with components");
+        match &self.run.profile {
+            Some(Profile::Guest { .. }) => {
+                bail!("Cannot use the guest profiler with components");
+            }
+            Some(Profile::Sample { .. }) => {
+                bail!("Cannot use the sampling profiler with `wasmtime serve`");
+            }
+            Some(Profile::Native(_)) | None => {}
+        }
+        if self.run.coverage.is_some() {
+            bail!("Cannot collect code coverage with `wasmtime serve`");
         }
 
         if self.run.common.wasi.nn == Some(true) {
@@ -353,7 +362,7 @@ impl ServeCommand {
             }
 
             // We bail early in `execute` if the guest profiler is configured.
-            Some(Profile::Guest { .. }) => unreachable!(),
+            Some(Profile::Guest { .. } | Profile::Sample { .. }) => unreachable!(),
 
             None => {}
         }
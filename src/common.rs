@@ -52,7 +52,8 @@ pub struct RunCommon {
     #[arg(long = "allow-precompiled")]
     pub allow_precompiled: bool,
 
-    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest)
+    /// Profiling strategy (valid options are: perfmap, jitdump, vtune, guest,
+    /// sample)
     ///
     /// The perfmap, jitdump, and vtune profiling strategies integrate Wasmtime
     /// with external profilers such as `perf`. The guest profiling strategy
@@ -67,6 +68,17 @@ pub struct RunCommon {
     /// where `path` is where to write the profile and `interval` is the
     /// duration between samples. When used with `--wasm-timeout` the timeout
     /// will be rounded up to the nearest multiple of this interval.
+    ///
+    /// The sample profiling strategy is similar to `guest` but also supports
+    /// components, resolves frames to source lines and inlined functions
+    /// using DWARF debug information, and can write profiles in the pprof
+    /// format in addition to the Firefox profiler format. It's configured as:
+    ///
+    ///     --profile=sample[:path[,interval]]
+    ///
+    /// where `path` defaults to `wasmtime-sample-profile.json`. Paths ending
+    /// in `.pb` or `.pprof` are written in the pprof format and all others in
+    /// the Firefox profiler format.
     #[arg(
         long,
         value_name = "STRATEGY",
@@ -74,6 +86,14 @@ pub struct RunCommon {
     )]
     pub profile: Option<Profile>,
 
+    /// Collect line-level code coverage of the guest and write it to `FILE`.
+    ///
+    /// Coverage is mapped back to source lines using the DWARF debug
+    /// information of the guest. Files ending in `.json` are written in the
+    /// JSON format of `llvm-cov export` and all others as an lcov tracefile.
+    #[arg(long, value_name = "FILE")]
+    pub coverage: Option<String>,
+
     /// Grant access of a host directory to a guest.
     ///
     /// If specified as just `HOST_DIR` then the same directory name on the
@@ -269,6 +289,10 @@ impl RunCommon {
         // something like `sleep(FOREVER)`.
         builder.allow_blocking_current_thread(self.common.wasm.timeout.is_none());
 
+        if self.common.wasm.deterministic == Some(true) {
+            builder.deterministic(0);
+        }
+
         if self.common.wasi.inherit_env == Some(true) {
             for (k, v) in std::env::vars() {
                 builder.env(&k, &v);
@@ -348,6 +372,7 @@ impl RunCommon {
 pub enum Profile {
     Native(wasmtime::ProfilingStrategy),
     Guest { path: String, interval: Duration },
+    Sample { path: String, interval: Duration },
 }
 
 impl Profile {
@@ -371,6 +396,18 @@ impl Profile {
                 path: path.to_string(),
                 interval: WasmtimeOptionValue::parse(Some(dur))?,
             }),
+            ["sample"] => Ok(Profile::Sample {
+                path: "wasmtime-sample-profile.json".to_string(),
+                interval: Duration::from_millis(10),
+            }),
+            [sample] if sample.starts_with("sample:") => Ok(Profile::Sample {
+                path: sample["sample:".len()..].to_string(),
+                interval: Duration::from_millis(10),
+            }),
+            [sample, dur] if sample.starts_with("sample:") => Ok(Profile::Sample {
+                path: sample["sample:".len()..].to_string(),
+                interval: WasmtimeOptionValue::parse(Some(dur))?,
+            }),
             _ => bail!("unknown profiling strategy: {s}"),
         }
     }
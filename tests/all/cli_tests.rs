@@ -856,6 +856,175 @@ fn run_basic_component() -> Result<()> {
     Ok(())
 }
 
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn invoke_component_export() -> Result<()> {
+    let path = "tests/all/cli_tests/component-invoke.wat";
+
+    let output = run_wasmtime(&["run", "--invoke", "add(1, 2)", path])?;
+    assert_eq!(output, "3\n");
+
+    // Functions within exported instances can be invoked too, and top-level
+    // exports shadow nested ones of the same name.
+    let output = run_wasmtime(&["run", "--invoke", "square(-3)", path])?;
+    assert_eq!(output, "9\n");
+
+    // Nested exports must be unambiguous.
+    let output = run_wasmtime_for_output(&["run", "--invoke", "negate(true)", path], None)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("`logic#negate`, `more-logic#negate`"),
+        "{stderr}"
+    );
+
+    // Arguments are typechecked against the function's parameters.
+    let output = run_wasmtime_for_output(&["run", "--invoke", "add(1)", path], None)?;
+    assert!(!output.status.success());
+    let output = run_wasmtime_for_output(&["run", "--invoke", "add(1, true)", path], None)?;
+    assert!(!output.status.success());
+
+    let output = run_wasmtime_for_output(&["run", "--invoke", "missing()", path], None)?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("no func export named `missing` found"),
+        "{stderr}"
+    );
+
+    // Traps are reported like they are for core modules.
+    let output = run_wasmtime_for_output(&["run", "--invoke", "trap()", path], None)?;
+    assert_eq!(output.stdout, b"");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("failed to invoke `trap`"), "{stderr}");
+    assert!(
+        stderr.contains("wasm `unreachable` instruction executed"),
+        "{stderr}"
+    );
+    assert_trap_code(&output.status);
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn preload_component() -> Result<()> {
+    let lib = "tests/all/cli_tests/component-preload-lib.wat";
+    let main = "tests/all/cli_tests/component-preload-main.wat";
+
+    // Imports of the main component are satisfied by the preload's exported
+    // instance of the same name.
+    let preload = format!("local:lib/math={lib}");
+    let output = run_wasmtime(&[
+        "run",
+        "--preload",
+        &preload,
+        "--invoke",
+        "quadruple(5)",
+        main,
+    ])?;
+    assert_eq!(output, "20\n");
+
+    // Without the preload the import is missing.
+    let output = run_wasmtime_for_output(&["run", "--invoke", "quadruple(5)", main], None)?;
+    assert!(!output.status.success());
+
+    // The name must be that of an instance exported by the preload.
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--preload",
+            &format!("lib={lib}"),
+            "--invoke",
+            "quadruple(5)",
+            main,
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("component does not export an instance named `lib`"),
+        "{stderr}"
+    );
+
+    // Components can't be preloaded for core modules.
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--preload",
+            &preload,
+            "tests/all/cli_tests/simple.wat",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("components cannot be preloaded for a core module"),
+        "{stderr}"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn virtualize_component_import() -> Result<()> {
+    let virt = "tests/all/cli_tests/component-virtualize-random.wat";
+    let main = "tests/all/cli_tests/component-virtualize-main.wat";
+
+    // Both packages and individual interfaces can be virtualized, and the
+    // monotonic clock is still provided by the host.
+    for interface in ["wasi:random", "wasi:random/random"] {
+        let virtualize = format!("{interface}={virt}");
+        let output = run_wasmtime(&[
+            "run",
+            "--virtualize",
+            &virtualize,
+            "--invoke",
+            "random()",
+            main,
+        ])?;
+        assert_eq!(output, "42\n");
+    }
+
+    // The virtualizing component must export the interface.
+    let virtualize = format!("wasi:cli={virt}");
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--virtualize",
+            &virtualize,
+            "--invoke",
+            "random()",
+            main,
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("does not export anything matching `wasi:cli`"),
+        "{stderr}"
+    );
+
+    // Core modules can't be virtualized.
+    let virtualize = format!("wasi:random={virt}");
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "--virtualize",
+            &virtualize,
+            "tests/all/cli_tests/simple.wat",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+
+    Ok(())
+}
+
 #[test]
 #[cfg_attr(not(feature = "component-model"), ignore)]
 fn run_precompiled_component() -> Result<()> {
@@ -1118,6 +1287,312 @@ fn increase_stack_size() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn deterministic_requires_fuel() -> Result<()> {
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "-Wdeterministic",
+            "--invoke",
+            "simple",
+            "tests/all/cli_tests/simple.wat",
+            "4",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("requires `-W fuel=N`"), "{stderr}");
+
+    let stdout = run_wasmtime(&[
+        "run",
+        "-Wdeterministic,fuel=100000",
+        "--invoke",
+        "simple",
+        "tests/all/cli_tests/simple.wat",
+        "4",
+    ])?;
+    assert_eq!(stdout, "4\n");
+    Ok(())
+}
+
+#[test]
+fn incremental_cache() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let flag = format!("-Cincremental-cache={}", dir.path().display());
+    let args = [
+        "run",
+        "--invoke",
+        "simple",
+        "-Ccache=n",
+        &flag,
+        "tests/all/cli_tests/simple.wat",
+        "4",
+    ];
+    assert_eq!(run_wasmtime(&args)?, "4\n");
+    let entries = std::fs::read_dir(dir.path())?.count();
+    assert!(entries > 0);
+
+    // A second run finds everything in the cache and doesn't add new entries.
+    assert_eq!(run_wasmtime(&args)?, "4\n");
+    assert_eq!(std::fs::read_dir(dir.path())?.count(), entries);
+    Ok(())
+}
+
+#[test]
+fn incremental_cache_stats() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let flag = format!(
+        "-Cincremental-cache={},incremental-cache-stats",
+        dir.path().display()
+    );
+    let args = [
+        "run",
+        "--invoke",
+        "simple",
+        "-Ccache=n",
+        &flag,
+        "tests/all/cli_tests/simple.wat",
+        "4",
+    ];
+
+    let output = run_wasmtime_for_output(&args, None)?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("incremental cache: 0 hits"), "{stderr}");
+
+    let output = run_wasmtime_for_output(&args, None)?;
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(stderr.contains("0 misses"), "{stderr}");
+    assert!(stderr.contains("(100.0% hit rate)"), "{stderr}");
+
+    // Stats require a cache to report on.
+    let output = run_wasmtime_for_output(
+        &[
+            "run",
+            "-Cincremental-cache-stats",
+            "tests/all/cli_tests/simple.wat",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    Ok(())
+}
+
+#[test]
+fn sample_profile() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pprof = dir.path().join("profile.pb");
+    let output = run_wasmtime(&[
+        "run",
+        "--invoke",
+        "spin",
+        &format!("--profile=sample:{},1ms", pprof.display()),
+        "tests/all/cli_tests/sample-profile.wat",
+        "100000000",
+    ])?;
+    assert_eq!(output, "0\n");
+    assert_pprof_samples(&pprof, "spin")?;
+
+    let json = dir.path().join("profile.json");
+    run_wasmtime(&[
+        "run",
+        "--invoke",
+        "spin",
+        &format!("--profile=sample:{},1ms", json.display()),
+        "tests/all/cli_tests/sample-profile.wat",
+        "100000000",
+    ])?;
+    assert_firefox_samples(&json, "spin")?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(feature = "component-model"), ignore)]
+fn sample_profile_component() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let pprof = dir.path().join("profile.pb");
+    run_wasmtime(&[
+        "run",
+        &format!("--profile=sample:{},1ms", pprof.display()),
+        "tests/all/cli_tests/sample-profile-component.wat",
+    ])?;
+    assert_pprof_samples(&pprof, "spin")?;
+
+    let json = dir.path().join("profile.json");
+    run_wasmtime(&[
+        "run",
+        &format!("--profile=sample:{},1ms", json.display()),
+        "tests/all/cli_tests/sample-profile-component.wat",
+    ])?;
+    assert_firefox_samples(&json, "spin")?;
+    Ok(())
+}
+
+/// Decodes the top-level fields of the uncompressed pprof `Profile` message at
+/// `path` and checks that it has samples and names `func` in its string table.
+fn assert_pprof_samples(path: &Path, func: &str) -> Result<()> {
+    fn varint(bytes: &mut &[u8]) -> Result<u64> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let Some((&byte, rest)) = bytes.split_first() else {
+                bail!("truncated varint");
+            };
+            *bytes = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("overlong varint")
+    }
+
+    let data = std::fs::read(path)?;
+    let mut bytes = &data[..];
+    let mut samples = 0;
+    let mut strings = Vec::new();
+    while !bytes.is_empty() {
+        let key = varint(&mut bytes)?;
+        match key & 7 {
+            0 => {
+                varint(&mut bytes)?;
+            }
+            2 => {
+                let len = usize::try_from(varint(&mut bytes)?)?;
+                if len > bytes.len() {
+                    bail!("truncated field");
+                }
+                let (field, rest) = bytes.split_at(len);
+                bytes = rest;
+                match key >> 3 {
+                    2 => samples += 1,
+                    6 => strings.push(String::from_utf8_lossy(field).into_owned()),
+                    _ => {}
+                }
+            }
+            ty => bail!("unexpected wire type {ty}"),
+        }
+    }
+    assert!(samples > 0, "no samples in {}", path.display());
+    assert!(
+        strings.iter().any(|s| s == func),
+        "`{func}` not in string table: {strings:?}"
+    );
+    Ok(())
+}
+
+/// Checks that the Firefox profile at `path` is valid JSON with samples that
+/// refer to a frame named `func`.
+fn assert_firefox_samples(path: &Path, func: &str) -> Result<()> {
+    fn has_string(value: &serde_json::Value, s: &str) -> bool {
+        match value {
+            serde_json::Value::String(v) => v == s,
+            serde_json::Value::Array(vs) => vs.iter().any(|v| has_string(v, s)),
+            serde_json::Value::Object(vs) => vs.values().any(|v| has_string(v, s)),
+            _ => false,
+        }
+    }
+
+    let profile: serde_json::Value = serde_json::from_slice(&std::fs::read(path)?)?;
+    let samples = profile["threads"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|thread| thread["samples"]["length"].as_u64().unwrap_or(0))
+        .sum::<u64>();
+    assert!(samples > 0, "no samples in {}", path.display());
+    assert!(has_string(&profile, func), "no `{func}` frame in profile");
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn serve_rejects_sample_profile() -> Result<()> {
+    let output = run_wasmtime_for_output(
+        &[
+            "serve",
+            "--profile=sample",
+            "tests/all/cli_tests/simple.wat",
+        ],
+        None,
+    )?;
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr)?;
+    assert!(
+        stderr.contains("Cannot use the sampling profiler with `wasmtime serve`"),
+        "{stderr}"
+    );
+    Ok(())
+}
+
+fn explore(args: &[&str]) -> Result<String> {
+    let dir = tempfile::tempdir()?;
+    let output = dir.path().join("explore.html");
+    let output = output.to_str().unwrap();
+    let mut explore_args = vec!["explore", "-o", output];
+    explore_args.extend_from_slice(args);
+    run_wasmtime(&explore_args)?;
+    Ok(std::fs::read_to_string(output)?)
+}
+
+#[test]
+fn explore_diff() -> Result<()> {
+    let html = explore(&["--diff=-Oopt-level=0", "tests/all/cli_tests/simple.wat"])?;
+    assert!(html.contains(r#"<div id="asm"></div>"#));
+    assert!(html.contains(r#"<div id="diff-asm"></div>"#));
+    assert!(html.contains("window.DIFF_ASM = {"));
+    // Unoptimized code differs from optimized code.
+    assert!(html.contains(r#""changed":true"#));
+
+    // Without `--diff` there is no second column.
+    let html = explore(&["tests/all/cli_tests/simple.wat"])?;
+    assert!(html.contains("window.ASM = {"));
+    assert!(!html.contains(r#"<div id="diff-asm"></div>"#));
+    assert!(!html.contains("window.DIFF_ASM = {"));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(not(target_arch = "x86_64"), ignore)]
+fn explore_winch() -> Result<()> {
+    let html = explore(&["--winch", "tests/all/cli_tests/simple.wat"])?;
+    assert!(html.contains("window.CLIF = {"));
+    assert!(html.contains(r#"<div id="diff-asm"></div>"#));
+    assert!(html.contains("window.DIFF_ASM = {"));
+
+    // `--winch` is shorthand for a `--diff` and can't be combined with one.
+    assert!(explore(&[
+        "--winch",
+        "--diff=-Oopt-level=0",
+        "tests/all/cli_tests/simple.wat"
+    ])
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn explore_pulley() -> Result<()> {
+    let html = explore(&["--target", "pulley64", "tests/all/cli_tests/simple.wat"])?;
+    // Pulley bytecode is disassembled rather than shown as raw bytes.
+    assert!(html.contains(r#""mnemonic":"push_frame""#));
+    assert!(html.contains(r#""mnemonic":"ret""#));
+    Ok(())
+}
+
+#[test]
+fn explore_dwarf_source_lines() -> Result<()> {
+    let html = explore(&["tests/all/debug/testsuite/fib-wasm.wasm"])?;
+    assert!(html.contains(r#""files":["./fib-wasm.c"]"#));
+    assert!(html.contains(r#""source":{"file":0,"line":"#));
+
+    // Modules without DWARF have no source locations.
+    let html = explore(&["tests/all/cli_tests/simple.wat"])?;
+    assert!(html.contains(r#""files":[]"#));
+    assert!(!html.contains(r#""source":{"#));
+    Ok(())
+}
+
 mod test_programs {
     use super::{get_wasmtime_command, run_wasmtime};
     use anyhow::{bail, Context, Result};
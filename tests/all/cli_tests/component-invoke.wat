@@ -0,0 +1,31 @@
+(component
+  (core module $m
+    (func (export "add") (param i32 i32) (result i32)
+      (i32.add (local.get 0) (local.get 1)))
+    (func (export "negate") (param i32) (result i32)
+      (i32.eqz (local.get 0)))
+    (func (export "square") (param i32) (result i32)
+      (i32.mul (local.get 0) (local.get 0)))
+    (func (export "trap")
+      unreachable)
+  )
+  (core instance $i (instantiate $m))
+
+  (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+    (canon lift (core func $i "add")))
+  (func (export "trap")
+    (canon lift (core func $i "trap")))
+
+  (func $negate (param "b" bool) (result bool)
+    (canon lift (core func $i "negate")))
+  (instance (export "logic")
+    (export "negate" (func $negate)))
+  (instance (export "more-logic")
+    (export "negate" (func $negate)))
+
+  (func $square (param "x" s32) (result s32)
+    (canon lift (core func $i "square")))
+  (instance (export (interface "local:demo/math"))
+    (export "square" (func $square))
+    (export "add" (func $square)))
+)
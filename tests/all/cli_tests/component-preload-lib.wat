@@ -0,0 +1,12 @@
+(component
+  (core module $m
+    (func (export "double") (param i32) (result i32)
+      local.get 0
+      local.get 0
+      i32.add)
+  )
+  (core instance $i (instantiate $m))
+  (func $double (param "x" s32) (result s32) (canon lift (core func $i "double")))
+  (instance $math (export "double" (func $double)))
+  (export "local:lib/math" (instance $math))
+)
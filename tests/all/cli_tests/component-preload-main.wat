@@ -0,0 +1,18 @@
+(component
+  (import "local:lib/math" (instance $math
+    (export "double" (func (param "x" s32) (result s32)))
+  ))
+  (core func $double (canon lower (func $math "double")))
+  (core module $m
+    (import "" "double" (func $double (param i32) (result i32)))
+    (func (export "quadruple") (param i32) (result i32)
+      local.get 0
+      call $double
+      call $double)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance (export "double" (func $double))))
+  ))
+  (func (export "quadruple") (param "x" s32) (result s32)
+    (canon lift (core func $i "quadruple")))
+)
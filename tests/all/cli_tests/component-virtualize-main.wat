@@ -0,0 +1,26 @@
+(component
+  (import "wasi:random/random@0.2.0" (instance $random
+    (export "get-random-u64" (func (result u64)))
+  ))
+  (import "wasi:clocks/monotonic-clock@0.2.0" (instance $clock
+    (export "now" (func (result u64)))
+  ))
+  (core func $get-random-u64 (canon lower (func $random "get-random-u64")))
+  (core func $now (canon lower (func $clock "now")))
+  (core module $m
+    (import "" "get-random-u64" (func $get-random-u64 (result i64)))
+    (import "" "now" (func $now (result i64)))
+    (func (export "random") (result i64)
+      call $now
+      drop
+      call $get-random-u64)
+  )
+  (core instance $i (instantiate $m
+    (with "" (instance
+      (export "get-random-u64" (func $get-random-u64))
+      (export "now" (func $now))
+    ))
+  ))
+  (func (export "random") (result u64)
+    (canon lift (core func $i "random")))
+)
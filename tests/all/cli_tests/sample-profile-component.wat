@@ -0,0 +1,22 @@
+(component
+  (core module $m
+    (func $spin (export "spin") (result i32)
+      (local i32)
+      i32.const 100000000
+      local.set 0
+      loop
+        local.get 0
+        i32.const 1
+        i32.sub
+        local.tee 0
+        br_if 0
+      end
+      local.get 0)
+  )
+  (core instance $i (instantiate $m))
+  (func $run (result (result))
+    (canon lift (core func $i "spin")))
+
+  (instance (export (interface "wasi:cli/run@0.2.0"))
+    (export "run" (func $run)))
+)
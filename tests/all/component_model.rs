@@ -9,15 +9,19 @@ mod aot;
 mod r#async;
 mod bindgen;
 mod call_hook;
+mod concurrent;
 mod dynamic;
 mod func;
 mod import;
 mod instance;
+mod intercept;
+mod limits;
 mod linker;
 mod macros;
 mod nested;
 mod post_return;
 mod resources;
+mod snapshot;
 mod strings;
 
 #[test]
@@ -0,0 +1,893 @@
+#![cfg(all(not(miri), feature = "component-model-async"))]
+
+use anyhow::Result;
+use futures::{SinkExt, StreamExt};
+use wasmtime::component::*;
+use wasmtime::{Engine, Store, StoreContextMut};
+
+fn concurrent_engine() -> Engine {
+    let mut config = component_test_util::config();
+    config.async_support(true);
+    config.wasm_component_model_async(true);
+    Engine::new(&config).unwrap()
+}
+
+/// An async-lifted export which calls an async-lowered host import and waits
+/// for the resulting subtask when the host does not complete immediately.
+const DOUBLE_COMPONENT: &str = r#"
+    (component
+        (import "double" (func $double (param "x" u32) (result u32)))
+
+        (core module $libc (memory (export "memory") 1))
+        (core instance $libc (instantiate $libc))
+
+        (core func $double (canon lower (func $double) async (memory $libc "memory")))
+        (core func $task-return (canon task.return (result u32)))
+        (core func $waitable-set-new (canon waitable-set.new))
+        (core func $waitable-join (canon waitable.join))
+        (core func $waitable-set-wait (canon waitable-set.wait (memory $libc "memory")))
+        (core func $subtask-drop (canon subtask.drop))
+
+        (core module $m
+            (import "" "memory" (memory 1))
+            (import "" "double" (func $double (param i32 i32) (result i32)))
+            (import "" "task.return" (func $task-return (param i32)))
+            (import "" "waitable-set.new" (func $waitable-set-new (result i32)))
+            (import "" "waitable.join" (func $waitable-join (param i32 i32)))
+            (import "" "waitable-set.wait" (func $waitable-set-wait (param i32 i32) (result i32)))
+            (import "" "subtask.drop" (func $subtask-drop (param i32)))
+
+            (func (export "run") (param $x i32)
+                (local $status i32)
+                (local $set i32)
+                (i32.store (i32.const 0) (local.get $x))
+                (local.set $status (call $double (i32.const 0) (i32.const 4)))
+                (if (i32.ne (i32.shr_u (local.get $status) (i32.const 30)) (i32.const 3))
+                    (then
+                        (local.set $set (call $waitable-set-new))
+                        (call $waitable-join
+                            (i32.and (local.get $status) (i32.const 0x3fffffff))
+                            (local.get $set))
+                        (loop $wait
+                            (br_if $wait (i32.ne
+                                (call $waitable-set-wait (local.get $set) (i32.const 8))
+                                (i32.const 3))))
+                        (call $subtask-drop
+                            (i32.and (local.get $status) (i32.const 0x3fffffff)))))
+                (call $task-return (i32.load (i32.const 4))))
+        )
+        (core instance $i (instantiate $m
+            (with "" (instance
+                (export "memory" (memory $libc "memory"))
+                (export "double" (func $double))
+                (export "task.return" (func $task-return))
+                (export "waitable-set.new" (func $waitable-set-new))
+                (export "waitable.join" (func $waitable-join))
+                (export "waitable-set.wait" (func $waitable-set-wait))
+                (export "subtask.drop" (func $subtask-drop))
+            ))
+        ))
+
+        (func (export "run") (param "x" u32) (result u32)
+            (canon lift (core func $i "run") async))
+    )
+"#;
+
+#[tokio::test]
+async fn async_lift_and_lower() -> Result<()> {
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, DOUBLE_COMPONENT)?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap_concurrent("double", |_, (x,): (u32,)| async move {
+            tokio::task::yield_now().await;
+            move |_: StoreContextMut<'_, ()>| Ok((x * 2,))
+        })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run = instance.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+
+    let mut promises = PromisesUnordered::new();
+    for x in 0..4 {
+        promises.push(run.call_concurrent(&mut store, (x,)).await?);
+    }
+    let mut results = Vec::new();
+    while let Some((y,)) = promises.next(&mut store).await? {
+        results.push(y);
+    }
+    results.sort();
+    assert_eq!(results, [0, 2, 4, 6]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn sync_lift_concurrent_call() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (func (export "add") (param i32 i32) (result i32)
+                    (i32.add (local.get 0) (local.get 1)))
+            )
+            (core instance $i (instantiate $m))
+            (func (export "add") (param "a" u32) (param "b" u32) (result u32)
+                (canon lift (core func $i "add")))
+        )
+    "#;
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate_async(&mut store, &component)
+        .await?;
+    let add = instance.get_typed_func::<(u32, u32), (u32,)>(&mut store, "add")?;
+
+    let promise = add.call_concurrent(&mut store, (1, 2)).await?;
+    assert_eq!(promise.get(&mut store).await?, (3,));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn host_stream_and_future() -> Result<()> {
+    let engine = concurrent_engine();
+    let mut store = Store::new(&engine, ());
+
+    let (tx, rx) = stream::<u32>(&mut store)?;
+    let write = tx.write(&mut store, vec![1, 2, 3])?;
+    let read = rx.read(&mut store)?;
+    let (rx, values) = read.get(&mut store).await?.unwrap();
+    assert_eq!(values, [1, 2, 3]);
+    let tx = write.get(&mut store).await?.unwrap();
+    tx.close(&mut store)?;
+    assert!(rx.read(&mut store)?.get(&mut store).await?.is_none());
+
+    let (tx, rx) = future::<String>(&mut store)?;
+    let write = tx.write(&mut store, "hello".to_string())?;
+    assert_eq!(
+        rx.read(&mut store)?.get(&mut store).await?.as_deref(),
+        Some("hello")
+    );
+    assert!(write.get(&mut store).await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn host_stream_through_guest() -> Result<()> {
+    let component = r#"
+        (component
+            (type $s (stream u32))
+            (import "produce" (func $produce (param "n" u32) (result $s)))
+            (import "consume" (func $consume (param "s" $s) (result u32)))
+            (core func $produce (canon lower (func $produce)))
+            (core func $consume (canon lower (func $consume)))
+            (core module $m
+                (import "" "produce" (func $produce (param i32) (result i32)))
+                (import "" "consume" (func $consume (param i32) (result i32)))
+                (func (export "run") (param i32) (result i32)
+                    (call $consume (call $produce (local.get 0))))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "produce" (func $produce))
+                    (export "consume" (func $consume))
+                ))
+            ))
+            (func (export "run") (param "n" u32) (result u32)
+                (canon lift (core func $i "run")))
+        )
+    "#;
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap_concurrent("produce", |_, (n,): (u32,)| {
+            // Produce more values than a lifted stream buffers so that the
+            // pumps need to apply backpressure.
+            let (mut tx, rx) = HostStream::channel(1);
+            async move {
+                tokio::spawn(async move {
+                    for i in 0..n {
+                        tx.send(i).await.unwrap();
+                    }
+                });
+                move |_: StoreContextMut<'_, ()>| Ok((rx,))
+            }
+        })?;
+    linker
+        .root()
+        .func_wrap_concurrent("consume", |_, (s,): (HostStream<u32>,)| async move {
+            let sum = s.fold(0, |sum, x| async move { sum + x }).await;
+            move |_: StoreContextMut<'_, ()>| Ok((sum,))
+        })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run = instance.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    let promise = run.call_concurrent(&mut store, (100,)).await?;
+    assert_eq!(promise.get(&mut store).await?, ((0..100).sum(),));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn host_future_through_guest() -> Result<()> {
+    let component = r#"
+        (component
+            (type $f (future string))
+            (import "make" (func $make (result $f)))
+            (import "length" (func $length (param "f" $f) (result u32)))
+            (core func $make (canon lower (func $make)))
+            (core func $length (canon lower (func $length)))
+            (core module $m
+                (import "" "make" (func $make (result i32)))
+                (import "" "length" (func $length (param i32) (result i32)))
+                (func (export "run") (result i32)
+                    (call $length (call $make)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "make" (func $make))
+                    (export "length" (func $length))
+                ))
+            ))
+            (func (export "run") (result u32)
+                (canon lift (core func $i "run")))
+        )
+    "#;
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap_concurrent("make", |_, (): ()| {
+        let f = HostFuture::new(async {
+            tokio::task::yield_now().await;
+            "hello".to_string()
+        });
+        async move { move |_: StoreContextMut<'_, ()>| Ok((f,)) }
+    })?;
+    linker
+        .root()
+        .func_wrap_concurrent("length", |_, (f,): (HostFuture<String>,)| async move {
+            let len = f.await.map_or(0, |s| s.len() as u32);
+            move |_: StoreContextMut<'_, ()>| Ok((len,))
+        })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run = instance.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    let promise = run.call_concurrent(&mut store, ()).await?;
+    assert_eq!(promise.get(&mut store).await?, (5,));
+
+    Ok(())
+}
+
+/// Imports for every stream, future and `error-context` intrinsic used by the
+/// guest-side tests below, along with a `$libc` module providing a bump
+/// allocator for `error-context.debug-message`.
+const INTRINSICS: &str = r#"
+    (type $s (stream u32))
+    (type $f (future u32))
+
+    (core module $libc
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+            (local $ret i32)
+            (local.set $ret (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get 3)))
+            (local.get $ret))
+    )
+    (core instance $libc (instantiate $libc))
+
+    (core func $stream-new (canon stream.new $s))
+    (core func $stream-read (canon stream.read $s async (memory $libc "memory")))
+    (core func $stream-write (canon stream.write $s async (memory $libc "memory")))
+    (core func $stream-cancel-read (canon stream.cancel-read $s async))
+    (core func $stream-cancel-write (canon stream.cancel-write $s async))
+    (core func $stream-close-readable (canon stream.close-readable $s))
+    (core func $stream-close-writable (canon stream.close-writable $s))
+    (core func $future-new (canon future.new $f))
+    (core func $future-read (canon future.read $f async (memory $libc "memory")))
+    (core func $future-write (canon future.write $f async (memory $libc "memory")))
+    (core func $future-cancel-read (canon future.cancel-read $f async))
+    (core func $future-cancel-write (canon future.cancel-write $f async))
+    (core func $future-close-readable (canon future.close-readable $f))
+    (core func $future-close-writable (canon future.close-writable $f))
+    (core func $error-context-new (canon error-context.new (memory $libc "memory")))
+    (core func $error-context-debug-message
+        (canon error-context.debug-message (memory $libc "memory") (realloc (func $libc "realloc"))))
+    (core func $error-context-drop (canon error-context.drop))
+
+    (core instance $intrinsics
+        (export "memory" (memory $libc "memory"))
+        (export "stream.new" (func $stream-new))
+        (export "stream.read" (func $stream-read))
+        (export "stream.write" (func $stream-write))
+        (export "stream.cancel-read" (func $stream-cancel-read))
+        (export "stream.cancel-write" (func $stream-cancel-write))
+        (export "stream.close-readable" (func $stream-close-readable))
+        (export "stream.close-writable" (func $stream-close-writable))
+        (export "future.new" (func $future-new))
+        (export "future.read" (func $future-read))
+        (export "future.write" (func $future-write))
+        (export "future.cancel-read" (func $future-cancel-read))
+        (export "future.cancel-write" (func $future-cancel-write))
+        (export "future.close-readable" (func $future-close-readable))
+        (export "future.close-writable" (func $future-close-writable))
+        (export "error-context.new" (func $error-context-new))
+        (export "error-context.debug-message" (func $error-context-debug-message))
+        (export "error-context.drop" (func $error-context-drop))
+    )
+"#;
+
+/// Core imports matching `INTRINSICS`.
+const CORE_INTRINSICS: &str = r#"
+    (import "" "memory" (memory 1))
+    (import "" "stream.new" (func $stream-new (result i32)))
+    (import "" "stream.read" (func $stream-read (param i32 i32 i32) (result i32)))
+    (import "" "stream.write" (func $stream-write (param i32 i32 i32) (result i32)))
+    (import "" "stream.cancel-read" (func $stream-cancel-read (param i32) (result i32)))
+    (import "" "stream.cancel-write" (func $stream-cancel-write (param i32) (result i32)))
+    (import "" "stream.close-readable" (func $stream-close-readable (param i32 i32)))
+    (import "" "stream.close-writable" (func $stream-close-writable (param i32 i32)))
+    (import "" "future.new" (func $future-new (result i32)))
+    (import "" "future.read" (func $future-read (param i32 i32) (result i32)))
+    (import "" "future.write" (func $future-write (param i32 i32) (result i32)))
+    (import "" "future.cancel-read" (func $future-cancel-read (param i32) (result i32)))
+    (import "" "future.cancel-write" (func $future-cancel-write (param i32) (result i32)))
+    (import "" "future.close-readable" (func $future-close-readable (param i32 i32)))
+    (import "" "future.close-writable" (func $future-close-writable (param i32 i32)))
+    (import "" "error-context.new" (func $error-context-new (param i32 i32) (result i32)))
+    (import "" "error-context.debug-message" (func $error-context-debug-message (param i32 i32)))
+    (import "" "error-context.drop" (func $error-context-drop (param i32)))
+"#;
+
+/// A helper which traps if its two arguments differ.
+const EXPECT: &str = r#"
+    (func $expect (param i32 i32)
+        (if (i32.ne (local.get 0) (local.get 1)) (then unreachable)))
+"#;
+
+/// Builds a component whose core module `$m` consists of `CORE_INTRINSICS`,
+/// `module` and `EXPECT`, and which exports `run: func() -> u32` from it.
+///
+/// A non-empty `imports` is spliced in before `INTRINSICS` and must define a
+/// core instance `$host`, which is supplied to `$m` as its `host` import.
+fn intrinsics_component(imports: &str, module: &str) -> String {
+    let host = if imports.is_empty() {
+        ""
+    } else {
+        r#"(with "host" (instance $host))"#
+    };
+    format!(
+        r#"
+            (component
+                {imports}
+                {INTRINSICS}
+                (core module $m {CORE_INTRINSICS} {module} {EXPECT})
+                (core instance $i (instantiate $m
+                    (with "" (instance $intrinsics))
+                    {host}
+                ))
+                (func (export "run") (result u32)
+                    (canon lift (core func $i "run")))
+            )
+        "#
+    )
+}
+
+/// Calls the `run` export of `component` in a fresh store.
+async fn call_run(linker: &Linker<()>, component: &Component) -> Result<u32> {
+    let mut store = Store::new(linker.engine(), ());
+    let instance = linker.instantiate_async(&mut store, component).await?;
+    let func = instance.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    let promise = func.call_concurrent(&mut store, ()).await?;
+    Ok(promise.get(&mut store).await?.0)
+}
+
+#[tokio::test]
+async fn guest_stream_read_write() -> Result<()> {
+    let component = intrinsics_component(
+        "",
+        r#"
+            (func (export "run") (result i32)
+                (local $s i32)
+                (local.set $s (call $stream-new))
+                (i32.store (i32.const 0) (i32.const 1))
+                (i32.store (i32.const 4) (i32.const 2))
+                (i32.store (i32.const 8) (i32.const 3))
+
+                ;; Nothing is reading yet, so the write blocks...
+                (call $expect
+                    (call $stream-write (local.get $s) (i32.const 0) (i32.const 3))
+                    (i32.const -1))
+                ;; ...until a read picks up all three values.
+                (call $expect
+                    (call $stream-read (local.get $s) (i32.const 16) (i32.const 4))
+                    (i32.const 3))
+
+                ;; Reading once the writable end is closed reports `closed`.
+                (call $stream-close-writable (local.get $s) (i32.const 0))
+                (call $expect
+                    (call $stream-read (local.get $s) (i32.const 32) (i32.const 4))
+                    (i32.const 0x80000000))
+                (call $stream-close-readable (local.get $s) (i32.const 0))
+
+                ;; Likewise for writing once the readable end is closed.
+                (local.set $s (call $stream-new))
+                (call $stream-close-readable (local.get $s) (i32.const 0))
+                (call $expect
+                    (call $stream-write (local.get $s) (i32.const 0) (i32.const 3))
+                    (i32.const 0x80000000))
+                (call $stream-close-writable (local.get $s) (i32.const 0))
+
+                (i32.add
+                    (i32.load (i32.const 16))
+                    (i32.add (i32.load (i32.const 20)) (i32.load (i32.const 24)))))
+        "#,
+    );
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let linker = Linker::new(&engine);
+    assert_eq!(call_run(&linker, &component).await?, 6);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guest_future_read_write_and_cancel() -> Result<()> {
+    let component = intrinsics_component(
+        "",
+        r#"
+            (func (export "run") (result i32)
+                (local $f i32)
+                (local.set $f (call $future-new))
+
+                ;; Both a pending read and a pending write may be canceled.
+                (call $expect
+                    (call $future-read (local.get $f) (i32.const 16))
+                    (i32.const -1))
+                (call $expect (call $future-cancel-read (local.get $f)) (i32.const 0))
+                (i32.store (i32.const 0) (i32.const 42))
+                (call $expect
+                    (call $future-write (local.get $f) (i32.const 0))
+                    (i32.const -1))
+                (call $expect (call $future-cancel-write (local.get $f)) (i32.const 0))
+
+                ;; After which the future still delivers its value.
+                (call $expect
+                    (call $future-write (local.get $f) (i32.const 0))
+                    (i32.const -1))
+                (call $expect
+                    (call $future-read (local.get $f) (i32.const 16))
+                    (i32.const 1))
+                (call $future-close-writable (local.get $f) (i32.const 0))
+                (call $future-close-readable (local.get $f) (i32.const 0))
+                (i32.load (i32.const 16)))
+        "#,
+    );
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let linker = Linker::new(&engine);
+    assert_eq!(call_run(&linker, &component).await?, 42);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guest_error_context() -> Result<()> {
+    let component = intrinsics_component(
+        r#"
+            (import "message" (func $message (param "e" error-context) (result u32)))
+            (core func $message (canon lower (func $message)))
+            (core instance $host (export "message" (func $message)))
+        "#,
+        r#"
+            (import "host" "message" (func $message (param i32) (result i32)))
+            (data (i32.const 0) "oops")
+
+            (func (export "run") (result i32)
+                (local $e i32)
+                (local $s i32)
+                (local $result i32)
+                (local.set $e (call $error-context-new (i32.const 0) (i32.const 4)))
+
+                ;; Closing a stream with an error hands the reader its own
+                ;; handle to the same `error-context`.
+                (local.set $s (call $stream-new))
+                (call $stream-close-writable (local.get $s) (local.get $e))
+                (local.set $result
+                    (call $stream-read (local.get $s) (i32.const 16) (i32.const 1)))
+                (call $expect
+                    (i32.and (local.get $result) (i32.const 0x80000000))
+                    (i32.const 0x80000000))
+                (local.set $result (i32.and (local.get $result) (i32.const 0x7fffffff)))
+                (call $expect (i32.eqz (local.get $result)) (i32.const 0))
+                (call $stream-close-readable (local.get $s) (i32.const 0))
+
+                (call $error-context-debug-message (local.get $result) (i32.const 32))
+                (call $expect (i32.load (i32.const 36)) (i32.const 4))
+                (call $expect (i32.load (i32.load (i32.const 32))) (i32.load (i32.const 0)))
+                (call $error-context-drop (local.get $result))
+
+                ;; The host sees the same message.
+                (local.set $result (call $message (local.get $e)))
+                (call $error-context-drop (local.get $e))
+                (local.get $result))
+        "#,
+    );
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker.root().func_wrap(
+        "message",
+        |mut store: StoreContextMut<'_, ()>, (e,): (ErrorContext,)| {
+            let message = e.debug_message(&mut store)?;
+            e.drop(&mut store)?;
+            Ok((u32::try_from(message.len())?,))
+        },
+    )?;
+    assert_eq!(call_run(&linker, &component).await?, 4);
+
+    let mut store = Store::new(&engine, ());
+    let e = ErrorContext::new(&mut store, "from the host")?;
+    assert_eq!(e.debug_message(&mut store)?, "from the host");
+    e.drop(&mut store)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guest_intrinsic_errors() -> Result<()> {
+    let cases = [
+        (
+            "reading a closed stream",
+            r#"
+                (local.set $h (call $stream-new))
+                (call $stream-close-readable (local.get $h) (i32.const 0))
+                (drop (call $stream-read (local.get $h) (i32.const 0) (i32.const 1)))
+            "#,
+            "is not the readable end of a stream",
+        ),
+        (
+            "writing a closed future",
+            r#"
+                (local.set $h (call $future-new))
+                (call $future-close-writable (local.get $h) (i32.const 0))
+                (drop (call $future-write (local.get $h) (i32.const 0)))
+            "#,
+            "is not the writable end of a future",
+        ),
+        (
+            "dropping a stream twice",
+            r#"
+                (local.set $h (call $stream-new))
+                (call $stream-close-readable (local.get $h) (i32.const 0))
+                (call $stream-close-writable (local.get $h) (i32.const 0))
+                (call $stream-close-writable (local.get $h) (i32.const 0))
+            "#,
+            "unknown handle",
+        ),
+        (
+            "dropping an error-context twice",
+            r#"
+                (local.set $h (call $error-context-new (i32.const 0) (i32.const 0)))
+                (call $error-context-drop (local.get $h))
+                (call $error-context-drop (local.get $h))
+            "#,
+            "unknown error-context handle",
+        ),
+        (
+            "closing with an unknown error-context",
+            r#"
+                (local.set $h (call $stream-new))
+                (call $stream-close-writable (local.get $h) (i32.const 100))
+            "#,
+            "unknown error-context handle: 100",
+        ),
+        (
+            "canceling without a pending read",
+            r#"
+                (local.set $h (call $stream-new))
+                (drop (call $stream-cancel-read (local.get $h)))
+            "#,
+            "has no pending operation to cancel",
+        ),
+        (
+            "writing a stream twice at once",
+            r#"
+                (local.set $h (call $stream-new))
+                (drop (call $stream-write (local.get $h) (i32.const 0) (i32.const 1)))
+                (drop (call $stream-write (local.get $h) (i32.const 0) (i32.const 1)))
+            "#,
+            "cannot write to stream while another write is pending",
+        ),
+        (
+            "reading a future twice",
+            r#"
+                (local.set $h (call $future-new))
+                (drop (call $future-write (local.get $h) (i32.const 0)))
+                (drop (call $future-read (local.get $h) (i32.const 0)))
+                (drop (call $future-read (local.get $h) (i32.const 0)))
+            "#,
+            "cannot read from future after previous read succeeded",
+        ),
+        (
+            "a misaligned buffer",
+            r#"
+                (local.set $h (call $stream-new))
+                (drop (call $stream-read (local.get $h) (i32.const 1) (i32.const 1)))
+            "#,
+            "pointer not aligned",
+        ),
+        (
+            "an out-of-bounds buffer",
+            r#"
+                (local.set $h (call $stream-new))
+                (drop (call $stream-read (local.get $h) (i32.const 65532) (i32.const 2)))
+            "#,
+            "buffer out of bounds of memory",
+        ),
+    ];
+
+    let engine = concurrent_engine();
+    let linker = Linker::new(&engine);
+    for (case, body, expected) in cases {
+        let component = intrinsics_component(
+            "",
+            &format!(
+                r#"
+                    (func (export "run") (result i32)
+                        (local $h i32)
+                        {body}
+                        (i32.const 0))
+                "#
+            ),
+        );
+        let component = Component::new(&engine, component)?;
+        let err = call_run(&linker, &component)
+            .await
+            .expect_err(&format!("{case} should fail"));
+        let err = format!("{err:?}");
+        assert!(err.contains(expected), "{case}: unexpected error: {err}");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guest_reads_host_stream() -> Result<()> {
+    let component = r#"
+        (component
+            (type $s (stream u32))
+
+            (core module $libc (memory (export "memory") 1))
+            (core instance $libc (instantiate $libc))
+
+            (core func $stream-read (canon stream.read $s async (memory $libc "memory")))
+            (core func $stream-close-readable (canon stream.close-readable $s))
+            (core func $task-return (canon task.return (result u32)))
+            (core func $waitable-set-new (canon waitable-set.new))
+            (core func $waitable-join (canon waitable.join))
+            (core func $waitable-set-wait (canon waitable-set.wait (memory $libc "memory")))
+
+            (core module $m
+                (import "" "memory" (memory 1))
+                (import "" "stream.read" (func $stream-read (param i32 i32 i32) (result i32)))
+                (import "" "stream.close-readable" (func $stream-close-readable (param i32 i32)))
+                (import "" "task.return" (func $task-return (param i32)))
+                (import "" "waitable-set.new" (func $waitable-set-new (result i32)))
+                (import "" "waitable.join" (func $waitable-join (param i32 i32)))
+                (import "" "waitable-set.wait" (func $waitable-set-wait (param i32 i32) (result i32)))
+
+                (func (export "run") (param $s i32)
+                    (local $set i32)
+                    (local $n i32)
+                    (local $i i32)
+                    (local $sum i32)
+                    (local.set $set (call $waitable-set-new))
+                    (call $waitable-join (local.get $s) (local.get $set))
+                    (loop $read
+                        (local.set $n
+                            (call $stream-read (local.get $s) (i32.const 0) (i32.const 4)))
+                        (if (i32.eq (local.get $n) (i32.const -1))
+                            (then
+                                ;; Wait for the stream-read event.
+                                (if (i32.ne
+                                        (call $waitable-set-wait (local.get $set) (i32.const 64))
+                                        (i32.const 5))
+                                    (then unreachable))
+                                (local.set $n (i32.load (i32.const 68)))))
+                        (if (i32.eqz (i32.and (local.get $n) (i32.const 0x80000000)))
+                            (then
+                                (local.set $i (i32.const 0))
+                                (loop $sum
+                                    (if (i32.lt_u (local.get $i) (local.get $n))
+                                        (then
+                                            (local.set $sum (i32.add
+                                                (local.get $sum)
+                                                (i32.load (i32.shl (local.get $i) (i32.const 2)))))
+                                            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                                            (br $sum))))
+                                (br $read))))
+                    (call $stream-close-readable (local.get $s) (i32.const 0))
+                    (call $task-return (local.get $sum)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "memory" (memory $libc "memory"))
+                    (export "stream.read" (func $stream-read))
+                    (export "stream.close-readable" (func $stream-close-readable))
+                    (export "task.return" (func $task-return))
+                    (export "waitable-set.new" (func $waitable-set-new))
+                    (export "waitable.join" (func $waitable-join))
+                    (export "waitable-set.wait" (func $waitable-set-wait))
+                ))
+            ))
+
+            (func (export "run") (param "s" $s) (result u32)
+                (canon lift (core func $i "run") async))
+        )
+    "#;
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine)
+        .instantiate_async(&mut store, &component)
+        .await?;
+    let run = instance.get_typed_func::<(HostStream<u32>,), (u32,)>(&mut store, "run")?;
+
+    let (mut tx, rx) = HostStream::channel(1);
+    tokio::spawn(async move {
+        for i in 0..100 {
+            tx.send(i).await.unwrap();
+        }
+    });
+    let promise = run.call_concurrent(&mut store, (rx,)).await?;
+    assert_eq!(promise.get(&mut store).await?, ((0..100).sum(),));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn guest_writes_host_stream() -> Result<()> {
+    let component = r#"
+        (component
+            (type $s (stream u32))
+            (import "consume" (func $consume (param "s" $s) (result u32)))
+
+            (core module $libc (memory (export "memory") 1))
+            (core instance $libc (instantiate $libc))
+
+            (core func $consume (canon lower (func $consume) async (memory $libc "memory")))
+            (core func $stream-new (canon stream.new $s))
+            (core func $stream-write (canon stream.write $s async (memory $libc "memory")))
+            (core func $stream-close-writable (canon stream.close-writable $s))
+            (core func $task-return (canon task.return (result u32)))
+            (core func $waitable-set-new (canon waitable-set.new))
+            (core func $waitable-join (canon waitable.join))
+            (core func $waitable-set-wait (canon waitable-set.wait (memory $libc "memory")))
+            (core func $subtask-drop (canon subtask.drop))
+
+            (core module $m
+                (import "" "memory" (memory 1))
+                (import "" "consume" (func $consume (param i32 i32) (result i32)))
+                (import "" "stream.new" (func $stream-new (result i32)))
+                (import "" "stream.write" (func $stream-write (param i32 i32 i32) (result i32)))
+                (import "" "stream.close-writable" (func $stream-close-writable (param i32 i32)))
+                (import "" "task.return" (func $task-return (param i32)))
+                (import "" "waitable-set.new" (func $waitable-set-new (result i32)))
+                (import "" "waitable.join" (func $waitable-join (param i32 i32)))
+                (import "" "waitable-set.wait" (func $waitable-set-wait (param i32 i32) (result i32)))
+                (import "" "subtask.drop" (func $subtask-drop (param i32)))
+
+                (func (export "run") (param $n i32)
+                    (local $s i32)
+                    (local $set i32)
+                    (local $status i32)
+                    (local $i i32)
+                    (local $ptr i32)
+                    (local $written i32)
+                    (loop $fill
+                        (if (i32.lt_u (local.get $i) (local.get $n))
+                            (then
+                                (i32.store
+                                    (i32.add (i32.const 256) (i32.shl (local.get $i) (i32.const 2)))
+                                    (local.get $i))
+                                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                                (br $fill))))
+
+                    ;; Hand the readable end to the host, then write to it.
+                    (local.set $s (call $stream-new))
+                    (i32.store (i32.const 0) (local.get $s))
+                    (local.set $status (call $consume (i32.const 0) (i32.const 4)))
+                    (local.set $set (call $waitable-set-new))
+                    (call $waitable-join (local.get $s) (local.get $set))
+                    (local.set $ptr (i32.const 256))
+                    (loop $write
+                        (if (local.get $n)
+                            (then
+                                (local.set $written
+                                    (call $stream-write (local.get $s) (local.get $ptr) (local.get $n)))
+                                (if (i32.eq (local.get $written) (i32.const -1))
+                                    (then
+                                        ;; Wait for the stream-write event.
+                                        (if (i32.ne
+                                                (call $waitable-set-wait (local.get $set) (i32.const 64))
+                                                (i32.const 6))
+                                            (then unreachable))
+                                        (local.set $written (i32.load (i32.const 68)))))
+                                ;; The host never closes its end early.
+                                (if (i32.and (local.get $written) (i32.const 0x80000000))
+                                    (then unreachable))
+                                (local.set $ptr (i32.add
+                                    (local.get $ptr)
+                                    (i32.shl (local.get $written) (i32.const 2))))
+                                (local.set $n (i32.sub (local.get $n) (local.get $written)))
+                                (br $write))))
+                    (call $stream-close-writable (local.get $s) (i32.const 0))
+
+                    ;; Wait for the host to finish summing the values.
+                    (if (i32.ne (i32.shr_u (local.get $status) (i32.const 30)) (i32.const 3))
+                        (then
+                            (call $waitable-join
+                                (i32.and (local.get $status) (i32.const 0x3fffffff))
+                                (local.get $set))
+                            (loop $wait
+                                (br_if $wait (i32.ne
+                                    (call $waitable-set-wait (local.get $set) (i32.const 64))
+                                    (i32.const 3))))
+                            (call $subtask-drop
+                                (i32.and (local.get $status) (i32.const 0x3fffffff)))))
+                    (call $task-return (i32.load (i32.const 4))))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "memory" (memory $libc "memory"))
+                    (export "consume" (func $consume))
+                    (export "stream.new" (func $stream-new))
+                    (export "stream.write" (func $stream-write))
+                    (export "stream.close-writable" (func $stream-close-writable))
+                    (export "task.return" (func $task-return))
+                    (export "waitable-set.new" (func $waitable-set-new))
+                    (export "waitable.join" (func $waitable-join))
+                    (export "waitable-set.wait" (func $waitable-set-wait))
+                    (export "subtask.drop" (func $subtask-drop))
+                ))
+            ))
+
+            (func (export "run") (param "n" u32) (result u32)
+                (canon lift (core func $i "run") async))
+        )
+    "#;
+
+    let engine = concurrent_engine();
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker
+        .root()
+        .func_wrap_concurrent("consume", |_, (s,): (HostStream<u32>,)| async move {
+            let sum = s.fold(0, |sum, x| async move { sum + x }).await;
+            move |_: StoreContextMut<'_, ()>| Ok((sum,))
+        })?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate_async(&mut store, &component).await?;
+    let run = instance.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    let promise = run.call_concurrent(&mut store, (100,)).await?;
+    assert_eq!(promise.get(&mut store).await?, ((0..100).sum(),));
+
+    Ok(())
+}
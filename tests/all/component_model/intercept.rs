@@ -0,0 +1,432 @@
+#![cfg(not(miri))]
+
+use anyhow::{bail, Result};
+use std::time::Duration;
+use wasmtime::component::*;
+use wasmtime::{Store, StoreContextMut};
+
+#[derive(Default)]
+struct Recorder {
+    deny: Option<&'static str>,
+}
+
+impl CallInterceptor<Vec<String>> for Recorder {
+    fn before_call(
+        &mut self,
+        mut store: StoreContextMut<'_, Vec<String>>,
+        call: InterceptedCall<'_>,
+        params: &[Val],
+    ) -> Result<()> {
+        if self.deny == Some(call.name()) {
+            bail!("call to `{}` denied", call.name());
+        }
+        let event = format!(
+            "before {:?} {:?} {} {params:?}",
+            call.kind(),
+            call.interface(),
+            call.name(),
+        );
+        store.data_mut().push(event);
+        Ok(())
+    }
+
+    fn after_call(
+        &mut self,
+        mut store: StoreContextMut<'_, Vec<String>>,
+        call: InterceptedCall<'_>,
+        results: &[Val],
+        elapsed: Duration,
+    ) -> Result<()> {
+        assert!(elapsed < Duration::from_secs(60));
+        let event = format!(
+            "after {:?} {:?} {} {results:?}",
+            call.kind(),
+            call.interface(),
+            call.name(),
+        );
+        store.data_mut().push(event);
+        Ok(())
+    }
+}
+
+const IMPORTS_AND_EXPORTS: &str = r#"
+    (component
+        (import "host" (instance $host
+            (export "double" (func (param "x" u32) (result u32)))
+        ))
+        (core func $double (canon lower (func $host "double")))
+        (core module $m
+            (import "" "double" (func $double (param i32) (result i32)))
+            (func (export "run") (param i32) (result i32)
+                (i32.add (call $double (local.get 0)) (i32.const 1)))
+        )
+        (core instance $i (instantiate $m
+            (with "" (instance (export "double" (func $double))))
+        ))
+        (func $run (param "x" u32) (result u32) (canon lift (core func $i "run")))
+        (instance (export "api")
+            (export "run" (func $run))
+        )
+    )
+"#;
+
+#[test]
+fn intercept_exports_and_imports() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, IMPORTS_AND_EXPORTS)?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("host")?
+        .func_wrap("double", |_, (x,): (u32,)| Ok((x * 2,)))?;
+
+    let mut store = Store::new(&engine, Vec::new());
+    store.component_call_interceptor(Recorder::default());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let api = instance.get_export(&mut store, None, "api").unwrap();
+    let run = instance.get_export(&mut store, Some(&api), "run").unwrap();
+
+    // Typed calls are intercepted...
+    let typed = instance.get_typed_func::<(u32,), (u32,)>(&mut store, &run)?;
+    assert_eq!(typed.call(&mut store, (3,))?, (7,));
+    typed.post_return(&mut store)?;
+
+    // ... as are dynamic calls.
+    let func = instance.get_func(&mut store, &run).unwrap();
+    let mut results = [Val::U32(0)];
+    func.call(&mut store, &[Val::U32(10)], &mut results)?;
+    func.post_return(&mut store)?;
+    assert_eq!(results, [Val::U32(21)]);
+
+    let mut expected = Vec::new();
+    for (x, double) in [(3, 6), (10, 20)] {
+        expected.extend([
+            format!("before Export Some(\"api\") run [U32({x})]"),
+            format!("before Import Some(\"host\") double [U32({x})]"),
+            format!("after Import Some(\"host\") double [U32({double})]"),
+            format!("after Export Some(\"api\") run [U32({})]", double + 1),
+        ]);
+    }
+    assert_eq!(store.data(), &expected);
+    Ok(())
+}
+
+#[test]
+fn intercept_dynamic_import() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, IMPORTS_AND_EXPORTS)?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("host")?
+        .func_new("double", |_, params, results| {
+            let Val::U32(x) = params[0] else { bail!("bad") };
+            results[0] = Val::U32(x * 2);
+            Ok(())
+        })?;
+
+    let mut store = Store::new(&engine, Vec::new());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let api = instance.get_export(&mut store, None, "api").unwrap();
+    let run = instance.get_export(&mut store, Some(&api), "run").unwrap();
+    let run = instance.get_typed_func::<(u32,), (u32,)>(&mut store, &run)?;
+
+    // Nothing is recorded until an interceptor is configured.
+    assert_eq!(run.call(&mut store, (1,))?, (3,));
+    run.post_return(&mut store)?;
+    assert!(store.data().is_empty());
+
+    store.component_call_interceptor(Recorder::default());
+    assert_eq!(run.call(&mut store, (2,))?, (5,));
+    run.post_return(&mut store)?;
+    assert_eq!(
+        store.data(),
+        &[
+            "before Export Some(\"api\") run [U32(2)]",
+            "before Import Some(\"host\") double [U32(2)]",
+            "after Import Some(\"host\") double [U32(4)]",
+            "after Export Some(\"api\") run [U32(5)]",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn intercept_denies_import() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, IMPORTS_AND_EXPORTS)?;
+
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("host")?
+        .func_wrap("double", |_, (x,): (u32,)| Ok((x * 2,)))?;
+
+    let mut store = Store::new(&engine, Vec::new());
+    store.component_call_interceptor(Recorder {
+        deny: Some("double"),
+    });
+    let instance = linker.instantiate(&mut store, &component)?;
+    let api = instance.get_export(&mut store, None, "api").unwrap();
+    let run = instance.get_export(&mut store, Some(&api), "run").unwrap();
+    let run = instance.get_typed_func::<(u32,), (u32,)>(&mut store, &run)?;
+
+    let err = run.call(&mut store, (3,)).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("call to `double` denied"),
+        "{err:?}"
+    );
+    assert_eq!(store.data(), &["before Export Some(\"api\") run [U32(3)]"]);
+    Ok(())
+}
+
+#[test]
+fn intercept_strings_and_resources() -> Result<()> {
+    let component = r#"
+        (component
+            (type $counter' (resource (rep i32)))
+            (export $counter "counter" (type $counter'))
+            (core func $new (canon resource.new $counter))
+            (core module $m
+                (import "" "new" (func $new (param i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "ctor") (param i32) (result i32)
+                    (call $new (local.get 0)))
+                (func (export "get") (param i32) (result i32)
+                    local.get 0)
+                (func (export "name") (param i32) (result i32)
+                    (i32.store (i32.const 8) (i32.const 100))
+                    (i32.store (i32.const 12) (i32.const 5))
+                    i32.const 8)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+                (data (i32.const 100) "hello")
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "new" (func $new))
+                ))
+            ))
+            (func (export "[constructor]counter") (param "v" u32) (result (own $counter))
+                (canon lift (core func $i "ctor")))
+            (func (export "[method]counter.get") (param "self" (borrow $counter)) (result u32)
+                (canon lift (core func $i "get")))
+            (func (export "[method]counter.name") (param "self" (borrow $counter)) (result string)
+                (canon lift (core func $i "name") (memory $i "memory")
+                    (realloc (func $i "realloc"))))
+        )
+    "#;
+
+    #[derive(Default)]
+    struct Resources;
+
+    impl CallInterceptor<Vec<String>> for Resources {
+        fn before_call(
+            &mut self,
+            mut store: StoreContextMut<'_, Vec<String>>,
+            call: InterceptedCall<'_>,
+            params: &[Val],
+        ) -> Result<()> {
+            let event = format!("before {} {}", call.name(), describe(params));
+            store.data_mut().push(event);
+            Ok(())
+        }
+
+        fn after_call(
+            &mut self,
+            mut store: StoreContextMut<'_, Vec<String>>,
+            call: InterceptedCall<'_>,
+            results: &[Val],
+            _elapsed: Duration,
+        ) -> Result<()> {
+            let event = format!("after {} {}", call.name(), describe(results));
+            store.data_mut().push(event);
+            Ok(())
+        }
+    }
+
+    fn describe(vals: &[Val]) -> String {
+        vals.iter()
+            .map(|val| match val {
+                Val::Resource(r) => format!("resource(owned={})", r.owned()),
+                other => format!("{other:?}"),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, Vec::new());
+    store.component_call_interceptor(Resources);
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let ty = instance.get_resource(&mut store, "counter").unwrap();
+    let funcs = instance.get_resource_funcs(&mut store, ty).unwrap();
+
+    // Inspecting the resources passed to and returned from calls doesn't take
+    // ownership of them, so they remain usable afterwards.
+    let counter = funcs.construct(&mut store, &[Val::U32(42)])?;
+    assert!(counter.owned());
+    let results = funcs.call_method(&mut store, &counter, "get", &[])?;
+    assert_eq!(results, [Val::U32(42)]);
+    let results = funcs.call_method(&mut store, &counter, "name", &[])?;
+    assert_eq!(results, [Val::String("hello".into())]);
+    counter.resource_drop(&mut store)?;
+
+    assert_eq!(
+        store.data(),
+        &[
+            "before [constructor]counter U32(42)",
+            "after [constructor]counter resource(owned=false)",
+            "before [method]counter.get resource(owned=false)",
+            "after [method]counter.get U32(42)",
+            "before [method]counter.name resource(owned=false)",
+            "after [method]counter.name String(\"hello\")",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn intercept_spilled_params() -> Result<()> {
+    // More than `MAX_FLAT_PARAMS` parameters are passed through a pointer into
+    // linear memory.
+    let params = (0..17)
+        .map(|i| format!("(param \"p{i}\" u32)"))
+        .collect::<String>();
+    let component = format!(
+        r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "run") (param i32) (result i32)
+                    (i32.load offset=64 (local.get 0)))
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    i32.const 16)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "run") {params} (result u32)
+                (canon lift (core func $i "run") (memory $i "memory")
+                    (realloc (func $i "realloc"))))
+        )
+        "#
+    );
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = Store::new(&engine, Vec::new());
+    store.component_call_interceptor(Recorder::default());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let func = instance.get_func(&mut store, "run").unwrap();
+    let args = (0..17).map(|i| Val::U32(i * 10)).collect::<Vec<_>>();
+    let mut results = [Val::U32(0)];
+    func.call(&mut store, &args, &mut results)?;
+    func.post_return(&mut store)?;
+    assert_eq!(results, [Val::U32(160)]);
+
+    assert_eq!(
+        store.data(),
+        &[
+            format!("before Export None run {args:?}"),
+            "after Export None run [U32(160)]".to_string(),
+        ]
+    );
+
+    // Spilled parameters are always read through a 32-bit pointer, which
+    // relies on components being unable to use 64-bit memories.
+    let mut config = component_test_util::config();
+    config.wasm_memory64(true);
+    let engine = wasmtime::Engine::new(&config)?;
+    let Err(err) = Component::new(
+        &engine,
+        r#"
+        (component
+            (core module $m
+                (memory (export "memory") i64 1)
+                (func (export "run") (param i32) (result i32) i32.const 0)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    i32.const 16)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "run") (param "s" string) (result u32)
+                (canon lift (core func $i "run") (memory $i "memory")
+                    (realloc (func $i "realloc"))))
+        )
+        "#,
+    ) else {
+        panic!("components with 64-bit memories should be rejected");
+    };
+    assert!(
+        format!("{err:?}").contains("64-bit linear memories are not compatible with components"),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn intercept_lift_failure_releases_call_scope() -> Result<()> {
+    let component = r#"
+        (component
+            (import "host" (instance $host
+                (export "log" (func (param "s" string)))
+            ))
+            (type $counter' (resource (rep i32)))
+            (export $counter "counter" (type $counter'))
+            (core module $libc
+                (memory (export "memory") 1)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+            )
+            (core instance $libc (instantiate $libc))
+            (core func $log (canon lower (func $host "log") (memory $libc "memory")))
+            (core func $new (canon resource.new $counter))
+            (core module $m
+                (import "" "log" (func $log (param i32 i32)))
+                (import "" "new" (func $new (param i32) (result i32)))
+                (func (export "bad")
+                    (call $log (i32.const 0x7fff0000) (i32.const 4)))
+                (func (export "ctor") (param i32) (result i32)
+                    (call $new (local.get 0)))
+                (func (export "get") (param i32) (result i32)
+                    local.get 0)
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "log" (func $log))
+                    (export "new" (func $new))
+                ))
+            ))
+            (func (export "bad") (canon lift (core func $i "bad")))
+            (func (export "[constructor]counter") (param "v" u32) (result (own $counter))
+                (canon lift (core func $i "ctor")))
+            (func (export "[method]counter.get") (param "self" (borrow $counter)) (result u32)
+                (canon lift (core func $i "get")))
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("host")?
+        .func_wrap("log", |_, (_,): (String,)| Ok(()))?;
+    let mut store = Store::new(&engine, Vec::new());
+    store.component_call_interceptor(Recorder::default());
+
+    // The interceptor fails to lift the out-of-bounds string passed to `log`,
+    // which fails the call after the interceptor's call scope was entered.
+    let instance = linker.instantiate(&mut store, &component)?;
+    let bad = instance.get_typed_func::<(), ()>(&mut store, "bad")?;
+    let err = bad.call(&mut store, ()).unwrap_err();
+    assert!(format!("{err:?}").contains("out of bounds"), "{err:?}");
+
+    // The interceptor's call scope was exited again, so borrows are still
+    // accounted for correctly in later calls.
+    let instance = linker.instantiate(&mut store, &component)?;
+    let ty = instance.get_resource(&mut store, "counter").unwrap();
+    let funcs = instance.get_resource_funcs(&mut store, ty).unwrap();
+    let counter = funcs.construct(&mut store, &[Val::U32(42)])?;
+    let results = funcs.call_method(&mut store, &counter, "get", &[])?;
+    assert_eq!(results, [Val::U32(42)]);
+    counter.resource_drop(&mut store)?;
+    Ok(())
+}
@@ -0,0 +1,306 @@
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use wasmtime::component::*;
+use wasmtime::{Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+fn store(engine: &wasmtime::Engine, limits: StoreLimitsBuilder) -> Store<StoreLimits> {
+    let mut store = Store::new(engine, limits.build());
+    store.limiter(|s| s);
+    store
+}
+
+#[test]
+fn lift_bytes() -> Result<()> {
+    let component = r#"
+        (component
+            (core module $m
+                (memory (export "memory") 1)
+                (func (export "list") (param i32) (result i32)
+                    (i32.store (i32.const 8) (i32.const 100))
+                    (i32.store (i32.const 12) (local.get 0))
+                    i32.const 8)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "list") (param "len" u32) (result (list u8))
+                (canon lift (core func $i "list") (memory $i "memory")
+                    (realloc (func $i "realloc")))
+            )
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let mut store = store(
+        &engine,
+        StoreLimitsBuilder::new().component_lift_bytes(1000),
+    );
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let list = instance.get_typed_func::<(u32,), (Vec<u8>,)>(&mut store, "list")?;
+
+    let (result,) = list.call(&mut store, (1000,))?;
+    assert_eq!(result.len(), 1000);
+    list.post_return(&mut store)?;
+
+    let err = list.call(&mut store, (1001,)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn resource_handles() -> Result<()> {
+    let component = r#"
+        (component
+            (type $a (resource (rep i32)))
+            (type $b (resource (rep i32)))
+            (core func $new-a (canon resource.new $a))
+            (core func $new-b (canon resource.new $b))
+            (core func $drop-a (canon resource.drop $a))
+            (core module $m
+                (import "" "new-a" (func $new-a (param i32) (result i32)))
+                (import "" "new-b" (func $new-b (param i32) (result i32)))
+                (import "" "drop-a" (func $drop-a (param i32)))
+                (func (export "new-a") (call $new-a (i32.const 0)) drop)
+                (func (export "new-b") (call $new-b (i32.const 0)) drop)
+                (func (export "drop-a") (param i32) (call $drop-a (local.get 0)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "new-a" (func $new-a))
+                    (export "new-b" (func $new-b))
+                    (export "drop-a" (func $drop-a))
+                ))
+            ))
+            (func (export "new-a") (canon lift (core func $i "new-a")))
+            (func (export "new-b") (canon lift (core func $i "new-b")))
+            (func (export "drop-a") (param "idx" u32) (canon lift (core func $i "drop-a")))
+        )
+    "#;
+
+    let engine = super::engine();
+    let component = Component::new(&engine, component)?;
+    let instantiate = || -> Result<_> {
+        let mut store = store(
+            &engine,
+            StoreLimitsBuilder::new()
+                .component_resource_handles(2)
+                .component_instance_resource_handles(3),
+        );
+        let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+        let new_a = instance.get_typed_func::<(), ()>(&mut store, "new-a")?;
+        let new_b = instance.get_typed_func::<(), ()>(&mut store, "new-b")?;
+        let drop_a = instance.get_typed_func::<(u32,), ()>(&mut store, "drop-a")?;
+        Ok((store, new_a, new_b, drop_a))
+    };
+    fn call<P: ComponentNamedList + Lower>(
+        store: &mut Store<StoreLimits>,
+        f: &TypedFunc<P, ()>,
+        params: P,
+    ) -> Result<()> {
+        f.call(&mut *store, params)?;
+        f.post_return(&mut *store)
+    }
+
+    // Two handles fit in the table for `a`, but a third does not.
+    let (mut store, new_a, _, _) = instantiate()?;
+    call(&mut store, &new_a, ())?;
+    call(&mut store, &new_a, ())?;
+    let err = call(&mut store, &new_a, ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+
+    // Dropping a handle makes room for another, and three handles fit across
+    // both tables, but a fourth does not.
+    let (mut store, new_a, new_b, drop_a) = instantiate()?;
+    call(&mut store, &new_a, ())?;
+    call(&mut store, &new_a, ())?;
+    call(&mut store, &drop_a, (1,))?;
+    call(&mut store, &new_a, ())?;
+    call(&mut store, &new_b, ())?;
+    let err = call(&mut store, &new_b, ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+/// A component whose `run` export passes a string of `len` bytes to another
+/// component, optionally lifting `run` with the async ABI.
+fn adapter_copy_component(async_lift: bool) -> String {
+    let (task_return_def, task_return_import, task_return_export, task_return, lift) = if async_lift
+    {
+        (
+            "(core func $task-return (canon task.return))",
+            r#"(import "" "task.return" (func $task-return))"#,
+            r#"(export "task.return" (func $task-return))"#,
+            "(call $task-return)",
+            "async",
+        )
+    } else {
+        ("", "", "", "", "")
+    };
+    format!(
+        r#"
+        (component
+            (component $callee
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "take") (param i32 i32))
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                        i32.const 16)
+                )
+                (core instance $i (instantiate $m))
+                (func (export "take") (param "s" string)
+                    (canon lift (core func $i "take") (memory $i "memory")
+                        (realloc (func $i "realloc")))
+                )
+            )
+            (component $caller
+                (import "take" (func $take (param "s" string)))
+                (core module $libc
+                    (memory (export "memory") 1)
+                )
+                (core instance $libc (instantiate $libc))
+                (core func $take (canon lower (func $take) (memory $libc "memory")))
+                {task_return_def}
+                (core module $m
+                    (import "" "take" (func $take (param i32 i32)))
+                    {task_return_import}
+                    (func (export "run") (param i32)
+                        (call $take (i32.const 0) (local.get 0))
+                        {task_return})
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance
+                        (export "take" (func $take))
+                        {task_return_export}
+                    ))
+                ))
+                (func (export "run") (param "len" u32)
+                    (canon lift (core func $i "run") {lift}))
+            )
+            (instance $callee (instantiate $callee))
+            (instance $caller (instantiate $caller
+                (with "take" (func $callee "take"))
+            ))
+            (export "run" (func $caller "run"))
+        )
+        "#
+    )
+}
+
+fn adapter_copy_engine(limit: bool, async_support: bool) -> Result<wasmtime::Engine> {
+    let mut config = component_test_util::config();
+    config.component_adapter_copy_limit(limit);
+    config.async_support(async_support);
+    #[cfg(feature = "component-model-async")]
+    config.wasm_component_model_async(true);
+    wasmtime::Engine::new(&config)
+}
+
+#[test]
+fn adapter_copy_bytes() -> Result<()> {
+    let engine = adapter_copy_engine(true, false)?;
+    let component = Component::new(&engine, adapter_copy_component(false))?;
+    let mut store = store(
+        &engine,
+        StoreLimitsBuilder::new().component_adapter_copy_bytes(1000),
+    );
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(u32,), ()>(&mut store, "run")?;
+
+    // The limit applies to each call, not across calls.
+    for _ in 0..3 {
+        run.call(&mut store, (1000,))?;
+        run.post_return(&mut store)?;
+    }
+
+    let err = run.call(&mut store, (1001,)).unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+fn adapter_copy_bytes_requires_config() -> Result<()> {
+    // Without `Config::component_adapter_copy_limit` adapters don't account
+    // for the bytes they copy, so the limit isn't enforced.
+    let engine = adapter_copy_engine(false, false)?;
+    let component = Component::new(&engine, adapter_copy_component(false))?;
+    let mut store = store(
+        &engine,
+        StoreLimitsBuilder::new().component_adapter_copy_bytes(1000),
+    );
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let run = instance.get_typed_func::<(u32,), ()>(&mut store, "run")?;
+    run.call(&mut store, (1001,))?;
+    run.post_return(&mut store)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn adapter_copy_bytes_async() -> Result<()> {
+    let engine = adapter_copy_engine(true, true)?;
+    let component = Component::new(&engine, adapter_copy_component(false))?;
+    let mut store = store(
+        &engine,
+        StoreLimitsBuilder::new().component_adapter_copy_bytes(1000),
+    );
+    let instance = Linker::new(&engine)
+        .instantiate_async(&mut store, &component)
+        .await?;
+    let run = instance.get_typed_func::<(u32,), ()>(&mut store, "run")?;
+    for _ in 0..3 {
+        run.call_async(&mut store, (1000,)).await?;
+        run.post_return_async(&mut store).await?;
+    }
+    let err = run.call_async(&mut store, (1001,)).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[cfg(feature = "component-model-async")]
+async fn adapter_copy_bytes_concurrent() -> Result<()> {
+    // Each concurrent call to an async-lifted export starts counting anew.
+    let engine = adapter_copy_engine(true, true)?;
+    let component = Component::new(&engine, adapter_copy_component(true))?;
+    let mut store = store(
+        &engine,
+        StoreLimitsBuilder::new().component_adapter_copy_bytes(1000),
+    );
+    let instance = Linker::new(&engine)
+        .instantiate_async(&mut store, &component)
+        .await?;
+    let run = instance.get_typed_func::<(u32,), ()>(&mut store, "run")?;
+    for _ in 0..3 {
+        let promise = run.call_concurrent(&mut store, (1000,)).await?;
+        promise.get(&mut store).await?;
+    }
+    let promise = run.call_concurrent(&mut store, (1001,)).await?;
+    let err = promise.get(&mut store).await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref(),
+        Some(&Trap::ComponentLimitExceeded),
+        "{err:?}"
+    );
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use component_test_util::TypedFuncExt;
 use wasmtime::component::types::ComponentItem;
 use wasmtime::component::{Component, Linker, ResourceType};
 use wasmtime::{Engine, Store};
@@ -182,3 +183,326 @@ fn linker_fails_to_define_unknown_core_module_imports_as_traps() -> Result<()> {
 
     Ok(())
 }
+
+const COUNTER_PROVIDER: &str = r#"
+    (component
+        (core module $m
+            (global $drops (mut i32) (i32.const 0))
+            (func (export "dtor") (param i32)
+                (global.set $drops (i32.add (global.get $drops) (i32.const 1))))
+            (func (export "drops") (result i32) global.get $drops)
+        )
+        (core instance $i (instantiate $m))
+        (type $counter (resource (rep i32) (dtor (func $i "dtor"))))
+        (core func $new (canon resource.new $counter))
+        (core module $m2
+            (import "" "new" (func $new (param i32) (result i32)))
+            (func (export "ctor") (param i32) (result i32) (call $new (local.get 0)))
+            (func (export "get") (param i32) (result i32) (local.get 0))
+        )
+        (core instance $i2 (instantiate $m2
+            (with "" (instance (export "new" (func $new))))
+        ))
+        (func $ctor (param "v" u32) (result (own $counter))
+            (canon lift (core func $i2 "ctor")))
+        (func $get (param "self" (borrow $counter)) (result u32)
+            (canon lift (core func $i2 "get")))
+        (instance $api
+            (export "counter" (type $counter))
+            (export "new-counter" (func $ctor))
+            (export "counter-get" (func $get))
+        )
+        (export "api" (instance $api))
+        (func (export "drops") (result u32)
+            (canon lift (core func $i "drops")))
+    )
+"#;
+
+fn counter_consumer(get_result: &str) -> String {
+    format!(
+        r#"
+        (component
+            (import "api" (instance $api
+                (export "counter" (type $c (sub resource)))
+                (export "new-counter" (func (param "v" u32) (result (own $c))))
+                (export "counter-get" (func (param "self" (borrow $c)) (result {get_result})))
+            ))
+            (alias export $api "counter" (type $c))
+            (alias export $api "new-counter" (func $ctor))
+            (alias export $api "counter-get" (func $get))
+            (core func $ctor (canon lower (func $ctor)))
+            (core func $get (canon lower (func $get)))
+            (core func $drop (canon resource.drop $c))
+            (core module $m
+                (import "" "ctor" (func $ctor (param i32) (result i32)))
+                (import "" "get" (func $get (param i32) (result i32)))
+                (import "" "drop" (func $drop (param i32)))
+                (func (export "run") (param i32) (result i32)
+                    (local $h i32)
+                    (local $r i32)
+                    (local.set $h (call $ctor (local.get 0)))
+                    (local.set $r (call $get (local.get $h)))
+                    (call $drop (local.get $h))
+                    (local.get $r))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "ctor" (func $ctor))
+                    (export "get" (func $get))
+                    (export "drop" (func $drop))
+                ))
+            ))
+            (func (export "run") (param "v" u32) (result u32)
+                (canon lift (core func $i "run")))
+        )
+        "#
+    )
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn define_component_instance() -> Result<()> {
+    let engine = Engine::default();
+    let provider = Component::new(&engine, COUNTER_PROVIDER)?;
+    let component = Component::new(&engine, counter_consumer("u32"))?;
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let provider = linker.instantiate(&mut store, &provider)?;
+    linker.define_component_instance(&mut store, &provider)?;
+
+    let consumer = linker.instantiate(&mut store, &component)?;
+    let run = consumer.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call_and_post_return(&mut store, (7,))?, (7,));
+
+    // Dropping the handle within the consumer ran the provider's destructor.
+    let drops = provider.get_typed_func::<(), (u32,)>(&mut store, "drops")?;
+    assert_eq!(drops.call_and_post_return(&mut store, ())?, (1,));
+
+    // The resource type the consumer sees is the one the provider defines.
+    let api = provider.get_export(&mut store, None, "api").unwrap();
+    let counter = provider
+        .get_export(&mut store, Some(&api), "counter")
+        .unwrap();
+    let counter = provider.get_resource(&mut store, &counter).unwrap();
+    let ty = linker.substituted_component_type(&component)?;
+    match ty.get_import(&engine, "api") {
+        Some(ComponentItem::ComponentInstance(api)) => match api.get_export(&engine, "counter") {
+            Some(ComponentItem::Resource(ty)) => assert_eq!(ty, counter),
+            other => panic!("unexpected export {other:?}"),
+        },
+        other => panic!("unexpected import {other:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn instance_from() -> Result<()> {
+    let engine = Engine::default();
+    let provider = Component::new(&engine, COUNTER_PROVIDER)?;
+    let consumer = Component::new(&engine, counter_consumer("u32"))?;
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let provider = linker.instantiate(&mut store, &provider)?;
+    let api = provider.get_export(&mut store, None, "api").unwrap();
+    linker.instance_from(&mut store, "api", &provider, &api)?;
+
+    let consumer = linker.instantiate(&mut store, &consumer)?;
+    let run = consumer.get_typed_func::<(u32,), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call_and_post_return(&mut store, (3,))?, (3,));
+    assert_eq!(run.call_and_post_return(&mut store, (4,))?, (4,));
+
+    let drops = provider.get_typed_func::<(), (u32,)>(&mut store, "drops")?;
+    assert_eq!(drops.call_and_post_return(&mut store, ())?, (2,));
+
+    // Only instances can be used with `instance_from`.
+    let drops = provider.get_export(&mut store, None, "drops").unwrap();
+    assert!(linker
+        .instance_from(&mut store, "drops", &provider, &drops)
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn instance_from_type_mismatch() -> Result<()> {
+    let engine = Engine::default();
+    let provider = Component::new(&engine, COUNTER_PROVIDER)?;
+    let consumer = Component::new(&engine, counter_consumer("s32"))?;
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let provider = linker.instantiate(&mut store, &provider)?;
+    let api = provider.get_export(&mut store, None, "api").unwrap();
+    linker.instance_from(&mut store, "api", &provider, &api)?;
+
+    let err = linker
+        .instantiate(&mut store, &consumer)
+        .err()
+        .expect("instantiation should fail");
+    assert!(
+        format!("{err:?}").contains("type mismatch with results"),
+        "{err:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn virtualize() -> Result<()> {
+    let engine = Engine::default();
+
+    // Wraps the host's `a:b/c` and re-exports it at a different version.
+    let virt = Component::new(
+        &engine,
+        r#"
+        (component
+            (import "a:b/c@0.2.1" (instance $c
+                (export "get" (func (result u32)))
+            ))
+            (core func $get (canon lower (func $c "get")))
+            (core module $m
+                (import "" "get" (func $get (result i32)))
+                (func (export "get") (result i32)
+                    (i32.add (call $get) (i32.const 1)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance (export "get" (func $get))))
+            ))
+            (func $get2 (result u32) (canon lift (core func $i "get")))
+            (instance (export "a:b/c@0.2.0") (export "get" (func $get2)))
+        )
+        "#,
+    )?;
+    let consumer = Component::new(
+        &engine,
+        r#"
+        (component
+            (import "a:b/c@0.2.1" (instance $c
+                (export "get" (func (result u32)))
+            ))
+            (import "a:b/d@0.2.0" (instance $d
+                (export "get" (func (result u32)))
+            ))
+            (core func $c (canon lower (func $c "get")))
+            (core func $d (canon lower (func $d "get")))
+            (core module $m
+                (import "" "c" (func $c (result i32)))
+                (import "" "d" (func $d (result i32)))
+                (func (export "run") (result i32)
+                    (i32.add (i32.mul (call $c) (i32.const 100)) (call $d)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "c" (func $c))
+                    (export "d" (func $d))
+                ))
+            ))
+            (func (export "run") (result u32) (canon lift (core func $i "run")))
+        )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    linker
+        .instance("a:b/c@0.2.1")?
+        .func_wrap("get", |_, ()| Ok((1u32,)))?;
+    linker
+        .instance("a:b/d@0.2.1")?
+        .func_wrap("get", |_, ()| Ok((10u32,)))?;
+
+    let virt = linker.instantiate(&mut store, &virt)?;
+    assert!(linker.virtualize(&mut store, "a:b/e", &virt).is_err());
+    assert!(linker.virtualize(&mut store, "a:b/c", &virt).is_ok());
+
+    // The consumer's import of `a:b/c` resolves to the virtualized version
+    // while `a:b/d` is still provided by the host.
+    let consumer = linker.instantiate(&mut store, &consumer)?;
+    let run = consumer.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call_and_post_return(&mut store, ())?, (210,));
+
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn virtualize_package_partially() -> Result<()> {
+    let engine = Engine::default();
+
+    // Exports only `get` of `a:b/c` and nothing else of the `a:b` package.
+    let virt = Component::new(
+        &engine,
+        r#"
+        (component
+            (core module $m
+                (func (export "get") (result i32) i32.const 2)
+            )
+            (core instance $i (instantiate $m))
+            (func $get (result u32) (canon lift (core func $i "get")))
+            (instance (export "a:b/c@0.2.0") (export "get" (func $get)))
+        )
+        "#,
+    )?;
+    let consumer = Component::new(
+        &engine,
+        r#"
+        (component
+            (import "a:b/c@0.2.0" (instance $c
+                (export "get" (func (result u32)))
+                (export "other" (func (result u32)))
+            ))
+            (import "a:b/d@0.2.0" (instance $d
+                (export "get" (func (result u32)))
+            ))
+            (core func $c (canon lower (func $c "get")))
+            (core func $other (canon lower (func $c "other")))
+            (core func $d (canon lower (func $d "get")))
+            (core module $m
+                (import "" "c" (func $c (result i32)))
+                (import "" "other" (func $other (result i32)))
+                (import "" "d" (func $d (result i32)))
+                (func (export "run") (result i32)
+                    (i32.add
+                        (i32.add
+                            (i32.mul (call $c) (i32.const 100))
+                            (i32.mul (call $other) (i32.const 10)))
+                        (call $d)))
+            )
+            (core instance $i (instantiate $m
+                (with "" (instance
+                    (export "c" (func $c))
+                    (export "other" (func $other))
+                    (export "d" (func $d))
+                ))
+            ))
+            (func (export "run") (result u32) (canon lift (core func $i "run")))
+        )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let mut c = linker.instance("a:b/c@0.2.0")?;
+    c.func_wrap("get", |_, ()| Ok((1u32,)))?;
+    c.func_wrap("other", |_, ()| Ok((3u32,)))?;
+    linker
+        .instance("a:b/d@0.2.0")?
+        .func_wrap("get", |_, ()| Ok((4u32,)))?;
+
+    let virt = linker.instantiate(&mut store, &virt)?;
+    linker.virtualize(&mut store, "a:b", &virt)?;
+
+    // Only `a:b/c#get` is virtualized, while `a:b/c#other` and all of `a:b/d`
+    // are still provided by the host.
+    let consumer = linker.instantiate(&mut store, &consumer)?;
+    let run = consumer.get_typed_func::<(), (u32,)>(&mut store, "run")?;
+    assert_eq!(run.call_and_post_return(&mut store, ())?, (234,));
+
+    Ok(())
+}
@@ -1532,3 +1532,104 @@ fn resource_any_to_typed_handles_borrow() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn resource_funcs_dynamic_calls() -> Result<()> {
+    let engine = super::engine();
+    let c = Component::new(
+        &engine,
+        r#"
+            (component
+                (component $c
+                    (type $counter' (resource (rep i32)))
+                    (export $counter "counter" (type $counter'))
+                    (core func $new (canon resource.new $counter))
+
+                    (core module $m
+                        (import "" "new" (func $new (param i32) (result i32)))
+                        (func (export "ctor") (param i32) (result i32)
+                            (call $new (local.get 0)))
+                        ;; Borrows of a component's own resources are passed as
+                        ;; their representation.
+                        (func (export "add") (param i32 i32) (result i32)
+                            (i32.add (local.get 0) (local.get 1)))
+                        (func (export "zero") (result i32)
+                            (call $new (i32.const 0)))
+                    )
+                    (core instance $i (instantiate $m
+                        (with "" (instance
+                            (export "new" (func $new))
+                        ))
+                    ))
+                    (func (export "[constructor]counter") (param "v" u32) (result (own $counter))
+                        (canon lift (core func $i "ctor")))
+                    (func (export "[method]counter.add")
+                        (param "self" (borrow $counter)) (param "n" u32) (result u32)
+                        (canon lift (core func $i "add")))
+                    (func (export "[static]counter.zero") (result (own $counter))
+                        (canon lift (core func $i "zero")))
+                )
+                (instance $i (instantiate $c))
+                (export "api" (instance $i))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let i = Linker::new(&engine).instantiate(&mut store, &c)?;
+    let api = i.get_export(&mut store, None, "api").unwrap();
+    let counter = i.get_export(&mut store, Some(&api), "counter").unwrap();
+    let ty = i.get_resource(&mut store, &counter).unwrap();
+
+    let funcs = i.get_resource_funcs(&mut store, ty).unwrap();
+    assert_eq!(funcs.ty(), ty);
+    assert_eq!(funcs.name(), "counter");
+    assert!(funcs.constructor().is_some());
+    assert_eq!(
+        funcs.methods().map(|(name, _)| name).collect::<Vec<_>>(),
+        ["add"]
+    );
+    assert_eq!(
+        funcs
+            .static_funcs()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        ["zero"]
+    );
+    let add = funcs.method("add").unwrap();
+    assert_eq!(add.params(&store).len(), 2);
+
+    let r = funcs.construct(&mut store, &[Val::U32(40)])?;
+    assert!(r.owned());
+    assert_eq!(
+        funcs.call_method(&mut store, &r, "add", &[Val::U32(2)])?,
+        [Val::U32(42)]
+    );
+    assert!(funcs.call_method(&mut store, &r, "sub", &[]).is_err());
+
+    // The type of a resource returned from any function finds its functions.
+    let zero = match funcs.call_static(&mut store, "zero", &[])?.as_slice() {
+        [Val::Resource(zero)] => *zero,
+        _ => unreachable!(),
+    };
+    let funcs = i.get_resource_funcs(&mut store, zero.ty()).unwrap();
+    assert_eq!(
+        funcs.call_method(&mut store, &zero, "add", &[Val::U32(1)])?,
+        [Val::U32(1)]
+    );
+
+    let host = ResourceAny::try_from_resource(Resource::<u32>::new_own(0), &mut store)?;
+    let err = funcs
+        .call_method(&mut store, &host, "add", &[Val::U32(1)])
+        .unwrap_err();
+    assert!(err.to_string().contains("not of type"), "{err:?}");
+    assert!(i
+        .get_resource_funcs(&mut store, ResourceType::host::<u32>())
+        .is_none());
+
+    r.resource_drop(&mut store)?;
+    zero.resource_drop(&mut store)?;
+    host.resource_drop(&mut store)?;
+
+    Ok(())
+}
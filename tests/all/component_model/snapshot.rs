@@ -0,0 +1,305 @@
+#![cfg(not(miri))]
+
+use anyhow::Result;
+use component_test_util::TypedFuncExt;
+use std::any::Any;
+use wasmtime::component::*;
+use wasmtime::Store;
+
+const COUNTER: &str = r#"
+    (component
+        (core module $m
+            (memory 1)
+            (global $calls (mut i32) (i32.const 0))
+            (table 2 funcref)
+            (elem (i32.const 0) func $by_one)
+            (elem declare func $by_ten)
+            (type $step (func (param i32) (result i32)))
+
+            (func $by_one (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add)
+            (func $by_ten (param i32) (result i32)
+                local.get 0
+                i32.const 10
+                i32.add)
+
+            (func (export "bump")
+                global.get $calls
+                i32.const 1
+                i32.add
+                global.set $calls
+                i32.const 100
+                i32.const 100
+                i32.load
+                i32.const 0
+                call_indirect (type $step)
+                i32.store)
+            (func (export "fast")
+                i32.const 0
+                ref.func $by_ten
+                table.set)
+            (func (export "get") (result i32)
+                i32.const 100
+                i32.load
+                global.get $calls
+                i32.const 1000
+                i32.mul
+                i32.add)
+        )
+        (core instance $i (instantiate $m))
+        (func (export "bump") (canon lift (core func $i "bump")))
+        (func (export "fast") (canon lift (core func $i "fast")))
+        (func (export "get") (result u32) (canon lift (core func $i "get")))
+    )
+"#;
+
+#[test]
+fn snapshot_and_restore() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let linker = Linker::new(&engine);
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let bump = instance.get_typed_func::<(), ()>(&mut store, "bump")?;
+    let fast = instance.get_typed_func::<(), ()>(&mut store, "fast")?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    bump.call_and_post_return(&mut store, ())?;
+    fast.call_and_post_return(&mut store, ())?;
+    bump.call_and_post_return(&mut store, ())?;
+    assert_eq!(get.call_and_post_return(&mut store, ())?, (2011,));
+
+    let bytes = instance.snapshot(&mut store, None)?.to_bytes();
+
+    // Restore into a fresh instance in a new store, which should pick up the
+    // memory, global, and table state from the snapshot.
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    instance.restore(&mut store, &InstanceSnapshot::from_bytes(&bytes)?, None)?;
+    let bump = instance.get_typed_func::<(), ()>(&mut store, "bump")?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    assert_eq!(get.call_and_post_return(&mut store, ())?, (2011,));
+    bump.call_and_post_return(&mut store, ())?;
+    assert_eq!(get.call_and_post_return(&mut store, ())?, (3021,));
+
+    assert!(InstanceSnapshot::from_bytes(&bytes[1..]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn restore_checks_component() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let linker = Linker::new(&engine);
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let bump = instance.get_typed_func::<(), ()>(&mut store, "bump")?;
+    bump.call_and_post_return(&mut store, ())?;
+    let snapshot = instance.snapshot(&mut store, None)?;
+
+    // The same component recompiled or deserialized, as in a runtime-only
+    // build, is accepted.
+    let recompiled = Component::new(&engine, COUNTER)?;
+    let deserialized = unsafe { Component::deserialize(&engine, component.serialize()?)? };
+    for component in [recompiled, deserialized] {
+        let mut store = Store::new(&engine, ());
+        let instance = linker.instantiate(&mut store, &component)?;
+        instance.restore(&mut store, &snapshot, None)?;
+        let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+        assert_eq!(get.call_and_post_return(&mut store, ())?, (1001,));
+    }
+
+    // A different component with the same shape is rejected.
+    let other = Component::new(&engine, COUNTER.replace("i32.const 1000", "i32.const 2000"))?;
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &other)?;
+    let err = instance.restore(&mut store, &snapshot, None).unwrap_err();
+    assert!(err.to_string().contains("different component"), "{err:?}");
+
+    Ok(())
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn restore_checks_component_with_libcalls() -> Result<()> {
+    // Without SSE 4.1 `f32.floor` is a libcall whose address is patched into
+    // the code when it's loaded, which mustn't affect whether a snapshot is
+    // accepted.
+    let mut config = component_test_util::config();
+    unsafe {
+        config.cranelift_flag_set("has_sse41", "false");
+    }
+    let engine = wasmtime::Engine::new(&config)?;
+    let wat = r#"
+        (component
+            (core module $m
+                (global $x (mut f32) (f32.const 2.5))
+                (func (export "floor")
+                    global.get $x
+                    f32.floor
+                    f32.const 10
+                    f32.add
+                    global.set $x)
+                (func (export "get") (result f32)
+                    global.get $x)
+            )
+            (core instance $i (instantiate $m))
+            (func (export "floor") (canon lift (core func $i "floor")))
+            (func (export "get") (result f32) (canon lift (core func $i "get")))
+        )
+    "#;
+    let component = Component::new(&engine, wat)?;
+    let linker = Linker::new(&engine);
+
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &component)?;
+    let floor = instance.get_typed_func::<(), ()>(&mut store, "floor")?;
+    floor.call_and_post_return(&mut store, ())?;
+    let snapshot = instance.snapshot(&mut store, None)?;
+
+    let deserialized = unsafe { Component::deserialize(&engine, component.serialize()?)? };
+    let mut store = Store::new(&engine, ());
+    let instance = linker.instantiate(&mut store, &deserialized)?;
+    instance.restore(&mut store, &snapshot, None)?;
+    let get = instance.get_typed_func::<(), (f32,)>(&mut store, "get")?;
+    assert_eq!(get.call_and_post_return(&mut store, ())?, (12.0,));
+    Ok(())
+}
+
+#[test]
+fn snapshot_requires_quiescent_instance() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(&engine, COUNTER)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Linker::new(&engine).instantiate(&mut store, &component)?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+
+    get.call(&mut store, ())?;
+    let err = instance.snapshot(&mut store, None).unwrap_err();
+    assert!(err.to_string().contains("post_return"), "{err:?}");
+    get.post_return(&mut store)?;
+    instance.snapshot(&mut store, None)?;
+
+    Ok(())
+}
+
+struct Counter(u32);
+
+struct Host {
+    table: ResourceTable,
+}
+
+struct Serializer;
+
+impl HostResourceSerializer<Host> for Serializer {
+    fn table<'a>(&mut self, data: &'a mut Host) -> &'a mut ResourceTable {
+        &mut data.table
+    }
+
+    fn serialize(&mut self, resource: &(dyn Any + Send)) -> Result<Vec<u8>> {
+        let counter = resource.downcast_ref::<Counter>().unwrap();
+        Ok(counter.0.to_le_bytes().to_vec())
+    }
+
+    fn deserialize(&mut self, bytes: &[u8]) -> Result<Box<dyn Any + Send>> {
+        Ok(Box::new(Counter(u32::from_le_bytes(bytes.try_into()?))))
+    }
+}
+
+#[test]
+fn snapshot_host_resources() -> Result<()> {
+    let engine = super::engine();
+    let component = Component::new(
+        &engine,
+        r#"
+            (component
+                (import "t" (type $t (sub resource)))
+                (import "make" (func $make (param "v" u32) (result (own $t))))
+                (import "value" (func $value (param "r" (borrow $t)) (result u32)))
+                (core func $make (canon lower (func $make)))
+                (core func $value (canon lower (func $value)))
+
+                (core module $m
+                    (import "" "make" (func $make (param i32) (result i32)))
+                    (import "" "value" (func $value (param i32) (result i32)))
+                    (global $h (mut i32) (i32.const 0))
+                    (func (export "init") (param i32)
+                        local.get 0
+                        call $make
+                        global.set $h)
+                    (func (export "get") (result i32)
+                        global.get $h
+                        call $value)
+                )
+                (core instance $i (instantiate $m
+                    (with "" (instance
+                        (export "make" (func $make))
+                        (export "value" (func $value))
+                    ))
+                ))
+                (func (export "init") (param "v" u32) (canon lift (core func $i "init")))
+                (func (export "get") (result u32) (canon lift (core func $i "get")))
+            )
+        "#,
+    )?;
+
+    let mut linker = Linker::<Host>::new(&engine);
+    linker
+        .root()
+        .resource("t", ResourceType::host::<Counter>(), |mut store, rep| {
+            store
+                .data_mut()
+                .table
+                .delete(Resource::<Counter>::new_own(rep))?;
+            Ok(())
+        })?;
+    linker.root().func_wrap("make", |mut store, (v,): (u32,)| {
+        Ok((store.data_mut().table.push(Counter(v))?,))
+    })?;
+    linker
+        .root()
+        .func_wrap("value", |store, (r,): (Resource<Counter>,)| {
+            Ok((store.data().table.get(&r)?.0,))
+        })?;
+
+    let mut store = Store::new(
+        &engine,
+        Host {
+            table: ResourceTable::new(),
+        },
+    );
+    // Push an unrelated resource first so the instance's resource isn't at
+    // index zero.
+    store.data_mut().table.push(Counter(1))?;
+    let instance = linker.instantiate(&mut store, &component)?;
+    let init = instance.get_typed_func::<(u32,), ()>(&mut store, "init")?;
+    init.call_and_post_return(&mut store, (42,))?;
+    let snapshot = instance.snapshot(&mut store, Some(&mut Serializer))?;
+
+    let mut store = Store::new(
+        &engine,
+        Host {
+            table: ResourceTable::new(),
+        },
+    );
+    let instance = linker.instantiate(&mut store, &component)?;
+    let err = instance.restore(&mut store, &snapshot, None).unwrap_err();
+    assert!(err.to_string().contains("host resources"), "{err:?}");
+
+    let mut store = Store::new(
+        &engine,
+        Host {
+            table: ResourceTable::new(),
+        },
+    );
+    let instance = linker.instantiate(&mut store, &component)?;
+    instance.restore(&mut store, &snapshot, Some(&mut Serializer))?;
+    let get = instance.get_typed_func::<(), (u32,)>(&mut store, "get")?;
+    assert_eq!(get.call_and_post_return(&mut store, ())?, (42,));
+
+    Ok(())
+}
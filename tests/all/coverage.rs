@@ -0,0 +1,88 @@
+use wasmtime::*;
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.coverage(true);
+    config.wasm_backtrace_details(WasmBacktraceDetails::Enable);
+    config
+}
+
+fn lcov(coverage: &Coverage) -> Result<String> {
+    let mut lcov = Vec::new();
+    coverage.write_lcov(&mut lcov)?;
+    Ok(String::from_utf8(lcov)?)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn lines_from_dwarf() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    let module = Module::from_file(&engine, "tests/all/debug/testsuite/fib-wasm.wasm")?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let fib = instance.get_typed_func::<i32, i32>(&mut store, "fib")?;
+    assert_eq!(fib.call(&mut store, 3)?, 3);
+
+    let mut coverage = Coverage::new();
+    coverage.collect(&mut store);
+    let lcov = lcov(&coverage)?;
+    assert!(lcov.contains("SF:./fib-wasm.c\n"), "{lcov}");
+    assert!(lcov.contains("FN:8,fib\nFNDA:1,fib\n"), "{lcov}");
+    // The loop condition runs once more than the loop body.
+    assert!(lcov.contains("DA:10,4\nDA:11,3\n"), "{lcov}");
+    assert!(lcov.contains("DA:15,1\n"), "{lcov}");
+
+    // Collecting again resets the counters of the store's instances.
+    assert_eq!(fib.call(&mut store, 0)?, 1);
+    coverage.collect(&mut store);
+    let lcov = self::lcov(&coverage)?;
+    assert!(lcov.contains("FNDA:2,fib\n"), "{lcov}");
+    assert!(lcov.contains("DA:10,5\nDA:11,3\n"), "{lcov}");
+
+    let mut json = Vec::new();
+    coverage.write_llvm_json(&mut json)?;
+    let json: serde_json::Value = serde_json::from_slice(&json)?;
+    let data = &json["data"][0];
+    assert_eq!(data["files"][0]["filename"], "./fib-wasm.c");
+    assert_eq!(data["functions"][0]["name"], "fib");
+    assert_eq!(data["functions"][0]["count"], 2);
+    assert_eq!(data["totals"]["functions"]["covered"], 1);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn no_dwarf() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "f") (param i32) (result i32)
+                    local.get 0
+                    if (result i32)
+                        i32.const 1
+                    else
+                        i32.const 2
+                    end))
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+    assert_eq!(f.call(&mut store, 1)?, 1);
+
+    // Without debug information nothing can be mapped to source lines.
+    let mut coverage = Coverage::new();
+    coverage.collect(&mut store);
+    assert_eq!(lcov(&coverage)?, "");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn winch_is_unsupported() {
+    let mut config = config();
+    config.strategy(Strategy::Winch);
+    assert!(Engine::new(&config).is_err());
+}
@@ -0,0 +1,177 @@
+use wasmtime::*;
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.deterministic(true).consume_fuel(true);
+    config
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn canonicalizes_nans() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "div") (param f32 f32) (result i32)
+                    (i32.reinterpret_f32 (f32.div (local.get 0) (local.get 1))))
+                (func (export "sqrt") (param f64) (result i64)
+                    (i64.reinterpret_f64 (f64.sqrt (local.get 0))))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    store.set_fuel(u64::MAX)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let div = instance.get_typed_func::<(f32, f32), i32>(&mut store, "div")?;
+    let sqrt = instance.get_typed_func::<f64, i64>(&mut store, "sqrt")?;
+
+    assert_eq!(div.call(&mut store, (0.0, 0.0))? as u32, 0x7fc00000);
+    assert_eq!(sqrt.call(&mut store, -1.0)? as u64, 0x7ff8000000000000);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn threads_are_disabled() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    assert!(Module::new(&engine, "(module (memory 1 1 shared))").is_err());
+
+    let ty = MemoryType::shared(1, 1);
+    let err = SharedMemory::new(&engine, ty).unwrap_err();
+    assert!(
+        err.to_string().contains("deterministic mode"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn conflicting_settings_are_rejected() -> Result<()> {
+    let check = |configure: fn(&mut Config), expected: &str| {
+        let mut config = config();
+        configure(&mut config);
+        let err = Engine::new(&config).unwrap_err();
+        assert!(
+            format!("{err:?}").contains(expected),
+            "expected {expected:?} in {err:?}"
+        );
+    };
+
+    check(
+        |c| {
+            c.wasm_threads(true);
+        },
+        "threads proposal",
+    );
+    check(
+        |c| {
+            c.epoch_interruption(true);
+        },
+        "use fuel instead",
+    );
+    check(
+        |c| {
+            c.relaxed_simd_deterministic(false);
+        },
+        "relaxed simd",
+    );
+    check(
+        |c| {
+            c.cranelift_nan_canonicalization(false);
+        },
+        "NaN canonicalization",
+    );
+    if cfg!(target_arch = "x86_64") {
+        check(
+            |c| {
+                c.strategy(Strategy::Winch);
+            },
+            "Winch",
+        );
+    }
+
+    // Fuel is the deterministic way of interrupting execution, and is
+    // required.
+    check(
+        |c| {
+            c.consume_fuel(false);
+        },
+        "requires fuel",
+    );
+    Engine::new(&config())?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn fuel_bounds_execution() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "spin")
+                    (loop $l (br $l)))
+            )
+        "#,
+    )?;
+    let run = || -> Result<u64> {
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(10_000)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let spin = instance.get_typed_func::<(), ()>(&mut store, "spin")?;
+        let err = spin.call(&mut store, ()).unwrap_err();
+        assert_eq!(err.downcast::<Trap>()?, Trap::OutOfFuel);
+        Ok(store.get_fuel()?)
+    };
+    assert_eq!(run()?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn wasi_is_deterministic() -> Result<()> {
+    let engine = Engine::new(&config())?;
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::preview1::add_to_linker_sync(&mut linker, |t| t)?;
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "wasi_snapshot_preview1" "random_get"
+                    (func $random_get (param i32 i32) (result i32)))
+                (import "wasi_snapshot_preview1" "clock_time_get"
+                    (func $clock_time_get (param i32 i64 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "run")
+                    (drop (call $random_get (i32.const 0) (i32.const 32)))
+                    (drop (call $clock_time_get (i32.const 0) (i64.const 1) (i32.const 32)))
+                    (drop (call $clock_time_get (i32.const 1) (i64.const 1) (i32.const 40)))
+                )
+            )
+        "#,
+    )?;
+
+    let run = |seed| -> Result<Vec<u8>> {
+        let mut wasi = wasmtime_wasi::WasiCtxBuilder::new();
+        wasi.deterministic(seed);
+        let mut store = Store::new(&engine, wasi.build_p1());
+        store.set_fuel(u64::MAX)?;
+        let instance = linker.instantiate(&mut store, &module)?;
+        let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+        run.call(&mut store, ())?;
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+        Ok(memory.data(&store)[..48].to_vec())
+    };
+
+    let a = run(1)?;
+    assert_eq!(a, run(1)?);
+    assert_ne!(a[..32], run(2)?[..32]);
+    // Both the realtime and monotonic clocks are stopped at zero.
+    assert!(a[32..].iter().all(|b| *b == 0));
+    Ok(())
+}
@@ -8,9 +8,11 @@ mod cli_tests;
 mod code_too_large;
 mod component_model;
 mod coredump;
+mod coverage;
 mod custom_code_memory;
 mod debug;
 mod defaults;
+mod deterministic;
 mod epoch_interruption;
 mod externals;
 mod fuel;
@@ -3,7 +3,7 @@ use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
 use std::sync::Arc;
 use target_lexicon::Triple;
 use wasmtime::*;
-use wasmtime_environ::TripleExt;
+use wasmtime_environ::{wasmparser, TripleExt};
 use wasmtime_test_macros::wasmtime_test;
 
 #[test]
@@ -649,3 +649,129 @@ fn deserialize_raw_fails_for_native() {
         );
     }
 }
+
+/// A function with a loop around an `if`, and a profile for it in which the
+/// `else` arm never executed while everything else executed many times.
+fn profiled_module() -> Result<(Vec<u8>, ExecutionProfile)> {
+    let wasm = wat::parse_str(
+        r#"
+        (module
+            (func (export "f") (param i32) (result i32)
+                (local i32)
+                loop
+                    local.get 0
+                    i32.const 7
+                    i32.rem_u
+                    if
+                        local.get 1
+                        i32.const 1
+                        i32.add
+                        local.set 1
+                    else
+                        local.get 1
+                        i32.const 3
+                        i32.mul
+                        local.set 1
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.tee 0
+                    br_if 0
+                end
+                local.get 1
+            )
+        )
+        "#,
+    )?;
+    let profile = profile_without_else(&wasm)?;
+    Ok((wasm, profile))
+}
+
+/// Returns a profile of all functions in `wasm`, which may also be a
+/// component, in which the `else` arms of `if`s never executed.
+fn profile_without_else(wasm: &[u8]) -> Result<ExecutionProfile> {
+    let mut profile = ExecutionProfile::new();
+    let mut in_else = false;
+    for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+        let wasmparser::Payload::CodeSectionEntry(body) = payload? else {
+            continue;
+        };
+        let mut reader = body.get_operators_reader()?;
+        while !reader.eof() {
+            let (op, offset) = reader.read_with_offset()?;
+            let count = if in_else { 0 } else { 1000 };
+            profile.add_count(u32::try_from(offset)?, count);
+            match op {
+                wasmparser::Operator::Else => in_else = true,
+                wasmparser::Operator::End if in_else => in_else = false,
+                _ => {}
+            }
+        }
+    }
+    Ok(profile)
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn execution_profile_guides_layout() -> Result<()> {
+    let (wasm, profile) = profiled_module()?;
+    let profile = Arc::new(profile);
+
+    let engine = Engine::default();
+    let unprofiled = CodeBuilder::new(&engine)
+        .wasm_binary(&wasm, None)?
+        .compile_module_serialized()?;
+    let profiled = CodeBuilder::new(&engine)
+        .wasm_binary(&wasm, None)?
+        .execution_profile(profile.clone())
+        .compile_module_serialized()?;
+
+    // The profile changes the generated code, but not its behavior.
+    assert_ne!(unprofiled, profiled);
+    for bytes in [unprofiled, profiled] {
+        let module = unsafe { Module::deserialize(&engine, bytes)? };
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let f = instance.get_typed_func::<i32, i32>(&mut store, "f")?;
+        assert_eq!(f.call(&mut store, 20)?, 78);
+    }
+
+    // A profile configured on the engine is used when compiling a module
+    // without a profile of its own.
+    let mut config = Config::new();
+    config.execution_profile(Some(profile));
+    let profiled_engine = Engine::new(&config)?;
+    assert_ne!(
+        engine.precompile_module(&wasm)?,
+        profiled_engine.precompile_module(&wasm)?
+    );
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "component-model")]
+#[cfg_attr(miri, ignore)]
+fn execution_profile_not_applied_to_components() -> Result<()> {
+    let (module, _) = profiled_module()?;
+    let mut component = wasm_encoder::Component::new();
+    component.section(&wasm_encoder::RawSection {
+        id: wasm_encoder::ComponentSectionId::CoreModule.into(),
+        data: &module,
+    });
+    let component = component.finish();
+    let profile = Arc::new(profile_without_else(&component)?);
+
+    // Profiles are keyed by offsets within a single module, so they don't
+    // apply to the core modules within components.
+    let engine = Engine::default();
+    let mut config = Config::new();
+    config.execution_profile(Some(profile));
+    let profiled_engine = Engine::new(&config)?;
+    assert_eq!(
+        engine.precompile_component(&component)?,
+        profiled_engine.precompile_component(&component)?
+    );
+    Ok(())
+}
@@ -146,6 +146,39 @@ fn test_trap_trace() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_trap_trace_inlined() -> Result<()> {
+    let mut config = Config::new();
+    config.cranelift_opt_level(OptLevel::Speed);
+    config.cranelift_inlining(true);
+    let engine = Engine::new(&config)?;
+    let mut store = Store::new(&engine, ());
+    let wat = r#"
+        (module $hello_mod
+            (func (export "run") (call $hello))
+            (func $hello (unreachable))
+        )
+    "#;
+
+    let module = Module::new(&engine, wat)?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run_func = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+
+    let e = run_func.call(&mut store, ()).unwrap_err();
+
+    // `hello` is inlined into `run`, so there's no frame for it, but the
+    // trapping instruction is still located within `hello`.
+    let trace = e.downcast_ref::<WasmBacktrace>().unwrap().frames();
+    assert_eq!(trace.len(), 1);
+    assert_eq!(trace[0].module().name().unwrap(), "hello_mod");
+    assert_eq!(trace[0].func_index(), 0);
+    assert_eq!(trace[0].func_name(), None);
+    assert_eq!(trace[0].module_offset(), Some(0x26));
+    assert_eq!(e.downcast::<Trap>()?, Trap::UnreachableCodeReached);
+
+    Ok(())
+}
+
 #[test]
 fn test_trap_through_host() -> Result<()> {
     let wat = r#"
@@ -56,7 +56,7 @@
 ;; @004c                               v18 = iadd v10, v17  ; v17 = 4
 ;; @004c                               v19 = load.i32 little heap v18
 ;; @0051                               v21 = iconst.i64 0x0010_0003
-;; @0051                               v22 = uadd_overflow_trap v6, v21, heap_oob  ; v21 = 0x0010_0003
+;; @0051                               v22 = iadd v6, v21  ; v21 = 0x0010_0003
 ;; @0051                               v24 = icmp ugt v22, v7
 ;; @0051                               trapnz v24, heap_oob
 ;; @0051                               v27 = iconst.i64 0x000f_ffff
@@ -89,7 +89,7 @@
 ;; @0064                               v17 = iadd v10, v16  ; v16 = 4
 ;; @0064                               store little heap v4, v17
 ;; @006b                               v19 = iconst.i64 0x0010_0003
-;; @006b                               v20 = uadd_overflow_trap v6, v19, heap_oob  ; v19 = 0x0010_0003
+;; @006b                               v20 = iadd v6, v19  ; v19 = 0x0010_0003
 ;; @006b                               v22 = icmp ugt v20, v7
 ;; @006b                               trapnz v22, heap_oob
 ;; @006b                               v25 = iconst.i64 0x000f_ffff
@@ -54,7 +54,7 @@
 ;; @004c                               v22 = select_spectre_guard v8, v11, v20  ; v11 = 0
 ;; @004c                               v23 = load.i32 little heap v22
 ;; @0051                               v25 = iconst.i64 0x0010_0003
-;; @0051                               v26 = uadd_overflow_trap v6, v25, heap_oob  ; v25 = 0x0010_0003
+;; @0051                               v26 = iadd v6, v25  ; v25 = 0x0010_0003
 ;; @0051                               v28 = icmp ugt v26, v7
 ;; @0051                               v31 = iconst.i64 0x000f_ffff
 ;; @0051                               v32 = iadd v10, v31  ; v31 = 0x000f_ffff
@@ -89,7 +89,7 @@
 ;; @0064                               v21 = select_spectre_guard v8, v11, v19  ; v11 = 0
 ;; @0064                               store little heap v4, v21
 ;; @006b                               v23 = iconst.i64 0x0010_0003
-;; @006b                               v24 = uadd_overflow_trap v6, v23, heap_oob  ; v23 = 0x0010_0003
+;; @006b                               v24 = iadd v6, v23  ; v23 = 0x0010_0003
 ;; @006b                               v26 = icmp ugt v24, v7
 ;; @006b                               v29 = iconst.i64 0x000f_ffff
 ;; @006b                               v30 = iadd v10, v29  ; v29 = 0x000f_ffff
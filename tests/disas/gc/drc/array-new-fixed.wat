@@ -22,7 +22,7 @@
 ;;                                     v43 = iconst.i64 0
 ;; @0025                               trapnz v43, user18  ; v43 = 0
 ;; @0025                               v6 = iconst.i32 24
-;; @0025                               v12 = uadd_overflow_trap v6, v6, user18  ; v6 = 24, v6 = 24
+;; @0025                               v12 = iadd v6, v6  ; v6 = 24, v6 = 24
 ;; @0025                               v14 = iconst.i32 -1476395008
 ;; @0025                               v15 = iconst.i32 0
 ;; @0025                               v16 = iconst.i32 8
@@ -20,9 +20,9 @@
 ;; @0024                               trapz v2, user16
 ;; @0024                               v9 = uextend.i64 v2
 ;; @0024                               v10 = iconst.i64 16
-;; @0024                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 16
+;; @0024                               v11 = iadd v9, v10  ; v10 = 16
 ;; @0024                               v12 = iconst.i64 4
-;; @0024                               v13 = uadd_overflow_trap v11, v12, user1  ; v12 = 4
+;; @0024                               v13 = iadd v11, v12  ; v12 = 4
 ;; @0024                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0024                               v14 = icmp ule v13, v8
 ;; @0024                               trapz v14, user1
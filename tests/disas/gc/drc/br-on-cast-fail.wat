@@ -47,8 +47,8 @@
 ;;                                 block4:
 ;; @002e                               v17 = uextend.i64 v2
 ;; @002e                               v18 = iconst.i64 4
-;; @002e                               v19 = uadd_overflow_trap v17, v18, user1  ; v18 = 4
-;; @002e                               v21 = uadd_overflow_trap v19, v18, user1  ; v18 = 4
+;; @002e                               v19 = iadd v17, v18  ; v18 = 4
+;; @002e                               v21 = iadd v19, v18  ; v18 = 4
 ;; @002e                               v16 = load.i64 notrap aligned readonly can_move v0+48
 ;; @002e                               v22 = icmp ule v21, v16
 ;; @002e                               trapz v22, user1
@@ -46,8 +46,8 @@
 ;;                                 block4:
 ;; @0034                               v19 = uextend.i64 v5
 ;; @0034                               v20 = iconst.i64 8
-;; @0034                               v21 = uadd_overflow_trap v19, v20, user1  ; v20 = 8
-;; @0034                               v23 = uadd_overflow_trap v21, v20, user1  ; v20 = 8
+;; @0034                               v21 = iadd v19, v20  ; v20 = 8
+;; @0034                               v23 = iadd v21, v20  ; v20 = 8
 ;; @0034                               v18 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0034                               v24 = icmp ule v23, v18
 ;; @0034                               trapz v24, user1
@@ -100,8 +100,8 @@
 ;;                                 block2:
 ;; @003b                               v14 = uextend.i64 v2
 ;; @003b                               v43 = iconst.i64 8
-;; @003b                               v16 = uadd_overflow_trap v14, v43, user1  ; v43 = 8
-;; @003b                               v18 = uadd_overflow_trap v16, v43, user1  ; v43 = 8
+;; @003b                               v16 = iadd v14, v43  ; v43 = 8
+;; @003b                               v18 = iadd v16, v43  ; v43 = 8
 ;; @003b                               v41 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003b                               v19 = icmp ule v18, v41
 ;; @003b                               trapz v19, user1
@@ -127,8 +127,8 @@
 ;;                                 block4:
 ;; @003b                               v42 = uextend.i64 v5
 ;;                                     v77 = iconst.i64 8
-;; @003b                               v44 = uadd_overflow_trap v42, v77, user1  ; v77 = 8
-;; @003b                               v46 = uadd_overflow_trap v44, v77, user1  ; v77 = 8
+;; @003b                               v44 = iadd v42, v77  ; v77 = 8
+;; @003b                               v46 = iadd v44, v77  ; v77 = 8
 ;;                                     v78 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003b                               v47 = icmp ule v46, v78
 ;; @003b                               trapz v47, user1
@@ -34,9 +34,9 @@
 ;; @0033                               trapz v2, user16
 ;; @0033                               v9 = uextend.i64 v2
 ;; @0033                               v10 = iconst.i64 16
-;; @0033                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 16
+;; @0033                               v11 = iadd v9, v10  ; v10 = 16
 ;;                                     v17 = iconst.i64 32
-;; @0033                               v13 = uadd_overflow_trap v9, v17, user1  ; v17 = 32
+;; @0033                               v13 = iadd v9, v17  ; v17 = 32
 ;; @0033                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0033                               v14 = icmp ule v13, v8
 ;; @0033                               trapz v14, user1
@@ -60,9 +60,9 @@
 ;; @003c                               trapz v2, user16
 ;; @003c                               v9 = uextend.i64 v2
 ;; @003c                               v10 = iconst.i64 20
-;; @003c                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 20
+;; @003c                               v11 = iadd v9, v10  ; v10 = 20
 ;;                                     v18 = iconst.i64 32
-;; @003c                               v13 = uadd_overflow_trap v9, v18, user1  ; v18 = 32
+;; @003c                               v13 = iadd v9, v18  ; v18 = 32
 ;; @003c                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003c                               v14 = icmp ule v13, v8
 ;; @003c                               trapz v14, user1
@@ -87,9 +87,9 @@
 ;; @0045                               trapz v2, user16
 ;; @0045                               v9 = uextend.i64 v2
 ;; @0045                               v10 = iconst.i64 20
-;; @0045                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 20
+;; @0045                               v11 = iadd v9, v10  ; v10 = 20
 ;;                                     v18 = iconst.i64 32
-;; @0045                               v13 = uadd_overflow_trap v9, v18, user1  ; v18 = 32
+;; @0045                               v13 = iadd v9, v18  ; v18 = 32
 ;; @0045                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0045                               v14 = icmp ule v13, v8
 ;; @0045                               trapz v14, user1
@@ -117,9 +117,9 @@
 ;; @004e                               trapz v2, user16
 ;; @004e                               v9 = uextend.i64 v2
 ;; @004e                               v10 = iconst.i64 24
-;; @004e                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 24
+;; @004e                               v11 = iadd v9, v10  ; v10 = 24
 ;;                                     v72 = iconst.i64 32
-;; @004e                               v13 = uadd_overflow_trap v9, v72, user1  ; v72 = 32
+;; @004e                               v13 = iadd v9, v72  ; v72 = 32
 ;; @004e                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @004e                               v14 = icmp ule v13, v8
 ;; @004e                               trapz v14, user1
@@ -146,8 +146,8 @@
 ;;                                 block4:
 ;; @004e                               v30 = uextend.i64 v16
 ;; @004e                               v31 = iconst.i64 8
-;; @004e                               v32 = uadd_overflow_trap v30, v31, user1  ; v31 = 8
-;; @004e                               v34 = uadd_overflow_trap v32, v31, user1  ; v31 = 8
+;; @004e                               v32 = iadd v30, v31  ; v31 = 8
+;; @004e                               v34 = iadd v32, v31  ; v31 = 8
 ;; @004e                               v35 = icmp ule v34, v8
 ;; @004e                               trapz v35, user1
 ;; @004e                               v36 = iadd.i64 v6, v32
@@ -43,8 +43,8 @@
 ;;                                 block2:
 ;;                                     v73 = iconst.i64 0
 ;; @0021                               v29 = iconst.i64 8
-;; @0021                               v30 = uadd_overflow_trap v73, v29, user1  ; v73 = 0, v29 = 8
-;; @0021                               v32 = uadd_overflow_trap v30, v29, user1  ; v29 = 8
+;; @0021                               v30 = iadd v73, v29  ; v73 = 0, v29 = 8
+;; @0021                               v32 = iadd v30, v29  ; v29 = 8
 ;; @0021                               v27 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0021                               v33 = icmp ule v32, v27
 ;; @0021                               trapz v33, user1
@@ -50,8 +50,8 @@
 ;;                                 block2:
 ;; @002a                               v28 = uextend.i64 v52
 ;; @002a                               v29 = iconst.i64 8
-;; @002a                               v30 = uadd_overflow_trap v28, v29, user1  ; v29 = 8
-;; @002a                               v32 = uadd_overflow_trap v30, v29, user1  ; v29 = 8
+;; @002a                               v30 = iadd v28, v29  ; v29 = 8
+;; @002a                               v32 = iadd v30, v29  ; v29 = 8
 ;; @002a                               v27 = load.i64 notrap aligned readonly can_move v0+48
 ;; @002a                               v33 = icmp ule v32, v27
 ;; @002a                               trapz v33, user1
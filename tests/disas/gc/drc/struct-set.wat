@@ -30,9 +30,9 @@
 ;; @0034                               trapz v2, user16
 ;; @0034                               v9 = uextend.i64 v2
 ;; @0034                               v10 = iconst.i64 16
-;; @0034                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 16
+;; @0034                               v11 = iadd v9, v10  ; v10 = 16
 ;;                                     v16 = iconst.i64 32
-;; @0034                               v13 = uadd_overflow_trap v9, v16, user1  ; v16 = 32
+;; @0034                               v13 = iadd v9, v16  ; v16 = 32
 ;; @0034                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0034                               v14 = icmp ule v13, v8
 ;; @0034                               trapz v14, user1
@@ -56,9 +56,9 @@
 ;; @003f                               trapz v2, user16
 ;; @003f                               v9 = uextend.i64 v2
 ;; @003f                               v10 = iconst.i64 20
-;; @003f                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 20
+;; @003f                               v11 = iadd v9, v10  ; v10 = 20
 ;;                                     v16 = iconst.i64 32
-;; @003f                               v13 = uadd_overflow_trap v9, v16, user1  ; v16 = 32
+;; @003f                               v13 = iadd v9, v16  ; v16 = 32
 ;; @003f                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003f                               v14 = icmp ule v13, v8
 ;; @003f                               trapz v14, user1
@@ -84,9 +84,9 @@
 ;; @004a                               trapz v2, user16
 ;; @004a                               v9 = uextend.i64 v2
 ;; @004a                               v10 = iconst.i64 24
-;; @004a                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 24
+;; @004a                               v11 = iadd v9, v10  ; v10 = 24
 ;;                                     v82 = iconst.i64 32
-;; @004a                               v13 = uadd_overflow_trap v9, v82, user1  ; v82 = 32
+;; @004a                               v13 = iadd v9, v82  ; v82 = 32
 ;; @004a                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @004a                               v14 = icmp ule v13, v8
 ;; @004a                               trapz v14, user1
@@ -104,8 +104,8 @@
 ;;                                 block2:
 ;; @004a                               v25 = uextend.i64 v3
 ;; @004a                               v54 = iconst.i64 8
-;; @004a                               v27 = uadd_overflow_trap v25, v54, user1  ; v54 = 8
-;; @004a                               v29 = uadd_overflow_trap v27, v54, user1  ; v54 = 8
+;; @004a                               v27 = iadd v25, v54  ; v54 = 8
+;; @004a                               v29 = iadd v27, v54  ; v54 = 8
 ;; @004a                               v30 = icmp ule v29, v8
 ;; @004a                               trapz v30, user1
 ;; @004a                               v31 = iadd.i64 v6, v27
@@ -128,8 +128,8 @@
 ;;                                 block4:
 ;; @004a                               v53 = uextend.i64 v16
 ;;                                     v87 = iconst.i64 8
-;; @004a                               v55 = uadd_overflow_trap v53, v87, user1  ; v87 = 8
-;; @004a                               v57 = uadd_overflow_trap v55, v87, user1  ; v87 = 8
+;; @004a                               v55 = iadd v53, v87  ; v87 = 8
+;; @004a                               v57 = iadd v55, v87  ; v87 = 8
 ;; @004a                               v58 = icmp ule v57, v8
 ;; @004a                               trapz v58, user1
 ;; @004a                               v59 = iadd.i64 v6, v55
@@ -20,9 +20,9 @@
 ;; @0027                               trapz v2, user16
 ;; @0027                               v10 = uextend.i64 v2
 ;; @0027                               v11 = iconst.i64 8
-;; @0027                               v12 = uadd_overflow_trap v10, v11, user1  ; v11 = 8
+;; @0027                               v12 = iadd v10, v11  ; v11 = 8
 ;; @0027                               v13 = iconst.i64 4
-;; @0027                               v14 = uadd_overflow_trap v12, v13, user1  ; v13 = 4
+;; @0027                               v14 = iadd v12, v13  ; v13 = 4
 ;; @0027                               v9 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0027                               v15 = icmp ule v14, v9
 ;; @0027                               trapz v15, user1
@@ -20,9 +20,9 @@
 ;; @0022                               trapz v2, user16
 ;; @0022                               v9 = uextend.i64 v2
 ;; @0022                               v10 = iconst.i64 8
-;; @0022                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 8
+;; @0022                               v11 = iadd v9, v10  ; v10 = 8
 ;; @0022                               v12 = iconst.i64 4
-;; @0022                               v13 = uadd_overflow_trap v11, v12, user1  ; v12 = 4
+;; @0022                               v13 = iadd v11, v12  ; v12 = 4
 ;; @0022                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0022                               v14 = icmp ule v13, v8
 ;; @0022                               trapz v14, user1
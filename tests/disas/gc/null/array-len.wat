@@ -20,9 +20,9 @@
 ;; @001f                               trapz v2, user16
 ;; @001f                               v8 = uextend.i64 v2
 ;; @001f                               v9 = iconst.i64 8
-;; @001f                               v10 = uadd_overflow_trap v8, v9, user1  ; v9 = 8
+;; @001f                               v10 = iadd v8, v9  ; v9 = 8
 ;; @001f                               v11 = iconst.i64 4
-;; @001f                               v12 = uadd_overflow_trap v10, v11, user1  ; v11 = 4
+;; @001f                               v12 = iadd v10, v11  ; v11 = 4
 ;; @001f                               v7 = load.i64 notrap aligned readonly can_move v0+48
 ;; @001f                               v13 = icmp ule v12, v7
 ;; @001f                               trapz v13, user1
@@ -21,7 +21,7 @@
 ;; @0025                               trapnz v58, user18  ; v58 = 0
 ;; @0025                               v6 = iconst.i32 16
 ;;                                     v59 = iconst.i32 24
-;; @0025                               v12 = uadd_overflow_trap v6, v59, user18  ; v6 = 16, v59 = 24
+;; @0025                               v12 = iadd v6, v59  ; v6 = 16, v59 = 24
 ;; @0025                               v14 = iconst.i32 -134217728
 ;; @0025                               v15 = band v12, v14  ; v14 = -134217728
 ;; @0025                               trapnz v15, user18
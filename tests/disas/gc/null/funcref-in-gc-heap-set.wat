@@ -22,9 +22,9 @@
 ;; @0022                               trapz v2, user16
 ;; @0022                               v9 = uextend.i64 v2
 ;; @0022                               v10 = iconst.i64 8
-;; @0022                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 8
+;; @0022                               v11 = iadd v9, v10  ; v10 = 8
 ;;                                     v19 = iconst.i64 16
-;; @0022                               v13 = uadd_overflow_trap v9, v19, user1  ; v19 = 16
+;; @0022                               v13 = iadd v9, v19  ; v19 = 16
 ;; @0022                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0022                               v14 = icmp ule v13, v8
 ;; @0022                               trapz v14, user1
@@ -22,9 +22,9 @@
 ;; @0023                               trapz v2, user16
 ;; @0023                               v10 = uextend.i64 v2
 ;; @0023                               v11 = iconst.i64 8
-;; @0023                               v12 = uadd_overflow_trap v10, v11, user1  ; v11 = 8
+;; @0023                               v12 = iadd v10, v11  ; v11 = 8
 ;;                                     v32 = iconst.i64 16
-;; @0023                               v14 = uadd_overflow_trap v10, v32, user1  ; v32 = 16
+;; @0023                               v14 = iadd v10, v32  ; v32 = 16
 ;; @0023                               v9 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0023                               v15 = icmp ule v14, v9
 ;; @0023                               trapz v15, user1
@@ -32,7 +32,7 @@
 ;; @0023                               v16 = iadd v7, v12
 ;; @0023                               v17 = load.f32 notrap aligned little v16
 ;; @0029                               v24 = iconst.i64 12
-;; @0029                               v25 = uadd_overflow_trap v10, v24, user1  ; v24 = 12
+;; @0029                               v25 = iadd v10, v24  ; v24 = 12
 ;; @0029                               v29 = iadd v7, v25
 ;; @0029                               v30 = load.i8 notrap aligned little v29
 ;; @002d                               jump block1
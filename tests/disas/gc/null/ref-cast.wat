@@ -35,8 +35,8 @@
 ;;                                 block3:
 ;; @001e                               v17 = uextend.i64 v2
 ;; @001e                               v18 = iconst.i64 4
-;; @001e                               v19 = uadd_overflow_trap v17, v18, user1  ; v18 = 4
-;; @001e                               v21 = uadd_overflow_trap v19, v18, user1  ; v18 = 4
+;; @001e                               v19 = iadd v17, v18  ; v18 = 4
+;; @001e                               v21 = iadd v19, v18  ; v18 = 4
 ;; @001e                               v16 = load.i64 notrap aligned readonly can_move v0+48
 ;; @001e                               v22 = icmp ule v21, v16
 ;; @001e                               trapz v22, user1
@@ -29,9 +29,9 @@
 ;;                                 block3:
 ;; @001b                               v15 = uextend.i64 v2
 ;; @001b                               v16 = iconst.i64 0
-;; @001b                               v17 = uadd_overflow_trap v15, v16, user1  ; v16 = 0
+;; @001b                               v17 = iadd v15, v16  ; v16 = 0
 ;;                                     v29 = iconst.i64 8
-;; @001b                               v19 = uadd_overflow_trap v15, v29, user1  ; v29 = 8
+;; @001b                               v19 = iadd v15, v29  ; v29 = 8
 ;; @001b                               v14 = load.i64 notrap aligned readonly can_move v0+48
 ;; @001b                               v20 = icmp ule v19, v14
 ;; @001b                               trapz v20, user1
@@ -34,9 +34,9 @@
 ;; @0033                               trapz v2, user16
 ;; @0033                               v9 = uextend.i64 v2
 ;; @0033                               v10 = iconst.i64 8
-;; @0033                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 8
+;; @0033                               v11 = iadd v9, v10  ; v10 = 8
 ;;                                     v17 = iconst.i64 24
-;; @0033                               v13 = uadd_overflow_trap v9, v17, user1  ; v17 = 24
+;; @0033                               v13 = iadd v9, v17  ; v17 = 24
 ;; @0033                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0033                               v14 = icmp ule v13, v8
 ;; @0033                               trapz v14, user1
@@ -60,9 +60,9 @@
 ;; @003c                               trapz v2, user16
 ;; @003c                               v9 = uextend.i64 v2
 ;; @003c                               v10 = iconst.i64 12
-;; @003c                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 12
+;; @003c                               v11 = iadd v9, v10  ; v10 = 12
 ;;                                     v18 = iconst.i64 24
-;; @003c                               v13 = uadd_overflow_trap v9, v18, user1  ; v18 = 24
+;; @003c                               v13 = iadd v9, v18  ; v18 = 24
 ;; @003c                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003c                               v14 = icmp ule v13, v8
 ;; @003c                               trapz v14, user1
@@ -87,9 +87,9 @@
 ;; @0045                               trapz v2, user16
 ;; @0045                               v9 = uextend.i64 v2
 ;; @0045                               v10 = iconst.i64 12
-;; @0045                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 12
+;; @0045                               v11 = iadd v9, v10  ; v10 = 12
 ;;                                     v18 = iconst.i64 24
-;; @0045                               v13 = uadd_overflow_trap v9, v18, user1  ; v18 = 24
+;; @0045                               v13 = iadd v9, v18  ; v18 = 24
 ;; @0045                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0045                               v14 = icmp ule v13, v8
 ;; @0045                               trapz v14, user1
@@ -114,9 +114,9 @@
 ;; @004e                               trapz v2, user16
 ;; @004e                               v9 = uextend.i64 v2
 ;; @004e                               v10 = iconst.i64 16
-;; @004e                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 16
+;; @004e                               v11 = iadd v9, v10  ; v10 = 16
 ;;                                     v17 = iconst.i64 24
-;; @004e                               v13 = uadd_overflow_trap v9, v17, user1  ; v17 = 24
+;; @004e                               v13 = iadd v9, v17  ; v17 = 24
 ;; @004e                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @004e                               v14 = icmp ule v13, v8
 ;; @004e                               trapz v14, user1
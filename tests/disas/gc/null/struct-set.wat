@@ -30,9 +30,9 @@
 ;; @0034                               trapz v2, user16
 ;; @0034                               v9 = uextend.i64 v2
 ;; @0034                               v10 = iconst.i64 8
-;; @0034                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 8
+;; @0034                               v11 = iadd v9, v10  ; v10 = 8
 ;;                                     v16 = iconst.i64 24
-;; @0034                               v13 = uadd_overflow_trap v9, v16, user1  ; v16 = 24
+;; @0034                               v13 = iadd v9, v16  ; v16 = 24
 ;; @0034                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0034                               v14 = icmp ule v13, v8
 ;; @0034                               trapz v14, user1
@@ -56,9 +56,9 @@
 ;; @003f                               trapz v2, user16
 ;; @003f                               v9 = uextend.i64 v2
 ;; @003f                               v10 = iconst.i64 12
-;; @003f                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 12
+;; @003f                               v11 = iadd v9, v10  ; v10 = 12
 ;;                                     v16 = iconst.i64 24
-;; @003f                               v13 = uadd_overflow_trap v9, v16, user1  ; v16 = 24
+;; @003f                               v13 = iadd v9, v16  ; v16 = 24
 ;; @003f                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @003f                               v14 = icmp ule v13, v8
 ;; @003f                               trapz v14, user1
@@ -82,9 +82,9 @@
 ;; @004a                               trapz v2, user16
 ;; @004a                               v9 = uextend.i64 v2
 ;; @004a                               v10 = iconst.i64 16
-;; @004a                               v11 = uadd_overflow_trap v9, v10, user1  ; v10 = 16
+;; @004a                               v11 = iadd v9, v10  ; v10 = 16
 ;;                                     v16 = iconst.i64 24
-;; @004a                               v13 = uadd_overflow_trap v9, v16, user1  ; v16 = 24
+;; @004a                               v13 = iadd v9, v16  ; v16 = 24
 ;; @004a                               v8 = load.i64 notrap aligned readonly can_move v0+48
 ;; @004a                               v14 = icmp ule v13, v8
 ;; @004a                               trapz v14, user1
@@ -45,8 +45,8 @@
 ;;                                 block2:
 ;;                                     v76 = iconst.i64 0
 ;; @0023                               v30 = iconst.i64 8
-;; @0023                               v31 = uadd_overflow_trap v76, v30, user1  ; v76 = 0, v30 = 8
-;; @0023                               v33 = uadd_overflow_trap v31, v30, user1  ; v30 = 8
+;; @0023                               v31 = iadd v76, v30  ; v76 = 0, v30 = 8
+;; @0023                               v33 = iadd v31, v30  ; v30 = 8
 ;; @0023                               v28 = load.i64 notrap aligned readonly can_move v0+48
 ;; @0023                               v34 = icmp ule v33, v28
 ;; @0023                               trapz v34, user1
@@ -0,0 +1,97 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = "-O opt-level=s -C inlining"
+
+;; When optimizing for size only the smallest callees are inlined.
+
+(module
+  (memory 1)
+  (func $small (param i32) (result i32)
+    (i32.add (local.get 0) (i32.const 1)))
+  (func $large (param i32) (result i32)
+    (i32.add
+      (i32.add
+        (i32.load offset=0 (local.get 0))
+        (i32.load offset=4 (local.get 0)))
+      (i32.add
+        (i32.load offset=8 (local.get 0))
+        (i32.load offset=12 (local.get 0)))))
+  (func (export "run") (param i32) (result i32)
+    (i32.add (call $small (local.get 0)) (call $large (local.get 0))))
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @002e                               jump block1
+;;
+;;                                 block1:
+;; @002b                               v4 = iconst.i32 1
+;;                                     v5 = iadd.i32 v2, v4  ; v4 = 1
+;; @002e                               return v5
+;; }
+;;
+;; function u0:1(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+88
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+80
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0033                               v5 = load.i64 notrap aligned readonly can_move checked v0+80
+;; @0033                               v4 = uextend.i64 v2
+;; @0033                               v6 = iadd v5, v4
+;; @0033                               v7 = load.i32 little heap v6
+;; @0038                               v11 = iconst.i64 4
+;; @0038                               v12 = iadd v6, v11  ; v11 = 4
+;; @0038                               v13 = load.i32 little heap v12
+;; @003e                               v18 = iconst.i64 8
+;; @003e                               v19 = iadd v6, v18  ; v18 = 8
+;; @003e                               v20 = load.i32 little heap v19
+;; @0043                               v24 = iconst.i64 12
+;; @0043                               v25 = iadd v6, v24  ; v24 = 12
+;; @0043                               v26 = load.i32 little heap v25
+;; @0048                               jump block1
+;;
+;;                                 block1:
+;; @003b                               v14 = iadd.i32 v7, v13
+;; @0046                               v27 = iadd.i32 v20, v26
+;; @0047                               v28 = iadd v14, v27
+;; @0048                               return v28
+;; }
+;;
+;; function u0:2(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     sig0 = (i64 vmctx, i64, i32) -> i32 tail
+;;     sig1 = (i64 vmctx, i64, i32) -> i32 tail
+;;     fn0 = colocated u0:0 sig0
+;;     fn1 = colocated u0:1 sig1
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @004d                               jump block3
+;;
+;;                                 block3:
+;; @002e                               jump block4
+;;
+;;                                 block4:
+;; @002e                               jump block2
+;;
+;;                                 block2:
+;; @0051                               v5 = call fn1(v0, v0, v2)
+;; @0054                               jump block1
+;;
+;;                                 block1:
+;; @002b                               v9 = iconst.i32 1
+;; @002d                               v10 = iadd.i32 v2, v9  ; v9 = 1
+;; @0053                               v6 = iadd v10, v5
+;; @0054                               return v6
+;; }
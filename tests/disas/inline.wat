@@ -0,0 +1,148 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = "-C inlining"
+
+;; Small functions defined in the same module are inlined into their callers,
+;; while imports and recursive calls are left as calls.
+
+(module
+  (import "" "log" (func $log (param i32)))
+  (memory 1)
+  (func $load (param i32) (result i32)
+    (i32.load (local.get 0)))
+  (func $add1 (param i32) (result i32)
+    (i32.add (local.get 0) (i32.const 1)))
+  (func $countdown (param i32)
+    (if (local.get 0)
+      (then (call $countdown (i32.sub (local.get 0) (i32.const 1))))))
+  (func (export "run") (param i32) (result i32)
+    (call $log (local.get 0))
+    (call $countdown (local.get 0))
+    (call $add1 (call $load (local.get 0))))
+)
+;; function u0:1(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+88
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+80
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @003a                               v5 = load.i64 notrap aligned readonly can_move checked v0+80
+;; @003a                               v4 = uextend.i64 v2
+;; @003a                               v6 = iadd v5, v4
+;; @003a                               v7 = load.i32 little heap v6
+;; @003d                               jump block1
+;;
+;;                                 block1:
+;; @003d                               return v7
+;; }
+;;
+;; function u0:2(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0045                               jump block1
+;;
+;;                                 block1:
+;; @0042                               v4 = iconst.i32 1
+;;                                     v5 = iadd.i32 v2, v4  ; v4 = 1
+;; @0045                               return v5
+;; }
+;;
+;; function u0:3(i64 vmctx, i64, i32) tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     sig0 = (i64 vmctx, i64, i32) tail
+;;     fn0 = colocated u0:3 sig0
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @004a                               brif v2, block2, block3
+;;
+;;                                 block2:
+;; @004e                               v3 = iconst.i32 1
+;; @0050                               v4 = isub.i32 v2, v3  ; v3 = 1
+;; @0051                               call fn0(v0, v0, v4)
+;; @0053                               jump block3
+;;
+;;                                 block3:
+;; @0054                               jump block1
+;;
+;;                                 block1:
+;; @0054                               return
+;; }
+;;
+;; function u0:4(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned readonly can_move checked gv0+80
+;;     sig0 = (i64 vmctx, i64, i32) tail
+;;     sig1 = (i64 vmctx, i64, i32) tail
+;;     sig2 = (i64 vmctx, i64, i32) -> i32 tail
+;;     sig3 = (i64 vmctx, i64, i32) -> i32 tail
+;;     fn0 = u0:0 sig0
+;;     fn1 = colocated u0:3 sig1
+;;     fn2 = colocated u0:1 sig2
+;;     fn3 = colocated u0:2 sig3
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0059                               v5 = load.i64 notrap aligned readonly can_move v0+96
+;; @0059                               v6 = load.i64 notrap aligned readonly can_move v0+112
+;; @0059                               call_indirect sig0, v5(v6, v0, v2)
+;; @005d                               jump block3
+;;
+;;                                 block3:
+;; @004a                               brif.i32 v2, block4, block5
+;;
+;;                                 block4:
+;; @0042                               v19 = iconst.i32 1
+;; @0050                               v10 = isub.i32 v2, v19  ; v19 = 1
+;; @0051                               call fn1(v0, v0, v10)
+;; @0053                               jump block5
+;;
+;;                                 block5:
+;; @0054                               jump block6
+;;
+;;                                 block6:
+;; @0054                               jump block2
+;;
+;;                                 block2:
+;; @0061                               jump block8
+;;
+;;                                 block8:
+;; @003a                               v14 = load.i64 notrap aligned readonly can_move checked v0+80
+;; @003a                               v13 = uextend.i64 v2
+;; @003a                               v15 = iadd v14, v13
+;; @003a                               v16 = load.i32 little heap v15
+;; @003d                               jump block9
+;;
+;;                                 block9:
+;; @003d                               jump block7
+;;
+;;                                 block7:
+;; @0063                               jump block11
+;;
+;;                                 block11:
+;; @0045                               jump block12
+;;
+;;                                 block12:
+;; @0045                               jump block10
+;;
+;;                                 block10:
+;; @0065                               jump block1
+;;
+;;                                 block1:
+;;                                     v22 = iconst.i32 1
+;; @0044                               v20 = iadd.i32 v16, v22  ; v22 = 1
+;; @0065                               return v20
+;; }
@@ -21,41 +21,35 @@
 ;; wasm[0]::function[0]:
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
-;;       mov     w13, w4
-;;       mov     w14, #-0xfffc
-;;       adds    x13, x13, x14
-;;       b.hs    #0x48
-;;   18: ldr     x14, [x2, #0x58]
-;;       ldr     x0, [x2, #0x50]
-;;       mov     x15, #0
-;;       add     x0, x0, w4, uxtw
-;;       mov     x1, #0xffff0000
-;;       add     x0, x0, x1
-;;       cmp     x13, x14
-;;       csel    x15, x15, x0, hi
+;;       mov     w12, #-0xfffc
+;;       add     x12, x12, w4, uxtw
+;;       ldr     x13, [x2, #0x58]
+;;       ldr     x15, [x2, #0x50]
+;;       mov     x14, #0
+;;       add     x15, x15, w4, uxtw
+;;       mov     x0, #0xffff0000
+;;       add     x15, x15, x0
+;;       cmp     x12, x13
+;;       csel    x13, x14, x15, hi
 ;;       csdb
-;;       str     w5, [x15]
+;;       str     w5, [x13]
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
-;;   48: .byte   0x1f, 0xc1, 0x00, 0x00
 ;;
 ;; wasm[0]::function[1]:
 ;;       stp     x29, x30, [sp, #-0x10]!
 ;;       mov     x29, sp
-;;       mov     w13, w4
-;;       mov     w14, #-0xfffc
-;;       adds    x13, x13, x14
-;;       b.hs    #0xa8
-;;   78: ldr     x14, [x2, #0x58]
-;;       ldr     x0, [x2, #0x50]
-;;       mov     x15, #0
-;;       add     x0, x0, w4, uxtw
-;;       mov     x1, #0xffff0000
-;;       add     x0, x0, x1
-;;       cmp     x13, x14
-;;       csel    x15, x15, x0, hi
+;;       mov     w12, #-0xfffc
+;;       add     x12, x12, w4, uxtw
+;;       ldr     x13, [x2, #0x58]
+;;       ldr     x15, [x2, #0x50]
+;;       mov     x14, #0
+;;       add     x15, x15, w4, uxtw
+;;       mov     x0, #0xffff0000
+;;       add     x15, x15, x0
+;;       cmp     x12, x13
+;;       csel    x13, x14, x15, hi
 ;;       csdb
-;;       ldr     w2, [x15]
+;;       ldr     w2, [x13]
 ;;       ldp     x29, x30, [sp], #0x10
 ;;       ret
-;;   a8: .byte   0x1f, 0xc1, 0x00, 0x00
@@ -29,17 +29,15 @@
 ;;       addi    a5, a1, 1
 ;;       slli    a5, a5, 2
 ;;       add     a5, a4, a5
-;;       bgeu    a5, a4, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a1, 0x58(a0)
 ;;       bgeu    a1, a5, 8
 ;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a5, 0x50(a0)
-;;       add     a5, a5, a4
-;;       lui     a4, 0xffff
-;;       slli    a0, a4, 4
-;;       add     a5, a5, a0
-;;       sw      a3, 0(a5)
+;;       add     a4, a5, a4
+;;       lui     a5, 0xffff
+;;       slli    a5, a5, 4
+;;       add     a4, a4, a5
+;;       sw      a3, 0(a4)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
@@ -56,17 +54,15 @@
 ;;       addi    a3, a1, 1
 ;;       slli    a5, a3, 2
 ;;       add     a3, a4, a5
-;;       bgeu    a3, a4, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a5, 0x58(a0)
 ;;       bgeu    a5, a3, 8
 ;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a5, 0x50(a0)
-;;       add     a5, a5, a4
-;;       lui     a4, 0xffff
-;;       slli    a0, a4, 4
-;;       add     a5, a5, a0
-;;       lw      a0, 0(a5)
+;;       add     a4, a5, a4
+;;       lui     a3, 0xffff
+;;       slli    a5, a3, 4
+;;       add     a4, a4, a5
+;;       lw      a0, 0(a4)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
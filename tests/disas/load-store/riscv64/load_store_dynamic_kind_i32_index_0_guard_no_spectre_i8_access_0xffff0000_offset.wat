@@ -26,19 +26,17 @@
 ;;       slli    a1, a2, 0x20
 ;;       srli    a2, a1, 0x20
 ;;       auipc   a1, 0
-;;       ld      a1, 0x48(a1)
+;;       ld      a1, 0x40(a1)
 ;;       add     a1, a2, a1
-;;       bgeu    a1, a2, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a4, 0x58(a0)
 ;;       bgeu    a4, a1, 8
 ;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a4, 0x50(a0)
-;;       add     a4, a4, a2
-;;       lui     a2, 0xffff
-;;       slli    a5, a2, 4
-;;       add     a4, a4, a5
-;;       sb      a3, 0(a4)
+;;       add     a2, a4, a2
+;;       lui     a1, 0xffff
+;;       slli    a4, a1, 4
+;;       add     a2, a2, a4
+;;       sb      a3, 0(a2)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
@@ -54,19 +52,17 @@
 ;;       slli    a1, a2, 0x20
 ;;       srli    a2, a1, 0x20
 ;;       auipc   a1, 0
-;;       ld      a1, 0x48(a1)
+;;       ld      a1, 0x40(a1)
 ;;       add     a1, a2, a1
-;;       bgeu    a1, a2, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a3, 0x58(a0)
 ;;       bgeu    a3, a1, 8
 ;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a3, 0x50(a0)
-;;       add     a3, a3, a2
-;;       lui     a2, 0xffff
-;;       slli    a4, a2, 4
-;;       add     a3, a3, a4
-;;       lbu     a0, 0(a3)
+;;       add     a2, a3, a2
+;;       lui     a1, 0xffff
+;;       slli    a3, a1, 4
+;;       add     a2, a2, a3
+;;       lbu     a0, 0(a2)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
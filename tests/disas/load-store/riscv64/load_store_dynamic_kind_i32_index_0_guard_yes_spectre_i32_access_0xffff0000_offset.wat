@@ -29,18 +29,16 @@
 ;;       addi    a1, a5, 1
 ;;       slli    a4, a1, 2
 ;;       add     a1, a2, a4
-;;       bgeu    a1, a2, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a4, 0x58(a0)
 ;;       ld      a5, 0x50(a0)
-;;       sltu    a4, a4, a1
+;;       sltu    a1, a4, a1
 ;;       add     a2, a5, a2
-;;       lui     a1, 0xffff
-;;       slli    a5, a1, 4
-;;       add     a2, a2, a5
-;;       neg     a0, a4
-;;       not     a4, a0
-;;       and     a4, a2, a4
+;;       lui     a0, 0xffff
+;;       slli    a4, a0, 4
+;;       add     a2, a2, a4
+;;       neg     a5, a1
+;;       not     a1, a5
+;;       and     a4, a2, a1
 ;;       sw      a3, 0(a4)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
@@ -58,19 +56,17 @@
 ;;       addi    a1, a5, 1
 ;;       slli    a3, a1, 2
 ;;       add     a1, a2, a3
-;;       bgeu    a1, a2, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
 ;;       ld      a3, 0x58(a0)
 ;;       ld      a4, 0x50(a0)
-;;       sltu    a3, a3, a1
+;;       sltu    a1, a3, a1
 ;;       add     a2, a4, a2
-;;       lui     a1, 0xffff
-;;       slli    a4, a1, 4
-;;       add     a2, a2, a4
-;;       neg     a0, a3
-;;       not     a3, a0
-;;       and     a4, a2, a3
-;;       lw      a0, 0(a4)
+;;       lui     a0, 0xffff
+;;       slli    a3, a0, 4
+;;       add     a2, a2, a3
+;;       neg     a5, a1
+;;       not     a1, a5
+;;       and     a3, a2, a1
+;;       lw      a0, 0(a3)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
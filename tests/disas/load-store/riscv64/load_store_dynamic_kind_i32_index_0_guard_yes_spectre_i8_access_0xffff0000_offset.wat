@@ -27,22 +27,20 @@
 ;;       slli    a4, a2, 0x20
 ;;       srli    a0, a4, 0x20
 ;;       auipc   a5, 0
-;;       ld      a5, 0x54(a5)
+;;       ld      a5, 0x4c(a5)
 ;;       add     a5, a0, a5
-;;       bgeu    a5, a0, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
-;;       mv      a2, a1
-;;       ld      a1, 0x58(a2)
-;;       ld      a2, 0x50(a2)
-;;       sltu    a1, a1, a5
+;;       mv      a4, a1
+;;       ld      a1, 0x58(a4)
+;;       ld      a2, 0x50(a4)
+;;       sltu    a5, a1, a5
 ;;       add     a0, a2, a0
-;;       lui     a5, 0xffff
-;;       slli    a2, a5, 4
-;;       add     a0, a0, a2
-;;       neg     a4, a1
-;;       not     a1, a4
-;;       and     a2, a0, a1
-;;       sb      a3, 0(a2)
+;;       lui     a4, 0xffff
+;;       slli    a1, a4, 4
+;;       add     a0, a0, a1
+;;       neg     a4, a5
+;;       not     a5, a4
+;;       and     a1, a0, a5
+;;       sb      a3, 0(a1)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
@@ -59,22 +57,20 @@
 ;;       slli    a4, a2, 0x20
 ;;       srli    a0, a4, 0x20
 ;;       auipc   a5, 0
-;;       ld      a5, 0x54(a5)
+;;       ld      a5, 0x4c(a5)
 ;;       add     a5, a0, a5
-;;       bgeu    a5, a0, 8
-;;       .byte   0x00, 0x00, 0x00, 0x00
-;;       mv      a2, a1
-;;       ld      a1, 0x58(a2)
-;;       ld      a2, 0x50(a2)
-;;       sltu    a1, a1, a5
+;;       mv      a4, a1
+;;       ld      a1, 0x58(a4)
+;;       ld      a2, 0x50(a4)
+;;       sltu    a5, a1, a5
 ;;       add     a0, a2, a0
-;;       lui     a5, 0xffff
-;;       slli    a2, a5, 4
-;;       add     a0, a0, a2
-;;       neg     a4, a1
-;;       not     a1, a4
-;;       and     a2, a0, a1
-;;       lbu     a0, 0(a2)
+;;       lui     a4, 0xffff
+;;       slli    a1, a4, 4
+;;       add     a0, a0, a1
+;;       neg     a3, a5
+;;       not     a5, a3
+;;       and     a1, a0, a5
+;;       lbu     a0, 0(a1)
 ;;       ld      ra, 8(sp)
 ;;       ld      s0, 0(sp)
 ;;       addi    sp, sp, 0x10
@@ -28,13 +28,11 @@
 ;;       aghi    %r15, -0xa0
 ;;       stg     %r1, 0(%r15)
 ;;       llgfr   %r7, %r4
-;;       lgr     %r3, %r4
-;;       llilf   %r4, 0xffff0001
-;;       algfr   %r4, %r3
-;;       jgnle   0x3c
-;;       lg      %r6, 0x58(%r2)
-;;       clgr    %r4, %r6
-;;       jgh     0x4c
+;;       llilf   %r6, 0xffff0001
+;;       agrk    %r6, %r7, %r6
+;;       lg      %r3, 0x58(%r2)
+;;       clgr    %r6, %r3
+;;       jgh     0x42
 ;;       ag      %r7, 0x50(%r2)
 ;;       llilh   %r2, 0xffff
 ;;       stc     %r5, 0(%r2, %r7)
@@ -51,13 +49,11 @@
 ;;       aghi    %r15, -0xa0
 ;;       stg     %r1, 0(%r15)
 ;;       llgfr   %r7, %r4
-;;       lgr     %r3, %r4
-;;       llilf   %r4, 0xffff0001
-;;       algfr   %r4, %r3
-;;       jgnle   0xa4
+;;       llilf   %r5, 0xffff0001
+;;       agrk    %r5, %r7, %r5
 ;;       lg      %r6, 0x58(%r2)
-;;       clgr    %r4, %r6
-;;       jgh     0xb4
+;;       clgr    %r5, %r6
+;;       jgh     0x9e
 ;;       ag      %r7, 0x50(%r2)
 ;;       llilh   %r2, 0xffff
 ;;       llc     %r2, 0(%r2, %r7)
@@ -28,16 +28,15 @@
 ;;       aghi    %r15, -0xa0
 ;;       stg     %r1, 0(%r15)
 ;;       llgfr   %r3, %r4
-;;       llilf   %r7, 0xffff0001
-;;       algfr   %r7, %r4
-;;       jgnle   0x38
-;;       lg      %r6, 0x58(%r2)
-;;       lghi    %r4, 0
+;;       llilf   %r4, 0xffff0001
+;;       agrk    %r4, %r3, %r4
+;;       lg      %r7, 0x58(%r2)
+;;       lghi    %r6, 0
 ;;       ag      %r3, 0x50(%r2)
 ;;       llilh   %r2, 0xffff
 ;;       agrk    %r2, %r3, %r2
-;;       clgr    %r7, %r6
-;;       locgrh  %r2, %r4
+;;       clgr    %r4, %r7
+;;       locgrh  %r2, %r6
 ;;       stc     %r5, 0(%r2)
 ;;       lmg     %r14, %r15, 0x110(%r15)
 ;;       br      %r14
@@ -52,16 +51,15 @@
 ;;       aghi    %r15, -0xa0
 ;;       stg     %r1, 0(%r15)
 ;;       llgfr   %r3, %r4
-;;       llilf   %r7, 0xffff0001
-;;       algfr   %r7, %r4
-;;       jgnle   0xa0
-;;       lg      %r5, 0x58(%r2)
-;;       lghi    %r4, 0
+;;       llilf   %r4, 0xffff0001
+;;       agrk    %r4, %r3, %r4
+;;       lg      %r6, 0x58(%r2)
+;;       lghi    %r5, 0
 ;;       ag      %r3, 0x50(%r2)
-;;       llilh   %r6, 0xffff
-;;       agrk    %r2, %r3, %r6
-;;       clgr    %r7, %r5
-;;       locgrh  %r2, %r4
-;;       llc     %r2, 0(%r2)
+;;       llilh   %r2, 0xffff
+;;       agrk    %r7, %r3, %r2
+;;       clgr    %r4, %r6
+;;       locgrh  %r7, %r5
+;;       llc     %r2, 0(%r7)
 ;;       lmg     %r14, %r15, 0x110(%r15)
 ;;       br      %r14
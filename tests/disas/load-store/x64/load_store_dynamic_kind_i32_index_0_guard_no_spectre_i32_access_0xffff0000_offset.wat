@@ -22,36 +22,30 @@
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r8d
-;;       movq    %r8, %r10
-;;       addq    0x27(%rip), %r10
-;;       jb      0x33
-;;   17: cmpq    0x58(%rdi), %r10
-;;       ja      0x35
-;;   21: addq    0x50(%rdi), %r8
+;;       movl    $0xffff0004, %r11d
+;;       addq    %r8, %r11
+;;       cmpq    0x58(%rdi), %r11
+;;       ja      0x2c
+;;   1a: addq    0x50(%rdi), %r8
 ;;       movl    $0xffff0000, %edi
 ;;       movl    %ecx, (%r8, %rdi)
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   33: ud2
-;;   35: ud2
-;;   37: addb    %al, (%rax, %rax)
+;;   2c: ud2
 ;;
 ;; wasm[0]::function[1]:
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r8d
-;;       movq    %r8, %r10
-;;       addq    0x27(%rip), %r10
-;;       jb      0x73
-;;   57: cmpq    0x58(%rdi), %r10
-;;       ja      0x75
-;;   61: addq    0x50(%rdi), %r8
+;;       movl    $0xffff0004, %r11d
+;;       addq    %r8, %r11
+;;       cmpq    0x58(%rdi), %r11
+;;       ja      0x6c
+;;   5a: addq    0x50(%rdi), %r8
 ;;       movl    $0xffff0000, %edi
 ;;       movl    (%r8, %rdi), %eax
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   73: ud2
-;;   75: ud2
-;;   77: addb    %al, (%rax, %rax)
+;;   6c: ud2
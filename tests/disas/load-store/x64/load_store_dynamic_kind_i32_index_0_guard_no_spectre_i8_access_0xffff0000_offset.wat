@@ -22,39 +22,30 @@
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r8d
-;;       movq    %r8, %r10
-;;       addq    0x27(%rip), %r10
-;;       jb      0x33
-;;   17: cmpq    0x58(%rdi), %r10
-;;       ja      0x35
-;;   21: addq    0x50(%rdi), %r8
+;;       movl    $0xffff0001, %r11d
+;;       addq    %r8, %r11
+;;       cmpq    0x58(%rdi), %r11
+;;       ja      0x2c
+;;   1a: addq    0x50(%rdi), %r8
 ;;       movl    $0xffff0000, %edi
 ;;       movb    %cl, (%r8, %rdi)
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   33: ud2
-;;   35: ud2
-;;   37: addb    %al, (%rcx)
-;;   39: addb    %bh, %bh
-;;   3b: incl    (%rax)
-;;   3d: addb    %al, (%rax)
+;;   2c: ud2
 ;;
 ;; wasm[0]::function[1]:
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r8d
-;;       movq    %r8, %r10
-;;       addq    0x27(%rip), %r10
-;;       jb      0x74
-;;   57: cmpq    0x58(%rdi), %r10
-;;       ja      0x76
-;;   61: addq    0x50(%rdi), %r8
+;;       movl    $0xffff0001, %r11d
+;;       addq    %r8, %r11
+;;       cmpq    0x58(%rdi), %r11
+;;       ja      0x6d
+;;   5a: addq    0x50(%rdi), %r8
 ;;       movl    $0xffff0000, %edi
 ;;       movzbq  (%r8, %rdi), %rax
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   74: ud2
-;;   76: ud2
-;;   78: addl    %eax, (%rax)
+;;   6d: ud2
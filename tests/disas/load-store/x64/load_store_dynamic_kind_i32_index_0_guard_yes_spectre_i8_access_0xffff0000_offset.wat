@@ -22,46 +22,34 @@
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r10d
-;;       movq    %r10, %rax
-;;       addq    0x2f(%rip), %rax
-;;       jb      0x3a
-;;   17: movq    0x58(%rdi), %r8
-;;       xorq    %rdx, %rdx
+;;       movl    $0xffff0001, %edx
+;;       addq    %r10, %rdx
+;;       movq    0x58(%rdi), %r9
+;;       xorq    %r8, %r8
 ;;       addq    0x50(%rdi), %r10
-;;       movl    $0xffff0000, %r9d
-;;       addq    %r10, %r9
-;;       cmpq    %r8, %rax
-;;       cmovaq  %rdx, %r9
-;;       movb    %cl, (%r9)
+;;       movl    $0xffff0000, %r11d
+;;       leaq    (%r10, %r11), %rax
+;;       cmpq    %r9, %rdx
+;;       cmovaq  %r8, %rax
+;;       movb    %cl, (%rax)
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   3a: ud2
-;;   3c: addb    %al, (%rax)
-;;   3e: addb    %al, (%rax)
-;;   40: addl    %eax, (%rax)
 ;;
 ;; wasm[0]::function[1]:
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    %edx, %r10d
-;;       movq    %r10, %rax
-;;       addq    0x2f(%rip), %rax
-;;       jb      0x9b
-;;   77: movq    0x58(%rdi), %rdx
-;;       xorq    %rcx, %rcx
+;;       movl    $0xffff0001, %ecx
+;;       addq    %r10, %rcx
+;;       movq    0x58(%rdi), %r8
+;;       xorq    %rdx, %rdx
 ;;       addq    0x50(%rdi), %r10
-;;       movl    $0xffff0000, %r8d
-;;       addq    %r10, %r8
-;;       cmpq    %rdx, %rax
-;;       cmovaq  %rcx, %r8
-;;       movzbq  (%r8), %rax
+;;       movl    $0xffff0000, %r9d
+;;       leaq    (%r10, %r9), %rax
+;;       cmpq    %r8, %rcx
+;;       cmovaq  %rdx, %rax
+;;       movzbq  (%rax), %rax
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq
-;;   9b: ud2
-;;   9d: addb    %al, (%rax)
-;;   9f: addb    %al, (%rcx)
-;;   a1: addb    %bh, %bh
-;;   a3: incl    (%rax)
-;;   a5: addb    %al, (%rax)
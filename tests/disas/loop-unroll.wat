@@ -0,0 +1,67 @@
+;;! target = "x86_64"
+;;! test = "optimize"
+;;! flags = "-O opt-level=2"
+
+;; A counted loop with a small constant trip count is fully unrolled, and
+;; the multiplication of its induction variable strength reduced.
+
+(module
+  (memory 1)
+  (func $sum (param $p i32) (result i32)
+    (local $i i32)
+    (local $acc i32)
+    (loop $l
+      (local.set $acc
+        (i32.add
+          (local.get $acc)
+          (i32.load (i32.add (local.get $p) (i32.mul (local.get $i) (i32.const 12))))))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $l (i32.lt_u (local.get $i) (i32.const 4))))
+    (local.get $acc))
+)
+;; function u0:0(i64 vmctx, i64, i32) -> i32 tail {
+;;     gv0 = vmctx
+;;     gv1 = load.i64 notrap aligned readonly gv0+8
+;;     gv2 = load.i64 notrap aligned gv1+16
+;;     gv3 = vmctx
+;;     gv4 = load.i64 notrap aligned gv3+88
+;;     gv5 = load.i64 notrap aligned readonly can_move checked gv3+80
+;;     stack_limit = gv2
+;;
+;;                                 block0(v0: i64, v1: i64, v2: i32):
+;; @0020                               jump block2
+;;
+;;                                 block2:
+;; @002c                               v30 = load.i64 notrap aligned readonly can_move checked v0+80
+;; @002c                               v29 = uextend.i64 v2
+;; @002c                               v31 = iadd v30, v29
+;; @002c                               v32 = load.i32 little heap v31
+;;                                     v23 = iconst.i32 12
+;; @002b                               v42 = iadd.i32 v2, v23  ; v23 = 12
+;; @002c                               v43 = uextend.i64 v42
+;; @002c                               v45 = iadd v30, v43
+;; @002c                               v46 = load.i32 little heap v45
+;;                                     v108 = iconst.i32 24
+;; @002b                               v56 = iadd.i32 v2, v108  ; v108 = 24
+;; @002c                               v57 = uextend.i64 v56
+;; @002c                               v59 = iadd v30, v57
+;; @002c                               v60 = load.i32 little heap v59
+;;                                     v128 = iconst.i32 36
+;; @002b                               v10 = iadd.i32 v2, v128  ; v128 = 36
+;; @002c                               v11 = uextend.i64 v10
+;; @002c                               v13 = iadd v30, v11
+;; @002c                               v14 = load.i32 little heap v13
+;; @003e                               jump block4
+;;
+;;                                 block4:
+;; @0040                               jump block3
+;;
+;;                                 block3:
+;; @0043                               jump block1
+;;
+;;                                 block1:
+;; @002f                               v47 = iadd.i32 v32, v46
+;;                                     v135 = iadd.i32 v60, v14
+;;                                     v136 = iadd v47, v135
+;; @0043                               return v136
+;; }
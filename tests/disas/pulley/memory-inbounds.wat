@@ -87,15 +87,14 @@
 ;;
 ;; wasm[0]::function[8]::maybe_inbounds_v2:
 ;;       push_frame
-;;       xzero x10
-;;       xconst32 x11, 131072
-;;       xadd64_uoverflow_trap x11, x10, x11
-;;       xload64le_o32 x12, x0, 88
-;;       xload64le_o32 x13, x0, 80
-;;       xadd64_u32 x13, x13, 131068
-;;       xult64 x9, x12, x11
-;;       xselect64 x11, x9, x10, x13
-;;       xload32le_z x0, x11, 0
+;;       xzero x9
+;;       xadd64_u32 x10, x9, 131072
+;;       xload64le_o32 x11, x0, 88
+;;       xload64le_o32 x12, x0, 80
+;;       xadd64_u32 x12, x12, 131068
+;;       xult64 x8, x11, x10
+;;       xselect64 x10, x8, x9, x12
+;;       xload32le_z x0, x10, 0
 ;;       pop_frame
 ;;       ret
 ;;
@@ -2,26 +2,31 @@
 ;;! test = "compile"
 
 (module
-  (global $g (mut i32) (i32.const 0))
+  (global $g0 (mut i32) (i32.const 0))
+  (global $g1 (mut i32) (i32.const 0))
+  (global $g2 (mut i32) (i32.const 0))
+  (global $g3 (mut i32) (i32.const 0))
+  (global $g4 (mut i32) (i32.const 0))
+  (global $g5 (mut i32) (i32.const 0))
 
   (func $foo
-    (global.set $g (i32.const 0))
-    (global.set $g (i32.const 1))
-    (global.set $g (i32.const -1))
-    (global.set $g (i32.const -10))
-    (global.set $g (i32.const 100000))
-    (global.set $g (i32.const 0x8fff_ffff))
+    (global.set $g0 (i32.const 0))
+    (global.set $g1 (i32.const 1))
+    (global.set $g2 (i32.const -1))
+    (global.set $g3 (i32.const -10))
+    (global.set $g4 (i32.const 100000))
+    (global.set $g5 (i32.const 0x8fff_ffff))
   )
 )
 ;; wasm[0]::function[0]::foo:
 ;;       pushq   %rbp
 ;;       movq    %rsp, %rbp
 ;;       movl    $0, 0x50(%rdi)
-;;       movl    $1, 0x50(%rdi)
-;;       movl    $0xffffffff, 0x50(%rdi)
-;;       movl    $0xfffffff6, 0x50(%rdi)
-;;       movl    $0x186a0, 0x50(%rdi)
-;;       movl    $0x8fffffff, 0x50(%rdi)
+;;       movl    $1, 0x60(%rdi)
+;;       movl    $0xffffffff, 0x70(%rdi)
+;;       movl    $0xfffffff6, 0x80(%rdi)
+;;       movl    $0x186a0, 0x90(%rdi)
+;;       movl    $0x8fffffff, 0xa0(%rdi)
 ;;       movq    %rbp, %rsp
 ;;       popq    %rbp
 ;;       retq